@@ -29,6 +29,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         ImportResult::Cooklang {
             content,
             conversion_metadata,
+            ..
         } => {
             println!("Successfully imported recipe with custom timeout:");
             println!("Recipe length: {} bytes", content.len());
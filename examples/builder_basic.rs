@@ -20,6 +20,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         ImportResult::Cooklang {
             content,
             conversion_metadata,
+            ..
         } => {
             println!("Recipe in Cooklang format:");
             println!("{}", content);
@@ -69,7 +70,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 "#;
 
     let result = RecipeImporter::builder()
-        .text(&format!("{}\n\n{}", ingredients, instructions))
+        .text(format!("{}\n\n{}", ingredients, instructions))
         .build()
         .await?;
 
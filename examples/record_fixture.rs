@@ -0,0 +1,51 @@
+//! Save a recipe page as a golden-test fixture
+//!
+//! Usage: cargo run --example record_fixture -- <url> <name>
+//!
+//! Writes tests/fixtures/<name>.html and the snapshot of what the extractors
+//! currently make of it, tests/fixtures/<name>.json. Check the snapshot
+//! before committing: it becomes the expected result.
+
+use cooklang_import::pipelines::Timeouts;
+use cooklang_import::stages;
+use serde_json::{json, Value};
+use std::path::Path;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().collect();
+    let (Some(url), Some(name)) = (args.get(1), args.get(2)) else {
+        eprintln!("Usage: record_fixture <url> <name>");
+        std::process::exit(2);
+    };
+
+    let html = stages::fetch(url, &Timeouts::default(), None, None).await?;
+    let snapshot = match stages::extract(&html, url).await {
+        Ok(components) => json!({
+            "url": url,
+            "name": components.name,
+            "metadata": metadata(&components.metadata),
+            "text": components.text,
+        }),
+        Err(e) => json!({"url": url, "error": e.to_string()}),
+    };
+
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join(format!("{}.html", name)), &html)?;
+    std::fs::write(
+        dir.join(format!("{}.json", name)),
+        serde_json::to_string_pretty(&snapshot)? + "\n",
+    )?;
+
+    println!("Recorded {} as tests/fixtures/{}.html", url, name);
+    if let Some(error) = snapshot["error"].as_str() {
+        println!("No extractor matched: {}", error);
+    }
+    Ok(())
+}
+
+/// The YAML metadata as an object; extractors don't keep the keys in order
+fn metadata(yaml: &str) -> Value {
+    serde_yaml::from_str::<Value>(yaml).unwrap_or(Value::Null)
+}
@@ -27,6 +27,9 @@ declare -A ANDROID_TARGETS=(
 # Minimum API level
 MIN_API_LEVEL="${ANDROID_MIN_API_LEVEL:-21}"
 
+# Extra cargo features, e.g. EXTRA_FEATURES=ocr-onnx for on-device OCR
+FEATURES="uniffi${EXTRA_FEATURES:+,${EXTRA_FEATURES}}"
+
 # Check for required tools
 check_requirements() {
     echo -e "${YELLOW}Checking requirements...${NC}"
@@ -112,7 +115,7 @@ build_targets() {
         cargo ndk \
             --target "$target" \
             --platform "$MIN_API_LEVEL" \
-            build --release --features uniffi
+            build --release --features "$FEATURES"
     done
 
     echo -e "${GREEN}All targets built!${NC}"
@@ -23,6 +23,9 @@ IOS_TARGETS=(
     "aarch64-apple-ios-sim"       # iOS Simulator (arm64, Apple Silicon Macs)
 )
 
+# Extra cargo features, e.g. EXTRA_FEATURES=ocr-onnx for on-device OCR
+FEATURES="uniffi${EXTRA_FEATURES:+,${EXTRA_FEATURES}}"
+
 # Check for required tools
 check_requirements() {
     echo -e "${YELLOW}Checking requirements...${NC}"
@@ -66,7 +69,7 @@ build_targets() {
 
     for target in "${IOS_TARGETS[@]}"; do
         echo "Building for $target..."
-        cargo build --release --target "$target" --features uniffi
+        cargo build --release --target "$target" --features "$FEATURES"
     done
 
     echo -e "${GREEN}All targets built!${NC}"
@@ -0,0 +1,87 @@
+//! Transcribing dictated recipes
+//!
+//! The speech-to-text backend (OpenAI's Whisper API or a local whisper.cpp
+//! build) comes from the `[transcription]` config section.
+
+mod whisper;
+mod whisper_cpp;
+
+use crate::config::TranscriptionBackend;
+use std::error::Error;
+use std::time::Duration;
+
+/// Transcribe the audio file at `path` (a voice memo: m4a, mp3, wav, ...)
+///
+/// # Arguments
+/// * `path` - Path to the audio file
+/// * `timeout` - Timeout for the transcription
+/// * `proxy` - Proxy URL overriding the configured one
+///
+/// # Errors
+/// Returns an error if the file can't be read, the backend fails or nothing
+/// was said
+pub async fn transcribe(
+    path: &str,
+    timeout: Duration,
+    proxy: Option<&str>,
+) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let config = crate::config::load_config().ok();
+    let transcription = config
+        .as_ref()
+        .map(|c| c.transcription.clone())
+        .unwrap_or_default();
+    let language = transcription.language.as_deref();
+
+    let text = match transcription.backend {
+        TranscriptionBackend::OpenAI => {
+            let api_key = transcription
+                .api_key
+                .clone()
+                .or_else(|| {
+                    config
+                        .as_ref()
+                        .and_then(|c| c.providers.get("openai"))
+                        .and_then(|provider| provider.api_key.clone())
+                })
+                .or_else(|| std::env::var("OPENAI_API_KEY").ok())
+                .ok_or("OPENAI_API_KEY not found in config or environment")?;
+            whisper::call_whisper_api(
+                transcription
+                    .base_url
+                    .as_deref()
+                    .unwrap_or(whisper::DEFAULT_BASE_URL),
+                &api_key,
+                transcription
+                    .model
+                    .as_deref()
+                    .unwrap_or(whisper::DEFAULT_MODEL),
+                language,
+                path,
+                timeout,
+                proxy,
+            )
+            .await?
+        }
+        TranscriptionBackend::WhisperCpp => {
+            let model = transcription.model.as_deref().ok_or(
+                "whisper_cpp needs the path to a ggml model file as [transcription] model",
+            )?;
+            whisper_cpp::run_whisper_cpp(
+                transcription
+                    .binary
+                    .as_deref()
+                    .unwrap_or(whisper_cpp::DEFAULT_BINARY),
+                model,
+                path,
+                language,
+                timeout,
+            )
+            .await?
+        }
+    };
+
+    if text.trim().is_empty() {
+        return Err("No speech detected in the recording".into());
+    }
+    Ok(text)
+}
@@ -0,0 +1,166 @@
+use reqwest::multipart::{Form, Part};
+use serde_json::Value;
+use std::error::Error;
+use std::path::Path;
+use std::time::Duration;
+use tracing::debug;
+
+pub(crate) const DEFAULT_MODEL: &str = "whisper-1";
+pub(crate) const DEFAULT_BASE_URL: &str = "https://api.openai.com";
+
+/// Transcribes an audio file with OpenAI's Whisper API
+///
+/// # Arguments
+/// * `base_url` - API URL (OpenAI or a server with the same API)
+/// * `api_key` - API key
+/// * `model` - Transcription model (e.g. whisper-1)
+/// * `language` - Spoken language as an ISO 639-1 code, or `None` to detect it
+/// * `path` - Path to the audio file
+/// * `timeout` - Request timeout
+/// * `proxy` - Proxy URL overriding the configured one
+///
+/// # Returns
+/// The transcript
+#[tracing::instrument(name = "transcription", skip_all, fields(backend = "openai", model = %model))]
+pub(crate) async fn call_whisper_api(
+    base_url: &str,
+    api_key: &str,
+    model: &str,
+    language: Option<&str>,
+    path: &str,
+    timeout: Duration,
+    proxy: Option<&str>,
+) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let audio = tokio::fs::read(path)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let file_name = Path::new(path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("audio")
+        .to_string();
+    debug!(
+        "Sending {} ({} bytes) to the Whisper API",
+        file_name,
+        audio.len()
+    );
+
+    // The API recognizes the format from the file name's extension
+    let mut form = Form::new()
+        .part("file", Part::bytes(audio).file_name(file_name))
+        .text("model", model.to_string())
+        .text("response_format", "json");
+    if let Some(language) = language {
+        form = form.text("language", language.to_string());
+    }
+
+    let response = crate::http::client(base_url, timeout, proxy)?
+        .post(format!(
+            "{}/v1/audio/transcriptions",
+            base_url.trim_end_matches('/')
+        ))
+        .bearer_auth(api_key)
+        .multipart(form)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await?;
+        return Err(format!("Whisper API error ({}): {}", status, error_text).into());
+    }
+
+    let response_body: Value = response.json().await?;
+    let text = response_body["text"]
+        .as_str()
+        .ok_or("No text found in Whisper API response")?
+        .trim()
+        .to_string();
+
+    debug!("Transcribed {} characters", text.len());
+
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server};
+
+    #[tokio::test]
+    async fn test_whisper_api_transcription() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/audio/transcriptions")
+            .match_header("authorization", "Bearer test-key")
+            .match_body(Matcher::AllOf(vec![
+                Matcher::Regex(r#"filename="memo.m4a""#.to_string()),
+                Matcher::Regex(r#"name="model"\s+whisper-1"#.to_string()),
+                Matcher::Regex(r#"name="language"\s+de"#.to_string()),
+            ]))
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"text": " Zwei Eier verquirlen. "}"#)
+            .create_async()
+            .await;
+        let path = std::env::temp_dir().join(format!("cooklang-whisper-{}", std::process::id()));
+        std::fs::create_dir_all(&path).unwrap();
+        let memo = path.join("memo.m4a");
+        std::fs::write(&memo, b"fake audio").unwrap();
+
+        let text = call_whisper_api(
+            &server.url(),
+            "test-key",
+            DEFAULT_MODEL,
+            Some("de"),
+            memo.to_str().unwrap(),
+            Duration::from_secs(5),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(text, "Zwei Eier verquirlen.");
+        mock.assert_async().await;
+
+        let missing = call_whisper_api(
+            &server.url(),
+            "test-key",
+            DEFAULT_MODEL,
+            None,
+            path.join("missing.m4a").to_str().unwrap(),
+            Duration::from_secs(5),
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert!(missing.to_string().contains("Failed to read"));
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_whisper_api_error() {
+        let mut server = Server::new_async().await;
+        let _mock = server
+            .mock("POST", "/v1/audio/transcriptions")
+            .with_status(401)
+            .with_body(r#"{"error": {"message": "Incorrect API key provided"}}"#)
+            .create_async()
+            .await;
+        let path =
+            std::env::temp_dir().join(format!("cooklang-whisper-err-{}.mp3", std::process::id()));
+        std::fs::write(&path, b"fake audio").unwrap();
+
+        let err = call_whisper_api(
+            &server.url(),
+            "wrong",
+            DEFAULT_MODEL,
+            None,
+            path.to_str().unwrap(),
+            Duration::from_secs(5),
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("401"));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,109 @@
+use std::error::Error;
+use std::time::Duration;
+use tokio::process::Command;
+use tracing::debug;
+
+pub(crate) const DEFAULT_BINARY: &str = "whisper-cli";
+
+/// Transcribes an audio file with a local whisper.cpp build
+///
+/// Older whisper.cpp builds only read 16 kHz WAV files; convert other
+/// recordings first, e.g. `ffmpeg -i memo.m4a -ar 16000 memo.wav`.
+///
+/// # Arguments
+/// * `binary` - The whisper.cpp executable (whisper-cli, or main in old builds)
+/// * `model` - Path to a ggml model file
+/// * `path` - Path to the audio file
+/// * `language` - Spoken language as an ISO 639-1 code, or `None` to detect it
+/// * `timeout` - Time allowed for the transcription
+///
+/// # Returns
+/// The transcript
+#[tracing::instrument(name = "transcription", skip_all, fields(backend = "whisper_cpp"))]
+pub(crate) async fn run_whisper_cpp(
+    binary: &str,
+    model: &str,
+    path: &str,
+    language: Option<&str>,
+    timeout: Duration,
+) -> Result<String, Box<dyn Error + Send + Sync>> {
+    debug!("Transcribing {} with {} ({})", path, binary, model);
+
+    let run = Command::new(binary)
+        .args(["-m", model, "-f", path, "-l", language.unwrap_or("auto")])
+        // No timestamps, and no progress output mixed into the transcript
+        .args(["--no-timestamps", "--no-prints"])
+        .kill_on_drop(true)
+        .output();
+    let output = tokio::time::timeout(timeout, run)
+        .await
+        .map_err(|_| format!("whisper.cpp took longer than {:?}", timeout))?
+        .map_err(|e| format!("Failed to run {}: {}", binary, e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "whisper.cpp failed ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+
+    // One line per segment, each with a leading space
+    let text = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    debug!("Transcribed {} characters", text.len());
+
+    Ok(text)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    /// A stand-in for whisper-cli that prints its arguments as segments
+    fn fake_whisper(dir: &std::path::Path, script: &str) -> String {
+        let binary = dir.join("whisper-cli");
+        std::fs::write(&binary, format!("#!/bin/sh\n{}\n", script)).unwrap();
+        std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+        binary.to_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_whisper_cpp_transcription() {
+        let dir = std::env::temp_dir().join(format!("cooklang-whisper-cpp-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let binary = fake_whisper(&dir, r#"echo " Two eggs, $6."; echo; echo " Fry them.""#);
+        let text = run_whisper_cpp(
+            &binary,
+            "ggml-base.bin",
+            "memo.wav",
+            None,
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+        // $6 is the language argument
+        assert_eq!(text, "Two eggs, auto.\nFry them.");
+
+        let failing = fake_whisper(&dir, r#"echo "failed to open model" >&2; exit 3"#);
+        let err = run_whisper_cpp(
+            &failing,
+            "missing.bin",
+            "memo.wav",
+            Some("en"),
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("failed to open model"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
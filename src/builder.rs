@@ -1,11 +1,23 @@
+use serde::{Deserialize, Serialize};
 use std::time::Duration;
+use tracing::{info, warn};
 
 use crate::{
-    config::{load_config, ProviderConfig},
-    converters::{self, ConversionMetadata, Converter},
-    images_to_text::ImageSource,
-    pipelines::RecipeComponents,
-    ImportError,
+    cache::Cache,
+    config::{
+        load_config, ConvertersConfig, DuplicateIngredients, OcrBackend, OvenTemperatures,
+        ProviderConfig, TranscriptionBackend,
+    },
+    converters::{self, ConversionMetadata, ConversionResult, Converter, UnitSystem},
+    images_to_text::{self, ImageOrder, ImageSource},
+    pipelines::{
+        equipment, html::SavedPage, sub_recipes::SplitRecipe, Llm, RecipeComponents, Timeouts,
+    },
+    provenance::Provenance,
+    stages::TitleStyle,
+    url_to_text::html::diagnostics::{DropReason, DroppedField},
+    url_to_text::text::TextExtractor,
+    ImportError, MetadataKey,
 };
 
 /// Represents the input source for a recipe
@@ -17,6 +29,16 @@ pub enum InputSource {
     Text { content: String, extract: bool },
     /// Use images (paths or base64)
     Images(Vec<ImageSource>),
+    /// A raw `.eml` message, e.g. a saved newsletter
+    Email(Vec<u8>),
+    /// A recipe written in Markdown
+    Markdown(String),
+    /// A dictated recipe: the path to an audio file
+    Audio(String),
+    /// A web page saved from a browser as HTML
+    Html(String),
+    /// A web page saved from a browser as an MHTML archive (`.mht`)
+    Mhtml(Vec<u8>),
 }
 
 /// Represents the desired output format
@@ -36,6 +58,9 @@ pub enum ImportResult {
     Cooklang {
         /// The converted Cooklang text
         content: String,
+        /// The recipe title (empty if none was found), also when
+        /// [`TitleStyle::Filename`] leaves it out of `content`
+        title: String,
         /// Metadata about the LLM conversion (model, tokens, latency)
         conversion_metadata: Option<ConversionMetadata>,
     },
@@ -43,6 +68,154 @@ pub enum ImportResult {
     Components(RecipeComponents),
 }
 
+impl ImportResult {
+    /// The recipe photo's URL (`image` in the metadata), if the source had one
+    pub fn image_url(&self) -> Option<String> {
+        metadata_field(&self.metadata(), MetadataKey::Image)
+    }
+
+    /// The conversion's confidence score from 0 to 1, for Cooklang results
+    pub fn confidence(&self) -> Option<f32> {
+        match self {
+            ImportResult::Cooklang {
+                conversion_metadata: Some(metadata),
+                ..
+            } => metadata.confidence,
+            _ => None,
+        }
+    }
+
+    /// Check the conversion's confidence against a minimum quality.
+    /// Results without a score always pass: unconverted ones, and photos
+    /// converted by a vision model, which leave no source text to score
+    /// against.
+    pub fn check_quality(&self, min: f32) -> Result<(), ImportError> {
+        match self.confidence() {
+            Some(confidence) if confidence < min => {
+                Err(ImportError::LowQuality { confidence, min })
+            }
+            Some(_) => Ok(()),
+            None => {
+                if matches!(self, ImportResult::Cooklang { .. }) {
+                    warn!(
+                        "The conversion has no confidence score; minimum quality {:.2} not checked",
+                        min
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// The frontmatter (or extracted metadata) without the title
+    fn metadata(&self) -> serde_yaml::Mapping {
+        let yaml = match self {
+            ImportResult::Cooklang { content, .. } => crate::model::split_frontmatter(content)
+                .0
+                .unwrap_or_default(),
+            ImportResult::Components(components) => components.metadata.as_str(),
+        };
+        let mut metadata: serde_yaml::Mapping = serde_yaml::from_str(yaml).unwrap_or_default();
+        metadata.remove("title");
+        metadata
+    }
+}
+
+/// A metadata value by key, under any of the key's spellings
+fn metadata_field(metadata: &serde_yaml::Mapping, key: MetadataKey) -> Option<String> {
+    metadata.iter().find_map(|(k, v)| {
+        let k = k.as_str()?;
+        (MetadataKey::parse(k) == Some(key))
+            .then(|| v.as_str())
+            .flatten()
+            .map(str::to_string)
+    })
+}
+
+/// The JSON form of an [`ImportResult`], so scripts don't have to parse
+/// frontmatter
+#[derive(Serialize)]
+struct ImportResultJson<'a> {
+    /// Cooklang with frontmatter; `None` for extract-only results
+    cooklang: Option<&'a str>,
+    /// Ingredients and instructions of extract-only results
+    text: Option<&'a str>,
+    name: &'a str,
+    /// The frontmatter (or extracted metadata) without the title
+    metadata: serde_yaml::Mapping,
+    source: Option<String>,
+    images: Vec<String>,
+    conversion: Option<&'a ConversionMetadata>,
+}
+
+impl Serialize for ImportResult {
+    /// An object with `cooklang`, `text`, `name`, `metadata`, `source`,
+    /// `images` and `conversion` fields
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (cooklang, text, name, conversion) = match self {
+            ImportResult::Cooklang {
+                content,
+                title,
+                conversion_metadata,
+            } => (
+                Some(content.as_str()),
+                None,
+                title,
+                conversion_metadata.as_ref(),
+            ),
+            ImportResult::Components(components) => {
+                (None, Some(components.text.as_str()), &components.name, None)
+            }
+        };
+
+        let metadata = self.metadata();
+        let source = metadata_field(&metadata, MetadataKey::Source);
+        let images = metadata_field(&metadata, MetadataKey::Image)
+            .into_iter()
+            .collect();
+
+        ImportResultJson {
+            cooklang,
+            text,
+            name,
+            metadata,
+            source,
+            images,
+            conversion,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// What an import would do, from [`RecipeImporterBuilder::dry_run`]
+#[derive(Debug, Clone)]
+pub struct ImportReport {
+    /// How the recipe was found: a structured extractor ("json-ld",
+    /// "microdata", "html-class") or "text" for pre-formatted text input.
+    /// `None` when LLM text extraction would be needed.
+    pub extractor: Option<String>,
+    /// The extracted recipe; with LLM text extraction pending, `text` holds
+    /// the raw page or input text
+    pub components: RecipeComponents,
+    /// Providers the conversion would use, in order (several for fallback
+    /// chains and races); empty for `extract_only`
+    pub providers: Vec<String>,
+    /// Model of the first provider
+    pub model: String,
+    /// Estimated prompt tokens across extraction and conversion
+    pub estimated_input_tokens: u64,
+    /// Estimated response tokens
+    pub estimated_output_tokens: u64,
+    /// Estimated cost in USD for the first provider, if its pricing is known
+    pub estimated_cost_usd: Option<f64>,
+    /// Fields the structured extractor found but didn't use: unmapped schema
+    /// keys and values that were empty after cleaning
+    pub dropped_fields: Vec<DroppedField>,
+    /// Page the recipe was extracted from, after redirects and swapping an
+    /// AMP page for its canonical page; `None` for input other than a URL
+    pub url: Option<String>,
+}
+
 /// Optional LLM provider configuration
 #[derive(Debug, Clone)]
 pub enum LlmProvider {
@@ -51,21 +224,178 @@ pub enum LlmProvider {
     Google,
     AzureOpenAI,
     Ollama,
+    Mistral,
+    Groq,
+    /// Canned or replayed responses, for tests (`mock` feature)
+    #[cfg(feature = "mock")]
+    Mock,
 }
 
 impl LlmProvider {
-    // Note: Conversion to string is handled directly in the converter factory
+    /// Name of the provider as used by `create_converter` and config.toml
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            LlmProvider::OpenAI => "open_ai",
+            LlmProvider::Anthropic => "anthropic",
+            LlmProvider::Google => "google",
+            LlmProvider::AzureOpenAI => "azure_openai",
+            LlmProvider::Ollama => "ollama",
+            LlmProvider::Mistral => "mistral",
+            LlmProvider::Groq => "groq",
+            #[cfg(feature = "mock")]
+            LlmProvider::Mock => "mock",
+        }
+    }
+}
+
+/// An import described as JSON, as sent to the HTTP server and the daemon:
+/// exactly one of `url`, `text` or `image` (base64), plus options
+///
+/// # Example
+/// ```
+/// use cooklang_import::ImportRequest;
+///
+/// // {"url": "https://example.com/recipe", "provider": "ollama"}
+/// let request = ImportRequest {
+///     url: Some("https://example.com/recipe".to_string()),
+///     provider: Some("ollama".to_string()),
+///     ..Default::default()
+/// };
+/// let builder = request.into_builder().unwrap();
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ImportRequest {
+    /// Recipe page to fetch
+    pub url: Option<String>,
+    /// Recipe text; pre-formatted unless `extract` is set
+    pub text: Option<String>,
+    /// Base64-encoded recipe photo, read with OCR
+    pub image: Option<String>,
+    /// Let the LLM pull the recipe out of free-form `text`
+    #[serde(default)]
+    pub extract: bool,
+    /// Return the extracted recipe without converting it to Cooklang
+    #[serde(default)]
+    pub extract_only: bool,
+    /// Provider name as on the command line (`openai`, `anthropic`, ...)
+    pub provider: Option<String>,
+}
+
+impl ImportRequest {
+    /// A builder set up for this request; fails if it doesn't name exactly
+    /// one source or names an unknown provider
+    pub fn into_builder(self) -> Result<RecipeImporterBuilder, ImportError> {
+        let mut builder = match (self.url, self.text, self.image) {
+            (Some(url), None, None) => RecipeImporter::builder().url(url),
+            (None, Some(text), None) if self.extract => {
+                RecipeImporter::builder().text_with_extraction(text)
+            }
+            (None, Some(text), None) => RecipeImporter::builder().text(text),
+            (None, None, Some(image)) => RecipeImporter::builder().image_base64(image),
+            _ => {
+                return Err(ImportError::BuilderError(
+                    "Set exactly one of url, text or image".to_string(),
+                ))
+            }
+        };
+        if self.extract_only {
+            builder = builder.extract_only();
+        }
+        if let Some(name) = self.provider {
+            let provider = match name.as_str() {
+                "openai" => LlmProvider::OpenAI,
+                "anthropic" => LlmProvider::Anthropic,
+                "google" => LlmProvider::Google,
+                "azure_openai" => LlmProvider::AzureOpenAI,
+                "ollama" => LlmProvider::Ollama,
+                "mistral" => LlmProvider::Mistral,
+                "groq" => LlmProvider::Groq,
+                #[cfg(feature = "mock")]
+                "mock" => LlmProvider::Mock,
+                _ => {
+                    return Err(ImportError::BuilderError(format!(
+                        "Unknown provider: {}",
+                        name
+                    )))
+                }
+            };
+            builder = builder.provider(provider);
+        }
+        Ok(builder)
+    }
+}
+
+/// Reject input that can't hold a recipe before any work is done: empty
+/// text, Markdown, email or pages, no images, and URLs that aren't http(s)
+/// pages
+fn validate_source(source: &InputSource) -> Result<(), ImportError> {
+    let problem = match source {
+        InputSource::Url(url) => crate::url_to_text::normalize::validate_url(url).err(),
+        InputSource::Text { content, .. } if content.trim().is_empty() => {
+            Some("The recipe text is empty".to_string())
+        }
+        InputSource::Markdown(markdown) if markdown.trim().is_empty() => {
+            Some("The Markdown is empty".to_string())
+        }
+        InputSource::Email(eml) if eml.is_empty() => Some("The email is empty".to_string()),
+        InputSource::Images(images) if images.is_empty() => Some("No images given".to_string()),
+        InputSource::Audio(path) if path.trim().is_empty() => {
+            Some("No audio file given".to_string())
+        }
+        InputSource::Html(html) if html.trim().is_empty() => Some("The page is empty".to_string()),
+        InputSource::Mhtml(mhtml) if mhtml.is_empty() => {
+            Some("The MHTML archive is empty".to_string())
+        }
+        _ => None,
+    };
+    match problem {
+        Some(problem) => Err(ImportError::BuilderError(problem)),
+        None => Ok(()),
+    }
+}
+
+/// The page of an [`InputSource::Html`] or [`InputSource::Mhtml`] source
+fn saved_page(source: &InputSource) -> Result<SavedPage, ImportError> {
+    match source {
+        InputSource::Mhtml(mhtml) => SavedPage::from_mhtml(mhtml),
+        InputSource::Html(html) => Ok(SavedPage::from_html(html.as_str())),
+        _ => unreachable!("not a saved page"),
+    }
 }
 
 /// Builder for configuring and executing recipe imports
 #[derive(Debug, Default)]
 pub struct RecipeImporterBuilder {
     source: Option<InputSource>,
+    source_url: Option<String>,
     mode: OutputMode,
     provider: Option<LlmProvider>,
     timeout: Option<Duration>,
+    llm_timeout: Option<Duration>,
+    proxy: Option<String>,
+    no_cache: bool,
+    offline: bool,
     api_key: Option<String>,
     model: Option<String>,
+    race: Option<Vec<LlmProvider>>,
+    structured_output: bool,
+    vision_extraction: bool,
+    merge_duplicates: bool,
+    check_ingredients: bool,
+    scale: Option<f64>,
+    units: Option<UnitSystem>,
+    both_temperatures: bool,
+    ingredient_aliases: Option<String>,
+    pantry: Option<Vec<String>>,
+    target_language: Option<String>,
+    keep_unknown_fields: bool,
+    provenance: bool,
+    title_style: TitleStyle,
+    providers: Option<Vec<LlmProvider>>,
+    multi: bool,
+    sub_recipes: bool,
+    image_order: ImageOrder,
 }
 
 impl RecipeImporterBuilder {
@@ -125,6 +455,107 @@ impl RecipeImporterBuilder {
         self
     }
 
+    /// Set the input source to an email (the raw contents of an `.eml` file)
+    ///
+    /// The HTML part goes through the same extractors as a web page, falling
+    /// back to LLM text extraction; text-only emails go straight to text
+    /// extraction. Forwarded messages attached to the email are searched
+    /// too.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cooklang_import::RecipeImporter;
+    ///
+    /// let eml = std::fs::read("newsletter.eml").unwrap();
+    /// let builder = RecipeImporter::builder()
+    ///     .email(eml);
+    /// ```
+    pub fn email(mut self, eml: impl Into<Vec<u8>>) -> Self {
+        self.source = Some(InputSource::Email(eml.into()));
+        self
+    }
+
+    /// Set the input source to a recipe written in Markdown
+    ///
+    /// The recipe needs an "Ingredients" heading over a list and an
+    /// "Instructions" (or "Method", "Directions", ...) heading over the
+    /// steps; the first heading or a `title` in YAML frontmatter is the
+    /// name, and `Servings: 4`-style lines become metadata. Markdown
+    /// without those headings goes through LLM text extraction instead.
+    ///
+    /// Offline, the recipe is converted to Cooklang without an LLM.
+    ///
+    /// # Example
+    /// ```
+    /// use cooklang_import::RecipeImporter;
+    ///
+    /// let markdown = "# Fried Eggs\n\n## Ingredients\n- 2 eggs\n\n## Method\n1. Fry the eggs.";
+    /// let builder = RecipeImporter::builder()
+    ///     .markdown(markdown);
+    /// ```
+    pub fn markdown(mut self, markdown: impl Into<String>) -> Self {
+        self.source = Some(InputSource::Markdown(markdown.into()));
+        self
+    }
+
+    /// Set the input source to a web page saved from a browser as HTML
+    ///
+    /// The page goes through the same extractors as a fetched one, without
+    /// any network access. The URL it came from is recorded as the
+    /// `source` when the page says (the `saved from url` comment browsers
+    /// write, or a canonical link) or [`source_url`](Self::source_url)
+    /// gives it.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cooklang_import::RecipeImporter;
+    ///
+    /// let html = std::fs::read_to_string("Pancakes.html").unwrap();
+    /// let builder = RecipeImporter::builder()
+    ///     .html(html)
+    ///     .source_url("https://example.com/recipes/pancakes");
+    /// ```
+    pub fn html(mut self, html: impl Into<String>) -> Self {
+        self.source = Some(InputSource::Html(html.into()));
+        self
+    }
+
+    /// Set the input source to a web page saved from a browser as an MHTML
+    /// archive (the raw contents of an `.mht` or `.mhtml` file)
+    ///
+    /// Like [`html`](Self::html), with the archive's snapshot location as
+    /// the page URL.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cooklang_import::RecipeImporter;
+    ///
+    /// let mhtml = std::fs::read("Pancakes.mhtml").unwrap();
+    /// let builder = RecipeImporter::builder()
+    ///     .mhtml(mhtml);
+    /// ```
+    pub fn mhtml(mut self, mhtml: impl Into<Vec<u8>>) -> Self {
+        self.source = Some(InputSource::Mhtml(mhtml.into()));
+        self
+    }
+
+    /// The URL a saved page ([`html`](Self::html), [`mhtml`](Self::mhtml))
+    /// was saved from, recorded as its `source` instead of the one the page
+    /// gives
+    ///
+    /// # Example
+    /// ```
+    /// use cooklang_import::RecipeImporter;
+    ///
+    /// let builder = RecipeImporter::builder()
+    ///     .html("<html>...</html>")
+    ///     .source_url("https://example.com/recipes/pancakes");
+    /// ```
+    pub fn source_url(mut self, url: impl Into<String>) -> Self {
+        self.source_url = Some(url.into());
+        self
+    }
+
     /// Add an image file path to the input sources
     ///
     /// Use this when you have a recipe image that needs to be OCR'd.
@@ -197,6 +628,44 @@ impl RecipeImporterBuilder {
         self
     }
 
+    /// Set the input source to a dictated recipe (a voice memo)
+    ///
+    /// The recording is transcribed with OpenAI's Whisper API (using
+    /// OPENAI_API_KEY) or a local whisper.cpp build, as the `[transcription]`
+    /// config says, and the transcript goes through LLM text extraction.
+    ///
+    /// # Example
+    /// ```
+    /// use cooklang_import::RecipeImporter;
+    ///
+    /// let builder = RecipeImporter::builder()
+    ///     .audio_path("/path/to/grandmas-goulash.m4a");
+    /// ```
+    pub fn audio_path(mut self, path: impl Into<String>) -> Self {
+        self.source = Some(InputSource::Audio(path.into()));
+        self
+    }
+
+    /// Choose the order in which the images are read as pages of one recipe
+    ///
+    /// Images are read in the order they were added by default. With
+    /// [`ImageOrder::Filename`] they're sorted by file name, numbers by
+    /// value, so "page-2.jpg" comes before "page-10.jpg".
+    ///
+    /// # Example
+    /// ```
+    /// use cooklang_import::{ImageOrder, RecipeImporter};
+    ///
+    /// let builder = RecipeImporter::builder()
+    ///     .image_path("scans/page-10.jpg")
+    ///     .image_path("scans/page-9.jpg")
+    ///     .image_order(ImageOrder::Filename);
+    /// ```
+    pub fn image_order(mut self, order: ImageOrder) -> Self {
+        self.image_order = order;
+        self
+    }
+
     /// Set output mode to extract only (no conversion)
     ///
     /// This returns a Recipe struct without converting to Cooklang format.
@@ -229,26 +698,35 @@ impl RecipeImporterBuilder {
         self
     }
 
-    /// Set a timeout for HTTP requests
+    /// Try several providers in order, falling back on failure
+    ///
+    /// Each provider is retried with exponential backoff before moving on to
+    /// the next one. Retry settings come from the `[fallback]` config section
+    /// when present (defaults: 3 attempts, 1000 ms initial delay). Providers
+    /// that can't be created (e.g. missing API key) are skipped.
+    ///
+    /// `api_key` and `model` overrides apply to the first provider only.
     ///
     /// # Example
     /// ```
-    /// use cooklang_import::RecipeImporter;
-    /// use std::time::Duration;
+    /// use cooklang_import::{RecipeImporter, LlmProvider};
     ///
     /// let builder = RecipeImporter::builder()
     ///     .url("https://example.com/recipe")
-    ///     .timeout(Duration::from_secs(30));
+    ///     .providers(&[LlmProvider::Ollama, LlmProvider::OpenAI]);
     /// ```
-    pub fn timeout(mut self, duration: Duration) -> Self {
-        self.timeout = Some(duration);
+    pub fn providers(mut self, providers: &[LlmProvider]) -> Self {
+        self.providers = Some(providers.to_vec());
         self
     }
 
-    /// Set the API key for the LLM provider
+    /// Race two providers against each other
     ///
-    /// This allows passing the API key directly instead of relying on
-    /// environment variables or config files.
+    /// The conversion is sent to both providers simultaneously and the first
+    /// response that passes the Cooklang syntax check is used. This roughly
+    /// doubles the cost of a conversion in exchange for lower latency.
+    ///
+    /// `api_key` and `model` overrides apply to the first provider only.
     ///
     /// # Example
     /// ```
@@ -256,15 +734,21 @@ impl RecipeImporterBuilder {
     ///
     /// let builder = RecipeImporter::builder()
     ///     .url("https://example.com/recipe")
-    ///     .provider(LlmProvider::Anthropic)
-    ///     .api_key("your-api-key");
+    ///     .race(LlmProvider::OpenAI, LlmProvider::Anthropic);
     /// ```
-    pub fn api_key(mut self, key: impl Into<String>) -> Self {
-        self.api_key = Some(key.into());
+    pub fn race(mut self, first: LlmProvider, second: LlmProvider) -> Self {
+        self.race = Some(vec![first, second]);
         self
     }
 
-    /// Set the model name for the LLM provider
+    /// Use structured output for conversion
+    ///
+    /// The LLM returns the recipe as JSON (ingredients with quantity/unit,
+    /// steps referencing ingredients) using OpenAI JSON mode or Anthropic tool
+    /// use, and the Cooklang is rendered deterministically. This avoids
+    /// malformed markup at the cost of some of the source wording.
+    ///
+    /// Supported by OpenAI, Anthropic and Ollama.
     ///
     /// # Example
     /// ```
@@ -273,184 +757,1737 @@ impl RecipeImporterBuilder {
     /// let builder = RecipeImporter::builder()
     ///     .url("https://example.com/recipe")
     ///     .provider(LlmProvider::Anthropic)
-    ///     .model("claude-3-5-sonnet-20241022");
+    ///     .structured_output();
     /// ```
-    pub fn model(mut self, model: impl Into<String>) -> Self {
-        self.model = Some(model.into());
+    pub fn structured_output(mut self) -> Self {
+        self.structured_output = true;
         self
     }
 
-    /// Build and execute the recipe import operation
+    /// Merge repeated ingredient declarations into one combined amount
     ///
-    /// # Returns
-    /// An `ImportResult` containing either a Cooklang string or Recipe struct
+    /// When an ingredient appears in several sections (salt in the dough and
+    /// the filling), the first declaration gets the summed quantity and later
+    /// ones become references (`@&salt{}`). Only declarations with numeric
+    /// quantities in the same unit are merged. Equivalent to
+    /// `converters.duplicate_ingredients = "merge"` in config.
     ///
-    /// # Errors
-    /// Returns `ImportError` if:
-    /// - No input source was specified
-    /// - URL fetch fails
-    /// - Recipe extraction fails
-    /// - Conversion fails
-    /// - Invalid combination of options (e.g., markdown + extract_only)
+    /// # Example
+    /// ```
+    /// use cooklang_import::RecipeImporter;
+    ///
+    /// let builder = RecipeImporter::builder()
+    ///     .url("https://example.com/recipe")
+    ///     .merge_duplicate_ingredients();
+    /// ```
+    pub fn merge_duplicate_ingredients(mut self) -> Self {
+        self.merge_duplicates = true;
+        self
+    }
+
+    /// Compare the ingredients of the Cooklang with the extracted ingredient
+    /// list, reporting dropped and invented ones in
+    /// `ConversionMetadata::ingredient_check`
+    ///
+    /// Skipped when translating, since the names can't be compared.
     ///
     /// # Example
     /// ```no_run
-    /// # use cooklang_import::RecipeImporter;
-    /// # #[tokio::main]
-    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let result = RecipeImporter::builder()
+    /// use cooklang_import::RecipeImporter;
+    ///
+    /// let builder = RecipeImporter::builder()
     ///     .url("https://example.com/recipe")
-    ///     .build()
-    ///     .await?;
-    /// # Ok(())
-    /// # }
+    ///     .check_ingredients();
     /// ```
-    pub async fn build(self) -> Result<ImportResult, ImportError> {
-        // Validate that source is set
-        let source = self.source.clone().ok_or_else(|| {
-            ImportError::BuilderError(
-                "No input source specified. Use .url(), .text(), or .image_path()".to_string(),
-            )
-        })?;
-
-        // Route to the appropriate pipeline based on input source
-        let components = match source {
-            InputSource::Url(url) => crate::pipelines::url::process(&url)
-                .await
-                .map_err(|e| ImportError::BuilderError(e.to_string()))?,
-            InputSource::Text { content, extract } => {
-                crate::pipelines::text::process(&content, extract)
-                    .await
-                    .map_err(|e| ImportError::BuilderError(e.to_string()))?
-            }
-            InputSource::Images(images) => crate::pipelines::image::process(&images)
-                .await
-                .map_err(|e| ImportError::BuilderError(e.to_string()))?,
-        };
-
-        // Return based on output mode
-        match self.mode {
-            OutputMode::Cooklang => {
-                // Convert to Cooklang format using a converter
-                let (content, conversion_metadata) = self.convert_to_cooklang(&components).await?;
-                Ok(ImportResult::Cooklang {
-                    content,
-                    conversion_metadata: Some(conversion_metadata),
-                })
-            }
-            OutputMode::Recipe => Ok(ImportResult::Components(components)),
-        }
+    pub fn check_ingredients(mut self) -> Self {
+        self.check_ingredients = true;
+        self
     }
 
-    /// Convert RecipeComponents to Cooklang using configured converter
-    async fn convert_to_cooklang(
-        &self,
-        components: &RecipeComponents,
-    ) -> Result<(String, ConversionMetadata), ImportError> {
-        // Get converter configuration
-        let converter = self.get_converter().await?;
-
-        // Convert the text (ingredients + instructions) to Cooklang
-        let conversion_result = converter
-            .convert(&components.text)
-            .await
-            .map_err(|e| ImportError::ConversionError(e.to_string()))?;
-
-        // Build YAML frontmatter from metadata and name
-        let mut output = String::new();
-        let has_name = !components.name.is_empty();
-        let has_metadata = !components.metadata.is_empty();
-
-        if has_name || has_metadata {
-            output.push_str("---\n");
-            if has_name {
-                let title_yaml = crate::pipelines::metadata_to_yaml(&[(
-                    "title".to_string(),
-                    components.name.clone(),
-                )]);
-                output.push_str(&title_yaml);
-            }
-            if has_metadata {
-                output.push_str(&components.metadata);
-                if !components.metadata.ends_with('\n') {
-                    output.push('\n');
-                }
-            }
-            output.push_str("---\n\n");
-        }
-        output.push_str(&conversion_result.content);
-
-        Ok((output, conversion_result.metadata))
+    /// Multiply ingredient quantities and servings in the Cooklang output
+    ///
+    /// Scaling happens on the generated Cooklang, not in the prompt, so it
+    /// is exact and doesn't change the cached conversion. Text quantities
+    /// ("pinch") and fixed quantities (`@salt{=1%tsp}`) are kept. Cannot be
+    /// combined with `extract_only`.
+    ///
+    /// # Example
+    /// ```
+    /// use cooklang_import::RecipeImporter;
+    ///
+    /// // Double the recipe
+    /// let builder = RecipeImporter::builder()
+    ///     .url("https://example.com/recipe")
+    ///     .scale(2.0);
+    /// ```
+    pub fn scale(mut self, factor: f64) -> Self {
+        self.scale = Some(factor);
+        self
     }
 
-    /// Get the appropriate converter based on configuration
-    async fn get_converter(&self) -> Result<Box<dyn Converter>, ImportError> {
-        // Determine which provider to use
-        let provider_name: String = match &self.provider {
-            Some(LlmProvider::OpenAI) => "open_ai".to_string(),
-            Some(LlmProvider::Anthropic) => "anthropic".to_string(),
-            Some(LlmProvider::Google) => "google".to_string(),
-            Some(LlmProvider::AzureOpenAI) => "azure_openai".to_string(),
-            Some(LlmProvider::Ollama) => "ollama".to_string(),
-            None => {
-                // Try to load from config, or default to open_ai
-                load_config()
-                    .map(|c| c.default_provider)
-                    .unwrap_or_else(|_| "open_ai".to_string())
-            }
-        };
-
-        // Build provider config
-        let provider_config = self.build_provider_config(&provider_name);
+    /// Convert quantities and oven temperatures in the Cooklang output to
+    /// metric or imperial units
+    ///
+    /// Uses a fixed conversion table for common cooking units (cups, ounces,
+    /// pounds, grams, milliliters, °F/°C). Like `scale`, it runs on the
+    /// generated Cooklang, after scaling. Cannot be combined with
+    /// `extract_only`.
+    ///
+    /// # Example
+    /// ```
+    /// use cooklang_import::{RecipeImporter, UnitSystem};
+    ///
+    /// let builder = RecipeImporter::builder()
+    ///     .url("https://example.com/recipe")
+    ///     .units(UnitSystem::Metric);
+    /// ```
+    pub fn units(mut self, system: UnitSystem) -> Self {
+        self.units = Some(system);
+        self
+    }
 
-        // Create the converter
-        converters::create_converter(&provider_name, &provider_config).ok_or_else(|| {
-            ImportError::ConversionError(format!(
-                "Failed to create converter '{}'. Check API key and configuration.",
-                provider_name
-            ))
-        })
+    /// Write oven temperatures in the steps in both °C and °F
+    ///
+    /// Each temperature is followed by the other scale, e.g. "180°C (355°F)"
+    /// or "gas mark 4 (180°C/350°F)", unless the step already gives both.
+    /// Equivalent to `converters.oven_temperatures = "both"` in config.
+    ///
+    /// # Example
+    /// ```
+    /// use cooklang_import::RecipeImporter;
+    ///
+    /// let builder = RecipeImporter::builder()
+    ///     .url("https://example.com/recipe")
+    ///     .both_temperatures();
+    /// ```
+    pub fn both_temperatures(mut self) -> Self {
+        self.both_temperatures = true;
+        self
     }
 
-    /// Build provider configuration from builder settings and environment
-    fn build_provider_config(&self, provider_name: &str) -> ProviderConfig {
-        // Try to load config from file first
-        let base_config = load_config()
-            .ok()
+    /// Rename ingredients to canonical names from an aliases file
+    ///
+    /// Each line of the file is a canonical name followed by its aliases,
+    /// separated by `|` (`green onions|scallions|spring onions`); an
+    /// `aisle.conf` works too. `@scallions{4}` becomes
+    /// `@green onions|scallions{4}`, so shopping lists add it up with other
+    /// recipes' green onions while the steps still say scallions. Renaming
+    /// happens before duplicate ingredients are merged. Equivalent to
+    /// `converters.ingredient_aliases` in config.
+    ///
+    /// # Example
+    /// ```
+    /// use cooklang_import::RecipeImporter;
+    ///
+    /// let builder = RecipeImporter::builder()
+    ///     .url("https://example.com/recipe")
+    ///     .ingredient_aliases("config/aliases.conf");
+    /// ```
+    pub fn ingredient_aliases(mut self, path: impl Into<String>) -> Self {
+        self.ingredient_aliases = Some(path.into());
+        self
+    }
+
+    /// Mark pantry staples so shopping lists can skip them
+    ///
+    /// Ingredients named in `items` are marked the way
+    /// `converters.pantry.mark` says, by default with a note:
+    /// `@salt{}(pantry)`. Replaces the staples listed in
+    /// `converters.pantry` in config.
+    ///
+    /// # Example
+    /// ```
+    /// use cooklang_import::RecipeImporter;
+    ///
+    /// let builder = RecipeImporter::builder()
+    ///     .url("https://example.com/recipe")
+    ///     .pantry(["salt", "pepper", "olive oil"]);
+    /// ```
+    pub fn pantry<I, S>(mut self, items: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.pantry = Some(items.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Record where the recipe came from in an `import:` frontmatter block
+    ///
+    /// The block has the source URL or file, the date, the extractor, the
+    /// provider and model that converted the recipe, a hash of the prompt
+    /// and the cooklang-import version (see [`Provenance`]), so an import
+    /// can be audited or repeated later. Equivalent to
+    /// `frontmatter.provenance` in config.
+    ///
+    /// [`Provenance`]: crate::provenance::Provenance
+    ///
+    /// # Example
+    /// ```
+    /// use cooklang_import::RecipeImporter;
+    ///
+    /// let builder = RecipeImporter::builder()
+    ///     .url("https://example.com/recipe")
+    ///     .provenance();
+    /// ```
+    pub fn provenance(mut self) -> Self {
+        self.provenance = true;
+        self
+    }
+
+    /// Keep fields the structured extractor doesn't know in the frontmatter
+    ///
+    /// Unmapped schema keys (e.g. `datePublished` or `publisher` in JSON-LD)
+    /// are normally dropped and only listed in
+    /// [`ImportReport::dropped_fields`]. With this option they are written to
+    /// the metadata under an `x-` prefix (`x-datePublished`), so nothing
+    /// from the page is lost.
+    ///
+    /// # Example
+    /// ```
+    /// use cooklang_import::RecipeImporter;
+    ///
+    /// let builder = RecipeImporter::builder()
+    ///     .url("https://example.com/recipe")
+    ///     .keep_unknown_fields();
+    /// ```
+    pub fn keep_unknown_fields(mut self) -> Self {
+        self.keep_unknown_fields = true;
+        self
+    }
+
+    /// Import every recipe on a roundup page ("10 best curry recipes")
+    /// instead of only the first; collect them with
+    /// [`build_all`](Self::build_all)
+    ///
+    /// The recipes are read from the page's JSON-LD, fetched or
+    /// [saved](Self::html). A page with a single recipe is imported as
+    /// usual.
+    ///
+    /// # Example
+    /// ```
+    /// use cooklang_import::RecipeImporter;
+    ///
+    /// let builder = RecipeImporter::builder()
+    ///     .url("https://example.com/best-curry-recipes")
+    ///     .multi();
+    /// ```
+    pub fn multi(mut self) -> Self {
+        self.multi = true;
+        self
+    }
+
+    /// Split component recipes (dough, filling, glaze) into recipes of their
+    /// own; collect them with [`build_all`](Self::build_all)
+    ///
+    /// An ingredient group and an instruction section with the same name
+    /// become a part named after the recipe ("Cinnamon Rolls Glaze"), and the
+    /// recipe's first step references each part as `@./Cinnamon Rolls
+    /// Glaze{}`. Save each result as its title's [`file_name`](crate::stages::file_name)
+    /// in one directory so the references resolve. A recipe without
+    /// matching groups and sections is imported as usual.
+    ///
+    /// # Example
+    /// ```
+    /// use cooklang_import::RecipeImporter;
+    ///
+    /// let builder = RecipeImporter::builder()
+    ///     .url("https://example.com/cinnamon-rolls")
+    ///     .sub_recipes();
+    /// ```
+    pub fn sub_recipes(mut self) -> Self {
+        self.sub_recipes = true;
+        self
+    }
+
+    /// Choose where the title goes in the Cooklang output
+    ///
+    /// By default it is written as frontmatter `title:`. Some tools expect a
+    /// leading `# Title` line instead, and others take the title from the
+    /// file name; with [`TitleStyle::Filename`] the title is left out of the
+    /// content and returned in [`ImportResult::Cooklang`]'s `title`, ready
+    /// for [`stages::file_name`](crate::stages::file_name).
+    ///
+    /// # Example
+    /// ```
+    /// use cooklang_import::{RecipeImporter, TitleStyle};
+    ///
+    /// let builder = RecipeImporter::builder()
+    ///     .url("https://example.com/recipe")
+    ///     .title_style(TitleStyle::Heading);
+    /// ```
+    pub fn title_style(mut self, style: TitleStyle) -> Self {
+        self.title_style = style;
+        self
+    }
+
+    /// Translate the recipe into `language` while converting it
+    ///
+    /// Takes a language code ("en", "de") or name ("English"). The LLM is
+    /// asked to translate ingredient names and steps but keep every quantity
+    /// and unit as written. The recipe's original language is kept in a
+    /// `source language` metadata field. Cannot be combined with
+    /// `extract_only`.
+    ///
+    /// # Example
+    /// ```
+    /// use cooklang_import::RecipeImporter;
+    ///
+    /// // German blog → English Cooklang
+    /// let builder = RecipeImporter::builder()
+    ///     .url("https://example.com/rezept")
+    ///     .target_language("en");
+    /// ```
+    pub fn target_language(mut self, language: impl Into<String>) -> Self {
+        self.target_language = Some(language.into());
+        self
+    }
+
+    /// Send images directly to a multimodal model
+    ///
+    /// Instead of running Google Vision OCR and then converting the text, the
+    /// photos are sent to the provider's vision model, which returns Cooklang
+    /// in one step. Works with OpenAI, Anthropic, Google and Ollama (with a
+    /// vision model such as `llava` or `llama3.2-vision`). Only applies to
+    /// image sources.
+    ///
+    /// # Example
+    /// ```
+    /// use cooklang_import::{RecipeImporter, LlmProvider};
+    ///
+    /// let builder = RecipeImporter::builder()
+    ///     .image_path("/path/to/recipe.jpg")
+    ///     .provider(LlmProvider::Anthropic)
+    ///     .vision_extraction();
+    /// ```
+    pub fn vision_extraction(mut self) -> Self {
+        self.vision_extraction = true;
+        self
+    }
+
+    /// Set a timeout for HTTP requests
+    ///
+    /// Used for page fetches unless `[timeouts] fetch` is configured, and a
+    /// ceiling for every other stage (rendering, OCR, extraction and
+    /// conversion).
+    ///
+    /// # Example
+    /// ```
+    /// use cooklang_import::RecipeImporter;
+    /// use std::time::Duration;
+    ///
+    /// let builder = RecipeImporter::builder()
+    ///     .url("https://example.com/recipe")
+    ///     .timeout(Duration::from_secs(30));
+    /// ```
+    pub fn timeout(mut self, duration: Duration) -> Self {
+        self.timeout = Some(duration);
+        self
+    }
+
+    /// Set a timeout for LLM and OCR requests
+    ///
+    /// Overrides the configured OCR, extraction and conversion timeouts;
+    /// still capped by [`timeout`](Self::timeout).
+    ///
+    /// # Example
+    /// ```
+    /// use cooklang_import::RecipeImporter;
+    /// use std::time::Duration;
+    ///
+    /// let builder = RecipeImporter::builder()
+    ///     .url("https://example.com/recipe")
+    ///     .timeout(Duration::from_secs(10))
+    ///     .llm_timeout(Duration::from_secs(90));
+    /// ```
+    pub fn llm_timeout(mut self, duration: Duration) -> Self {
+        self.llm_timeout = Some(duration);
+        self
+    }
+
+    /// Send all requests (page fetches, OCR and LLM calls) through a proxy
+    ///
+    /// Without this, the `[http] proxy` config setting or the standard
+    /// `HTTP_PROXY`/`HTTPS_PROXY` environment variables are used.
+    ///
+    /// # Example
+    /// ```
+    /// use cooklang_import::RecipeImporter;
+    ///
+    /// let builder = RecipeImporter::builder()
+    ///     .url("https://example.com/recipe")
+    ///     .proxy("http://proxy.example.com:3128");
+    /// ```
+    pub fn proxy(mut self, url: impl Into<String>) -> Self {
+        self.proxy = Some(url.into());
+        self
+    }
+
+    /// Skip the on-disk cache: always fetch the page and call the LLM
+    ///
+    /// Fresh results are not written to the cache either.
+    ///
+    /// # Example
+    /// ```
+    /// use cooklang_import::RecipeImporter;
+    ///
+    /// let builder = RecipeImporter::builder()
+    ///     .url("https://example.com/recipe")
+    ///     .no_cache();
+    /// ```
+    pub fn no_cache(mut self) -> Self {
+        self.no_cache = true;
+        self
+    }
+
+    /// Never use the network; fail with [`ImportError::OfflineError`] when a
+    /// stage would need it
+    ///
+    /// Text and image files are read locally, and conversions come from the
+    /// cache or from an Ollama server on this machine (`localhost` or a
+    /// loopback address) run at temperature 0. Without one, recipes are
+    /// converted by rules, as Markdown is, unless a provider was chosen with
+    /// [`provider`](Self::provider) (or a chain or race) or the recipe is to
+    /// be translated. LLM text extraction uses the same providers, so it
+    /// works with a local Ollama server. Fetching URLs and Google Vision OCR
+    /// are refused; OCR works with the `tesseract`, `onnx` or `ollama` backend
+    /// of `[ocr]`. `offline = true` in config.toml has the same effect.
+    ///
+    /// # Example
+    /// ```
+    /// use cooklang_import::{LlmProvider, RecipeImporter};
+    ///
+    /// let builder = RecipeImporter::builder()
+    ///     .text("2 eggs\n\nFry the eggs.")
+    ///     .provider(LlmProvider::Ollama)
+    ///     .offline();
+    /// ```
+    pub fn offline(mut self) -> Self {
+        self.offline = true;
+        self
+    }
+
+    /// Set the API key for the LLM provider
+    ///
+    /// This allows passing the API key directly instead of relying on
+    /// environment variables or config files.
+    ///
+    /// # Example
+    /// ```
+    /// use cooklang_import::{RecipeImporter, LlmProvider};
+    ///
+    /// let builder = RecipeImporter::builder()
+    ///     .url("https://example.com/recipe")
+    ///     .provider(LlmProvider::Anthropic)
+    ///     .api_key("your-api-key");
+    /// ```
+    pub fn api_key(mut self, key: impl Into<String>) -> Self {
+        self.api_key = Some(key.into());
+        self
+    }
+
+    /// Set the model name for the LLM provider
+    ///
+    /// # Example
+    /// ```
+    /// use cooklang_import::{RecipeImporter, LlmProvider};
+    ///
+    /// let builder = RecipeImporter::builder()
+    ///     .url("https://example.com/recipe")
+    ///     .provider(LlmProvider::Anthropic)
+    ///     .model("claude-3-5-sonnet-20241022");
+    /// ```
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Build and execute the recipe import operation
+    ///
+    /// # Returns
+    /// An `ImportResult` containing either a Cooklang string or Recipe struct
+    ///
+    /// # Errors
+    /// Returns `ImportError` if:
+    /// - No input source was specified
+    /// - URL fetch fails
+    /// - Recipe extraction fails
+    /// - Conversion fails
+    /// - Invalid combination of options (e.g., scale + extract_only)
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use cooklang_import::RecipeImporter;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let result = RecipeImporter::builder()
+    ///     .url("https://example.com/recipe")
+    ///     .build()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn build(self) -> Result<ImportResult, ImportError> {
+        self.build_with_extractor()
+            .await
+            .map(|(result, _extractor)| result)
+    }
+
+    /// Like [`build`](Self::build), also saying how the recipe was found:
+    /// the structured extractor as in [`ImportReport::extractor`], "text"
+    /// for pre-formatted text, "markdown" for parsed Markdown, or `None`
+    /// when an LLM or OCR read it
+    ///
+    /// Batch tools use this to record where each recipe came from.
+    pub async fn build_with_extractor(self) -> Result<(ImportResult, Option<String>), ImportError> {
+        if self.multi || self.sub_recipes {
+            return Err(ImportError::BuilderError(
+                "multi() and sub_recipes() can import several recipes; use build_all()".to_string(),
+            ));
+        }
+        let mut results = self.import().await?;
+        Ok(results.remove(0))
+    }
+
+    /// Import every recipe the source holds.
+    ///
+    /// With [`multi`](Self::multi), a page whose JSON-LD lists several
+    /// recipes gives one result per recipe, in page order; each one is
+    /// converted (or extracted) as [`build`](Self::build) would. With
+    /// [`sub_recipes`](Self::sub_recipes), each recipe is followed by its
+    /// parts. Any other source gives a single result.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use cooklang_import::{ImportResult, RecipeImporter};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let results = RecipeImporter::builder()
+    ///     .url("https://example.com/best-curry-recipes")
+    ///     .multi()
+    ///     .build_all()
+    ///     .await?;
+    /// for result in results {
+    ///     if let ImportResult::Cooklang { title, .. } = result {
+    ///         println!("{}", title);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn build_all(self) -> Result<Vec<ImportResult>, ImportError> {
+        Ok(self
+            .import()
+            .await?
+            .into_iter()
+            .map(|(result, _extractor)| result)
+            .collect())
+    }
+
+    /// Validate the options, run the source's pipeline and convert each
+    /// recipe found (one unless [`multi`](Self::multi) or
+    /// [`sub_recipes`](Self::sub_recipes) is set)
+    #[tracing::instrument(name = "import", skip_all)]
+    async fn import(mut self) -> Result<Vec<(ImportResult, Option<String>)>, ImportError> {
+        // `offline = true` in config.toml turns offline mode on for everything
+        self.offline |= load_config().is_ok_and(|config| config.offline);
+
+        // Validate that source is set
+        let source = self.source.clone().ok_or_else(|| {
+            ImportError::BuilderError(
+                "No input source specified. Use .url(), .text(), or .image_path()".to_string(),
+            )
+        })?;
+        validate_source(&source)?;
+        self.validate_source_url()?;
+        let source = match source {
+            InputSource::Images(images) => {
+                InputSource::Images(images_to_text::order(&images, self.image_order))
+            }
+            source => source,
+        };
+
+        if let Some(proxy) = &self.proxy {
+            crate::http::validate_proxy(proxy).map_err(ImportError::BuilderError)?;
+        }
+
+        if self.units.is_some() && matches!(self.mode, OutputMode::Recipe) {
+            return Err(ImportError::BuilderError(
+                "units applies to Cooklang output and cannot be combined with extract_only"
+                    .to_string(),
+            ));
+        }
+
+        if let Some(language) = &self.target_language {
+            if language.trim().is_empty() {
+                return Err(ImportError::BuilderError(
+                    "target_language cannot be empty".to_string(),
+                ));
+            }
+            if matches!(self.mode, OutputMode::Recipe) {
+                return Err(ImportError::BuilderError(
+                    "target_language translates during conversion and cannot be combined with extract_only"
+                        .to_string(),
+                ));
+            }
+        }
+
+        if let Some(factor) = self.scale {
+            if !factor.is_finite() || factor <= 0.0 {
+                return Err(ImportError::BuilderError(format!(
+                    "scale must be a positive number, got {}",
+                    factor
+                )));
+            }
+            if matches!(self.mode, OutputMode::Recipe) {
+                return Err(ImportError::BuilderError(
+                    "scale applies to Cooklang output and cannot be combined with extract_only"
+                        .to_string(),
+                ));
+            }
+        }
+
+        if let InputSource::Images(images) = &source {
+            if self.vision_extraction {
+                return Ok(vec![(self.convert_images_to_cooklang(images).await?, None)]);
+            }
+            self.require_local_ocr()?;
+        }
+        if let InputSource::Audio(_) = &source {
+            self.require_local_transcription()?;
+        }
+
+        if let InputSource::Markdown(markdown) = &source {
+            if self.offline && matches!(self.mode, OutputMode::Cooklang) {
+                return Ok(vec![(
+                    self.convert_markdown(markdown)?,
+                    Some("markdown".to_string()),
+                )]);
+            }
+        }
+
+        // Route to the appropriate pipeline based on input source
+        let timeouts = self.timeouts(load_config().ok().as_ref());
+        let proxy = self.proxy.as_deref();
+        // Further recipes on a multi-recipe page
+        let mut more = Vec::new();
+        let (components, extractor) = match source {
+            InputSource::Url(url) => {
+                self.require_network(format!("fetching {}", url))?;
+                let extractor = self.text_extractor().await.map_err(|e| e.to_string());
+                let llm = Llm::from_result(&extractor);
+                let cache = self.cache();
+                let extractions = if self.multi {
+                    crate::pipelines::url::extract_all(&url, &timeouts, proxy, cache.as_ref(), llm)
+                        .await
+                } else {
+                    crate::pipelines::url::extract(&url, &timeouts, proxy, cache.as_ref(), llm)
+                        .await
+                        .map(|extraction| vec![extraction])
+                }
+                .map_err(ImportError::from_fetch)?;
+                let mut extracted = extractions.into_iter().map(|extraction| {
+                    (
+                        self.with_unknown_fields(extraction.components, &extraction.dropped_fields),
+                        extraction.extractor.map(str::to_string),
+                    )
+                });
+                let first = extracted.next().expect("the URL pipeline returns a recipe");
+                more.extend(extracted);
+                first
+            }
+            InputSource::Text {
+                content,
+                extract: true,
+            } => {
+                let extractor = self.text_extractor().await?;
+                let components = crate::pipelines::text::extract(&content, &extractor)
+                    .await
+                    .map_err(|e| ImportError::from_pipeline(e, ImportError::BuilderError))?;
+                (components, None)
+            }
+            InputSource::Text {
+                content,
+                extract: false,
+            } => {
+                let components = crate::pipelines::text::process(&content, false, &timeouts, proxy)
+                    .await
+                    .map_err(|e| ImportError::from_pipeline(e, ImportError::BuilderError))?;
+                (components, Some("text".to_string()))
+            }
+            InputSource::Images(images) => {
+                let extractor = self.text_extractor().await.map_err(|e| e.to_string());
+                let components = crate::pipelines::image::process_with(
+                    &images,
+                    &timeouts,
+                    proxy,
+                    Llm::from_result(&extractor),
+                )
+                .await
+                .map_err(|e| ImportError::from_pipeline(e, ImportError::BuilderError))?;
+                (components, None)
+            }
+            InputSource::Audio(path) => {
+                let extractor = self.text_extractor().await.map_err(|e| e.to_string());
+                let components = crate::pipelines::audio::process_with(
+                    &path,
+                    &timeouts,
+                    proxy,
+                    Llm::from_result(&extractor),
+                )
+                .await
+                .map_err(|e| ImportError::from_pipeline(e, ImportError::BuilderError))?;
+                (components, None)
+            }
+            InputSource::Email(eml) => {
+                // Offline without a local provider, an email without
+                // structured data is converted from its plain text
+                let extractor = self.text_extractor().await.map_err(|e| e.to_string());
+                let llm = match &extractor {
+                    Err(_) if self.offline => Llm::Off,
+                    extractor => Llm::from_result(extractor),
+                };
+                let extraction = crate::pipelines::email::extract(&eml, llm)
+                    .await
+                    .map_err(|e| ImportError::from_pipeline(e, ImportError::BuilderError))?;
+                (
+                    self.with_unknown_fields(extraction.components, &extraction.dropped_fields),
+                    extraction.extractor.map(str::to_string),
+                )
+            }
+            source @ (InputSource::Html(_) | InputSource::Mhtml(_)) => {
+                // Offline without a local provider, a page without
+                // structured data is converted from its plain text
+                let page = saved_page(&source)?;
+                let extractor = self.text_extractor().await.map_err(|e| e.to_string());
+                let llm = match &extractor {
+                    Err(_) if self.offline => Llm::Off,
+                    extractor => Llm::from_result(extractor),
+                };
+                let extractions = crate::pipelines::html::extract(
+                    &page,
+                    self.source_url.as_deref(),
+                    self.multi,
+                    llm,
+                )
+                .await
+                .map_err(|e| ImportError::from_pipeline(e, ImportError::ExtractionError))?;
+                let mut extracted = extractions.into_iter().map(|extraction| {
+                    (
+                        self.with_unknown_fields(extraction.components, &extraction.dropped_fields),
+                        extraction.extractor.map(str::to_string),
+                    )
+                });
+                let first = extracted
+                    .next()
+                    .expect("the page pipeline returns a recipe");
+                more.extend(extracted);
+                first
+            }
+            InputSource::Markdown(markdown) => match crate::pipelines::markdown::process(&markdown)
+            {
+                Ok(components) => (components, Some("markdown".to_string())),
+                Err(e) => {
+                    // Free-form Markdown is still a recipe the LLM can read
+                    let extractor = self.text_extractor().await?;
+                    warn!("{}; using LLM text extraction", e);
+                    let components = crate::pipelines::text::extract(&markdown, &extractor)
+                        .await
+                        .map_err(|e| ImportError::from_pipeline(e, ImportError::BuilderError))?;
+                    (components, None)
+                }
+            },
+        };
+        let mut results = Vec::new();
+        for (components, extractor) in std::iter::once((components, extractor)).chain(more) {
+            let components = crate::stages::normalize(components);
+            let split = if self.sub_recipes {
+                crate::stages::split_sub_recipes(&components)
+            } else {
+                SplitRecipe {
+                    parent: components,
+                    parts: Vec::new(),
+                }
+            };
+            let names: Vec<&str> = split.parts.iter().map(|part| part.name.as_str()).collect();
+            let parent = self
+                .finish(split.parent, &names, extractor.as_deref())
+                .await?;
+            results.push((parent, extractor.clone()));
+            for part in split.parts {
+                let part = self.finish(part, &[], extractor.as_deref()).await?;
+                results.push((part, extractor.clone()));
+            }
+        }
+        Ok(results)
+    }
+
+    /// Convert (or return) one normalized recipe, keeping the references to
+    /// its `parts` in the Cooklang
+    async fn finish(
+        &self,
+        components: RecipeComponents,
+        parts: &[&str],
+        extractor: Option<&str>,
+    ) -> Result<ImportResult, ImportError> {
+        Ok(match self.mode {
+            OutputMode::Cooklang => {
+                // Convert to Cooklang format using a converter
+                let conversion = self.convert(&components).await?;
+                let components = self.with_source_language(components);
+                let components =
+                    self.with_provenance(components, extractor, Some(&conversion.metadata));
+                let cooklang = if parts.is_empty() {
+                    conversion.content
+                } else {
+                    crate::pipelines::sub_recipes::ensure_references(&conversion.content, parts)
+                };
+                ImportResult::Cooklang {
+                    content: crate::stages::render_titled(&components, &cooklang, self.title_style),
+                    title: components.name,
+                    conversion_metadata: Some(conversion.metadata),
+                }
+            }
+            OutputMode::Recipe => {
+                ImportResult::Components(self.with_provenance(components, extractor, None))
+            }
+        })
+    }
+
+    /// Fetch and extract the recipe, then report what a full import would
+    /// do without calling any LLM
+    ///
+    /// The report says which extractor matched, what was extracted, which
+    /// provider and model would convert it, and roughly how many tokens that
+    /// would take and cost. Useful for debugging failed imports. Image
+    /// sources are not supported, since reading them needs OCR.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use cooklang_import::RecipeImporter;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let report = RecipeImporter::builder()
+    ///     .url("https://example.com/recipe")
+    ///     .dry_run()
+    ///     .await?;
+    /// println!("Extractor: {:?}", report.extractor);
+    /// println!("Estimated tokens: {}", report.estimated_input_tokens);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn dry_run(mut self) -> Result<ImportReport, ImportError> {
+        self.offline |= load_config().is_ok_and(|config| config.offline);
+        let source = self.source.clone().ok_or_else(|| {
+            ImportError::BuilderError(
+                "No input source specified. Use .url(), .text(), or .image_path()".to_string(),
+            )
+        })?;
+        validate_source(&source)?;
+        self.validate_source_url()?;
+
+        if let Some(proxy) = &self.proxy {
+            crate::http::validate_proxy(proxy).map_err(ImportError::BuilderError)?;
+        }
+
+        let timeouts = self.timeouts(load_config().ok().as_ref());
+        let mut page_url = None;
+        let (components, extractor, dropped_fields) = match source {
+            InputSource::Url(url) => {
+                self.require_network(format!("fetching {}", url))?;
+                let extraction = crate::pipelines::url::extract(
+                    &url,
+                    &timeouts,
+                    self.proxy.as_deref(),
+                    self.cache().as_ref(),
+                    Llm::Off,
+                )
+                .await
+                .map_err(ImportError::from_fetch)?;
+                page_url = extraction.url;
+                (
+                    extraction.components,
+                    extraction.extractor.map(str::to_string),
+                    extraction.dropped_fields,
+                )
+            }
+            InputSource::Text {
+                content,
+                extract: true,
+            } => (
+                RecipeComponents {
+                    text: content,
+                    ..RecipeComponents::default()
+                },
+                None,
+                Vec::new(),
+            ),
+            InputSource::Text {
+                content,
+                extract: false,
+            } => (
+                crate::pipelines::text::process(&content, false, &timeouts, None)
+                    .await
+                    .map_err(|e| ImportError::from_pipeline(e, ImportError::BuilderError))?,
+                Some("text".to_string()),
+                Vec::new(),
+            ),
+            InputSource::Email(eml) => {
+                let extraction = crate::pipelines::email::extract(&eml, Llm::Off)
+                    .await
+                    .map_err(|e| ImportError::from_pipeline(e, ImportError::BuilderError))?;
+                (
+                    extraction.components,
+                    extraction.extractor.map(str::to_string),
+                    extraction.dropped_fields,
+                )
+            }
+            InputSource::Markdown(markdown) => {
+                match crate::pipelines::markdown::process(&markdown) {
+                    Ok(components) => (components, Some("markdown".to_string()), Vec::new()),
+                    Err(_) => (
+                        RecipeComponents {
+                            text: markdown,
+                            ..RecipeComponents::default()
+                        },
+                        None,
+                        Vec::new(),
+                    ),
+                }
+            }
+            source @ (InputSource::Html(_) | InputSource::Mhtml(_)) => {
+                let page = saved_page(&source)?;
+                let extraction = crate::pipelines::html::extract(
+                    &page,
+                    self.source_url.as_deref(),
+                    false,
+                    Llm::Off,
+                )
+                .await
+                .map_err(|e| ImportError::from_pipeline(e, ImportError::ExtractionError))?
+                .remove(0);
+                page_url = extraction.url;
+                (
+                    extraction.components,
+                    extraction.extractor.map(str::to_string),
+                    extraction.dropped_fields,
+                )
+            }
+            InputSource::Images(_) => {
+                return Err(ImportError::BuilderError(
+                    "Dry runs are not supported for image sources".to_string(),
+                ))
+            }
+            InputSource::Audio(_) => {
+                return Err(ImportError::BuilderError(
+                    "Dry runs are not supported for audio sources".to_string(),
+                ))
+            }
+        };
+        let components =
+            crate::stages::normalize(self.with_unknown_fields(components, &dropped_fields));
+
+        // The text extractor sends the text (cut down to its budget) and
+        // returns about as much
+        let mut input_tokens = 0;
+        let mut output_tokens = 0;
+        if extractor.is_none() {
+            let text = crate::url_to_text::text::select_recipe_text(
+                &components.text,
+                crate::url_to_text::text::max_input_tokens(),
+            );
+            input_tokens += converters::estimate_tokens(&text);
+            output_tokens += converters::estimate_tokens(&text);
+        }
+
+        let providers = match self.mode {
+            OutputMode::Cooklang => self.planned_providers(),
+            OutputMode::Recipe => Vec::new(),
+        };
+        let model = providers
+            .first()
+            .map(|name| self.build_provider_config(name, true).model)
+            .unwrap_or_default();
+        if !providers.is_empty() {
+            let structured = self.structured_output
+                || load_config().is_ok_and(|c| c.converters.structured_output);
+            let language = self.target_language.as_deref();
+            let prompt = if structured {
+                converters::inject_structured_recipe_in(&components.text, language)
+            } else {
+                self.conversion_messages(&providers[0], &components.text)
+            };
+            // Cooklang output is about as long as the recipe text it marks up
+            input_tokens += converters::estimate_tokens(&prompt);
+            output_tokens += converters::estimate_tokens(&components.text);
+        }
+
+        let estimated_cost_usd = match providers.first() {
+            Some(provider) => {
+                converters::estimate_cost(provider, &model, input_tokens, output_tokens)
+            }
+            None => Some(0.0),
+        };
+
+        Ok(ImportReport {
+            extractor,
+            components,
+            providers,
+            model,
+            estimated_input_tokens: input_tokens,
+            estimated_output_tokens: output_tokens,
+            estimated_cost_usd,
+            dropped_fields,
+            url: page_url,
+        })
+    }
+
+    /// Per-stage timeouts from config, with `llm_timeout` overriding the
+    /// LLM/OCR stages and `timeout` capping all of them
+    fn timeouts(&self, config: Option<&crate::config::AiConfig>) -> Timeouts {
+        let mut timeouts = Timeouts::resolve(config, self.timeout);
+        if let Some(llm_timeout) = self.llm_timeout {
+            timeouts.ocr = llm_timeout;
+            timeouts.extraction = llm_timeout;
+            timeouts.conversion = llm_timeout;
+        }
+        match self.timeout {
+            Some(ceiling) => timeouts.capped(ceiling),
+            None => timeouts,
+        }
+    }
+
+    /// Convert RecipeComponents to Cooklang (without frontmatter) using the
+    /// configured converter and post-processing
+    pub(crate) async fn convert(
+        &self,
+        components: &RecipeComponents,
+    ) -> Result<ConversionResult, ImportError> {
+        // Get converter configuration; offline, a provider that needs the
+        // network is the error to report, not its missing API key
+        let converter = match self.get_converter().await {
+            Ok(converter) => converter,
+            Err(e) => {
+                if let Err(offline) = self.require_local_providers() {
+                    return self.convert_by_rules(components, offline);
+                }
+                return Err(e);
+            }
+        };
+
+        let converters_config = load_config().map(|c| c.converters).unwrap_or_default();
+        let structured = self.structured_output || converters_config.structured_output;
+
+        // Listed equipment the model left untagged is tagged afterwards
+        let listed_equipment = equipment::listed(&components.text);
+        let cache = self.cache();
+        let language = self.target_language.as_deref();
+        let prompt = if structured {
+            converters::inject_structured_recipe_in(&components.text, language)
+        } else {
+            self.conversion_messages(converter.name(), &components.text)
+        };
+        // The prompt without the recipe, for provenance
+        let prompt_hash = crate::cache::hash(&[&if structured {
+            converters::inject_structured_recipe_in("", language)
+        } else {
+            self.conversion_messages(converter.name(), "")
+        }]);
+        let model = self.cache_model(converter.as_ref());
+        let cache_key = [converter.name(), model.as_str(), prompt.as_str()];
+        if let Some(cached) = cache.as_ref().and_then(|c| c.get_conversion(&cache_key)) {
+            let cached = converters::by_provider(cached, converter.name());
+            return Ok(self.scored(
+                components,
+                self.post_process(
+                    equipment::ensure_cookware(&cached.content, &listed_equipment),
+                    &converters_config,
+                )?,
+                ConversionMetadata {
+                    prompt_hash: Some(prompt_hash),
+                    ..cached.metadata
+                },
+            ));
+        }
+
+        if let Err(offline) = self.require_local_providers() {
+            return self.convert_by_rules(components, offline);
+        }
+
+        // Convert the text (ingredients + instructions) to Cooklang, retrying
+        // when the model summarized instead of converting
+        let mut attempt = 0;
+        let conversion_result = loop {
+            let result = if structured {
+                converter.convert_structured(&components.text).await
+            } else {
+                converter.convert(&components.text).await
+            }
+            .map_err(|e| ImportError::from_pipeline(e, ImportError::ConversionError))?;
+            let result = converters::by_provider(result, converter.name());
+
+            match converters::check_completeness(&components.text, &result.content) {
+                Ok(()) => {
+                    if let Some(cache) = &cache {
+                        cache.put_conversion(&cache_key, &result);
+                    }
+                    break result;
+                }
+                Err(reason) if attempt < converters_config.suspicious_output_retries => {
+                    attempt += 1;
+                    warn!("Conversion looks incomplete ({}), retrying", reason);
+                }
+                Err(reason) => {
+                    warn!("Conversion looks incomplete: {}", reason);
+                    break result;
+                }
+            }
+        };
+
+        Ok(self.scored(
+            components,
+            self.post_process(
+                equipment::ensure_cookware(&conversion_result.content, &listed_equipment),
+                &converters_config,
+            )?,
+            ConversionMetadata {
+                prompt_hash: Some(prompt_hash),
+                ..conversion_result.metadata
+            },
+        ))
+    }
+
+    /// The final conversion of `components`, with its confidence score
+    /// Offline with no local model, convert the way Markdown is, by rules:
+    /// each ingredient line is marked up where the steps first name it.
+    /// When a provider was chosen by name, or the recipe is to be
+    /// translated, `offline_error` is returned instead.
+    fn convert_by_rules(
+        &self,
+        components: &RecipeComponents,
+        offline_error: ImportError,
+    ) -> Result<ConversionResult, ImportError> {
+        if self.provider.is_some()
+            || self.providers.is_some()
+            || self.race.is_some()
+            || self.target_language.is_some()
+        {
+            return Err(offline_error);
+        }
+        info!("No local model to convert with offline; converting by rules");
+        let converters_config = load_config().map(|c| c.converters).unwrap_or_default();
+        let content = self.post_process(
+            crate::pipelines::markdown::text_to_cooklang(&components.text),
+            &converters_config,
+        )?;
+        Ok(self.scored(components, content, ConversionMetadata::default()))
+    }
+
+    fn scored(
+        &self,
+        components: &RecipeComponents,
+        content: String,
+        mut metadata: ConversionMetadata,
+    ) -> ConversionResult {
+        // Translated ingredient names can't be matched against the source
+        let source = match self.target_language {
+            Some(_) => "",
+            None => components.text.as_str(),
+        };
+        metadata.confidence = Some(converters::score_conversion(source, &content));
+        if self.check_ingredients && !source.is_empty() {
+            metadata.ingredient_check = Some(converters::check_ingredients(source, &content));
+        }
+        ConversionResult { content, metadata }
+    }
+
+    /// The on-disk cache, unless disabled by `no_cache` or config
+    fn cache(&self) -> Option<Cache> {
+        if self.no_cache {
+            None
+        } else {
+            Cache::from_config()
+        }
+    }
+
+    /// Add unknown fields to the metadata under an `x-` prefix when
+    /// `keep_unknown_fields` is set
+    fn with_unknown_fields(
+        &self,
+        mut components: RecipeComponents,
+        dropped_fields: &[DroppedField],
+    ) -> RecipeComponents {
+        if !self.keep_unknown_fields {
+            return components;
+        }
+        let entries: Vec<(String, String)> = dropped_fields
+            .iter()
+            .filter(|field| field.reason == DropReason::Unknown && !field.value.trim().is_empty())
+            .map(|field| (format!("x-{}", field.key), field.value.clone()))
+            .collect();
+        if entries.is_empty() {
+            return components;
+        }
+        if !components.metadata.is_empty() && !components.metadata.ends_with('\n') {
+            components.metadata.push('\n');
+        }
+        components
+            .metadata
+            .push_str(&crate::pipelines::metadata_to_yaml(&entries));
+        components
+    }
+
+    /// Record the recipe's original language in its metadata when it is
+    /// being translated
+    fn with_source_language(&self, mut components: RecipeComponents) -> RecipeComponents {
+        if self.target_language.is_none() {
+            return components;
+        }
+        if let Some(language) = converters::detected_language(&components.text) {
+            if !components.metadata.is_empty() && !components.metadata.ends_with('\n') {
+                components.metadata.push('\n');
+            }
+            components
+                .metadata
+                .push_str(&crate::pipelines::metadata_to_yaml(&[(
+                    "source language".to_string(),
+                    language,
+                )]));
+        }
+        components
+    }
+
+    /// Add the `import:` provenance block to the metadata when
+    /// `provenance` is set
+    fn with_provenance(
+        &self,
+        mut components: RecipeComponents,
+        extractor: Option<&str>,
+        conversion: Option<&ConversionMetadata>,
+    ) -> RecipeComponents {
+        let enabled =
+            self.provenance || load_config().is_ok_and(|config| config.frontmatter.provenance);
+        if !enabled {
+            return components;
+        }
+        let provenance = Provenance {
+            source: match &self.source {
+                Some(InputSource::Url(url)) => Some(url.clone()),
+                Some(InputSource::Images(images)) => {
+                    Some(crate::pipelines::image::source_label(images))
+                }
+                Some(InputSource::Audio(path)) => Some(path.clone()),
+                Some(InputSource::Html(_) | InputSource::Mhtml(_)) => self.source_url.clone(),
+                _ => None,
+            },
+            extractor: extractor.map(str::to_string),
+            provider: conversion.and_then(|c| c.provider.clone()),
+            model: conversion.and_then(|c| c.model_version.clone()),
+            prompt: conversion.and_then(|c| c.prompt_hash.clone()),
+            ..Provenance::now()
+        };
+        if !components.metadata.is_empty() && !components.metadata.ends_with('\n') {
+            components.metadata.push('\n');
+        }
+        components.metadata.push_str(&provenance.to_yaml());
+        components
+    }
+
+    /// Fail with [`ImportError::OfflineError`] in offline mode
+    /// Reject a [`source_url`](Self::source_url) that isn't an http(s) page
+    fn validate_source_url(&self) -> Result<(), ImportError> {
+        match &self.source_url {
+            Some(url) => {
+                crate::url_to_text::normalize::validate_url(url).map_err(ImportError::BuilderError)
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn require_network(&self, what: impl Into<String>) -> Result<(), ImportError> {
+        if self.offline {
+            Err(ImportError::OfflineError(what.into()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// In offline mode, fail unless every provider the conversion could use
+    /// is an Ollama server on this machine
+    fn require_local_providers(&self) -> Result<(), ImportError> {
+        if !self.offline {
+            return Ok(());
+        }
+        for name in self.planned_providers() {
+            let config = self.build_provider_config(&name, true);
+            let base_url = config
+                .base_url
+                .as_deref()
+                .unwrap_or("http://localhost:11434");
+            let local = match name.as_str() {
+                "ollama" => crate::http::is_loopback(base_url),
+                "mock" => cfg!(feature = "mock"),
+                _ => false,
+            };
+            if !local {
+                return self.require_network(format!("the {} provider", name));
+            }
+        }
+        Ok(())
+    }
+
+    /// In offline mode, fail unless OCR runs on this machine: Tesseract, ONNX
+    /// models or a local Ollama server
+    fn require_local_ocr(&self) -> Result<(), ImportError> {
+        if !self.offline {
+            return Ok(());
+        }
+        let ocr = load_config().map(|c| c.ocr).unwrap_or_default();
+        let base_url = ocr.base_url.as_deref().unwrap_or("http://localhost:11434");
+        match ocr.backend {
+            OcrBackend::Google => self.require_network("OCR with Google Vision"),
+            OcrBackend::Ollama if !crate::http::is_loopback(base_url) => {
+                self.require_network(format!("OCR at {}", base_url))
+            }
+            OcrBackend::Ollama | OcrBackend::Tesseract | OcrBackend::Onnx => Ok(()),
+        }
+    }
+
+    /// Offline, transcription must run on a local whisper.cpp build or an
+    /// API server on this machine
+    fn require_local_transcription(&self) -> Result<(), ImportError> {
+        if !self.offline {
+            return Ok(());
+        }
+        let transcription = load_config().map(|c| c.transcription).unwrap_or_default();
+        match transcription.backend {
+            TranscriptionBackend::OpenAI => match transcription.base_url.as_deref() {
+                Some(base_url) if crate::http::is_loopback(base_url) => Ok(()),
+                Some(base_url) => self.require_network(format!("transcription at {}", base_url)),
+                None => self.require_network("transcription with the Whisper API"),
+            },
+            TranscriptionBackend::WhisperCpp => Ok(()),
+        }
+    }
+
+    /// The conversion prompt `provider` sends, for cache keys and estimates;
+    /// an unreadable template fails when the converter is created instead
+    fn prompt(&self, provider: &str) -> converters::Prompt {
+        let config = self.build_provider_config(provider, true);
+        converters::Prompt::from_config(&config)
+            .unwrap_or_else(|_| converters::Prompt::in_language(config.target_language.as_deref()))
+    }
+
+    /// Everything `provider` is sent to convert `text`, few-shot examples
+    /// included, for cache keys and token estimates
+    fn conversion_messages(&self, provider: &str, text: &str) -> String {
+        serde_json::Value::from(self.prompt(provider).messages(text)).to_string()
+    }
+
+    /// Model name for conversion cache keys, so switching models misses the cache
+    fn cache_model(&self, converter: &dyn Converter) -> String {
+        self.build_provider_config(converter.name(), true).model
+    }
+
+    /// Deterministic clean-up applied to every converter response
+    fn post_process(
+        &self,
+        content: String,
+        config: &ConvertersConfig,
+    ) -> Result<String, ImportError> {
+        let content = converters::tag_timers(&content);
+        let content = match self
+            .ingredient_aliases
+            .as_ref()
+            .or(config.ingredient_aliases.as_ref())
+        {
+            Some(path) => {
+                let aliases = converters::IngredientAliases::load(path).map_err(|e| {
+                    ImportError::ConfigError(config::ConfigError::Message(format!(
+                        "Failed to read ingredient aliases {}: {}",
+                        path, e
+                    )))
+                })?;
+                converters::canonicalize_ingredients(&content, &aliases)
+            }
+            None => content,
+        };
+        let content = if self.merge_duplicates
+            || config.duplicate_ingredients == DuplicateIngredients::Merge
+        {
+            converters::merge_duplicate_ingredients(&content)
+        } else {
+            content
+        };
+        let content = match self.scale {
+            Some(factor) => converters::scale_recipe(&content, factor),
+            None => content,
+        };
+        let content = match self.units {
+            Some(system) => converters::convert_quantities(&content, system),
+            None => content,
+        };
+        let both = self.both_temperatures || config.oven_temperatures == OvenTemperatures::Both;
+        let content = converters::normalize_temperatures(&content, self.units, both);
+        let pantry = match &self.pantry {
+            Some(items) => items.clone(),
+            None => {
+                let mut items = config.pantry.items.clone();
+                if let Some(path) = &config.pantry.file {
+                    let text = std::fs::read_to_string(path).map_err(|e| {
+                        ImportError::ConfigError(config::ConfigError::Message(format!(
+                            "Failed to read pantry file {}: {}",
+                            path, e
+                        )))
+                    })?;
+                    items.extend(converters::parse_pantry(&text));
+                }
+                items
+            }
+        };
+        Ok(converters::mark_pantry_ingredients(
+            &content,
+            &pantry,
+            config.pantry.mark,
+            config.pantry.note.as_deref().unwrap_or("pantry"),
+        ))
+    }
+
+    /// Convert a Markdown recipe to Cooklang without an LLM
+    fn convert_markdown(&self, markdown: &str) -> Result<ImportResult, ImportError> {
+        let components = crate::stages::normalize(crate::pipelines::markdown::process(markdown)?);
+        let components = self.with_provenance(components, Some("markdown"), None);
+        let converters_config = load_config().map(|c| c.converters).unwrap_or_default();
+        let content = self.post_process(
+            crate::pipelines::markdown::to_cooklang(markdown)?,
+            &converters_config,
+        )?;
+        Ok(ImportResult::Cooklang {
+            content: crate::stages::render_titled(&components, &content, self.title_style),
+            title: components.name,
+            conversion_metadata: None,
+        })
+    }
+
+    /// Convert images to Cooklang in one step with a multimodal model
+    async fn convert_images_to_cooklang(
+        &self,
+        images: &[ImageSource],
+    ) -> Result<ImportResult, ImportError> {
+        if matches!(self.mode, OutputMode::Recipe) {
+            return Err(ImportError::BuilderError(
+                "vision_extraction produces Cooklang directly and cannot be combined with extract_only"
+                    .to_string(),
+            ));
+        }
+
+        let mut encoded = Vec::with_capacity(images.len());
+        for image in images {
+            encoded.push(
+                crate::images_to_text::encode(image)
+                    .await
+                    .map_err(|e| ImportError::from_pipeline(e, ImportError::ExtractionError))?,
+            );
+        }
+
+        let converter = match self.get_converter().await {
+            Ok(converter) => converter,
+            Err(e) => {
+                self.require_local_providers()?;
+                return Err(e);
+            }
+        };
+        let cache = self.cache();
+        let model = self.cache_model(converter.as_ref());
+        let prompt = self.prompt(converter.name()).image();
+        let mut cache_key = vec![converter.name(), model.as_str(), prompt.as_str()];
+        cache_key.extend(encoded.iter().map(|image| image.data.as_str()));
+
+        let conversion_result = match cache.as_ref().and_then(|c| c.get_conversion(&cache_key)) {
+            Some(cached) => converters::by_provider(cached, converter.name()),
+            None => {
+                self.require_local_providers()?;
+                let result = converter
+                    .convert_images(&encoded)
+                    .await
+                    .map_err(|e| ImportError::from_pipeline(e, ImportError::ConversionError))?;
+                let result = converters::by_provider(result, converter.name());
+                if let Some(cache) = &cache {
+                    cache.put_conversion(&cache_key, &result);
+                }
+                result
+            }
+        };
+
+        let converters_config = load_config().map(|c| c.converters).unwrap_or_default();
+        let content = self.post_process(conversion_result.content, &converters_config)?;
+        let mut metadata = conversion_result.metadata;
+        // The model read the photos itself, so there is no source text to
+        // score the output against; unscored results pass `check_quality`
+        metadata.confidence = None;
+        metadata.prompt_hash = Some(crate::cache::hash(&[&prompt]));
+
+        let components = RecipeComponents {
+            text: String::new(),
+            metadata: crate::pipelines::metadata_to_yaml(&[(
+                "source".to_string(),
+                crate::pipelines::image::source_label(images),
+            )]),
+            name: String::new(),
+        };
+        let components = self.with_provenance(components, None, Some(&metadata));
+
+        Ok(ImportResult::Cooklang {
+            content: crate::stages::render(&components, &content),
+            title: String::new(),
+            conversion_metadata: Some(metadata),
+        })
+    }
+
+    /// Get the appropriate converter based on configuration
+    async fn get_converter(&self) -> Result<Box<dyn Converter>, ImportError> {
+        let config = load_config().ok();
+
+        if let Some(race) = self.race_providers(config.as_ref()) {
+            let converters = race
+                .iter()
+                .enumerate()
+                .map(|(i, name)| self.create_converter(name, i == 0))
+                .collect::<Result<Vec<_>, _>>()?;
+            let race = converters::RaceConverter::new(converters)
+                .map_err(|e| ImportError::BuilderError(e.to_string()))?;
+            return Ok(Box::new(race));
+        }
+
+        if let Some(chain) = self.fallback_providers(config.as_ref()) {
+            return self.create_fallback(&chain, config.as_ref());
+        }
+
+        let converter = self.create_converter(&self.single_provider(config.as_ref()), true)?;
+        let retry = config.map(|c| c.fallback).unwrap_or_default();
+        Ok(Box::new(converters::RetryConverter::new(
+            converter,
+            converters::RetryPolicy::from_config(&retry),
+        )))
+    }
+
+    /// LLM text extraction with the provider (or fallback chain or race)
+    /// this builder converts with, and the `extraction` stage timeout.
+    /// Offline, only a local Ollama server is allowed.
+    pub(crate) async fn text_extractor(&self) -> Result<TextExtractor, ImportError> {
+        self.require_local_providers()?;
+        let extraction = RecipeImporterBuilder {
+            provider: self.provider.clone(),
+            providers: self.providers.clone(),
+            race: self.race.clone(),
+            api_key: self.api_key.clone(),
+            model: self.model.clone(),
+            proxy: self.proxy.clone(),
+            offline: self.offline,
+            llm_timeout: Some(self.timeouts(load_config().ok().as_ref()).extraction),
+            ..Default::default()
+        };
+        Ok(TextExtractor::new(extraction.get_converter().await?))
+    }
+
+    /// The builder's provider, else the configured default, else open_ai
+    fn single_provider(&self, config: Option<&crate::config::AiConfig>) -> String {
+        match &self.provider {
+            Some(provider) => provider.as_str().to_string(),
+            None => config
+                .map(|c| c.default_provider.clone())
+                .unwrap_or_else(|| "open_ai".to_string()),
+        }
+    }
+
+    /// Providers `get_converter` would use, without creating them
+    fn planned_providers(&self) -> Vec<String> {
+        let config = load_config().ok();
+        if let Some(race) = self.race_providers(config.as_ref()) {
+            return race;
+        }
+        if let Some(chain) = self.fallback_providers(config.as_ref()) {
+            return chain;
+        }
+        vec![self.single_provider(config.as_ref())]
+    }
+
+    /// Providers for the fallback chain, from the builder or (if no provider
+    /// was chosen) from an enabled `[fallback]` config section
+    fn fallback_providers(&self, config: Option<&crate::config::AiConfig>) -> Option<Vec<String>> {
+        if let Some(providers) = &self.providers {
+            return Some(providers.iter().map(|p| p.as_str().to_string()).collect());
+        }
+        if self.provider.is_some() {
+            return None;
+        }
+        config
+            .map(|c| &c.fallback)
+            .filter(|fallback| fallback.enabled && !fallback.order.is_empty())
+            .map(|fallback| fallback.order.clone())
+    }
+
+    /// Build a fallback chain, skipping providers that can't be created
+    fn create_fallback(
+        &self,
+        chain: &[String],
+        config: Option<&crate::config::AiConfig>,
+    ) -> Result<Box<dyn Converter>, ImportError> {
+        let converters: Vec<_> = chain
+            .iter()
+            .enumerate()
+            .filter_map(|(i, name)| match self.create_converter(name, i == 0) {
+                Ok(converter) => Some(converter),
+                Err(e) => {
+                    warn!("Skipping provider '{}' in fallback chain: {}", name, e);
+                    None
+                }
+            })
+            .collect();
+
+        let retry = config.map(|c| c.fallback.clone()).unwrap_or_default();
+        let converter = converters::FallbackConverter::new(
+            converters,
+            retry.retry_attempts,
+            Duration::from_millis(retry.retry_delay_ms),
+        )
+        .map(|fallback| fallback.max_retry_wait(Duration::from_millis(retry.max_retry_wait_ms)))
+        .map_err(|e| {
+            ImportError::ConfigError(config::ConfigError::Message(format!(
+                "No provider in fallback chain is available ({}). Check API keys and configuration.",
+                e
+            )))
+        })?;
+        Ok(Box::new(converter))
+    }
+
+    /// Providers to race, from the builder or (if no provider was chosen) from config
+    fn race_providers(&self, config: Option<&crate::config::AiConfig>) -> Option<Vec<String>> {
+        if let Some(race) = &self.race {
+            return Some(race.iter().map(|p| p.as_str().to_string()).collect());
+        }
+        if self.provider.is_some() {
+            return None;
+        }
+        config
+            .map(|c| &c.race)
+            .filter(|race| race.enabled)
+            .map(|race| race.providers.clone())
+    }
+
+    /// Create a single converter, optionally applying builder overrides
+    fn create_converter(
+        &self,
+        provider_name: &str,
+        apply_overrides: bool,
+    ) -> Result<Box<dyn Converter>, ImportError> {
+        let provider_config = self.build_provider_config(provider_name, apply_overrides);
+
+        let converter =
+            converters::create_converter(provider_name, &provider_config).ok_or_else(|| {
+                ImportError::ConfigError(config::ConfigError::Message(format!(
+                    "Failed to create converter '{}'. Check API key and configuration.",
+                    provider_name
+                )))
+            })?;
+        let limit = converters::RateLimit {
+            requests_per_minute: provider_config.requests_per_minute,
+            tokens_per_minute: provider_config.tokens_per_minute,
+        };
+        Ok(match converters::rate_limiter(provider_name, limit) {
+            Some(limiter) => {
+                let overhead =
+                    converters::estimate_tokens(&self.conversion_messages(provider_name, ""))
+                        + provider_config.max_tokens as u64;
+                Box::new(converters::RateLimitedConverter::new(
+                    converter, limiter, overhead,
+                ))
+            }
+            None => converter,
+        })
+    }
+
+    /// Build provider configuration from builder settings and environment
+    fn build_provider_config(&self, provider_name: &str, apply_overrides: bool) -> ProviderConfig {
+        // Try to load config from file first
+        let config = load_config().ok();
+        let base_config = config
+            .as_ref()
             .and_then(|c| c.providers.get(provider_name).cloned());
 
+        // A provider's own timeout beats the configured conversion stage,
+        // but not the builder's `llm_timeout` or `timeout` ceiling
+        let conversion = self.timeouts(config.as_ref()).conversion;
+        let timeout = match base_config.as_ref().and_then(|c| c.timeout) {
+            Some(secs) if self.llm_timeout.is_none() => {
+                let provider = Duration::from_secs(secs);
+                self.timeout
+                    .map_or(provider, |ceiling| provider.min(ceiling))
+            }
+            _ => conversion,
+        };
+
         // Build config with overrides from builder
         ProviderConfig {
             enabled: true,
-            model: self.model.clone().unwrap_or_else(|| {
-                base_config
-                    .as_ref()
-                    .map(|c| c.model.clone())
-                    .unwrap_or_else(|| default_model_for_provider(provider_name).to_string())
-            }),
-            temperature: base_config.as_ref().map(|c| c.temperature).unwrap_or(0.7),
+            model: self
+                .model
+                .clone()
+                .filter(|_| apply_overrides)
+                .unwrap_or_else(|| {
+                    base_config
+                        .as_ref()
+                        .map(|c| c.model.clone())
+                        .unwrap_or_else(|| default_model_for_provider(provider_name).to_string())
+                }),
+            // Offline runs should be reproducible
+            temperature: if self.offline {
+                0.0
+            } else {
+                base_config.as_ref().map(|c| c.temperature).unwrap_or(0.7)
+            },
             max_tokens: base_config.as_ref().map(|c| c.max_tokens).unwrap_or(4000),
             api_key: self
                 .api_key
                 .clone()
+                .filter(|_| apply_overrides)
                 .or_else(|| base_config.as_ref().and_then(|c| c.api_key.clone())),
+            api_key_cmd: base_config.as_ref().and_then(|c| c.api_key_cmd.clone()),
+            api_key_secret: base_config.as_ref().and_then(|c| c.api_key_secret),
             base_url: base_config.as_ref().and_then(|c| c.base_url.clone()),
             endpoint: base_config.as_ref().and_then(|c| c.endpoint.clone()),
             deployment_name: base_config.as_ref().and_then(|c| c.deployment_name.clone()),
             api_version: base_config.as_ref().and_then(|c| c.api_version.clone()),
             project_id: base_config.as_ref().and_then(|c| c.project_id.clone()),
+            auto_pull: base_config.as_ref().is_some_and(|c| c.auto_pull),
+            timeout: Some(timeout.as_secs().max(1)),
+            proxy: self
+                .proxy
+                .clone()
+                .or_else(|| base_config.as_ref().and_then(|c| c.proxy.clone())),
+            requests_per_minute: base_config.as_ref().and_then(|c| c.requests_per_minute),
+            tokens_per_minute: base_config.as_ref().and_then(|c| c.tokens_per_minute),
+            target_language: self.target_language.clone(),
+            units: self.units,
+            prompt: config
+                .as_ref()
+                .map(|c| c.converters.prompt.for_provider(provider_name))
+                .unwrap_or_default(),
         }
     }
 }
 
 /// Get default model for a given provider
-fn default_model_for_provider(provider: &str) -> &'static str {
+pub(crate) fn default_model_for_provider(provider: &str) -> &'static str {
     match provider {
         "open_ai" => "gpt-4o-mini",
         "anthropic" => "claude-haiku-4-5",
         "google" => "gemini-1.5-flash",
         "azure_openai" => "gpt-4",
         "ollama" => "llama2",
+        "mistral" => "mistral-small-latest",
+        "groq" => "llama-3.3-70b-versatile",
+        "mock" => "mock",
         _ => "gpt-4o-mini",
     }
 }
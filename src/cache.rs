@@ -0,0 +1,226 @@
+use crate::config::load_config;
+use crate::converters::{ConversionMetadata, ConversionResult};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tracing::debug;
+
+/// On-disk cache for fetched pages and LLM conversions
+///
+/// Pages are keyed by a hash of their URL, conversions by a hash of the
+/// provider, model and full prompt, so editing the prompt or switching
+/// models never returns a stale result. Entries older than the TTL are
+/// ignored. Read and write failures are logged and treated as misses.
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+/// What gets stored for a conversion; token counts are dropped because a
+/// cache hit costs nothing
+#[derive(Serialize, Deserialize)]
+struct CachedConversion {
+    content: String,
+    model_version: Option<String>,
+    #[serde(default)]
+    provider: Option<String>,
+}
+
+impl Cache {
+    pub fn new(dir: impl Into<PathBuf>, ttl: Duration) -> Self {
+        Cache {
+            dir: dir.into(),
+            ttl,
+        }
+    }
+
+    /// Cache from the `[cache]` config section, or `None` if it is disabled
+    pub fn from_config() -> Option<Self> {
+        let config = load_config().map(|c| c.cache).unwrap_or_default();
+        if !config.enabled {
+            return None;
+        }
+        let dir = config.dir.map(PathBuf::from).unwrap_or_else(default_dir);
+        Some(Cache::new(dir, Duration::from_secs(config.ttl)))
+    }
+
+    /// A cached page for `url`, unless the URL points at this machine
+    pub(crate) fn get_page(&self, url: &str) -> Option<String> {
+        if is_local(url) {
+            return None;
+        }
+        let html = self.read(&self.path("pages", &[url], "html"))?;
+        debug!("Using cached page for {}", url);
+        Some(html)
+    }
+
+    pub(crate) fn put_page(&self, url: &str, html: &str) {
+        if !is_local(url) {
+            self.write(&self.path("pages", &[url], "html"), html);
+        }
+    }
+
+    /// A cached conversion for the given key parts (provider, model, prompt)
+    pub(crate) fn get_conversion(&self, key: &[&str]) -> Option<ConversionResult> {
+        let json = self.read(&self.path("conversions", key, "json"))?;
+        let cached: CachedConversion = serde_json::from_str(&json).ok()?;
+        debug!("Using cached conversion");
+        Some(ConversionResult {
+            content: cached.content,
+            metadata: ConversionMetadata {
+                model_version: cached.model_version,
+                provider: cached.provider,
+                ..ConversionMetadata::default()
+            },
+        })
+    }
+
+    pub(crate) fn put_conversion(&self, key: &[&str], result: &ConversionResult) {
+        let cached = CachedConversion {
+            content: result.content.clone(),
+            model_version: result.metadata.model_version.clone(),
+            provider: result.metadata.provider.clone(),
+        };
+        if let Ok(json) = serde_json::to_string(&cached) {
+            self.write(&self.path("conversions", key, "json"), &json);
+        }
+    }
+
+    fn path(&self, kind: &str, key: &[&str], extension: &str) -> PathBuf {
+        self.dir
+            .join(kind)
+            .join(format!("{}.{}", hash(key), extension))
+    }
+
+    fn read(&self, path: &Path) -> Option<String> {
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+        let age = SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default();
+        if age > self.ttl {
+            debug!("Cache entry {} expired", path.display());
+            return None;
+        }
+        std::fs::read_to_string(path).ok()
+    }
+
+    fn write(&self, path: &Path, contents: &str) {
+        let result = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(path, contents));
+        if let Err(e) = result {
+            debug!("Failed to write cache entry {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// `$XDG_CACHE_HOME/cooklang-import`, `~/.cache/cooklang-import`, or the temp dir
+fn default_dir() -> PathBuf {
+    std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .unwrap_or_else(std::env::temp_dir)
+        .join("cooklang-import")
+}
+
+/// Local pages (dev servers, test fixtures) change freely and are cheap to fetch
+fn is_local(url: &str) -> bool {
+    let host = url
+        .split("://")
+        .nth(1)
+        .unwrap_or(url)
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default();
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) if !port.is_empty() && port.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => host,
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "[::1]")
+}
+
+/// FNV-1a over the key parts; stable across builds, unlike `DefaultHasher`
+pub(crate) fn hash(parts: &[&str]) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for part in parts {
+        for byte in part.bytes().chain(std::iter::once(0)) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_cache(name: &str, ttl: Duration) -> Cache {
+        let dir = std::env::temp_dir().join(format!(
+            "cooklang-import-cache-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        Cache::new(dir, ttl)
+    }
+
+    #[test]
+    fn test_page_round_trip() {
+        let cache = temp_cache("page", Duration::from_secs(60));
+        assert!(cache.get_page("https://example.com/soup").is_none());
+
+        cache.put_page("https://example.com/soup", "<html>soup</html>");
+        assert_eq!(
+            cache.get_page("https://example.com/soup").as_deref(),
+            Some("<html>soup</html>")
+        );
+        assert!(cache.get_page("https://example.com/stew").is_none());
+    }
+
+    #[test]
+    fn test_conversion_round_trip_and_expiry() {
+        let cache = temp_cache("conversion", Duration::from_secs(60));
+        let result = ConversionResult {
+            content: "Fry @eggs{2}.".to_string(),
+            metadata: ConversionMetadata {
+                model_version: Some("gpt-4o-mini".to_string()),
+                ..ConversionMetadata::default()
+            },
+        };
+        cache.put_conversion(&["open_ai", "gpt-4o-mini", "prompt"], &result);
+
+        let cached = cache
+            .get_conversion(&["open_ai", "gpt-4o-mini", "prompt"])
+            .unwrap();
+        assert_eq!(cached.content, "Fry @eggs{2}.");
+        assert_eq!(
+            cached.metadata.model_version.as_deref(),
+            Some("gpt-4o-mini")
+        );
+        assert!(cache
+            .get_conversion(&["open_ai", "gpt-4.1", "prompt"])
+            .is_none());
+
+        let expired = Cache::new(cache.dir.clone(), Duration::ZERO);
+        std::thread::sleep(Duration::from_millis(10));
+        assert!(expired
+            .get_conversion(&["open_ai", "gpt-4o-mini", "prompt"])
+            .is_none());
+    }
+
+    #[test]
+    fn test_local_pages_are_not_cached() {
+        assert!(is_local("http://127.0.0.1:1234/recipe"));
+        assert!(is_local("http://localhost/recipe"));
+        assert!(!is_local("https://example.com:8443/recipe"));
+    }
+
+    #[test]
+    fn test_hash_separates_parts() {
+        assert_ne!(hash(&["ab", "c"]), hash(&["a", "bc"]));
+        assert_eq!(hash(&["a"]), hash(&["a"]));
+    }
+}
@@ -0,0 +1,190 @@
+//! Decoding pages and emails that aren't UTF-8
+//!
+//! Older recipe sites and mail clients still send ISO-8859-1, Windows-1252,
+//! ISO-8859-15 or Shift-JIS text. Reading those bytes as UTF-8 turns
+//! "crème" into "cr�me", so the charset is taken from the `Content-Type`
+//! header, a byte-order mark or `<meta charset>`, and sniffed from the bytes
+//! when nothing (or something wrong) is declared.
+
+use encoding_rs::Encoding;
+use tracing::{debug, warn};
+
+/// How much of a page to search for `<meta charset>`, as browsers do
+const META_PRESCAN_BYTES: usize = 1024;
+
+/// Decode `bytes` labelled with `charset`.
+///
+/// Any charset label browsers know is decoded the way they do it, so
+/// ISO-8859-1 and US-ASCII are read as Windows-1252, its superset. Unknown
+/// labels are read as UTF-8 with a warning.
+pub(crate) fn decode(bytes: &[u8], charset: &str) -> String {
+    let label = normalize_label(charset);
+    match Encoding::for_label(label.as_bytes()) {
+        Some(encoding) => encoding.decode_without_bom_handling(bytes).0.into_owned(),
+        None => {
+            warn!("Unknown charset '{}'; reading the text as UTF-8", label);
+            String::from_utf8_lossy(bytes).into_owned()
+        }
+    }
+}
+
+/// Decode a fetched page.
+///
+/// The charset comes from, in order: a UTF-8 byte-order mark, the
+/// `charset` of the `Content-Type` header, a `<meta charset>` (or
+/// `http-equiv`) tag near the top of the page, and finally [`sniff`]. A page
+/// declared as UTF-8 that isn't valid UTF-8 is sniffed as well, since old
+/// sites often label Windows-1252 pages as UTF-8.
+pub(crate) fn decode_page(bytes: &[u8], content_type: Option<&str>) -> String {
+    if let Some(rest) = bytes.strip_prefix(b"\xEF\xBB\xBF") {
+        return String::from_utf8_lossy(rest).into_owned();
+    }
+
+    let declared = content_type
+        .and_then(charset_param)
+        .or_else(|| meta_charset(bytes));
+    let charset = match declared {
+        Some(charset) if !is_utf8_label(&charset) || std::str::from_utf8(bytes).is_ok() => charset,
+        declared => {
+            let sniffed = sniff(bytes);
+            debug!(?declared, sniffed, "Sniffed page charset");
+            sniffed.to_string()
+        }
+    };
+    decode(bytes, &charset)
+}
+
+/// Guess the charset of undeclared text: "utf-8" when the bytes are valid
+/// UTF-8, "shift_jis" when most non-ASCII bytes come in two-byte Shift-JIS
+/// sequences, otherwise "windows-1252" (where every byte is a character)
+pub(crate) fn sniff(bytes: &[u8]) -> &'static str {
+    if std::str::from_utf8(bytes).is_ok() {
+        return "utf-8";
+    }
+
+    // In Japanese text, non-ASCII bytes pair up (a lead byte followed by a
+    // high trail byte); in European text, an accented letter stands alone
+    // between ASCII letters
+    let (mut pairs, mut singles) = (0usize, 0usize);
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = bytes[i];
+        if byte < 0x80 {
+            i += 1;
+        } else if is_shift_jis_lead(byte) && bytes.get(i + 1).is_some_and(|&b| b >= 0x80) {
+            pairs += 1;
+            i += 2;
+        } else {
+            singles += 1;
+            i += 1;
+        }
+    }
+    if pairs >= 4 && pairs > singles * 2 {
+        "shift_jis"
+    } else {
+        "windows-1252"
+    }
+}
+
+fn is_shift_jis_lead(byte: u8) -> bool {
+    matches!(byte, 0x81..=0x9F | 0xE0..=0xFC)
+}
+
+fn is_utf8_label(charset: &str) -> bool {
+    matches!(normalize_label(charset).as_str(), "utf-8" | "utf8")
+}
+
+fn normalize_label(charset: &str) -> String {
+    charset
+        .trim()
+        .trim_matches(|c| c == '"' || c == '\'')
+        .to_lowercase()
+}
+
+/// `charset` parameter of a `Content-Type` value
+fn charset_param(content_type: &str) -> Option<String> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| normalize_label(value))
+            .filter(|value| !value.is_empty())
+    })
+}
+
+/// Charset named by a `<meta charset="...">` or
+/// `<meta http-equiv="Content-Type" content="...; charset=...">` tag in the
+/// first kilobyte of a page
+fn meta_charset(bytes: &[u8]) -> Option<String> {
+    let head = &bytes[..bytes.len().min(META_PRESCAN_BYTES)];
+    let head = String::from_utf8_lossy(head).to_lowercase();
+    head.split("<meta").skip(1).find_map(|tag| {
+        let tag = tag.split('>').next()?;
+        let value = tag.split_once("charset")?.1.trim_start();
+        let value = value.strip_prefix('=')?.trim_start();
+        let value = value.trim_start_matches(['"', '\'']);
+        let end = value
+            .find(|c: char| c == '"' || c == '\'' || c == ';' || c == '/' || c.is_whitespace())
+            .unwrap_or(value.len());
+        Some(value[..end].to_string()).filter(|value| !value.is_empty())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_page_declared_charset() {
+        // "Crème brûlée – 2 œufs" in Windows-1252
+        let page = b"<p>Cr\xE8me br\xFBl\xE9e \x96 2 \x9Cufs</p>";
+        assert_eq!(
+            decode_page(page, Some("text/html; charset=ISO-8859-1")),
+            "<p>Crème brûlée – 2 œufs</p>"
+        );
+
+        let page = b"<html><head><meta http-equiv=\"Content-Type\" content=\"text/html; charset=iso-8859-15\"></head>\xBDufs \xA4";
+        assert!(decode_page(page, Some("text/html")).ends_with("œufs €"));
+
+        let page = b"<meta charset='windows-1252'><p>\x93Quotes\x94</p>";
+        assert_eq!(
+            decode_page(page, None),
+            "<meta charset='windows-1252'><p>“Quotes”</p>"
+        );
+    }
+
+    #[test]
+    fn test_decode_page_sniffs_mislabelled_pages() {
+        assert_eq!(
+            decode_page(b"caf\xE9 cr\xE8me", Some("text/html; charset=utf-8")),
+            "café crème"
+        );
+        assert_eq!(
+            decode_page("café crème".as_bytes(), Some("text/html")),
+            "café crème"
+        );
+        assert_eq!(
+            decode_page(
+                b"\xEF\xBB\xBFcaf\xC3\xA9",
+                Some("text/html; charset=iso-8859-1")
+            ),
+            "café"
+        );
+    }
+
+    #[test]
+    fn test_sniff() {
+        assert_eq!(sniff("crème".as_bytes()), "utf-8");
+        assert_eq!(sniff(b"cr\xE8me fra\xEEche, p\xE2te"), "windows-1252");
+        // "まぜます" (stir) in Shift-JIS
+        assert_eq!(sniff(b"\x82\xdc\x82\xba\x82\xdc\x82\xb7"), "shift_jis");
+    }
+
+    #[test]
+    fn test_decode_shift_jis() {
+        // "卵をまぜます" (stir the eggs) in Shift-JIS
+        let text = b"\x97\x91\x82\xf0\x82\xdc\x82\xba\x82\xdc\x82\xb7";
+        assert_eq!(decode(text, "Shift_JIS"), "卵をまぜます");
+        assert_eq!(decode_page(text, None), "卵をまぜます");
+    }
+}
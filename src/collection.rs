@@ -0,0 +1,132 @@
+//! Saving into a CookCLI recipe collection (`--import-into DIR`)
+//!
+//! A collection is a directory tree of `.cook` files, each recipe's photo
+//! next to it with the same name ("Pancakes.cook", "Pancakes.jpg"), and a
+//! `config/` directory at the top for shared files such as `aisle.conf`.
+//! This module finds those files and keeps an optional Markdown index of the
+//! recipes up to date.
+
+use std::path::{Component, Path, PathBuf};
+
+/// The top of the collection `dir` is in: the nearest directory, `dir`
+/// itself included, that has a `config/` directory. Without one, `dir` is
+/// the top.
+pub fn collection_root(dir: &Path) -> &Path {
+    dir.ancestors()
+        .find(|ancestor| ancestor.join("config").is_dir())
+        .unwrap_or(dir)
+}
+
+/// The collection's `config/aisle.conf`, if it has one
+pub fn aisle_conf(dir: &Path) -> Option<PathBuf> {
+    Some(collection_root(dir).join("config").join("aisle.conf")).filter(|path| path.is_file())
+}
+
+/// Link from `index` to `recipe`: relative when the recipe is in the index's
+/// directory or below, with `/` separators on every platform
+pub fn index_link(index: &Path, recipe: &Path) -> String {
+    let base = index.parent().unwrap_or(Path::new(""));
+    let relative = recipe.strip_prefix(base).unwrap_or(recipe);
+    relative
+        .components()
+        .filter_map(|component| match component {
+            Component::CurDir => None,
+            component => Some(component.as_os_str().to_string_lossy()),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// `index` with an entry for the recipe titled `title` at `link` added.
+///
+/// The index is a Markdown list with one `- [Title](<link>)` line per
+/// recipe; the angle brackets keep links with spaces working. A recipe
+/// already listed under `link` is left as it is, and a new index starts
+/// with a `# Recipes` heading.
+pub fn update_index(index: &str, title: &str, link: &str) -> String {
+    let target = format!("](<{}>)", link);
+    if index.lines().any(|line| line.contains(&target)) {
+        return index.to_string();
+    }
+    let mut output = if index.trim().is_empty() {
+        "# Recipes\n\n".to_string()
+    } else {
+        index.to_string()
+    };
+    if !output.ends_with('\n') {
+        output.push('\n');
+    }
+    let title = title.replace('[', "\\[").replace(']', "\\]");
+    output.push_str(&format!("- [{}](<{}>)\n", title, link));
+    output
+}
+
+/// Add the recipe saved at `recipe` to the index file `index`, creating the
+/// file if needed
+pub fn add_to_index(index: &Path, title: &str, recipe: &Path) -> std::io::Result<()> {
+    let existing = match std::fs::read_to_string(index) {
+        Ok(existing) => existing,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    let updated = update_index(&existing, title, &index_link(index, recipe));
+    if updated != existing {
+        std::fs::write(index, updated)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_index() {
+        let index = update_index("", "Pancakes", "Pancakes.cook");
+        assert_eq!(index, "# Recipes\n\n- [Pancakes](<Pancakes.cook>)\n");
+
+        let index = update_index(&index, "Pad Thai [easy]", "Noodles/Pad Thai.cook");
+        assert_eq!(
+            index,
+            "# Recipes\n\n- [Pancakes](<Pancakes.cook>)\n\
+             - [Pad Thai \\[easy\\]](<Noodles/Pad Thai.cook>)\n"
+        );
+        assert_eq!(update_index(&index, "Pancakes", "Pancakes.cook"), index);
+    }
+
+    #[test]
+    fn test_index_link() {
+        assert_eq!(
+            index_link(
+                Path::new("recipes/README.md"),
+                Path::new("recipes/Desserts/Flan.cook")
+            ),
+            "Desserts/Flan.cook"
+        );
+        assert_eq!(
+            index_link(Path::new("index.md"), Path::new("./Flan.cook")),
+            "Flan.cook"
+        );
+        assert_eq!(
+            index_link(Path::new("notes/index.md"), Path::new("recipes/Flan.cook")),
+            "recipes/Flan.cook"
+        );
+    }
+
+    #[test]
+    fn test_collection_root() {
+        let root = std::env::temp_dir().join(format!("cooklang-collection-{}", std::process::id()));
+        let desserts = root.join("Desserts");
+        std::fs::create_dir_all(root.join("config")).unwrap();
+        std::fs::create_dir_all(&desserts).unwrap();
+        assert_eq!(collection_root(&desserts), root);
+        assert_eq!(aisle_conf(&desserts), None);
+
+        std::fs::write(root.join("config").join("aisle.conf"), "[produce]\n").unwrap();
+        assert_eq!(
+            aisle_conf(&desserts),
+            Some(root.join("config").join("aisle.conf"))
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
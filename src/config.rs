@@ -1,6 +1,8 @@
-use config::{Config, ConfigError, Environment, File};
+use config::{Config, ConfigError, Environment, File, FileFormat};
 use serde::Deserialize;
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
 
 /// Main AI configuration structure
 #[derive(Debug, Deserialize, Clone)]
@@ -14,6 +16,9 @@ pub struct AiConfig {
     /// Fallback configuration for automatic provider switching
     #[serde(default)]
     pub fallback: FallbackConfig,
+    /// Race configuration for sending one conversion to several providers at once
+    #[serde(default)]
+    pub race: RaceConfig,
     /// Extractors configuration
     #[serde(default)]
     pub extractors: ExtractorsConfig,
@@ -23,9 +28,50 @@ pub struct AiConfig {
     /// Page scriber configuration for browser-based fetching
     #[serde(default)]
     pub page_scriber: PageScriberConfig,
+    /// OCR configuration for image imports
+    #[serde(default)]
+    pub ocr: OcrConfig,
+    /// Speech-to-text configuration for audio imports
+    #[serde(default)]
+    pub transcription: TranscriptionConfig,
+    /// Never use the network, as with `--offline`
+    #[serde(default)]
+    pub offline: bool,
+    /// Conversions with a confidence score below this (0.0-1.0) are set
+    /// aside in batch imports, as with `--min-quality`
+    pub min_quality: Option<f32>,
+    /// What happens to conversions below `min_quality`
+    #[serde(default)]
+    pub below_quality: BelowQuality,
     /// Request timeout in seconds
     #[serde(default = "default_timeout")]
     pub timeout: u64,
+    /// Timeout in seconds for LLM and OCR requests (default: 120)
+    pub llm_timeout: Option<u64>,
+    /// Per-stage timeouts, overriding `timeout` and `llm_timeout`
+    #[serde(default)]
+    pub timeouts: TimeoutsConfig,
+    /// Network settings shared by all HTTP clients
+    #[serde(default)]
+    pub http: HttpConfig,
+    /// Headers sent with page and photo fetches
+    #[serde(default)]
+    pub fetch: FetchConfig,
+    /// On-disk cache for fetched pages and LLM conversions
+    #[serde(default)]
+    pub cache: CacheConfig,
+    /// Length caps for frontmatter values
+    #[serde(default)]
+    pub frontmatter: FrontmatterConfig,
+    /// Renames for metadata keys
+    #[serde(default)]
+    pub metadata: MetadataConfig,
+    /// Size and format of saved recipe photos
+    #[serde(default)]
+    pub images: ImagesConfig,
+    /// Imports the daemon runs on a timer
+    #[serde(default)]
+    pub schedule: ScheduleConfig,
 }
 
 /// Configuration for a specific AI provider
@@ -45,6 +91,12 @@ pub struct ProviderConfig {
     // Optional provider-specific fields
     /// API key for authentication (can also be set via environment variable)
     pub api_key: Option<String>,
+    /// Command that prints the API key, e.g. "pass show openai", run when the
+    /// converter is created and `api_key` isn't set
+    pub api_key_cmd: Option<String>,
+    /// OS secret store holding the API key, under service "cooklang-import"
+    /// and the provider's name
+    pub api_key_secret: Option<SecretStore>,
     /// Base URL for API endpoint (for custom or proxy endpoints)
     pub base_url: Option<String>,
     /// Specific endpoint path (for Azure or custom deployments)
@@ -55,6 +107,43 @@ pub struct ProviderConfig {
     pub api_version: Option<String>,
     /// Project ID (Google Cloud specific)
     pub project_id: Option<String>,
+    /// Pull the model when the server doesn't have it yet (Ollama specific)
+    #[serde(default)]
+    pub auto_pull: bool,
+    /// Timeout in seconds for requests to this provider (overrides `llm_timeout`)
+    pub timeout: Option<u64>,
+    /// Proxy URL for requests to this provider (overrides `[http] proxy`)
+    pub proxy: Option<String>,
+    /// Most requests per minute sent to this provider, shared by all
+    /// conversions in the process (0 or unset: no limit)
+    pub requests_per_minute: Option<u32>,
+    /// Most tokens per minute sent to this provider (estimated prompt
+    /// tokens plus `max_tokens`, as providers count them; 0 or unset: no
+    /// limit)
+    pub tokens_per_minute: Option<u32>,
+    /// Language to write the Cooklang in, translating the recipe if needed
+    /// (default: the recipe's own language). Set by the builder, not read
+    /// from `config.toml`.
+    #[serde(skip)]
+    pub target_language: Option<String>,
+    /// Units the recipe is converted to, for the prompt's `{{UNITS}}`. Set
+    /// by the builder.
+    #[serde(skip)]
+    pub units: Option<crate::converters::UnitSystem>,
+    /// Prompt template and style from `[converters.prompt]`. Set by the
+    /// builder, not read from the provider's table.
+    #[serde(skip)]
+    pub prompt: PromptTemplateConfig,
+}
+
+/// Where a provider's API key is stored outside config.toml
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretStore {
+    /// The macOS login keychain (`security find-generic-password`)
+    Keychain,
+    /// The Secret Service on Linux, e.g. GNOME Keyring (`secret-tool lookup`)
+    Libsecret,
 }
 
 /// Configuration for provider fallback and retry behavior
@@ -72,6 +161,10 @@ pub struct FallbackConfig {
     /// Initial delay between retries in milliseconds (uses exponential backoff)
     #[serde(default = "default_retry_delay_ms")]
     pub retry_delay_ms: u64,
+    /// Longest `Retry-After` of a rate-limited provider worth waiting for,
+    /// in milliseconds; longer waits move on to the next provider
+    #[serde(default = "default_max_retry_wait_ms")]
+    pub max_retry_wait_ms: u64,
 }
 
 impl Default for FallbackConfig {
@@ -81,10 +174,25 @@ impl Default for FallbackConfig {
             order: Vec::new(),
             retry_attempts: default_retry_attempts(),
             retry_delay_ms: default_retry_delay_ms(),
+            max_retry_wait_ms: default_max_retry_wait_ms(),
         }
     }
 }
 
+/// Configuration for the provider racing strategy
+///
+/// When enabled, the conversion is sent to all listed providers simultaneously
+/// and the first response that passes the Cooklang syntax check wins.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct RaceConfig {
+    /// Whether racing is enabled
+    #[serde(default)]
+    pub enabled: bool,
+    /// Providers to race (at least two)
+    #[serde(default)]
+    pub providers: Vec<String>,
+}
+
 /// Configuration for recipe extractors
 #[derive(Debug, Clone, Deserialize, Default)]
 pub struct ExtractorsConfig {
@@ -94,6 +202,14 @@ pub struct ExtractorsConfig {
     /// Order in which extractors should be tried
     #[serde(default = "default_extractors")]
     pub order: Vec<String>,
+    /// Site adapter files (TOML selector configs), tried before the
+    /// built-in extractors on the domains they list
+    #[serde(default)]
+    pub adapters: Vec<String>,
+    /// Token budget for text sent to LLM text extraction; longer text is cut
+    /// down to its most recipe-like parts (default 16000)
+    #[serde(default)]
+    pub max_input_tokens: Option<u64>,
 }
 
 /// Configuration for recipe converters
@@ -108,6 +224,125 @@ pub struct ConvertersConfig {
     /// Default converter to use
     #[serde(default)]
     pub default: String,
+    /// Ask the LLM for a JSON recipe and render the Cooklang locally
+    #[serde(default)]
+    pub structured_output: bool,
+    /// Extra attempts when the output looks like a summary rather than a
+    /// full conversion (0 = only log a warning)
+    #[serde(default)]
+    pub suspicious_output_retries: u32,
+    /// How to handle an ingredient declared in several places
+    #[serde(default)]
+    pub duplicate_ingredients: DuplicateIngredients,
+    /// How oven temperatures are written in the steps
+    #[serde(default)]
+    pub oven_temperatures: OvenTemperatures,
+    /// File mapping ingredient names to canonical ones, e.g. scallions to
+    /// green onions (see `converters::IngredientAliases`)
+    #[serde(default)]
+    pub ingredient_aliases: Option<String>,
+    /// Staples to mark in every recipe so shopping lists can skip them
+    #[serde(default)]
+    pub pantry: PantryConfig,
+    /// Custom conversion prompt, with per-provider overrides
+    #[serde(default)]
+    pub prompt: PromptConfig,
+}
+
+/// Pantry staples (`[converters.pantry]`)
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct PantryConfig {
+    /// Ingredients always at hand, e.g. salt, pepper, olive oil
+    #[serde(default)]
+    pub items: Vec<String>,
+    /// File listing more staples, one per line
+    #[serde(default)]
+    pub file: Option<String>,
+    /// How staples are marked: a note (default), `hidden` (`@-salt{}`) or
+    /// `optional` (`@?salt{}`)
+    #[serde(default)]
+    pub mark: crate::converters::PantryMark,
+    /// Note added with `mark = "note"` (default "pantry")
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// The conversion prompt (`[converters.prompt]`)
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct PromptConfig {
+    /// Template and style for every provider
+    #[serde(flatten)]
+    pub default: PromptTemplateConfig,
+    /// Overrides by provider name, e.g. `[converters.prompt.providers.ollama]`
+    #[serde(default)]
+    pub providers: HashMap<String, PromptTemplateConfig>,
+}
+
+impl PromptConfig {
+    /// The settings for `provider`: its override, with unset fields taken
+    /// from the defaults. "openai" and "open_ai" name the same provider.
+    pub fn for_provider(&self, provider: &str) -> PromptTemplateConfig {
+        let name = provider.replace('_', "");
+        let provider = self
+            .providers
+            .iter()
+            .find(|(key, _)| key.replace('_', "") == name)
+            .map(|(_, config)| config.clone())
+            .unwrap_or_default();
+        PromptTemplateConfig {
+            template: provider.template.or_else(|| self.default.template.clone()),
+            style: provider.style.or_else(|| self.default.style.clone()),
+            examples: provider.examples.or_else(|| self.default.examples.clone()),
+        }
+    }
+}
+
+/// A conversion prompt template and its style instructions
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+pub struct PromptTemplateConfig {
+    /// File with a template replacing the built-in prompt. `{{RECIPE}}` is
+    /// required; `{{LANGUAGE}}`, `{{UNITS}}` and `{{STYLE}}` are filled in too.
+    pub template: Option<String>,
+    /// Extra instructions, filled in at `{{STYLE}}` or appended to a
+    /// template without it
+    pub style: Option<String>,
+    /// Directory of few-shot examples: `<name>.txt` recipe text with the
+    /// `<name>.cook` it converts to
+    pub examples: Option<String>,
+}
+
+/// Handling of ingredients declared more than once (e.g. salt in dough and filling)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateIngredients {
+    /// Keep each declaration with its own quantity
+    #[default]
+    Keep,
+    /// Combine into the first declaration; later mentions become references
+    Merge,
+}
+
+/// How oven temperatures in the steps are written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OvenTemperatures {
+    /// In the scale the recipe uses (or `--units` converts to)
+    #[default]
+    Keep,
+    /// Followed by the other scale: "180°C (355°F)", "gas mark 4 (180°C/350°F)"
+    Both,
+}
+
+/// What happens to a conversion scoring below `min_quality`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BelowQuality {
+    /// Keep it for checking by hand: batch imports save it in a `review/`
+    /// folder
+    #[default]
+    Review,
+    /// Leave it out
+    Skip,
 }
 
 /// Configuration for the page scriber service (browser-based fetching)
@@ -121,11 +356,260 @@ pub struct PageScriberConfig {
     pub domains: Vec<String>,
 }
 
+/// Configuration for image text recognition
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct OcrConfig {
+    /// Which OCR backend to use
+    #[serde(default)]
+    pub backend: OcrBackend,
+    /// Vision model for the Ollama backend (default: llama3.2-vision)
+    pub model: Option<String>,
+    /// Ollama base URL (default: http://localhost:11434)
+    pub base_url: Option<String>,
+    /// Tesseract executable for the `tesseract` backend (default: tesseract)
+    pub binary: Option<String>,
+    /// Tesseract languages, e.g. "eng+deu" (default: eng)
+    pub language: Option<String>,
+    /// Directory with det.onnx, rec.onnx and charset.txt for the `onnx`
+    /// backend
+    pub model_dir: Option<String>,
+}
+
+/// OCR backend used by `images_to_text`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OcrBackend {
+    /// Google Cloud Vision (requires GOOGLE_API_KEY)
+    #[default]
+    Google,
+    /// Local Ollama multimodal model
+    Ollama,
+    /// Local Tesseract executable
+    Tesseract,
+    /// ONNX models run on this device (needs the `ocr-onnx` feature)
+    Onnx,
+}
+
+/// Configuration for transcribing dictated recipes
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct TranscriptionConfig {
+    /// Which speech-to-text backend to use
+    #[serde(default)]
+    pub backend: TranscriptionBackend,
+    /// Whisper model: the API model for `openai` (default: whisper-1), the
+    /// path to a ggml model file for `whisper_cpp`
+    pub model: Option<String>,
+    /// OpenAI-compatible API URL (default: https://api.openai.com)
+    pub base_url: Option<String>,
+    /// API key for the `openai` backend (default: the openai provider's key,
+    /// then OPENAI_API_KEY)
+    pub api_key: Option<String>,
+    /// whisper.cpp executable for `whisper_cpp` (default: whisper-cli)
+    pub binary: Option<String>,
+    /// Spoken language as an ISO 639-1 code, e.g. "de"; detected when unset
+    pub language: Option<String>,
+}
+
+/// Speech-to-text backend used by `audio_to_text`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptionBackend {
+    /// OpenAI's Whisper API, or a server with the same API
+    #[default]
+    #[serde(rename = "openai")]
+    OpenAI,
+    /// A local whisper.cpp build
+    WhisperCpp,
+}
+
+/// Per-stage timeouts in seconds; unset stages use `timeout`/`llm_timeout`
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct TimeoutsConfig {
+    /// Fetching recipe pages
+    pub fetch: Option<u64>,
+    /// Rendering pages through the page scriber (default: 60)
+    pub render: Option<u64>,
+    /// Image text recognition
+    pub ocr: Option<u64>,
+    /// Audio transcription
+    pub transcription: Option<u64>,
+    /// LLM extraction of recipe fields from unstructured text
+    pub extraction: Option<u64>,
+    /// LLM conversion to Cooklang
+    pub conversion: Option<u64>,
+}
+
+/// Network settings applied to page fetches, OCR and provider requests
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct HttpConfig {
+    /// Proxy URL for all requests (default: HTTP_PROXY/HTTPS_PROXY environment variables)
+    pub proxy: Option<String>,
+    /// PEM files with extra root certificates to trust (e.g. a corporate CA)
+    #[serde(default)]
+    pub ca_certificates: Vec<String>,
+    /// DANGEROUS: hosts (and their subdomains) whose TLS certificates are not
+    /// verified at all. Prefer `ca_certificates`; this is a last resort for
+    /// TLS-inspecting proxies whose CA can't be exported.
+    #[serde(default)]
+    pub danger_accept_invalid_certs: Vec<String>,
+    /// Largest page read, in bytes; longer pages are cut off (default: 10 MiB)
+    pub max_page_size: Option<u64>,
+}
+
+/// How recipe pages and photos are requested
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct FetchConfig {
+    /// User-Agent header (default: a desktop Chrome browser's); overridden
+    /// by `--user-agent`
+    pub user_agent: Option<String>,
+    /// When a page is gone (HTTP 404/410 or its domain no longer resolves),
+    /// import the Wayback Machine's latest snapshot of it instead; also
+    /// enabled by `--wayback`
+    #[serde(default)]
+    pub wayback: bool,
+}
+
+/// Configuration for the on-disk page and conversion cache
+#[derive(Debug, Deserialize, Clone)]
+pub struct CacheConfig {
+    /// Whether the cache is used (default: true)
+    #[serde(default = "default_cache_enabled")]
+    pub enabled: bool,
+    /// Cache directory (default: ~/.cache/cooklang-import)
+    pub dir: Option<String>,
+    /// How long entries stay valid, in seconds (default: 86400)
+    #[serde(default = "default_cache_ttl")]
+    pub ttl: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_cache_enabled(),
+            dir: None,
+            ttl: default_cache_ttl(),
+        }
+    }
+}
+
+/// Length caps for frontmatter values, so long descriptions and notes don't
+/// produce multi-kilobyte frontmatter
+#[derive(Debug, Deserialize, Clone)]
+pub struct FrontmatterConfig {
+    /// Whether values are truncated (default: false)
+    #[serde(default)]
+    pub truncate: bool,
+    /// Maximum characters for any value, including the ellipsis (default: 500)
+    #[serde(default = "default_frontmatter_max_length")]
+    pub max_length: usize,
+    /// Per-field maximums by key (e.g. `description = 300`), overriding
+    /// `max_length`
+    #[serde(default)]
+    pub fields: HashMap<String, usize>,
+    /// Add an `import:` block recording the source, date, extractor,
+    /// provider, model and prompt (see `provenance::Provenance`)
+    #[serde(default)]
+    pub provenance: bool,
+}
+
+impl Default for FrontmatterConfig {
+    fn default() -> Self {
+        Self {
+            truncate: false,
+            max_length: default_frontmatter_max_length(),
+            fields: HashMap::new(),
+            provenance: false,
+        }
+    }
+}
+
+/// Metadata key renames, so the frontmatter uses the keys a downstream app
+/// expects (`yield` instead of `servings`, ...)
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct MetadataConfig {
+    /// Key as written by the extractors → key to write instead
+    /// (e.g. `servings = "yield"`); an empty name drops the field
+    #[serde(default)]
+    pub map: HashMap<String, String>,
+}
+
+/// How recipe photos are saved (with the `image` feature): always
+/// re-encoded, which drops EXIF and GPS metadata
+#[derive(Debug, Deserialize, Clone)]
+pub struct ImagesConfig {
+    /// Longest side in pixels; larger photos are scaled down (default: 1600)
+    #[serde(default = "default_image_max_dimension")]
+    pub max_dimension: u32,
+    /// File format to save (default: jpeg)
+    #[serde(default)]
+    pub format: ImageFormat,
+    /// JPEG quality, 1-100 (default: 85)
+    #[serde(default = "default_image_quality")]
+    pub quality: u8,
+}
+
+impl Default for ImagesConfig {
+    fn default() -> Self {
+        Self {
+            max_dimension: default_image_max_dimension(),
+            format: ImageFormat::default(),
+            quality: default_image_quality(),
+        }
+    }
+}
+
+/// Batch imports `cooklang-import daemon` runs on a timer. The times are
+/// 5-field cron expressions (minute hour day-of-month month day-of-week) in
+/// UTC; a job without one never runs.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct ScheduleConfig {
+    /// When to import the recipes linked from `feed_urls`, e.g. "0 6 * * *"
+    pub feeds: Option<String>,
+    /// When to import the files in `folder_paths`, e.g. "*/30 * * * *"
+    pub folders: Option<String>,
+    /// Index pages or sitemaps whose recipe links are imported
+    #[serde(default)]
+    pub feed_urls: Vec<String>,
+    /// Directories of saved pages, Markdown, text and email files to import
+    #[serde(default)]
+    pub folder_paths: Vec<String>,
+    /// Where the recipes are saved; a manifest there keeps each source from
+    /// being imported twice
+    pub output_dir: Option<String>,
+    /// URL each run's summary is POSTed to as JSON
+    pub webhook: Option<String>,
+}
+
+/// File format of saved recipe photos
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    /// Lossy JPEG at `quality`
+    #[default]
+    Jpeg,
+    /// Lossless WebP (`quality` doesn't apply)
+    Webp,
+}
+
+impl ImageFormat {
+    /// File extension, without the dot
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Jpeg => "jpg",
+            ImageFormat::Webp => "webp",
+        }
+    }
+}
+
 // Default value functions
 fn default_provider() -> String {
     "open_ai".to_string()
 }
 
+fn default_frontmatter_max_length() -> usize {
+    500
+}
+
 fn default_temperature() -> f32 {
     0.7
 }
@@ -142,6 +626,10 @@ fn default_retry_delay_ms() -> u64 {
     1000
 }
 
+fn default_max_retry_wait_ms() -> u64 {
+    60_000
+}
+
 fn default_extractors() -> Vec<String> {
     vec![
         "json_ld".to_string(),
@@ -154,13 +642,26 @@ fn default_timeout() -> u64 {
     30
 }
 
+fn default_cache_enabled() -> bool {
+    true
+}
+
+fn default_cache_ttl() -> u64 {
+    24 * 60 * 60
+}
+
+fn default_image_max_dimension() -> u32 {
+    1600
+}
+
+fn default_image_quality() -> u8 {
+    85
+}
+
 impl AiConfig {
     /// Load configuration from file and environment variables
     ///
-    /// Configuration is loaded with the following priority (highest to lowest):
-    /// 1. Environment variables with COOKLANG__ prefix
-    /// 2. config.toml file in current directory
-    /// 3. Default values
+    /// See [`load_config`] for the files read and their priority.
     ///
     /// Environment variable format: COOKLANG__PROVIDERS__OPENAI__API_KEY
     pub fn load() -> Result<Self, ConfigError> {
@@ -172,14 +673,64 @@ impl AiConfig {
 ///
 /// Configuration is loaded with the following priority (highest to lowest):
 /// 1. Environment variables with COOKLANG__ prefix
-/// 2. config.toml file in current directory
-/// 3. Default values
+/// 2. The file named by `COOKLANG_CONFIG` (`--config PATH`), which must exist
+/// 3. config.toml file in current directory
+/// 4. `$XDG_CONFIG_HOME/cooklang-import/config.toml` (default
+///    `~/.config/cooklang-import/config.toml`)
+/// 5. Default values
+///
+/// Files are merged: a key set in a higher-priority file overrides the same
+/// key in a lower one, and other keys are kept.
 ///
 /// Environment variable format: COOKLANG__PROVIDERS__OPENAI__API_KEY
 pub fn load_config() -> Result<AiConfig, ConfigError> {
-    let settings = Config::builder()
-        // Optional config file (can be missing)
-        .add_source(File::with_name("config").required(false))
+    if let Some(config) = KEPT_CONFIG.lock().unwrap().as_ref() {
+        return Ok(config.clone());
+    }
+    read_config()
+}
+
+/// Environment variable naming a config file that overrides the others
+/// (what `--config PATH` sets)
+pub const CONFIG_PATH_VAR: &str = "COOKLANG_CONFIG";
+
+/// Config read once by [`keep_config`]
+static KEPT_CONFIG: Mutex<Option<AiConfig>> = Mutex::new(None);
+
+/// Read the config now and reuse it for the rest of the process, so a
+/// long-running daemon or server doesn't parse config.toml on every import.
+/// Changes to the file or environment apply after a restart.
+pub(crate) fn keep_config() -> Result<(), ConfigError> {
+    let config = read_config()?;
+    *KEPT_CONFIG.lock().unwrap() = Some(config);
+    Ok(())
+}
+
+fn read_config() -> Result<AiConfig, ConfigError> {
+    read_config_files(
+        user_config_path(),
+        std::env::var_os(CONFIG_PATH_VAR).map(PathBuf::from),
+    )
+}
+
+/// Merge the user config, ./config(.toml), an explicit file and the
+/// environment, each overriding the ones before
+fn read_config_files(
+    user: Option<PathBuf>,
+    explicit: Option<PathBuf>,
+) -> Result<AiConfig, ConfigError> {
+    let mut settings = Config::builder();
+    // Optional user config, so the CLI works from any directory
+    if let Some(path) = user {
+        settings = settings.add_source(File::from(path).required(false));
+    }
+    // Optional per-directory config file (can be missing)
+    settings = settings.add_source(File::with_name("config").required(false));
+    // Explicit config file (--config)
+    if let Some(path) = explicit {
+        settings = settings.add_source(File::from(path).format(FileFormat::Toml).required(true));
+    }
+    let settings = settings
         // Environment variables with COOKLANG_ prefix
         // Use double underscore for nested: COOKLANG__PROVIDERS__OPENAI__API_KEY
         .add_source(
@@ -192,6 +743,15 @@ pub fn load_config() -> Result<AiConfig, ConfigError> {
     settings.try_deserialize()
 }
 
+/// `$XDG_CONFIG_HOME/cooklang-import/config.toml`, else
+/// `~/.config/cooklang-import/config.toml`
+pub fn user_config_path() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("cooklang-import").join("config.toml"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -224,11 +784,21 @@ mod tests {
             temperature: 0.7,
             max_tokens: 2000,
             api_key: None,
+            api_key_cmd: None,
+            api_key_secret: None,
             base_url: None,
             endpoint: None,
             deployment_name: None,
             api_version: None,
             project_id: None,
+            auto_pull: false,
+            timeout: None,
+            proxy: None,
+            requests_per_minute: None,
+            tokens_per_minute: None,
+            target_language: None,
+            units: None,
+            prompt: Default::default(),
         };
 
         assert!(config.api_key.is_none());
@@ -256,6 +826,74 @@ mod tests {
         assert!(result.is_ok() || result.is_err());
     }
 
+    #[test]
+    fn test_config_files_merge_in_priority_order() {
+        let dir = env::temp_dir().join(format!("cooklang-import-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let user = dir.join("config.toml");
+        std::fs::write(
+            &user,
+            "default_provider = \"anthropic\"\n[converters]\nstructured_output = true\n",
+        )
+        .unwrap();
+        let explicit = dir.join("project.conf");
+        std::fs::write(&explicit, "default_provider = \"ollama\"\n").unwrap();
+
+        let config = read_config_files(Some(user.clone()), Some(explicit)).unwrap();
+        assert_eq!(config.default_provider, "ollama");
+        assert!(config.converters.structured_output);
+
+        let config = read_config_files(Some(dir.join("missing.toml")), None).unwrap();
+        assert!(!config.converters.structured_output);
+        assert!(read_config_files(None, Some(dir.join("missing.toml"))).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_prompt_config_per_provider() {
+        let config: ConvertersConfig = Config::builder()
+            .add_source(File::from_str(
+                r#"
+                [prompt]
+                template = "prompts/cooklang.txt"
+                style = "Use British spelling."
+
+                [prompt.providers.ollama]
+                template = "prompts/small-model.txt"
+                "#,
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+
+        let ollama = config.prompt.for_provider("ollama");
+        assert_eq!(ollama.template.as_deref(), Some("prompts/small-model.txt"));
+        assert_eq!(ollama.style.as_deref(), Some("Use British spelling."));
+        assert_eq!(config.prompt.for_provider("open_ai"), config.prompt.default);
+        assert_eq!(
+            ConvertersConfig::default().prompt.for_provider("groq"),
+            PromptTemplateConfig::default()
+        );
+    }
+
+    #[test]
+    fn test_cache_config_default() {
+        let cache = CacheConfig::default();
+        assert!(cache.enabled);
+        assert!(cache.dir.is_none());
+        assert_eq!(cache.ttl, 86400);
+    }
+
+    #[test]
+    fn test_race_config_default() {
+        let race = RaceConfig::default();
+        assert!(!race.enabled);
+        assert!(race.providers.is_empty());
+    }
+
     #[test]
     fn test_page_scriber_config_default() {
         let config = PageScriberConfig::default();
@@ -275,11 +913,21 @@ mod tests {
                 temperature: 0.7,
                 max_tokens: 2000,
                 api_key: Some("test-key".to_string()),
+                api_key_cmd: None,
+                api_key_secret: None,
                 base_url: None,
                 endpoint: None,
                 deployment_name: None,
                 api_version: None,
                 project_id: None,
+                auto_pull: false,
+                timeout: None,
+                proxy: None,
+                requests_per_minute: None,
+                tokens_per_minute: None,
+                target_language: None,
+                units: None,
+                prompt: Default::default(),
             },
         );
 
@@ -287,10 +935,25 @@ mod tests {
             default_provider: "openai".to_string(),
             providers,
             fallback: FallbackConfig::default(),
+            race: RaceConfig::default(),
             extractors: ExtractorsConfig::default(),
             converters: ConvertersConfig::default(),
             page_scriber: PageScriberConfig::default(),
+            ocr: OcrConfig::default(),
+            transcription: TranscriptionConfig::default(),
+            offline: false,
+            min_quality: None,
+            below_quality: BelowQuality::default(),
             timeout: default_timeout(),
+            llm_timeout: None,
+            timeouts: TimeoutsConfig::default(),
+            http: HttpConfig::default(),
+            fetch: FetchConfig::default(),
+            cache: CacheConfig::default(),
+            frontmatter: FrontmatterConfig::default(),
+            metadata: MetadataConfig::default(),
+            images: ImagesConfig::default(),
+            schedule: ScheduleConfig::default(),
         };
 
         assert_eq!(config.default_provider, "openai");
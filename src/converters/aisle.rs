@@ -0,0 +1,417 @@
+use super::scan::{ingredient_names, normalize};
+use std::collections::HashSet;
+
+/// Built-in ingredient → shopping aisle table, in the order aisles are written.
+///
+/// Entries are singular; plurals are matched by normalising both sides. When
+/// several entries match, the one with the most words wins ("coconut milk"
+/// over "milk"), then the one that appears first in the ingredient name.
+const AISLES: &[(&str, &[&str])] = &[
+    (
+        "produce",
+        &[
+            "onion",
+            "red onion",
+            "shallot",
+            "garlic",
+            "leek",
+            "scallion",
+            "spring onion",
+            "green onion",
+            "potato",
+            "sweet potato",
+            "carrot",
+            "celery",
+            "tomato",
+            "cherry tomato",
+            "cucumber",
+            "zucchini",
+            "courgette",
+            "eggplant",
+            "aubergine",
+            "bell pepper",
+            "red pepper",
+            "chili",
+            "chilli",
+            "jalapeno",
+            "lettuce",
+            "spinach",
+            "kale",
+            "cabbage",
+            "broccoli",
+            "cauliflower",
+            "mushroom",
+            "asparagus",
+            "avocado",
+            "lemon",
+            "lime",
+            "orange",
+            "apple",
+            "banana",
+            "berry",
+            "strawberry",
+            "blueberry",
+            "raspberry",
+            "pear",
+            "peach",
+            "mango",
+            "pineapple",
+            "grape",
+            "ginger",
+            "parsley",
+            "cilantro",
+            "coriander",
+            "basil",
+            "mint",
+            "dill",
+            "thyme",
+            "rosemary",
+            "beetroot",
+            "beet",
+            "radish",
+            "fennel",
+            "squash",
+            "pumpkin",
+        ],
+    ),
+    (
+        "dairy",
+        &[
+            "milk",
+            "butter",
+            "cream",
+            "heavy cream",
+            "sour cream",
+            "cream cheese",
+            "creme fraiche",
+            "buttermilk",
+            "yogurt",
+            "yoghurt",
+            "cheese",
+            "parmesan",
+            "mozzarella",
+            "cheddar",
+            "feta",
+            "ricotta",
+            "mascarpone",
+            "egg",
+        ],
+    ),
+    (
+        "meat",
+        &[
+            "chicken",
+            "beef",
+            "ground beef",
+            "pork",
+            "lamb",
+            "veal",
+            "duck",
+            "turkey",
+            "bacon",
+            "sausage",
+            "ham",
+            "mince",
+            "chorizo",
+            "prosciutto",
+        ],
+    ),
+    (
+        "seafood",
+        &[
+            "fish", "salmon", "tuna", "cod", "shrimp", "prawn", "mussel", "clam", "scallop",
+            "anchovy", "crab", "squid",
+        ],
+    ),
+    (
+        "bakery",
+        &[
+            "bread",
+            "baguette",
+            "bun",
+            "tortilla",
+            "pita",
+            "breadcrumb",
+            "brioche",
+        ],
+    ),
+    (
+        "baking",
+        &[
+            "flour",
+            "sugar",
+            "brown sugar",
+            "icing sugar",
+            "powdered sugar",
+            "baking powder",
+            "baking soda",
+            "bicarbonate of soda",
+            "yeast",
+            "cornstarch",
+            "corn starch",
+            "cornflour",
+            "cocoa",
+            "chocolate",
+            "vanilla",
+            "honey",
+            "maple syrup",
+            "oat",
+            "almond",
+            "walnut",
+            "pecan",
+            "hazelnut",
+            "raisin",
+        ],
+    ),
+    (
+        "pantry",
+        &[
+            "rice",
+            "pasta",
+            "spaghetti",
+            "noodle",
+            "egg noodle",
+            "lentil",
+            "chickpea",
+            "bean",
+            "stock",
+            "broth",
+            "chicken stock",
+            "chicken broth",
+            "beef stock",
+            "beef broth",
+            "vegetable stock",
+            "oil",
+            "olive oil",
+            "vegetable oil",
+            "vinegar",
+            "soy sauce",
+            "fish sauce",
+            "ketchup",
+            "mustard",
+            "mayonnaise",
+            "tomato paste",
+            "tomato puree",
+            "canned tomato",
+            "coconut milk",
+            "peanut butter",
+            "tahini",
+        ],
+    ),
+    (
+        "spices",
+        &[
+            "salt",
+            "pepper",
+            "black pepper",
+            "peppercorn",
+            "cumin",
+            "paprika",
+            "turmeric",
+            "cinnamon",
+            "nutmeg",
+            "oregano",
+            "chili powder",
+            "chili flake",
+            "red pepper flake",
+            "garlic powder",
+            "onion powder",
+            "bay leaf",
+            "curry powder",
+            "garam masala",
+            "cayenne",
+            "ground coriander",
+            "coriander seed",
+            "cardamom",
+            "allspice",
+            "sesame seed",
+        ],
+    ),
+    ("frozen", &["ice cream", "frozen pea", "frozen spinach"]),
+];
+
+/// Group a recipe's ingredients by shopping aisle.
+///
+/// Returns `(aisle, ingredients)` pairs in table order, with ingredient
+/// names as written in the recipe. Ingredients the built-in table doesn't
+/// know are left out.
+pub fn aisle_hints(cooklang: &str) -> Vec<(&'static str, Vec<String>)> {
+    let mut hints: Vec<(&'static str, Vec<String>)> = Vec::new();
+    let mut seen = HashSet::new();
+
+    for name in ingredient_names(cooklang) {
+        if !seen.insert(name.to_lowercase()) {
+            continue;
+        }
+        let Some(aisle) = aisle_for(&name) else {
+            continue;
+        };
+        match hints.iter_mut().find(|(a, _)| *a == aisle) {
+            Some((_, names)) => names.push(name),
+            None => hints.push((aisle, vec![name])),
+        }
+    }
+
+    hints.sort_by_key(|(aisle, _)| AISLES.iter().position(|(a, _)| a == aisle));
+    hints
+}
+
+/// Render a recipe's aisle hints as an `aisle.conf` fragment.
+///
+/// The fragment uses the `[aisle]` sections cooklang-chef reads for shopping
+/// lists, e.g. `[produce]` followed by one ingredient per line.
+pub fn aisle_conf(cooklang: &str) -> String {
+    aisle_hints(cooklang)
+        .into_iter()
+        .map(|(aisle, names)| format!("[{}]\n{}\n", aisle, names.join("\n")))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Add a recipe's ingredients to an existing `aisle.conf`.
+///
+/// Ingredients already listed anywhere in the file (including as `a|b`
+/// synonyms) are skipped. New ones go at the end of their aisle's section,
+/// or into a new section appended to the file. Everything else in the file
+/// is kept as is.
+pub fn update_aisle_conf(existing: &str, cooklang: &str) -> String {
+    let mut lines: Vec<String> = existing.lines().map(str::to_string).collect();
+    let known: HashSet<String> = lines
+        .iter()
+        .filter(|line| !line.trim_start().starts_with('['))
+        .flat_map(|line| line.split('|'))
+        .map(|name| name.trim().to_lowercase())
+        .filter(|name| !name.is_empty())
+        .collect();
+
+    for (aisle, names) in aisle_hints(cooklang) {
+        let names: Vec<String> = names
+            .into_iter()
+            .filter(|name| !known.contains(&name.to_lowercase()))
+            .collect();
+        if names.is_empty() {
+            continue;
+        }
+
+        let header = format!("[{}]", aisle);
+        match lines.iter().position(|line| line.trim() == header) {
+            Some(start) => {
+                // After the last entry of the section, before blank lines
+                let end = lines[start + 1..]
+                    .iter()
+                    .position(|line| line.trim_start().starts_with('['))
+                    .map_or(lines.len(), |i| start + 1 + i);
+                let insert_at = lines[start + 1..end]
+                    .iter()
+                    .rposition(|line| !line.trim().is_empty())
+                    .map_or(start + 1, |i| start + 2 + i);
+                lines.splice(insert_at..insert_at, names);
+            }
+            None => {
+                if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                    lines.push(String::new());
+                }
+                lines.push(header);
+                lines.extend(names);
+            }
+        }
+    }
+
+    let mut output = lines.join("\n");
+    output.push('\n');
+    output
+}
+
+/// The aisle of the best-matching table entry
+fn aisle_for(name: &str) -> Option<&'static str> {
+    let words = normalize(name);
+    let mut best: Option<(usize, usize, &'static str)> = None;
+
+    for (aisle, entries) in AISLES {
+        for entry in *entries {
+            let entry = normalize(entry);
+            let Some(position) = words
+                .windows(entry.len())
+                .position(|window| window == entry.as_slice())
+            else {
+                continue;
+            };
+            let better = best.is_none_or(|(len, pos, _)| {
+                entry.len() > len || (entry.len() == len && position < pos)
+            });
+            if better {
+                best = Some((entry.len(), position, aisle));
+            }
+        }
+    }
+
+    best.map(|(_, _, aisle)| aisle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RECIPE: &str = "---\ntitle: Pasta\n---\n\n\
+        Fry @red onions{2} and @garlic{3%cloves} in @olive oil{2%tbsp}.\n\n\
+        Add @Cherry Tomatoes{200%g}, @coconut milk{1%can} and @salt.\n\n\
+        Stir in @&garlic{} and @parmesan{50%g}.\n\n\
+        > Serve with @unobtainium{}.\n\n\
+        Garnish with @basil leaves{} and @-pepper.";
+
+    #[test]
+    fn test_aisle_hints() {
+        assert_eq!(
+            aisle_hints(RECIPE),
+            vec![
+                (
+                    "produce",
+                    vec![
+                        "red onions".to_string(),
+                        "garlic".to_string(),
+                        "Cherry Tomatoes".to_string(),
+                        "basil leaves".to_string(),
+                    ]
+                ),
+                ("dairy", vec!["parmesan".to_string()]),
+                (
+                    "pantry",
+                    vec!["olive oil".to_string(), "coconut milk".to_string()]
+                ),
+                ("spices", vec!["salt".to_string(), "pepper".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_aisle_conf_fragment() {
+        assert_eq!(
+            aisle_conf("Whisk @eggs{2} with @flour{100%g} and @milk{200%ml}."),
+            "[dairy]\neggs\nmilk\n\n[baking]\nflour\n"
+        );
+        assert_eq!(aisle_conf("Boil @water{1%l}."), "");
+    }
+
+    #[test]
+    fn test_longest_match_wins() {
+        assert_eq!(aisle_for("peanut butter"), Some("pantry"));
+        assert_eq!(aisle_for("chicken stock"), Some("pantry"));
+        assert_eq!(aisle_for("garlic cloves"), Some("produce"));
+        assert_eq!(aisle_for("fresh raspberries"), Some("produce"));
+        assert_eq!(aisle_for("unobtainium"), None);
+    }
+
+    #[test]
+    fn test_update_aisle_conf() {
+        let existing = "[produce]\npotatoes\ngarlic|garlic clove\n\n[dairy]\nbutter\n";
+        let updated = update_aisle_conf(
+            existing,
+            "Mash @potatoes{1%kg} with @butter{50%g}, @garlic{2%cloves}, @leeks{2} and @salt.",
+        );
+        assert_eq!(
+            updated,
+            "[produce]\npotatoes\ngarlic|garlic clove\nleeks\n\n[dairy]\nbutter\n\n[spices]\nsalt\n"
+        );
+        assert_eq!(update_aisle_conf("", "Add @salt."), "[spices]\nsalt\n");
+    }
+}
@@ -0,0 +1,174 @@
+use super::scan::{find_ingredient, ingredient_at, normalize};
+use crate::model::recipe_lines;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Ingredient names mapped to the name shopping lists should use.
+///
+/// An aliases file has one ingredient per line: the canonical name, then
+/// the names it is also written as, separated by `|` the way cooklang's
+/// `aisle.conf` lists synonyms:
+///
+/// ```text
+/// green onions|scallions|spring onions
+/// cilantro|coriander leaves
+/// ```
+///
+/// `[aisle]` headings, blank lines and `#` comments are skipped, so an
+/// existing `aisle.conf` works as an aliases file. Names match regardless of
+/// case and plurals ("Scallion" is an alias of "green onions" above).
+#[derive(Debug, Clone, Default)]
+pub struct IngredientAliases {
+    /// Normalized alias → canonical name
+    canonical: HashMap<Vec<String>, String>,
+}
+
+impl IngredientAliases {
+    /// Parse the contents of an aliases file
+    pub fn parse(text: &str) -> Self {
+        let mut canonical = HashMap::new();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('[') || line.starts_with('#') {
+                continue;
+            }
+            let mut names = line.split('|').map(str::trim).filter(|n| !n.is_empty());
+            let Some(name) = names.next() else {
+                continue;
+            };
+            let own = normalize(name);
+            for alias in names.map(normalize).filter(|alias| *alias != own) {
+                // The first line listing an alias wins
+                canonical.entry(alias).or_insert_with(|| name.to_string());
+            }
+        }
+        IngredientAliases { canonical }
+    }
+
+    /// Read an aliases file
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        std::fs::read_to_string(path).map(|text| Self::parse(&text))
+    }
+
+    /// The canonical name for `name`, if it is an alias
+    pub fn canonical(&self, name: &str) -> Option<&str> {
+        self.canonical.get(&normalize(name)).map(String::as_str)
+    }
+
+    /// Whether the file listed no aliases
+    pub fn is_empty(&self) -> bool {
+        self.canonical.is_empty()
+    }
+}
+
+/// Rename a recipe's ingredients to their canonical names.
+///
+/// An ingredient written as an alias gets the canonical name, keeping the
+/// recipe's wording as the Cooklang alias shown in the steps:
+/// `@scallions{2}` becomes `@green onions|scallions{2}`, which shopping
+/// lists add up with every other recipe's green onions. References
+/// (`@&scallions{}`) are renamed the same way. Notes, comments and
+/// frontmatter are left alone.
+pub fn canonicalize_ingredients(cooklang: &str, aliases: &IngredientAliases) -> String {
+    if aliases.is_empty() {
+        return cooklang.to_string();
+    }
+    let mut output = String::with_capacity(cooklang.len());
+
+    for (in_frontmatter, line) in recipe_lines(cooklang) {
+        let trimmed = line.trim();
+        if in_frontmatter || trimmed.starts_with('>') || trimmed.starts_with("--") {
+            output.push_str(line);
+        } else {
+            output.push_str(&rename_ingredients(line, aliases));
+        }
+    }
+    output
+}
+
+/// Rename the aliased ingredients on one line
+fn rename_ingredients(line: &str, aliases: &IngredientAliases) -> String {
+    let mut output = String::with_capacity(line.len());
+    let mut last = 0;
+    let mut search = 0;
+
+    while let Some(at) = find_ingredient(line, search) {
+        search = at + 1;
+        let ingredient = ingredient_at(line, at);
+        let (start, end) = (ingredient.name.start, ingredient.name.end);
+        let written = &line[start..end];
+        let (name, shown) = match written.split_once('|') {
+            Some((name, shown)) => (name.trim(), shown.trim()),
+            None => (written.trim(), written.trim()),
+        };
+        let Some(canonical) = aliases.canonical(name).filter(|c| *c != name) else {
+            continue;
+        };
+
+        output.push_str(&line[last..start]);
+        output.push_str(canonical);
+        output.push('|');
+        output.push_str(shown);
+        if !ingredient.braced {
+            output.push_str("{}");
+        }
+        last = end;
+        search = end;
+    }
+    output.push_str(&line[last..]);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALIASES: &str = "[produce]\n\
+        green onions|scallions|spring onions\n\
+        # herbs\n\
+        cilantro|coriander leaves\n\
+        \n\
+        [dairy]\n\
+        butter\n";
+
+    #[test]
+    fn test_parse() {
+        let aliases = IngredientAliases::parse(ALIASES);
+        assert_eq!(aliases.canonical("Scallion"), Some("green onions"));
+        assert_eq!(aliases.canonical("spring onions"), Some("green onions"));
+        assert_eq!(aliases.canonical("coriander leaves"), Some("cilantro"));
+        assert_eq!(aliases.canonical("green onions"), None);
+        assert_eq!(aliases.canonical("butter"), None);
+        assert!(IngredientAliases::parse("[produce]\nonion\n").is_empty());
+    }
+
+    #[test]
+    fn test_canonicalize_ingredients() {
+        let aliases = IngredientAliases::parse(ALIASES);
+        let recipe = "---\ntitle: Noodles\ntags: scallions\n---\n\n\
+            Slice @scallions{4} and @coriander leaves{1%handful}.\n\n\
+            Fry in @butter{20%g}, then add @&Scallions{}.\n\n\
+            > @spring onions work too.\n\n\
+            Top with @spring onions|the greens{}.";
+        assert_eq!(
+            canonicalize_ingredients(recipe, &aliases),
+            "---\ntitle: Noodles\ntags: scallions\n---\n\n\
+            Slice @green onions|scallions{4} and @cilantro|coriander leaves{1%handful}.\n\n\
+            Fry in @butter{20%g}, then add @&green onions|Scallions{}.\n\n\
+            > @spring onions work too.\n\n\
+            Top with @green onions|the greens{}."
+        );
+    }
+
+    #[test]
+    fn test_single_word_gets_braces() {
+        let aliases = IngredientAliases::parse("green onions|scallions");
+        assert_eq!(
+            canonicalize_ingredients("Garnish with @scallions.", &aliases),
+            "Garnish with @green onions|scallions{}."
+        );
+        assert_eq!(
+            canonicalize_ingredients("Garnish with @green onions{}.", &aliases),
+            "Garnish with @green onions{}."
+        );
+    }
+}
@@ -1,11 +1,20 @@
-use super::{inject_recipe, ConversionMetadata, ConversionResult, Converter, TokenUsage};
+use super::structured::{structured_recipe_schema, STRUCTURED_TOOL_NAME};
+use super::{
+    http_client, inject_structured_recipe_in, parse_structured_recipe, record_usage,
+    render_cooklang, user_message, ConversionMetadata, ConversionResult, Converter, Prompt,
+    TokenUsage,
+};
 use crate::config::ProviderConfig;
+use crate::images_to_text::EncodedImage;
+use crate::ImportError;
 use async_trait::async_trait;
-use log::debug;
 use reqwest::Client;
 use serde_json::{json, Value};
 use std::error::Error;
 use std::time::Instant;
+use tracing::{debug, field::Empty, instrument};
+
+const API_URL: &str = "https://api.anthropic.com/v1/messages";
 
 pub struct AnthropicConverter {
     client: Client,
@@ -13,6 +22,7 @@ pub struct AnthropicConverter {
     model: String,
     temperature: f32,
     max_tokens: u32,
+    prompt: Prompt,
 }
 
 impl AnthropicConverter {
@@ -26,59 +36,71 @@ impl AnthropicConverter {
             .ok_or("ANTHROPIC_API_KEY not found in config or environment")?;
 
         Ok(AnthropicConverter {
-            client: Client::new(),
+            client: http_client(API_URL, config.timeout, config.proxy.as_deref())?,
             api_key,
             model: config.model.clone(),
             temperature: config.temperature,
             max_tokens: config.max_tokens,
+            prompt: Prompt::from_config(config)?,
         })
     }
 
     #[doc(hidden)]
     pub fn with_base_url(api_key: String, _base_url: String, model: String) -> Self {
         AnthropicConverter {
-            client: Client::builder().build().unwrap_or_else(|_| Client::new()),
+            client: http_client(API_URL, None, None).expect("Failed to create HTTP client"),
             api_key,
             model,
             temperature: 0.7,
             max_tokens: 4000,
+            prompt: Prompt::default(),
         }
     }
-}
 
-#[async_trait]
-impl Converter for AnthropicConverter {
-    fn name(&self) -> &str {
-        "anthropic"
-    }
-
-    async fn convert(
+    /// Send a single-message request. With `tool_use`, the model is forced to
+    /// answer by calling the recipe tool and the tool input is returned as JSON.
+    ///
+    /// `content` is a prompt string, or an array of text and image blocks.
+    #[instrument(
+        name = "llm",
+        skip_all,
+        fields(provider = self.name(), model = %self.model, input_tokens = Empty, output_tokens = Empty, latency_ms = Empty)
+    )]
+    async fn messages(
         &self,
-        content: &str,
+        messages: Vec<Value>,
+        tool_use: bool,
     ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
         let start = Instant::now();
 
+        let mut body = json!({
+            "model": self.model,
+            "max_tokens": self.max_tokens,
+            "temperature": self.temperature,
+            "messages": messages
+        });
+        if tool_use {
+            body["tools"] = json!([{
+                "name": STRUCTURED_TOOL_NAME,
+                "description": "Record the recipe as structured data",
+                "input_schema": structured_recipe_schema()
+            }]);
+            body["tool_choice"] = json!({"type": "tool", "name": STRUCTURED_TOOL_NAME});
+        }
+
         let response = self
             .client
-            .post("https://api.anthropic.com/v1/messages")
+            .post(API_URL)
             .header("x-api-key", &self.api_key)
             .header("anthropic-version", "2023-06-01")
-            .json(&json!({
-                "model": self.model,
-                "max_tokens": self.max_tokens,
-                "temperature": self.temperature,
-                "messages": [
-                    {
-                        "role": "user",
-                        "content": inject_recipe(content)
-                    }
-                ]
-            }))
+            .json(&body)
             .send()
             .await?;
 
         let latency_ms = start.elapsed().as_millis() as u64;
 
+        let status = response.status();
+        let headers = response.headers().clone();
         let response_body: Value = response.json().await?;
         debug!("Anthropic response: {:?}", response_body);
 
@@ -86,19 +108,32 @@ impl Converter for AnthropicConverter {
         if let Some(error) = response_body.get("error") {
             let error_type = error["type"].as_str().unwrap_or("unknown");
             let error_message = error["message"].as_str().unwrap_or("Unknown error");
-            return Err(format!("Anthropic API error ({}): {}", error_type, error_message).into());
+            return Err(ImportError::llm_api(
+                "Anthropic",
+                status,
+                &headers,
+                format!("{} ({})", error_message, error_type),
+            )
+            .into());
         }
 
-        let cooklang_recipe = response_body["content"][0]["text"]
-            .as_str()
-            .ok_or_else(|| {
-                format!(
-                    "Failed to extract content from Anthropic response. Response: {}",
-                    serde_json::to_string_pretty(&response_body)
-                        .unwrap_or_else(|_| response_body.to_string())
-                )
-            })?
-            .to_string();
+        let cooklang_recipe = if tool_use {
+            response_body["content"]
+                .as_array()
+                .and_then(|blocks| blocks.iter().find(|b| b["type"] == "tool_use"))
+                .map(|block| block["input"].to_string())
+        } else {
+            response_body["content"][0]["text"]
+                .as_str()
+                .map(|s| s.to_string())
+        }
+        .ok_or_else(|| {
+            format!(
+                "Failed to extract content from Anthropic response. Response: {}",
+                serde_json::to_string_pretty(&response_body)
+                    .unwrap_or_else(|_| response_body.to_string())
+            )
+        })?;
 
         // Extract metadata from response
         let model_version = response_body["model"].as_str().map(|s| s.to_string());
@@ -109,7 +144,7 @@ impl Converter for AnthropicConverter {
             .as_u64()
             .map(|v| v as u32);
 
-        Ok(ConversionResult {
+        Ok(record_usage(ConversionResult {
             content: cooklang_recipe,
             metadata: ConversionMetadata {
                 model_version,
@@ -118,8 +153,73 @@ impl Converter for AnthropicConverter {
                     output_tokens,
                 },
                 latency_ms,
+                confidence: None,
+                ingredient_check: None,
+                provider: None,
+                prompt_hash: None,
             },
-        })
+        }))
+    }
+}
+
+#[async_trait]
+impl Converter for AnthropicConverter {
+    fn name(&self) -> &str {
+        "anthropic"
+    }
+
+    async fn convert(
+        &self,
+        content: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        self.messages(self.prompt.messages(content), false).await
+    }
+
+    async fn convert_structured(
+        &self,
+        content: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        let mut result = self
+            .messages(
+                vec![user_message(json!(inject_structured_recipe_in(
+                    content,
+                    self.prompt.language()
+                )))],
+                true,
+            )
+            .await?;
+        result.content = render_cooklang(&parse_structured_recipe(&result.content)?);
+        Ok(result)
+    }
+
+    async fn convert_images(
+        &self,
+        images: &[EncodedImage],
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        let mut blocks: Vec<Value> = images
+            .iter()
+            .map(|image| {
+                json!({
+                    "type": "image",
+                    "source": {
+                        "type": "base64",
+                        "media_type": image.mime_type,
+                        "data": image.data
+                    }
+                })
+            })
+            .collect();
+        blocks.push(json!({"type": "text", "text": self.prompt.image()}));
+        self.messages(vec![user_message(Value::Array(blocks))], false)
+            .await
+    }
+
+    async fn complete(
+        &self,
+        prompt: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        self.messages(vec![user_message(json!(prompt))], false)
+            .await
     }
 }
 
@@ -138,11 +238,21 @@ mod tests {
             temperature: 0.7,
             max_tokens: 4000,
             api_key: Some("test-key".to_string()),
+            api_key_cmd: None,
+            api_key_secret: None,
             base_url: None,
             endpoint: None,
             deployment_name: None,
             api_version: None,
             project_id: None,
+            auto_pull: false,
+            timeout: None,
+            proxy: None,
+            requests_per_minute: None,
+            tokens_per_minute: None,
+            target_language: None,
+            units: None,
+            prompt: Default::default(),
         };
 
         let converter = AnthropicConverter::new(&config);
@@ -157,11 +267,21 @@ mod tests {
             temperature: 0.7,
             max_tokens: 4000,
             api_key: Some("test-key".to_string()),
+            api_key_cmd: None,
+            api_key_secret: None,
             base_url: None,
             endpoint: None,
             deployment_name: None,
             api_version: None,
             project_id: None,
+            auto_pull: false,
+            timeout: None,
+            proxy: None,
+            requests_per_minute: None,
+            tokens_per_minute: None,
+            target_language: None,
+            units: None,
+            prompt: Default::default(),
         };
 
         let converter = AnthropicConverter::new(&config).unwrap();
@@ -1,11 +1,15 @@
-use super::{inject_recipe, ConversionMetadata, ConversionResult, Converter, TokenUsage};
+use super::{
+    http_client, record_usage, user_message, ConversionMetadata, ConversionResult, Converter,
+    Prompt, TokenUsage,
+};
 use crate::config::ProviderConfig;
+use crate::ImportError;
 use async_trait::async_trait;
-use log::debug;
 use reqwest::Client;
 use serde_json::{json, Value};
 use std::error::Error;
 use std::time::Instant;
+use tracing::{debug, field::Empty, instrument};
 
 pub struct AzureOpenAiConverter {
     client: Client,
@@ -15,6 +19,7 @@ pub struct AzureOpenAiConverter {
     api_version: String,
     temperature: f32,
     max_tokens: u32,
+    prompt: Prompt,
 }
 
 impl AzureOpenAiConverter {
@@ -43,26 +48,26 @@ impl AzureOpenAiConverter {
             .unwrap_or_else(|| "2024-02-15-preview".to_string());
 
         Ok(AzureOpenAiConverter {
-            client: Client::new(),
+            client: http_client(&endpoint, config.timeout, config.proxy.as_deref())?,
             api_key,
             endpoint,
             deployment_name,
             api_version,
             temperature: config.temperature,
             max_tokens: config.max_tokens,
+            prompt: Prompt::from_config(config)?,
         })
     }
-}
-
-#[async_trait]
-impl Converter for AzureOpenAiConverter {
-    fn name(&self) -> &str {
-        "azure_openai"
-    }
 
-    async fn convert(
+    /// Send a chat completion with `messages`
+    #[instrument(
+        name = "llm",
+        skip_all,
+        fields(provider = self.name(), model = %self.deployment_name, input_tokens = Empty, output_tokens = Empty, latency_ms = Empty)
+    )]
+    async fn chat(
         &self,
-        content: &str,
+        messages: Vec<Value>,
     ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
         let start = Instant::now();
 
@@ -80,9 +85,7 @@ impl Converter for AzureOpenAiConverter {
             .post(&url)
             .header("api-key", &self.api_key)
             .json(&json!({
-                "messages": [
-                    {"role": "user", "content": inject_recipe(content)}
-                ],
+                "messages": messages,
                 "temperature": self.temperature,
                 "max_tokens": self.max_tokens
             }))
@@ -91,6 +94,8 @@ impl Converter for AzureOpenAiConverter {
 
         let latency_ms = start.elapsed().as_millis() as u64;
 
+        let status = response.status();
+        let headers = response.headers().clone();
         let response_body: Value = response.json().await?;
         debug!("Azure OpenAI response: {:?}", response_body);
 
@@ -98,9 +103,13 @@ impl Converter for AzureOpenAiConverter {
         if let Some(error) = response_body.get("error") {
             let error_code = error["code"].as_str().unwrap_or("unknown");
             let error_message = error["message"].as_str().unwrap_or("Unknown error");
-            return Err(
-                format!("Azure OpenAI API error ({}): {}", error_code, error_message).into(),
-            );
+            return Err(ImportError::llm_api(
+                "Azure OpenAI",
+                status,
+                &headers,
+                format!("{} ({})", error_message, error_code),
+            )
+            .into());
         }
 
         let cooklang_recipe = response_body["choices"][0]["message"]["content"]
@@ -123,7 +132,7 @@ impl Converter for AzureOpenAiConverter {
             .as_u64()
             .map(|v| v as u32);
 
-        Ok(ConversionResult {
+        Ok(record_usage(ConversionResult {
             content: cooklang_recipe,
             metadata: ConversionMetadata {
                 model_version,
@@ -132,8 +141,33 @@ impl Converter for AzureOpenAiConverter {
                     output_tokens,
                 },
                 latency_ms,
+                confidence: None,
+                ingredient_check: None,
+                provider: None,
+                prompt_hash: None,
             },
-        })
+        }))
+    }
+}
+
+#[async_trait]
+impl Converter for AzureOpenAiConverter {
+    fn name(&self) -> &str {
+        "azure_openai"
+    }
+
+    async fn convert(
+        &self,
+        content: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        self.chat(self.prompt.messages(content)).await
+    }
+
+    async fn complete(
+        &self,
+        prompt: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        self.chat(vec![user_message(json!(prompt))]).await
     }
 }
 
@@ -150,11 +184,21 @@ mod tests {
             temperature: 0.7,
             max_tokens: 2000,
             api_key: Some("test-key".to_string()),
+            api_key_cmd: None,
+            api_key_secret: None,
             base_url: None,
             endpoint: Some("https://test.openai.azure.com".to_string()),
             deployment_name: Some("gpt-4".to_string()),
             api_version: Some("2024-02-15-preview".to_string()),
             project_id: None,
+            auto_pull: false,
+            timeout: None,
+            proxy: None,
+            requests_per_minute: None,
+            tokens_per_minute: None,
+            target_language: None,
+            units: None,
+            prompt: Default::default(),
         };
 
         let converter = AzureOpenAiConverter::new(&config).unwrap();
@@ -188,11 +232,21 @@ mod tests {
             temperature: 0.7,
             max_tokens: 2000,
             api_key: Some("test-key".to_string()),
+            api_key_cmd: None,
+            api_key_secret: None,
             base_url: None,
             endpoint: Some(server.url()),
             deployment_name: Some("gpt-4".to_string()),
             api_version: Some("2024-02-15-preview".to_string()),
             project_id: None,
+            auto_pull: false,
+            timeout: None,
+            proxy: None,
+            requests_per_minute: None,
+            tokens_per_minute: None,
+            target_language: None,
+            units: None,
+            prompt: Default::default(),
         };
 
         let converter = AzureOpenAiConverter::new(&config).unwrap();
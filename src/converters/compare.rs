@@ -0,0 +1,423 @@
+use super::scan::{find_ingredient, ingredient_at, name_len, normalize, MODIFIERS};
+use crate::diff::{diff_lines, DiffLine};
+use crate::model::recipe_lines;
+use crate::RecipeComponents;
+use serde::Serialize;
+use std::fmt;
+
+/// An ingredient both recipes have, in different amounts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AmountChange {
+    pub name: String,
+    /// Amount in the first recipe, e.g. "2 tbsp"; empty when none was given
+    pub first: String,
+    /// Amount in the second recipe
+    pub second: String,
+}
+
+/// How two imports of a recipe differ, from [`compare_recipes`] or
+/// [`compare_components`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RecipeDiff {
+    /// Ingredients only the first recipe has
+    pub only_in_first: Vec<String>,
+    /// Ingredients only the second recipe has
+    pub only_in_second: Vec<String>,
+    /// Ingredients in both with different amounts
+    pub amount_changes: Vec<AmountChange>,
+    /// Ingredients the recipes share, whatever their amounts
+    pub ingredients_in_both: usize,
+    /// Steps only the first recipe has, as plain text
+    pub steps_only_in_first: Vec<String>,
+    /// Steps only the second recipe has, as plain text
+    pub steps_only_in_second: Vec<String>,
+    /// Steps the recipes share word for word
+    pub steps_in_both: usize,
+}
+
+impl RecipeDiff {
+    /// Whether the recipes have the same ingredients, amounts and steps
+    pub fn is_empty(&self) -> bool {
+        self.only_in_first.is_empty()
+            && self.only_in_second.is_empty()
+            && self.amount_changes.is_empty()
+            && self.steps_only_in_first.is_empty()
+            && self.steps_only_in_second.is_empty()
+    }
+}
+
+impl fmt::Display for RecipeDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "Same ingredients and steps");
+        }
+        if !(self.only_in_first.is_empty()
+            && self.only_in_second.is_empty()
+            && self.amount_changes.is_empty())
+        {
+            writeln!(f, "Ingredients:")?;
+            for name in &self.only_in_first {
+                writeln!(f, "  - {}", name)?;
+            }
+            for name in &self.only_in_second {
+                writeln!(f, "  + {}", name)?;
+            }
+            for change in &self.amount_changes {
+                let amount = |amount: &str| match amount {
+                    "" => "(no amount)".to_string(),
+                    amount => amount.to_string(),
+                };
+                writeln!(
+                    f,
+                    "  ~ {}: {} → {}",
+                    change.name,
+                    amount(&change.first),
+                    amount(&change.second)
+                )?;
+            }
+        }
+        writeln!(
+            f,
+            "Steps: {} the same, {} only in the first, {} only in the second",
+            self.steps_in_both,
+            self.steps_only_in_first.len(),
+            self.steps_only_in_second.len()
+        )?;
+        for step in &self.steps_only_in_first {
+            writeln!(f, "  - {}", step)?;
+        }
+        for step in &self.steps_only_in_second {
+            writeln!(f, "  + {}", step)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compare two Cooklang recipes, e.g. the same page converted by two
+/// providers.
+///
+/// Ingredients are matched by name regardless of case and plurals
+/// (`@Eggs{3}` and `@egg{2}` are one ingredient with a changed amount).
+/// Steps are compared as plain text, with the Cooklang markup taken out, so
+/// a step only counts as changed when its wording is. Frontmatter, notes,
+/// comments and section headings are ignored.
+pub fn compare_recipes(first: &str, second: &str) -> RecipeDiff {
+    let mut diff = compare_ingredients(&ingredients(first), &ingredients(second));
+    compare_steps(&mut diff, &steps(first), &steps(second));
+    diff
+}
+
+/// Compare two extracted recipes ([`extract_only`] results): the ingredient
+/// lines and the steps of their text
+///
+/// [`extract_only`]: crate::RecipeImporterBuilder::extract_only
+pub fn compare_components(first: &RecipeComponents, second: &RecipeComponents) -> RecipeDiff {
+    let (first_ingredients, first_steps) = split_text(&first.text);
+    let (second_ingredients, second_steps) = split_text(&second.text);
+    let mut diff = RecipeDiff::default();
+    let lines = |list: &[String]| list.join("\n");
+    for line in diff_lines(&lines(&first_ingredients), &lines(&second_ingredients)) {
+        match line {
+            DiffLine::Removed(line) => diff.only_in_first.push(line.to_string()),
+            DiffLine::Added(line) => diff.only_in_second.push(line.to_string()),
+            DiffLine::Same(_) => diff.ingredients_in_both += 1,
+        }
+    }
+    compare_steps(&mut diff, &first_steps, &second_steps);
+    diff
+}
+
+/// How alike the steps of two Cooklang recipes read, from 0 to 1: the share
+/// of their words, markup taken out, that both have in the same order.
+///
+/// Unlike the step lists of [`compare_recipes`], this gives credit for steps
+/// that are reworded rather than rewritten.
+pub fn step_similarity(first: &str, second: &str) -> f32 {
+    let words = |recipe: &str| {
+        steps(recipe)
+            .join(" ")
+            .split_whitespace()
+            .map(|word| {
+                word.trim_matches(|c: char| !c.is_alphanumeric())
+                    .to_lowercase()
+            })
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let (first, second) = (words(first), words(second));
+    let total = first.lines().count() + second.lines().count();
+    if total == 0 {
+        return 1.0;
+    }
+    let common = diff_lines(&first, &second)
+        .iter()
+        .filter(|line| matches!(line, DiffLine::Same(_)))
+        .count();
+    (2 * common) as f32 / total as f32
+}
+
+/// Match ingredients by name and compare their amounts
+fn compare_ingredients(first: &[(String, String)], second: &[(String, String)]) -> RecipeDiff {
+    let mut diff = RecipeDiff::default();
+    for (name, amount) in first {
+        match second
+            .iter()
+            .find(|(other, _)| normalize(other) == normalize(name))
+        {
+            None => diff.only_in_first.push(name.clone()),
+            Some((_, other)) => {
+                diff.ingredients_in_both += 1;
+                if other != amount {
+                    diff.amount_changes.push(AmountChange {
+                        name: name.clone(),
+                        first: amount.clone(),
+                        second: other.clone(),
+                    });
+                }
+            }
+        }
+    }
+    for (name, _) in second {
+        if !first
+            .iter()
+            .any(|(other, _)| normalize(other) == normalize(name))
+        {
+            diff.only_in_second.push(name.clone());
+        }
+    }
+    diff
+}
+
+/// Record which steps only one recipe has
+fn compare_steps(diff: &mut RecipeDiff, first: &[String], second: &[String]) {
+    for line in diff_lines(&first.join("\n"), &second.join("\n")) {
+        match line {
+            DiffLine::Same(_) => diff.steps_in_both += 1,
+            DiffLine::Removed(step) => diff.steps_only_in_first.push(step.to_string()),
+            DiffLine::Added(step) => diff.steps_only_in_second.push(step.to_string()),
+        }
+    }
+}
+
+/// Lines of a recipe's steps, skipping frontmatter, notes, comments,
+/// metadata and section headings
+fn step_lines(cooklang: &str) -> Vec<&str> {
+    let mut lines = Vec::new();
+    for (in_frontmatter, line) in recipe_lines(cooklang) {
+        let line = line.trim_end_matches(['\r', '\n']);
+        let trimmed = line.trim();
+        if in_frontmatter
+            || trimmed.starts_with('>')
+            || trimmed.starts_with("--")
+            || trimmed.starts_with('=')
+        {
+            continue;
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Ingredients declared in a recipe with their amounts ("2 tbsp"). An
+/// ingredient declared more than once has its amounts joined with " + ".
+fn ingredients(cooklang: &str) -> Vec<(String, String)> {
+    let mut found: Vec<(String, String)> = Vec::new();
+    for line in step_lines(cooklang) {
+        let mut search = 0;
+        while let Some(at) = find_ingredient(line, search) {
+            search = at + 1;
+            let ingredient = ingredient_at(line, at);
+            if line[ingredient.modifiers].contains('&') {
+                continue;
+            }
+            let Some((name, amount, _)) = marker(&line[ingredient.name.start..]) else {
+                continue;
+            };
+            // `@name|alias{}` is shopped for as `name`
+            let name = name.split('|').next().unwrap_or_default().trim();
+            if name.is_empty() {
+                continue;
+            }
+            match found
+                .iter_mut()
+                .find(|(other, _)| normalize(other) == normalize(name))
+            {
+                Some((_, existing)) if !amount.is_empty() => {
+                    if !existing.is_empty() {
+                        existing.push_str(" + ");
+                    }
+                    existing.push_str(&amount);
+                }
+                Some(_) => {}
+                None => found.push((name.to_string(), amount)),
+            }
+        }
+    }
+    found
+}
+
+/// The steps of a recipe as plain text, one per paragraph
+fn steps(cooklang: &str) -> Vec<String> {
+    let mut steps = Vec::new();
+    let mut step = Vec::new();
+    for line in step_lines(cooklang).into_iter().chain(std::iter::once("")) {
+        if line.trim().is_empty() {
+            if !step.is_empty() {
+                steps.push(plain_text(&step.join(" ")));
+                step.clear();
+            }
+        } else {
+            step.push(line.trim());
+        }
+    }
+    steps
+}
+
+/// A step with its markup taken out: `Fry @eggs{2} in a #pan{} for
+/// ~{3%minutes}` reads "Fry eggs in a pan for 3 minutes"
+fn plain_text(step: &str) -> String {
+    let mut text = String::with_capacity(step.len());
+    let mut rest = step;
+    while let Some(at) = rest.find(['@', '#', '~']) {
+        text.push_str(&rest[..at]);
+        let body = rest[at + 1..].trim_start_matches(MODIFIERS);
+        match marker(body) {
+            Some((name, amount, len)) => {
+                let name = name.rsplit('|').next().unwrap_or_default().trim();
+                // Timers are shown by their duration
+                text.push_str(if name.is_empty() { &amount } else { name });
+                rest = &body[len..];
+            }
+            None => {
+                text.push_str(&rest[at..at + 1]);
+                rest = &rest[at + 1..];
+            }
+        }
+    }
+    text.push_str(rest);
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The name, amount and length of the `name{amount}` (or single-word `name`)
+/// after a marker
+fn marker(body: &str) -> Option<(&str, String, usize)> {
+    match name_len(body) {
+        (open, true) => {
+            let close = open + body[open..].find('}')?;
+            let amount = body[open + 1..close]
+                .split('%')
+                .map(str::trim)
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+            // `(note)` right after the amount belongs to the marker
+            let len = match body[close + 1..].strip_prefix('(') {
+                Some(note) => note.find(')').map_or(close + 1, |end| close + 3 + end),
+                None => close + 1,
+            };
+            Some((&body[..open], amount, len))
+        }
+        (end, false) => (end > 0).then(|| (&body[..end], String::new(), end)),
+    }
+}
+
+/// Ingredient lines and steps of extracted recipe text: the first paragraph
+/// lists the ingredients, each later paragraph is a step
+fn split_text(text: &str) -> (Vec<String>, Vec<String>) {
+    let mut paragraphs = text
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|paragraph| !paragraph.is_empty());
+    let ingredients = paragraphs
+        .next()
+        .map(|block| block.lines().map(|line| line.trim().to_string()).collect())
+        .unwrap_or_default();
+    let steps = paragraphs
+        .map(|step| step.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect();
+    (ingredients, steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_recipes() {
+        let first = "---\ntitle: Eggs\n---\n\n\
+            Melt @butter{1%tbsp} in a #pan{}.\n\n\
+            Fry @eggs{2} for ~{3%minutes}.\n\n\
+            Season with @salt.";
+        let second = "---\ntitle: Fried Eggs\n---\n\n\
+            Melt @butter{1%tbsp} in a #frying pan{}.\n\n\
+            Fry @eggs{3} for ~{3%minutes}.\n\n\
+            > Serve hot.\n\n\
+            Season with @pepper{}(to taste).";
+        let diff = compare_recipes(first, second);
+        assert_eq!(diff.only_in_first, vec!["salt"]);
+        assert_eq!(diff.only_in_second, vec!["pepper"]);
+        assert_eq!(
+            diff.amount_changes,
+            vec![AmountChange {
+                name: "eggs".to_string(),
+                first: "2".to_string(),
+                second: "3".to_string(),
+            }]
+        );
+        assert_eq!(diff.steps_in_both, 1);
+        assert_eq!(
+            diff.steps_only_in_first,
+            vec!["Melt butter in a pan.", "Season with salt."]
+        );
+        assert_eq!(
+            diff.steps_only_in_second,
+            vec!["Melt butter in a frying pan.", "Season with pepper."]
+        );
+        assert_eq!(diff.ingredients_in_both, 2);
+        assert!(compare_recipes(first, first).is_empty());
+
+        let plural = compare_recipes("Beat @eggs{2}.", "Beat @Egg{2}.");
+        assert!(plural.only_in_first.is_empty() && plural.amount_changes.is_empty());
+    }
+
+    #[test]
+    fn test_step_similarity() {
+        let first = "Melt @butter{1%tbsp} in a #pan{}.\n\nFry @eggs{2}.";
+        assert_eq!(step_similarity(first, first), 1.0);
+        // 7 words and 8 words, 6 in common
+        let second = "Melt the @butter{} in a #pan{}.\n\nFry @egg{3}.";
+        assert!((step_similarity(first, second) - 12.0 / 15.0).abs() < 1e-6);
+        assert_eq!(step_similarity(first, ""), 0.0);
+    }
+
+    #[test]
+    fn test_display() {
+        let diff = compare_recipes(
+            "Fry @eggs{2} in @butter{}.",
+            "Fry @eggs{3} in @butter{}.\n\nServe.",
+        );
+        assert_eq!(
+            diff.to_string(),
+            "Ingredients:\n  ~ eggs: 2 → 3\n\
+             Steps: 1 the same, 0 only in the first, 1 only in the second\n  + Serve.\n"
+        );
+    }
+
+    #[test]
+    fn test_compare_components() {
+        let first = RecipeComponents {
+            text: "2 eggs\n1 tbsp butter\n\nMelt the butter.\n\nFry the eggs.".to_string(),
+            ..RecipeComponents::default()
+        };
+        let second = RecipeComponents {
+            text: "2 eggs\n1 tbsp oil\n\nHeat the oil.\n\nFry the eggs.".to_string(),
+            ..RecipeComponents::default()
+        };
+        let diff = compare_components(&first, &second);
+        assert_eq!(diff.only_in_first, vec!["1 tbsp butter"]);
+        assert_eq!(diff.only_in_second, vec!["1 tbsp oil"]);
+        assert_eq!(diff.steps_in_both, 1);
+        assert_eq!(diff.steps_only_in_first, vec!["Melt the butter."]);
+    }
+}
@@ -0,0 +1,202 @@
+use super::scan::{amount_at, find_ingredient, ingredient_at};
+use crate::model::recipe_lines;
+use std::collections::HashMap;
+
+/// A plain `@name{amount}` ingredient declaration found in a recipe
+struct Declaration {
+    /// Byte range of `@name{amount}` within the recipe
+    start: usize,
+    end: usize,
+    name: String,
+    quantity: f64,
+    unit: String,
+}
+
+/// Merge repeated ingredient declarations into one with a combined amount.
+///
+/// When the same ingredient is declared more than once (e.g. salt in both the
+/// dough and the filling section) and every declaration has a numeric
+/// quantity in the same unit, the first declaration gets the total and the
+/// later ones become references (`@&salt{}`). Ingredients with mixed units,
+/// text quantities or modifiers are left untouched.
+pub fn merge_duplicate_ingredients(cooklang: &str) -> String {
+    let declarations = find_declarations(cooklang);
+
+    let mut groups: HashMap<String, Vec<&Declaration>> = HashMap::new();
+    for declaration in &declarations {
+        groups
+            .entry(declaration.name.to_lowercase())
+            .or_default()
+            .push(declaration);
+    }
+
+    // (start, end, replacement)
+    let mut replacements = Vec::new();
+    for group in groups.values().filter(|g| g.len() > 1) {
+        let unit = group[0].unit.to_lowercase();
+        if group.iter().any(|d| d.unit.to_lowercase() != unit) {
+            continue;
+        }
+
+        let total: f64 = group.iter().map(|d| d.quantity).sum();
+        let first = group[0];
+        let amount = if first.unit.is_empty() {
+            format_quantity(total)
+        } else {
+            format!("{}%{}", format_quantity(total), first.unit)
+        };
+        replacements.push((
+            first.start,
+            first.end,
+            format!("@{}{{{}}}", first.name, amount),
+        ));
+        for later in &group[1..] {
+            replacements.push((later.start, later.end, format!("@&{}{{}}", later.name)));
+        }
+    }
+
+    replacements.sort_by_key(|(start, _, _)| *start);
+    let mut output = String::with_capacity(cooklang.len());
+    let mut last = 0;
+    for (start, end, replacement) in replacements {
+        output.push_str(&cooklang[last..start]);
+        output.push_str(&replacement);
+        last = end;
+    }
+    output.push_str(&cooklang[last..]);
+    output
+}
+
+/// Find `@name{quantity%unit}` declarations outside notes, comments and frontmatter.
+fn find_declarations(cooklang: &str) -> Vec<Declaration> {
+    let mut declarations = Vec::new();
+    let mut offset = 0;
+
+    for (in_frontmatter, line) in recipe_lines(cooklang) {
+        let line_start = offset;
+        offset += line.len();
+
+        let trimmed = line.trim();
+        if in_frontmatter || trimmed.starts_with('>') || trimmed.starts_with("--") {
+            continue;
+        }
+
+        let mut search = 0;
+        while let Some(at) = find_ingredient(line, search) {
+            search = at + 1;
+
+            let ingredient = ingredient_at(line, at);
+            // Modifiers, or ingredients without an amount
+            if !ingredient.modifiers.is_empty() {
+                continue;
+            }
+            let Some(amount) = amount_at(line, &ingredient) else {
+                continue;
+            };
+            let close = amount.end;
+
+            let amount = &line[amount];
+            let (quantity, unit) = amount.split_once('%').unwrap_or((amount, ""));
+            if let Some(quantity) = parse_quantity(quantity) {
+                declarations.push(Declaration {
+                    start: line_start + at,
+                    end: line_start + close + 1,
+                    name: line[ingredient.name].trim().to_string(),
+                    quantity,
+                    unit: unit.trim().to_string(),
+                });
+            }
+            search = close + 1;
+        }
+    }
+
+    declarations
+}
+
+/// Parse "2", "1.5", "1,5", "1/2" or "1 1/2"
+pub(super) fn parse_quantity(quantity: &str) -> Option<f64> {
+    let quantity = quantity.trim();
+    if quantity.is_empty() {
+        return None;
+    }
+
+    let mut total = 0.0;
+    for part in quantity.split_whitespace() {
+        total += match part.split_once('/') {
+            Some((num, den)) => {
+                let den: f64 = den.parse().ok()?;
+                if den == 0.0 {
+                    return None;
+                }
+                num.parse::<f64>().ok()? / den
+            }
+            None => part.replace(',', ".").parse::<f64>().ok()?,
+        };
+    }
+    Some(total)
+}
+
+/// Format a quantity without trailing zeros ("2", "1.5", "0.333")
+pub(super) fn format_quantity(quantity: f64) -> String {
+    let formatted = format!("{:.3}", quantity);
+    formatted
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_across_sections() {
+        let recipe = "== Dough ==\n\nMix @flour{500%g} with @salt{1%tsp}.\n\n\
+                      == Filling ==\n\nSeason with @salt{1/2%tsp} and @black pepper{}.";
+        assert_eq!(
+            merge_duplicate_ingredients(recipe),
+            "== Dough ==\n\nMix @flour{500%g} with @salt{1.5%tsp}.\n\n\
+             == Filling ==\n\nSeason with @&salt{} and @black pepper{}."
+        );
+    }
+
+    #[test]
+    fn test_multi_word_names_next_to_braceless_ingredients() {
+        let recipe = "Season with @salt and @olive oil{2%tbsp}.\n\nDrizzle @olive oil{1%tbsp}.";
+        assert_eq!(
+            merge_duplicate_ingredients(recipe),
+            "Season with @salt and @olive oil{3%tbsp}.\n\nDrizzle @&olive oil{}."
+        );
+    }
+
+    #[test]
+    fn test_mixed_units_are_kept() {
+        let recipe = "Add @butter{50%g}.\n\nAdd @butter{2%tbsp}.";
+        assert_eq!(merge_duplicate_ingredients(recipe), recipe);
+    }
+
+    #[test]
+    fn test_unitless_quantities_and_preparation() {
+        let recipe = "Chop @onion{1}(diced).\n\nAdd @onion{2} and @eggs{3}.";
+        assert_eq!(
+            merge_duplicate_ingredients(recipe),
+            "Chop @onion{3}(diced).\n\nAdd @&onion{} and @eggs{3}."
+        );
+    }
+
+    #[test]
+    fn test_notes_frontmatter_and_text_quantities_are_ignored() {
+        let recipe =
+            "---\nauthor: a@b{1}\n---\n\n> @salt{1%tsp}\n\nAdd @salt{1%tsp} and @salt{pinch}.";
+        assert_eq!(merge_duplicate_ingredients(recipe), recipe);
+    }
+
+    #[test]
+    fn test_parse_quantity() {
+        assert_eq!(parse_quantity("1 1/2"), Some(1.5));
+        assert_eq!(parse_quantity("0,5"), Some(0.5));
+        assert_eq!(parse_quantity("pinch"), None);
+        assert_eq!(format_quantity(2.0), "2");
+        assert_eq!(format_quantity(1.0 / 3.0), "0.333");
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use super::scan::{ingredient_names, normalize};
+use crate::error::ImportError;
+use crate::model::split_frontmatter;
+
+/// A recipe in an existing collection that looks like the one being imported
+#[derive(Debug, Clone, PartialEq)]
+pub struct Duplicate {
+    /// Path of the existing `.cook` file
+    pub path: PathBuf,
+    /// Its title: frontmatter `title:`, or the file name without `.cook`
+    pub title: String,
+    /// Share of title words the two recipes have in common, 0.0 to 1.0
+    pub title_similarity: f64,
+    /// Share of ingredients the two recipes have in common, 0.0 to 1.0
+    pub ingredient_overlap: f64,
+}
+
+/// Find recipes under `dir` (searched recursively) that are likely the same
+/// as `cooklang`.
+///
+/// A file counts as a duplicate when its title is nearly the same, when the
+/// title is similar and at least half the ingredients are shared, or when
+/// the ingredient lists almost entirely overlap. Words and ingredient names
+/// are compared lowercase and singular. Matches are returned best first.
+///
+/// Files that can't be read as UTF-8 are skipped; a missing or unreadable
+/// `dir` is an error.
+pub fn find_duplicates(
+    cooklang: &str,
+    dir: impl AsRef<Path>,
+) -> Result<Vec<Duplicate>, ImportError> {
+    let title = recipe_title(cooklang).unwrap_or_default();
+    let title_words = words(&title);
+    let ingredients = ingredient_set(cooklang);
+
+    let mut files = Vec::new();
+    collect_cook_files(dir.as_ref(), &mut files)?;
+
+    let mut duplicates: Vec<Duplicate> = files
+        .into_iter()
+        .filter_map(|path| {
+            let existing = std::fs::read_to_string(&path).ok()?;
+            let existing_title = recipe_title(&existing).unwrap_or_else(|| {
+                path.file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            });
+            let title_similarity = jaccard(&title_words, &words(&existing_title));
+            let existing_ingredients = ingredient_set(&existing);
+            let ingredient_overlap = jaccard(&ingredients, &existing_ingredients);
+            // A near-total overlap of two or three ingredients says little
+            let enough_ingredients = ingredients.len().min(existing_ingredients.len()) >= 4;
+
+            let duplicate = title_similarity >= 0.8
+                || title_similarity >= 0.5 && ingredient_overlap >= 0.5
+                || enough_ingredients && ingredient_overlap >= 0.8;
+            duplicate.then_some(Duplicate {
+                path,
+                title: existing_title,
+                title_similarity,
+                ingredient_overlap,
+            })
+        })
+        .collect();
+
+    duplicates.sort_by(|a, b| {
+        let score = |d: &Duplicate| d.title_similarity + d.ingredient_overlap;
+        score(b)
+            .total_cmp(&score(a))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    Ok(duplicates)
+}
+
+fn collect_cook_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), ImportError> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_cook_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "cook") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// The frontmatter `title:` of a recipe, if it has one
+fn recipe_title(cooklang: &str) -> Option<String> {
+    let mapping: serde_yaml::Mapping = serde_yaml::from_str(split_frontmatter(cooklang).0?).ok()?;
+    match mapping.get("title")? {
+        serde_yaml::Value::String(title) => Some(title.clone()),
+        _ => None,
+    }
+}
+
+fn words(text: &str) -> HashSet<String> {
+    normalize(text).into_iter().collect()
+}
+
+fn ingredient_set(cooklang: &str) -> HashSet<String> {
+    ingredient_names(cooklang)
+        .iter()
+        .map(|name| normalize(name).join(" "))
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collection(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "cooklang-import-duplicates-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        for (path, content) in files {
+            let path = dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    const PANCAKES: &str = "---\ntitle: Fluffy Pancakes\n---\n\nWhisk @flour{200%g}, @eggs{2}, @milk{300%ml} and @sugar{1%tbsp}.\n";
+
+    #[test]
+    fn test_finds_duplicate_by_title_and_ingredients() {
+        let dir = collection(
+            "title",
+            &[
+                (
+                    "breakfast/Pancakes.cook",
+                    "---\ntitle: Fluffy pancake\n---\n\nMix @flour{1%cup}, @egg{1} and @milk{1%cup}.\n",
+                ),
+                (
+                    "Tomato Soup.cook",
+                    "Simmer @tomatoes{6} with @onion{1} and @stock{1%l}.\n",
+                ),
+                ("notes.txt", PANCAKES),
+            ],
+        );
+
+        let duplicates = find_duplicates(PANCAKES, &dir).unwrap();
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].title, "Fluffy pancake");
+        assert_eq!(duplicates[0].title_similarity, 1.0);
+        assert!(duplicates[0].path.ends_with("breakfast/Pancakes.cook"));
+    }
+
+    #[test]
+    fn test_falls_back_to_file_name_and_ingredients() {
+        let dir = collection(
+            "ingredients",
+            &[
+                (
+                    "Grandma's Hotcakes.cook",
+                    "Whisk @flour{}, @eggs{}, @milk{} and @sugar{}.\n",
+                ),
+                (
+                    "Fluffy Omelette.cook",
+                    "Beat @eggs{3} with @butter{} and @salt{}.\n",
+                ),
+            ],
+        );
+
+        let duplicates = find_duplicates(PANCAKES, &dir).unwrap();
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].title, "Grandma's Hotcakes");
+        assert_eq!(duplicates[0].ingredient_overlap, 1.0);
+    }
+
+    #[test]
+    fn test_missing_directory_is_an_error() {
+        let dir = std::env::temp_dir().join("cooklang-import-duplicates-test-missing");
+        assert!(find_duplicates(PANCAKES, dir).is_err());
+    }
+}
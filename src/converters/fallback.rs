@@ -0,0 +1,178 @@
+use super::{by_provider, call_with_retries, ConversionResult, Converter, Request, RetryPolicy};
+use crate::images_to_text::EncodedImage;
+use async_trait::async_trait;
+use std::error::Error;
+use std::time::Duration;
+
+/// Converter that tries providers in order, retrying each with exponential
+/// backoff before moving on to the next one.
+///
+/// Rate-limited providers are retried after the wait they ask for; errors
+/// that won't pass on retry (a bad key, an unknown model) move on to the
+/// next provider at once. See [`is_retryable`](super::is_retryable).
+pub struct FallbackConverter {
+    converters: Vec<Box<dyn Converter>>,
+    policy: RetryPolicy,
+}
+
+impl FallbackConverter {
+    /// Create a fallback chain
+    ///
+    /// # Arguments
+    /// * `converters` - Providers in the order they should be tried
+    /// * `retry_attempts` - Attempts per provider (at least one is always made)
+    /// * `retry_delay` - Delay before the first retry, doubled after each failure
+    pub fn new(
+        converters: Vec<Box<dyn Converter>>,
+        retry_attempts: u32,
+        retry_delay: Duration,
+    ) -> Result<Self, Box<dyn Error>> {
+        if converters.is_empty() {
+            return Err("Fallback chain needs at least one provider".into());
+        }
+        Ok(FallbackConverter {
+            converters,
+            policy: RetryPolicy {
+                attempts: retry_attempts.max(1),
+                delay: retry_delay,
+                ..RetryPolicy::default()
+            },
+        })
+    }
+
+    /// Longest `Retry-After` to wait for before moving on to the next
+    /// provider (default one minute)
+    pub fn max_retry_wait(mut self, wait: Duration) -> Self {
+        self.policy.max_wait = wait;
+        self
+    }
+
+    async fn run(
+        &self,
+        request: Request<'_>,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        let mut errors = Vec::new();
+
+        for converter in &self.converters {
+            match call_with_retries(converter.as_ref(), request, &self.policy).await {
+                Ok(conversion) => return Ok(by_provider(conversion, converter.name())),
+                Err(e) => errors.push(format!("{}: {}", converter.name(), e)),
+            }
+        }
+
+        Err(format!("All providers failed: {}", errors.join("; ")).into())
+    }
+}
+
+#[async_trait]
+impl Converter for FallbackConverter {
+    fn name(&self) -> &str {
+        "fallback"
+    }
+
+    async fn convert(
+        &self,
+        content: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        self.run(Request::Text(content)).await
+    }
+
+    async fn convert_structured(
+        &self,
+        content: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        self.run(Request::Structured(content)).await
+    }
+
+    async fn convert_images(
+        &self,
+        images: &[EncodedImage],
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        self.run(Request::Images(images)).await
+    }
+
+    async fn complete(
+        &self,
+        prompt: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        self.run(Request::Prompt(prompt)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::converters::OllamaConverter;
+    use mockito::Server;
+
+    #[tokio::test]
+    async fn test_fallback_to_second_provider() {
+        let mut failing = Server::new_async().await;
+        let failing_mock = failing
+            .mock("POST", "/v1/chat/completions")
+            .with_status(500)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"error": "overloaded"}"#)
+            .expect(2)
+            .create();
+
+        let mut working = Server::new_async().await;
+        let working_mock = working
+            .mock("POST", "/v1/chat/completions")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"choices": [{"message": {"content": "Fry @eggs{2}."}}]}"#)
+            .create();
+
+        let fallback = FallbackConverter::new(
+            vec![
+                Box::new(OllamaConverter::with_base_url(
+                    failing.url(),
+                    "a".to_string(),
+                )),
+                Box::new(OllamaConverter::with_base_url(
+                    working.url(),
+                    "b".to_string(),
+                )),
+            ],
+            2,
+            Duration::from_millis(1),
+        )
+        .unwrap();
+
+        let result = fallback.convert("2 eggs\n\nFry.").await.unwrap();
+        assert_eq!(result.content, "Fry @eggs{2}.");
+        failing_mock.assert();
+        working_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_fallback_all_fail() {
+        let mut server = Server::new_async().await;
+        let _mock = server
+            .mock("POST", "/v1/chat/completions")
+            .with_status(500)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"error": "boom"}"#)
+            .create();
+
+        let fallback = FallbackConverter::new(
+            vec![Box::new(OllamaConverter::with_base_url(
+                server.url(),
+                "a".to_string(),
+            ))],
+            1,
+            Duration::ZERO,
+        )
+        .unwrap();
+
+        let err = fallback.convert("text").await.unwrap_err().to_string();
+        assert!(err.contains("All providers failed"));
+        assert!(err.contains("ollama: Ollama API error: boom"));
+    }
+
+    #[test]
+    fn test_fallback_requires_a_provider() {
+        assert!(FallbackConverter::new(Vec::new(), 3, Duration::ZERO).is_err());
+    }
+}
@@ -1,11 +1,17 @@
-use super::{inject_recipe, ConversionMetadata, ConversionResult, Converter, TokenUsage};
+use super::{
+    http_client, record_usage, ConversionMetadata, ConversionResult, Converter, Prompt, TokenUsage,
+};
 use crate::config::ProviderConfig;
+use crate::images_to_text::EncodedImage;
+use crate::ImportError;
 use async_trait::async_trait;
-use log::debug;
 use reqwest::Client;
 use serde_json::{json, Value};
 use std::error::Error;
 use std::time::Instant;
+use tracing::{debug, field::Empty, instrument};
+
+const API_URL: &str = "https://generativelanguage.googleapis.com";
 
 pub struct GoogleConverter {
     client: Client,
@@ -13,6 +19,7 @@ pub struct GoogleConverter {
     model: String,
     temperature: f32,
     max_tokens: u32,
+    prompt: Prompt,
 }
 
 impl GoogleConverter {
@@ -26,42 +33,38 @@ impl GoogleConverter {
             .ok_or("GOOGLE_API_KEY not found in config or environment")?;
 
         Ok(GoogleConverter {
-            client: Client::new(),
+            client: http_client(API_URL, config.timeout, config.proxy.as_deref())?,
             api_key,
             model: config.model.clone(),
             temperature: config.temperature,
             max_tokens: config.max_tokens,
+            prompt: Prompt::from_config(config)?,
         })
     }
-}
-
-#[async_trait]
-impl Converter for GoogleConverter {
-    fn name(&self) -> &str {
-        "google"
-    }
 
-    async fn convert(
+    /// Send a `generateContent` request with the given conversation turns.
+    #[instrument(
+        name = "llm",
+        skip_all,
+        fields(provider = self.name(), model = %self.model, input_tokens = Empty, output_tokens = Empty, latency_ms = Empty)
+    )]
+    async fn generate(
         &self,
-        content: &str,
+        contents: Vec<Value>,
     ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
         let start = Instant::now();
 
         // Google Gemini API endpoint
         let url = format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
-            self.model, self.api_key
+            "{}/v1beta/models/{}:generateContent?key={}",
+            API_URL, self.model, self.api_key
         );
 
         let response = self
             .client
             .post(&url)
             .json(&json!({
-                "contents": [{
-                    "parts": [{
-                        "text": inject_recipe(content)
-                    }]
-                }],
+                "contents": contents,
                 "generationConfig": {
                     "temperature": self.temperature,
                     "maxOutputTokens": self.max_tokens
@@ -72,18 +75,17 @@ impl Converter for GoogleConverter {
 
         let latency_ms = start.elapsed().as_millis() as u64;
 
+        let status = response.status();
+        let headers = response.headers().clone();
         let response_body: Value = response.json().await?;
         debug!("Google Gemini response: {:?}", response_body);
 
         // Check for API error response
         if let Some(error) = response_body.get("error") {
-            let error_code = error["code"].as_i64().unwrap_or(0);
             let error_message = error["message"].as_str().unwrap_or("Unknown error");
-            return Err(format!(
-                "Google Gemini API error ({}): {}",
-                error_code, error_message
-            )
-            .into());
+            return Err(
+                ImportError::llm_api("Google Gemini", status, &headers, error_message).into(),
+            );
         }
 
         let cooklang_recipe = response_body["candidates"][0]["content"]["parts"][0]["text"]
@@ -110,7 +112,7 @@ impl Converter for GoogleConverter {
             .as_u64()
             .map(|v| v as u32);
 
-        Ok(ConversionResult {
+        Ok(record_usage(ConversionResult {
             content: cooklang_recipe,
             metadata: ConversionMetadata {
                 model_version,
@@ -119,8 +121,67 @@ impl Converter for GoogleConverter {
                     output_tokens,
                 },
                 latency_ms,
+                confidence: None,
+                ingredient_check: None,
+                provider: None,
+                prompt_hash: None,
             },
-        })
+        }))
+    }
+}
+
+#[async_trait]
+impl Converter for GoogleConverter {
+    fn name(&self) -> &str {
+        "google"
+    }
+
+    async fn convert(
+        &self,
+        content: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        // Gemini calls the assistant "model" and wraps text in parts
+        let contents = self
+            .prompt
+            .messages(content)
+            .into_iter()
+            .map(|message| {
+                let role = match message["role"].as_str() {
+                    Some("assistant") => "model",
+                    _ => "user",
+                };
+                json!({"role": role, "parts": [{"text": message["content"]}]})
+            })
+            .collect();
+        self.generate(contents).await
+    }
+
+    async fn convert_images(
+        &self,
+        images: &[EncodedImage],
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        let mut parts: Vec<Value> = images
+            .iter()
+            .map(|image| {
+                json!({
+                    "inline_data": {
+                        "mime_type": image.mime_type,
+                        "data": image.data
+                    }
+                })
+            })
+            .collect();
+        parts.push(json!({"text": self.prompt.image()}));
+        self.generate(vec![json!({"role": "user", "parts": parts})])
+            .await
+    }
+
+    async fn complete(
+        &self,
+        prompt: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        self.generate(vec![json!({"role": "user", "parts": [{"text": prompt}]})])
+            .await
     }
 }
 
@@ -136,11 +197,21 @@ mod tests {
             temperature: 0.7,
             max_tokens: 2000,
             api_key: Some("test-key".to_string()),
+            api_key_cmd: None,
+            api_key_secret: None,
             base_url: None,
             endpoint: None,
             deployment_name: None,
             api_version: None,
             project_id: None,
+            auto_pull: false,
+            timeout: None,
+            proxy: None,
+            requests_per_minute: None,
+            tokens_per_minute: None,
+            target_language: None,
+            units: None,
+            prompt: Default::default(),
         };
 
         let converter = GoogleConverter::new(&config).unwrap();
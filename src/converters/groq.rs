@@ -0,0 +1,248 @@
+use super::{
+    http_client, record_usage, user_message, ConversionMetadata, ConversionResult, Converter,
+    Prompt, TokenUsage,
+};
+use crate::config::ProviderConfig;
+use crate::ImportError;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::error::Error;
+use std::time::Instant;
+use tracing::{debug, field::Empty, instrument};
+
+pub struct GroqConverter {
+    client: Client,
+    api_key: String,
+    base_url: String,
+    model: String,
+    temperature: f32,
+    max_tokens: u32,
+    prompt: Prompt,
+}
+
+impl GroqConverter {
+    /// Create a new Groq converter from configuration
+    pub fn new(config: &ProviderConfig) -> Result<Self, Box<dyn Error>> {
+        // Try config first, then fall back to environment variable
+        let api_key = config
+            .api_key
+            .clone()
+            .or_else(|| std::env::var("GROQ_API_KEY").ok())
+            .ok_or("GROQ_API_KEY not found in config or environment")?;
+
+        let base_url = config
+            .base_url
+            .clone()
+            .unwrap_or_else(|| "https://api.groq.com/openai".to_string());
+
+        Ok(GroqConverter {
+            client: http_client(&base_url, config.timeout, config.proxy.as_deref())?,
+            api_key,
+            base_url,
+            model: config.model.clone(),
+            temperature: config.temperature,
+            max_tokens: config.max_tokens,
+            prompt: Prompt::from_config(config)?,
+        })
+    }
+
+    #[doc(hidden)]
+    pub fn with_base_url(api_key: String, base_url: String, model: String) -> Self {
+        GroqConverter {
+            client: http_client(&base_url, None, None).expect("Failed to create HTTP client"),
+            api_key,
+            base_url,
+            model,
+            temperature: 0.7,
+            max_tokens: 2000,
+            prompt: Prompt::default(),
+        }
+    }
+
+    /// Send a chat completion with `messages`
+    #[instrument(
+        name = "llm",
+        skip_all,
+        fields(provider = self.name(), model = %self.model, input_tokens = Empty, output_tokens = Empty, latency_ms = Empty)
+    )]
+    async fn chat(
+        &self,
+        messages: Vec<Value>,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        let start = Instant::now();
+
+        // Groq exposes an OpenAI-compatible API under /openai
+        let response = self
+            .client
+            .post(format!("{}/v1/chat/completions", self.base_url))
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .json(&json!({
+                "model": self.model,
+                "messages": messages,
+                "temperature": self.temperature,
+                "max_completion_tokens": self.max_tokens,
+                "stream": false
+            }))
+            .send()
+            .await?;
+
+        let latency_ms = start.elapsed().as_millis() as u64;
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        let response_body: Value = response.json().await?;
+        debug!("Groq response: {:?}", response_body);
+
+        // Check for API error response
+        if let Some(error) = response_body.get("error") {
+            let error_type = error["type"].as_str().unwrap_or("unknown");
+            let error_message = error["message"].as_str().unwrap_or("Unknown error");
+            return Err(ImportError::llm_api(
+                "Groq",
+                status,
+                &headers,
+                format!("{} ({})", error_message, error_type),
+            )
+            .into());
+        }
+
+        let cooklang_recipe = response_body["choices"][0]["message"]["content"]
+            .as_str()
+            .ok_or_else(|| {
+                format!(
+                    "Failed to extract content from Groq response. Response: {}",
+                    serde_json::to_string_pretty(&response_body)
+                        .unwrap_or_else(|_| response_body.to_string())
+                )
+            })?
+            .to_string();
+
+        // Extract metadata from response (OpenAI-compatible format)
+        let model_version = response_body["model"].as_str().map(|s| s.to_string());
+        let input_tokens = response_body["usage"]["prompt_tokens"]
+            .as_u64()
+            .map(|v| v as u32);
+        let output_tokens = response_body["usage"]["completion_tokens"]
+            .as_u64()
+            .map(|v| v as u32);
+
+        Ok(record_usage(ConversionResult {
+            content: cooklang_recipe,
+            metadata: ConversionMetadata {
+                model_version,
+                tokens_used: TokenUsage {
+                    input_tokens,
+                    output_tokens,
+                },
+                latency_ms,
+                confidence: None,
+                ingredient_check: None,
+                provider: None,
+                prompt_hash: None,
+            },
+        }))
+    }
+}
+
+#[async_trait]
+impl Converter for GroqConverter {
+    fn name(&self) -> &str {
+        "groq"
+    }
+
+    async fn convert(
+        &self,
+        content: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        self.chat(self.prompt.messages(content)).await
+    }
+
+    async fn complete(
+        &self,
+        prompt: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        self.chat(vec![user_message(json!(prompt))]).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+
+    #[tokio::test]
+    async fn test_groq_convert() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/chat/completions")
+            .match_header("authorization", "Bearer fake_api_key")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{
+                    "model": "llama-3.3-70b-versatile",
+                    "choices": [{
+                        "message": {
+                            "content": "Cook @pasta{500%g} and add @sauce{}"
+                        }
+                    }],
+                    "usage": {"prompt_tokens": 98, "completion_tokens": 31}
+                }"#,
+            )
+            .create();
+
+        let converter = GroqConverter::with_base_url(
+            "fake_api_key".to_string(),
+            server.url(),
+            "llama-3.3-70b-versatile".to_string(),
+        );
+
+        let result = converter
+            .convert("pasta\nsauce\n\nCook pasta with sauce")
+            .await
+            .unwrap();
+        assert!(result.content.contains("@sauce"));
+        assert_eq!(result.metadata.tokens_used.input_tokens, Some(98));
+        assert_eq!(result.metadata.tokens_used.output_tokens, Some(31));
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_groq_api_error() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/chat/completions")
+            .with_status(400)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"error": {"message": "model not found", "type": "invalid_request_error"}}"#,
+            )
+            .create();
+
+        let converter = GroqConverter::with_base_url(
+            "fake_api_key".to_string(),
+            server.url(),
+            "nope".to_string(),
+        );
+
+        let result = converter.convert("ingredient\n\nstep").await;
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("model not found"));
+        assert!(matches!(
+            err.downcast_ref::<ImportError>(),
+            Some(ImportError::LlmApi { provider, status: Some(400), .. }) if provider == "Groq"
+        ));
+        mock.assert();
+    }
+
+    #[test]
+    fn test_converter_name() {
+        let converter = GroqConverter::with_base_url(
+            "fake_api_key".to_string(),
+            "https://api.groq.com/openai".to_string(),
+            "llama-3.3-70b-versatile".to_string(),
+        );
+        assert_eq!(converter.name(), "groq");
+    }
+}
@@ -0,0 +1,261 @@
+//! Converters that answer without a model, for tests (`mock` feature)
+//!
+//! Both are registered in [`create_converter`](super::create_converter) as
+//! `"mock"`: with a `base_url` in `[providers.mock]` it is a
+//! [`ReplayConverter`] reading that directory, otherwise a [`MockConverter`].
+
+use super::{ConversionMetadata, ConversionResult, Converter};
+use crate::images_to_text::EncodedImage;
+use async_trait::async_trait;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// A converter with canned answers: a fixed response, or the recipe text
+/// itself (plain text is valid Cooklang, if not a very useful one)
+#[derive(Debug, Clone, Default)]
+pub struct MockConverter {
+    response: Option<String>,
+}
+
+impl MockConverter {
+    /// Echo every recipe back unchanged
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer every request with `response`
+    pub fn with_response(response: impl Into<String>) -> Self {
+        MockConverter {
+            response: Some(response.into()),
+        }
+    }
+
+    fn answer(&self, content: &str) -> ConversionResult {
+        ConversionResult {
+            content: self.response.clone().unwrap_or_else(|| content.to_string()),
+            metadata: metadata(),
+        }
+    }
+}
+
+#[async_trait]
+impl Converter for MockConverter {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    async fn convert(
+        &self,
+        content: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        Ok(self.answer(content))
+    }
+
+    async fn convert_structured(
+        &self,
+        content: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        Ok(self.answer(content))
+    }
+
+    async fn convert_images(
+        &self,
+        _images: &[EncodedImage],
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        match &self.response {
+            Some(response) => Ok(self.answer(response)),
+            None => Err("The mock provider needs a canned response for images".into()),
+        }
+    }
+
+    async fn complete(
+        &self,
+        prompt: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        match &self.response {
+            Some(response) => Ok(self.answer(response)),
+            None => Err(format!(
+                "The mock provider needs a canned response for prompts ({} characters)",
+                prompt.len()
+            )
+            .into()),
+        }
+    }
+}
+
+/// Which `Converter` method a request came through
+#[derive(Clone, Copy)]
+enum Request<'a> {
+    Text(&'a str),
+    Structured(&'a str),
+    Images(&'a [EncodedImage]),
+    Prompt(&'a str),
+}
+
+/// A converter that replays responses recorded in a directory, one
+/// `<hash>.txt` file per request, keyed by a hash of the request
+///
+/// A recording replayer asks another converter for responses it doesn't
+/// have yet and saves them, so a test suite can be recorded once against a
+/// real provider and then run without API keys or network.
+pub struct ReplayConverter {
+    dir: PathBuf,
+    recorder: Option<Box<dyn Converter>>,
+}
+
+impl ReplayConverter {
+    /// Replay the responses in `dir`; requests without one fail
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        ReplayConverter {
+            dir: dir.into(),
+            recorder: None,
+        }
+    }
+
+    /// Replay the responses in `dir`, recording missing ones from `converter`
+    pub fn recording(dir: impl Into<PathBuf>, converter: Box<dyn Converter>) -> Self {
+        ReplayConverter {
+            dir: dir.into(),
+            recorder: Some(converter),
+        }
+    }
+
+    /// The recorded response's file
+    fn path(&self, request: Request) -> PathBuf {
+        let key: Vec<&str> = match request {
+            Request::Text(content) => vec!["convert", content],
+            Request::Structured(content) => vec!["structured", content],
+            Request::Images(images) => std::iter::once("images")
+                .chain(images.iter().map(|image| image.data.as_str()))
+                .collect(),
+            Request::Prompt(prompt) => vec!["complete", prompt],
+        };
+        self.dir.join(format!("{}.txt", crate::cache::hash(&key)))
+    }
+
+    async fn replay(
+        &self,
+        request: Request<'_>,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        let path = self.path(request);
+        if let Ok(content) = std::fs::read_to_string(&path) {
+            debug!("Replaying {}", path.display());
+            return Ok(ConversionResult {
+                content,
+                metadata: metadata(),
+            });
+        }
+
+        let Some(recorder) = &self.recorder else {
+            return Err(format!("No recorded response: save it as {}", path.display()).into());
+        };
+        let result = match request {
+            Request::Text(content) => recorder.convert(content).await,
+            Request::Structured(content) => recorder.convert_structured(content).await,
+            Request::Images(images) => recorder.convert_images(images).await,
+            Request::Prompt(prompt) => recorder.complete(prompt).await,
+        }?;
+        save(&path, &result.content)?;
+        debug!("Recorded {}", path.display());
+        Ok(result)
+    }
+}
+
+fn save(path: &Path, content: &str) -> Result<(), String> {
+    path.parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(path, content))
+        .map_err(|e| format!("Failed to record {}: {}", path.display(), e))
+}
+
+#[async_trait]
+impl Converter for ReplayConverter {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    async fn convert(
+        &self,
+        content: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        self.replay(Request::Text(content)).await
+    }
+
+    async fn convert_structured(
+        &self,
+        content: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        self.replay(Request::Structured(content)).await
+    }
+
+    async fn convert_images(
+        &self,
+        images: &[EncodedImage],
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        self.replay(Request::Images(images)).await
+    }
+
+    async fn complete(
+        &self,
+        prompt: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        self.replay(Request::Prompt(prompt)).await
+    }
+}
+
+fn metadata() -> ConversionMetadata {
+    ConversionMetadata {
+        model_version: Some("mock".to_string()),
+        ..ConversionMetadata::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mock_converter() {
+        let echo = MockConverter::new();
+        assert_eq!(echo.convert("2 eggs").await.unwrap().content, "2 eggs");
+        assert!(echo.complete("Extract the recipe").await.is_err());
+
+        let canned = MockConverter::with_response("Fry @eggs{2}.");
+        assert_eq!(
+            canned.complete("Extract the recipe").await.unwrap().content,
+            "Fry @eggs{2}."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_replay_converter() {
+        let dir = std::env::temp_dir().join(format!("cooklang-replay-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let replay = ReplayConverter::new(&dir);
+        let err = replay.convert("2 eggs").await.unwrap_err().to_string();
+        assert!(
+            err.starts_with("No recorded response: save it as"),
+            "{}",
+            err
+        );
+
+        let recording = ReplayConverter::recording(
+            &dir,
+            Box::new(MockConverter::with_response("Fry @eggs{2}.")),
+        );
+        assert_eq!(
+            recording.convert("2 eggs").await.unwrap().content,
+            "Fry @eggs{2}."
+        );
+
+        // Replayed from disk now, and keyed by method as well as input
+        assert_eq!(
+            replay.convert("2 eggs").await.unwrap().content,
+            "Fry @eggs{2}."
+        );
+        assert!(replay.complete("2 eggs").await.is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
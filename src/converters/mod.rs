@@ -1,20 +1,98 @@
+mod aisle;
+mod aliases;
 mod anthropic;
 mod azure_openai;
+mod compare;
+mod dedup;
+mod duplicates;
+mod fallback;
 mod google;
+mod groq;
+mod mistral;
+#[cfg(feature = "mock")]
+mod mock;
 mod ollama;
 mod open_ai;
+mod pantry;
+mod pricing;
 mod prompt;
+mod quality;
+mod race;
+mod rate_limit;
+mod retry;
+mod sanity;
+mod scale;
+mod scan;
+mod structured;
+mod syntax;
+mod temperatures;
+mod timers;
+mod units;
 
+pub use aisle::{aisle_conf, aisle_hints, update_aisle_conf};
+pub use aliases::{canonicalize_ingredients, IngredientAliases};
 pub use anthropic::AnthropicConverter;
 pub use azure_openai::AzureOpenAiConverter;
+pub use compare::{compare_components, compare_recipes, step_similarity, AmountChange, RecipeDiff};
+pub use dedup::merge_duplicate_ingredients;
+pub use duplicates::{find_duplicates, Duplicate};
+pub use fallback::FallbackConverter;
 pub use google::GoogleConverter;
+pub use groq::GroqConverter;
+pub use mistral::MistralConverter;
+#[cfg(feature = "mock")]
+pub use mock::{MockConverter, ReplayConverter};
 pub use ollama::OllamaConverter;
+pub(crate) use ollama::{has_model as ollama_has_model, list_models as ollama_models};
 pub use open_ai::OpenAiConverter;
-pub use prompt::{inject_recipe, COOKLANG_CONVERTER_PROMPT};
+pub use pantry::{mark_pantry_ingredients, parse_pantry, PantryMark};
+pub use pricing::{estimate_cost, estimate_tokens};
+pub(crate) use prompt::{detected_language, user_message};
+pub use prompt::{
+    inject_image_recipe, inject_image_recipe_in, inject_recipe, inject_recipe_in, Example, Prompt,
+    COOKLANG_CONVERTER_PROMPT,
+};
+pub use quality::{check_ingredients, score_conversion, IngredientCheck, LOW_CONFIDENCE};
+pub use race::RaceConverter;
+pub use rate_limit::{rate_limiter, RateLimit, RateLimitedConverter, RateLimiter};
+pub(crate) use retry::{call_with_retries, Request};
+pub use retry::{is_retryable, retry_after, RetryConverter, RetryPolicy};
+pub use sanity::check_completeness;
+pub use scale::scale_recipe;
+pub use structured::{
+    inject_structured_recipe, inject_structured_recipe_in, parse_structured_recipe,
+    render_cooklang, StructuredCookware, StructuredIngredient, StructuredRecipe, StructuredSection,
+    STRUCTURED_RECIPE_PROMPT,
+};
+pub use syntax::check_cooklang;
+pub use temperatures::normalize_temperatures;
+pub use timers::tag_timers;
+pub(crate) use units::convert_quantities;
+pub(crate) use units::is_unit;
+pub use units::{convert_units, UnitSystem};
 
+use crate::images_to_text::EncodedImage;
 use async_trait::async_trait;
+use reqwest::Client;
 use serde::Serialize;
 use std::error::Error;
+use std::time::Duration;
+
+/// Timeout for LLM requests when neither the builder nor config sets one
+pub const DEFAULT_LLM_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// HTTP client for provider requests to `url`, with the given timeout in
+/// seconds (or [`DEFAULT_LLM_TIMEOUT`]) and an optional provider-specific proxy
+pub(crate) fn http_client(
+    url: &str,
+    timeout_secs: Option<u64>,
+    proxy: Option<&str>,
+) -> reqwest::Result<Client> {
+    let timeout = timeout_secs
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_LLM_TIMEOUT);
+    crate::http::client(url, timeout, proxy)
+}
 
 /// Metadata about token usage from LLM conversion
 #[derive(Debug, Clone, Default, Serialize)]
@@ -34,6 +112,20 @@ pub struct ConversionMetadata {
     pub tokens_used: TokenUsage,
     /// Time taken for the conversion in milliseconds
     pub latency_ms: u64,
+    /// How trustworthy the output looks, from 0 to 1 (see
+    /// [`score_conversion`]); set by the importer after post-processing
+    pub confidence: Option<f32>,
+    /// Ingredients missing from or added to the output, when the importer
+    /// was asked to check them (see [`check_ingredients`])
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ingredient_check: Option<IngredientCheck>,
+    /// Provider that did the conversion; for a fallback chain or race, the
+    /// one whose answer was used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// Hash of the conversion prompt without the recipe; set by the importer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_hash: Option<String>,
 }
 
 /// Result of a conversion operation including the converted text and metadata
@@ -45,6 +137,30 @@ pub struct ConversionResult {
     pub metadata: ConversionMetadata,
 }
 
+/// `result` credited to the provider `name`, unless a provider nested
+/// deeper (in a fallback chain or race) already took the credit
+pub(crate) fn by_provider(mut result: ConversionResult, name: &str) -> ConversionResult {
+    result
+        .metadata
+        .provider
+        .get_or_insert_with(|| name.to_string());
+    result
+}
+
+/// Record a response's token usage and latency on the current `llm` span
+pub(crate) fn record_usage(result: ConversionResult) -> ConversionResult {
+    let span = tracing::Span::current();
+    let usage = &result.metadata.tokens_used;
+    if let Some(tokens) = usage.input_tokens {
+        span.record("input_tokens", tokens);
+    }
+    if let Some(tokens) = usage.output_tokens {
+        span.record("output_tokens", tokens);
+    }
+    span.record("latency_ms", result.metadata.latency_ms);
+    result
+}
+
 /// Unified trait for all converters that transform recipe text to Cooklang format
 #[async_trait]
 pub trait Converter: Send + Sync {
@@ -56,6 +172,49 @@ pub trait Converter: Send + Sync {
         &self,
         ingredients_and_instructions: &str,
     ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>>;
+
+    /// Convert using structured output: the model returns a JSON recipe
+    /// ([`StructuredRecipe`]) and the Cooklang is rendered locally by
+    /// [`render_cooklang`], so markup is always well-formed.
+    ///
+    /// Providers without a JSON mode keep this default, which returns an error.
+    async fn convert_structured(
+        &self,
+        ingredients_and_instructions: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        let _ = ingredients_and_instructions;
+        Err(format!(
+            "Provider '{}' does not support structured output",
+            self.name()
+        )
+        .into())
+    }
+
+    /// Convert recipe photos straight to Cooklang with a multimodal model,
+    /// skipping the separate OCR step.
+    ///
+    /// Providers without image input keep this default, which returns an error.
+    async fn convert_images(
+        &self,
+        images: &[EncodedImage],
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        let _ = images;
+        Err(format!("Provider '{}' does not support image input", self.name()).into())
+    }
+
+    /// Send `prompt` as is and return the model's reply, for LLM work other
+    /// than conversion, such as
+    /// [`TextExtractor`](crate::url_to_text::text::TextExtractor).
+    ///
+    /// Providers without plain prompts keep this default, which returns an
+    /// error.
+    async fn complete(
+        &self,
+        prompt: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        let _ = prompt;
+        Err(format!("Provider '{}' does not support plain prompts", self.name()).into())
+    }
 }
 
 /// Factory function to create a converter by name
@@ -66,13 +225,17 @@ pub trait Converter: Send + Sync {
 ///
 /// # Returns
 /// * `Some(Box<dyn Converter>)` if the converter exists
-/// * `None` if the converter name is not recognized
+/// * `None` if the converter name is not recognized, or its `api_key_cmd` or
+///   `api_key_secret` lookup fails
 pub fn create_converter(
     name: &str,
     config: &crate::config::ProviderConfig,
 ) -> Option<Box<dyn Converter>> {
+    let config = &crate::secrets::with_api_key(name, config)
+        .map_err(|e| tracing::warn!("Failed to read the API key: {}", e))
+        .ok()?;
     match name {
-        "open_ai" => OpenAiConverter::new(config)
+        "open_ai" | "openai" => OpenAiConverter::new(config)
             .ok()
             .map(|c| Box::new(c) as Box<dyn Converter>),
         "anthropic" => AnthropicConverter::new(config)
@@ -87,6 +250,18 @@ pub fn create_converter(
         "ollama" => OllamaConverter::new(config)
             .ok()
             .map(|c| Box::new(c) as Box<dyn Converter>),
+        "mistral" => MistralConverter::new(config)
+            .ok()
+            .map(|c| Box::new(c) as Box<dyn Converter>),
+        "groq" => GroqConverter::new(config)
+            .ok()
+            .map(|c| Box::new(c) as Box<dyn Converter>),
+        // base_url is the replay directory; there is no server
+        #[cfg(feature = "mock")]
+        "mock" => Some(match &config.base_url {
+            Some(dir) => Box::new(ReplayConverter::new(dir)) as Box<dyn Converter>,
+            None => Box::new(MockConverter::new()),
+        }),
         _ => None,
     }
 }
@@ -1,11 +1,18 @@
-use super::{inject_recipe, ConversionMetadata, ConversionResult, Converter, TokenUsage};
+use super::{
+    http_client, inject_structured_recipe_in, parse_structured_recipe, record_usage,
+    render_cooklang, user_message, ConversionMetadata, ConversionResult, Converter, Prompt,
+    TokenUsage,
+};
 use crate::config::ProviderConfig;
+use crate::images_to_text::EncodedImage;
+use crate::ImportError;
 use async_trait::async_trait;
-use log::debug;
 use reqwest::Client;
 use serde_json::{json, Value};
 use std::error::Error;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Instant;
+use tracing::{debug, field::Empty, info, instrument, warn};
 
 pub struct OllamaConverter {
     client: Client,
@@ -13,6 +20,11 @@ pub struct OllamaConverter {
     model: String,
     temperature: f32,
     max_tokens: u32,
+    prompt: Prompt,
+    proxy: Option<String>,
+    auto_pull: bool,
+    /// Set once the server is known to have the model
+    model_ready: AtomicBool,
 }
 
 impl OllamaConverter {
@@ -24,55 +36,169 @@ impl OllamaConverter {
             .unwrap_or_else(|| "http://localhost:11434".to_string());
 
         Ok(OllamaConverter {
-            client: Client::new(),
+            client: http_client(&base_url, config.timeout, config.proxy.as_deref())?,
             base_url,
             model: config.model.clone(),
             temperature: config.temperature,
             max_tokens: config.max_tokens,
+            prompt: Prompt::from_config(config)?,
+            proxy: config.proxy.clone(),
+            auto_pull: config.auto_pull,
+            model_ready: AtomicBool::new(false),
         })
     }
 
     #[doc(hidden)]
     pub fn with_base_url(base_url: String, model: String) -> Self {
         OllamaConverter {
-            client: Client::new(),
+            client: http_client(&base_url, None, None).expect("Failed to create HTTP client"),
             base_url,
             model,
             temperature: 0.7,
             max_tokens: 2000,
+            prompt: Prompt::default(),
+            proxy: None,
+            auto_pull: false,
+            model_ready: AtomicBool::new(false),
         }
     }
-}
 
-#[async_trait]
-impl Converter for OllamaConverter {
-    fn name(&self) -> &str {
-        "ollama"
+    /// Check once that the server has the model, pulling it if `auto_pull`
+    /// is set, so a missing model is reported by name instead of as a 404
+    async fn ensure_model(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if self.model_ready.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let models = match list_models(&self.client, &self.base_url).await {
+            Ok(models) => models,
+            // Not every OpenAI-compatible server has Ollama's own API; the
+            // chat request reports a server that is down
+            Err(e) => {
+                debug!("Couldn't list Ollama models, skipping the check: {}", e);
+                return Ok(());
+            }
+        };
+        if !has_model(&models, &self.model) {
+            if !self.auto_pull {
+                return Err(ImportError::LlmApi {
+                    provider: "Ollama".to_string(),
+                    status: None,
+                    message: format!(
+                        "model {} isn't pulled (have: {}); run `ollama pull {}` or set auto_pull = true in [providers.ollama]",
+                        self.model,
+                        if models.is_empty() {
+                            "none".to_string()
+                        } else {
+                            models.join(", ")
+                        },
+                        self.model
+                    ),
+                }
+                .into());
+            }
+            self.pull().await?;
+        }
+        self.model_ready.store(true, Ordering::Relaxed);
+        Ok(())
     }
 
-    async fn convert(
+    /// Pull the model, logging the download progress
+    async fn pull(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        warn!("Ollama doesn't have {}, pulling it", self.model);
+        let url = format!("{}/api/pull", self.base_url.trim_end_matches('/'));
+        // A pull takes as long as the download does, so no request timeout
+        let client = crate::http::client_builder(&url, self.proxy.as_deref()).build()?;
+        let mut response = client
+            .post(&url)
+            .json(&json!({"model": self.model, "stream": true}))
+            .send()
+            .await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+
+        // Newline-delimited JSON, one object per status update
+        let mut pending = Vec::new();
+        let mut progress = PullProgress::default();
+        let mut done = false;
+        while let Some(chunk) = response.chunk().await? {
+            pending.extend_from_slice(&chunk);
+            while let Some(end) = pending.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = pending.drain(..=end).collect();
+                done |= self.pull_update(&line, &mut progress, status, &headers)?;
+            }
+        }
+        done |= self.pull_update(&pending, &mut progress, status, &headers)?;
+        if !done {
+            return Err(ImportError::LlmApi {
+                provider: "Ollama".to_string(),
+                status: (!status.is_success()).then_some(status.as_u16()),
+                message: format!("pulling {} stopped before it finished", self.model),
+            }
+            .into());
+        }
+        warn!("Pulled {}", self.model);
+        Ok(())
+    }
+
+    /// Log one line of `/api/pull` output; whether it says the pull is done
+    fn pull_update(
         &self,
-        content: &str,
+        line: &[u8],
+        progress: &mut PullProgress,
+        status: reqwest::StatusCode,
+        headers: &reqwest::header::HeaderMap,
+    ) -> Result<bool, ImportError> {
+        let Ok(update) = serde_json::from_slice::<Value>(line) else {
+            return Ok(false);
+        };
+        if let Some(error) = update["error"].as_str() {
+            return Err(ImportError::llm_api("Ollama", status, headers, error));
+        }
+        if let Some(message) = progress.update(&update) {
+            info!("Pulling {}: {}", self.model, message);
+        }
+        Ok(update["status"] == "success")
+    }
+
+    /// Send a single-message chat completion, optionally in JSON mode.
+    ///
+    /// `content` is the user message content: a prompt string, or an array of
+    /// text and image parts.
+    #[instrument(
+        name = "llm",
+        skip_all,
+        fields(provider = self.name(), model = %self.model, input_tokens = Empty, output_tokens = Empty, latency_ms = Empty)
+    )]
+    async fn chat(
+        &self,
+        messages: Vec<Value>,
+        json_mode: bool,
     ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        self.ensure_model().await?;
         let start = Instant::now();
 
+        let mut body = json!({
+            "model": self.model,
+            "messages": messages,
+            "temperature": self.temperature,
+            "max_tokens": self.max_tokens
+        });
+        if json_mode {
+            body["response_format"] = json!({"type": "json_object"});
+        }
+
         // Ollama uses OpenAI-compatible API
         let response = self
             .client
             .post(format!("{}/v1/chat/completions", self.base_url))
-            .json(&json!({
-                "model": self.model,
-                "messages": [
-                    {"role": "user", "content": inject_recipe(content)}
-                ],
-                "temperature": self.temperature,
-                "max_tokens": self.max_tokens
-            }))
+            .json(&body)
             .send()
             .await?;
 
         let latency_ms = start.elapsed().as_millis() as u64;
 
+        let status = response.status();
+        let headers = response.headers().clone();
         let response_body: Value = response.json().await?;
         debug!("Ollama response: {:?}", response_body);
 
@@ -81,7 +207,7 @@ impl Converter for OllamaConverter {
             let error_message = error
                 .as_str()
                 .unwrap_or_else(|| error["message"].as_str().unwrap_or("Unknown error"));
-            return Err(format!("Ollama API error: {}", error_message).into());
+            return Err(ImportError::llm_api("Ollama", status, &headers, error_message).into());
         }
 
         let cooklang_recipe = response_body["choices"][0]["message"]["content"]
@@ -104,7 +230,7 @@ impl Converter for OllamaConverter {
             .as_u64()
             .map(|v| v as u32);
 
-        Ok(ConversionResult {
+        Ok(record_usage(ConversionResult {
             content: cooklang_recipe,
             metadata: ConversionMetadata {
                 model_version,
@@ -113,8 +239,125 @@ impl Converter for OllamaConverter {
                     output_tokens,
                 },
                 latency_ms,
+                confidence: None,
+                ingredient_check: None,
+                provider: None,
+                prompt_hash: None,
             },
-        })
+        }))
+    }
+}
+
+/// Names of the models Ollama at `base_url` has pulled, from `/api/tags`
+pub(crate) async fn list_models(client: &Client, base_url: &str) -> reqwest::Result<Vec<String>> {
+    let tags: Value = client
+        .get(format!("{}/api/tags", base_url.trim_end_matches('/')))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(tags["models"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|m| m["name"].as_str().map(str::to_string))
+        .collect())
+}
+
+/// Whether `model` is among `models`; "llama3" is pulled as "llama3:latest"
+pub(crate) fn has_model(models: &[String], model: &str) -> bool {
+    models
+        .iter()
+        .any(|name| name == model || name.strip_suffix(":latest") == Some(model))
+}
+
+/// What has been logged of a pull: the last status, and how far its
+/// download had got in steps of 10%
+#[derive(Default)]
+struct PullProgress {
+    status: String,
+    percent: u64,
+}
+
+impl PullProgress {
+    /// The message worth logging for an `/api/pull` update, if any: each new
+    /// status, and downloads every 10%
+    fn update(&mut self, update: &Value) -> Option<String> {
+        let status = update["status"].as_str()?;
+        let new_status = status != self.status;
+        if new_status {
+            self.status = status.to_string();
+            self.percent = 0;
+        }
+        let total = update["total"].as_u64().filter(|&total| total > 0);
+        let Some(total) = total else {
+            return new_status.then(|| status.to_string());
+        };
+        let completed = update["completed"].as_u64().unwrap_or(0).min(total);
+        let percent = completed * 100 / total / 10 * 10;
+        if !new_status && percent <= self.percent {
+            return None;
+        }
+        self.percent = percent;
+        Some(format!(
+            "{} {}% of {} MB",
+            status,
+            percent,
+            total.div_ceil(1_000_000)
+        ))
+    }
+}
+
+#[async_trait]
+impl Converter for OllamaConverter {
+    fn name(&self) -> &str {
+        "ollama"
+    }
+
+    async fn convert(
+        &self,
+        content: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        self.chat(self.prompt.messages(content), false).await
+    }
+
+    async fn convert_structured(
+        &self,
+        content: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        let mut result = self
+            .chat(
+                vec![user_message(json!(inject_structured_recipe_in(
+                    content,
+                    self.prompt.language()
+                )))],
+                true,
+            )
+            .await?;
+        result.content = render_cooklang(&parse_structured_recipe(&result.content)?);
+        Ok(result)
+    }
+
+    async fn convert_images(
+        &self,
+        images: &[EncodedImage],
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        let mut parts = vec![json!({"type": "text", "text": self.prompt.image()})];
+        parts.extend(
+            images
+                .iter()
+                .map(|image| json!({"type": "image_url", "image_url": {"url": image.data_url()}})),
+        );
+        self.chat(vec![user_message(Value::Array(parts))], false)
+            .await
+    }
+
+    async fn complete(
+        &self,
+        prompt: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        self.chat(vec![user_message(json!(prompt))], false).await
     }
 }
 
@@ -150,6 +393,168 @@ mod tests {
         mock.assert();
     }
 
+    #[tokio::test]
+    async fn test_missing_model() {
+        let mut server = Server::new_async().await;
+        let _tags = server
+            .mock("GET", "/api/tags")
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"models": [{"name": "llava:13b"}]}"#)
+            .create_async()
+            .await;
+        let chat = server
+            .mock("POST", "/v1/chat/completions")
+            .expect(0)
+            .create_async()
+            .await;
+
+        let converter = OllamaConverter::with_base_url(server.url(), "llama3".to_string());
+        let err = converter.convert("2 eggs").await.unwrap_err().to_string();
+        assert!(err.contains("have: llava:13b"), "{}", err);
+        assert!(err.contains("ollama pull llama3"), "{}", err);
+        chat.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_auto_pull() {
+        let mut server = Server::new_async().await;
+        let tags = server
+            .mock("GET", "/api/tags")
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"models": []}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let pull = server
+            .mock("POST", "/api/pull")
+            .match_body(mockito::Matcher::PartialJson(json!({"model": "llama3"})))
+            .with_body(
+                "{\"status\":\"pulling manifest\"}\n\
+                 {\"status\":\"pulling 6a0746a1ec1a\",\"total\":4000000000,\"completed\":2000000000}\n\
+                 {\"status\":\"success\"}",
+            )
+            .create_async()
+            .await;
+        let _chat = server
+            .mock("POST", "/v1/chat/completions")
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"choices": [{"message": {"content": "Fry @eggs{2}."}}]}"#)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let mut converter = OllamaConverter::with_base_url(server.url(), "llama3".to_string());
+        converter.auto_pull = true;
+        for _ in 0..2 {
+            let result = converter.convert("2 eggs").await.unwrap();
+            assert_eq!(result.content, "Fry @eggs{2}.");
+        }
+        tags.assert_async().await;
+        pull.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_auto_pull_error() {
+        let mut server = Server::new_async().await;
+        let _tags = server
+            .mock("GET", "/api/tags")
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"models": []}"#)
+            .create_async()
+            .await;
+        let _pull = server
+            .mock("POST", "/api/pull")
+            .with_body(
+                "{\"status\":\"pulling manifest\"}\n{\"error\":\"pull model manifest: file does not exist\"}\n",
+            )
+            .create_async()
+            .await;
+
+        let mut converter = OllamaConverter::with_base_url(server.url(), "lama3".to_string());
+        converter.auto_pull = true;
+        let err = converter.convert("2 eggs").await.unwrap_err().to_string();
+        assert!(err.contains("file does not exist"), "{}", err);
+    }
+
+    #[test]
+    fn test_pull_progress() {
+        let mut progress = PullProgress::default();
+        let mut update = |value: Value| progress.update(&value);
+        assert_eq!(
+            update(json!({"status": "pulling manifest"})).as_deref(),
+            Some("pulling manifest")
+        );
+        assert_eq!(update(json!({"status": "pulling manifest"})), None);
+        let layer = |completed: u64| json!({"status": "pulling 6a0746a1ec1a", "total": 4_000_000_000u64, "completed": completed});
+        assert_eq!(
+            update(layer(0)).as_deref(),
+            Some("pulling 6a0746a1ec1a 0% of 4000 MB")
+        );
+        assert_eq!(update(layer(300_000_000)), None);
+        assert_eq!(
+            update(layer(1_000_000_000)).as_deref(),
+            Some("pulling 6a0746a1ec1a 20% of 4000 MB")
+        );
+        assert_eq!(
+            update(json!({"status": "success"})).as_deref(),
+            Some("success")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_few_shot_examples() {
+        let dir = std::env::temp_dir().join(format!("cooklang-examples-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("eggs.txt"), "2 eggs\nFry the eggs.").unwrap();
+        std::fs::write(dir.join("eggs.cook"), "Fry @eggs{2}.").unwrap();
+
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(json!({
+                "messages": [
+                    {"role": "user", "content": "Convert this recipe to Cooklang:\n\n<recipe>\n2 eggs\nFry the eggs.\n</recipe>"},
+                    {"role": "assistant", "content": "Fry @eggs{2}."},
+                ]
+            })))
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"choices": [{"message": {"content": "Toast @bread{1%slice}."}}]}"#)
+            .create_async()
+            .await;
+
+        let config = ProviderConfig {
+            enabled: true,
+            model: "llama3".to_string(),
+            temperature: 0.7,
+            max_tokens: 2000,
+            api_key: None,
+            api_key_cmd: None,
+            api_key_secret: None,
+            base_url: Some(server.url()),
+            endpoint: None,
+            deployment_name: None,
+            api_version: None,
+            project_id: None,
+            auto_pull: false,
+            timeout: None,
+            proxy: None,
+            requests_per_minute: None,
+            tokens_per_minute: None,
+            target_language: None,
+            units: None,
+            prompt: crate::config::PromptTemplateConfig {
+                examples: Some(dir.to_str().unwrap().to_string()),
+                ..Default::default()
+            },
+        };
+
+        let converter = OllamaConverter::new(&config).unwrap();
+        let result = converter.convert("1 slice bread\nToast it.").await.unwrap();
+        assert_eq!(result.content, "Toast @bread{1%slice}.");
+        mock.assert_async().await;
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[tokio::test]
     async fn test_converter_name() {
         let config = ProviderConfig {
@@ -158,11 +563,21 @@ mod tests {
             temperature: 0.7,
             max_tokens: 2000,
             api_key: None,
+            api_key_cmd: None,
+            api_key_secret: None,
             base_url: Some("http://localhost:11434".to_string()),
             endpoint: None,
             deployment_name: None,
             api_version: None,
             project_id: None,
+            auto_pull: false,
+            timeout: None,
+            proxy: None,
+            requests_per_minute: None,
+            tokens_per_minute: None,
+            target_language: None,
+            units: None,
+            prompt: Default::default(),
         };
 
         let converter = OllamaConverter::new(&config).unwrap();
@@ -177,14 +592,65 @@ mod tests {
             temperature: 0.7,
             max_tokens: 2000,
             api_key: None,
+            api_key_cmd: None,
+            api_key_secret: None,
             base_url: None,
             endpoint: None,
             deployment_name: None,
             api_version: None,
             project_id: None,
+            auto_pull: false,
+            timeout: None,
+            proxy: None,
+            requests_per_minute: None,
+            tokens_per_minute: None,
+            target_language: None,
+            units: None,
+            prompt: Default::default(),
         };
 
         let converter = OllamaConverter::new(&config).unwrap();
         assert_eq!(converter.base_url, "http://localhost:11434");
     }
+
+    #[tokio::test]
+    async fn test_provider_timeout() {
+        let mut server = Server::new_async().await;
+        let _mock = server
+            .mock("POST", "/v1/chat/completions")
+            .with_status(200)
+            .with_chunked_body(|w| {
+                std::thread::sleep(std::time::Duration::from_secs(3));
+                w.write_all(br#"{"choices": [{"message": {"content": "late"}}]}"#)
+            })
+            .create();
+
+        let config = ProviderConfig {
+            enabled: true,
+            model: "llama3".to_string(),
+            temperature: 0.7,
+            max_tokens: 2000,
+            api_key: None,
+            api_key_cmd: None,
+            api_key_secret: None,
+            base_url: Some(server.url()),
+            endpoint: None,
+            deployment_name: None,
+            api_version: None,
+            project_id: None,
+            auto_pull: false,
+            timeout: Some(1),
+            proxy: None,
+            requests_per_minute: None,
+            tokens_per_minute: None,
+            target_language: None,
+            units: None,
+            prompt: Default::default(),
+        };
+
+        let converter = OllamaConverter::new(&config).unwrap();
+        let err = converter.convert("2 eggs").await.unwrap_err();
+        let err = err.downcast_ref::<reqwest::Error>().unwrap();
+        assert!(err.is_timeout(), "{}", err);
+    }
 }
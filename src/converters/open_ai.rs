@@ -1,11 +1,17 @@
-use super::{inject_recipe, ConversionMetadata, ConversionResult, Converter, TokenUsage};
+use super::{
+    http_client, inject_structured_recipe_in, parse_structured_recipe, record_usage,
+    render_cooklang, user_message, ConversionMetadata, ConversionResult, Converter, Prompt,
+    TokenUsage,
+};
 use crate::config::ProviderConfig;
+use crate::images_to_text::EncodedImage;
+use crate::ImportError;
 use async_trait::async_trait;
-use log::debug;
 use reqwest::Client;
 use serde_json::{json, Value};
 use std::error::Error;
 use std::time::Instant;
+use tracing::{debug, field::Empty, instrument};
 
 pub struct OpenAiConverter {
     client: Client,
@@ -14,6 +20,7 @@ pub struct OpenAiConverter {
     model: String,
     temperature: f32,
     max_tokens: u32,
+    prompt: Prompt,
 }
 
 impl OpenAiConverter {
@@ -32,12 +39,13 @@ impl OpenAiConverter {
             .unwrap_or_else(|| "https://api.openai.com".to_string());
 
         Ok(OpenAiConverter {
-            client: Client::new(),
+            client: http_client(&base_url, config.timeout, config.proxy.as_deref())?,
             api_key,
             base_url,
             model: config.model.clone(),
             temperature: config.temperature,
             max_tokens: config.max_tokens,
+            prompt: Prompt::from_config(config)?,
         })
     }
 
@@ -51,60 +59,69 @@ impl OpenAiConverter {
         let model = std::env::var("OPENAI_MODEL").unwrap_or_else(|_| "gpt-4.1-mini".to_string());
 
         Ok(OpenAiConverter {
-            client: Client::new(),
+            client: http_client("https://api.openai.com", None, None)?,
             api_key,
             base_url: "https://api.openai.com".to_string(),
             model,
             temperature: 0.9,
             max_tokens: 2000,
+            prompt: Prompt::default(),
         })
     }
 
     #[doc(hidden)]
     pub fn with_base_url(api_key: String, base_url: String, model: String) -> Self {
         OpenAiConverter {
-            client: Client::new(),
+            client: http_client(&base_url, None, None).expect("Failed to create HTTP client"),
             api_key,
             base_url,
             model,
             temperature: 0.9,
             max_tokens: 2000,
+            prompt: Prompt::default(),
         }
     }
-}
-
-#[async_trait]
-impl Converter for OpenAiConverter {
-    fn name(&self) -> &str {
-        "open_ai"
-    }
 
-    async fn convert(
+    /// Send a single-message chat completion, optionally in JSON mode.
+    ///
+    /// `content` is the user message content: a prompt string, or an array of
+    /// text and image parts.
+    #[instrument(
+        name = "llm",
+        skip_all,
+        fields(provider = self.name(), model = %self.model, input_tokens = Empty, output_tokens = Empty, latency_ms = Empty)
+    )]
+    async fn chat(
         &self,
-        content: &str,
+        messages: Vec<Value>,
+        json_mode: bool,
     ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
         let start = Instant::now();
 
+        let mut body = json!({
+            "model": self.model,
+            "messages": messages,
+            "temperature": self.temperature,
+            "max_tokens": self.max_tokens,
+            "stream": false
+        });
+        if json_mode {
+            body["response_format"] = json!({"type": "json_object"});
+        }
+
         let response = self
             .client
             .post(format!("{}/v1/chat/completions", self.base_url))
             .header("Authorization", format!("Bearer {}", self.api_key))
             .header("Accept-Encoding", "identity")
-            .json(&json!({
-                "model": self.model,
-                "messages": [
-                    {"role": "user", "content": inject_recipe(content)}
-                ],
-                "temperature": self.temperature,
-                "max_tokens": self.max_tokens,
-                "stream": false
-            }))
+            .json(&body)
             .send()
             .await?;
 
         let latency_ms = start.elapsed().as_millis() as u64;
 
         let status = response.status();
+        let headers = response.headers().clone();
         let response_text = response
             .text()
             .await
@@ -122,7 +139,7 @@ impl Converter for OpenAiConverter {
         // Check for API error response
         if let Some(error) = response_body.get("error") {
             let error_msg = error["message"].as_str().unwrap_or("Unknown API error");
-            return Err(format!("OpenAI API error: {}", error_msg).into());
+            return Err(ImportError::llm_api("OpenAI", status, &headers, error_msg).into());
         }
 
         let cooklang_recipe = response_body["choices"][0]["message"]["content"]
@@ -145,7 +162,7 @@ impl Converter for OpenAiConverter {
             .as_u64()
             .map(|v| v as u32);
 
-        Ok(ConversionResult {
+        Ok(record_usage(ConversionResult {
             content: cooklang_recipe,
             metadata: ConversionMetadata {
                 model_version,
@@ -154,8 +171,64 @@ impl Converter for OpenAiConverter {
                     output_tokens,
                 },
                 latency_ms,
+                confidence: None,
+                ingredient_check: None,
+                provider: None,
+                prompt_hash: None,
             },
-        })
+        }))
+    }
+}
+
+#[async_trait]
+impl Converter for OpenAiConverter {
+    fn name(&self) -> &str {
+        "open_ai"
+    }
+
+    async fn convert(
+        &self,
+        content: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        self.chat(self.prompt.messages(content), false).await
+    }
+
+    async fn convert_structured(
+        &self,
+        content: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        let mut result = self
+            .chat(
+                vec![user_message(json!(inject_structured_recipe_in(
+                    content,
+                    self.prompt.language()
+                )))],
+                true,
+            )
+            .await?;
+        result.content = render_cooklang(&parse_structured_recipe(&result.content)?);
+        Ok(result)
+    }
+
+    async fn convert_images(
+        &self,
+        images: &[EncodedImage],
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        let mut parts = vec![json!({"type": "text", "text": self.prompt.image()})];
+        parts.extend(
+            images
+                .iter()
+                .map(|image| json!({"type": "image_url", "image_url": {"url": image.data_url()}})),
+        );
+        self.chat(vec![user_message(Value::Array(parts))], false)
+            .await
+    }
+
+    async fn complete(
+        &self,
+        prompt: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        self.chat(vec![user_message(json!(prompt))], false).await
     }
 }
 
@@ -217,6 +290,95 @@ mod tests {
         mock.assert();
     }
 
+    #[tokio::test]
+    async fn test_convert_rate_limited() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/chat/completions")
+            .with_status(429)
+            .with_header("content-type", "application/json")
+            .with_header("retry-after", "20")
+            .with_body(r#"{"error": {"message": "Rate limit reached for requests"}}"#)
+            .create();
+
+        let converter = OpenAiConverter::with_base_url(
+            "fake_api_key".to_string(),
+            server.url(),
+            "gpt-3.5-turbo".to_string(),
+        );
+
+        let err = converter.convert("ingredient\n\nstep").await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ImportError>(),
+            Some(ImportError::RateLimited { retry_after: Some(wait), .. })
+                if *wait == std::time::Duration::from_secs(20)
+        ));
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_convert_structured() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "response_format": {"type": "json_object"}
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                serde_json::json!({
+                    "choices": [{"message": {"content": serde_json::json!({
+                        "ingredients": [{"id": "pasta", "name": "pasta", "quantity": "500", "unit": "g"}],
+                        "sections": [{"name": null, "steps": ["Cook {{ingredient:pasta}}."]}]
+                    }).to_string()}}]
+                })
+                .to_string(),
+            )
+            .create();
+
+        let converter = OpenAiConverter::with_base_url(
+            "fake_api_key".to_string(),
+            server.url(),
+            "gpt-4.1-mini".to_string(),
+        );
+
+        let result = converter
+            .convert_structured("500 g pasta\n\nCook pasta")
+            .await
+            .unwrap();
+        assert_eq!(result.content, "Cook @pasta{500%g}.");
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_convert_images() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::Regex(
+                r#""url":"data:image/png;base64,iVBORw0KGgo=""#.to_string(),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"choices": [{"message": {"content": "Fry @eggs{2}."}}]}"#)
+            .create();
+
+        let converter = OpenAiConverter::with_base_url(
+            "fake_api_key".to_string(),
+            server.url(),
+            "gpt-4o".to_string(),
+        );
+        let images = vec![EncodedImage {
+            mime_type: "image/png".to_string(),
+            data: "iVBORw0KGgo=".to_string(),
+        }];
+
+        let result = converter.convert_images(&images).await.unwrap();
+        assert_eq!(result.content, "Fry @eggs{2}.");
+        mock.assert();
+    }
+
     #[tokio::test]
     async fn test_converter_name() {
         let converter = OpenAiConverter::with_base_url(
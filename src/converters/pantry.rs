@@ -0,0 +1,184 @@
+use super::scan::{find_ingredient, ingredient_at, normalize};
+use crate::model::recipe_lines;
+use serde::Deserialize;
+
+/// How pantry staples are marked in the Cooklang
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PantryMark {
+    /// A note after the amount: `@salt{}(pantry)`
+    #[default]
+    Note,
+    /// The hidden modifier, `@-salt{}`, which shopping lists leave out
+    Hidden,
+    /// The optional modifier, `@?salt{}`
+    Optional,
+}
+
+/// Read a pantry list: one ingredient per line, skipping blank lines and
+/// `#` comments
+pub fn parse_pantry(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Mark the ingredients a recipe shares with `pantry`, so shopping list
+/// tools can skip staples like salt and oil.
+///
+/// Names match regardless of case and plurals, but only as a whole:
+/// "pepper" in the pantry marks `@pepper{}`, not `@bell pepper{1}`. With
+/// [`PantryMark::Note`] the note `note` is added in parentheses after the
+/// amount, or appended to a note the ingredient already has
+/// (`@salt{}(to taste, pantry)`). References, notes, comments and
+/// frontmatter are left alone.
+pub fn mark_pantry_ingredients(
+    cooklang: &str,
+    pantry: &[String],
+    mark: PantryMark,
+    note: &str,
+) -> String {
+    let pantry: Vec<Vec<String>> = pantry
+        .iter()
+        .map(|name| normalize(name))
+        .filter(|words| !words.is_empty())
+        .collect();
+    if pantry.is_empty() {
+        return cooklang.to_string();
+    }
+    let mut output = String::with_capacity(cooklang.len());
+
+    for (in_frontmatter, line) in recipe_lines(cooklang) {
+        let trimmed = line.trim();
+        if in_frontmatter || trimmed.starts_with('>') || trimmed.starts_with("--") {
+            output.push_str(line);
+        } else {
+            output.push_str(&mark_line(line, &pantry, mark, note));
+        }
+    }
+    output
+}
+
+/// Mark the pantry ingredients on one line
+fn mark_line(line: &str, pantry: &[Vec<String>], mark: PantryMark, note: &str) -> String {
+    let mut output = String::with_capacity(line.len());
+    let mut last = 0;
+    let mut search = 0;
+
+    while let Some(at) = find_ingredient(line, search) {
+        search = at + 1;
+        let ingredient = ingredient_at(line, at);
+        let modifiers = &line[ingredient.modifiers];
+        let (start, end) = (ingredient.name.start, ingredient.name.end);
+        // `@name|alias{}` is shopped for as `name`
+        let name = line[start..end].split('|').next().unwrap_or_default();
+        if modifiers.contains('&') || !pantry.contains(&normalize(name)) {
+            continue;
+        }
+        let amount_end = match ingredient.braced {
+            true => match line[end..].find('}') {
+                Some(close) => end + close + 1,
+                None => continue,
+            },
+            false => end,
+        };
+        search = amount_end;
+
+        match mark {
+            PantryMark::Note => {
+                output.push_str(&line[last..end]);
+                if !ingredient.braced {
+                    output.push_str("{}");
+                }
+                output.push_str(&line[end..amount_end]);
+                match line[amount_end..].strip_prefix('(') {
+                    // Add to the ingredient's own note
+                    Some(rest) if rest.contains(')') => {
+                        let close = amount_end + 1 + rest.find(')').unwrap_or_default();
+                        let existing = &line[amount_end + 1..close];
+                        if !existing.split(',').any(|part| part.trim() == note) {
+                            output.push_str(&line[amount_end..close]);
+                            output.push_str(", ");
+                            output.push_str(note);
+                            last = close;
+                        } else {
+                            last = amount_end;
+                        }
+                    }
+                    _ => {
+                        output.push('(');
+                        output.push_str(note);
+                        output.push(')');
+                        last = amount_end;
+                    }
+                }
+            }
+            PantryMark::Hidden | PantryMark::Optional => {
+                let modifier = if mark == PantryMark::Hidden { '-' } else { '?' };
+                if modifiers.contains(modifier) {
+                    continue;
+                }
+                output.push_str(&line[last..at + 1]);
+                output.push(modifier);
+                output.push_str(&line[at + 1..end]);
+                if !ingredient.braced {
+                    output.push_str("{}");
+                }
+                last = end;
+            }
+        }
+    }
+    output.push_str(&line[last..]);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pantry() -> Vec<String> {
+        parse_pantry("# staples\nsalt\n\npepper\nolive oil\n")
+    }
+
+    #[test]
+    fn test_parse_pantry() {
+        assert_eq!(pantry(), vec!["salt", "pepper", "olive oil"]);
+    }
+
+    #[test]
+    fn test_note() {
+        let recipe = "---\ntags: salt\n---\n\n\
+            Fry @bell peppers{2} in @olive oil{2%tbsp} with @Salt{}(to taste).\n\n\
+            Season with @pepper and more @&salt{}.\n\n\
+            > Use flaky @salt.";
+        assert_eq!(
+            mark_pantry_ingredients(recipe, &pantry(), PantryMark::Note, "pantry"),
+            "---\ntags: salt\n---\n\n\
+            Fry @bell peppers{2} in @olive oil{2%tbsp}(pantry) with @Salt{}(to taste, pantry).\n\n\
+            Season with @pepper{}(pantry) and more @&salt{}.\n\n\
+            > Use flaky @salt."
+        );
+
+        // Marking twice changes nothing
+        let marked = mark_pantry_ingredients(recipe, &pantry(), PantryMark::Note, "pantry");
+        assert_eq!(
+            mark_pantry_ingredients(&marked, &pantry(), PantryMark::Note, "pantry"),
+            marked
+        );
+    }
+
+    #[test]
+    fn test_modifiers() {
+        let recipe = "Toss with @olive oil{1%tbsp}, @salt and @-pepper{}.";
+        assert_eq!(
+            mark_pantry_ingredients(recipe, &pantry(), PantryMark::Hidden, "pantry"),
+            "Toss with @-olive oil{1%tbsp}, @-salt{} and @-pepper{}."
+        );
+        assert_eq!(
+            mark_pantry_ingredients(recipe, &pantry(), PantryMark::Optional, "pantry"),
+            "Toss with @?olive oil{1%tbsp}, @?salt{} and @?-pepper{}."
+        );
+    }
+}
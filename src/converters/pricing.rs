@@ -0,0 +1,80 @@
+/// Approximate list prices in USD per million (input, output) tokens, by
+/// model name prefix. The longest matching prefix wins, so "gpt-4o-mini"
+/// isn't priced as "gpt-4o". Prices change; these are only for estimates.
+const PRICES: &[(&str, f64, f64)] = &[
+    ("gpt-4o-mini", 0.15, 0.60),
+    ("gpt-4o", 2.50, 10.00),
+    ("gpt-4.1-nano", 0.10, 0.40),
+    ("gpt-4.1-mini", 0.40, 1.60),
+    ("gpt-4.1", 2.00, 8.00),
+    ("gpt-4-turbo", 10.00, 30.00),
+    ("gpt-4", 30.00, 60.00),
+    ("gpt-3.5-turbo", 0.50, 1.50),
+    ("claude-haiku-4", 1.00, 5.00),
+    ("claude-3-5-haiku", 0.80, 4.00),
+    ("claude-3-haiku", 0.25, 1.25),
+    ("claude-sonnet-4", 3.00, 15.00),
+    ("claude-3-7-sonnet", 3.00, 15.00),
+    ("claude-3-5-sonnet", 3.00, 15.00),
+    ("claude-opus-4", 15.00, 75.00),
+    ("gemini-1.5-flash", 0.075, 0.30),
+    ("gemini-1.5-pro", 1.25, 5.00),
+    ("gemini-2.0-flash", 0.10, 0.40),
+    ("gemini-2.5-flash", 0.30, 2.50),
+    ("gemini-2.5-pro", 1.25, 10.00),
+    ("mistral-small", 0.20, 0.60),
+    ("mistral-medium", 0.40, 2.00),
+    ("mistral-large", 2.00, 6.00),
+    ("llama-3.3-70b", 0.59, 0.79),
+    ("llama-3.1-8b", 0.05, 0.08),
+];
+
+/// Rough token count for a prompt or response (about four characters per token)
+pub fn estimate_tokens(text: &str) -> u64 {
+    (text.chars().count() as u64).div_ceil(4)
+}
+
+/// Estimated cost in USD of a request, or `None` for unknown models.
+///
+/// Ollama runs locally, so it is always free.
+pub fn estimate_cost(
+    provider: &str,
+    model: &str,
+    input_tokens: u64,
+    output_tokens: u64,
+) -> Option<f64> {
+    if provider == "ollama" {
+        return Some(0.0);
+    }
+    let (_, input, output) = PRICES
+        .iter()
+        .filter(|(prefix, _, _)| model.starts_with(prefix))
+        .max_by_key(|(prefix, _, _)| prefix.len())?;
+    Some((input_tokens as f64 * input + output_tokens as f64 * output) / 1_000_000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("2 eggs"), 2);
+        assert_eq!(estimate_tokens(&"a".repeat(400)), 100);
+    }
+
+    #[test]
+    fn test_estimate_cost_uses_longest_prefix() {
+        let mini = estimate_cost("open_ai", "gpt-4o-mini", 1_000_000, 0).unwrap();
+        assert!((mini - 0.15).abs() < 1e-9);
+        let full = estimate_cost("open_ai", "gpt-4o-2024-08-06", 0, 1_000_000).unwrap();
+        assert!((full - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_estimate_cost_unknown_and_local() {
+        assert_eq!(estimate_cost("open_ai", "my-finetune", 1000, 1000), None);
+        assert_eq!(estimate_cost("ollama", "llama3", 1000, 1000), Some(0.0));
+    }
+}
@@ -1,3 +1,7 @@
+use super::UnitSystem;
+use crate::config::ProviderConfig;
+use serde_json::{json, Value};
+use std::path::Path;
 use whatlang::detect;
 
 /// The system prompt template used for converting recipes to Cooklang format.
@@ -14,18 +18,273 @@ use whatlang::detect;
 pub const COOKLANG_CONVERTER_PROMPT: &str = include_str!("prompt.txt");
 
 /// Detects the language of the given text and returns a human-readable language name.
-fn detect_language(text: &str) -> String {
-    detect(text)
-        .map(|info| info.lang().eng_name().to_string())
-        .unwrap_or_else(|| "the original language".to_string())
+pub(crate) fn detect_language(text: &str) -> String {
+    detected_language(text).unwrap_or_else(|| "the original language".to_string())
+}
+
+/// English name of the text's language, or `None` if it can't be detected
+pub(crate) fn detected_language(text: &str) -> Option<String> {
+    detect(text).map(|info| info.lang().eng_name().to_string())
+}
+
+/// Stands in for the recipe text when the recipe is in attached images
+const IMAGE_RECIPE: &str = "The recipe is in the attached image(s). Read all of the text, \
+including handwriting, in page order.";
+
+/// Asks for an example's conversion; the full prompt only comes with the
+/// recipe itself
+const EXAMPLE_REQUEST: &str = "Convert this recipe to Cooklang:";
+
+/// A worked conversion: raw recipe text and the Cooklang it should become
+#[derive(Debug, Clone, PartialEq)]
+pub struct Example {
+    pub input: String,
+    pub output: String,
+}
+
+/// Reads the examples in `dir`: each `<name>.cook` with the `<name>.txt`
+/// it was converted from, in file name order
+fn load_examples(dir: &str) -> Result<Vec<Example>, String> {
+    let entries =
+        std::fs::read_dir(dir).map_err(|e| format!("Failed to read examples {}: {}", dir, e))?;
+    let mut outputs: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "cook"))
+        .collect();
+    outputs.sort();
+
+    let read = |path: &Path| {
+        std::fs::read_to_string(path)
+            .map(|text| text.trim().to_string())
+            .map_err(|e| format!("Failed to read example {}: {}", path.display(), e))
+    };
+    let examples = outputs
+        .iter()
+        .map(|output| {
+            Ok(Example {
+                input: read(&output.with_extension("txt"))?,
+                output: read(output)?,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    if examples.is_empty() {
+        return Err(format!("No .cook examples found in {}", dir));
+    }
+    Ok(examples)
+}
+
+/// A chat message from the user
+pub(crate) fn user_message(content: Value) -> Value {
+    json!({"role": "user", "content": content})
+}
+
+/// The conversion prompt a converter sends: the template (built in, or from
+/// `[converters.prompt]`), the values of its variables and any few-shot
+/// examples
+#[derive(Debug, Clone, Default)]
+pub struct Prompt {
+    /// Custom template text; the built-in prompt when `None`
+    template: Option<String>,
+    /// Language to write the Cooklang in; the recipe's own when `None`
+    language: Option<String>,
+    /// Units the recipe will be converted to, for `{{UNITS}}`
+    units: Option<UnitSystem>,
+    /// Extra instructions, for `{{STYLE}}`
+    style: Option<String>,
+    /// Worked conversions sent ahead of the recipe
+    examples: Vec<Example>,
+}
+
+impl Prompt {
+    /// The prompt for a converter set up with `config`, reading its
+    /// template file and examples
+    pub fn from_config(config: &ProviderConfig) -> Result<Self, String> {
+        let template = match &config.prompt.template {
+            Some(path) => {
+                let template = std::fs::read_to_string(path)
+                    .map_err(|e| format!("Failed to read prompt template {}: {}", path, e))?;
+                if !template.contains("{{RECIPE}}") {
+                    return Err(format!(
+                        "Prompt template {} has no {{{{RECIPE}}}} placeholder",
+                        path
+                    ));
+                }
+                Some(template)
+            }
+            None => None,
+        };
+        let examples = match &config.prompt.examples {
+            Some(dir) => load_examples(dir)?,
+            None => Vec::new(),
+        };
+        Ok(Prompt {
+            template,
+            language: config.target_language.clone(),
+            units: config.units,
+            style: config.prompt.style.clone(),
+            examples,
+        })
+    }
+
+    /// The built-in prompt, asking for the Cooklang in `language`
+    pub fn in_language(language: Option<&str>) -> Self {
+        Prompt {
+            language: language.map(str::to_string),
+            ..Prompt::default()
+        }
+    }
+
+    /// The language the Cooklang is written in, if not the recipe's own
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// The prompt for converting `recipe_content`
+    pub fn recipe(&self, recipe_content: &str) -> String {
+        let language = match &self.language {
+            Some(language) => translation_target(language),
+            None => detect_language(recipe_content),
+        };
+        self.render(recipe_content, &language)
+    }
+
+    /// The few-shot examples
+    pub fn examples(&self) -> &[Example] {
+        &self.examples
+    }
+
+    /// The chat messages for converting `recipe_content`: each example as a
+    /// user request and the assistant's answer, then the prompt
+    pub fn messages(&self, recipe_content: &str) -> Vec<Value> {
+        let mut messages = Vec::with_capacity(self.examples.len() * 2 + 1);
+        for example in &self.examples {
+            messages.push(user_message(json!(format!(
+                "{}\n\n<recipe>\n{}\n</recipe>",
+                EXAMPLE_REQUEST, example.input
+            ))));
+            messages.push(json!({"role": "assistant", "content": example.output}));
+        }
+        messages.push(user_message(json!(self.recipe(recipe_content))));
+        messages
+    }
+
+    /// The prompt for a recipe supplied as attached images
+    pub fn image(&self) -> String {
+        let language = match &self.language {
+            Some(language) => translation_target(language),
+            None => "the language of the recipe in the image(s)".to_string(),
+        };
+        self.render(IMAGE_RECIPE, &language)
+    }
+
+    fn render(&self, recipe: &str, language: &str) -> String {
+        let template = self
+            .template
+            .as_deref()
+            .unwrap_or(COOKLANG_CONVERTER_PROMPT);
+        let style = self.style.as_deref().unwrap_or_default().trim();
+        let units = match self.units {
+            Some(UnitSystem::Metric) => "metric units (g, kg, ml, l, cm, °C)",
+            Some(UnitSystem::Imperial) => {
+                "US customary units (oz, lb, cups, tbsp, tsp, inches, °F)"
+            }
+            None => "the units used in the recipe",
+        };
+        let mut prompt = template
+            .replace("{{LANGUAGE}}", language)
+            .replace("{{UNITS}}", units)
+            .replace("{{STYLE}}", style);
+        if !style.is_empty() && !template.contains("{{STYLE}}") {
+            prompt.push_str("\n\nADDITIONAL INSTRUCTIONS:\n");
+            prompt.push_str(style);
+        }
+        // Last, so recipe text that looks like a placeholder is left alone
+        prompt.replace("{{RECIPE}}", recipe)
+    }
 }
 
 /// Injects the recipe content and detected language into the prompt template.
 pub fn inject_recipe(recipe_content: &str) -> String {
-    let language = detect_language(recipe_content);
-    COOKLANG_CONVERTER_PROMPT
-        .replace("{{RECIPE}}", recipe_content)
-        .replace("{{LANGUAGE}}", &language)
+    inject_recipe_in(recipe_content, None)
+}
+
+/// Like [`inject_recipe`], but asks for the Cooklang in `language` (e.g.
+/// "English" or "en"), translating the recipe if it is written in another
+/// one. `None` keeps the recipe's own language.
+pub fn inject_recipe_in(recipe_content: &str, language: Option<&str>) -> String {
+    Prompt::in_language(language).recipe(recipe_content)
+}
+
+/// Builds the conversion prompt for a recipe supplied as attached images.
+///
+/// Used by multimodal models that read the photo directly instead of
+/// receiving OCR text.
+pub fn inject_image_recipe() -> String {
+    inject_image_recipe_in(None)
+}
+
+/// Like [`inject_image_recipe`], translating the recipe into `language`
+pub fn inject_image_recipe_in(language: Option<&str>) -> String {
+    Prompt::in_language(language).image()
+}
+
+/// Prompt wording for a requested output language. Ingredient names and
+/// steps are translated, quantities and units are not.
+pub(crate) fn translation_target(language: &str) -> String {
+    format!(
+        "{} (translate the recipe if it is in another language, but keep every quantity and unit exactly as written)",
+        language_name(language)
+    )
+}
+
+/// ISO 639-1 codes of common recipe languages
+const LANGUAGE_CODES: &[(&str, &str)] = &[
+    ("ar", "Arabic"),
+    ("cs", "Czech"),
+    ("da", "Danish"),
+    ("de", "German"),
+    ("el", "Greek"),
+    ("en", "English"),
+    ("es", "Spanish"),
+    ("fi", "Finnish"),
+    ("fr", "French"),
+    ("he", "Hebrew"),
+    ("hi", "Hindi"),
+    ("hu", "Hungarian"),
+    ("it", "Italian"),
+    ("ja", "Japanese"),
+    ("ko", "Korean"),
+    ("nl", "Dutch"),
+    ("no", "Norwegian"),
+    ("pl", "Polish"),
+    ("pt", "Portuguese"),
+    ("ro", "Romanian"),
+    ("ru", "Russian"),
+    ("sv", "Swedish"),
+    ("th", "Thai"),
+    ("tr", "Turkish"),
+    ("uk", "Ukrainian"),
+    ("vi", "Vietnamese"),
+    ("zh", "Chinese"),
+];
+
+/// English name for a language code ("de", "deu", "de-AT"), or the input as
+/// given, so "German" or "Brazilian Portuguese" also work
+pub(crate) fn language_name(language: &str) -> String {
+    let language = language.trim();
+    let code = language
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    LANGUAGE_CODES
+        .iter()
+        .find(|(iso, _)| *iso == code)
+        .map(|(_, name)| name.to_string())
+        .or_else(|| {
+            whatlang::Lang::from_code(code.as_str()).map(|lang| lang.eng_name().to_string())
+        })
+        .unwrap_or_else(|| language.to_string())
 }
 
 #[cfg(test)]
@@ -44,6 +303,122 @@ mod tests {
         assert!(COOKLANG_CONVERTER_PROMPT.contains("timer"));
     }
 
+    #[test]
+    fn test_target_language() {
+        assert_eq!(language_name("de"), "German");
+        assert_eq!(language_name("es"), "Spanish");
+        assert_eq!(language_name("pt-BR"), "Portuguese");
+        assert_eq!(language_name("fra"), "French");
+        assert_eq!(
+            language_name("Brazilian Portuguese"),
+            "Brazilian Portuguese"
+        );
+
+        let prompt = inject_recipe_in("200 g Mehl mit 2 Eiern verrühren", Some("en"));
+        assert!(prompt.contains("Provide converted recipe in English (translate"));
+        assert!(inject_image_recipe_in(Some("en")).contains("in English (translate"));
+    }
+
+    #[test]
+    fn test_custom_prompt_template() {
+        let path = std::env::temp_dir().join(format!("cooklang-prompt-{}.txt", std::process::id()));
+        std::fs::write(
+            &path,
+            "Write Cooklang in {{LANGUAGE}} using {{UNITS}}.\n{{STYLE}}\n<recipe>{{RECIPE}}</recipe>",
+        )
+        .unwrap();
+        let mut config = crate::config::ProviderConfig {
+            enabled: true,
+            model: "llama3".to_string(),
+            temperature: 0.0,
+            max_tokens: 4000,
+            api_key: None,
+            api_key_cmd: None,
+            api_key_secret: None,
+            base_url: None,
+            endpoint: None,
+            deployment_name: None,
+            api_version: None,
+            project_id: None,
+            auto_pull: false,
+            timeout: None,
+            proxy: None,
+            requests_per_minute: None,
+            tokens_per_minute: None,
+            target_language: Some("de".to_string()),
+            units: Some(UnitSystem::Metric),
+            prompt: crate::config::PromptTemplateConfig {
+                template: Some(path.to_str().unwrap().to_string()),
+                style: Some("Keep steps short.".to_string()),
+                examples: None,
+            },
+        };
+
+        let prompt = Prompt::from_config(&config).unwrap();
+        assert_eq!(
+            prompt.recipe("2 eggs {{STYLE}}"),
+            "Write Cooklang in German (translate the recipe if it is in another language, \
+             but keep every quantity and unit exactly as written) using metric units \
+             (g, kg, ml, l, cm, °C).\nKeep steps short.\n<recipe>2 eggs {{STYLE}}</recipe>"
+        );
+        assert!(prompt
+            .image()
+            .contains("<recipe>The recipe is in the attached image(s)."));
+
+        // Style without a {{STYLE}} placeholder is appended to the built-in prompt
+        config.prompt.template = None;
+        let prompt = Prompt::from_config(&config).unwrap().recipe("2 eggs");
+        assert!(prompt.starts_with("You are a Cooklang Converter."));
+        assert!(prompt.ends_with("ADDITIONAL INSTRUCTIONS:\nKeep steps short."));
+
+        std::fs::write(&path, "Convert this recipe.").unwrap();
+        config.prompt.template = Some(path.to_str().unwrap().to_string());
+        let err = Prompt::from_config(&config).unwrap_err();
+        assert!(err.contains("{{RECIPE}}"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_load_examples() {
+        let dir =
+            std::env::temp_dir().join(format!("cooklang-load-examples-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert!(load_examples(dir.to_str().unwrap())
+            .unwrap_err()
+            .contains("No .cook examples"));
+
+        std::fs::write(dir.join("2-toast.txt"), "1 slice bread\n").unwrap();
+        std::fs::write(dir.join("2-toast.cook"), "Toast @bread{1%slice}.\n").unwrap();
+        std::fs::write(dir.join("1-eggs.txt"), "2 eggs").unwrap();
+        std::fs::write(dir.join("1-eggs.cook"), "Fry @eggs{2}.").unwrap();
+        std::fs::write(dir.join("notes.md"), "not an example").unwrap();
+
+        let prompt = Prompt {
+            examples: load_examples(dir.to_str().unwrap()).unwrap(),
+            ..Prompt::default()
+        };
+        assert_eq!(prompt.examples()[1].input, "1 slice bread");
+        let messages = prompt.messages("3 apples");
+        assert_eq!(messages.len(), 5);
+        assert_eq!(messages[1]["content"], "Fry @eggs{2}.");
+        assert_eq!(messages[3]["role"], "assistant");
+        assert_eq!(messages[4]["content"], prompt.recipe("3 apples"));
+
+        // A .cook file without its recipe text
+        std::fs::write(dir.join("3-soup.cook"), "Heat @soup{}.").unwrap();
+        assert!(load_examples(dir.to_str().unwrap())
+            .unwrap_err()
+            .contains("3-soup.txt"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_image_prompt_has_no_placeholders() {
+        let prompt = inject_image_recipe();
+        assert!(prompt.contains("attached image"));
+        assert!(!prompt.contains("{{"));
+    }
+
     #[test]
     fn test_prompt_contains_examples() {
         // Verify the prompt includes examples
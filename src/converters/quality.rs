@@ -0,0 +1,319 @@
+use super::scan::{declared_names, normalize};
+use super::syntax::check_cooklang;
+use crate::model::recipe_lines;
+use serde::Serialize;
+use tracing::debug;
+
+/// Below this confidence a conversion should be checked by hand.
+pub const LOW_CONFIDENCE: f32 = 0.7;
+
+/// Confidence lost per quantity left in the step text, and at most.
+const ORPHAN_PENALTY: f32 = 0.05;
+const MAX_ORPHAN_PENALTY: f32 = 0.25;
+/// Confidence lost when the markup has a syntax error.
+const SYNTAX_PENALTY: f32 = 0.3;
+/// Lines with more words than this are instructions, not ingredients.
+const MAX_INGREDIENT_WORDS: usize = 12;
+
+/// Units that mark a quantity, so "200 g" left outside `@flour{200%g}` is
+/// caught but "20 minutes" or "2 eggs" is not.
+const UNITS: &[&str] = &[
+    "g",
+    "gram",
+    "grams",
+    "kg",
+    "mg",
+    "ml",
+    "l",
+    "dl",
+    "cl",
+    "litre",
+    "liter",
+    "litres",
+    "liters",
+    "cup",
+    "cups",
+    "tbsp",
+    "tsp",
+    "tablespoon",
+    "tablespoons",
+    "teaspoon",
+    "teaspoons",
+    "oz",
+    "ounce",
+    "ounces",
+    "lb",
+    "lbs",
+    "pound",
+    "pounds",
+    "pint",
+    "pints",
+    "quart",
+    "quarts",
+];
+
+/// Score a conversion from 0 (unusable) to 1 (nothing looks wrong).
+///
+/// Starts from the share of the source's ingredient lines that show up as
+/// `@ingredient{}` in the Cooklang, then takes off for quantities left in
+/// the step text and for markup errors. `source` is the text that was
+/// converted; its ingredient list is the first block of short lines (plus
+/// later blocks that start with a "For the dough:" heading). Pass an empty
+/// source when the names can't be compared, e.g. after translation.
+pub fn score_conversion(source: &str, cooklang: &str) -> f32 {
+    if cooklang.trim().eq_ignore_ascii_case("no recipe") {
+        return 0.0;
+    }
+
+    let coverage = ingredient_coverage(source, cooklang);
+    let orphans = orphaned_quantities(cooklang);
+    let syntax = check_cooklang(cooklang);
+    debug!(
+        "Conversion quality: {:.0}% of ingredients marked up, {} orphaned quantities, syntax {:?}",
+        coverage * 100.0,
+        orphans.len(),
+        syntax
+    );
+
+    let mut score = coverage - (orphans.len() as f32 * ORPHAN_PENALTY).min(MAX_ORPHAN_PENALTY);
+    if syntax.is_err() {
+        score -= SYNTAX_PENALTY;
+    }
+    // Two decimals are all the precision a heuristic has
+    (score.clamp(0.0, 1.0) * 100.0).round() / 100.0
+}
+
+/// Share of the source's ingredient lines that name an ingredient of the
+/// Cooklang; 1 when the source has no recognizable ingredient list
+fn ingredient_coverage(source: &str, cooklang: &str) -> f32 {
+    let lines = source_ingredients(source).len();
+    if lines == 0 {
+        return 1.0;
+    }
+    let missing = check_ingredients(source, cooklang).missing.len();
+    (lines - missing) as f32 / lines as f32
+}
+
+/// Ingredients that differ between the source's ingredient list and the
+/// generated Cooklang
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct IngredientCheck {
+    /// Source ingredient lines with no matching `@ingredient{}`
+    pub missing: Vec<String>,
+    /// Cooklang ingredients that no source line mentions
+    pub extra: Vec<String>,
+}
+
+impl IngredientCheck {
+    /// Whether both lists agree
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty()
+    }
+}
+
+/// Diff the ingredient list of the converted `source` text against the
+/// ingredients declared in `cooklang`, since LLMs routinely drop items from
+/// long lists.
+///
+/// A source line matches an ingredient when it contains all of the
+/// ingredient's words ("2 large eggs, beaten" and `@eggs{2%large}`), so
+/// both lists must be in the same language. Returns empty lists when the
+/// source has no recognizable ingredient list.
+pub fn check_ingredients(source: &str, cooklang: &str) -> IngredientCheck {
+    let lines = source_ingredients(source);
+    if lines.is_empty() {
+        return IngredientCheck::default();
+    }
+
+    // `@green onions|scallions{}` matches a line with either name
+    let names: Vec<(String, Vec<Vec<String>>)> = declared_names(cooklang)
+        .into_iter()
+        .map(|declared| {
+            let words: Vec<Vec<String>> = declared
+                .split('|')
+                .map(normalize)
+                .filter(|words| !words.is_empty())
+                .collect();
+            let name = declared.split('|').next().unwrap_or_default().trim();
+            (name.to_string(), words)
+        })
+        .filter(|(name, words)| !name.is_empty() && !words.is_empty())
+        .collect();
+    let lines: Vec<(&str, Vec<String>)> = lines.into_iter().map(|l| (l, normalize(l))).collect();
+    let matches = |name: &[Vec<String>], line: &[String]| {
+        name.iter()
+            .any(|words| words.iter().all(|word| line.contains(word)))
+    };
+
+    let missing = lines
+        .iter()
+        .filter(|(_, line)| !names.iter().any(|(_, name)| matches(name, line)))
+        .map(|(line, _)| line.to_string())
+        .collect();
+    let mut extra: Vec<String> = names
+        .iter()
+        .filter(|(_, name)| !lines.iter().any(|(_, line)| matches(name, line)))
+        .map(|(name, _)| name.clone())
+        .collect();
+    extra.sort();
+    extra.dedup();
+    IngredientCheck { missing, extra }
+}
+
+/// The ingredient lines of extracted recipe text, which puts the
+/// ingredients first and a blank line before the instructions
+fn source_ingredients(source: &str) -> Vec<&str> {
+    let blocks: Vec<Vec<&str>> = source
+        .split("\n\n")
+        .map(|block| {
+            block
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<_>>()
+        })
+        .filter(|block| !block.is_empty())
+        .collect();
+    // Without a blank line there is no telling ingredients from steps
+    if blocks.len() < 2 {
+        return Vec::new();
+    }
+
+    let mut lines = Vec::new();
+    for (i, block) in blocks.iter().enumerate() {
+        let sectioned = block[0].ends_with(':');
+        let short = block
+            .iter()
+            .all(|line| line.split_whitespace().count() <= MAX_INGREDIENT_WORDS);
+        if !short || (i > 0 && !sectioned) {
+            break;
+        }
+        lines.extend(
+            block
+                .iter()
+                .copied()
+                .filter(|line| !is_heading(line))
+                .map(|line| line.trim_start_matches(['-', '*', '•', '·', ' '])),
+        );
+    }
+    lines
+}
+
+/// "Ingredients", "For the sauce:" and the like
+fn is_heading(line: &str) -> bool {
+    line.ends_with(':') || line.eq_ignore_ascii_case("ingredients")
+}
+
+/// Quantities with a unit in the step text outside any `{}`, e.g. "add
+/// 200 g @flour{}", which Cooklang tools can't scale or shop for
+fn orphaned_quantities(cooklang: &str) -> Vec<String> {
+    let mut orphans = Vec::new();
+
+    for (in_frontmatter, line) in recipe_lines(cooklang) {
+        let line = line.trim_end_matches(['\r', '\n']);
+        let trimmed = line.trim();
+        if in_frontmatter
+            || trimmed.starts_with('>')
+            || trimmed.starts_with("--")
+            || trimmed.starts_with('=')
+        {
+            continue;
+        }
+
+        // The text outside braces, with markup separated into words
+        let mut prose = String::new();
+        let mut depth = 0usize;
+        for c in trimmed.chars() {
+            match c {
+                '{' => depth += 1,
+                '}' => {
+                    depth = depth.saturating_sub(1);
+                    prose.push(' ');
+                }
+                _ if depth == 0 => prose.push(c),
+                _ => {}
+            }
+        }
+
+        let words: Vec<&str> = prose
+            .split(|c: char| c.is_whitespace() || matches!(c, ',' | '.' | ';' | '(' | ')'))
+            .filter(|word| !word.is_empty())
+            .collect();
+        for (j, word) in words.iter().enumerate() {
+            // "200g" or "200 g"
+            let digits = word.trim_end_matches(|c: char| c.is_alphabetic());
+            let (amount, unit) = if digits.len() < word.len() {
+                (digits, &word[digits.len()..])
+            } else {
+                (*word, words.get(j + 1).copied().unwrap_or_default())
+            };
+            let is_amount = !amount.is_empty()
+                && amount
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || "/½¼¾⅓⅔".contains(c));
+            if is_amount && UNITS.contains(&unit.to_lowercase().as_str()) {
+                orphans.push(format!("{} {}", amount, unit));
+            }
+        }
+    }
+
+    orphans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "Ingredients\n200 g flour\n2 eggs\n1 cup milk\n\nWhisk everything.";
+
+    #[test]
+    fn test_score_conversion() {
+        let good = "Whisk @flour{200%g}, @eggs{2} and @milk{1%cup}.";
+        assert_eq!(score_conversion(SOURCE, good), 1.0);
+
+        // Milk was dropped and its quantity left in the text
+        let dropped = "Whisk @flour{200%g}, @eggs{2} and 1 cup milk.";
+        assert_eq!(orphaned_quantities(dropped), vec!["1 cup"]);
+        assert_eq!(score_conversion(SOURCE, dropped), 0.62);
+
+        let broken = "Whisk @flour{200%g, @eggs{2} and @milk{1%cup}.";
+        assert_eq!(score_conversion(SOURCE, broken), 0.7);
+
+        // Nothing to compare against
+        assert_eq!(score_conversion("", good), 1.0);
+        assert_eq!(score_conversion(SOURCE, "no recipe"), 0.0);
+    }
+
+    #[test]
+    fn test_check_ingredients() {
+        let source = "200 g flour\n2 large eggs, beaten\n1 cup milk\nPinch of salt\n\nWhisk.";
+        let cooklang = "Whisk @flour{200%g}, @eggs{2%large} and @salt{}.\n\n\
+                        Fry in @butter{} and season with more @&salt{}.";
+        let check = check_ingredients(source, cooklang);
+        assert_eq!(check.missing, vec!["1 cup milk"]);
+        assert_eq!(check.extra, vec!["butter"]);
+        assert!(!check.is_ok());
+
+        assert!(check_ingredients("Whisk 2 eggs.", cooklang).is_ok());
+
+        // A canonical name matches the source through its alias
+        let check = check_ingredients(
+            "4 scallions\n200 g noodles\n\nBoil.",
+            "Boil @noodles{200%g}, top with @green onions|scallions{4}.",
+        );
+        assert!(check.is_ok(), "{:?}", check);
+    }
+
+    #[test]
+    fn test_source_ingredients() {
+        let source = "For the dough:\n500g flour\n- 1 tsp salt\n\nFor the filling:\n2 apples\n\n\
+                      Knead the dough for ten minutes until it is smooth and elastic, then rest it.\n\n\
+                      Bake.";
+        assert_eq!(
+            source_ingredients(source),
+            vec!["500g flour", "1 tsp salt", "2 apples"]
+        );
+        // A single block can't be split into ingredients and steps
+        assert!(source_ingredients("2 eggs\nFry them.").is_empty());
+    }
+}
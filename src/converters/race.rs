@@ -0,0 +1,195 @@
+use super::{by_provider, check_cooklang, ConversionResult, Converter};
+use crate::images_to_text::EncodedImage;
+use async_trait::async_trait;
+use futures_util::stream::{FuturesUnordered, StreamExt};
+use std::error::Error;
+use tracing::{debug, warn};
+
+/// Converter that sends the same recipe to several providers at once and
+/// returns the first response that passes the Cooklang syntax check.
+///
+/// Trades cost (every provider is billed) for latency on interactive imports.
+/// Slower providers are dropped as soon as a winner is found.
+pub struct RaceConverter {
+    converters: Vec<Box<dyn Converter>>,
+}
+
+/// Which `Converter` method each raced provider is called with
+#[derive(Clone, Copy)]
+enum Request<'a> {
+    Text(&'a str),
+    Structured(&'a str),
+    Images(&'a [EncodedImage]),
+    Prompt(&'a str),
+}
+
+impl RaceConverter {
+    pub fn new(converters: Vec<Box<dyn Converter>>) -> Result<Self, Box<dyn Error>> {
+        if converters.len() < 2 {
+            return Err("Race strategy needs at least two providers".into());
+        }
+        Ok(RaceConverter { converters })
+    }
+
+    async fn race(
+        &self,
+        request: Request<'_>,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        let mut pending: FuturesUnordered<_> = self
+            .converters
+            .iter()
+            .map(|converter| async move {
+                let result = match request {
+                    Request::Text(content) => converter.convert(content).await,
+                    Request::Structured(content) => converter.convert_structured(content).await,
+                    Request::Images(images) => converter.convert_images(images).await,
+                    Request::Prompt(prompt) => converter.complete(prompt).await,
+                };
+                let name = converter.name();
+                (name, result.map(|conversion| by_provider(conversion, name)))
+            })
+            .collect();
+
+        let mut errors = Vec::new();
+        while let Some((name, result)) = pending.next().await {
+            match result {
+                // Plain prompts aren't answered in Cooklang
+                Ok(conversion) if matches!(request, Request::Prompt(_)) => {
+                    debug!("Race won by '{}'", name);
+                    return Ok(conversion);
+                }
+                Ok(conversion) => match check_cooklang(&conversion.content) {
+                    Ok(()) => {
+                        debug!("Race won by '{}'", name);
+                        return Ok(conversion);
+                    }
+                    Err(e) => {
+                        warn!("Discarding invalid response from '{}': {}", name, e);
+                        errors.push(format!("{}: invalid Cooklang ({})", name, e));
+                    }
+                },
+                Err(e) => {
+                    warn!("Provider '{}' failed during race: {}", name, e);
+                    errors.push(format!("{}: {}", name, e));
+                }
+            }
+        }
+
+        Err(format!("All raced providers failed: {}", errors.join("; ")).into())
+    }
+}
+
+#[async_trait]
+impl Converter for RaceConverter {
+    fn name(&self) -> &str {
+        "race"
+    }
+
+    async fn convert(
+        &self,
+        content: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        self.race(Request::Text(content)).await
+    }
+
+    async fn convert_structured(
+        &self,
+        content: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        self.race(Request::Structured(content)).await
+    }
+
+    async fn convert_images(
+        &self,
+        images: &[EncodedImage],
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        self.race(Request::Images(images)).await
+    }
+
+    async fn complete(
+        &self,
+        prompt: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        self.race(Request::Prompt(prompt)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::converters::OllamaConverter;
+    use mockito::Server;
+
+    fn ollama_body(content: &str) -> String {
+        serde_json::json!({
+            "choices": [{"message": {"content": content}}]
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_race_prefers_valid_response() {
+        let mut fast = Server::new_async().await;
+        let _fast_mock = fast
+            .mock("POST", "/v1/chat/completions")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ollama_body("Add @salt{1%tsp and stir."))
+            .create();
+
+        let mut slow = Server::new_async().await;
+        let slow_mock = slow
+            .mock("POST", "/v1/chat/completions")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ollama_body("Add @salt{1%tsp} and stir."))
+            .create();
+
+        let race = RaceConverter::new(vec![
+            Box::new(OllamaConverter::with_base_url(fast.url(), "a".to_string())),
+            Box::new(OllamaConverter::with_base_url(slow.url(), "b".to_string())),
+        ])
+        .unwrap();
+
+        let result = race.convert("1 tsp salt\n\nStir.").await.unwrap();
+        assert_eq!(result.content, "Add @salt{1%tsp} and stir.");
+        slow_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_race_all_fail() {
+        let mut server = Server::new_async().await;
+        let _mock = server
+            .mock("POST", "/v1/chat/completions")
+            .with_status(500)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"error": "boom"}"#)
+            .expect(2)
+            .create();
+
+        let race = RaceConverter::new(vec![
+            Box::new(OllamaConverter::with_base_url(
+                server.url(),
+                "a".to_string(),
+            )),
+            Box::new(OllamaConverter::with_base_url(
+                server.url(),
+                "b".to_string(),
+            )),
+        ])
+        .unwrap();
+
+        let err = race.convert("text").await.unwrap_err().to_string();
+        assert!(err.contains("All raced providers failed"));
+        assert!(err.contains("boom"));
+    }
+
+    #[test]
+    fn test_race_requires_two_providers() {
+        let single: Vec<Box<dyn Converter>> = vec![Box::new(OllamaConverter::with_base_url(
+            "http://localhost:11434".to_string(),
+            "a".to_string(),
+        ))];
+        assert!(RaceConverter::new(single).is_err());
+    }
+}
@@ -0,0 +1,247 @@
+use super::{estimate_tokens, ConversionResult, Converter};
+use crate::images_to_text::EncodedImage;
+use async_trait::async_trait;
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::debug;
+
+const MINUTE: Duration = Duration::from_secs(60);
+
+/// Tokens counted for each image sent to a vision model
+const IMAGE_TOKENS: u64 = 1000;
+
+/// A provider's per-minute limits from its `[providers.<name>]` table. A
+/// limit of 0 means no limit, like a missing one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RateLimit {
+    pub requests_per_minute: Option<u32>,
+    pub tokens_per_minute: Option<u32>,
+}
+
+impl RateLimit {
+    /// The limits with those set to 0 removed
+    fn without_zeros(self) -> Self {
+        RateLimit {
+            requests_per_minute: self.requests_per_minute.filter(|max| *max > 0),
+            tokens_per_minute: self.tokens_per_minute.filter(|max| *max > 0),
+        }
+    }
+
+    fn is_unlimited(&self) -> bool {
+        self.requests_per_minute.is_none() && self.tokens_per_minute.is_none()
+    }
+}
+
+/// Requests sent to one provider in the last minute, shared by every
+/// conversion that uses it so concurrent imports stay under its limits
+#[derive(Debug)]
+pub struct RateLimiter {
+    limit: RateLimit,
+    /// When each request of the last minute was sent, and its tokens
+    sent: Mutex<VecDeque<(Instant, u64)>>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: RateLimit) -> Self {
+        RateLimiter {
+            limit: limit.without_zeros(),
+            sent: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Wait until a request of `tokens` fits in the limits, then count it.
+    ///
+    /// A request larger than `tokens_per_minute` is let through once
+    /// nothing else was sent in the last minute, rather than never.
+    pub async fn acquire(&self, tokens: u64) {
+        loop {
+            let wait = {
+                let mut sent = self.sent.lock().unwrap();
+                let now = Instant::now();
+                while sent
+                    .front()
+                    .is_some_and(|(at, _)| now.duration_since(*at) >= MINUTE)
+                {
+                    sent.pop_front();
+                }
+
+                let requests_fit = self
+                    .limit
+                    .requests_per_minute
+                    .is_none_or(|max| sent.len() < max as usize);
+                let used: u64 = sent.iter().map(|(_, tokens)| tokens).sum();
+                let tokens_fit = self
+                    .limit
+                    .tokens_per_minute
+                    .is_none_or(|max| used + tokens <= max as u64 || sent.is_empty());
+                if requests_fit && tokens_fit {
+                    sent.push_back((now, tokens));
+                    return;
+                }
+                // Check again when the oldest request leaves the window
+                sent.front()
+                    .map_or(Duration::ZERO, |(at, _)| MINUTE - now.duration_since(*at))
+            };
+            debug!("Rate limit reached, waiting {:?}", wait);
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Limiters by provider name, for the rest of the process
+static LIMITERS: Mutex<Option<HashMap<String, Arc<RateLimiter>>>> = Mutex::new(None);
+
+/// The process-wide limiter for `provider`; `None` without limits. A new
+/// limiter replaces the old one when the limits change.
+pub fn rate_limiter(provider: &str, limit: RateLimit) -> Option<Arc<RateLimiter>> {
+    let limit = limit.without_zeros();
+    if limit.is_unlimited() {
+        return None;
+    }
+    let mut limiters = LIMITERS.lock().unwrap();
+    let limiters = limiters.get_or_insert_with(HashMap::new);
+    let limiter = limiters
+        .entry(provider.to_string())
+        .and_modify(|limiter| {
+            if limiter.limit != limit {
+                *limiter = Arc::new(RateLimiter::new(limit));
+            }
+        })
+        .or_insert_with(|| Arc::new(RateLimiter::new(limit)));
+    Some(Arc::clone(limiter))
+}
+
+/// A provider whose requests wait for its [`RateLimiter`]
+pub struct RateLimitedConverter {
+    converter: Box<dyn Converter>,
+    limiter: Arc<RateLimiter>,
+    /// Tokens every request costs besides its input: the prompt template
+    /// and examples, plus the `max_tokens` reserved for the response
+    overhead: u64,
+}
+
+impl RateLimitedConverter {
+    pub fn new(converter: Box<dyn Converter>, limiter: Arc<RateLimiter>, overhead: u64) -> Self {
+        RateLimitedConverter {
+            converter,
+            limiter,
+            overhead,
+        }
+    }
+}
+
+#[async_trait]
+impl Converter for RateLimitedConverter {
+    fn name(&self) -> &str {
+        self.converter.name()
+    }
+
+    async fn convert(
+        &self,
+        content: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        self.limiter
+            .acquire(estimate_tokens(content) + self.overhead)
+            .await;
+        self.converter.convert(content).await
+    }
+
+    async fn convert_structured(
+        &self,
+        content: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        self.limiter
+            .acquire(estimate_tokens(content) + self.overhead)
+            .await;
+        self.converter.convert_structured(content).await
+    }
+
+    async fn convert_images(
+        &self,
+        images: &[EncodedImage],
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        self.limiter
+            .acquire(images.len() as u64 * IMAGE_TOKENS + self.overhead)
+            .await;
+        self.converter.convert_images(images).await
+    }
+
+    async fn complete(
+        &self,
+        prompt: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        self.limiter
+            .acquire(estimate_tokens(prompt) + self.overhead)
+            .await;
+        self.converter.complete(prompt).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limiter() {
+        let limiter = RateLimiter::new(RateLimit {
+            requests_per_minute: Some(2),
+            tokens_per_minute: Some(1000),
+        });
+        let start = tokio::time::Instant::now();
+        limiter.acquire(100).await;
+        limiter.acquire(100).await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        // The third request waits for the first to leave the window
+        limiter.acquire(100).await;
+        assert!(start.elapsed() >= MINUTE);
+
+        // Too many tokens: waits for the window to empty
+        let start = tokio::time::Instant::now();
+        limiter.acquire(5000).await;
+        assert!(start.elapsed() >= MINUTE);
+    }
+
+    #[test]
+    fn test_rate_limiter_is_shared() {
+        let limit = RateLimit {
+            requests_per_minute: Some(60),
+            tokens_per_minute: None,
+        };
+        let first = rate_limiter("test-shared", limit).unwrap();
+        let second = rate_limiter("test-shared", limit).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        let changed = RateLimit {
+            requests_per_minute: Some(30),
+            ..limit
+        };
+        assert!(!Arc::ptr_eq(
+            &first,
+            &rate_limiter("test-shared", changed).unwrap()
+        ));
+        assert!(rate_limiter("test-shared", RateLimit::default()).is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_zero_is_unlimited() {
+        let zero = RateLimit {
+            requests_per_minute: Some(0),
+            tokens_per_minute: Some(0),
+        };
+        assert!(rate_limiter("test-zero", zero).is_none());
+
+        let limiter = RateLimiter::new(RateLimit {
+            requests_per_minute: Some(0),
+            tokens_per_minute: Some(1000),
+        });
+        let start = tokio::time::Instant::now();
+        for _ in 0..5 {
+            limiter.acquire(100).await;
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+    }
+}
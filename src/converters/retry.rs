@@ -0,0 +1,405 @@
+use super::{ConversionResult, Converter};
+use crate::config::FallbackConfig;
+use crate::images_to_text::EncodedImage;
+use crate::ImportError;
+use async_trait::async_trait;
+use reqwest::header::HeaderMap;
+use std::error::Error;
+use std::time::{Duration, SystemTime};
+use tracing::{debug, warn};
+
+/// How a provider call is retried: how often, how long to wait between
+/// attempts, and the longest `Retry-After` worth waiting for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts per call, including the first (at least one is always made)
+    pub attempts: u32,
+    /// Delay before the first retry, doubled after each failure
+    pub delay: Duration,
+    /// A rate-limited provider asking to wait longer than this fails
+    /// straight away instead
+    pub max_wait: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::from_config(&FallbackConfig::default())
+    }
+}
+
+impl RetryPolicy {
+    /// The retry settings of the `[fallback]` config section
+    pub fn from_config(config: &FallbackConfig) -> Self {
+        RetryPolicy {
+            attempts: config.retry_attempts.max(1),
+            delay: Duration::from_millis(config.retry_delay_ms),
+            max_wait: Duration::from_millis(config.max_retry_wait_ms),
+        }
+    }
+}
+
+/// Which `Converter` method a retried call goes through
+#[derive(Clone, Copy)]
+pub(crate) enum Request<'a> {
+    Text(&'a str),
+    Structured(&'a str),
+    Images(&'a [EncodedImage]),
+    Prompt(&'a str),
+}
+
+impl Request<'_> {
+    async fn send(
+        self,
+        converter: &dyn Converter,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        match self {
+            Request::Text(content) => converter.convert(content).await,
+            Request::Structured(content) => converter.convert_structured(content).await,
+            Request::Images(images) => converter.convert_images(images).await,
+            Request::Prompt(prompt) => converter.complete(prompt).await,
+        }
+    }
+}
+
+/// Call `converter`, retrying failures that may pass: rate limits (after the
+/// wait the provider asked for), timeouts, server errors and malformed
+/// responses. Permanent errors, such as a bad API key or an unknown model,
+/// are returned at once.
+pub(crate) async fn call_with_retries(
+    converter: &dyn Converter,
+    request: Request<'_>,
+    policy: &RetryPolicy,
+) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+    let mut delay = policy.delay;
+    let mut attempt = 1;
+    loop {
+        let error = match request.send(converter).await {
+            Ok(conversion) => {
+                debug!(
+                    "Converted with '{}' (attempt {})",
+                    converter.name(),
+                    attempt
+                );
+                return Ok(conversion);
+            }
+            Err(e) => e,
+        };
+        warn!(
+            "Provider '{}' failed (attempt {}/{}): {}",
+            converter.name(),
+            attempt,
+            policy.attempts,
+            error
+        );
+        if attempt >= policy.attempts || !is_retryable(error.as_ref()) {
+            return Err(error);
+        }
+
+        let wait = match error.downcast_ref::<ImportError>() {
+            Some(ImportError::RateLimited {
+                retry_after: Some(wait),
+                ..
+            }) => *wait,
+            _ => delay,
+        };
+        if wait > policy.max_wait {
+            debug!(
+                "Not waiting {:?} for '{}' (max_retry_wait_ms is {:?})",
+                wait,
+                converter.name(),
+                policy.max_wait
+            );
+            return Err(error);
+        }
+        tokio::time::sleep(wait).await;
+        delay *= 2;
+        attempt += 1;
+    }
+}
+
+/// Whether a provider error may go away on retry.
+///
+/// Rate limits, timeouts, connection failures, HTTP 408/409/425 and 5xx
+/// (including Anthropic's 529 "overloaded") are; other API errors with a
+/// 4xx status (bad request, bad key, unknown model) are not. Errors of
+/// unknown origin, like an unparseable response, count as retryable.
+pub fn is_retryable(error: &(dyn Error + Send + Sync + 'static)) -> bool {
+    if let Some(error) = error.downcast_ref::<ImportError>() {
+        return match error {
+            ImportError::RateLimited { .. } | ImportError::Timeout(_) => true,
+            ImportError::LlmApi {
+                status: Some(status),
+                ..
+            } => matches!(status, 408 | 409 | 425 | 429) || *status >= 500,
+            // An error in a successful response
+            ImportError::LlmApi { status: None, .. } => false,
+            ImportError::FetchError(e) => !e.is_builder(),
+            _ => false,
+        };
+    }
+    match error.downcast_ref::<reqwest::Error>() {
+        Some(e) => !e.is_builder(),
+        None => true,
+    }
+}
+
+/// How long a rate-limited response asks to wait before retrying.
+///
+/// Reads `retry-after-ms`, then `Retry-After` (seconds or an HTTP date),
+/// then OpenAI-style `x-ratelimit-reset-*` headers for the exhausted limit.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name: &str| headers.get(name)?.to_str().ok().map(str::trim);
+
+    if let Some(ms) = header("retry-after-ms").and_then(|ms| ms.parse::<f64>().ok()) {
+        return Duration::try_from_secs_f64(ms / 1000.0).ok();
+    }
+    if let Some(value) = header("retry-after") {
+        if let Ok(secs) = value.parse::<f64>() {
+            return Duration::try_from_secs_f64(secs).ok();
+        }
+        if let Ok(date) = httpdate::parse_http_date(value) {
+            return Some(
+                date.duration_since(SystemTime::now())
+                    .unwrap_or(Duration::ZERO),
+            );
+        }
+    }
+    ["requests", "tokens"]
+        .iter()
+        .filter(|limit| header(&format!("x-ratelimit-remaining-{}", limit)) == Some("0"))
+        .filter_map(|limit| header(&format!("x-ratelimit-reset-{}", limit)))
+        .filter_map(parse_reset)
+        .max()
+}
+
+/// A reset time like `"20ms"`, `"1.5s"` or `"6m0s"`
+fn parse_reset(value: &str) -> Option<Duration> {
+    let mut total = 0.0;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .filter(|&end| end > 0)?;
+        let amount: f64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+        let unit = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        total += amount
+            * match &rest[..unit] {
+                "ms" => 0.001,
+                "s" => 1.0,
+                "m" => 60.0,
+                "h" => 3600.0,
+                _ => return None,
+            };
+        rest = &rest[unit..];
+    }
+    Duration::try_from_secs_f64(total).ok()
+}
+
+/// A single provider with retries, for conversions without a `[fallback]`
+/// chain (which retries each of its providers itself)
+pub struct RetryConverter {
+    converter: Box<dyn Converter>,
+    policy: RetryPolicy,
+}
+
+impl RetryConverter {
+    pub fn new(converter: Box<dyn Converter>, policy: RetryPolicy) -> Self {
+        RetryConverter { converter, policy }
+    }
+}
+
+#[async_trait]
+impl Converter for RetryConverter {
+    /// The wrapped provider's name, so prompts and cache keys are the same
+    /// with and without retries
+    fn name(&self) -> &str {
+        self.converter.name()
+    }
+
+    async fn convert(
+        &self,
+        content: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        call_with_retries(
+            self.converter.as_ref(),
+            Request::Text(content),
+            &self.policy,
+        )
+        .await
+    }
+
+    async fn convert_structured(
+        &self,
+        content: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        call_with_retries(
+            self.converter.as_ref(),
+            Request::Structured(content),
+            &self.policy,
+        )
+        .await
+    }
+
+    async fn convert_images(
+        &self,
+        images: &[EncodedImage],
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        call_with_retries(
+            self.converter.as_ref(),
+            Request::Images(images),
+            &self.policy,
+        )
+        .await
+    }
+
+    async fn complete(
+        &self,
+        prompt: &str,
+    ) -> Result<ConversionResult, Box<dyn Error + Send + Sync>> {
+        call_with_retries(
+            self.converter.as_ref(),
+            Request::Prompt(prompt),
+            &self.policy,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::converters::OllamaConverter;
+    use mockito::Server;
+    use reqwest::header::HeaderValue;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let secs = Duration::from_secs;
+        assert_eq!(
+            retry_after(&headers(&[("retry-after", "20")])),
+            Some(secs(20))
+        );
+        assert_eq!(
+            retry_after(&headers(&[
+                ("retry-after-ms", "1500"),
+                ("retry-after", "2")
+            ])),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(
+            retry_after(&headers(&[(
+                "retry-after",
+                "Wed, 21 Oct 2015 07:28:00 GMT"
+            )])),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(
+            retry_after(&headers(&[
+                ("x-ratelimit-remaining-requests", "12"),
+                ("x-ratelimit-reset-requests", "6m0s"),
+                ("x-ratelimit-remaining-tokens", "0"),
+                ("x-ratelimit-reset-tokens", "1.5s"),
+            ])),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(retry_after(&headers(&[])), None);
+        assert_eq!(parse_reset("1h2m3s"), Some(secs(3723)));
+        assert_eq!(parse_reset("20ms"), Some(Duration::from_millis(20)));
+        assert_eq!(parse_reset("soon"), None);
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        let api = |status| -> Box<dyn Error + Send + Sync> {
+            Box::new(ImportError::LlmApi {
+                provider: "OpenAI".to_string(),
+                status,
+                message: "error".to_string(),
+            })
+        };
+        assert!(is_retryable(api(Some(500)).as_ref()));
+        assert!(is_retryable(api(Some(529)).as_ref()));
+        assert!(!is_retryable(api(Some(401)).as_ref()));
+        assert!(!is_retryable(api(Some(404)).as_ref()));
+        let rate_limited: Box<dyn Error + Send + Sync> = Box::new(ImportError::RateLimited {
+            provider: "OpenAI".to_string(),
+            retry_after: None,
+        });
+        assert!(is_retryable(rate_limited.as_ref()));
+        let unknown: Box<dyn Error + Send + Sync> = "No content in response".into();
+        assert!(is_retryable(unknown.as_ref()));
+    }
+
+    fn retrying(url: String, attempts: u32) -> RetryConverter {
+        RetryConverter::new(
+            Box::new(OllamaConverter::with_base_url(url, "a".to_string())),
+            RetryPolicy {
+                attempts,
+                delay: Duration::from_secs(60),
+                max_wait: Duration::from_secs(1),
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_retry_converter() {
+        let mut server = Server::new_async().await;
+        let limited = server
+            .mock("POST", "/v1/chat/completions")
+            .with_status(429)
+            .with_header("content-type", "application/json")
+            .with_header("retry-after-ms", "10")
+            .with_body(r#"{"error": "slow down"}"#)
+            .expect(2)
+            .create();
+
+        // Retried after the 10 ms asked for, not the 60 s delay
+        let start = std::time::Instant::now();
+        let err = retrying(server.url(), 2)
+            .convert("2 eggs")
+            .await
+            .unwrap_err();
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(matches!(
+            err.downcast_ref::<ImportError>(),
+            Some(ImportError::RateLimited { .. })
+        ));
+        limited.assert();
+
+        // Longer than max_wait: not worth waiting for
+        let mut server = Server::new_async().await;
+        let limited = server
+            .mock("POST", "/v1/chat/completions")
+            .with_status(429)
+            .with_header("content-type", "application/json")
+            .with_header("retry-after", "120")
+            .with_body(r#"{"error": "slow down"}"#)
+            .expect(1)
+            .create();
+        assert!(retrying(server.url(), 3).convert("2 eggs").await.is_err());
+        limited.assert();
+
+        // Neither is a bad key
+        let mut server = Server::new_async().await;
+        let unauthorized = server
+            .mock("POST", "/v1/chat/completions")
+            .with_status(401)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"error": "invalid api key"}"#)
+            .expect(1)
+            .create();
+        assert!(retrying(server.url(), 3).convert("2 eggs").await.is_err());
+        unauthorized.assert();
+    }
+}
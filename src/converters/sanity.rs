@@ -0,0 +1,175 @@
+use crate::model::split_frontmatter;
+
+/// Source instructions shorter than this are too small to judge.
+const MIN_SOURCE_SENTENCES: usize = 6;
+const MIN_SOURCE_CHARS: usize = 300;
+/// At most this many source sentences may be folded into one output step.
+const MAX_SENTENCES_PER_STEP: usize = 5;
+/// Output prose must keep at least this share of the source prose.
+const MIN_LENGTH_RATIO: f32 = 0.35;
+
+/// Check that a conversion kept the recipe rather than summarizing it.
+///
+/// Compares the instruction sentences of the source text with the steps and
+/// prose length of the generated Cooklang. Ingredient-list lines are ignored
+/// since they are folded into the steps. Returns a description of the problem
+/// when the output looks like a summary.
+pub fn check_completeness(source: &str, output: &str) -> Result<(), String> {
+    if output.trim().eq_ignore_ascii_case("no recipe") {
+        return Ok(());
+    }
+
+    let instructions: Vec<&str> = source
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.split_whitespace().count() >= 5)
+        .collect();
+    let source_sentences: usize = instructions.iter().map(|l| count_sentences(l)).sum();
+    let source_chars: usize = instructions.iter().map(|l| l.chars().count()).sum();
+
+    let steps = output_steps(output);
+    let output_chars: usize = steps.iter().map(|s| prose_len(s)).sum();
+
+    if source_sentences >= MIN_SOURCE_SENTENCES
+        && steps.len() * MAX_SENTENCES_PER_STEP < source_sentences
+    {
+        return Err(format!(
+            "Output has {} steps for {} instruction sentences",
+            steps.len(),
+            source_sentences
+        ));
+    }
+
+    if source_chars >= MIN_SOURCE_CHARS
+        && (output_chars as f32) < source_chars as f32 * MIN_LENGTH_RATIO
+    {
+        return Err(format!(
+            "Output is {} characters long for {} characters of instructions",
+            output_chars, source_chars
+        ));
+    }
+
+    Ok(())
+}
+
+/// Count sentences in a line by their terminating punctuation.
+///
+/// A period after a digit is treated as step numbering ("1. Preheat").
+fn count_sentences(line: &str) -> usize {
+    let chars: Vec<char> = line.chars().collect();
+    let ends = chars
+        .iter()
+        .enumerate()
+        .filter(|&(i, &c)| {
+            let numbering = c == '.' && i > 0 && chars[i - 1].is_ascii_digit();
+            matches!(c, '.' | '!' | '?')
+                && !numbering
+                && chars.get(i + 1).is_none_or(|next| next.is_whitespace())
+        })
+        .count();
+    // A trailing sentence without punctuation still counts
+    let unterminated = !matches!(chars.last(), Some('.' | '!' | '?'));
+    ends + usize::from(unterminated)
+}
+
+/// Paragraphs of the Cooklang body that are steps (not notes, comments,
+/// section headers, metadata or frontmatter).
+fn output_steps(output: &str) -> Vec<String> {
+    let (_, body) = split_frontmatter(output);
+
+    let mut steps = Vec::new();
+    let mut current = String::new();
+    for line in body.lines().map(str::trim) {
+        let skip = line.starts_with('>')
+            || line.starts_with("--")
+            || line.starts_with('=')
+            || line.starts_with("[-");
+        if line.is_empty() || skip {
+            if !current.is_empty() {
+                steps.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(line);
+    }
+    if !current.is_empty() {
+        steps.push(current);
+    }
+    steps
+}
+
+/// Length of a step without Cooklang markup.
+fn prose_len(step: &str) -> usize {
+    let mut len = 0;
+    let mut in_braces = false;
+    for c in step.chars() {
+        match c {
+            '{' => in_braces = true,
+            '}' => in_braces = false,
+            '@' | '#' | '~' => {}
+            _ if !in_braces => len += 1,
+            _ => {}
+        }
+    }
+    len
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "200 g flour\n1 tsp salt\n\n\
+        Preheat the oven to 200C and line a tray with paper. \
+        Mix the flour and salt in a large bowl until combined. \
+        Add the water slowly and knead for ten minutes until smooth. \
+        Cover the dough and leave it to rest in a warm place. \
+        Shape the dough into a round loaf on the tray. \
+        Bake for thirty minutes until golden and hollow sounding.";
+
+    #[test]
+    fn test_full_conversion_passes() {
+        let output = "Preheat the oven to 200C and line a tray with paper.\n\n\
+            Mix the @flour{200%g} and @salt{1%tsp} in a #large bowl{} until combined.\n\n\
+            Add the water slowly and knead for ~{10%minutes} until smooth.\n\n\
+            Cover the dough and leave it to rest in a warm place.\n\n\
+            Shape the dough into a round loaf on the tray.\n\n\
+            Bake for ~{30%minutes} until golden and hollow sounding.";
+        assert!(check_completeness(SOURCE, output).is_ok());
+    }
+
+    #[test]
+    fn test_summary_is_rejected() {
+        let output = "Make bread with @flour{200%g} and @salt{1%tsp}.";
+        let err = check_completeness(SOURCE, output).unwrap_err();
+        assert!(err.contains("1 steps"));
+    }
+
+    #[test]
+    fn test_short_output_is_rejected() {
+        let output =
+            "Preheat.\n\nMix @flour{200%g}.\n\nAdd @salt{1%tsp}.\n\nRest.\n\nShape.\n\nBake.";
+        let err = check_completeness(SOURCE, output).unwrap_err();
+        assert!(err.contains("characters"));
+    }
+
+    #[test]
+    fn test_short_source_and_no_recipe_are_skipped() {
+        assert!(check_completeness("2 eggs\n\nFry the eggs.", "Fry.").is_ok());
+        assert!(check_completeness(SOURCE, "no recipe").is_ok());
+    }
+
+    #[test]
+    fn test_count_sentences() {
+        assert_eq!(count_sentences("1. Preheat the oven. Mix well!"), 2);
+        assert_eq!(count_sentences("Stir until thick"), 1);
+    }
+
+    #[test]
+    fn test_notes_and_sections_are_not_steps() {
+        let steps = output_steps("---\ntitle: x\n---\n\n== Dough ==\n\nMix.\n\n> Tip\n\nBake.");
+        assert_eq!(steps, vec!["Mix.", "Bake."]);
+    }
+}
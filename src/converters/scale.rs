@@ -0,0 +1,210 @@
+use super::dedup::{format_quantity, parse_quantity};
+use super::scan::{amount_at, find_ingredient, ingredient_at};
+use crate::model::recipe_lines;
+
+/// Multiply a recipe's ingredient quantities and servings by `factor`.
+///
+/// Numeric quantities in `@ingredient{quantity%unit}` are scaled; text
+/// quantities ("pinch"), fixed quantities (`@salt{=1%tsp}`), cookware and
+/// timers are left as they are. A leading number in the `servings`
+/// metadata is scaled too, so the recipe still says how many it feeds.
+/// Notes and comments are not touched.
+///
+/// This is a lexical rewrite of the amounts, line by line, rather than a
+/// round trip through a Cooklang parser, so everything else in the recipe
+/// keeps its exact spelling. An escaped `\@` is text, and amounts that
+/// aren't closed on their line or have more than one `%` are left as
+/// written.
+pub fn scale_recipe(cooklang: &str, factor: f64) -> String {
+    let mut output = String::with_capacity(cooklang.len());
+
+    for (in_frontmatter, line) in recipe_lines(cooklang) {
+        let trimmed = line.trim();
+        if in_frontmatter {
+            output.push_str(&scale_servings(line, "", factor));
+        } else if trimmed.starts_with(">>") {
+            output.push_str(&scale_servings(line, ">>", factor));
+        } else if trimmed.starts_with('>') || trimmed.starts_with("--") {
+            output.push_str(line);
+        } else {
+            output.push_str(&scale_ingredients(line, factor));
+        }
+    }
+    output
+}
+
+/// Scale the quantities of the ingredients on one line
+fn scale_ingredients(line: &str, factor: f64) -> String {
+    map_amounts(line, |quantity, unit, fixed| {
+        (!fixed).then(|| (quantity * factor, unit.map(str::to_string)))
+    })
+}
+
+/// Rewrite the numeric `@ingredient{quantity%unit}` amounts on one line.
+///
+/// `map` gets the quantity, the unit (if any) and whether the quantity is
+/// fixed (`{=1%tsp}`), and returns the new quantity and unit, or `None` to
+/// keep the amount as written. Text quantities are always kept.
+pub(super) fn map_amounts(
+    line: &str,
+    mut map: impl FnMut(f64, Option<&str>, bool) -> Option<(f64, Option<String>)>,
+) -> String {
+    let mut output = String::with_capacity(line.len());
+    let mut last = 0;
+    let mut search = 0;
+
+    while let Some(at) = find_ingredient(line, search) {
+        search = at + 1;
+
+        // Ingredients without an amount, or whose `}` isn't on this line
+        let Some(amount) = amount_at(line, &ingredient_at(line, at)) else {
+            continue;
+        };
+        let (open, close) = (amount.start, amount.end);
+        search = close + 1;
+
+        let amount = &line[amount];
+        let (quantity, unit) = match amount.split_once('%') {
+            // `{1%cup%sifted}` isn't a quantity and unit
+            Some((_, unit)) if unit.contains('%') => continue,
+            Some((quantity, unit)) => (quantity, Some(unit.trim())),
+            None => (amount, None),
+        };
+        let (quantity, fixed) = match quantity.trim_start().strip_prefix('=') {
+            Some(quantity) => (quantity, true),
+            None => (quantity, false),
+        };
+        let Some((quantity, unit)) = parse_quantity(quantity).and_then(|q| map(q, unit, fixed))
+        else {
+            continue;
+        };
+
+        output.push_str(&line[last..open]);
+        if fixed {
+            output.push('=');
+        }
+        output.push_str(&format_quantity(quantity));
+        if let Some(unit) = unit {
+            output.push('%');
+            output.push_str(&unit);
+        }
+        last = close;
+    }
+    output.push_str(&line[last..]);
+    output
+}
+
+/// Scale the leading number of a `servings` metadata line
+fn scale_servings(line: &str, prefix: &str, factor: f64) -> String {
+    let Some((key, value)) = line
+        .trim_start()
+        .strip_prefix(prefix)
+        .and_then(|rest| rest.split_once(':'))
+    else {
+        return line.to_string();
+    };
+    if !key.trim().eq_ignore_ascii_case("servings") {
+        return line.to_string();
+    }
+
+    let value_start = line.len() - value.len();
+    let number_start = value_start + (value.len() - value.trim_start().len());
+    let number_end = line[number_start..]
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == ','))
+        .map_or(line.len(), |i| number_start + i);
+    match parse_quantity(&line[number_start..number_end]) {
+        Some(servings) => format!(
+            "{}{}{}",
+            &line[..number_start],
+            format_quantity(servings * factor),
+            &line[number_end..]
+        ),
+        None => line.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scale_ingredients() {
+        let recipe = "Mix @flour{500%g}, @eggs{2} and @milk{1 1/2%cups}.\n\
+                      Season with @salt{=1%tsp}, @pepper{pinch} and @&flour{50%g}.\n\
+                      Bake in a #baking dish{1} for ~{30%minutes}.";
+        assert_eq!(
+            scale_recipe(recipe, 2.0),
+            "Mix @flour{1000%g}, @eggs{4} and @milk{3%cups}.\n\
+             Season with @salt{=1%tsp}, @pepper{pinch} and @&flour{100%g}.\n\
+             Bake in a #baking dish{1} for ~{30%minutes}."
+        );
+    }
+
+    #[test]
+    fn test_scale_servings_metadata() {
+        let recipe = "---\ntitle: Pancakes\nservings: 4 people\n---\n\nMix @eggs{2}.";
+        assert_eq!(
+            scale_recipe(recipe, 0.5),
+            "---\ntitle: Pancakes\nservings: 2 people\n---\n\nMix @eggs{1}."
+        );
+
+        assert_eq!(
+            scale_recipe(">> servings: 2\nAdd @butter{25%g}.", 3.0),
+            ">> servings: 6\nAdd @butter{75%g}."
+        );
+    }
+
+    #[test]
+    fn test_crlf_and_unclosed_frontmatter() {
+        let recipe = "\r\n---\r\nservings: 4\r\nsource: @home{2}\r\n---\r\nMix @eggs{2}.\r\n";
+        assert_eq!(
+            scale_recipe(recipe, 2.0),
+            "\r\n---\r\nservings: 8\r\nsource: @home{2}\r\n---\r\nMix @eggs{4}.\r\n"
+        );
+        // Without a closing fence the `---` is just a line of the steps
+        assert_eq!(
+            scale_recipe("---\nservings: 4\nMix @eggs{2}.", 2.0),
+            "---\nservings: 4\nMix @eggs{4}."
+        );
+    }
+
+    #[test]
+    fn test_multi_word_names_next_to_braceless_ingredients() {
+        assert_eq!(
+            scale_recipe(
+                "Season with @salt, @olive oil{2%tbsp} and @?parsley{1%bunch}.",
+                2.0
+            ),
+            "Season with @salt, @olive oil{4%tbsp} and @?parsley{2%bunch}."
+        );
+    }
+
+    #[test]
+    fn test_escaped_and_unclosed_amounts_are_kept() {
+        assert_eq!(
+            scale_recipe("Email chef\\@home{2} for @eggs{2}.", 2.0),
+            "Email chef\\@home{2} for @eggs{4}."
+        );
+        assert_eq!(
+            scale_recipe("Add @flour{500%g and stir.\nAdd @eggs{2}.", 2.0),
+            "Add @flour{500%g and stir.\nAdd @eggs{4}."
+        );
+        assert_eq!(
+            scale_recipe("Add @flour{500%g and @eggs{2}.", 2.0),
+            "Add @flour{500%g and @eggs{4}."
+        );
+        assert_eq!(
+            scale_recipe("Sift @flour{1%cup%sifted} with @sugar{50%g}.", 2.0),
+            "Sift @flour{1%cup%sifted} with @sugar{100%g}."
+        );
+    }
+
+    #[test]
+    fn test_notes_and_comments_are_kept() {
+        let recipe = "> Use @eggs{2} at room temperature\n-- @salt{1%tsp}\nAdd @eggs{2}.";
+        assert_eq!(
+            scale_recipe(recipe, 2.0),
+            "> Use @eggs{2} at room temperature\n-- @salt{1%tsp}\nAdd @eggs{4}."
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use crate::model::recipe_lines;
+use std::ops::Range;
+
+/// Lowercase singular words, so "Cherry Tomatoes" matches "cherry tomato"
+pub(super) fn normalize(name: &str) -> Vec<String> {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            if let Some(stem) = word.strip_suffix("ies") {
+                format!("{}y", stem)
+            } else if ["oes", "shes", "ches", "xes"]
+                .iter()
+                .any(|s| word.ends_with(s))
+            {
+                word[..word.len() - 2].to_string()
+            } else if word.len() > 3 && word.ends_with('s') && !word.ends_with("ss") {
+                word[..word.len() - 1].to_string()
+            } else {
+                word.to_string()
+            }
+        })
+        .collect()
+}
+
+/// Names of the ingredients declared in a recipe, outside notes, comments
+/// and frontmatter. References (`@&name`) are skipped.
+pub(super) fn ingredient_names(cooklang: &str) -> Vec<String> {
+    declared_names(cooklang)
+        .into_iter()
+        // `@name|alias{}` - the first name is the one that's shopped for
+        .filter_map(|name| {
+            let name = name.split('|').next().unwrap_or_default().trim();
+            (!name.is_empty()).then(|| name.to_string())
+        })
+        .collect()
+}
+
+/// What can come between `@` and an ingredient's name: `&` for a reference
+/// to an earlier ingredient, `?` optional, `-` hidden, `+` and `=` for how
+/// it's scaled
+pub(super) const MODIFIERS: [char; 5] = ['&', '?', '-', '+', '='];
+
+/// An `@ingredient` in a line of Cooklang, as byte ranges of the line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct IngredientSpan {
+    /// The [`MODIFIERS`] after the `@`
+    pub modifiers: Range<usize>,
+    /// The name as written, with any `|alias`
+    pub name: Range<usize>,
+    /// Whether `{amount}` follows the name
+    pub braced: bool,
+}
+
+/// The byte offset of the first `@` at or after `from` that marks an
+/// ingredient; an escaped `\@` is text
+pub(super) fn find_ingredient(line: &str, from: usize) -> Option<usize> {
+    let mut search = from;
+    while let Some(at) = line[search..].find('@').map(|i| search + i) {
+        if !line[..at].ends_with('\\') {
+            return Some(at);
+        }
+        search = at + 1;
+    }
+    None
+}
+
+/// The ingredient marked by the `@` at byte `at` of `line`
+pub(super) fn ingredient_at(line: &str, at: usize) -> IngredientSpan {
+    let start = line[at + 1..]
+        .find(|c: char| !MODIFIERS.contains(&c))
+        .map_or(line.len(), |i| at + 1 + i);
+    let (len, braced) = name_len(&line[start..]);
+    IngredientSpan {
+        modifiers: at + 1..start,
+        name: start..start + len,
+        braced,
+    }
+}
+
+/// The text between the braces of a braced ingredient's `{amount}`, as a
+/// byte range of `line`, or `None` if the line doesn't close them before
+/// another `{` opens
+pub(super) fn amount_at(line: &str, ingredient: &IngredientSpan) -> Option<Range<usize>> {
+    if !ingredient.braced {
+        return None;
+    }
+    let open = ingredient.name.end + 1;
+    let close = open + line[open..].find(['{', '}'])?;
+    (line[close..].starts_with('}')).then_some(open..close)
+}
+
+/// The length of the name `body` starts with (the text after an `@`, `#` or
+/// `~` and its modifiers), and whether `{` follows it
+///
+/// Multi-word names end at '{'; anything else is a single word.
+pub(super) fn name_len(body: &str) -> (usize, bool) {
+    let braced = body
+        .find('{')
+        .filter(|&open| !body[..open].contains(['@', '#', '~', '}', '.', ',', '\n']));
+    match braced {
+        Some(open) => (open, true),
+        None => {
+            let end = body
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+                .unwrap_or(body.len());
+            (end, false)
+        }
+    }
+}
+
+/// Ingredient names as declared, with any `|alias` shown in the steps
+pub(super) fn declared_names(cooklang: &str) -> Vec<String> {
+    let mut names = Vec::new();
+
+    for (in_frontmatter, line) in recipe_lines(cooklang) {
+        let line = line.trim_end_matches(['\r', '\n']);
+        let trimmed = line.trim();
+        if in_frontmatter || trimmed.starts_with('>') || trimmed.starts_with("--") {
+            continue;
+        }
+
+        let mut search = 0;
+        while let Some(at) = find_ingredient(line, search) {
+            let ingredient = ingredient_at(line, at);
+            search = at + 1;
+            if line[ingredient.modifiers].contains('&') {
+                continue;
+            }
+            let name = line[ingredient.name].trim();
+            if !name.is_empty() {
+                names.push(name.to_string());
+            }
+        }
+    }
+
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ingredient_at() {
+        let line = "Add @?&olive oil|oil{2%tbsp} and @salt.";
+        let oil = ingredient_at(line, 4);
+        assert_eq!(&line[oil.modifiers], "?&");
+        assert_eq!(&line[oil.name], "olive oil|oil");
+        assert!(oil.braced);
+
+        let salt = ingredient_at(line, line.rfind('@').unwrap());
+        assert_eq!(&line[salt.name], "salt");
+        assert!(!salt.braced);
+
+        // A brace further on belongs to another marker
+        let line = "Season with @pepper, then #pan{}";
+        assert_eq!(&line[ingredient_at(line, 12).name], "pepper");
+    }
+
+    #[test]
+    fn test_escaped_markers_and_unclosed_amounts() {
+        let line = "Mail me\\@home, add @eggs{2} and @flour{1%cup";
+        let eggs = find_ingredient(line, 0).unwrap();
+        assert_eq!(&line[eggs..eggs + 5], "@eggs");
+        let amount = amount_at(line, &ingredient_at(line, eggs)).unwrap();
+        assert_eq!(&line[amount], "2");
+
+        let flour = find_ingredient(line, eggs + 1).unwrap();
+        assert_eq!(amount_at(line, &ingredient_at(line, flour)), None);
+        assert_eq!(find_ingredient(line, flour + 1), None);
+    }
+}
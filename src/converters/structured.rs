@@ -0,0 +1,421 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::error::Error;
+
+/// Name of the tool Anthropic is forced to call in structured mode.
+pub(crate) const STRUCTURED_TOOL_NAME: &str = "emit_recipe";
+
+/// Instructions for structured-output mode. The model only has to fill in
+/// JSON; all Cooklang syntax is produced by [`render_cooklang`].
+pub const STRUCTURED_RECIPE_PROMPT: &str = r#"Convert the recipe below into JSON. Do NOT write Cooklang markup; return only a JSON object with this shape:
+
+{
+  "ingredients": [
+    {"id": "flour", "name": "flour", "quantity": "200", "unit": "g", "preparation": "sifted", "optional": false}
+  ],
+  "cookware": [
+    {"id": "bowl", "name": "large bowl"}
+  ],
+  "sections": [
+    {"name": null, "steps": ["Sift {{ingredient:flour}} into a {{cookware:bowl}} and rest for {{timer:10%minutes}}."]}
+  ],
+  "notes": ["Plain-text tips or background information."]
+}
+
+Rules:
+- Every ingredient needs a short unique "id". "quantity", "unit" and "preparation" may be null.
+- Reference ingredients and cookware inside steps with {{ingredient:ID}} and {{cookware:ID}}. Reference each ingredient at least once, at the step where it is used.
+- Mark durations in steps with {{timer:QUANTITY%UNIT}}.
+- Items of an "Equipment:" list at the end of the recipe go in "cookware"; reference each at the step where it is first used, and do not turn the list into a step.
+- Use one section with "name": null unless the recipe has named components (sauce, dough, filling, ...).
+- Preserve the original wording of the steps and keep all text in {{LANGUAGE}}.
+- If the input contains no cooking steps, return {"ingredients": [], "sections": []}.
+
+Recipe:
+{{RECIPE}}"#;
+
+/// A recipe as returned by the LLM in structured-output mode.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StructuredRecipe {
+    #[serde(default)]
+    pub ingredients: Vec<StructuredIngredient>,
+    #[serde(default)]
+    pub cookware: Vec<StructuredCookware>,
+    #[serde(default)]
+    pub sections: Vec<StructuredSection>,
+    #[serde(default)]
+    pub notes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StructuredIngredient {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub quantity: Option<String>,
+    #[serde(default)]
+    pub unit: Option<String>,
+    #[serde(default)]
+    pub preparation: Option<String>,
+    #[serde(default)]
+    pub optional: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StructuredCookware {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StructuredSection {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub steps: Vec<String>,
+}
+
+/// Build the structured-mode prompt for a recipe.
+pub fn inject_structured_recipe(recipe: &str) -> String {
+    inject_structured_recipe_in(recipe, None)
+}
+
+/// Build the structured-mode prompt, asking for the recipe in `language`
+/// (translated if needed). `None` keeps the recipe's own language.
+pub fn inject_structured_recipe_in(recipe: &str, language: Option<&str>) -> String {
+    let language = match language {
+        Some(language) => super::prompt::translation_target(language),
+        None => super::prompt::detect_language(recipe),
+    };
+    STRUCTURED_RECIPE_PROMPT
+        .replace("{{LANGUAGE}}", &language)
+        .replace("{{RECIPE}}", recipe)
+}
+
+/// JSON schema of [`StructuredRecipe`], used for Anthropic tool input.
+pub(crate) fn structured_recipe_schema() -> Value {
+    let nullable_string = json!({"type": ["string", "null"]});
+    json!({
+        "type": "object",
+        "properties": {
+            "ingredients": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "string"},
+                        "name": {"type": "string"},
+                        "quantity": nullable_string,
+                        "unit": nullable_string,
+                        "preparation": nullable_string,
+                        "optional": {"type": "boolean"}
+                    },
+                    "required": ["id", "name"]
+                }
+            },
+            "cookware": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "string"},
+                        "name": {"type": "string"}
+                    },
+                    "required": ["id", "name"]
+                }
+            },
+            "sections": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "name": nullable_string,
+                        "steps": {"type": "array", "items": {"type": "string"}}
+                    },
+                    "required": ["steps"]
+                }
+            },
+            "notes": {"type": "array", "items": {"type": "string"}}
+        },
+        "required": ["ingredients", "sections"]
+    })
+}
+
+/// Parse the model's JSON answer, tolerating a surrounding ```json fence.
+pub fn parse_structured_recipe(
+    response: &str,
+) -> Result<StructuredRecipe, Box<dyn Error + Send + Sync>> {
+    let trimmed = response.trim();
+    let json = trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|s| s.trim_end().strip_suffix("```"))
+        .unwrap_or(trimmed);
+
+    serde_json::from_str(json).map_err(|e| {
+        format!(
+            "Failed to parse structured recipe: {}. Raw response: {}",
+            e,
+            json.chars().take(500).collect::<String>()
+        )
+        .into()
+    })
+}
+
+/// Render a structured recipe as Cooklang.
+///
+/// The first reference to an ingredient carries its quantity and preparation;
+/// later references are bare `@name{}`. Cookware is only marked the first time.
+/// Ingredients the model never referenced are listed in a leading step so
+/// nothing from the source is silently lost.
+pub fn render_cooklang(recipe: &StructuredRecipe) -> String {
+    if recipe.sections.iter().all(|s| s.steps.is_empty()) {
+        return "no recipe".to_string();
+    }
+
+    let mut used_ingredients = HashSet::new();
+    let mut used_cookware = HashSet::new();
+    let mut blocks = Vec::new();
+
+    for note in &recipe.notes {
+        let note = note.trim();
+        if !note.is_empty() {
+            blocks.push(format!("> {}", note));
+        }
+    }
+
+    let mut rendered_sections = Vec::new();
+    for section in &recipe.sections {
+        let mut section_blocks = Vec::new();
+        if let Some(name) = section.name.as_deref().map(str::trim) {
+            if !name.is_empty() {
+                section_blocks.push(format!("== {} ==", name));
+            }
+        }
+        for step in &section.steps {
+            let step = render_step(step, recipe, &mut used_ingredients, &mut used_cookware);
+            if !step.trim().is_empty() {
+                section_blocks.push(step.trim().to_string());
+            }
+        }
+        rendered_sections.push(section_blocks);
+    }
+
+    let unreferenced: Vec<String> = recipe
+        .ingredients
+        .iter()
+        .filter(|i| !used_ingredients.contains(i.id.as_str()))
+        .map(render_ingredient)
+        .collect();
+    if !unreferenced.is_empty() {
+        blocks.push(unreferenced.join(", "));
+    }
+
+    for section in rendered_sections {
+        blocks.extend(section);
+    }
+
+    blocks.join("\n\n")
+}
+
+fn render_step<'a>(
+    step: &str,
+    recipe: &'a StructuredRecipe,
+    used_ingredients: &mut HashSet<&'a str>,
+    used_cookware: &mut HashSet<&'a str>,
+) -> String {
+    let mut out = String::with_capacity(step.len());
+    let mut rest = step;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start..].find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let placeholder = &rest[start + 2..start + len];
+        rest = &rest[start + len + 2..];
+
+        let (kind, value) = placeholder.split_once(':').unwrap_or(("", placeholder));
+        let value = value.trim();
+        match kind.trim() {
+            "ingredient" => match recipe.ingredients.iter().find(|i| i.id == value) {
+                Some(ingredient) if used_ingredients.insert(ingredient.id.as_str()) => {
+                    out.push_str(&render_ingredient(ingredient));
+                }
+                Some(ingredient) => {
+                    out.push_str(&format!("@{}{{}}", clean(&ingredient.name)));
+                }
+                None => out.push_str(value),
+            },
+            "cookware" => match recipe.cookware.iter().find(|c| c.id == value) {
+                Some(cookware) if used_cookware.insert(cookware.id.as_str()) => {
+                    out.push_str(&format!("#{}{{}}", clean(&cookware.name)));
+                }
+                Some(cookware) => out.push_str(&cookware.name),
+                None => out.push_str(value),
+            },
+            "timer" => {
+                let duration = match value.split_once('%') {
+                    Some((q, u)) => format!("{}%{}", clean(q.trim()), clean(u.trim())),
+                    None => match value.split_once(char::is_whitespace) {
+                        Some((q, u)) => format!("{}%{}", clean(q), clean(u.trim())),
+                        None => clean(value),
+                    },
+                };
+                if duration.is_empty() {
+                    out.push_str(value);
+                } else {
+                    out.push_str(&format!("~{{{}}}", duration));
+                }
+            }
+            _ => out.push_str(value),
+        }
+    }
+    out.push_str(rest);
+
+    out
+}
+
+fn render_ingredient(ingredient: &StructuredIngredient) -> String {
+    let quantity = ingredient
+        .quantity
+        .as_deref()
+        .map(clean)
+        .filter(|q| !q.is_empty());
+    let unit = ingredient
+        .unit
+        .as_deref()
+        .map(clean)
+        .filter(|u| !u.is_empty());
+    let amount = match (quantity, unit) {
+        (Some(q), Some(u)) => format!("{}%{}", q, u),
+        (Some(q), None) => q,
+        (None, Some(u)) => u,
+        (None, None) => String::new(),
+    };
+
+    let mut out = format!(
+        "@{}{}{{{}}}",
+        if ingredient.optional { "?" } else { "" },
+        clean(&ingredient.name),
+        amount
+    );
+    if let Some(prep) = ingredient.preparation.as_deref().map(str::trim) {
+        if !prep.is_empty() {
+            out.push_str(&format!("({})", prep.replace(['(', ')'], "")));
+        }
+    }
+    out
+}
+
+/// Strip characters that would break Cooklang markup.
+fn clean(s: &str) -> String {
+    s.trim()
+        .chars()
+        .filter(|c| !matches!(c, '{' | '}' | '%' | '@' | '#' | '~'))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::converters::check_cooklang;
+
+    fn sample() -> StructuredRecipe {
+        parse_structured_recipe(
+            r#"{
+                "ingredients": [
+                    {"id": "flour", "name": "flour", "quantity": "200", "unit": "g", "preparation": "sifted"},
+                    {"id": "salt", "name": "salt", "quantity": null, "unit": "pinch"},
+                    {"id": "herbs", "name": "fresh herbs", "optional": true}
+                ],
+                "cookware": [{"id": "bowl", "name": "large bowl"}],
+                "sections": [
+                    {"name": null, "steps": [
+                        "Mix {{ingredient:flour}} and {{ingredient:salt}} in a {{cookware:bowl}}.",
+                        "Rest the {{ingredient:flour}} mixture in the {{cookware:bowl}} for {{timer:10 minutes}}."
+                    ]}
+                ],
+                "notes": ["Works with spelt flour too."]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_render_cooklang() {
+        let rendered = render_cooklang(&sample());
+        assert_eq!(
+            rendered,
+            "> Works with spelt flour too.\n\n\
+             @?fresh herbs{}\n\n\
+             Mix @flour{200%g}(sifted) and @salt{pinch} in a #large bowl{}.\n\n\
+             Rest the @flour{} mixture in the large bowl for ~{10%minutes}."
+        );
+        assert!(check_cooklang(&rendered).is_ok());
+    }
+
+    #[test]
+    fn test_render_sections() {
+        let recipe = StructuredRecipe {
+            sections: vec![
+                StructuredSection {
+                    name: Some("Dough".to_string()),
+                    steps: vec!["Knead.".to_string()],
+                },
+                StructuredSection {
+                    name: Some("Filling".to_string()),
+                    steps: vec!["Stir {{unknown:thing}}.".to_string()],
+                },
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            render_cooklang(&recipe),
+            "== Dough ==\n\nKnead.\n\n== Filling ==\n\nStir thing."
+        );
+    }
+
+    #[test]
+    fn test_render_no_steps() {
+        assert_eq!(render_cooklang(&StructuredRecipe::default()), "no recipe");
+    }
+
+    #[test]
+    fn test_parse_fenced_json() {
+        let recipe = parse_structured_recipe("```json\n{\"sections\": []}\n```").unwrap();
+        assert!(recipe.sections.is_empty());
+        assert!(parse_structured_recipe("not json").is_err());
+    }
+
+    #[test]
+    fn test_parse_error_with_multibyte_text() {
+        // Byte 500 of the reply falls inside a "½"
+        let reply = format!("{{\"notes\":[\"{}", "½".repeat(600));
+        let error = parse_structured_recipe(&reply).unwrap_err().to_string();
+        // The first 500 characters are kept
+        assert!(error.ends_with(&format!("[\"{}", "½".repeat(489))));
+    }
+
+    #[test]
+    fn test_markup_characters_are_stripped() {
+        let recipe = StructuredRecipe {
+            ingredients: vec![StructuredIngredient {
+                id: "a".to_string(),
+                name: "sugar {brown}".to_string(),
+                quantity: Some("1".to_string()),
+                unit: Some("cup".to_string()),
+                ..Default::default()
+            }],
+            sections: vec![StructuredSection {
+                name: None,
+                steps: vec!["Add {{ingredient:a}}.".to_string()],
+            }],
+            ..Default::default()
+        };
+        let rendered = render_cooklang(&recipe);
+        assert_eq!(rendered, "Add @sugar brown{1%cup}.");
+        assert!(check_cooklang(&rendered).is_ok());
+    }
+}
@@ -0,0 +1,102 @@
+use crate::model::split_frontmatter;
+
+/// Lightweight structural check of generated Cooklang.
+///
+/// This is not a full parser — it catches the mistakes LLMs actually make
+/// (unclosed `{`, stray `}`, empty timers, empty output) so callers can
+/// reject a response and try another one.
+pub fn check_cooklang(content: &str) -> Result<(), String> {
+    if content.trim().is_empty() {
+        return Err("Converter returned an empty recipe".to_string());
+    }
+
+    // Line numbers count from the top of the file, frontmatter included
+    let (_, body) = split_frontmatter(content);
+    let first_line = content[..content.len() - body.len()].lines().count() + 1;
+
+    for (line_no, line) in (first_line..).zip(body.lines()) {
+        // Notes and comments are free text
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('>') || trimmed.starts_with("--") {
+            continue;
+        }
+
+        let chars: Vec<char> = line.chars().collect();
+        let mut open: Option<(usize, char)> = None;
+
+        for (i, &c) in chars.iter().enumerate() {
+            match c {
+                '{' => {
+                    if open.is_some() {
+                        return Err(format!("Line {}: nested '{{'", line_no));
+                    }
+                    let marker = find_marker(&chars[..i]);
+                    open = Some((i, marker.unwrap_or(' ')));
+                }
+                '}' => {
+                    let Some((start, marker)) = open.take() else {
+                        return Err(format!("Line {}: unmatched '}}'", line_no));
+                    };
+                    let inner: String = chars[start + 1..i].iter().collect();
+                    if marker == '~' && inner.trim().is_empty() {
+                        return Err(format!("Line {}: timer without a duration", line_no));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if open.is_some() {
+            return Err(format!("Line {}: unclosed '{{'", line_no));
+        }
+    }
+
+    Ok(())
+}
+
+/// Walk back from a `{` to find which Cooklang marker (`@`, `#`, `~`) opened it.
+fn find_marker(before: &[char]) -> Option<char> {
+    before
+        .iter()
+        .rev()
+        .find(|c| matches!(c, '@' | '#' | '~' | '{' | '}'))
+        .copied()
+        .filter(|c| matches!(c, '@' | '#' | '~'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_recipe() {
+        let recipe = "---\ntitle: Pasta\n---\n\nBoil @pasta{500%g} in a #large pot{} for ~{10%minutes}.\n\n> Tip: salt the water {generously}";
+        assert!(check_cooklang(recipe).is_ok());
+    }
+
+    #[test]
+    fn test_empty_recipe() {
+        assert!(check_cooklang("  \n").is_err());
+    }
+
+    #[test]
+    fn test_unclosed_brace() {
+        let err = check_cooklang("Add @salt{1%tsp and stir.").unwrap_err();
+        assert!(err.contains("unclosed"));
+
+        let err = check_cooklang("---\r\ntitle: Soup\r\n---\r\n\r\nAdd @salt{1%tsp.").unwrap_err();
+        assert_eq!(err, "Line 5: unclosed '{'");
+    }
+
+    #[test]
+    fn test_unmatched_brace() {
+        let err = check_cooklang("Add @salt} and stir.").unwrap_err();
+        assert!(err.contains("unmatched"));
+    }
+
+    #[test]
+    fn test_empty_timer() {
+        let err = check_cooklang("Bake for ~{}.").unwrap_err();
+        assert!(err.contains("timer"));
+    }
+}
@@ -0,0 +1,367 @@
+use super::units::{round_to, UnitSystem};
+use crate::model::recipe_lines;
+
+/// Gas marks with their usual oven temperatures in °C and °F
+const GAS_MARKS: &[(&str, f64, f64)] = &[
+    ("1/4", 110.0, 225.0),
+    ("1/2", 120.0, 250.0),
+    ("1", 140.0, 275.0),
+    ("2", 150.0, 300.0),
+    ("3", 170.0, 325.0),
+    ("4", 180.0, 350.0),
+    ("5", 190.0, 375.0),
+    ("6", 200.0, 400.0),
+    ("7", 220.0, 425.0),
+    ("8", 230.0, 450.0),
+    ("9", 240.0, 475.0),
+];
+
+/// A temperature found in a step
+enum Reading {
+    Celsius(f64),
+    Fahrenheit(f64),
+    /// A gas mark, with its temperature in °C and °F
+    GasMark(f64, f64),
+}
+
+/// Write the oven temperatures in a recipe's steps one way.
+///
+/// "180 °C", "180C", "180 degrees Celsius" and the German "180 Grad"
+/// become "180°C", keeping whatever follows ("180°C Ober-/Unterhitze").
+/// With `units`, temperatures in the other scale are converted and gas
+/// marks replaced ("gas mark 4" → "180°C"). With `both`, each temperature
+/// also gets the other scale in parentheses ("180°C (355°F)", "gas mark 4
+/// (180°C/350°F)"). Temperatures followed by the other scale, in
+/// parentheses or after a slash ("220°C (200°C fan) / 425°F"), are only
+/// respelled. Frontmatter, notes and comments are left alone.
+pub fn normalize_temperatures(cooklang: &str, units: Option<UnitSystem>, both: bool) -> String {
+    let mut output = String::with_capacity(cooklang.len());
+
+    for (in_frontmatter, line) in recipe_lines(cooklang) {
+        let trimmed = line.trim();
+        if in_frontmatter || trimmed.starts_with('>') || trimmed.starts_with("--") {
+            output.push_str(line);
+        } else {
+            output.push_str(&normalize_line(line, units, both));
+        }
+    }
+    output
+}
+
+fn normalize_line(line: &str, units: Option<UnitSystem>, both: bool) -> String {
+    let mut output = String::with_capacity(line.len());
+    let mut copied = 0;
+    let mut from = 0;
+    while let Some((start, end, reading)) = find_reading(line, from) {
+        // "180°C (350°F)", "180°C/350°F" or "220°C (200°C fan) / 425°F"
+        // already give both scales, so they are only respelled
+        let (group_end, group) = group_after(line, end);
+        let paired = group
+            .iter()
+            .any(|(_, _, other)| other_scale(&reading, other));
+        let units = if paired { None } else { units };
+
+        output.push_str(&line[copied..start]);
+        output.push_str(&replacement(&line[start..end], &reading, units));
+        if both && !paired {
+            output.push_str(&format!(" ({})", annotation(&reading, units)));
+        }
+        copied = end;
+        // Temperatures inside the group are converted but never annotated,
+        // so "220°C (200°C fan)" doesn't get a parenthesis in a parenthesis
+        for (start, end, other) in &group {
+            output.push_str(&line[copied..*start]);
+            output.push_str(&replacement(&line[*start..*end], other, units));
+            copied = *end;
+        }
+        from = group_end;
+    }
+    output.push_str(&line[copied..]);
+    output
+}
+
+/// `reading` (written as `text`) spelled one way, converted to `units`
+fn replacement(text: &str, reading: &Reading, units: Option<UnitSystem>) -> String {
+    match (reading, units) {
+        (Reading::Celsius(c), Some(UnitSystem::Imperial)) => fahrenheit_text(to_fahrenheit(*c)),
+        (Reading::Fahrenheit(f), Some(UnitSystem::Metric)) => celsius_text(to_celsius(*f)),
+        (Reading::Celsius(c), _) => celsius_text(*c),
+        (Reading::Fahrenheit(f), _) => fahrenheit_text(*f),
+        (Reading::GasMark(c, _), Some(UnitSystem::Metric)) => celsius_text(*c),
+        (Reading::GasMark(_, f), Some(UnitSystem::Imperial)) => fahrenheit_text(*f),
+        (Reading::GasMark(..), None) => text.to_string(),
+    }
+}
+
+/// The other scale for `reading` once it has been converted to `units`
+fn annotation(reading: &Reading, units: Option<UnitSystem>) -> String {
+    match (reading, units) {
+        (Reading::GasMark(c, f), None) => format!("{}°C/{}°F", c, f),
+        (Reading::GasMark(c, _), Some(UnitSystem::Imperial)) => celsius_text(*c),
+        (Reading::GasMark(_, f), Some(UnitSystem::Metric)) => fahrenheit_text(*f),
+        (Reading::Celsius(c), Some(UnitSystem::Imperial)) => celsius_text(*c),
+        (Reading::Fahrenheit(f), Some(UnitSystem::Metric)) => fahrenheit_text(*f),
+        (Reading::Celsius(c), _) => fahrenheit_text(to_fahrenheit(*c)),
+        (Reading::Fahrenheit(f), _) => celsius_text(to_celsius(*f)),
+    }
+}
+
+fn celsius_text(c: f64) -> String {
+    format!("{}°C", c)
+}
+
+fn fahrenheit_text(f: f64) -> String {
+    format!("{}°F", f)
+}
+
+fn to_fahrenheit(c: f64) -> f64 {
+    round_to(c * 9.0 / 5.0 + 32.0, 5.0)
+}
+
+fn to_celsius(f: f64) -> f64 {
+    round_to((f - 32.0) * 5.0 / 9.0, 5.0)
+}
+
+/// Whether `other` gives `reading` in the other scale
+fn other_scale(reading: &Reading, other: &Reading) -> bool {
+    matches!(
+        (reading, other),
+        (Reading::Celsius(_), Reading::Fahrenheit(_))
+            | (Reading::Fahrenheit(_), Reading::Celsius(_))
+            | (
+                Reading::GasMark(..),
+                Reading::Celsius(_) | Reading::Fahrenheit(_)
+            )
+    )
+}
+
+/// The parentheticals and slash-joined temperatures right after a
+/// temperature ending at byte `end`, as in "220°C (200°C fan) / 425°F":
+/// where the group ends and the temperatures in it
+fn group_after(line: &str, end: usize) -> (usize, Vec<(usize, usize, Reading)>) {
+    let mut group_end = end;
+    let mut readings = Vec::new();
+    loop {
+        let rest = line[group_end..].trim_start_matches(' ');
+        let offset = line.len() - rest.len();
+        let next = if rest.starts_with('(') {
+            rest.find(')').map(|close| offset + close + 1)
+        } else if let Some(after) = rest.strip_prefix('/') {
+            let reading_start = line.len() - after.trim_start_matches(' ').len();
+            find_reading(line, reading_start)
+                .filter(|(start, _, _)| *start == reading_start)
+                .map(|(_, end, _)| end)
+        } else {
+            None
+        };
+        let Some(next) = next else {
+            break;
+        };
+
+        let mut from = group_end;
+        while let Some((start, end, reading)) = find_reading(line, from) {
+            if end > next {
+                break;
+            }
+            readings.push((start, end, reading));
+            from = end;
+        }
+        group_end = next;
+    }
+    (group_end, readings)
+}
+
+/// The next temperature at or after byte `from`: where it starts and ends
+fn find_reading(line: &str, from: usize) -> Option<(usize, usize, Reading)> {
+    let bytes = line.as_bytes();
+    let lower = line.to_ascii_lowercase();
+    let mut i = from;
+    while i < bytes.len() {
+        let boundary = i == 0 || !bytes[i - 1].is_ascii_alphanumeric();
+        if boundary && lower.as_bytes()[i..].starts_with(b"gas mark") {
+            if let Some((end, reading)) = gas_mark(line, i + "gas mark".len()) {
+                return Some((i, end, reading));
+            }
+        }
+        // Below-zero temperatures ("-18°C") are freezers, not ovens
+        let signed = i > 0 && matches!(bytes[i - 1], b'-' | b'.');
+        if bytes[i].is_ascii_digit() && boundary && !signed {
+            if let Some((end, reading)) = degrees(line, i) {
+                return Some((i, end, reading));
+            }
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+            continue;
+        }
+        i += 1;
+    }
+    None
+}
+
+/// A temperature in degrees starting with the number at byte `start`:
+/// "180°C", "180 °C", "180º F", "180 degrees Celsius" or "180 Grad"
+fn degrees(line: &str, start: usize) -> Option<(usize, Reading)> {
+    let digits = line[start..]
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(line.len() - start);
+    let value: f64 = line[start..start + digits].parse().ok()?;
+
+    // "200C" or "400F" without a degree sign; two digits at least, so "2C"
+    // (cups) isn't read as a temperature
+    let attached = &line[start + digits..];
+    let bare_scale = attached
+        .chars()
+        .next()
+        .filter(|c| matches!(c, 'C' | 'F') && digits >= 2)
+        .filter(|_| !attached[1..].starts_with(|c: char| c.is_alphanumeric()));
+    if let Some(scale) = bare_scale {
+        let reading = match scale {
+            'C' => Reading::Celsius(value),
+            _ => Reading::Fahrenheit(value),
+        };
+        return Some((start + digits + 1, reading));
+    }
+
+    let after = line[start + digits..].trim_start_matches(' ');
+    let (marker, tail) = ["°", "º", "degrees", "degree", "Grad"]
+        .iter()
+        .find_map(|marker| after.strip_prefix(marker).map(|tail| (*marker, tail)))
+        // "180 Gradient" has no marker
+        .filter(|(marker, tail)| {
+            !marker.starts_with(char::is_alphabetic)
+                || !tail.starts_with(|c: char| c.is_alphanumeric())
+        })?;
+    let scale_text = tail.trim_start_matches(' ');
+    let lower = scale_text.to_ascii_lowercase();
+    let (scale, scale_len) = [
+        ("celsius", 'C'),
+        ("fahrenheit", 'F'),
+        ("c", 'C'),
+        ("f", 'F'),
+    ]
+    .iter()
+    .find(|(name, _)| {
+        lower.starts_with(name) && !lower[name.len()..].starts_with(|c: char| c.is_alphanumeric())
+    })
+    .map(|(name, scale)| (*scale, name.len()))
+    // "180 Grad Umluft" is Celsius; "180°" alone could be either
+    .or((marker == "Grad").then_some(('C', 0)))?;
+
+    let end = if scale_len == 0 {
+        line.len() - tail.len()
+    } else {
+        line.len() - scale_text.len() + scale_len
+    };
+    let reading = match scale {
+        'C' => Reading::Celsius(value),
+        _ => Reading::Fahrenheit(value),
+    };
+    Some((end, reading))
+}
+
+/// The gas mark number after "gas mark" ending at byte `from`
+fn gas_mark(line: &str, from: usize) -> Option<(usize, Reading)> {
+    let rest = line[from..].trim_start_matches(' ');
+    let offset = line.len() - rest.len();
+    GAS_MARKS
+        .iter()
+        .find(|(mark, _, _)| {
+            rest.strip_prefix(mark)
+                .is_some_and(|tail| !tail.starts_with(|c: char| c.is_ascii_digit() || c == '/'))
+        })
+        .map(|&(mark, c, f)| (offset + mark.len(), Reading::GasMark(c, f)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalizes_spelling() {
+        assert_eq!(
+            normalize_temperatures(
+                "Bake at 180 °C Ober-/Unterhitze, or 160 Grad Umluft.\n\nKeep at 70 degrees Celsius.",
+                None,
+                false
+            ),
+            "Bake at 180°C Ober-/Unterhitze, or 160°C Umluft.\n\nKeep at 70°C."
+        );
+        let kept = "Bake at gas mark 4 for ~{20%minutes}. Store at -18°C or 180° fan.";
+        assert_eq!(normalize_temperatures(kept, None, false), kept);
+    }
+
+    #[test]
+    fn test_both_scales() {
+        assert_eq!(
+            normalize_temperatures(
+                "Bake at 180 °C Ober-/Unterhitze, then 350°F. Roast at gas mark 6.",
+                None,
+                true
+            ),
+            "Bake at 180°C (355°F) Ober-/Unterhitze, then 350°F (175°C). Roast at gas mark 6 (200°C/400°F)."
+        );
+        let paired = "Preheat to 200°C (400°F) or 180°C/350°F.";
+        assert_eq!(normalize_temperatures(paired, None, true), paired);
+    }
+
+    #[test]
+    fn test_groups_with_both_scales_are_kept() {
+        let grouped = "Bake at 220°C (200°C fan) / 425°F.";
+        assert_eq!(normalize_temperatures(grouped, None, true), grouped);
+        assert_eq!(
+            normalize_temperatures(grouped, Some(UnitSystem::Imperial), true),
+            grouped
+        );
+        assert_eq!(
+            normalize_temperatures("Bake at 220°C (200°C fan).", None, true),
+            "Bake at 220°C (430°F) (200°C fan)."
+        );
+        assert_eq!(
+            normalize_temperatures(
+                "Bake at 220°C (200°C fan).",
+                Some(UnitSystem::Imperial),
+                false
+            ),
+            "Bake at 430°F (390°F fan)."
+        );
+    }
+
+    #[test]
+    fn test_temperatures_without_degree_sign() {
+        assert_eq!(
+            normalize_temperatures("Preheat to 200C / 400F.", Some(UnitSystem::Metric), true),
+            "Preheat to 200°C / 400°F."
+        );
+        assert_eq!(
+            normalize_temperatures("Bake at 200C, with @milk{2%C}.", None, true),
+            "Bake at 200°C (390°F), with @milk{2%C}."
+        );
+        assert_eq!(
+            normalize_temperatures("Roast at 400F.", Some(UnitSystem::Metric), false),
+            "Roast at 205°C."
+        );
+    }
+
+    #[test]
+    fn test_converts_per_units() {
+        assert_eq!(
+            normalize_temperatures(
+                "Bake at 180 Grad, then at gas mark 1/2.",
+                Some(UnitSystem::Imperial),
+                false
+            ),
+            "Bake at 355°F, then at 250°F."
+        );
+        assert_eq!(
+            normalize_temperatures("Roast at gas mark 7.", Some(UnitSystem::Metric), true),
+            "Roast at 220°C (425°F)."
+        );
+    }
+
+    #[test]
+    fn test_frontmatter_and_notes_are_kept() {
+        let recipe = "---\ntemperature: 180 °C\n---\n\n> Gas mark 4 works too.\n";
+        assert_eq!(normalize_temperatures(recipe, None, true), recipe);
+    }
+}
@@ -0,0 +1,256 @@
+use crate::model::recipe_lines;
+/// Duration units by spelling, with the unit written in the timer
+const UNITS: &[(&[&str], &str)] = &[
+    (&["seconds", "second", "secs", "sec"], "seconds"),
+    (&["minutes", "minute", "mins", "min"], "minutes"),
+    (&["hours", "hour", "hrs", "hr"], "hours"),
+];
+
+/// Turn durations the model left as prose into timers, e.g. "bake for 25
+/// minutes" → "bake for ~{25%minutes}".
+///
+/// Covers whole, decimal and fractional numbers ("1 1/2 hours"), ranges
+/// ("10-15 minutes", "10 to 15 minutes") and hours followed by minutes
+/// ("1 hour 30 minutes"), which become a single timer in minutes, as do
+/// fractional hours. Durations inside `{}` or `()`, notes, comments and
+/// section headers are left alone, as are adjectives like "5-minute".
+pub fn tag_timers(cooklang: &str) -> String {
+    let mut output = String::with_capacity(cooklang.len());
+    let mut tagged = 0;
+
+    for (in_frontmatter, line) in recipe_lines(cooklang) {
+        let trimmed = line.trim();
+        if in_frontmatter
+            || trimmed.starts_with('>')
+            || trimmed.starts_with("--")
+            || trimmed.starts_with('=')
+        {
+            output.push_str(line);
+        } else {
+            let (line, count) = tag_line(line);
+            tagged += count;
+            output.push_str(&line);
+        }
+    }
+    if tagged > 0 {
+        tracing::debug!("Tagged {} durations the model left as text", tagged);
+    }
+    output
+}
+
+/// A duration found in a line
+struct Duration {
+    start: usize,
+    end: usize,
+    /// Timer contents, e.g. "25%minutes"
+    timer: String,
+}
+
+fn tag_line(line: &str) -> (String, usize) {
+    let mut output = String::with_capacity(line.len());
+    let mut copied = 0;
+    let mut count = 0;
+    while let Some(duration) = find_duration(line, copied) {
+        output.push_str(&line[copied..duration.start]);
+        output.push_str(&format!("~{{{}}}", duration.timer));
+        copied = duration.end;
+        count += 1;
+    }
+    output.push_str(&line[copied..]);
+    (output, count)
+}
+
+/// The next duration at or after byte `from` that is in plain prose
+fn find_duration(line: &str, from: usize) -> Option<Duration> {
+    let bytes = line.as_bytes();
+    let mut i = from;
+    while i < bytes.len() {
+        let boundary = i == 0 || !(bytes[i - 1].is_ascii_alphanumeric() || bytes[i - 1] == b'.');
+        if !bytes[i].is_ascii_digit() || !boundary || in_markup(&line[..i]) {
+            i += 1;
+            continue;
+        }
+        match duration_at(line, i) {
+            Some(duration) => return Some(duration),
+            None => {
+                // Skip the rest of this number
+                while i < bytes.len() && (bytes[i].is_ascii_digit() || bytes[i] == b'.') {
+                    i += 1;
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Whether text ending here is inside `{}`, `()`, or a tag's name
+fn in_markup(before: &str) -> bool {
+    let open = |open, close| before.matches(open).count() > before.matches(close).count();
+    let in_tag = before
+        .rfind(['@', '#', '~'])
+        .is_some_and(|marker| !before[marker..].contains(['{', ' ']));
+    open('{', '}') || open('(', ')') || in_tag
+}
+
+/// A duration starting with the number at byte `start`
+fn duration_at(line: &str, start: usize) -> Option<Duration> {
+    let (low, rest) = number(&line[start..])?;
+    // "10-15", "10–15" or "10 to 15"
+    let range = ["-", "–", " - ", " – ", " to "]
+        .iter()
+        .find_map(|separator| rest.strip_prefix(separator))
+        .and_then(number);
+    let (high, rest) = match range {
+        Some((high, rest)) => (Some(high), rest),
+        None => (None, rest),
+    };
+    let (unit, rest) = duration_unit(rest)?;
+
+    // "1 hour 30 minutes", "1 hour and 30 minutes"
+    if unit == "hours" && high.is_none() {
+        let after = rest.trim_start_matches([' ', ',']);
+        let after = after.strip_prefix("and ").unwrap_or(after);
+        if let Some((minutes, tail)) = number(after)
+            .and_then(|(minutes, tail)| {
+                duration_unit(tail).map(|(unit, tail)| (minutes, unit, tail))
+            })
+            .filter(|(_, unit, _)| *unit == "minutes")
+            .map(|(minutes, _, tail)| (minutes, tail))
+        {
+            let total = low * 60.0 + minutes;
+            return Some(Duration {
+                start,
+                end: line.len() - tail.len(),
+                timer: format!("{}%minutes", format_number(total)),
+            });
+        }
+    }
+
+    let end = line.len() - rest.len();
+    let fractional_hours =
+        unit == "hours" && (low.fract() != 0.0 || high.is_some_and(|h| h.fract() != 0.0));
+    let (scale, unit) = if fractional_hours {
+        (60.0, "minutes")
+    } else {
+        (1.0, unit)
+    };
+    let quantity = match high {
+        Some(high) => format!(
+            "{}-{}",
+            format_number(low * scale),
+            format_number(high * scale)
+        ),
+        None => format_number(low * scale),
+    };
+    Some(Duration {
+        start,
+        end,
+        timer: format!("{}%{}", quantity, unit),
+    })
+}
+
+/// A number at the start of `text`: "25", "2.5", "1/2" or "1 1/2", and the
+/// text after it
+fn number(text: &str) -> Option<(f64, &str)> {
+    let digits = |s: &str| s.len() - s.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let whole_len = digits(text);
+    if whole_len == 0 {
+        return None;
+    }
+    let whole: f64 = text[..whole_len].parse().ok()?;
+    let rest = &text[whole_len..];
+
+    // "2.5"
+    if let Some(decimals) = rest.strip_prefix('.') {
+        let len = digits(decimals);
+        if len > 0 {
+            let value = text[..whole_len + 1 + len].parse().ok()?;
+            return Some((value, &decimals[len..]));
+        }
+    }
+    // "1/2"
+    if let Some(value) = fraction(text) {
+        return Some(value);
+    }
+    // "1 1/2"
+    if let Some((part, tail)) = rest.strip_prefix(' ').and_then(fraction) {
+        return Some((whole + part, tail));
+    }
+    Some((whole, rest))
+}
+
+/// "1/2" at the start of `text`, and the text after it
+fn fraction(text: &str) -> Option<(f64, &str)> {
+    let (numerator, rest) = text.split_once('/')?;
+    let denominator_len = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if numerator.is_empty()
+        || !numerator.chars().all(|c| c.is_ascii_digit())
+        || denominator_len == 0
+    {
+        return None;
+    }
+    let numerator: f64 = numerator.parse().ok()?;
+    let denominator: f64 = rest[..denominator_len].parse().ok()?;
+    (denominator != 0.0).then(|| (numerator / denominator, &rest[denominator_len..]))
+}
+
+/// A duration unit after optional spaces, as written in timers, and the
+/// text after it. "5-minute" (an adjective) has no unit.
+fn duration_unit(text: &str) -> Option<(&'static str, &str)> {
+    let word_start = text.trim_start_matches(' ');
+    let word_len = word_start.len()
+        - word_start
+            .trim_start_matches(|c: char| c.is_ascii_alphabetic())
+            .len();
+    let word = word_start[..word_len].to_ascii_lowercase();
+    let (_, unit) = UNITS
+        .iter()
+        .find(|(names, _)| names.contains(&word.as_str()))?;
+    let rest = &word_start[word_len..];
+    // "25 min." keeps its period as the end of the sentence
+    Some((unit, rest))
+}
+
+/// Whole numbers without a decimal point, others with up to two decimals
+fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{}", value as i64)
+    } else {
+        let rounded = format!("{:.2}", value);
+        rounded
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tags_plain_durations() {
+        assert_eq!(
+            tag_timers("Bake for 25 minutes, then rest 10 to 15 mins.\n"),
+            "Bake for ~{25%minutes}, then rest ~{10-15%minutes}.\n"
+        );
+        assert_eq!(
+            tag_timers("Simmer 1 hour 30 minutes. Chill 2 hours. Roast 1 1/2 hours."),
+            "Simmer ~{90%minutes}. Chill ~{2%hours}. Roast ~{90%minutes}."
+        );
+        assert_eq!(
+            tag_timers("Boil the @eggs{3} for 6-7 min and cool for 30 seconds."),
+            "Boil the @eggs{3} for ~{6-7%minutes} and cool for ~{30%seconds}."
+        );
+    }
+
+    #[test]
+    fn test_leaves_timers_and_markup_alone() {
+        let cooklang = "---\ntime required: 45 minutes\n---\n\n== Rest 10 minutes ==\n\nBake ~{25%minutes}, then ~cool{5%minutes}.\n\nAdd @rice{200%g}(rinsed 2 minutes) and give it a 5-minute rest.\n\n> Keeps 3 days, or 20 minutes in the oven.\n";
+        assert_eq!(tag_timers(cooklang), cooklang);
+        assert_eq!(
+            tag_timers("Use 2 cups of stock and 3 eggs."),
+            "Use 2 cups of stock and 3 eggs."
+        );
+    }
+}
@@ -0,0 +1,343 @@
+use super::scale::map_amounts;
+use super::temperatures::normalize_temperatures;
+use crate::model::recipe_lines;
+
+/// Measurement system to convert ingredient quantities and temperatures to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitSystem {
+    /// Grams, milliliters, centimeters and °C
+    Metric,
+    /// Ounces, pounds, cups, inches and °F (US customary)
+    Imperial,
+}
+
+impl std::str::FromStr for UnitSystem {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "metric" => Ok(UnitSystem::Metric),
+            "imperial" | "us" => Ok(UnitSystem::Imperial),
+            _ => Err(format!(
+                "Unknown unit system: {} (expected metric or imperial)",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Dimension {
+    Mass,
+    Volume,
+    Length,
+}
+
+/// Units by spelling: dimension, size in grams/milliliters/centimeters,
+/// and which system they belong to. Teaspoons and tablespoons are used in
+/// both systems, so they are never converted.
+const UNITS: &[(&[&str], Dimension, f64, UnitSystem)] = &[
+    (
+        &["g", "gr", "gram", "grams", "gramm"],
+        Dimension::Mass,
+        1.0,
+        UnitSystem::Metric,
+    ),
+    (
+        &["kg", "kilogram", "kilograms", "kilo", "kilos"],
+        Dimension::Mass,
+        1000.0,
+        UnitSystem::Metric,
+    ),
+    (
+        &["oz", "ounce", "ounces"],
+        Dimension::Mass,
+        28.3495,
+        UnitSystem::Imperial,
+    ),
+    (
+        &["lb", "lbs", "pound", "pounds"],
+        Dimension::Mass,
+        453.592,
+        UnitSystem::Imperial,
+    ),
+    (
+        &[
+            "ml",
+            "milliliter",
+            "milliliters",
+            "millilitre",
+            "millilitres",
+        ],
+        Dimension::Volume,
+        1.0,
+        UnitSystem::Metric,
+    ),
+    (
+        &[
+            "cl",
+            "centiliter",
+            "centiliters",
+            "centilitre",
+            "centilitres",
+        ],
+        Dimension::Volume,
+        10.0,
+        UnitSystem::Metric,
+    ),
+    (
+        &["dl", "deciliter", "deciliters", "decilitre", "decilitres"],
+        Dimension::Volume,
+        100.0,
+        UnitSystem::Metric,
+    ),
+    (
+        &["l", "liter", "liters", "litre", "litres"],
+        Dimension::Volume,
+        1000.0,
+        UnitSystem::Metric,
+    ),
+    (
+        &["fl oz", "fl. oz", "fluid ounce", "fluid ounces"],
+        Dimension::Volume,
+        29.5735,
+        UnitSystem::Imperial,
+    ),
+    (
+        &["cup", "cups", "c"],
+        Dimension::Volume,
+        236.588,
+        UnitSystem::Imperial,
+    ),
+    (
+        &["pint", "pints", "pt"],
+        Dimension::Volume,
+        473.176,
+        UnitSystem::Imperial,
+    ),
+    (
+        &["quart", "quarts", "qt"],
+        Dimension::Volume,
+        946.353,
+        UnitSystem::Imperial,
+    ),
+    (
+        &["gallon", "gallons", "gal"],
+        Dimension::Volume,
+        3785.41,
+        UnitSystem::Imperial,
+    ),
+    (
+        &[
+            "cm",
+            "centimeter",
+            "centimeters",
+            "centimetre",
+            "centimetres",
+        ],
+        Dimension::Length,
+        1.0,
+        UnitSystem::Metric,
+    ),
+    (
+        &[
+            "mm",
+            "millimeter",
+            "millimeters",
+            "millimetre",
+            "millimetres",
+        ],
+        Dimension::Length,
+        0.1,
+        UnitSystem::Metric,
+    ),
+    (
+        &["in", "inch", "inches"],
+        Dimension::Length,
+        2.54,
+        UnitSystem::Imperial,
+    ),
+];
+
+/// Convert a recipe's ingredient quantities and oven temperatures to
+/// `system`.
+///
+/// Uses a fixed table of common cooking units: grams/kilograms ↔
+/// ounces/pounds, milliliters/liters ↔ cups/fluid ounces, centimeters ↔
+/// inches. Volumes stay volumes and weights stay weights, since converting
+/// between them needs the ingredient's density. Teaspoons, tablespoons,
+/// counts and text quantities are kept. Metric results are rounded to whole
+/// grams or milliliters (to 5 above 50), imperial ones to quarters. Oven
+/// temperatures are converted by [`normalize_temperatures`], which keeps
+/// ones the step already gives in both scales.
+pub fn convert_units(cooklang: &str, system: UnitSystem) -> String {
+    normalize_temperatures(&convert_quantities(cooklang, system), Some(system), false)
+}
+
+/// The ingredient quantity half of [`convert_units`], for callers that
+/// normalize temperatures themselves
+pub(crate) fn convert_quantities(cooklang: &str, system: UnitSystem) -> String {
+    let mut output = String::with_capacity(cooklang.len());
+
+    for (in_frontmatter, line) in recipe_lines(cooklang) {
+        let trimmed = line.trim();
+        if in_frontmatter || trimmed.starts_with('>') || trimmed.starts_with("--") {
+            output.push_str(line);
+        } else {
+            output.push_str(&map_amounts(line, |quantity, unit, _| {
+                convert_amount(quantity, unit?, system)
+            }));
+        }
+    }
+    output
+}
+
+/// Whether `word` is one of the convertible units, e.g. "g", "cups" or "oz"
+pub(crate) fn is_unit(word: &str) -> bool {
+    let word = word.to_lowercase();
+    UNITS
+        .iter()
+        .any(|(names, _, _, _)| names.contains(&word.as_str()))
+}
+
+/// A quantity in `unit` expressed in `system`, or `None` if it already is
+/// or the unit is unknown
+fn convert_amount(quantity: f64, unit: &str, system: UnitSystem) -> Option<(f64, Option<String>)> {
+    let unit = unit.to_lowercase();
+    let &(_, dimension, size, from) = UNITS
+        .iter()
+        .find(|(names, _, _, _)| names.contains(&unit.as_str()))?;
+    if from == system {
+        return None;
+    }
+    let base = quantity * size;
+
+    let (quantity, unit) = match (system, dimension) {
+        (UnitSystem::Metric, Dimension::Mass) if base >= 1000.0 => {
+            (round_to(base / 1000.0, 0.05), "kg")
+        }
+        (UnitSystem::Metric, Dimension::Mass) => (round_metric(base), "g"),
+        (UnitSystem::Metric, Dimension::Volume) if base >= 1000.0 => {
+            (round_to(base / 1000.0, 0.05), "l")
+        }
+        (UnitSystem::Metric, Dimension::Volume) => (round_metric(base), "ml"),
+        (UnitSystem::Metric, Dimension::Length) => (round_to(base, 0.5), "cm"),
+        (UnitSystem::Imperial, Dimension::Mass) if base >= 453.592 => {
+            (round_to(base / 453.592, 0.25), "lb")
+        }
+        (UnitSystem::Imperial, Dimension::Mass) => (round_to(base / 28.3495, 0.25), "oz"),
+        // Below a quarter cup, fluid ounces read better than fractions of cups
+        (UnitSystem::Imperial, Dimension::Volume) if base < 59.0 => {
+            (round_to(base / 29.5735, 0.25), "fl oz")
+        }
+        (UnitSystem::Imperial, Dimension::Volume) => {
+            let cups = round_to(base / 236.588, 0.25);
+            (cups, if cups > 1.0 { "cups" } else { "cup" })
+        }
+        (UnitSystem::Imperial, Dimension::Length) => (round_to(base / 2.54, 0.25), "in"),
+    };
+    Some((quantity, Some(unit.to_string())))
+}
+
+/// Whole units, or multiples of 5 above 50
+fn round_metric(value: f64) -> f64 {
+    if value > 50.0 {
+        round_to(value, 5.0)
+    } else {
+        value.round().max(1.0)
+    }
+}
+
+/// Round to the nearest multiple of `step`, but never down to zero
+pub(super) fn round_to(value: f64, step: f64) -> f64 {
+    ((value / step).round() * step).max(step)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_imperial_to_metric() {
+        let recipe = "Mix @flour{2%cups}, @butter{8%oz} and @chicken{2 1/2%lb}.\n\
+                      Add @salt{1%tsp}, @eggs{3} and @milk{pinch}.\n\
+                      Bake at 350°F (or 325 °F fan) in a 9 inch #pan{}.";
+        assert_eq!(
+            convert_units(recipe, UnitSystem::Metric),
+            "Mix @flour{475%ml}, @butter{225%g} and @chicken{1.15%kg}.\n\
+             Add @salt{1%tsp}, @eggs{3} and @milk{pinch}.\n\
+             Bake at 175°C (or 165°C fan) in a 9 inch #pan{}."
+        );
+    }
+
+    #[test]
+    fn test_metric_to_imperial() {
+        let recipe = "Whisk @flour{250%g}, @milk{500%ml} and @oil{30%ml}.\n\
+                      Roast @potatoes{1%kg} at 200 degrees Celsius.";
+        assert_eq!(
+            convert_units(recipe, UnitSystem::Imperial),
+            "Whisk @flour{8.75%oz}, @milk{2%cups} and @oil{1%fl oz}.\n\
+             Roast @potatoes{2.25%lb} at 390°F."
+        );
+    }
+
+    #[test]
+    fn test_single_cup_is_singular() {
+        assert_eq!(
+            convert_units(
+                "Add @milk{240%ml} and @water{120%ml}.",
+                UnitSystem::Imperial
+            ),
+            "Add @milk{1%cup} and @water{0.5%cup}."
+        );
+    }
+
+    #[test]
+    fn test_paired_temperatures_are_kept() {
+        let recipe = "Bake at 180°C (350°F), then at 350°F / 175°C.";
+        assert_eq!(convert_units(recipe, UnitSystem::Metric), recipe);
+        assert_eq!(convert_units(recipe, UnitSystem::Imperial), recipe);
+    }
+
+    #[test]
+    fn test_same_system_and_frontmatter_are_kept() {
+        let recipe = "---\ntemperature: 180°C\n---\n\nAdd @flour{500%g} at 180°C.";
+        assert_eq!(convert_units(recipe, UnitSystem::Metric), recipe);
+    }
+
+    #[test]
+    fn test_crlf_and_unclosed_frontmatter() {
+        let recipe = "\r\n---\r\noven: 350°F\r\n---\r\nBake @butter{8%oz} at 350°F.\r\n";
+        assert_eq!(
+            convert_units(recipe, UnitSystem::Metric),
+            "\r\n---\r\noven: 350°F\r\n---\r\nBake @butter{225%g} at 175°C.\r\n"
+        );
+        // Without a closing fence the `---` is just a line of the steps
+        assert_eq!(
+            convert_units("---\nMelt @butter{8%oz}.", UnitSystem::Metric),
+            "---\nMelt @butter{225%g}."
+        );
+    }
+
+    #[test]
+    fn test_escaped_and_unclosed_amounts_are_kept() {
+        assert_eq!(
+            convert_units(
+                "Ask \\@chef{8%oz}, then add @butter{8%oz}.",
+                UnitSystem::Metric
+            ),
+            "Ask \\@chef{8%oz}, then add @butter{225%g}."
+        );
+        assert_eq!(
+            convert_units("Add @butter{8%oz and @milk{1%cup}.", UnitSystem::Metric),
+            "Add @butter{8%oz and @milk{235%ml}."
+        );
+    }
+
+    #[test]
+    fn test_parse_unit_system() {
+        assert_eq!("Metric".parse::<UnitSystem>(), Ok(UnitSystem::Metric));
+        assert_eq!("imperial".parse::<UnitSystem>(), Ok(UnitSystem::Imperial));
+        assert!("kelvin".parse::<UnitSystem>().is_err());
+    }
+}
@@ -0,0 +1,262 @@
+//! Long-running import daemon: JSON-RPC 2.0 over stdin/stdout
+//!
+//! For apps that import many recipes: the runtime, config and HTTP
+//! connection pools stay up between imports. One request per line in, one
+//! response per line out, handled in order:
+//!
+//! ```text
+//! → {"jsonrpc": "2.0", "id": 1, "method": "import", "params": {"url": "https://example.com/recipe"}}
+//! ← {"jsonrpc": "2.0", "id": 1, "result": {"cooklang": "...", "name": "...", ...}}
+//! ```
+//!
+//! Methods:
+//! - `import`: params are an [`ImportRequest`]; the result is the same JSON
+//!   as `--format json`. With `min_quality` in config.toml, a conversion
+//!   scoring below it gets `"review": true` in the result, or with
+//!   `below_quality = "skip"` a [`LOW_QUALITY`] error
+//! - `ping`: answers `"pong"`
+//! - `shutdown`: answers `null` and stops; so does closing stdin
+//!
+//! Requests without an `id` are notifications and get no response.
+//!
+//! With a `[schedule]` section in config.toml, the daemon also runs feed
+//! and folder imports on a timer while it serves; see [`crate::schedule`].
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tracing::warn;
+
+use crate::config::{load_config, BelowQuality};
+use crate::schedule::Scheduler;
+use crate::{ImportError, ImportRequest, ImportResult};
+
+/// The line isn't JSON
+pub const PARSE_ERROR: i64 = -32700;
+/// The JSON isn't a request object
+pub const INVALID_REQUEST: i64 = -32600;
+/// Unknown method
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// Params aren't a valid [`ImportRequest`]
+pub const INVALID_PARAMS: i64 = -32602;
+/// The import ran and failed; the message is the [`ImportError`]
+pub const IMPORT_FAILED: i64 = -32000;
+/// The conversion scored below `min_quality` and `below_quality` is `skip`
+pub const LOW_QUALITY: i64 = -32001;
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+/// Serve requests from `input` until it ends or a `shutdown` arrives
+pub async fn run<R, W>(input: R, mut output: W) -> std::io::Result<()>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut lines = input.lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (response, shutdown) = handle(&line).await;
+        if let Some(response) = response {
+            output.write_all(response.to_string().as_bytes()).await?;
+            output.write_all(b"\n").await?;
+            output.flush().await?;
+        }
+        if shutdown {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Serve requests on stdin/stdout, keeping config and HTTP clients between
+/// imports, and run the imports config.toml's `[schedule]` sets until then
+pub async fn run_stdio() -> std::io::Result<()> {
+    keep_warm();
+    let scheduler = Scheduler::from_config()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?
+        .map(|scheduler| tokio::spawn(scheduler.run()));
+    let served = run(BufReader::new(tokio::io::stdin()), tokio::io::stdout()).await;
+    if let Some(scheduler) = scheduler {
+        scheduler.abort();
+    }
+    served
+}
+
+/// Read config once and reuse HTTP clients for the rest of the process
+pub(crate) fn keep_warm() {
+    if let Err(e) = crate::config::keep_config() {
+        warn!("Config will be read per import: {}", e);
+    }
+    crate::http::keep_clients();
+}
+
+/// The response to one line (`None` for notifications) and whether to stop
+async fn handle(line: &str) -> (Option<Value>, bool) {
+    let value: Value = match serde_json::from_str(line) {
+        Ok(value) => value,
+        Err(e) => return (Some(error(Value::Null, PARSE_ERROR, e.to_string())), false),
+    };
+    let request: Request = match serde_json::from_value(value) {
+        Ok(request) => request,
+        Err(e) => {
+            return (
+                Some(error(Value::Null, INVALID_REQUEST, e.to_string())),
+                false,
+            )
+        }
+    };
+
+    let shutdown = request.method == "shutdown";
+    let outcome = match request.method.as_str() {
+        "import" => import(request.params).await,
+        "ping" => Ok(json!("pong")),
+        "shutdown" => Ok(Value::Null),
+        other => Err((METHOD_NOT_FOUND, format!("Unknown method: {}", other))),
+    };
+
+    let response = request.id.map(|id| match outcome {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => error(id, code, message),
+    });
+    (response, shutdown)
+}
+
+async fn import(params: Value) -> Result<Value, (i64, String)> {
+    let request: ImportRequest =
+        serde_json::from_value(params).map_err(|e| (INVALID_PARAMS, e.to_string()))?;
+    let builder = request
+        .into_builder()
+        .map_err(|e| (INVALID_PARAMS, e.to_string()))?;
+    match builder.build().await {
+        Ok(result) => {
+            let config = load_config().ok();
+            let quality = config.and_then(|c| c.min_quality.map(|min| (min, c.below_quality)));
+            gate(result, quality)
+        }
+        Err(e @ ImportError::BuilderError(_)) => Err((INVALID_PARAMS, e.to_string())),
+        Err(e) => Err((IMPORT_FAILED, e.to_string())),
+    }
+}
+
+/// The JSON of an import's result, marked for review or refused when it
+/// scores below the minimum `quality`
+fn gate(
+    result: ImportResult,
+    quality: Option<(f32, BelowQuality)>,
+) -> Result<Value, (i64, String)> {
+    let below = quality.and_then(|(min, action)| Some((result.check_quality(min).err()?, action)));
+    let mut json = serde_json::to_value(result).map_err(|e| (IMPORT_FAILED, e.to_string()))?;
+    match below {
+        Some((e, BelowQuality::Skip)) => return Err((LOW_QUALITY, e.to_string())),
+        Some((_, BelowQuality::Review)) => json["review"] = true.into(),
+        None => {}
+    }
+    Ok(json)
+}
+
+fn error(id: Value, code: i64, message: String) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn responses(input: &str) -> Vec<Value> {
+        let mut output = Vec::new();
+        run(input.as_bytes(), &mut output).await.unwrap();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_import_and_ping() {
+        let responses = responses(
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "ping"}
+{"jsonrpc": "2.0", "id": 2, "method": "import", "params": {"text": "2 eggs\n\nFry the eggs.", "extract_only": true}}
+"#,
+        )
+        .await;
+
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0]["result"], "pong");
+        assert_eq!(responses[1]["id"], 2);
+        assert!(responses[1]["result"]["text"]
+            .as_str()
+            .unwrap()
+            .contains("Fry the eggs."));
+    }
+
+    #[tokio::test]
+    async fn test_errors() {
+        let responses = responses(
+            r#"not json
+{"jsonrpc": "2.0", "id": 1, "method": "cook"}
+{"jsonrpc": "2.0", "id": 2, "method": "import", "params": {"url": "https://example.com", "text": "2 eggs"}}
+"#,
+        )
+        .await;
+
+        assert_eq!(responses[0]["error"]["code"], PARSE_ERROR);
+        assert_eq!(responses[0]["id"], Value::Null);
+        assert_eq!(responses[1]["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(responses[2]["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_quality_gate() {
+        let result = ImportResult::Cooklang {
+            content: "Stir.".to_string(),
+            title: "Soup".to_string(),
+            conversion_metadata: Some(crate::converters::ConversionMetadata {
+                confidence: Some(0.4),
+                ..Default::default()
+            }),
+        };
+        let passed = gate(result.clone(), Some((0.3, BelowQuality::Skip))).unwrap();
+        assert_eq!(passed["review"], Value::Null);
+
+        let review = gate(result.clone(), Some((0.7, BelowQuality::Review))).unwrap();
+        assert_eq!(review["review"], true);
+        assert_eq!(review["cooklang"], "Stir.");
+
+        let (code, message) = gate(result, Some((0.7, BelowQuality::Skip))).unwrap_err();
+        assert_eq!(code, LOW_QUALITY);
+        assert!(message.contains("0.40"));
+
+        // Vision imports have no score to gate on
+        let unscored = ImportResult::Cooklang {
+            content: "Stir.".to_string(),
+            title: "Soup".to_string(),
+            conversion_metadata: Some(crate::converters::ConversionMetadata::default()),
+        };
+        let passed = gate(unscored, Some((0.7, BelowQuality::Skip))).unwrap();
+        assert_eq!(passed["review"], Value::Null);
+    }
+
+    #[tokio::test]
+    async fn test_notifications_and_shutdown() {
+        let responses = responses(
+            r#"{"jsonrpc": "2.0", "method": "ping"}
+{"jsonrpc": "2.0", "id": 1, "method": "shutdown"}
+{"jsonrpc": "2.0", "id": 2, "method": "ping"}
+"#,
+        )
+        .await;
+
+        assert_eq!(responses.len(), 1);
+        assert_eq!(responses[0]["id"], 1);
+        assert_eq!(responses[0]["result"], Value::Null);
+    }
+}
@@ -0,0 +1,293 @@
+//! Line diffs and three-way merges of recipe files
+//!
+//! `--update` uses these to show what changed at the source of a recipe
+//! that was edited after it was imported, or to fold those changes into the
+//! edited file while keeping the edits.
+
+/// One line of a diff
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLine<'a> {
+    /// In both texts
+    Same(&'a str),
+    /// Only in the old text
+    Removed(&'a str),
+    /// Only in the new text
+    Added(&'a str),
+}
+
+/// The lines of `old` and `new` in order, marked as kept, removed or added,
+/// with as many lines kept as possible
+pub fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let matches = common_lines(&old, &new);
+
+    let mut lines = Vec::with_capacity(old.len().max(new.len()));
+    let (mut i, mut j) = (0, 0);
+    for (oi, ni) in matches
+        .into_iter()
+        .chain(std::iter::once((old.len(), new.len())))
+    {
+        lines.extend(old[i..oi].iter().map(|line| DiffLine::Removed(line)));
+        lines.extend(new[j..ni].iter().map(|line| DiffLine::Added(line)));
+        if oi < old.len() {
+            lines.push(DiffLine::Same(old[oi]));
+        }
+        (i, j) = (oi + 1, ni + 1);
+    }
+    lines
+}
+
+/// A unified diff from `old` to `new` with three lines of context, headed
+/// `--- old_label` / `+++ new_label`; empty when the texts have the same
+/// lines
+pub fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    const CONTEXT: usize = 3;
+    let lines = diff_lines(old, new);
+    let changed: Vec<usize> = (0..lines.len())
+        .filter(|&i| !matches!(lines[i], DiffLine::Same(_)))
+        .collect();
+    if changed.is_empty() {
+        return String::new();
+    }
+
+    // Changes close enough to share context go in one hunk
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for &i in &changed {
+        let start = i.saturating_sub(CONTEXT);
+        let end = (i + CONTEXT + 1).min(lines.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut output = format!("--- {}\n+++ {}\n", old_label, new_label);
+    for (start, end) in hunks {
+        // Line numbers where the hunk starts in each text
+        let before = &lines[..start];
+        let old_start = 1 + before
+            .iter()
+            .filter(|l| !matches!(l, DiffLine::Added(_)))
+            .count();
+        let new_start = 1 + before
+            .iter()
+            .filter(|l| !matches!(l, DiffLine::Removed(_)))
+            .count();
+        let hunk = &lines[start..end];
+        let old_count = hunk
+            .iter()
+            .filter(|l| !matches!(l, DiffLine::Added(_)))
+            .count();
+        let new_count = hunk
+            .iter()
+            .filter(|l| !matches!(l, DiffLine::Removed(_)))
+            .count();
+        output.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            range(old_start, old_count),
+            range(new_start, new_count)
+        ));
+        for line in hunk {
+            let (prefix, text) = match line {
+                DiffLine::Same(text) => (' ', text),
+                DiffLine::Removed(text) => ('-', text),
+                DiffLine::Added(text) => ('+', text),
+            };
+            output.push(prefix);
+            output.push_str(text);
+            output.push('\n');
+        }
+    }
+    output
+}
+
+/// A hunk range: `start,count`, with an empty range numbered from the line
+/// before it
+fn range(start: usize, count: usize) -> String {
+    match count {
+        0 => format!("{},0", start - 1),
+        1 => start.to_string(),
+        _ => format!("{},{}", start, count),
+    }
+}
+
+/// Result of [`merge3`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Merged {
+    /// The merged text, with conflict markers where both sides changed the
+    /// same lines differently
+    pub text: String,
+    /// Number of conflicts left in `text`
+    pub conflicts: usize,
+}
+
+/// Merge the changes `ours` and `theirs` each made to `base`.
+///
+/// Lines only one side changed take that side's version. Where both sides
+/// changed the same lines differently, both versions are kept between
+/// git-style markers labelled `ours_label` and `theirs_label`.
+pub fn merge3(
+    base: &str,
+    ours: &str,
+    theirs: &str,
+    ours_label: &str,
+    theirs_label: &str,
+) -> Merged {
+    let base: Vec<&str> = base.lines().collect();
+    let ours: Vec<&str> = ours.lines().collect();
+    let theirs: Vec<&str> = theirs.lines().collect();
+
+    // Base line → line in each side, for the lines a side kept
+    let mut in_ours = vec![None; base.len()];
+    for (b, o) in common_lines(&base, &ours) {
+        in_ours[b] = Some(o);
+    }
+    let mut in_theirs = vec![None; base.len()];
+    for (b, t) in common_lines(&base, &theirs) {
+        in_theirs[b] = Some(t);
+    }
+
+    let mut merged = Merged {
+        text: String::new(),
+        conflicts: 0,
+    };
+    let (mut b, mut o, mut t) = (0, 0, 0);
+    loop {
+        // The next base line both sides kept ends the current chunk
+        let stable =
+            (b..base.len()).find_map(|next| Some((next, in_ours[next]?, in_theirs[next]?)));
+        let (next_b, next_o, next_t) = stable.unwrap_or((base.len(), ours.len(), theirs.len()));
+        merge_chunk(
+            &mut merged,
+            &base[b..next_b],
+            &ours[o..next_o],
+            &theirs[t..next_t],
+            ours_label,
+            theirs_label,
+        );
+        if stable.is_none() {
+            break;
+        }
+        merged.text.push_str(base[next_b]);
+        merged.text.push('\n');
+        (b, o, t) = (next_b + 1, next_o + 1, next_t + 1);
+    }
+    merged
+}
+
+/// Add one chunk between stable lines to the merge
+fn merge_chunk(
+    merged: &mut Merged,
+    base: &[&str],
+    ours: &[&str],
+    theirs: &[&str],
+    ours_label: &str,
+    theirs_label: &str,
+) {
+    let push = |text: &mut String, lines: &[&str]| {
+        for line in lines {
+            text.push_str(line);
+            text.push('\n');
+        }
+    };
+    if ours == theirs || theirs == base {
+        push(&mut merged.text, ours);
+    } else if ours == base {
+        push(&mut merged.text, theirs);
+    } else {
+        merged.conflicts += 1;
+        merged.text.push_str(&format!("<<<<<<< {}\n", ours_label));
+        push(&mut merged.text, ours);
+        merged.text.push_str("=======\n");
+        push(&mut merged.text, theirs);
+        merged.text.push_str(&format!(">>>>>>> {}\n", theirs_label));
+    }
+}
+
+/// Index pairs of a longest common subsequence of `a` and `b`, in order
+fn common_lines(a: &[&str], b: &[&str]) -> Vec<(usize, usize)> {
+    // lengths[i][j]: LCS length of a[i..] and b[j..]
+    let mut lengths = vec![vec![0u32; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i][j] = if a[i] == b[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines() {
+        assert_eq!(
+            diff_lines("a\nb\nc\n", "a\nc\nd\n"),
+            vec![
+                DiffLine::Same("a"),
+                DiffLine::Removed("b"),
+                DiffLine::Same("c"),
+                DiffLine::Added("d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+        let new = "1\n2\nthree\n4\n5\n6\n7\n8\n9\n10\n11\n";
+        assert_eq!(
+            unified_diff(old, new, "Soup.cook", "source"),
+            "--- Soup.cook\n+++ source\n\
+             @@ -1,6 +1,6 @@\n 1\n 2\n-3\n+three\n 4\n 5\n 6\n\
+             @@ -8,3 +8,4 @@\n 8\n 9\n 10\n+11\n"
+        );
+        assert_eq!(unified_diff(old, old, "a", "b"), "");
+        assert_eq!(
+            unified_diff("", "new\n", "a", "b"),
+            "--- a\n+++ b\n@@ -0,0 +1 @@\n+new\n"
+        );
+    }
+
+    #[test]
+    fn test_merge3() {
+        let base = "---\nservings: 2\n---\n\nBoil @water{1%l}.\n\nAdd @salt{}.\n";
+        // Local edit: more servings; the source fixed a step
+        let ours = "---\nservings: 4\n---\n\nBoil @water{1%l}.\n\nAdd @salt{}.\n";
+        let theirs = "---\nservings: 2\n---\n\nBoil @water{1.5%l}.\n\nAdd @salt{}.\n";
+        let merged = merge3(base, ours, theirs, "local", "source");
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(
+            merged.text,
+            "---\nservings: 4\n---\n\nBoil @water{1.5%l}.\n\nAdd @salt{}.\n"
+        );
+
+        let theirs = "---\nservings: 6\n---\n\nBoil @water{1%l}.\n\nAdd @salt{}.\n";
+        let merged = merge3(base, ours, theirs, "local", "source");
+        assert_eq!(merged.conflicts, 1);
+        assert_eq!(
+            merged.text,
+            "---\n<<<<<<< local\nservings: 4\n=======\nservings: 6\n>>>>>>> source\n---\n\n\
+             Boil @water{1%l}.\n\nAdd @salt{}.\n"
+        );
+    }
+}
@@ -0,0 +1,520 @@
+//! Setup diagnostics for `cooklang-import doctor`
+//!
+//! Each check reports what it found and, when something is wrong, what to do
+//! about it. Network checks only need an answer from the server; they don't
+//! send recipes or spend tokens, except that a Google Vision key is checked
+//! with an empty annotate request.
+
+use crate::config::{load_config, user_config_path, AiConfig, OcrBackend, CONFIG_PATH_VAR};
+use crate::converters::{ollama_has_model, ollama_models};
+use crate::images_to_text::{GOOGLE_VISION_URL, OLLAMA_OCR_MODEL, TESSERACT_BINARY};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// Time allowed for each network check
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Providers by config name: the environment variable holding their key
+/// and the server reached when no `base_url` is set
+const PROVIDERS: &[(&str, Option<&str>, &str)] = &[
+    ("open_ai", Some("OPENAI_API_KEY"), "https://api.openai.com"),
+    (
+        "anthropic",
+        Some("ANTHROPIC_API_KEY"),
+        "https://api.anthropic.com",
+    ),
+    (
+        "google",
+        Some("GOOGLE_API_KEY"),
+        "https://generativelanguage.googleapis.com",
+    ),
+    ("azure_openai", Some("AZURE_OPENAI_API_KEY"), ""),
+    ("mistral", Some("MISTRAL_API_KEY"), "https://api.mistral.ai"),
+    ("groq", Some("GROQ_API_KEY"), "https://api.groq.com"),
+    ("ollama", None, "http://localhost:11434"),
+];
+
+/// How a check went
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// Nothing to fix
+    Ok,
+    /// Works, but something is missing or only some features will work
+    Warning,
+    /// Imports will fail until this is fixed
+    Error,
+}
+
+/// The outcome of one check
+#[derive(Debug, Clone)]
+pub struct Check {
+    /// What was checked, e.g. "Provider anthropic"
+    pub name: String,
+    /// Whether it passed
+    pub status: Status,
+    /// What was found
+    pub detail: String,
+    /// What to do about a warning or error
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn ok(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Check {
+            name: name.into(),
+            status: Status::Ok,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn problem(
+        status: Status,
+        name: impl Into<String>,
+        detail: impl Into<String>,
+        hint: impl Into<String>,
+    ) -> Self {
+        Check {
+            name: name.into(),
+            status,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+/// Run every check: config, the providers it uses (API key, reachability,
+/// Ollama models) and OCR
+pub async fn run() -> Vec<Check> {
+    let mut checks = vec![config_files()];
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(e) => {
+            checks.push(Check::problem(
+                Status::Error,
+                "Config",
+                e.to_string(),
+                "Fix the file named above, or the COOKLANG__ environment variable",
+            ));
+            return checks;
+        }
+    };
+
+    for provider in used_providers(&config) {
+        checks.extend(check_provider(&config, &provider).await);
+    }
+    checks.push(check_ocr(&config).await);
+    checks
+}
+
+/// Which config files exist and will be read
+fn config_files() -> Check {
+    let mut files: Vec<String> = user_config_path()
+        .into_iter()
+        .chain(["config.toml".into()])
+        .filter(|path| path.exists())
+        .map(|path| path.display().to_string())
+        .collect();
+    if let Some(path) = std::env::var_os(CONFIG_PATH_VAR) {
+        files.push(std::path::PathBuf::from(path).display().to_string());
+    }
+    let overrides = std::env::vars()
+        .filter(|(key, _)| key.starts_with("COOKLANG__"))
+        .count();
+    let env = match overrides {
+        0 => String::new(),
+        n => format!(", {} COOKLANG__ environment variables", n),
+    };
+    if files.is_empty() {
+        Check::problem(
+            Status::Warning,
+            "Config files",
+            format!("none found, using defaults{}", env),
+            "Run `cooklang-import init`, or copy config.toml.example to ~/.config/cooklang-import/config.toml",
+        )
+    } else {
+        Check::ok("Config files", format!("{}{}", files.join(", "), env))
+    }
+}
+
+/// The default provider plus any in the fallback chain or race
+fn used_providers(config: &AiConfig) -> Vec<String> {
+    let mut providers = vec![config.default_provider.clone()];
+    let extra = config
+        .fallback
+        .order
+        .iter()
+        .filter(|_| config.fallback.enabled)
+        .chain(config.race.providers.iter().filter(|_| config.race.enabled));
+    for provider in extra {
+        if !providers.iter().any(|p| same_provider(p, provider)) {
+            providers.push(provider.clone());
+        }
+    }
+    providers
+}
+
+/// "openai" and "open_ai" name the same provider
+fn same_provider(a: &str, b: &str) -> bool {
+    a.replace('_', "") == b.replace('_', "")
+}
+
+async fn check_provider(config: &AiConfig, provider: &str) -> Vec<Check> {
+    let name = format!("Provider {}", provider);
+    let Some(&(known, env_var, default_url)) = PROVIDERS
+        .iter()
+        .find(|(known, _, _)| same_provider(known, provider))
+    else {
+        return vec![Check::problem(
+            Status::Error,
+            name,
+            "unknown provider",
+            "Use one of open_ai, anthropic, google, azure_openai, ollama, mistral, groq",
+        )];
+    };
+    let provider_config = config
+        .providers
+        .iter()
+        .find(|(key, _)| same_provider(key, provider))
+        .map(|(_, config)| config);
+    let mut checks = Vec::new();
+
+    // The key, where the converter would find it
+    if let Some(env_var) = env_var {
+        let source = match provider_config {
+            Some(c) if c.api_key.is_some() => Ok("api_key in config".to_string()),
+            Some(c) if c.api_key_cmd.is_some() || c.api_key_secret.is_some() => {
+                crate::secrets::with_api_key(known, c).map(|_| match c.api_key_cmd {
+                    Some(_) => "api_key_cmd".to_string(),
+                    None => "secret store".to_string(),
+                })
+            }
+            _ if std::env::var_os(env_var).is_some() => Ok(env_var.to_string()),
+            _ => Err(format!("{} is not set", env_var)),
+        };
+        checks.push(match source {
+            Ok(source) => Check::ok(format!("{} key", name), format!("from {}", source)),
+            Err(e) => Check::problem(
+                Status::Error,
+                format!("{} key", name),
+                e,
+                format!(
+                    "Set {} or api_key (or api_key_cmd) in [providers.{}]",
+                    env_var, provider
+                ),
+            ),
+        });
+    }
+
+    let base_url = provider_config
+        .and_then(|c| c.base_url.clone().or_else(|| c.endpoint.clone()))
+        .unwrap_or_else(|| default_url.to_string());
+    if base_url.is_empty() {
+        checks.push(Check::problem(
+            Status::Error,
+            name,
+            "no endpoint configured",
+            format!("Set endpoint in [providers.{}]", provider),
+        ));
+        return checks;
+    }
+    let proxy = provider_config.and_then(|c| c.proxy.clone());
+
+    if known == "ollama" {
+        let model = provider_config
+            .map_or(crate::builder::default_model_for_provider("ollama"), |c| {
+                c.model.as_str()
+            });
+        checks.push(check_ollama(&name, &base_url, model, proxy.as_deref()).await);
+    } else {
+        checks.push(check_reachable(&name, &base_url, proxy.as_deref()).await);
+    }
+    checks
+}
+
+/// An HTTP client that couldn't be created from the `[http]` settings
+fn client_problem(name: &str, error: reqwest::Error) -> Check {
+    Check::problem(
+        Status::Error,
+        name,
+        format!("Can't create an HTTP client: {}", error),
+        "Check the [http] settings (proxy, ca_certificates)",
+    )
+}
+
+/// Whether the server at `base_url` answers at all; any HTTP status counts
+async fn check_reachable(name: &str, base_url: &str, proxy: Option<&str>) -> Check {
+    let client = match crate::http::client(base_url, TIMEOUT, proxy) {
+        Ok(client) => client,
+        Err(e) => return client_problem(name, e),
+    };
+    match client.get(base_url).send().await {
+        Ok(_) => Check::ok(name, format!("{} is reachable", base_url)),
+        Err(e) => Check::problem(
+            Status::Error,
+            name,
+            format!("{} is unreachable: {}", base_url, e),
+            "Check the network, proxy settings ([http] proxy) and base_url",
+        ),
+    }
+}
+
+/// Whether Ollama is running at `base_url` and has `model` pulled
+pub(crate) async fn check_ollama(
+    name: &str,
+    base_url: &str,
+    model: &str,
+    proxy: Option<&str>,
+) -> Check {
+    let url = format!("{}/api/tags", base_url.trim_end_matches('/'));
+    let client = match crate::http::client(&url, TIMEOUT, proxy) {
+        Ok(client) => client,
+        Err(e) => return client_problem(name, e),
+    };
+    let models = match ollama_models(&client, base_url).await {
+        Ok(models) => models,
+        Err(e) => {
+            return Check::problem(
+                Status::Error,
+                name,
+                format!("Ollama isn't running at {}: {}", base_url, e),
+                "Start it with `ollama serve`, or set base_url in [providers.ollama]",
+            )
+        }
+    };
+    if ollama_has_model(&models, model) {
+        Check::ok(name, format!("Ollama is running with {}", model))
+    } else {
+        Check::problem(
+            Status::Error,
+            name,
+            format!(
+                "Ollama is running but {} isn't pulled (have: {})",
+                model,
+                if models.is_empty() {
+                    "none".to_string()
+                } else {
+                    models.join(", ")
+                }
+            ),
+            format!("Run `ollama pull {}`", model),
+        )
+    }
+}
+
+async fn check_ocr(config: &AiConfig) -> Check {
+    match config.ocr.backend {
+        OcrBackend::Ollama => {
+            let base_url = config
+                .ocr
+                .base_url
+                .as_deref()
+                .unwrap_or("http://localhost:11434");
+            let model = config.ocr.model.as_deref().unwrap_or(OLLAMA_OCR_MODEL);
+            check_ollama("OCR (ollama)", base_url, model, None).await
+        }
+        OcrBackend::Tesseract => {
+            let binary = config.ocr.binary.as_deref().unwrap_or(TESSERACT_BINARY);
+            check_tesseract(binary).await
+        }
+        OcrBackend::Onnx => check_onnx(config.ocr.model_dir.as_deref()),
+        OcrBackend::Google => match std::env::var("GOOGLE_API_KEY") {
+            Ok(key) => check_vision_key(GOOGLE_VISION_URL, &key).await,
+            Err(_) => Check::problem(
+                Status::Warning,
+                "OCR (Google Vision)",
+                "GOOGLE_API_KEY is not set",
+                "Only needed for --image: set GOOGLE_API_KEY, or backend = \"ollama\" in [ocr]",
+            ),
+        },
+    }
+}
+
+/// Whether the Tesseract executable runs, and its version
+async fn check_tesseract(binary: &str) -> Check {
+    let name = "OCR (Tesseract)";
+    let run = tokio::process::Command::new(binary)
+        .arg("--version")
+        .kill_on_drop(true)
+        .output();
+    match tokio::time::timeout(TIMEOUT, run).await {
+        Ok(Ok(output)) if output.status.success() => {
+            let version = String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string();
+            Check::ok(name, version)
+        }
+        Ok(Ok(output)) => Check::problem(
+            Status::Warning,
+            name,
+            format!("{} --version failed ({})", binary, output.status),
+            "Only needed for --image: check the Tesseract install, or binary in [ocr]",
+        ),
+        Ok(Err(e)) => Check::problem(
+            Status::Warning,
+            name,
+            format!("{} could not be run: {}", binary, e),
+            "Only needed for --image: install Tesseract (tesseract-ocr), or set binary in [ocr]",
+        ),
+        Err(_) => Check::problem(
+            Status::Warning,
+            name,
+            format!("{} --version did not finish", binary),
+            "Only needed for --image: check the Tesseract install",
+        ),
+    }
+}
+
+/// Whether this build runs ONNX models and `model_dir` has the files they
+/// need
+fn check_onnx(model_dir: Option<&str>) -> Check {
+    let name = "OCR (on-device ONNX)";
+    if !cfg!(feature = "ocr-onnx") {
+        return Check::problem(
+            Status::Warning,
+            name,
+            "This build has no ONNX support",
+            "Only needed for --image: build with --features ocr-onnx, or pick another backend in [ocr]",
+        );
+    }
+    let Some(model_dir) = model_dir else {
+        return Check::problem(
+            Status::Warning,
+            name,
+            "model_dir is not set",
+            "Only needed for --image: set model_dir in [ocr] to the directory with det.onnx, rec.onnx and charset.txt",
+        );
+    };
+    let missing: Vec<&str> = ["det.onnx", "rec.onnx", "charset.txt"]
+        .into_iter()
+        .filter(|file| !std::path::Path::new(model_dir).join(file).is_file())
+        .collect();
+    if missing.is_empty() {
+        Check::ok(name, model_dir)
+    } else {
+        Check::problem(
+            Status::Warning,
+            name,
+            format!("{} is missing {}", model_dir, missing.join(", ")),
+            "Only needed for --image: put the detection and recognition models in model_dir",
+        )
+    }
+}
+
+/// Whether Google Vision accepts `key`, with a request that annotates nothing
+pub(crate) async fn check_vision_key(base_url: &str, key: &str) -> Check {
+    let name = "OCR (Google Vision)";
+    let url = format!("{}/v1/images:annotate?key={}", base_url, key);
+    let client = match crate::http::client(&url, TIMEOUT, None) {
+        Ok(client) => client,
+        Err(e) => return client_problem(name, e.without_url()),
+    };
+    let response = match client
+        .post(&url)
+        .json(&json!({ "requests": [] }))
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => {
+            return Check::problem(
+                Status::Error,
+                name,
+                format!("{} is unreachable: {}", base_url, e.without_url()),
+                "Check the network and proxy settings ([http] proxy)",
+            )
+        }
+    };
+    if response.status().is_success() {
+        return Check::ok(name, "GOOGLE_API_KEY is valid");
+    }
+    let status = response.status();
+    let body: Value = response.json().await.unwrap_or(Value::Null);
+    let message = body["error"]["message"]
+        .as_str()
+        .unwrap_or("no details")
+        .to_string();
+    Check::problem(
+        Status::Error,
+        name,
+        format!("GOOGLE_API_KEY was rejected ({}): {}", status, message),
+        "Create a key with the Cloud Vision API enabled and set GOOGLE_API_KEY",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+
+    #[tokio::test]
+    async fn test_ollama_model_pulled() {
+        let mut server = Server::new_async().await;
+        let _mock = server
+            .mock("GET", "/api/tags")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"models": [{"name": "llama3:latest"}, {"name": "llava:13b"}]}"#)
+            .create_async()
+            .await;
+
+        let check = check_ollama("Provider ollama", &server.url(), "llama3", None).await;
+        assert_eq!(check.status, Status::Ok);
+
+        let check = check_ollama("Provider ollama", &server.url(), "mistral", None).await;
+        assert_eq!(check.status, Status::Error);
+        assert!(check.detail.contains("llama3:latest, llava:13b"));
+        assert_eq!(check.hint.as_deref(), Some("Run `ollama pull mistral`"));
+    }
+
+    #[tokio::test]
+    async fn test_ollama_not_running() {
+        let check = check_ollama("Provider ollama", "http://127.0.0.1:9", "llama3", None).await;
+        assert_eq!(check.status, Status::Error);
+        assert!(check.hint.unwrap().contains("ollama serve"));
+    }
+
+    #[tokio::test]
+    async fn test_vision_key() {
+        let mut server = Server::new_async().await;
+        let _valid = server
+            .mock("POST", "/v1/images:annotate?key=good")
+            .with_status(200)
+            .with_body("{}")
+            .create_async()
+            .await;
+        let _invalid = server
+            .mock("POST", "/v1/images:annotate?key=bad")
+            .with_status(400)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"error": {"message": "API key not valid. Please pass a valid API key."}}"#,
+            )
+            .create_async()
+            .await;
+
+        assert_eq!(
+            check_vision_key(&server.url(), "good").await.status,
+            Status::Ok
+        );
+        let check = check_vision_key(&server.url(), "bad").await;
+        assert_eq!(check.status, Status::Error);
+        assert!(check.detail.contains("API key not valid"));
+    }
+
+    #[test]
+    fn test_used_providers() {
+        let mut config: AiConfig = config::Config::builder()
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+        config.default_provider = "openai".to_string();
+        config.fallback.enabled = true;
+        config.fallback.order = vec!["open_ai".to_string(), "ollama".to_string()];
+        assert_eq!(used_providers(&config), vec!["openai", "ollama"]);
+    }
+}
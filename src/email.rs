@@ -0,0 +1,401 @@
+//! Recipes from email: saved `.eml` files such as newsletters, including
+//! ones forwarded as attachments. Web pages a browser saved as MHTML
+//! (`.mht`, `.mhtml`) are MIME messages too, and are read the same way.
+
+use crate::error::ImportError;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+/// The parts of an email an import needs
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Email {
+    pub subject: Option<String>,
+    pub from: Option<String>,
+    /// First `text/html` part, decoded
+    pub html: Option<String>,
+    /// First `text/plain` part, decoded
+    pub text: Option<String>,
+    /// Where the HTML was saved from: an MHTML archive's
+    /// `Snapshot-Content-Location`, else the HTML part's `Content-Location`
+    pub location: Option<String>,
+}
+
+impl Email {
+    /// Parse a raw RFC 822 message.
+    ///
+    /// Multipart bodies are searched depth first, including forwarded
+    /// `message/rfc822` attachments. Base64 and quoted-printable parts are
+    /// decoded, UTF-8, Latin-1, Windows-1252 and ISO-8859-15 text is read
+    /// (anything else as UTF-8), and encoded-word headers (`=?UTF-8?Q?...?=`) are decoded.
+    pub fn parse(raw: &[u8]) -> Result<Self, ImportError> {
+        let (headers, _) = split_part(raw);
+        if !headers
+            .iter()
+            .any(|(name, _)| ["from", "subject", "content-type", "date"].contains(&name.as_str()))
+        {
+            return Err(ImportError::ParseError(
+                "Not an email: no From, Subject, Date or Content-Type header".to_string(),
+            ));
+        }
+
+        let mut email = Email {
+            subject: header(&headers, "subject").map(decode_words),
+            from: header(&headers, "from").map(decode_words),
+            ..Email::default()
+        };
+        email.collect_parts(raw, 0);
+        if let Some(location) = header(&headers, "snapshot-content-location") {
+            email.location = Some(location.to_string());
+        }
+        if email.html.is_none() && email.text.is_none() {
+            return Err(ImportError::ParseError(
+                "The email has no text or HTML body".to_string(),
+            ));
+        }
+        Ok(email)
+    }
+
+    /// The subject without `Fwd:`/`Re:` prefixes
+    pub fn title(&self) -> Option<String> {
+        let mut subject = self.subject.as_deref()?.trim();
+        while let Some((prefix, rest)) = subject.split_once(':') {
+            if !["fwd", "fw", "re", "wg", "tr"].contains(&prefix.trim().to_lowercase().as_str()) {
+                break;
+            }
+            subject = rest.trim();
+        }
+        Some(subject.to_string()).filter(|subject| !subject.is_empty())
+    }
+
+    fn collect_parts(&mut self, part: &[u8], depth: usize) {
+        // Guard against malicious nesting
+        if depth > 10 {
+            return;
+        }
+        let (headers, body) = split_part(part);
+        let content_type = header(&headers, "content-type").unwrap_or("text/plain");
+        let (mime, params) = parse_content_type(content_type);
+        let attachment = header(&headers, "content-disposition")
+            .is_some_and(|d| d.trim().to_lowercase().starts_with("attachment"));
+
+        if mime.starts_with("multipart/") {
+            if let Some(boundary) = param(&params, "boundary") {
+                for child in split_multipart(body, boundary) {
+                    self.collect_parts(child, depth + 1);
+                }
+            }
+            return;
+        }
+        if mime == "message/rfc822" {
+            self.collect_parts(body, depth + 1);
+            return;
+        }
+        if attachment || !(mime == "text/html" || mime == "text/plain") {
+            return;
+        }
+
+        let encoding = header(&headers, "content-transfer-encoding")
+            .unwrap_or("7bit")
+            .trim()
+            .to_lowercase();
+        let bytes = match encoding.as_str() {
+            "base64" => {
+                let cleaned: Vec<u8> = body
+                    .iter()
+                    .copied()
+                    .filter(|b| !b.is_ascii_whitespace())
+                    .collect();
+                match STANDARD.decode(cleaned) {
+                    Ok(bytes) => bytes,
+                    Err(_) => return,
+                }
+            }
+            "quoted-printable" => decode_quoted_printable(body, false),
+            _ => body.to_vec(),
+        };
+        let text = crate::charset::decode(&bytes, param(&params, "charset").unwrap_or("utf-8"));
+
+        let slot = if mime == "text/html" {
+            &mut self.html
+        } else {
+            &mut self.text
+        };
+        if slot.is_none() && !text.trim().is_empty() {
+            *slot = Some(text);
+            if mime == "text/html" {
+                self.location = header(&headers, "content-location").map(str::to_string);
+            }
+        }
+    }
+}
+
+/// Headers (lowercase name, unfolded value) and body of a message or part
+fn split_part(part: &[u8]) -> (Vec<(String, String)>, &[u8]) {
+    // A part without headers starts with the blank line
+    for blank in [&b"\r\n"[..], b"\n"] {
+        if let Some(body) = part.strip_prefix(blank) {
+            return (Vec::new(), body);
+        }
+    }
+    let (head, body) = match find(part, b"\r\n\r\n") {
+        Some(i) => (&part[..i], &part[i + 4..]),
+        None => match find(part, b"\n\n") {
+            Some(i) => (&part[..i], &part[i + 2..]),
+            None => (part, &part[part.len()..]),
+        },
+    };
+
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for line in String::from_utf8_lossy(head).lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+    (headers, body)
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, value)| value.as_str())
+}
+
+/// `text/html; charset="utf-8"` → ("text/html", [("charset", "utf-8")])
+fn parse_content_type(value: &str) -> (String, Vec<(String, String)>) {
+    let mut pieces = value.split(';');
+    let mime = pieces.next().unwrap_or_default().trim().to_lowercase();
+    let params = pieces
+        .filter_map(|piece| piece.split_once('='))
+        .map(|(name, value)| {
+            (
+                name.trim().to_lowercase(),
+                value.trim().trim_matches('"').to_string(),
+            )
+        })
+        .collect();
+    (mime, params)
+}
+
+fn param<'a>(params: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    params
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, value)| value.as_str())
+}
+
+/// The parts between `--boundary` lines, without the preamble and epilogue
+fn split_multipart<'a>(body: &'a [u8], boundary: &str) -> Vec<&'a [u8]> {
+    let delimiter = format!("--{}", boundary).into_bytes();
+    let mut parts = Vec::new();
+    let mut rest = body;
+    let mut started = false;
+    while let Some(i) = find(rest, &delimiter) {
+        if started {
+            parts.push(trim_line_end(&rest[..i]));
+        }
+        started = true;
+        rest = &rest[i + delimiter.len()..];
+        if rest.starts_with(b"--") {
+            break;
+        }
+        // Skip the rest of the delimiter line
+        match rest.iter().position(|&b| b == b'\n') {
+            Some(newline) => rest = &rest[newline + 1..],
+            None => break,
+        }
+    }
+    parts
+}
+
+fn trim_line_end(bytes: &[u8]) -> &[u8] {
+    let bytes = bytes.strip_suffix(b"\n").unwrap_or(bytes);
+    bytes.strip_suffix(b"\r").unwrap_or(bytes)
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Decode quoted-printable; in headers (`q_encoding`) `_` is a space
+fn decode_quoted_printable(bytes: &[u8], q_encoding: bool) -> Vec<u8> {
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'=' if bytes[i + 1..].starts_with(b"\r\n") => i += 3,
+            b'=' if bytes[i + 1..].starts_with(b"\n") => i += 2,
+            b'=' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                    Some(byte) => {
+                        out.push(byte);
+                        i += 3;
+                    }
+                    None => {
+                        out.push(b'=');
+                        i += 1;
+                    }
+                }
+            }
+            b'_' if q_encoding => {
+                out.push(b' ');
+                i += 1;
+            }
+            byte => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Decode RFC 2047 encoded words in a header value
+fn decode_words(value: &str) -> String {
+    let mut out = String::new();
+    let mut rest = value;
+    let mut last_was_word = false;
+    while let Some(start) = rest.find("=?") {
+        let decoded = rest[start + 2..].splitn(3, '?').collect::<Vec<_>>();
+        let (charset, encoding, tail) = match decoded.as_slice() {
+            [charset, encoding, tail] => (*charset, *encoding, *tail),
+            _ => break,
+        };
+        let Some(end) = tail.find("?=") else {
+            break;
+        };
+        let between = &rest[..start];
+        // Whitespace between two encoded words is dropped
+        if !(last_was_word && between.trim().is_empty()) {
+            out.push_str(between);
+        }
+        let text = &tail[..end];
+        let bytes = match encoding.to_ascii_uppercase().as_str() {
+            "B" => STANDARD.decode(text).unwrap_or_default(),
+            _ => decode_quoted_printable(text.as_bytes(), true),
+        };
+        out.push_str(&crate::charset::decode(&bytes, charset));
+        last_was_word = true;
+        rest = &tail[end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NEWSLETTER: &str = "From: Weeknight Kitchen <hello@example.com>\r
+Subject: =?UTF-8?Q?Fwd:_Cr=C3=A8me_br=C3=BBl=C3=A9e?=\r
+MIME-Version: 1.0\r
+Content-Type: multipart/alternative;\r
+ boundary=\"b1\"\r
+\r
+This is a multi-part message.\r
+--b1\r
+Content-Type: text/plain; charset=utf-8\r
+\r
+Plain version\r
+--b1\r
+Content-Type: text/html; charset=\"iso-8859-1\"\r
+Content-Transfer-Encoding: quoted-printable\r
+\r
+<h1>Cr=E8me br=FBl=E9e</h1><ul><li>4 egg yolks</li></ul><p>Whisk and bake=\r
+ at 150=B0C.</p>\r
+--b1--\r
+";
+
+    #[test]
+    fn test_parse_multipart_newsletter() {
+        let email = Email::parse(NEWSLETTER.as_bytes()).unwrap();
+        assert_eq!(email.subject.as_deref(), Some("Fwd: Crème brûlée"));
+        assert_eq!(email.title().as_deref(), Some("Crème brûlée"));
+        assert_eq!(
+            email.from.as_deref(),
+            Some("Weeknight Kitchen <hello@example.com>")
+        );
+        assert_eq!(email.text.as_deref(), Some("Plain version"));
+        assert_eq!(
+            email.html.as_deref(),
+            Some(
+                "<h1>Crème brûlée</h1><ul><li>4 egg yolks</li></ul><p>Whisk and bake at 150°C.</p>"
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_forwarded_attachment() {
+        let inner = "Subject: Pancakes\nContent-Type: text/html; charset=utf-8\nContent-Transfer-Encoding: base64\n\nPGgxPlBhbmNha2VzPC9oMT4=\n";
+        let eml = format!(
+            "From: me@example.com\nSubject: Fw: Pancakes\nContent-Type: multipart/mixed; boundary=outer\n\n--outer\nContent-Type: text/plain\n\nLook at this!\n--outer\nContent-Type: message/rfc822\n\n{}\n--outer--\n",
+            inner
+        );
+        let email = Email::parse(eml.as_bytes()).unwrap();
+        assert_eq!(email.html.as_deref(), Some("<h1>Pancakes</h1>"));
+        assert_eq!(email.text.as_deref(), Some("Look at this!"));
+        assert_eq!(email.title().as_deref(), Some("Pancakes"));
+    }
+
+    #[test]
+    fn test_parse_mhtml_archive() {
+        let mhtml = "From: <Saved by Blink>\r
+Snapshot-Content-Location: https://example.com/recipes/pancakes\r
+Subject: Fluffy Pancakes\r
+MIME-Version: 1.0\r
+Content-Type: multipart/related;\r
+\ttype=\"text/html\";\r
+\tboundary=\"----MultipartBoundary--abc----\"\r
+\r
+\r
+------MultipartBoundary--abc----\r
+Content-Type: text/html\r
+Content-ID: <frame-1@mhtml.blink>\r
+Content-Transfer-Encoding: quoted-printable\r
+Content-Location: https://example.com/recipes/pancakes?utm_source=feed\r
+\r
+<html><head><meta http-equiv=3D\"Content-Type\" content=3D\"text/html; charset=\r
+=3DUTF-8\"></head><body><h1>Fluffy Pancakes</h1></body></html>\r
+------MultipartBoundary--abc----\r
+Content-Type: text/css\r
+Content-Location: cid:css-1@mhtml.blink\r
+\r
+h1 { color: red; }\r
+------MultipartBoundary--abc------\r
+";
+        let page = Email::parse(mhtml.as_bytes()).unwrap();
+        assert_eq!(
+            page.location.as_deref(),
+            Some("https://example.com/recipes/pancakes")
+        );
+        assert!(page
+            .html
+            .as_deref()
+            .unwrap()
+            .contains("<body><h1>Fluffy Pancakes</h1></body>"));
+
+        // Without the snapshot header, the HTML part says where it's from
+        let part_only = mhtml.replace(
+            "Snapshot-Content-Location: https://example.com/recipes/pancakes\r\n",
+            "",
+        );
+        let page = Email::parse(part_only.as_bytes()).unwrap();
+        assert_eq!(
+            page.location.as_deref(),
+            Some("https://example.com/recipes/pancakes?utm_source=feed")
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_non_email() {
+        assert!(Email::parse(b"<html><body>Pancakes</body></html>").is_err());
+        assert!(Email::parse(b"Subject: Empty\n\n").is_err());
+    }
+}
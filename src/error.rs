@@ -1,3 +1,6 @@
+use crate::url_to_text::html::diagnostics::ExtractionDiagnostics;
+use serde::Serialize;
+use std::time::Duration;
 use thiserror::Error;
 
 /// Errors that can occur during recipe import operations
@@ -11,9 +14,10 @@ pub enum ImportError {
     #[error("Failed to parse recipe: {0}")]
     ParseError(String),
 
-    /// No extractor could successfully parse the recipe
+    /// No extractor could successfully parse the recipe; the diagnostics
+    /// say what each extractor found on the page
     #[error("No extractor could parse the recipe from this webpage")]
-    NoExtractorMatched,
+    NoExtractorMatched(Box<ExtractionDiagnostics>),
 
     /// Failed to convert recipe to Cooklang format
     #[error("Conversion failed: {0}")]
@@ -42,4 +46,169 @@ pub enum ImportError {
     /// Configuration error
     #[error("Configuration error: {0}")]
     ConfigError(#[from] config::ConfigError),
+
+    /// A stage needs the network but offline mode is on; says what needed it
+    #[error("Offline mode: {0} needs the network")]
+    OfflineError(String),
+
+    /// Failed to read or write a file
+    #[error("I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    /// A recipe photo couldn't be downloaded, decoded or re-encoded
+    #[error("Image processing failed: {0}")]
+    ImageError(String),
+
+    /// A web page answered with an HTTP error status
+    #[error("Failed to fetch page: HTTP {code} from {url}")]
+    HttpStatus { code: u16, url: String },
+
+    /// An LLM provider's API returned an error; `status` is the HTTP status
+    /// when the response had one
+    #[error("{provider} API error: {message}{}", status.map(|code| format!(" (HTTP {})", code)).unwrap_or_default())]
+    LlmApi {
+        provider: String,
+        status: Option<u16>,
+        message: String,
+    },
+
+    /// Text recognition failed (Google Vision or an Ollama vision model)
+    #[error("OCR failed: {0}")]
+    OcrError(String),
+
+    /// A request to a page or provider ran past its timeout
+    #[error("Request timed out: {0}")]
+    Timeout(#[source] reqwest::Error),
+
+    /// An LLM provider refused the request for exceeding its rate limit;
+    /// `retry_after` is how long it asked to wait, when it said
+    #[error("{provider} rate limit exceeded{}", retry_after.map(|wait| format!(", retry after {}s", wait.as_secs())).unwrap_or_default())]
+    RateLimited {
+        provider: String,
+        retry_after: Option<Duration>,
+    },
+
+    /// The conversion's confidence score is below the minimum quality asked
+    /// for (`--min-quality`, `min_quality` in config.toml)
+    #[error("Conversion confidence {confidence:.2} is below the minimum quality {min:.2}")]
+    LowQuality { confidence: f32, min: f32 },
+}
+
+/// The broad cause of an [`ImportError`], for callers that react to the
+/// kind of failure rather than the message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// The page couldn't be fetched: network error or HTTP error status
+    Fetch,
+    /// The page or text was read but no recipe was found in it
+    NoRecipe,
+    /// The LLM provider failed, refused the request or returned no recipe
+    Llm,
+    /// config.toml, an environment variable or a provider's settings (such
+    /// as its API key) are missing or invalid
+    Config,
+    /// A page or provider request ran past its timeout
+    Timeout,
+    /// The options or input can't be imported as given
+    Input,
+    /// The recipe was converted, but scored below the minimum quality
+    LowQuality,
+    /// Anything else: files, photos, OCR
+    Other,
+}
+
+impl ErrorKind {
+    /// The name used in machine-readable output, e.g. "no_recipe"
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorKind::Fetch => "fetch",
+            ErrorKind::NoRecipe => "no_recipe",
+            ErrorKind::Llm => "llm",
+            ErrorKind::Config => "config",
+            ErrorKind::Timeout => "timeout",
+            ErrorKind::Input => "input",
+            ErrorKind::LowQuality => "low_quality",
+            ErrorKind::Other => "other",
+        }
+    }
+}
+
+impl ImportError {
+    /// What kind of failure this is
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ImportError::FetchError(_)
+            | ImportError::HeaderError(_)
+            | ImportError::HttpStatus { .. } => ErrorKind::Fetch,
+            ImportError::NoExtractorMatched(_)
+            | ImportError::ParseError(_)
+            | ImportError::ExtractionError(_) => ErrorKind::NoRecipe,
+            ImportError::ConversionError(_)
+            | ImportError::LlmApi { .. }
+            | ImportError::RateLimited { .. } => ErrorKind::Llm,
+            ImportError::ConfigError(_) | ImportError::EnvError(_) => ErrorKind::Config,
+            ImportError::Timeout(_) => ErrorKind::Timeout,
+            ImportError::LowQuality { .. } => ErrorKind::LowQuality,
+            ImportError::BuilderError(_)
+            | ImportError::InvalidMarkdown(_)
+            | ImportError::OfflineError(_) => ErrorKind::Input,
+            ImportError::IoError(_) | ImportError::ImageError(_) | ImportError::OcrError(_) => {
+                ErrorKind::Other
+            }
+        }
+    }
+
+    /// Keep extraction diagnostics, typed import errors and timeouts from a
+    /// pipeline error; wrap anything else with `other`
+    pub(crate) fn from_pipeline(
+        error: Box<dyn std::error::Error + Send + Sync>,
+        other: fn(String) -> ImportError,
+    ) -> ImportError {
+        let error = match error.downcast::<ExtractionDiagnostics>() {
+            Ok(diagnostics) => return ImportError::NoExtractorMatched(diagnostics),
+            Err(error) => error,
+        };
+        let error = match error.downcast::<ImportError>() {
+            Ok(error) => return *error,
+            Err(error) => error,
+        };
+        match error.downcast::<reqwest::Error>() {
+            Ok(error) if error.is_timeout() => ImportError::Timeout(*error),
+            Ok(error) => other(error.to_string()),
+            Err(error) => other(error.to_string()),
+        }
+    }
+
+    /// [`from_pipeline`](ImportError::from_pipeline) for a pipeline that
+    /// fetches a page, where a network error means the fetch failed
+    pub(crate) fn from_fetch(error: Box<dyn std::error::Error + Send + Sync>) -> ImportError {
+        match error.downcast::<reqwest::Error>() {
+            Ok(error) if !error.is_timeout() => ImportError::FetchError(*error),
+            Ok(error) => ImportError::from_pipeline(error, ImportError::BuilderError),
+            Err(error) => ImportError::from_pipeline(error, ImportError::BuilderError),
+        }
+    }
+
+    /// The error for an LLM API response with an error `message`:
+    /// [`RateLimited`](ImportError::RateLimited) for HTTP 429, else
+    /// [`LlmApi`](ImportError::LlmApi)
+    pub(crate) fn llm_api(
+        provider: &str,
+        status: reqwest::StatusCode,
+        headers: &reqwest::header::HeaderMap,
+        message: impl Into<String>,
+    ) -> ImportError {
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return ImportError::RateLimited {
+                provider: provider.to_string(),
+                retry_after: crate::converters::retry_after(headers),
+            };
+        }
+        ImportError::LlmApi {
+            provider: provider.to_string(),
+            status: (!status.is_success()).then_some(status.as_u16()),
+            message: message.into(),
+        }
+    }
 }
@@ -0,0 +1,394 @@
+//! Provider benchmarks (`cooklang-import eval DIR`)
+//!
+//! An eval set is a directory of recipes, each as `NAME.txt` (the recipe
+//! text a provider is given) next to `NAME.cook` (a vetted conversion to
+//! score it against). [`run`] converts every recipe with every candidate
+//! provider and model and scores the results, so models can be compared on
+//! the same recipes for quality, speed and cost:
+//!
+//! ```text
+//! Provider                     Cases  Ingredients  Amounts  Steps  Syntax  Latency  Cost
+//! anthropic/claude-haiku-4-5   3/3    97%          92%      81%    3/3     2.4s     $0.0061
+//! ollama/llama3.2              3/3    85%          71%      64%    2/3     9.8s     $0.0000
+//! ```
+
+use crate::converters::{self, check_cooklang, compare_recipes, step_similarity};
+use crate::{ImportResult, LlmProvider, RecipeImporter};
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+use tracing::info;
+
+/// One recipe of an eval set
+#[derive(Debug, Clone)]
+pub struct EvalCase {
+    /// File name without the extension
+    pub name: String,
+    /// Recipe text to convert
+    pub source: String,
+    /// The conversion to score against
+    pub reference: String,
+}
+
+/// The recipes of the eval set in `dir`, by name. Every `NAME.txt` needs a
+/// `NAME.cook` reference.
+pub fn load_cases(dir: &Path) -> std::io::Result<Vec<EvalCase>> {
+    let mut sources: Vec<_> = std::fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<_, _>>()?;
+    sources.retain(|path| path.extension().is_some_and(|ext| ext == "txt"));
+    sources.sort();
+
+    let mut cases = Vec::with_capacity(sources.len());
+    for source in sources {
+        let reference = source.with_extension("cook");
+        if !reference.is_file() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!(
+                    "{} has no reference {}",
+                    source.display(),
+                    reference.display()
+                ),
+            ));
+        }
+        cases.push(EvalCase {
+            name: source
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned(),
+            source: std::fs::read_to_string(&source)?,
+            reference: std::fs::read_to_string(&reference)?,
+        });
+    }
+    Ok(cases)
+}
+
+/// A provider, and optionally a model other than its configured one, to
+/// evaluate
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub provider: LlmProvider,
+    pub model: Option<String>,
+}
+
+impl Candidate {
+    /// "provider" or "provider/model"
+    pub fn label(&self) -> String {
+        match &self.model {
+            Some(model) => format!("{}/{}", self.provider.as_str(), model),
+            None => self.provider.as_str().to_string(),
+        }
+    }
+}
+
+/// How close a conversion came to its reference
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Scores {
+    /// Share of the reference's ingredients the conversion has
+    pub ingredient_recall: f32,
+    /// Share of the conversion's ingredients the reference has
+    pub ingredient_precision: f32,
+    /// Share of the shared ingredients with the reference's amount
+    pub amount_accuracy: f32,
+    /// How alike the steps read (see [`step_similarity`])
+    pub step_similarity: f32,
+    /// Whether the conversion is valid Cooklang
+    pub valid_syntax: bool,
+}
+
+impl Scores {
+    /// Harmonic mean of ingredient recall and precision
+    pub fn ingredient_f1(&self) -> f32 {
+        let sum = self.ingredient_recall + self.ingredient_precision;
+        if sum == 0.0 {
+            0.0
+        } else {
+            2.0 * self.ingredient_recall * self.ingredient_precision / sum
+        }
+    }
+}
+
+/// Score `cooklang` against the vetted `reference`
+pub fn score(reference: &str, cooklang: &str) -> Scores {
+    let diff = compare_recipes(reference, cooklang);
+    let share = |part: usize, whole: usize| match whole {
+        0 => 1.0,
+        whole => part as f32 / whole as f32,
+    };
+    let both = diff.ingredients_in_both;
+    Scores {
+        ingredient_recall: share(both, both + diff.only_in_first.len()),
+        ingredient_precision: share(both, both + diff.only_in_second.len()),
+        // Nothing to get right only when the reference has no ingredients
+        amount_accuracy: match (both, diff.only_in_first.len()) {
+            (0, missed) if missed > 0 => 0.0,
+            _ => share(both - diff.amount_changes.len(), both),
+        },
+        step_similarity: step_similarity(reference, cooklang),
+        valid_syntax: check_cooklang(cooklang).is_ok(),
+    }
+}
+
+/// One candidate's result on one recipe
+#[derive(Debug, Clone, Serialize)]
+pub struct CaseResult {
+    pub case: String,
+    /// Why the conversion failed; the other fields are empty if it did
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scores: Option<Scores>,
+    /// Model that answered, as the provider reported it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_tokens: Option<u32>,
+    /// Estimated cost in USD, when the model's price is known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+}
+
+/// One candidate's results on the whole eval set
+#[derive(Debug, Clone, Serialize)]
+pub struct CandidateResult {
+    pub candidate: String,
+    pub cases: Vec<CaseResult>,
+}
+
+impl CandidateResult {
+    fn scores(&self) -> impl Iterator<Item = &Scores> {
+        self.cases.iter().filter_map(|case| case.scores.as_ref())
+    }
+
+    /// Number of recipes converted without an error
+    pub fn converted(&self) -> usize {
+        self.scores().count()
+    }
+
+    /// Mean of `metric` over the converted recipes
+    pub fn mean(&self, metric: impl Fn(&Scores) -> f32) -> Option<f32> {
+        let converted = self.converted();
+        (converted > 0).then(|| self.scores().map(metric).sum::<f32>() / converted as f32)
+    }
+
+    /// Mean conversion time of the converted recipes, in milliseconds
+    pub fn mean_latency_ms(&self) -> Option<u64> {
+        let converted = self.converted() as u64;
+        (converted > 0).then(|| {
+            self.cases
+                .iter()
+                .filter(|case| case.scores.is_some())
+                .map(|case| case.latency_ms)
+                .sum::<u64>()
+                / converted
+        })
+    }
+
+    /// Estimated cost of the whole set, if every conversion's is known
+    pub fn total_cost(&self) -> Option<f64> {
+        self.cases
+            .iter()
+            .filter(|case| case.scores.is_some())
+            .map(|case| case.cost)
+            .sum()
+    }
+}
+
+/// Results of [`run`]; displays as a comparison table
+#[derive(Debug, Clone, Serialize)]
+pub struct EvalReport {
+    pub cases: usize,
+    pub candidates: Vec<CandidateResult>,
+}
+
+impl fmt::Display for EvalReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .candidates
+            .iter()
+            .map(|result| result.candidate.len())
+            .max()
+            .unwrap_or(0)
+            .max("Provider".len());
+        writeln!(
+            f,
+            "{:width$}  Cases  Ingredients  Amounts  Steps  Syntax  Latency  Cost",
+            "Provider"
+        )?;
+        let percent = |value: Option<f32>| match value {
+            Some(value) => format!("{:.0}%", value * 100.0),
+            None => "-".to_string(),
+        };
+        for result in &self.candidates {
+            let converted = result.converted();
+            let valid = result.scores().filter(|scores| scores.valid_syntax).count();
+            let latency = match result.mean_latency_ms() {
+                Some(ms) => format!("{:.1}s", ms as f64 / 1000.0),
+                None => "-".to_string(),
+            };
+            let cost = match result.total_cost() {
+                Some(cost) if converted > 0 => format!("${:.4}", cost),
+                _ => "-".to_string(),
+            };
+            writeln!(
+                f,
+                "{:width$}  {:<5}  {:<11}  {:<7}  {:<5}  {:<6}  {:<7}  {}",
+                result.candidate,
+                format!("{}/{}", converted, self.cases),
+                percent(result.mean(Scores::ingredient_f1)),
+                percent(result.mean(|scores| scores.amount_accuracy)),
+                percent(result.mean(|scores| scores.step_similarity)),
+                format!("{}/{}", valid, converted),
+                latency,
+                cost
+            )?;
+        }
+        for result in &self.candidates {
+            for case in &result.cases {
+                if let Some(error) = &case.error {
+                    writeln!(f, "{} failed on {}: {}", result.candidate, case.case, error)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Convert every case with every candidate, one at a time, and score the
+/// results against the references.
+///
+/// Conversions skip the cache, so latency and cost are those of a real
+/// request. A failed conversion is recorded in its [`CaseResult`] and the
+/// run goes on.
+pub async fn run(cases: &[EvalCase], candidates: &[Candidate]) -> EvalReport {
+    let mut results = Vec::with_capacity(candidates.len());
+    for candidate in candidates {
+        let label = candidate.label();
+        let mut case_results = Vec::with_capacity(cases.len());
+        for case in cases {
+            info!("Evaluating {} on {}", label, case.name);
+            case_results.push(run_case(case, candidate).await);
+        }
+        results.push(CandidateResult {
+            candidate: label,
+            cases: case_results,
+        });
+    }
+    EvalReport {
+        cases: cases.len(),
+        candidates: results,
+    }
+}
+
+async fn run_case(case: &EvalCase, candidate: &Candidate) -> CaseResult {
+    let mut builder = RecipeImporter::builder()
+        .text(case.source.clone())
+        .provider(candidate.provider.clone())
+        .no_cache();
+    if let Some(model) = &candidate.model {
+        builder = builder.model(model.clone());
+    }
+    let mut result = CaseResult {
+        case: case.name.clone(),
+        error: None,
+        scores: None,
+        model: None,
+        latency_ms: 0,
+        input_tokens: None,
+        output_tokens: None,
+        cost: None,
+    };
+    match builder.build().await {
+        Ok(ImportResult::Cooklang {
+            content,
+            conversion_metadata,
+            ..
+        }) => {
+            result.scores = Some(score(&case.reference, &content));
+            if let Some(metadata) = conversion_metadata {
+                let usage = &metadata.tokens_used;
+                result.cost = match (&metadata.model_version, usage.input_tokens) {
+                    (Some(model), Some(input)) => converters::estimate_cost(
+                        candidate.provider.as_str(),
+                        model,
+                        input.into(),
+                        usage.output_tokens.unwrap_or(0).into(),
+                    ),
+                    _ => None,
+                };
+                result.latency_ms = metadata.latency_ms;
+                result.input_tokens = usage.input_tokens;
+                result.output_tokens = usage.output_tokens;
+                result.model = metadata.model_version;
+            }
+        }
+        Ok(_) => result.error = Some("no Cooklang was returned".to_string()),
+        Err(e) => result.error = Some(e.to_string()),
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REFERENCE: &str = "---\ntitle: Fried Eggs\n---\n\n\
+        Melt @butter{1%tbsp} in a #pan{}.\n\n\
+        Fry @eggs{2} for ~{3%minutes}.\n\n\
+        Season with @salt{}.";
+
+    #[test]
+    fn test_score() {
+        let perfect = score(REFERENCE, REFERENCE);
+        assert_eq!(perfect.ingredient_f1(), 1.0);
+        assert_eq!(perfect.amount_accuracy, 1.0);
+        assert_eq!(perfect.step_similarity, 1.0);
+        assert!(perfect.valid_syntax);
+
+        // Salt dropped, pepper invented, one egg too many
+        let conversion = "Melt @butter{1%tbsp} in a #pan{}.\n\n\
+            Fry @eggs{3} for ~{3%minutes}.\n\n\
+            Season with @pepper{}.";
+        let scores = score(REFERENCE, conversion);
+        assert!((scores.ingredient_recall - 2.0 / 3.0).abs() < 1e-6);
+        assert!((scores.ingredient_precision - 2.0 / 3.0).abs() < 1e-6);
+        assert_eq!(scores.amount_accuracy, 0.5);
+        assert!(scores.step_similarity < 1.0);
+
+        let unmarked = score(REFERENCE, "Fry the eggs.");
+        assert_eq!(unmarked.ingredient_recall, 0.0);
+        assert_eq!(unmarked.amount_accuracy, 0.0);
+    }
+
+    #[test]
+    fn test_report_table() {
+        let case = |name: &str, error: Option<&str>| CaseResult {
+            case: name.to_string(),
+            error: error.map(str::to_string),
+            scores: error.is_none().then(|| score(REFERENCE, REFERENCE)),
+            model: None,
+            latency_ms: 1500,
+            input_tokens: None,
+            output_tokens: None,
+            cost: Some(0.002),
+        };
+        let report = EvalReport {
+            cases: 2,
+            candidates: vec![CandidateResult {
+                candidate: "anthropic".to_string(),
+                cases: vec![case("eggs", None), case("soup", Some("timed out"))],
+            }],
+        };
+        assert_eq!(
+            report.to_string(),
+            "Provider   Cases  Ingredients  Amounts  Steps  Syntax  Latency  Cost\n\
+             anthropic  1/2    100%         100%     100%   1/1     1.5s     $0.0020\n\
+             anthropic failed on soup: timed out\n"
+        );
+    }
+}
@@ -0,0 +1,392 @@
+use crate::config::{load_config, HttpConfig};
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, ACCEPT_LANGUAGE, USER_AGENT};
+use reqwest::{redirect, Certificate, Client, ClientBuilder, NoProxy, Proxy};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::warn;
+
+/// Start an HTTP client for requests to `url` with the shared network
+/// settings applied.
+///
+/// The proxy is, in order: the `proxy` argument (builder), the `[http] proxy`
+/// config setting, or the standard `HTTP_PROXY`/`HTTPS_PROXY`/`NO_PROXY`
+/// environment variables. Certificates listed in `[http] ca_certificates`
+/// or the `SSL_CERT_FILE` environment variable are trusted in addition to
+/// the bundled roots. Certificate checks are turned off only if `url`'s host
+/// is listed in `[http] danger_accept_invalid_certs`, and such clients refuse
+/// redirects to hosts that aren't listed. Invalid settings are
+/// logged and skipped so a bad entry doesn't take every client down.
+pub(crate) fn client_builder(url: &str, proxy: Option<&str>) -> ClientBuilder {
+    let mut config = load_config().map(|c| c.http).unwrap_or_default();
+    if let Some(path) = std::env::var_os("SSL_CERT_FILE") {
+        config
+            .ca_certificates
+            .push(path.to_string_lossy().into_owned());
+    }
+    apply(Client::builder(), &config, url, proxy)
+}
+
+/// An HTTP client for requests to `url` with the shared network settings
+/// and a request timeout
+///
+/// Fails rather than falling back to a default client, which would ignore
+/// the proxy, certificates and timeout.
+pub(crate) fn client(url: &str, timeout: Duration, proxy: Option<&str>) -> reqwest::Result<Client> {
+    shared_client("api", url, timeout, proxy, || {
+        client_builder(url, proxy).timeout(timeout).build()
+    })
+}
+
+/// Sent as the User-Agent of page fetches unless `[fetch] user_agent` says
+/// otherwise; several recipe sites refuse reqwest's own
+pub(crate) const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36";
+
+/// The headers a browser sends when opening a page: the configured
+/// User-Agent plus standard Accept and Accept-Language
+pub(crate) fn browser_headers() -> HeaderMap {
+    let user_agent = load_config().ok().and_then(|c| c.fetch.user_agent);
+    let user_agent = user_agent
+        .as_deref()
+        .and_then(|agent| match HeaderValue::from_str(agent) {
+            Ok(value) => Some(value),
+            Err(_) => {
+                warn!("Ignoring invalid [fetch] user_agent {:?}", agent);
+                None
+            }
+        })
+        .unwrap_or(HeaderValue::from_static(DEFAULT_USER_AGENT));
+
+    let mut headers = HeaderMap::new();
+    headers.insert(USER_AGENT, user_agent);
+    headers.insert(
+        ACCEPT,
+        HeaderValue::from_static(
+            "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,*/*;q=0.8",
+        ),
+    );
+    headers.insert(ACCEPT_LANGUAGE, HeaderValue::from_static("en-US,en;q=0.9"));
+    headers
+}
+
+/// Pages are read up to this many bytes unless `[http] max_page_size` says
+/// otherwise
+const DEFAULT_MAX_PAGE_SIZE: u64 = 10 * 1024 * 1024;
+
+/// The most bytes of a page worth reading
+pub(crate) fn max_page_size() -> u64 {
+    load_config()
+        .ok()
+        .and_then(|c| c.http.max_page_size)
+        .unwrap_or(DEFAULT_MAX_PAGE_SIZE)
+}
+
+/// What a kept client was built for: purpose, host, timeout and proxy
+type ClientKey = (&'static str, String, Duration, Option<String>);
+
+/// Clients kept by [`keep_clients`]; `None` builds a fresh client per request
+static KEPT_CLIENTS: Mutex<Option<HashMap<ClientKey, Client>>> = Mutex::new(None);
+
+/// Reuse clients (and their connection pools) across imports for the rest
+/// of the process. Only for long-running processes on one runtime: pooled
+/// connections belong to the runtime that opened them.
+pub(crate) fn keep_clients() {
+    KEPT_CLIENTS
+        .lock()
+        .unwrap()
+        .get_or_insert_with(HashMap::new);
+}
+
+/// The kept client for `purpose` and `url`'s host, built with `build` the
+/// first time; just `build` unless [`keep_clients`] was called
+pub(crate) fn shared_client(
+    purpose: &'static str,
+    url: &str,
+    timeout: Duration,
+    proxy: Option<&str>,
+    build: impl FnOnce() -> reqwest::Result<Client>,
+) -> reqwest::Result<Client> {
+    let mut kept = KEPT_CLIENTS.lock().unwrap();
+    let Some(clients) = kept.as_mut() else {
+        return build();
+    };
+    let key = (purpose, host(url), timeout, proxy.map(str::to_string));
+    if let Some(client) = clients.get(&key) {
+        return Ok(client.clone());
+    }
+    let client = build()?;
+    clients.insert(key, client.clone());
+    Ok(client)
+}
+
+/// Check that a proxy URL is usable before starting an import
+pub(crate) fn validate_proxy(proxy: &str) -> Result<(), String> {
+    Proxy::all(proxy)
+        .map(|_| ())
+        .map_err(|e| format!("Invalid proxy URL '{}': {}", proxy, e))
+}
+
+fn apply(
+    mut builder: ClientBuilder,
+    config: &HttpConfig,
+    url: &str,
+    proxy: Option<&str>,
+) -> ClientBuilder {
+    if let Some(url) = proxy.or(config.proxy.as_deref()) {
+        match Proxy::all(url) {
+            Ok(proxy) => builder = builder.proxy(proxy.no_proxy(NoProxy::from_env())),
+            Err(e) => warn!("Ignoring invalid proxy URL '{}': {}", url, e),
+        }
+    }
+
+    for path in &config.ca_certificates {
+        let certificates = std::fs::read(path)
+            .map_err(|e| e.to_string())
+            .and_then(|pem| Certificate::from_pem_bundle(&pem).map_err(|e| e.to_string()));
+        match certificates {
+            Ok(certificates) => {
+                for certificate in certificates {
+                    builder = builder.add_root_certificate(certificate);
+                }
+            }
+            Err(e) => warn!("Ignoring CA certificate '{}': {}", path, e),
+        }
+    }
+
+    if accepts_invalid_certs(config, url) {
+        warn!(
+            "TLS certificate verification is DISABLED for {} (http.danger_accept_invalid_certs)",
+            host(url)
+        );
+        // Redirects would otherwise carry the unchecked client to any host
+        let listed = config.danger_accept_invalid_certs.clone();
+        builder = builder
+            .danger_accept_invalid_certs(true)
+            .redirect(redirect::Policy::custom(move |attempt| {
+                if attempt.previous().len() >= MAX_REDIRECTS {
+                    attempt.error("too many redirects")
+                } else if is_listed(&listed, attempt.url().as_str()) {
+                    attempt.follow()
+                } else {
+                    let message = format!(
+                        "Not following redirect to {}: TLS certificate verification is disabled and it isn't in http.danger_accept_invalid_certs",
+                        host(attempt.url().as_str())
+                    );
+                    attempt.error(message)
+                }
+            }));
+    }
+
+    builder
+}
+
+/// Redirects followed by clients with certificate checks off, as many as
+/// reqwest's default policy allows
+const MAX_REDIRECTS: usize = 10;
+
+/// Whether `url`'s host (or a parent domain) is in `danger_accept_invalid_certs`
+fn accepts_invalid_certs(config: &HttpConfig, url: &str) -> bool {
+    is_listed(&config.danger_accept_invalid_certs, url)
+}
+
+/// Whether `url`'s host is one of `hosts` or a subdomain of one
+fn is_listed(hosts: &[String], url: &str) -> bool {
+    let host = host(url);
+    hosts.iter().any(|listed| {
+        let listed = listed.to_lowercase();
+        host == listed || host.ends_with(&format!(".{}", listed))
+    })
+}
+
+/// Whether a URL points at this machine (localhost or a loopback address),
+/// so requests to it stay off the network
+pub(crate) fn is_loopback(url: &str) -> bool {
+    let host = host(url);
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    host == "localhost"
+        || host.ends_with(".localhost")
+        || host
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Lowercase host of a URL, without credentials or port
+fn host(url: &str) -> String {
+    let authority = url
+        .split("://")
+        .nth(1)
+        .unwrap_or(url)
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default();
+    let host_port = authority.rsplit('@').next().unwrap_or_default();
+    let host = match host_port.rsplit_once(':') {
+        Some((name, port)) if !port.is_empty() && port.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => host_port,
+    };
+    host.to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+
+    #[tokio::test]
+    async fn test_requests_go_through_proxy() {
+        // The mock server stands in for the proxy; the request still names
+        // the original host
+        let mut proxy = Server::new_async().await;
+        let mock = proxy
+            .mock("GET", "/pancakes")
+            .match_header("host", "recipes.invalid")
+            .with_status(200)
+            .with_body("via proxy")
+            .create();
+
+        let client = apply(
+            Client::builder(),
+            &HttpConfig::default(),
+            "http://recipes.invalid/pancakes",
+            Some(&proxy.url()),
+        )
+        .build()
+        .unwrap();
+        let body = client
+            .get("http://recipes.invalid/pancakes")
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+
+        assert_eq!(body, "via proxy");
+        mock.assert();
+    }
+
+    #[test]
+    fn test_is_loopback() {
+        assert!(is_loopback("http://localhost:11434"));
+        assert!(is_loopback("http://127.0.0.1:11434/v1"));
+        assert!(is_loopback("http://[::1]:11434"));
+        assert!(!is_loopback("http://ollama.lan:11434"));
+        assert!(!is_loopback("https://api.openai.com"));
+    }
+
+    #[test]
+    fn test_validate_proxy() {
+        assert!(validate_proxy("http://proxy.corp:3128").is_ok());
+        assert!(validate_proxy("not a url").is_err());
+    }
+
+    #[test]
+    fn test_missing_ca_certificate_is_skipped() {
+        let config = HttpConfig {
+            ca_certificates: vec!["/nonexistent/corp-ca.pem".to_string()],
+            ..HttpConfig::default()
+        };
+        assert!(
+            apply(Client::builder(), &config, "https://example.com", None)
+                .build()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_host() {
+        assert_eq!(
+            host("https://Intranet.Corp:8443/recipes?x=1"),
+            "intranet.corp"
+        );
+        assert_eq!(host("https://user:pw@wiki.corp/page"), "wiki.corp");
+        assert_eq!(host("http://[::1]:11434"), "[::1]");
+    }
+
+    #[test]
+    fn test_invalid_certs_only_for_listed_hosts() {
+        let config = HttpConfig {
+            danger_accept_invalid_certs: vec!["Corp.Example".to_string()],
+            ..HttpConfig::default()
+        };
+        assert!(accepts_invalid_certs(
+            &config,
+            "https://corp.example/recipe"
+        ));
+        assert!(accepts_invalid_certs(
+            &config,
+            "https://wiki.corp.example:8443/"
+        ));
+        assert!(!accepts_invalid_certs(&config, "https://notcorp.example/"));
+        assert!(!accepts_invalid_certs(&config, "https://api.openai.com/v1"));
+        assert!(!accepts_invalid_certs(
+            &HttpConfig::default(),
+            "https://corp.example/"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_insecure_client_follows_redirects_only_to_listed_hosts() {
+        let mut server = Server::new_async().await;
+        let port = server
+            .host_with_port()
+            .rsplit(':')
+            .next()
+            .unwrap()
+            .to_string();
+        server
+            .mock("GET", "/moved")
+            .with_status(302)
+            .with_header("location", "/recipe")
+            .create();
+        server
+            .mock("GET", "/recipe")
+            .with_status(200)
+            .with_body("recipe")
+            .create();
+        server
+            .mock("GET", "/elsewhere")
+            .with_status(302)
+            .with_header("location", &format!("http://localhost:{}/recipe", port))
+            .create();
+
+        let config = HttpConfig {
+            danger_accept_invalid_certs: vec!["127.0.0.1".to_string()],
+            ..HttpConfig::default()
+        };
+        let url = format!("{}/moved", server.url());
+        let client = apply(Client::builder(), &config, &url, None)
+            .build()
+            .unwrap();
+
+        let same_host = client.get(&url).send().await.unwrap();
+        assert_eq!(same_host.text().await.unwrap(), "recipe");
+
+        let cross_host = client
+            .get(format!("{}/elsewhere", server.url()))
+            .send()
+            .await;
+        assert!(cross_host.unwrap_err().is_redirect());
+    }
+
+    /// This test is ignored by default since it requires network access
+    #[tokio::test]
+    #[ignore]
+    async fn test_self_signed_certificate_accepted_for_listed_host() {
+        let config = HttpConfig {
+            danger_accept_invalid_certs: vec!["self-signed.badssl.com".to_string()],
+            ..HttpConfig::default()
+        };
+        let url = "https://self-signed.badssl.com/";
+
+        let listed = apply(Client::builder(), &config, url, None)
+            .build()
+            .unwrap();
+        assert!(listed.get(url).send().await.is_ok());
+
+        let strict = apply(Client::builder(), &HttpConfig::default(), url, None)
+            .build()
+            .unwrap();
+        assert!(strict.get(url).send().await.is_err());
+    }
+}
@@ -0,0 +1,60 @@
+use super::ImageSource;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use std::error::Error;
+
+/// An image ready to be sent to a multimodal model
+#[derive(Debug, Clone)]
+pub struct EncodedImage {
+    /// MIME type, e.g. "image/jpeg"
+    pub mime_type: String,
+    /// Base64-encoded image bytes
+    pub data: String,
+}
+
+impl EncodedImage {
+    /// The image as a `data:` URL (OpenAI-compatible `image_url`)
+    pub fn data_url(&self) -> String {
+        format!("data:{};base64,{}", self.mime_type, self.data)
+    }
+}
+
+/// Load an image source, transcoding HEIC (and with the `image` feature
+/// PNG, WebP and GIF) to JPEG, and detect its MIME type
+pub async fn encode(source: &ImageSource) -> Result<EncodedImage, Box<dyn Error + Send + Sync>> {
+    let bytes = super::transcode::load(source).await?;
+    Ok(EncodedImage {
+        mime_type: sniff_mime_type(&bytes).to_string(),
+        data: STANDARD.encode(&bytes),
+    })
+}
+
+/// Identify common image formats by their magic bytes, defaulting to JPEG
+fn sniff_mime_type(bytes: &[u8]) -> &'static str {
+    match bytes {
+        [0x89, b'P', b'N', b'G', ..] => "image/png",
+        [b'G', b'I', b'F', b'8', ..] => "image/gif",
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => "image/webp",
+        _ => "image/jpeg",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_encode_base64_detects_png() {
+        let png = STANDARD.encode([0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 0]);
+        let image = encode(&ImageSource::Base64(png.clone())).await.unwrap();
+        assert_eq!(image.mime_type, "image/png");
+        assert_eq!(image.data, png);
+        assert_eq!(image.data_url(), format!("data:image/png;base64,{}", png));
+    }
+
+    #[test]
+    fn test_sniff_mime_type() {
+        assert_eq!(sniff_mime_type(b"RIFF\0\0\0\0WEBPVP8 "), "image/webp");
+        assert_eq!(sniff_mime_type(b"GIF89a"), "image/gif");
+        assert_eq!(sniff_mime_type(&[0xFF, 0xD8, 0xFF]), "image/jpeg");
+    }
+}
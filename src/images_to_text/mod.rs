@@ -1,3 +1,18 @@
+mod encoded;
 mod ocr;
+mod ollama;
+#[cfg(feature = "ocr-onnx")]
+mod onnx;
+mod pages;
+mod tesseract;
+mod transcode;
 
-pub use ocr::{extract, ImageSource};
+pub use encoded::{encode, EncodedImage};
+pub(crate) use ocr::GOOGLE_VISION_URL;
+pub use ocr::{extract, extract_all, ImageSource};
+pub(crate) use ollama::DEFAULT_MODEL as OLLAMA_OCR_MODEL;
+#[cfg(feature = "ocr-onnx")]
+pub use onnx::extract_on_device;
+pub use pages::{join_pages, order, ImageOrder};
+pub(crate) use tesseract::DEFAULT_BINARY as TESSERACT_BINARY;
+pub use transcode::to_jpeg;
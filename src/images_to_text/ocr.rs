@@ -1,8 +1,10 @@
+use crate::config::OcrBackend;
+use crate::ImportError;
 use base64::{engine::general_purpose::STANDARD, Engine as _};
-use log::debug;
-use reqwest::Client;
 use serde_json::{json, Value};
 use std::error::Error;
+use std::time::Duration;
+use tracing::debug;
 // Note: Path and fs imports commented out as the functions using them are currently unused
 // use std::path::Path;
 // use tokio::fs;
@@ -16,80 +18,209 @@ pub enum ImageSource {
     Base64(String),
 }
 
+/// The Google Cloud Vision API
+pub(crate) const GOOGLE_VISION_URL: &str = "https://vision.googleapis.com";
+
+/// Most images Google Vision annotates in one `images:annotate` request
+const GOOGLE_BATCH_SIZE: usize = 16;
+
 /// Main entry point for extracting text from an image source
 ///
-/// Routes to the appropriate handler based on the image source type
+/// Routes to the appropriate handler based on the image source type. The OCR
+/// backend (Google Vision, a local Ollama vision model, Tesseract or
+/// on-device ONNX models) comes from the `[ocr]` config section.
 ///
 /// # Arguments
 /// * `source` - The image source (file path or base64 data)
+/// * `timeout` - Timeout for the OCR request
+/// * `proxy` - Proxy URL overriding the configured one
 ///
 /// # Returns
 /// The extracted text from the image
 ///
 /// # Errors
 /// Returns an error if OCR processing fails
-pub async fn extract(source: &ImageSource) -> Result<String, Box<dyn Error + Send + Sync>> {
-    match source {
-        ImageSource::Path(path) => extract_from_file(path).await,
-        ImageSource::Base64(data) => extract_from_base64(data).await,
+pub async fn extract(
+    source: &ImageSource,
+    timeout: Duration,
+    proxy: Option<&str>,
+) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let text = extract_all(std::slice::from_ref(source), timeout, proxy)
+        .await?
+        .pop()
+        .unwrap_or_default();
+    if text.trim().is_empty() {
+        return Err(ImportError::OcrError("No text detected in image".to_string()).into());
     }
+    Ok(text)
 }
 
-/// Extracts text from an image file
+/// Extracts the text of several images, in order
 ///
-/// # Arguments
-/// * `path` - Path to the image file
+/// Google Vision reads up to 16 images per request, so a photographed
+/// recipe of a few pages takes a single call; an Ollama model or Tesseract
+/// reads one image at a time. HEIC photos are sent as JPEG (see
+/// [`to_jpeg`](super::to_jpeg)). An image without any text gives an empty
+/// string.
 ///
-/// # Returns
-/// The extracted text from the image
-async fn extract_from_file(path: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
-    let image_data = tokio::fs::read(path).await?;
-    let base64 = STANDARD.encode(&image_data);
-    call_google_vision(&base64).await
+/// # Errors
+/// Returns an error if an image can't be read or OCR fails for any image
+pub async fn extract_all(
+    sources: &[ImageSource],
+    timeout: Duration,
+    proxy: Option<&str>,
+) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
+    let mut images = Vec::with_capacity(sources.len());
+    for source in sources {
+        images.push(super::transcode::load(source).await?);
+    }
+
+    let config = crate::config::load_config()
+        .map(|c| c.ocr)
+        .unwrap_or_default();
+    // Google Vision and Ollama take the images as base64
+    let encoded = || {
+        images
+            .iter()
+            .map(|image| STANDARD.encode(image))
+            .collect::<Vec<_>>()
+    };
+
+    let mut texts = Vec::with_capacity(images.len());
+    match config.backend {
+        OcrBackend::Google => {
+            for batch in encoded().chunks(GOOGLE_BATCH_SIZE) {
+                texts.extend(
+                    call_google_vision(GOOGLE_VISION_URL, batch, timeout, proxy)
+                        .await
+                        .map_err(ocr_error)?,
+                );
+            }
+        }
+        OcrBackend::Ollama => {
+            let base_url = config
+                .base_url
+                .as_deref()
+                .unwrap_or(super::ollama::DEFAULT_BASE_URL);
+            let model = config
+                .model
+                .as_deref()
+                .unwrap_or(super::ollama::DEFAULT_MODEL);
+            for image in &encoded() {
+                texts.push(
+                    super::ollama::call_ollama_vision(base_url, model, image, timeout, proxy)
+                        .await
+                        .map_err(ocr_error)?,
+                );
+            }
+        }
+        OcrBackend::Tesseract => {
+            let binary = config
+                .binary
+                .as_deref()
+                .unwrap_or(super::tesseract::DEFAULT_BINARY);
+            for image in &images {
+                texts.push(
+                    super::tesseract::run_tesseract(
+                        binary,
+                        image,
+                        config.language.as_deref(),
+                        timeout,
+                    )
+                    .await
+                    .map_err(ocr_error)?,
+                );
+            }
+        }
+        OcrBackend::Onnx => {
+            let model_dir = config.model_dir.as_deref().ok_or_else(|| {
+                ImportError::OcrError("The onnx OCR backend needs [ocr] model_dir".to_string())
+            })?;
+            for image in images {
+                texts.push(onnx(model_dir, image, timeout).await?);
+            }
+        }
+    }
+    Ok(texts)
 }
 
-/// Extracts text from base64-encoded image data
-///
-/// # Arguments
-/// * `data` - Base64-encoded image data
-///
-/// # Returns
-/// The extracted text from the image
-async fn extract_from_base64(data: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
-    call_google_vision(data).await
+#[cfg(feature = "ocr-onnx")]
+async fn onnx(
+    model_dir: &str,
+    image: Vec<u8>,
+    timeout: Duration,
+) -> Result<String, Box<dyn Error + Send + Sync>> {
+    super::onnx::run_onnx(model_dir.as_ref(), image, timeout)
+        .await
+        .map_err(ocr_error)
+}
+
+#[cfg(not(feature = "ocr-onnx"))]
+async fn onnx(
+    _model_dir: &str,
+    _image: Vec<u8>,
+    _timeout: Duration,
+) -> Result<String, Box<dyn Error + Send + Sync>> {
+    Err(ImportError::OcrError(
+        "The onnx OCR backend needs cooklang-import built with the `ocr-onnx` feature".to_string(),
+    )
+    .into())
+}
+
+/// A failed OCR call as [`ImportError::OcrError`], keeping timeouts
+fn ocr_error(error: Box<dyn Error + Send + Sync>) -> Box<dyn Error + Send + Sync> {
+    Box::new(ImportError::from_pipeline(error, ImportError::OcrError))
 }
 
-/// Calls the Google Cloud Vision API to perform OCR on base64-encoded image data
+/// Calls the Google Cloud Vision API to perform OCR on a batch of
+/// base64-encoded images in one request
 ///
 /// # Arguments
-/// * `base64_image` - Base64-encoded image data
+/// * `base_url` - The Vision API URL
+/// * `base64_images` - Base64-encoded image data, at most 16 images
+/// * `timeout` - Request timeout
+/// * `proxy` - Proxy URL overriding the configured one
 ///
 /// # Returns
-/// The extracted text from the image
-async fn call_google_vision(base64_image: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+/// The text of each image, in order; empty for an image without text
+#[tracing::instrument(name = "ocr", skip_all, fields(backend = "google", images = base64_images.len()))]
+async fn call_google_vision(
+    base_url: &str,
+    base64_images: &[String],
+    timeout: Duration,
+    proxy: Option<&str>,
+) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
     // Get API key from environment
     let api_key = std::env::var("GOOGLE_API_KEY")
         .map_err(|_| "GOOGLE_API_KEY environment variable not set")?;
 
     // Create request to Google Vision API
-    let client = Client::new();
     let url = format!(
-        "https://vision.googleapis.com/v1/images:annotate?key={}",
+        "{}/v1/images:annotate?key={}",
+        base_url.trim_end_matches('/'),
         api_key
     );
+    let client = crate::http::client(&url, timeout, proxy)?;
 
-    let request_body = json!({
-        "requests": [{
-            "image": {
-                "content": base64_image
-            },
-            "features": [{
-                "type": "TEXT_DETECTION"
-            }]
-        }]
-    });
+    let requests: Vec<Value> = base64_images
+        .iter()
+        .map(|image| {
+            json!({
+                "image": {
+                    "content": image
+                },
+                "features": [{
+                    "type": "TEXT_DETECTION"
+                }]
+            })
+        })
+        .collect();
+    let request_body = json!({ "requests": requests });
 
-    debug!("Sending OCR request to Google Vision API");
+    debug!(
+        "Sending OCR request for {} images to Google Vision API",
+        base64_images.len()
+    );
 
     let response = client
         .post(&url)
@@ -108,20 +239,35 @@ async fn call_google_vision(base64_image: &str) -> Result<String, Box<dyn Error
     let response_body: Value = response.json().await?;
     debug!("Google Vision API response: {:?}", response_body);
 
-    // Extract text from response
-    // The API returns all detected text in the first annotation's description
-    let text = response_body["responses"][0]["fullTextAnnotation"]["text"]
-        .as_str()
-        .ok_or("No text found in image")?
-        .to_string();
-
-    if text.trim().is_empty() {
-        return Err("No text detected in image".into());
+    // One response per image, in request order; each holds all the image's
+    // detected text in its full text annotation
+    let responses = response_body["responses"]
+        .as_array()
+        .filter(|responses| responses.len() == base64_images.len())
+        .ok_or("Google Vision API returned the wrong number of responses")?;
+    let mut texts = Vec::with_capacity(responses.len());
+    for (index, response) in responses.iter().enumerate() {
+        if let Some(message) = response["error"]["message"].as_str() {
+            return Err(format!(
+                "Google Vision API error for image {}: {}",
+                index + 1,
+                message
+            )
+            .into());
+        }
+        let text = response["fullTextAnnotation"]["text"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        debug!(
+            "Extracted text from image {}: {} characters",
+            index + 1,
+            text.len()
+        );
+        texts.push(text);
     }
 
-    debug!("Extracted text from image: {} characters", text.len());
-
-    Ok(text)
+    Ok(texts)
 }
 
 // Note: These functions are currently unused but may be needed for future direct OCR support
@@ -188,13 +334,50 @@ mod tests {
 
         // Test using call_google_vision directly
         let fake_base64_image = STANDARD.encode(b"fake image data");
-        let result = call_google_vision(&fake_base64_image).await;
+        let result = call_google_vision(
+            GOOGLE_VISION_URL,
+            &[fake_base64_image],
+            Duration::from_secs(5),
+            None,
+        )
+        .await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("GOOGLE_API_KEY"));
 
+        // With a key, a batch of images goes out in one request and the
+        // texts come back in request order. Checked here rather than in a
+        // test of its own, which would race with the key being unset.
+        std::env::set_var("GOOGLE_API_KEY", "test-key");
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/images:annotate?key=test-key")
+            .match_body(mockito::Matcher::Regex(
+                r#""content":"cGFnZSAx".*"content":"cGFnZSAy""#.to_string(),
+            ))
+            .with_body(
+                r#"{"responses": [
+                    {"fullTextAnnotation": {"text": "Page one"}},
+                    {}
+                ]}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let texts = call_google_vision(
+            &server.url(),
+            &[STANDARD.encode("page 1"), STANDARD.encode("page 2")],
+            Duration::from_secs(5),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(texts, ["Page one", ""]);
+        mock.assert_async().await;
+
         // Restore original key if it existed
-        if let Some(key) = original_key {
-            std::env::set_var("GOOGLE_API_KEY", key);
+        match original_key {
+            Some(key) => std::env::set_var("GOOGLE_API_KEY", key),
+            None => std::env::remove_var("GOOGLE_API_KEY"),
         }
     }
 }
@@ -0,0 +1,126 @@
+use serde_json::{json, Value};
+use std::error::Error;
+use std::time::Duration;
+use tracing::debug;
+
+pub(crate) const DEFAULT_MODEL: &str = "llama3.2-vision";
+pub(crate) const DEFAULT_BASE_URL: &str = "http://localhost:11434";
+
+const TRANSCRIBE_PROMPT: &str = "Transcribe all text in this image exactly as written, \
+including handwriting, preserving line breaks and reading order. \
+Output only the transcribed text, without commentary.";
+
+/// Transcribes text from a base64-encoded image with an Ollama multimodal model
+///
+/// # Arguments
+/// * `base_url` - Ollama server URL
+/// * `model` - Vision-capable model (e.g. llama3.2-vision, llava)
+/// * `base64_image` - Base64-encoded image data
+/// * `timeout` - Request timeout
+/// * `proxy` - Proxy URL overriding the configured one
+///
+/// # Returns
+/// The extracted text from the image
+#[tracing::instrument(name = "ocr", skip_all, fields(backend = "ollama", model = %model))]
+pub(crate) async fn call_ollama_vision(
+    base_url: &str,
+    model: &str,
+    base64_image: &str,
+    timeout: Duration,
+    proxy: Option<&str>,
+) -> Result<String, Box<dyn Error + Send + Sync>> {
+    debug!("Sending OCR request to Ollama model {}", model);
+
+    let response = crate::http::client(base_url, timeout, proxy)?
+        .post(format!("{}/api/chat", base_url.trim_end_matches('/')))
+        .json(&json!({
+            "model": model,
+            "messages": [{
+                "role": "user",
+                "content": TRANSCRIBE_PROMPT,
+                "images": [base64_image]
+            }],
+            "stream": false,
+            "options": {"temperature": 0}
+        }))
+        .send()
+        .await?;
+
+    let status = response.status();
+    let response_body: Value = response.json().await?;
+    debug!("Ollama vision response: {:?}", response_body);
+
+    if let Some(error) = response_body.get("error") {
+        let error_message = error.as_str().unwrap_or("Unknown error");
+        return Err(format!("Ollama vision error ({}): {}", status, error_message).into());
+    }
+
+    let text = response_body["message"]["content"]
+        .as_str()
+        .ok_or("No text found in Ollama response")?
+        .trim()
+        .to_string();
+
+    if text.is_empty() {
+        return Err("No text detected in image".into());
+    }
+
+    debug!("Extracted text from image: {} characters", text.len());
+
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+
+    #[tokio::test]
+    async fn test_ollama_vision_ocr() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/api/chat")
+            .match_body(mockito::Matcher::PartialJson(json!({
+                "model": "llava",
+                "messages": [{"images": ["aW1hZ2U="]}]
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"message": {"role": "assistant", "content": "2 eggs\nFry.\n"}}"#)
+            .create();
+
+        let text = call_ollama_vision(
+            &server.url(),
+            "llava",
+            "aW1hZ2U=",
+            Duration::from_secs(5),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(text, "2 eggs\nFry.");
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_ollama_vision_model_missing() {
+        let mut server = Server::new_async().await;
+        let _mock = server
+            .mock("POST", "/api/chat")
+            .with_status(404)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"error": "model \"llava\" not found, try pulling it first"}"#)
+            .create();
+
+        let err = call_ollama_vision(
+            &server.url(),
+            "llava",
+            "aW1hZ2U=",
+            Duration::from_secs(5),
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("not found"));
+    }
+}
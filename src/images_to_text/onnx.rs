@@ -0,0 +1,631 @@
+//! On-device text recognition with ONNX models, run in pure Rust by tract
+//!
+//! The models are a PaddleOCR-style pair in one directory:
+//! - `det.onnx`: text detection (DB), an image in and a map of how likely
+//!   each pixel is to be text out
+//! - `rec.onnx`: text recognition (CTC), a 48-pixel-high line in and the
+//!   character scores at each step along it out
+//! - `charset.txt`: the recognition model's characters, one per line
+//!
+//! The image never leaves the device and no system library or server is
+//! needed, which is what the mobile bindings want.
+
+use super::ImageSource;
+use image::{imageops, imageops::FilterType, RgbImage};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::debug;
+use tract_onnx::prelude::*;
+
+/// Side of the square the detection model reads; larger images are scaled
+/// down to fit and the rest is padded
+const DETECTION_SIZE: usize = 960;
+/// Height and greatest width of a line for the recognition model
+const LINE_HEIGHT: usize = 48;
+const LINE_WIDTH: usize = 320;
+/// Pixels scoring above this are text
+const TEXT_THRESHOLD: f32 = 0.3;
+/// Regions scoring below this on average are dropped
+const BOX_THRESHOLD: f32 = 0.5;
+/// Regions narrower or shorter than this many pixels are noise
+const MIN_BOX_SIZE: usize = 3;
+/// How far regions are grown to cover whole glyphs, as DB's unclip ratio
+const UNCLIP_RATIO: f32 = 1.5;
+/// Normalization of the detection model's input (ImageNet)
+const MEAN: [f32; 3] = [0.485, 0.456, 0.406];
+const STD: [f32; 3] = [0.229, 0.224, 0.225];
+
+type Plan = TypedRunnableModel<TypedModel>;
+
+/// Models loaded by [`models`], kept so batch imports load them once
+static LOADED: Mutex<Option<(PathBuf, Arc<OnnxOcr>)>> = Mutex::new(None);
+
+/// Reads the text of an image on this device with the ONNX models in
+/// `model_dir`, as the `onnx` backend of `[ocr]` does
+///
+/// # Errors
+/// Returns an error if the models or the image can't be read, or no text is
+/// found
+pub async fn extract_on_device(
+    source: &ImageSource,
+    model_dir: impl AsRef<Path>,
+    timeout: Duration,
+) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let image = super::transcode::load(source).await?;
+    let text = run_onnx(model_dir.as_ref(), image, timeout).await?;
+    if text.trim().is_empty() {
+        return Err(crate::ImportError::OcrError("No text detected in image".to_string()).into());
+    }
+    Ok(text)
+}
+
+/// Reads the text of an image with the ONNX models in `model_dir`
+///
+/// Inference is CPU-bound, so it runs on a blocking thread.
+///
+/// # Returns
+/// The text, one line per line of the image
+#[tracing::instrument(name = "ocr", skip_all, fields(backend = "onnx"))]
+pub(crate) async fn run_onnx(
+    model_dir: &Path,
+    image: Vec<u8>,
+    timeout: Duration,
+) -> Result<String, Box<dyn Error + Send + Sync>> {
+    debug!("Reading {} bytes with {}", image.len(), model_dir.display());
+    let model_dir = model_dir.to_path_buf();
+    let read = tokio::task::spawn_blocking(move || models(&model_dir)?.read(&image));
+    let text = tokio::time::timeout(timeout, read)
+        .await
+        .map_err(|_| format!("On-device OCR took longer than {:?}", timeout))???;
+    debug!("Read {} characters", text.len());
+    Ok(text)
+}
+
+/// The models in `dir`, loaded on first use
+fn models(dir: &Path) -> Result<Arc<OnnxOcr>, Box<dyn Error + Send + Sync>> {
+    let mut loaded = LOADED.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((loaded_dir, models)) = loaded.as_ref() {
+        if loaded_dir == dir {
+            return Ok(models.clone());
+        }
+    }
+    let models = Arc::new(OnnxOcr::load(dir)?);
+    *loaded = Some((dir.to_path_buf(), models.clone()));
+    Ok(models)
+}
+
+/// A detection and a recognition model, ready to run
+pub(crate) struct OnnxOcr {
+    detection: Plan,
+    recognition: Plan,
+    /// Recognition classes after the CTC blank
+    charset: Vec<String>,
+}
+
+impl OnnxOcr {
+    fn load(dir: &Path) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let model = |name: &str| {
+            let path = dir.join(name);
+            onnx()
+                .model_for_path(&path)
+                .map_err(|e| format!("Failed to load {}: {}", path.display(), e))
+        };
+        let (detection, recognition) = (model("det.onnx")?, model("rec.onnx")?);
+        let charset_path = dir.join("charset.txt");
+        let charset = std::fs::read_to_string(&charset_path)
+            .map_err(|e| format!("Failed to read {}: {}", charset_path.display(), e))?;
+        OnnxOcr::new(
+            detection,
+            recognition,
+            charset.lines().map(str::to_string).collect(),
+        )
+    }
+
+    /// Fix the models' input shapes and optimize them for those
+    fn new(
+        detection: InferenceModel,
+        recognition: InferenceModel,
+        charset: Vec<String>,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let plan = |model: InferenceModel, height: usize, width: usize| -> TractResult<Plan> {
+            model
+                .with_input_fact(0, f32::fact([1, 3, height, width]).into())?
+                .into_optimized()?
+                .into_runnable()
+        };
+        Ok(OnnxOcr {
+            detection: plan(detection, DETECTION_SIZE, DETECTION_SIZE)
+                .map_err(|e| format!("Unsupported text detection model: {}", e))?,
+            recognition: plan(recognition, LINE_HEIGHT, LINE_WIDTH)
+                .map_err(|e| format!("Unsupported text recognition model: {}", e))?,
+            charset,
+        })
+    }
+
+    /// The text of an encoded image, lines top to bottom and the words of a
+    /// line left to right
+    fn read(&self, image: &[u8]) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let image = image::load_from_memory(image)
+            .map_err(|e| format!("Failed to decode image: {}", e))?
+            .to_rgb8();
+        let mut lines = Vec::new();
+        for line in reading_order(self.detect(&image)?) {
+            let mut words = Vec::new();
+            for text_box in line {
+                let word = self.recognize(&image, &text_box)?;
+                if !word.trim().is_empty() {
+                    words.push(word.trim().to_string());
+                }
+            }
+            if !words.is_empty() {
+                lines.push(words.join(" "));
+            }
+        }
+        Ok(lines.join("\n"))
+    }
+
+    /// Where the text is, in image pixels
+    fn detect(&self, image: &RgbImage) -> Result<Vec<TextBox>, Box<dyn Error + Send + Sync>> {
+        let scale = (DETECTION_SIZE as f32 / image.width().max(image.height()) as f32).min(1.0);
+        let width = ((image.width() as f32 * scale).round() as usize).max(1);
+        let height = ((image.height() as f32 * scale).round() as usize).max(1);
+        let resized = imageops::resize(image, width as u32, height as u32, FilterType::Triangle);
+        let input = tract_ndarray::Array4::from_shape_fn(
+            (1, 3, DETECTION_SIZE, DETECTION_SIZE),
+            |(_, channel, y, x)| {
+                if x < width && y < height {
+                    let value = resized.get_pixel(x as u32, y as u32)[channel] as f32 / 255.0;
+                    (value - MEAN[channel]) / STD[channel]
+                } else {
+                    0.0
+                }
+            },
+        );
+        let output = self.detection.run(tvec!(input.into_tvalue()))?;
+        let map = output[0].to_array_view::<f32>()?;
+        if map.shape() != [1, 1, DETECTION_SIZE, DETECTION_SIZE] {
+            return Err(format!("Unexpected text detection output shape {:?}", map.shape()).into());
+        }
+        // Padding isn't part of the image
+        let scores: Vec<f32> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (y, x)))
+            .map(|(y, x)| map[[0, 0, y, x]])
+            .collect();
+        Ok(text_boxes(&scores, width, height)
+            .into_iter()
+            .map(|text_box| text_box.scaled(1.0 / scale, image.width(), image.height()))
+            .collect())
+    }
+
+    /// The text in one box of the image
+    fn recognize(
+        &self,
+        image: &RgbImage,
+        text_box: &TextBox,
+    ) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let (x, y) = (text_box.left as u32, text_box.top as u32);
+        let crop_width = (text_box.right as u32).saturating_sub(x).max(1);
+        let crop_height = (text_box.bottom as u32).saturating_sub(y).max(1);
+        let crop = imageops::crop_imm(image, x, y, crop_width, crop_height).to_image();
+        // Keep the aspect ratio, squeezing only lines too long to fit
+        let width = ((crop_width as f32 * LINE_HEIGHT as f32 / crop_height as f32).ceil() as usize)
+            .clamp(1, LINE_WIDTH);
+        let line = imageops::resize(
+            &crop,
+            width as u32,
+            LINE_HEIGHT as u32,
+            FilterType::Triangle,
+        );
+        let input = tract_ndarray::Array4::from_shape_fn(
+            (1, 3, LINE_HEIGHT, LINE_WIDTH),
+            |(_, channel, y, x)| {
+                if x < width {
+                    line.get_pixel(x as u32, y as u32)[channel] as f32 / 127.5 - 1.0
+                } else {
+                    0.0
+                }
+            },
+        );
+        let output = self.recognition.run(tvec!(input.into_tvalue()))?;
+        let scores = output[0].to_array_view::<f32>()?;
+        let &[1, steps, classes] = scores.shape() else {
+            return Err(format!(
+                "Unexpected text recognition output shape {:?}",
+                scores.shape()
+            )
+            .into());
+        };
+        let scores = scores
+            .as_slice()
+            .map(<[f32]>::to_vec)
+            .unwrap_or_else(|| scores.iter().copied().collect());
+        Ok(ctc_decode(&scores, steps, classes, &self.charset))
+    }
+}
+
+/// A region of text, with exclusive right and bottom edges
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TextBox {
+    left: f32,
+    top: f32,
+    right: f32,
+    bottom: f32,
+}
+
+impl TextBox {
+    fn center_y(&self) -> f32 {
+        (self.top + self.bottom) / 2.0
+    }
+
+    fn height(&self) -> f32 {
+        self.bottom - self.top
+    }
+
+    /// Scaled by `factor` and kept inside a `width` by `height` image
+    fn scaled(&self, factor: f32, width: u32, height: u32) -> TextBox {
+        TextBox {
+            left: (self.left * factor).max(0.0),
+            top: (self.top * factor).max(0.0),
+            right: (self.right * factor).min(width as f32),
+            bottom: (self.bottom * factor).min(height as f32),
+        }
+    }
+}
+
+/// The text regions of a `width` by `height` probability map (row by row):
+/// the connected pixels scoring above [`TEXT_THRESHOLD`], grown by
+/// [`UNCLIP_RATIO`] as DB does, leaving out small and faint ones
+fn text_boxes(scores: &[f32], width: usize, height: usize) -> Vec<TextBox> {
+    let mut seen = vec![false; scores.len()];
+    let mut boxes = Vec::new();
+    for start in 0..scores.len() {
+        if seen[start] || scores[start] <= TEXT_THRESHOLD {
+            continue;
+        }
+        seen[start] = true;
+        let (mut left, mut top, mut right, mut bottom) = (width, height, 0, 0);
+        let (mut total, mut count) = (0.0, 0);
+        let mut pending = vec![start];
+        while let Some(pixel) = pending.pop() {
+            let (x, y) = (pixel % width, pixel / width);
+            left = left.min(x);
+            top = top.min(y);
+            right = right.max(x + 1);
+            bottom = bottom.max(y + 1);
+            total += scores[pixel];
+            count += 1;
+            let neighbours = [
+                (x > 0).then(|| pixel - 1),
+                (x + 1 < width).then(|| pixel + 1),
+                (y > 0).then(|| pixel - width),
+                (y + 1 < height).then(|| pixel + width),
+            ];
+            for next in neighbours.into_iter().flatten() {
+                if !seen[next] && scores[next] > TEXT_THRESHOLD {
+                    seen[next] = true;
+                    pending.push(next);
+                }
+            }
+        }
+        let (box_width, box_height) = (right - left, bottom - top);
+        if box_width.min(box_height) < MIN_BOX_SIZE || total / (count as f32) < BOX_THRESHOLD {
+            continue;
+        }
+        let (box_width, box_height) = (box_width as f32, box_height as f32);
+        let grow = box_width * box_height * UNCLIP_RATIO / (2.0 * (box_width + box_height));
+        boxes.push(TextBox {
+            left: (left as f32 - grow).max(0.0),
+            top: (top as f32 - grow).max(0.0),
+            right: (right as f32 + grow).min(width as f32),
+            bottom: (bottom as f32 + grow).min(height as f32),
+        });
+    }
+    boxes
+}
+
+/// Boxes grouped into lines, top to bottom, each left to right. A box
+/// belongs to the line above when its middle is within half a line of that
+/// line's middle.
+fn reading_order(mut boxes: Vec<TextBox>) -> Vec<Vec<TextBox>> {
+    boxes.sort_by(|a, b| a.center_y().total_cmp(&b.center_y()));
+    let mut lines: Vec<Vec<TextBox>> = Vec::new();
+    for text_box in boxes {
+        match lines.last_mut() {
+            Some(line)
+                if (text_box.center_y() - line[0].center_y()).abs()
+                    < line[0].height().min(text_box.height()) / 2.0 =>
+            {
+                line.push(text_box)
+            }
+            _ => lines.push(vec![text_box]),
+        }
+    }
+    for line in &mut lines {
+        line.sort_by(|a, b| a.left.total_cmp(&b.left));
+    }
+    lines
+}
+
+/// Greedy CTC decoding of `steps` rows of `classes` scores: the best class
+/// at each step, repeats merged and blanks (class 0) dropped. Class `i` is
+/// `charset[i - 1]`, and the one after the charset a space, as PaddleOCR's
+/// models have it.
+fn ctc_decode(scores: &[f32], steps: usize, classes: usize, charset: &[String]) -> String {
+    let mut text = String::new();
+    let mut previous = 0;
+    for step in scores.chunks(classes).take(steps) {
+        let best = step
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map_or(0, |(class, _)| class);
+        if best != 0 && best != previous {
+            match charset.get(best - 1) {
+                Some(character) => text.push_str(character),
+                None if best - 1 == charset.len() => text.push(' '),
+                None => {}
+            }
+        }
+        previous = best;
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tract_onnx::pb::{
+        attribute_proto::AttributeType, type_proto, AttributeProto, GraphProto, ModelProto,
+        NodeProto, OperatorSetIdProto, TensorProto, TypeProto, ValueInfoProto,
+    };
+
+    fn charset(characters: &str) -> Vec<String> {
+        characters.chars().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_ctc_decode() {
+        let charset = charset("abc");
+        // blank, a, a, blank, a, b, space, c
+        let best = [0, 1, 1, 0, 1, 2, 4, 3];
+        let scores: Vec<f32> = best
+            .iter()
+            .flat_map(|&class| (0..5).map(move |c| if c == class { 0.9 } else { 0.02 }))
+            .collect();
+        assert_eq!(ctc_decode(&scores, best.len(), 5, &charset), "aab c");
+    }
+
+    /// A probability map with the given `(left, top, right, bottom)`
+    /// rectangles at `score`
+    fn map(
+        width: usize,
+        height: usize,
+        rects: &[(usize, usize, usize, usize)],
+        score: f32,
+    ) -> Vec<f32> {
+        let mut scores = vec![0.0; width * height];
+        for &(left, top, right, bottom) in rects {
+            for y in top..bottom {
+                for x in left..right {
+                    scores[y * width + x] = score;
+                }
+            }
+        }
+        scores
+    }
+
+    #[test]
+    fn test_text_boxes() {
+        let scores = map(
+            100,
+            50,
+            &[(10, 10, 40, 20), (60, 10, 90, 20), (0, 40, 2, 42)],
+            0.9,
+        );
+        let boxes = text_boxes(&scores, 100, 50);
+        // The 2x2 speck is noise
+        assert_eq!(boxes.len(), 2);
+        // Grown by 30 * 10 * 1.5 / 80 = 5.625 on each side
+        assert_eq!(
+            boxes[0],
+            TextBox {
+                left: 4.375,
+                top: 4.375,
+                right: 45.625,
+                bottom: 25.625
+            }
+        );
+
+        let faint = map(100, 50, &[(10, 10, 40, 20)], 0.4);
+        assert!(text_boxes(&faint, 100, 50).is_empty());
+    }
+
+    #[test]
+    fn test_reading_order() {
+        let text_box = |left: f32, top: f32| TextBox {
+            left,
+            top,
+            right: left + 20.0,
+            bottom: top + 10.0,
+        };
+        let lines = reading_order(vec![
+            text_box(50.0, 31.0),
+            text_box(60.0, 2.0),
+            text_box(0.0, 30.0),
+            text_box(0.0, 0.0),
+        ]);
+        assert_eq!(
+            lines,
+            vec![
+                vec![text_box(0.0, 0.0), text_box(60.0, 2.0)],
+                vec![text_box(0.0, 30.0), text_box(50.0, 31.0)],
+            ]
+        );
+    }
+
+    fn node(op: &str, inputs: &[&str], output: &str, attributes: Vec<AttributeProto>) -> NodeProto {
+        NodeProto {
+            op_type: op.to_string(),
+            input: inputs.iter().map(|input| input.to_string()).collect(),
+            output: vec![output.to_string()],
+            attribute: attributes,
+            ..Default::default()
+        }
+    }
+
+    fn ints(name: &str, values: &[i64]) -> AttributeProto {
+        AttributeProto {
+            name: name.to_string(),
+            r#type: AttributeType::Ints as i32,
+            ints: values.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    fn int(name: &str, value: i64) -> AttributeProto {
+        AttributeProto {
+            name: name.to_string(),
+            r#type: AttributeType::Int as i32,
+            i: value,
+            ..Default::default()
+        }
+    }
+
+    /// A float tensor of any shape
+    fn value(name: &str) -> ValueInfoProto {
+        ValueInfoProto {
+            name: name.to_string(),
+            r#type: Some(TypeProto {
+                value: Some(type_proto::Value::TensorType(type_proto::Tensor {
+                    elem_type: 1,
+                    shape: None,
+                })),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn model(nodes: Vec<NodeProto>, initializer: Vec<TensorProto>) -> InferenceModel {
+        let proto = ModelProto {
+            ir_version: 7,
+            opset_import: vec![OperatorSetIdProto {
+                domain: String::new(),
+                version: 13,
+            }],
+            graph: Some(GraphProto {
+                node: nodes,
+                initializer,
+                input: vec![value("image")],
+                output: vec![value("scores")],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        onnx().model_for_proto_model(&proto).unwrap()
+    }
+
+    /// Stand-ins for real models: detection scores dark pixels as text;
+    /// recognition reads "ab" whatever it's shown
+    fn fake_models() -> OnnxOcr {
+        let detection = model(
+            vec![
+                node(
+                    "ReduceMean",
+                    &["image"],
+                    "mean",
+                    vec![ints("axes", &[1]), int("keepdims", 1)],
+                ),
+                node("Neg", &["mean"], "dark", vec![]),
+                node("Sigmoid", &["dark"], "scores", vec![]),
+            ],
+            vec![],
+        );
+        // blank, a, a, blank, b with the image's mean times zero added
+        let best = [0, 1, 1, 0, 2];
+        let logits = TensorProto {
+            name: "logits".to_string(),
+            dims: vec![1, 5, 4],
+            data_type: 1,
+            float_data: best
+                .iter()
+                .flat_map(|&class| (0..4).map(move |c| if c == class { 5.0 } else { 0.0 }))
+                .collect(),
+            ..Default::default()
+        };
+        let zero = TensorProto {
+            name: "zero".to_string(),
+            dims: vec![1],
+            data_type: 1,
+            float_data: vec![0.0],
+            ..Default::default()
+        };
+        let recognition = model(
+            vec![
+                node(
+                    "ReduceMean",
+                    &["image"],
+                    "mean",
+                    vec![ints("axes", &[1, 2, 3]), int("keepdims", 0)],
+                ),
+                node("Mul", &["mean", "zero"], "nothing", vec![]),
+                node("Add", &["logits", "nothing"], "scores", vec![]),
+            ],
+            vec![logits, zero],
+        );
+        OnnxOcr::new(detection, recognition, charset("abc")).unwrap()
+    }
+
+    /// An encoded PNG: white, with black `(left, top, right, bottom)`
+    /// rectangles
+    fn page(width: u32, height: u32, rects: &[(u32, u32, u32, u32)]) -> Vec<u8> {
+        let image = RgbImage::from_fn(width, height, |x, y| {
+            let ink = rects.iter().any(|&(left, top, right, bottom)| {
+                (left..right).contains(&x) && (top..bottom).contains(&y)
+            });
+            if ink {
+                image::Rgb([0, 0, 0])
+            } else {
+                image::Rgb([255, 255, 255])
+            }
+        });
+        let mut png = Vec::new();
+        image
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        png
+    }
+
+    #[test]
+    fn test_read_lines_and_words() {
+        let ocr = fake_models();
+        // Two words on the first line, one on the second
+        let png = page(
+            400,
+            200,
+            &[(20, 20, 120, 50), (200, 25, 300, 50), (20, 120, 150, 150)],
+        );
+        assert_eq!(ocr.read(&png).unwrap(), "ab ab\nab");
+
+        let blank = page(400, 200, &[]);
+        assert_eq!(ocr.read(&blank).unwrap(), "");
+    }
+
+    #[test]
+    fn test_large_image_scaled_to_fit() {
+        let ocr = fake_models();
+        let png = page(3000, 1000, &[(100, 100, 900, 300), (100, 600, 900, 800)]);
+        assert_eq!(ocr.read(&png).unwrap(), "ab\nab");
+    }
+
+    #[tokio::test]
+    async fn test_missing_models() {
+        let dir = std::env::temp_dir().join(format!("cooklang-onnx-{}", std::process::id()));
+        let error = run_onnx(&dir, page(10, 10, &[]), Duration::from_secs(5))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("det.onnx"), "{}", error);
+    }
+}
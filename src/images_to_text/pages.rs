@@ -0,0 +1,190 @@
+//! Ordering and joining the pages of a multi-photo recipe
+
+use super::ImageSource;
+use std::cmp::Ordering;
+use std::path::Path;
+
+/// The order in which the photos of a recipe are read
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImageOrder {
+    /// The order the images were added in
+    #[default]
+    Given,
+    /// By file name, with numbers compared by value ("page-2.jpg" before
+    /// "page-10.jpg"); base64 images keep their place after the files
+    Filename,
+}
+
+impl std::str::FromStr for ImageOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "given" => Ok(ImageOrder::Given),
+            "filename" => Ok(ImageOrder::Filename),
+            _ => Err(format!(
+                "Unknown image order: {} (expected given or filename)",
+                s
+            )),
+        }
+    }
+}
+
+/// The images in reading order
+pub fn order(images: &[ImageSource], order: ImageOrder) -> Vec<ImageSource> {
+    let mut ordered = images.to_vec();
+    if order == ImageOrder::Filename {
+        // Stable, so equal names and base64 images keep their given order
+        ordered.sort_by(|a, b| match (a, b) {
+            (ImageSource::Path(a), ImageSource::Path(b)) => natural_cmp(file_name(a), file_name(b)),
+            (ImageSource::Path(_), ImageSource::Base64(_)) => Ordering::Less,
+            (ImageSource::Base64(_), ImageSource::Path(_)) => Ordering::Greater,
+            (ImageSource::Base64(_), ImageSource::Base64(_)) => Ordering::Equal,
+        });
+    }
+    ordered
+}
+
+/// Join the text of consecutive pages.
+///
+/// Pages normally start a new paragraph, but a sentence cut off at the
+/// bottom of a page ("Fold in the" / "egg whites and bake") continues on the
+/// next one, and a word hyphenated across pages is put back together.
+/// Ingredient lines are never run together, even when the next page starts
+/// with a lowercase ingredient. Empty pages are skipped.
+pub fn join_pages(pages: &[String]) -> String {
+    let mut joined = String::new();
+    for page in pages
+        .iter()
+        .map(|page| page.trim())
+        .filter(|page| !page.is_empty())
+    {
+        if joined.is_empty() {
+            joined.push_str(page);
+            continue;
+        }
+        let last_line = joined.lines().last().unwrap_or_default().trim();
+        let continues =
+            page.chars().next().is_some_and(char::is_lowercase) && !is_ingredient_line(last_line);
+        if continues && last_line.ends_with('-') {
+            joined.pop();
+            joined.push_str(page);
+        } else if continues && !last_line.ends_with(['.', '!', '?', ':', ';']) {
+            joined.push(' ');
+            joined.push_str(page);
+        } else {
+            joined.push_str("\n\n");
+            joined.push_str(page);
+        }
+    }
+    joined
+}
+
+/// Whether a line looks like an ingredient ("200 g flour", "- 2 eggs")
+/// rather than a numbered step ("2. Whisk the eggs")
+fn is_ingredient_line(line: &str) -> bool {
+    if line.starts_with(['-', '*', '•', '·']) {
+        return true;
+    }
+    let rest = line.trim_start_matches(|c: char| c.is_ascii_digit() || "½¼¾⅓⅔/ ,".contains(c));
+    rest.len() < line.len() && !rest.starts_with(['.', ')'])
+}
+
+fn file_name(path: &str) -> &str {
+    Path::new(path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(path)
+}
+
+/// Compare names case-insensitively, with runs of digits compared as numbers
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.chars().peekable(), b.chars().peekable());
+    loop {
+        match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let number = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+                    let mut digits = String::new();
+                    while let Some(c) = chars.next_if(char::is_ascii_digit) {
+                        digits.push(c);
+                    }
+                    digits.trim_start_matches('0').to_string()
+                };
+                let (x, y) = (number(&mut a), number(&mut b));
+                let by_value = x.len().cmp(&y.len()).then_with(|| x.cmp(&y));
+                if by_value != Ordering::Equal {
+                    return by_value;
+                }
+            }
+            (Some(x), Some(y)) => {
+                let by_char = x.to_lowercase().cmp(y.to_lowercase());
+                if by_char != Ordering::Equal {
+                    return by_char;
+                }
+                a.next();
+                b.next();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order_by_filename() {
+        let images = vec![
+            ImageSource::Path("photos/Page-10.jpg".to_string()),
+            ImageSource::Base64("aGVsbG8=".to_string()),
+            ImageSource::Path("scans/page-2.jpg".to_string()),
+            ImageSource::Path("page-1.jpg".to_string()),
+        ];
+        let names: Vec<String> = order(&images, ImageOrder::Filename)
+            .into_iter()
+            .map(|image| match image {
+                ImageSource::Path(path) => path,
+                ImageSource::Base64(_) => "base64".to_string(),
+            })
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "page-1.jpg",
+                "scans/page-2.jpg",
+                "photos/Page-10.jpg",
+                "base64"
+            ]
+        );
+        assert_eq!(order(&images, ImageOrder::Given).len(), 4);
+        assert_eq!("filename".parse::<ImageOrder>(), Ok(ImageOrder::Filename));
+    }
+
+    #[test]
+    fn test_join_pages() {
+        let pages =
+            |pages: &[&str]| join_pages(&pages.iter().map(|p| p.to_string()).collect::<Vec<_>>());
+
+        // A step cut off mid-sentence, and a hyphenated word
+        assert_eq!(
+            pages(&[
+                "2. Fold in the",
+                "egg whites and bake until gol-",
+                "den.\n3. Cool."
+            ]),
+            "2. Fold in the egg whites and bake until golden.\n3. Cool."
+        );
+        // Ingredient lists and finished sentences start a new paragraph
+        assert_eq!(
+            pages(&["200 g flour\n2 eggs", "butter\nsugar"]),
+            "200 g flour\n2 eggs\n\nbutter\nsugar"
+        );
+        assert_eq!(
+            pages(&["1. Whisk the eggs.", "  ", "2. Add the flour."]),
+            "1. Whisk the eggs.\n\n2. Add the flour."
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use std::error::Error;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tracing::debug;
+
+pub(crate) const DEFAULT_BINARY: &str = "tesseract";
+const DEFAULT_LANGUAGE: &str = "eng";
+
+/// Reads the text of an image with a local Tesseract executable
+///
+/// The image is piped to Tesseract, so nothing is written to disk.
+///
+/// # Arguments
+/// * `binary` - The Tesseract executable
+/// * `image` - Image data in a format Tesseract reads (JPEG, PNG, TIFF, ...)
+/// * `language` - Tesseract languages such as "eng+deu", or `None` for English
+/// * `timeout` - Time allowed for the recognition
+///
+/// # Returns
+/// The text of the image
+#[tracing::instrument(name = "ocr", skip_all, fields(backend = "tesseract"))]
+pub(crate) async fn run_tesseract(
+    binary: &str,
+    image: &[u8],
+    language: Option<&str>,
+    timeout: Duration,
+) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let language = language.unwrap_or(DEFAULT_LANGUAGE);
+    debug!(
+        "Reading {} bytes with {} ({})",
+        image.len(),
+        binary,
+        language
+    );
+
+    let mut child = Command::new(binary)
+        .args(["stdin", "stdout", "-l", language])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", binary, e))?;
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or("Tesseract has no standard input")?;
+    let run = async {
+        stdin.write_all(image).await?;
+        // Tesseract reads until the end of its input
+        drop(stdin);
+        child.wait_with_output().await
+    };
+    let output = tokio::time::timeout(timeout, run)
+        .await
+        .map_err(|_| format!("Tesseract took longer than {:?}", timeout))?
+        .map_err(|e| format!("Failed to run {}: {}", binary, e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Tesseract failed ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    debug!("Read {} characters", text.len());
+    Ok(text)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    /// A stand-in for tesseract that runs `script`
+    fn fake_tesseract(dir: &std::path::Path, script: &str) -> String {
+        let binary = dir.join("tesseract");
+        std::fs::write(&binary, format!("#!/bin/sh\n{}\n", script)).unwrap();
+        std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+        binary.to_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_tesseract_reads_stdin() {
+        let dir = std::env::temp_dir().join(format!("cooklang-tesseract-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // Echo the image back, then the arguments
+        let binary = fake_tesseract(&dir, r#"cat; echo; echo "$@""#);
+        let text = run_tesseract(&binary, b"2 eggs", Some("deu"), Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(text, "2 eggs\nstdin stdout -l deu");
+
+        let binary = fake_tesseract(&dir, "echo 'Error opening data file' >&2; exit 1");
+        let error = run_tesseract(&binary, b"", None, Duration::from_secs(5))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("Error opening data file"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
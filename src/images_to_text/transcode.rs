@@ -0,0 +1,166 @@
+//! Turning camera files into images every OCR backend and vision model reads
+//!
+//! iPhones save photos as HEIC, which Google Vision and the multimodal
+//! models reject. With the `heic` feature those are decoded with libheif and
+//! sent as JPEG; with the `image` feature PNG, WebP and GIF are sent as JPEG
+//! too, which keeps screenshots from bloating the request.
+
+use super::ImageSource;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use std::error::Error;
+
+/// JPEG quality for transcoded images; high enough for small print
+#[cfg(feature = "image")]
+const JPEG_QUALITY: u8 = 90;
+
+/// The bytes of an image source, transcoded to JPEG where needed
+pub(crate) async fn load(source: &ImageSource) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+    let bytes = match source {
+        ImageSource::Path(path) => tokio::fs::read(path)
+            .await
+            .map_err(|e| format!("Failed to read {}: {}", path, e))?,
+        ImageSource::Base64(data) => STANDARD
+            .decode(data.trim())
+            .map_err(|e| format!("Invalid base64 image data: {}", e))?,
+    };
+    to_jpeg(bytes)
+}
+
+/// Transcode HEIC, PNG, WebP and GIF images to JPEG.
+///
+/// HEIC must be converted; it fails without the `heic` feature. The other
+/// formats are converted with the `image` feature and passed on as they are
+/// without it, or when they can't be decoded, since the backends accept them.
+pub fn to_jpeg(bytes: Vec<u8>) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+    if is_heic(&bytes) {
+        return heic_to_jpeg(&bytes);
+    }
+    #[cfg(feature = "image")]
+    if matches!(
+        image::guess_format(&bytes),
+        Ok(image::ImageFormat::Png | image::ImageFormat::WebP | image::ImageFormat::Gif)
+    ) {
+        if let Ok(image) = image::load_from_memory(&bytes) {
+            return encode_jpeg(&image);
+        }
+    }
+    Ok(bytes)
+}
+
+/// Whether the bytes are a HEIC/HEIF photo (an ISO media file with a HEIF
+/// brand)
+pub(crate) fn is_heic(bytes: &[u8]) -> bool {
+    const BRANDS: [&[u8]; 8] = [
+        b"heic", b"heix", b"hevc", b"hevx", b"heim", b"heis", b"mif1", b"msf1",
+    ];
+    bytes.len() >= 12 && &bytes[4..8] == b"ftyp" && BRANDS.contains(&&bytes[8..12])
+}
+
+#[cfg(feature = "heic")]
+fn heic_to_jpeg(bytes: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+    use libheif_rs::{ColorSpace, HeifContext, LibHeif, RgbChroma};
+
+    let context = HeifContext::read_from_bytes(bytes)?;
+    let handle = context.primary_image_handle()?;
+    // Decoding applies the photo's rotation and mirroring
+    let decoded = LibHeif::new().decode(&handle, ColorSpace::Rgb(RgbChroma::Rgb), None)?;
+    let plane = decoded
+        .planes()
+        .interleaved
+        .ok_or("HEIC image has no RGB data")?;
+    // Rows are padded to `stride` bytes
+    let row_bytes = plane.width as usize * 3;
+    let pixels: Vec<u8> = plane
+        .data
+        .chunks(plane.stride)
+        .take(plane.height as usize)
+        .flat_map(|row| &row[..row_bytes])
+        .copied()
+        .collect();
+    let image = image::RgbImage::from_raw(plane.width, plane.height, pixels)
+        .ok_or("HEIC image has an unexpected size")?;
+    encode_jpeg(&image::DynamicImage::ImageRgb8(image))
+}
+
+#[cfg(not(feature = "heic"))]
+fn heic_to_jpeg(_bytes: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+    Err(
+        "HEIC photos need cooklang-import built with the `heic` feature; \
+         convert the photo to JPEG first"
+            .into(),
+    )
+}
+
+#[cfg(feature = "image")]
+fn encode_jpeg(image: &image::DynamicImage) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+    let mut rgb = image.to_rgb8();
+    // JPEG has no alpha channel: put transparent screenshots on white
+    if image.color().has_alpha() {
+        for (pixel, rgba) in rgb.pixels_mut().zip(image.to_rgba8().pixels()) {
+            let alpha = u16::from(rgba[3]);
+            for channel in 0..3 {
+                pixel[channel] =
+                    ((u16::from(rgba[channel]) * alpha + 255 * (255 - alpha)) / 255) as u8;
+            }
+        }
+    }
+    let mut output = Vec::new();
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut output, JPEG_QUALITY)
+        .encode_image(&rgb)?;
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The start of an iPhone photo: an `ftyp` box with the `heic` brand
+    const HEIC_HEADER: &[u8] = b"\0\0\0\x18ftypheic\0\0\0\0mif1heic";
+
+    #[tokio::test]
+    async fn test_load_passes_jpeg_through() {
+        let jpeg = vec![0xFF, 0xD8, 0xFF, 0xE0, 0, 0];
+        let loaded = load(&ImageSource::Base64(STANDARD.encode(&jpeg)))
+            .await
+            .unwrap();
+        assert_eq!(loaded, jpeg);
+        assert!(load(&ImageSource::Base64("not base64!".to_string()))
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_is_heic() {
+        assert!(is_heic(HEIC_HEADER));
+        assert!(!is_heic(b"\0\0\0\x18ftypavif\0\0\0\0"));
+        assert!(!is_heic(&[0xFF, 0xD8, 0xFF]));
+    }
+
+    #[cfg(not(feature = "heic"))]
+    #[test]
+    fn test_heic_needs_feature() {
+        let error = to_jpeg(HEIC_HEADER.to_vec()).unwrap_err();
+        assert!(error.to_string().contains("`heic` feature"));
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_png_becomes_jpeg() {
+        let mut png = Vec::new();
+        image::DynamicImage::ImageRgba8(image::RgbaImage::new(8, 4))
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let jpeg = to_jpeg(png).unwrap();
+        assert_eq!(
+            image::guess_format(&jpeg).unwrap(),
+            image::ImageFormat::Jpeg
+        );
+        // Transparent pixels end up white
+        let decoded = image::load_from_memory(&jpeg).unwrap().to_rgb8();
+        assert!(decoded.pixels().all(|pixel| pixel[0] > 240));
+
+        // A PNG that won't decode is passed on unchanged
+        let broken = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0];
+        assert_eq!(to_jpeg(broken.clone()).unwrap(), broken);
+    }
+}
@@ -1,12 +1,38 @@
+#[cfg(not(any(feature = "rustls", feature = "native-tls")))]
+compile_error!("enable a TLS backend: the `rustls` (default) or `native-tls` feature");
+
+pub mod audio_to_text;
 pub mod builder;
+pub mod cache;
+pub(crate) mod charset;
+pub mod collection;
 pub mod config;
 pub mod converters;
+pub mod daemon;
+pub mod diff;
+pub mod doctor;
+pub mod email;
 pub mod error;
+pub mod eval;
+pub(crate) mod http;
 pub mod images_to_text;
+pub mod manifest;
+pub mod mirror;
 pub(crate) mod model;
 pub mod pipelines;
+pub mod provenance;
+pub mod schedule;
+pub(crate) mod secrets;
+pub mod setup;
+pub mod stages;
 pub mod url_to_text;
 
+#[cfg(feature = "image")]
+pub mod recipe_image;
+
+#[cfg(feature = "server")]
+pub mod server;
+
 #[cfg(feature = "uniffi")]
 pub mod uniffi_bindings;
 
@@ -16,13 +42,21 @@ pub use uniffi_bindings::*;
 
 // Public API re-exports
 pub use config::AiConfig;
-pub use converters::{ConversionMetadata, ConversionResult, TokenUsage};
-pub use error::ImportError;
-pub use images_to_text::ImageSource;
-pub use pipelines::RecipeComponents;
+pub use converters::{ConversionMetadata, ConversionResult, TokenUsage, UnitSystem};
+pub use error::{ErrorKind, ImportError};
+pub use images_to_text::{ImageOrder, ImageSource};
+pub use model::{split_frontmatter, MetadataKey, Recipe};
+pub use pipelines::{parse_duration, RecipeComponents, RecipeTimes};
+pub use stages::TitleStyle;
+pub use url_to_text::html::detect::{is_probably_recipe, RecipeLikelihood};
+pub use url_to_text::html::diagnostics::{DropReason, DroppedField, ExtractionDiagnostics};
+pub use url_to_text::html::discover::discover_recipe_links;
+pub use url_to_text::html::extractors::register_extractor;
 
 // Advanced builder API (for users who need more control)
-pub use builder::{ImportResult, LlmProvider, RecipeImporter, RecipeImporterBuilder};
+pub use builder::{
+    ImportReport, ImportRequest, ImportResult, LlmProvider, RecipeImporter, RecipeImporterBuilder,
+};
 
 /// Extract recipe components from a URL.
 ///
@@ -49,9 +83,14 @@ pub use builder::{ImportResult, LlmProvider, RecipeImporter, RecipeImporterBuild
 /// }
 /// ```
 pub async fn url_to_recipe(url: &str) -> Result<RecipeComponents, ImportError> {
-    pipelines::url::process(url)
-        .await
-        .map_err(|e| ImportError::ExtractionError(e.to_string()))
+    pipelines::url::process(
+        url,
+        &pipelines::Timeouts::from_config(),
+        None,
+        cache::Cache::from_config().as_ref(),
+    )
+    .await
+    .map_err(|e| ImportError::from_pipeline(e, ImportError::ExtractionError))
 }
 
 /// Extract recipe components from images.
@@ -81,9 +120,9 @@ pub async fn url_to_recipe(url: &str) -> Result<RecipeComponents, ImportError> {
 /// }
 /// ```
 pub async fn image_to_recipe(images: &[ImageSource]) -> Result<RecipeComponents, ImportError> {
-    pipelines::image::process(images)
+    pipelines::image::process(images, &pipelines::Timeouts::from_config(), None)
         .await
-        .map_err(|e| ImportError::ExtractionError(e.to_string()))
+        .map_err(|e| ImportError::from_pipeline(e, ImportError::ExtractionError))
 }
 
 /// Parse text into recipe components.
@@ -113,9 +152,9 @@ pub async fn image_to_recipe(images: &[ImageSource]) -> Result<RecipeComponents,
 /// }
 /// ```
 pub async fn text_to_recipe(text: &str, extract: bool) -> Result<RecipeComponents, ImportError> {
-    pipelines::text::process(text, extract)
+    pipelines::text::process(text, extract, &pipelines::Timeouts::from_config(), None)
         .await
-        .map_err(|e| ImportError::ExtractionError(e.to_string()))
+        .map_err(|e| ImportError::from_pipeline(e, ImportError::ExtractionError))
 }
 
 /// Convert recipe text to Cooklang format.
@@ -153,31 +192,7 @@ pub async fn text_to_cooklang(components: &RecipeComponents) -> Result<String, I
         .build()
         .await?
     {
-        ImportResult::Cooklang { mut content, .. } => {
-            // Prepend frontmatter if we have name or metadata
-            let has_name = !components.name.is_empty();
-            let has_metadata = !components.metadata.is_empty();
-
-            if has_name || has_metadata {
-                let mut frontmatter = String::from("---\n");
-                if has_name {
-                    let title_yaml = crate::pipelines::metadata_to_yaml(&[(
-                        "title".to_string(),
-                        components.name.clone(),
-                    )]);
-                    frontmatter.push_str(&title_yaml);
-                }
-                if has_metadata {
-                    frontmatter.push_str(&components.metadata);
-                    if !components.metadata.ends_with('\n') {
-                        frontmatter.push('\n');
-                    }
-                }
-                frontmatter.push_str("---\n\n");
-                content = frontmatter + &content;
-            }
-            Ok(content)
-        }
+        ImportResult::Cooklang { content, .. } => Ok(stages::render(components, &content)),
         ImportResult::Components(_) => unreachable!("Default mode is Cooklang"),
     }
 }
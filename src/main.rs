@@ -1,6 +1,22 @@
-use cooklang_import::{ImportResult, LlmProvider, RecipeImporter};
+use cooklang_import::{
+    collection,
+    config::BelowQuality,
+    converters, diff, discover_recipe_links,
+    doctor::Status,
+    eval, is_probably_recipe,
+    manifest::{ImportStatus, Manifest, ManifestEntry},
+    mirror,
+    pipelines::{html::SavedPage, Timeouts},
+    provenance, setup, stages,
+    url_to_text::html::extractors::SiteAdapter,
+    ErrorKind, ImageOrder, ImageSource, ImportError, ImportReport, ImportResult, LlmProvider,
+    RecipeImporter, RecipeImporterBuilder, RecipeLikelihood, TitleStyle, UnitSystem,
+};
 use log::info;
 use std::env;
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::process::ExitCode;
 use std::time::Duration;
 
 fn print_help() {
@@ -9,7 +25,19 @@ fn print_help() {
 
 USAGE:
     cooklang-import [URL] [OPTIONS]
-    cooklang-import --markdown [OPTIONS]
+    cooklang-import --markdown FILE.md [OPTIONS]
+    cooklang-import --email FILE.eml [OPTIONS]
+    cooklang-import --html-file PAGE.html [--source-url URL] [OPTIONS]
+    cooklang-import --crawl INDEX_URL [--limit N] [--yes] [--resume] [OPTIONS]
+    cooklang-import --retry-failed [--manifest PATH] [OPTIONS]
+    cooklang-import --mirror DIR [--source-url URL] [--import-into OUT] [--resume] [OPTIONS]
+    cooklang-import test-site PAGE.html --adapter SITE.toml [--url URL]
+    cooklang-import diff FIRST SECOND [--format json]
+    cooklang-import eval DIR [--providers P[:MODEL],...] [--format json]
+    cooklang-import serve [--port 8080] [--host 127.0.0.1]
+    cooklang-import daemon
+    cooklang-import init
+    cooklang-import doctor
 
 USE CASES:
     1. URL → Cooklang (default):
@@ -20,21 +48,210 @@ USE CASES:
 
     3. Text → Cooklang (convert plain text):
        cooklang-import --text "Take 2 eggs and 1 cup flour. Mix and bake at 350F."
+       cooklang-import --text-file recipe.txt
+       pbpaste | cooklang-import --stdin
 
     4. Image → Cooklang (OCR then convert):
        cooklang-import --image /path/to/recipe-image.jpg
 
+    5. Email or saved page → Cooklang (a newsletter saved as .eml, a page
+       saved from the browser as .html or .mhtml):
+       cooklang-import --email newsletter.eml
+       cooklang-import --html-file Pancakes.html --source-url https://example.com/pancakes
+
+    6. Markdown → Cooklang (Ingredients and Instructions headings):
+       cooklang-import --markdown pancakes.md
+
+    7. Index page → one .cook file per linked recipe (asks first; without a
+       terminal, prints the recipe URLs instead). A site saved with wget or
+       HTTrack gives one per recipe page, laid out like the saved site:
+       cooklang-import --crawl https://example.com/recipes/desserts --limit 10
+       cooklang-import --mirror mirror/example.com --source-url https://example.com/
+
+    8. Try a site adapter (CSS selectors in TOML) on a saved page, printing
+       selectors that match nothing and the extracted recipe:
+       cooklang-import test-site saved-page.html --adapter mysite.toml
+
+    9. Compare two imports of a recipe (say, the same URL converted by two
+       providers): ingredients only one has, changed amounts and changed
+       steps. Other files than .cook are compared as extracted recipes:
+       cooklang-import diff anthropic/Pancakes.cook openai/Pancakes.cook
+
+    10. Benchmark providers and models on an eval set: NAME.txt recipes
+        with vetted NAME.cook conversions. Prints ingredient, amount and
+        step scores, valid syntax, latency and cost per provider (default:
+        the providers enabled in config.toml):
+        cooklang-import eval tests/eval --providers anthropic,openai:gpt-4o-mini
+
+    11. HTTP import API for self-hosted apps (built with --features server);
+       POST /import with {{"url": ...}}, {{"text": ...}} or {{"image": BASE64}}:
+       cooklang-import serve --port 8080
+
+    12. Daemon for apps that import many recipes: JSON-RPC 2.0 over
+        stdin/stdout, one request per line (methods import, ping, shutdown):
+        cooklang-import daemon
+
+    13. First-time setup: choose a provider, check the API key, write
+        ~/.config/cooklang-import/config.toml (or --config PATH) and convert
+        a sample recipe:
+        cooklang-import init
+
+    14. Check the setup: config files, API keys, whether the configured
+        providers (and Ollama models) and Google Vision answer:
+        cooklang-import doctor
+
 OPTIONS:
     --extract-only      Extract recipe without converting to Cooklang format
+    --all-recipes       Import every recipe on a page with several (JSON-LD),
+                        each as "<Title>.cook" in the current directory
+    --sub-recipes       Save component recipes (dough, filling, glaze) as their
+                        own "<Title>.cook" files, referenced from the main
+                        recipe as @./<Title>{{}}
 
     --text TEXT         Convert plain text recipe to Cooklang
+    --text-file PATH    Same, reading the recipe text from a file
+    --stdin             Same, reading the recipe text from standard input
+                        (e.g. piped from the clipboard)
+
+    --email FILE        Convert a recipe email (.eml, e.g. a saved or forwarded
+                        newsletter): structured data in the HTML first, then LLM
+                        text extraction
+
+    --html-file PATH    Convert a page saved from the browser (.html, or .mht/.mhtml
+                        single-file archives) with the same extractors as a URL;
+                        nothing is fetched
+    --source-url URL    With --html-file: the URL the page was saved from, recorded
+                        as its source (default: the one the saved page gives)
+
+    --markdown FILE     Convert a Markdown recipe (an Ingredients heading over a
+                        list, an Instructions/Method heading over the steps);
+                        with --offline it is converted without an LLM
+
+    --crawl URL         Find recipe links on a category/index page (JSON-LD
+                        item lists, sitemaps, or recipe-like links) and import
+                        each as "<Title>.cook" in the current directory
+    --limit N           Import at most N of the links found (default: 20)
+    --yes, -y           Import without asking first
+    --manifest PATH     Record each URL's output file, extractor, tokens and
+                        status here (default: cooklang-import-manifest.json)
+    --resume            With --crawl, skip URLs the manifest lists as imported
+    --retry-failed      Import again only the manifest's failed URLs
+    --mirror DIR        Convert every recipe page in a site saved with wget or
+                        HTTrack; each "<Title>.cook" goes in the page's directory
+                        under the current directory (or --import-into), outcomes
+                        go in the manifest and a summary is printed at the end.
+                        --source-url gives the URL of DIR, for pages that don't
+                        say where they're from; --limit N stops after N pages,
+                        --resume skips pages already converted
+    --min-quality SCORE Set aside conversions whose confidence (0 to 1) is below
+                        SCORE: batch imports save them in review/ under the
+                        output directory and mark them low_quality in the
+                        manifest; a single import exits with code 8
+                        (default: min_quality in config.toml)
+    --below-quality ACTION
+                        What --min-quality does with them: review (default) or
+                        skip, which saves and prints nothing
+    --update FILE       Import FILE.cook again from the source in its frontmatter
+                        (import: source, or source:) with the provider and model
+                        it was imported with, and print a diff against the local
+                        file; the file itself is never changed
+    --base FILE         With --update: the recipe as first imported; print the
+                        local file with the source's changes merged in instead,
+                        conflicts marked with <<<<<<< and >>>>>>>
 
     --image PATH        Convert recipe image to Cooklang (uses Google Vision OCR)
                         Requires GOOGLE_API_KEY environment variable
+                        Repeat for a recipe photographed over several pages
+                        HEIC photos need a build with the heic feature
+    --image-order ORDER Read the pages in the order given (default) or by
+                        file name (filename: page-2.jpg before page-10.jpg)
+    --vision            With --image: send the photo directly to the provider's
+                        vision model instead of OCR (openai, anthropic, google, ollama)
+    --audio FILE        Convert a dictated recipe (voice memo) to Cooklang; it is
+                        transcribed with OpenAI Whisper (OPENAI_API_KEY) or a local
+                        whisper.cpp build ([transcription] in config.toml)
 
-    --provider NAME     LLM provider to use (openai, anthropic, google, azure_openai, ollama)
+    --config PATH       Read this config file on top of
+                        ~/.config/cooklang-import/config.toml and ./config.toml
+
+    --provider NAME     LLM provider to use (openai, anthropic, google, azure_openai,
+                        ollama, mistral, groq)
                         Requires config.toml with provider configuration
-    --timeout SECONDS   Timeout for HTTP requests in seconds (default: no timeout)
+    --providers A,B,..  Try providers in order, falling back to the next on failure
+    --race A,B          Send the conversion to two providers at once and use the
+                        first valid response (faster, but both are billed)
+    --structured        Ask the LLM for a JSON recipe and render the Cooklang locally
+                        (openai, anthropic and ollama only)
+    --check-ingredients Compare the Cooklang's ingredients with the extracted
+                        ingredient list and report missing and extra items
+    --timeout SECONDS   Timeout for page fetches in seconds (default: 30); also the
+                        upper limit for every other stage
+    --llm-timeout SECONDS
+                        Timeout for LLM and OCR requests (default: 120)
+    --proxy URL         Send all requests through this proxy (default: HTTP_PROXY /
+                        HTTPS_PROXY environment variables)
+    --user-agent UA     User-Agent for page and photo fetches (default: a desktop
+                        Chrome browser's, or [fetch] user_agent)
+    --wayback           If the page is gone (HTTP 404/410 or the domain no longer
+                        resolves), import its latest archive.org snapshot
+    --no-cache          Always fetch the page and call the LLM, bypassing the
+                        on-disk cache (~/.cache/cooklang-import)
+    --offline           Never use the network: only local text and images, cached
+                        conversions or a local Ollama (temperature 0), else
+                        conversion by rules; fails if a stage would need the
+                        network (always on with offline = true in config.toml)
+    --dry-run           Fetch and extract only; report the extractor that matched,
+                        metadata found, provider and estimated tokens/cost
+                        without calling any LLM
+    --verbose, -v       When no recipe is found, explain why (extractor errors,
+                        JSON-LD scripts, recipe classes, page title)
+    --scale FACTOR      Multiply ingredient quantities and servings, e.g. 2 to
+                        double the recipe or 0.5 to halve it
+    --units SYSTEM      Convert quantities and oven temperatures to metric
+                        (g, ml, °C) or imperial (oz, cups, °F)
+    --both-temperatures Write oven temperatures in both °C and °F, e.g.
+                        "180°C (355°F)" or "gas mark 4 (180°C/350°F)"
+    --aliases PATH      Rename ingredients to canonical names from a file of
+                        "green onions|scallions|spring onions" lines (an
+                        aisle.conf works), so shopping lists add them up
+    --pantry PATH       Mark the staples listed in a file (one per line, e.g.
+                        salt, pepper, olive oil) as "@salt{{}}(pantry)" so
+                        shopping lists can skip them ([converters.pantry])
+    --save-image        Save the recipe photo next to the recipe as "<Title>.jpg",
+                        resized and without EXIF/GPS data (built with
+                        --features image; see [images] in config.toml)
+    --keep-unknown-fields
+                        Write page fields the extractor doesn't map (ratings,
+                        video, ...) to the frontmatter with an x- prefix
+    --provenance        Record the source, date, extractor, provider, model and
+                        prompt hash in an import: block of the frontmatter
+    --target-language LANG
+                        Translate the recipe while converting it, e.g. en or
+                        German; the original language is kept as metadata
+    --aisle-conf PATH   Add the recipe's ingredients to a cooklang-chef aisle.conf
+                        (produce, dairy, ...), creating the file if needed
+    --format FORMAT     cooklang (default) or json: an object with the Cooklang
+                        text, name, metadata, source, images and conversion
+                        metadata, for scripts
+    --error-format FORMAT
+                        text (default) or json: on failure, print one line
+                        {{"error": {{"kind", "exit_code", "message", ...}}}} to stderr
+    --title-style STYLE Where the title goes: frontmatter (default, title:), heading
+                        (a leading # Title line) or filename (saved to
+                        "<Title>.cook" in the current directory instead of printed;
+                        an existing file is never overwritten)
+    --dedupe-against DIR
+                        Warn when the recipe looks like one already in DIR (by
+                        title and ingredients)
+    --skip-duplicates   With --dedupe-against: print nothing for duplicates
+    --import-into DIR   Add the recipe to a CookCLI collection: save it as
+                        "<Title>.cook" in DIR (created if needed) with its photo
+                        as "<Title>.jpg" (with --features image), update the
+                        collection's config/aisle.conf if it has one and warn
+                        about recipes it already has
+    --index FILE        With --import-into: add a "- [Title](<path>)" line to a
+                        Markdown index, relative to the top of the collection
+                        (the directory with config/)
 
     --help, -h          Show this help message
 
@@ -51,62 +268,1324 @@ EXAMPLES:
     # Convert recipe image
     cooklang-import --image recipe-photo.jpg
 
+    # Convert a recipe photographed over several pages, in file name order
+    cooklang-import --image scans/page-10.jpg --image scans/page-9.jpg --image-order filename
+
+    # Convert a dictated recipe
+    cooklang-import --audio grandmas-goulash.m4a
+
+    # Convert a newsletter saved from your mail client
+    cooklang-import --email newsletter.eml
+
+    # Convert a page saved from the browser, e.g. behind a paywall or login
+    cooklang-import --html-file ~/Downloads/Pancakes.mhtml --source-url https://example.com/pancakes
+
+    # Convert a Markdown recipe without calling an LLM
+    cooklang-import --markdown pancakes.md --offline
+
+    # Convert recipe image with a vision model (no separate OCR)
+    cooklang-import --image recipe-photo.jpg --provider anthropic --vision
+
     # Use custom provider (requires config.toml)
     cooklang-import https://example.com/recipe --provider anthropic
 
+    # Fall back to OpenAI when the local model fails
+    cooklang-import https://example.com/recipe --providers ollama,openai
+
+    # Race two providers for the fastest valid result
+    cooklang-import https://example.com/recipe --race openai,anthropic
+
+    # Structured output (JSON recipe rendered to Cooklang)
+    cooklang-import https://example.com/recipe --provider anthropic --structured
+
     # Set custom timeout
     cooklang-import https://example.com/recipe --timeout 60
 
+    # Convert a text file with a local model and no network access
+    cooklang-import --text-file recipe.txt --provider ollama --offline
+
+    # Same without any model: ingredients are marked up by rules
+    cooklang-import --text-file recipe.txt --offline
+
+    # Convert a recipe copied to the clipboard (macOS)
+    pbpaste | cooklang-import --stdin
+
+    # Serve the import API on all interfaces
+    cooklang-import serve --host 0.0.0.0 --port 8080
+
+    # Import through the daemon
+    echo '{{"jsonrpc": "2.0", "id": 1, "method": "import", "params": {{"url": "https://example.com/recipe"}}}}' | cooklang-import daemon
+
+    # See what an import would do before spending tokens
+    cooklang-import https://example.com/recipe --dry-run
+
+    # Import a recipe doubled
+    cooklang-import https://example.com/recipe --scale 2
+
+    # Import an American recipe in grams and milliliters
+    cooklang-import https://example.com/recipe --units metric
+
+    # Import a German recipe in English
+    cooklang-import https://example.de/rezept --target-language en
+
+    # Keep the shopping list aisles up to date
+    cooklang-import https://example.com/recipe --aisle-conf config/aisle.conf
+
+    # Save as "Slow Cooker Chilli Con Carne.cook" with no title in the file
+    cooklang-import https://example.com/recipe --title-style filename
+
+    # Machine-readable output for scripts
+    cooklang-import https://example.com/recipe --format json | jq .metadata
+
+    # Tell a missing recipe from a network failure in a script
+    cooklang-import https://example.com/recipe --error-format json 2> error.json
+
+    # See what changed at the source of a recipe edited since it was imported
+    cooklang-import --update Pancakes.cook
+
+    # Add a recipe to a CookCLI collection and its README index
+    cooklang-import https://example.com/recipe --import-into ~/recipes/Mains --index README.md
+
+    # Don't import recipes the collection already has
+    cooklang-import https://example.com/recipe --dedupe-against ~/recipes --skip-duplicates
+
+    # Crawl a blog, keeping doubtful conversions out of the collection
+    cooklang-import --crawl https://example.com/recipes --import-into ~/recipes --min-quality 0.7
+
+    # Give a slow local model more time
+    cooklang-import https://example.com/recipe --provider ollama --llm-timeout 300
+
+EXIT CODES:
+    0   Success
+    1   Other failure (file, photo or OCR error, invalid option)
+    2   Invalid input or option combination (kind "input")
+    3   The page couldn't be fetched (kind "fetch")
+    4   No recipe found on the page or in the text (kind "no_recipe")
+    5   The LLM provider failed (kind "llm")
+    6   Missing or invalid configuration or API key (kind "config")
+    7   A request timed out (kind "timeout")
+    8   The conversion scored below --min-quality (kind "low_quality")
+
 ENVIRONMENT VARIABLES:
     OPENAI_API_KEY      OpenAI API key (required for default provider)
     OPENAI_MODEL        OpenAI model to use (default: gpt-4)
     GOOGLE_API_KEY      Google Cloud Vision API key (required for --image)
+    MISTRAL_API_KEY     Mistral API key (for --provider mistral)
+    GROQ_API_KEY        Groq API key (for --provider groq)
+    HTTPS_PROXY         Proxy for HTTPS requests (also HTTP_PROXY, NO_PROXY)
     RUST_LOG            Set log level (debug, info, warn, error)
 
-For more information, see: https://github.com/cooklang/cooklang-import
-"#
-    );
+For more information, see: https://github.com/cooklang/cooklang-import
+"#
+    );
+}
+
+fn parse_provider(name: &str) -> Result<LlmProvider, String> {
+    match name {
+        "openai" => Ok(LlmProvider::OpenAI),
+        "anthropic" => Ok(LlmProvider::Anthropic),
+        "google" => Ok(LlmProvider::Google),
+        "azure_openai" => Ok(LlmProvider::AzureOpenAI),
+        "ollama" => Ok(LlmProvider::Ollama),
+        "mistral" => Ok(LlmProvider::Mistral),
+        "groq" => Ok(LlmProvider::Groq),
+        #[cfg(feature = "mock")]
+        "mock" => Ok(LlmProvider::Mock),
+        _ => Err(format!(
+            "Unknown provider: {}. Available: openai, anthropic, google, azure_openai, ollama, mistral, groq",
+            name
+        )),
+    }
+}
+
+fn print_report(report: &ImportReport) {
+    if let Some(url) = &report.url {
+        println!("Page:       {}", url);
+    }
+    println!(
+        "Extractor:  {}",
+        report
+            .extractor
+            .as_deref()
+            .unwrap_or("none matched (LLM text extraction needed)")
+    );
+    if !report.components.name.is_empty() {
+        println!("Title:      {}", report.components.name);
+    }
+    let metadata: Vec<&str> = report
+        .components
+        .metadata
+        .lines()
+        .filter_map(|line| line.split_once(':').map(|(key, _)| key.trim()))
+        .filter(|key| !key.is_empty() && !key.starts_with('-'))
+        .collect();
+    if !metadata.is_empty() {
+        println!("Metadata:   {}", metadata.join(", "));
+    }
+    if !report.dropped_fields.is_empty() {
+        let dropped: Vec<String> = report
+            .dropped_fields
+            .iter()
+            .map(|field| format!("{} ({})", field.key, field.reason))
+            .collect();
+        println!("Dropped:    {}", dropped.join(", "));
+    }
+    println!(
+        "Text:       {} lines, {} characters",
+        report.components.text.lines().count(),
+        report.components.text.chars().count()
+    );
+    if report.providers.is_empty() {
+        println!("Provider:   none (extract only)");
+    } else {
+        println!(
+            "Provider:   {} (model: {})",
+            report.providers.join(", "),
+            report.model
+        );
+    }
+    println!(
+        "Tokens:     ~{} input, ~{} output",
+        report.estimated_input_tokens, report.estimated_output_tokens
+    );
+    match report.estimated_cost_usd {
+        Some(cost) => println!("Cost:       ~${:.4}", cost),
+        None => println!("Cost:       unknown for this model"),
+    }
+}
+
+/// Warn about recipes in `dir` that look like `recipe`. Returns true when
+/// the recipe should be skipped.
+fn is_duplicate(recipe: &str, dir: &str, skip: bool) -> Result<bool, Box<dyn std::error::Error>> {
+    let duplicates = cooklang_import::converters::find_duplicates(recipe, dir)
+        .map_err(|e| format!("Failed to read {}: {}", dir, e))?;
+    for duplicate in &duplicates {
+        eprintln!(
+            "Warning: similar to \"{}\" in {} ({:.0}% title match, {:.0}% shared ingredients)",
+            duplicate.title,
+            duplicate.path.display(),
+            duplicate.title_similarity * 100.0,
+            duplicate.ingredient_overlap * 100.0
+        );
+    }
+    if skip && !duplicates.is_empty() {
+        eprintln!("Skipped: recipe already in {}", dir);
+        return Ok(true);
+    }
+    Ok(false)
+}
+
+/// Where saved recipes go: the working directory, or the collection given
+/// with `--import-into`
+#[derive(Clone, Default)]
+struct Destination {
+    /// Directory the "<Title>.cook" files are written to; empty for the
+    /// working directory
+    dir: PathBuf,
+    /// Markdown index each saved recipe is added to (`--index`)
+    index: Option<PathBuf>,
+}
+
+impl Destination {
+    /// Where recipes below `--min-quality` are saved: `review/` in the
+    /// directory, left out of the index
+    fn review(&self) -> Destination {
+        Destination {
+            dir: self.dir.join("review"),
+            index: None,
+        }
+    }
+}
+
+/// `--min-quality`: what happens to conversions whose confidence is below
+/// `min`
+#[derive(Clone, Copy)]
+struct QualityGate {
+    min: f32,
+    action: BelowQuality,
+}
+
+impl QualityGate {
+    /// What to do with `result`, if it's below the minimum
+    fn check(&self, result: &ImportResult) -> Option<(ImportError, BelowQuality)> {
+        let error = result.check_quality(self.min).err()?;
+        Some((error, self.action))
+    }
+}
+
+/// Say what became of an import below `--min-quality`
+fn report_low_quality(entry: &ManifestEntry) {
+    let confidence = entry.confidence.unwrap_or_default();
+    match &entry.output {
+        Some(_) => eprintln!(
+            "Low quality ({:.2}), saved for review: {}",
+            confidence, entry.source
+        ),
+        None => eprintln!("Skipped {}: low quality ({:.2})", entry.source, confidence),
+    }
+}
+
+/// Print the recipe, or with `TitleStyle::Filename` save it to a file named
+/// after the title
+fn emit(
+    recipe: &str,
+    title: &str,
+    style: TitleStyle,
+    destination: &Destination,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if style != TitleStyle::Filename {
+        println!("{}", recipe);
+        return Ok(());
+    }
+    save(recipe, title, destination)?;
+    Ok(())
+}
+
+/// Save the recipe as "<Title>.cook" in `destination` and return its path
+fn save(
+    recipe: &str,
+    title: &str,
+    destination: &Destination,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let name = stages::file_name(title)
+        .ok_or("--title-style filename needs a recipe title, but none was found")?;
+    if !destination.dir.as_os_str().is_empty() {
+        std::fs::create_dir_all(&destination.dir)
+            .map_err(|e| format!("Failed to create {}: {}", destination.dir.display(), e))?;
+    }
+    let path = destination.dir.join(name);
+    // Never overwrite a recipe that's already there
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    std::io::Write::write_all(&mut file, recipe.as_bytes())
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    eprintln!("Saved to {}", path.display());
+    if let Some(index) = &destination.index {
+        collection::add_to_index(index, title, &path)
+            .map_err(|e| format!("Failed to update {}: {}", index.display(), e))?;
+    }
+    Ok(path.display().to_string())
+}
+
+/// Save a recipe imported with `TitleStyle::Filename` as "<Title>.cook" and
+/// return its path
+fn save_result(
+    result: &ImportResult,
+    destination: &Destination,
+) -> Result<String, Box<dyn std::error::Error>> {
+    match result {
+        ImportResult::Cooklang { content, title, .. } => save(content, title, destination),
+        ImportResult::Components(components) => save(
+            &stages::render_titled(components, &components.text, TitleStyle::Filename),
+            &components.name,
+            destination,
+        ),
+    }
+}
+
+/// Settings for `--crawl` and `--retry-failed`
+struct Batch {
+    /// Import at most this many sources
+    limit: usize,
+    /// Import without asking
+    assume_yes: bool,
+    /// Manifest file recording each source's outcome
+    manifest: String,
+    /// Skip sources the manifest lists as imported
+    resume: bool,
+    /// Save each recipe's photo next to it (`--save-image`)
+    photos: Option<Photos>,
+    /// Where the recipes are saved
+    destination: Destination,
+    /// Set aside recipes below a minimum quality (`--min-quality`)
+    quality: Option<QualityGate>,
+}
+
+/// How `--save-image` downloads recipe photos
+#[derive(Clone)]
+#[cfg_attr(not(feature = "image"), allow(dead_code))]
+struct Photos {
+    timeout: Duration,
+    proxy: Option<String>,
+}
+
+const NO_IMAGE_FEATURE: &str =
+    "--save-image needs the image feature: cargo install cooklang-import --features image";
+
+/// Save the recipe's photo, if it has one, next to `cook_path` with the same
+/// name ("Pancakes.cook" → "Pancakes.jpg"). Failures are only reported: the
+/// recipe itself was imported.
+async fn save_photo(result: &ImportResult, cook_path: &str, photos: &Photos) {
+    let Some(url) = result.image_url() else {
+        return;
+    };
+    match download_photo(&url, cook_path, photos).await {
+        Ok(path) => eprintln!("Saved photo to {}", path),
+        Err(e) => eprintln!("Couldn't save the photo {}: {}", url, e),
+    }
+}
+
+#[cfg(feature = "image")]
+async fn download_photo(
+    url: &str,
+    cook_path: &str,
+    photos: &Photos,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let config = cooklang_import::config::load_config()
+        .map(|c| c.images)
+        .unwrap_or_default();
+    let path = cooklang_import::recipe_image::save(
+        url,
+        std::path::Path::new(cook_path),
+        &config,
+        photos.timeout,
+        photos.proxy.as_deref(),
+    )
+    .await?;
+    Ok(path.display().to_string())
+}
+
+#[cfg(not(feature = "image"))]
+async fn download_photo(
+    _url: &str,
+    _cook_path: &str,
+    _photos: &Photos,
+) -> Result<String, Box<dyn std::error::Error>> {
+    Err(NO_IMAGE_FEATURE.into())
+}
+
+/// `--crawl URL`: find recipe links on an index page, then import each one
+/// as "<Title>.cook". Without a terminal to confirm on (and no `--yes`), the
+/// links are printed instead.
+async fn crawl(
+    index: &str,
+    batch: &Batch,
+    timeouts: &Timeouts,
+    proxy: Option<&str>,
+    configure: impl Fn(RecipeImporterBuilder) -> RecipeImporterBuilder,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let html = stages::fetch(index, timeouts, proxy, None).await?;
+    let found = discover_recipe_links(&html, index);
+    if found.is_empty() {
+        return Err(format!("No recipe links found on {}", index).into());
+    }
+
+    let mut manifest = Manifest::load(&batch.manifest)?;
+    let (done, urls): (Vec<String>, Vec<String>) = found
+        .into_iter()
+        .partition(|url| batch.resume && manifest.is_imported(url));
+    if !done.is_empty() {
+        eprintln!(
+            "Skipping {} recipes already imported ({})",
+            done.len(),
+            batch.manifest
+        );
+    }
+    let urls: Vec<String> = urls.into_iter().take(batch.limit).collect();
+    if urls.is_empty() {
+        eprintln!("Nothing left to import");
+        return Ok(());
+    }
+
+    if !batch.assume_yes && !std::io::stdin().is_terminal() {
+        for url in &urls {
+            println!("{}", url);
+        }
+        return Ok(());
+    }
+    eprintln!("Found {} recipe links:", urls.len());
+    for url in &urls {
+        eprintln!("  {}", url);
+    }
+    if !batch.assume_yes {
+        eprint!("Import all {}? [y/N] ", urls.len());
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        if !answer.trim().eq_ignore_ascii_case("y") {
+            return Ok(());
+        }
+    }
+
+    import_all(&urls, &mut manifest, batch, configure).await
+}
+
+/// `--retry-failed`: import again the sources whose last attempt failed
+async fn retry_failed(
+    batch: &Batch,
+    configure: impl Fn(RecipeImporterBuilder) -> RecipeImporterBuilder,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut manifest = Manifest::load(&batch.manifest)?;
+    let urls: Vec<String> = manifest.failed().into_iter().take(batch.limit).collect();
+    if urls.is_empty() {
+        eprintln!("No failed imports in {}", batch.manifest);
+        return Ok(());
+    }
+    import_all(&urls, &mut manifest, batch, configure).await
+}
+
+/// `--update FILE.cook`: import the recipe again from the source it was
+/// imported from and print what changed as a diff against the local file,
+/// or with `--base` (the file as first imported) merge the changes into the
+/// local edits. The local file is never written.
+async fn update(
+    path: &str,
+    base: Option<&str>,
+    reuse_provider: bool,
+    configure: impl Fn(RecipeImporterBuilder) -> RecipeImporterBuilder,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let local =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let source = provenance::recipe_source(&local).ok_or_else(|| {
+        format!(
+            "{} doesn't say where it was imported from (no import: source or source: in the frontmatter)",
+            path
+        )
+    })?;
+    let recorded = provenance::Provenance::from_cooklang(&local);
+
+    let mut builder = RecipeImporter::builder().url(&source);
+    // The same provider and model as the first import, unless others were given
+    if let Some(recorded) = recorded.as_ref().filter(|_| reuse_provider) {
+        let provider = recorded.provider.as_deref().map(|name| match name {
+            "open_ai" => "openai",
+            name => name,
+        });
+        if let Some(provider) = provider.and_then(|name| parse_provider(name).ok()) {
+            builder = builder.provider(provider);
+            if let Some(model) = &recorded.model {
+                builder = builder.model(model);
+            }
+        }
+    }
+    if recorded.is_some() {
+        builder = builder.provenance();
+    }
+    // Title where the local file has it
+    let title_style = if local.lines().any(|line| line.starts_with("title:")) {
+        TitleStyle::Frontmatter
+    } else if local.lines().any(|line| line.starts_with("# ")) {
+        TitleStyle::Heading
+    } else {
+        TitleStyle::Filename
+    };
+    let fresh = match configure(builder.title_style(title_style)).build().await? {
+        ImportResult::Cooklang { content, .. } => content,
+        ImportResult::Components(components) => {
+            stages::render_titled(&components, &components.text, title_style)
+        }
+    };
+
+    match base {
+        Some(base_path) => {
+            let base = std::fs::read_to_string(base_path)
+                .map_err(|e| format!("Failed to read {}: {}", base_path, e))?;
+            let merged = diff::merge3(&base, &local, &fresh, path, &source);
+            print!("{}", merged.text);
+            if merged.conflicts > 0 {
+                eprintln!(
+                    "Warning: {} conflicts where both {} and the source changed the same lines",
+                    merged.conflicts, path
+                );
+            }
+        }
+        None => {
+            let changes = diff::unified_diff(&local, &fresh, path, &source);
+            if changes.is_empty() {
+                eprintln!("{} is up to date with {}", path, source);
+            }
+            print!("{}", changes);
+        }
+    }
+    Ok(())
+}
+
+/// Import each URL as "<Title>.cook", recording every outcome in the
+/// manifest as it happens so an interrupted batch can be resumed
+async fn import_all(
+    urls: &[String],
+    manifest: &mut Manifest,
+    batch: &Batch,
+    configure: impl Fn(RecipeImporterBuilder) -> RecipeImporterBuilder,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let review = batch.destination.review();
+    let mut imported = 0;
+    let mut low_quality = 0;
+    for url in urls {
+        let entry = import_one(
+            configure(RecipeImporter::builder().url(url)),
+            url,
+            batch.photos.as_ref(),
+            &batch.destination,
+            batch.quality,
+            &review,
+        )
+        .await;
+        match entry.status {
+            ImportStatus::Imported => imported += 1,
+            ImportStatus::LowQuality => {
+                low_quality += 1;
+                report_low_quality(&entry);
+            }
+            ImportStatus::Failed => eprintln!(
+                "Skipped {}: {}",
+                url,
+                entry.error.as_deref().unwrap_or_default()
+            ),
+        }
+        manifest.record(entry);
+        manifest.save(&batch.manifest)?;
+    }
+    eprintln!(
+        "Imported {} of {} recipes (manifest: {})",
+        imported,
+        urls.len(),
+        batch.manifest
+    );
+    if low_quality > 0 {
+        eprintln!("{} below the minimum quality", low_quality);
+    }
+    Ok(())
+}
+
+/// Import one recipe as "<Title>.cook" in `destination`, and its photo with
+/// `photos`; below the `quality` gate it goes to `review` or nowhere. The
+/// outcome is the manifest entry for `source`.
+async fn import_one(
+    builder: RecipeImporterBuilder,
+    source: &str,
+    photos: Option<&Photos>,
+    destination: &Destination,
+    quality: Option<QualityGate>,
+    review: &Destination,
+) -> ManifestEntry {
+    let (result, extractor) = match builder
+        .title_style(TitleStyle::Filename)
+        .build_with_extractor()
+        .await
+    {
+        Ok(imported) => imported,
+        Err(e) => return ManifestEntry::failed(source, e),
+    };
+    let below = quality.and_then(|gate| gate.check(&result));
+    let destination = match below {
+        Some((_, BelowQuality::Skip)) => {
+            return ManifestEntry::low_quality(source, None, &result, extractor)
+        }
+        Some((_, BelowQuality::Review)) => review,
+        None => destination,
+    };
+    match save_result(&result, destination) {
+        Ok(path) => {
+            if let Some(photos) = photos {
+                save_photo(&result, &path, photos).await;
+            }
+            match below {
+                Some(_) => ManifestEntry::low_quality(source, Some(path), &result, extractor),
+                None => ManifestEntry::imported(source, Some(path), &result, extractor),
+            }
+        }
+        Err(e) => ManifestEntry::failed(source, e),
+    }
+}
+
+/// `--mirror DIR`: convert the recipe pages of a site saved with wget or
+/// HTTrack, saving each as "<Title>.cook" in the same directory under the
+/// destination as the page is under DIR, then report what was converted
+/// and what failed. Pages that don't look like recipes are skipped without
+/// calling an LLM.
+async fn convert_mirror(
+    root: &str,
+    base_url: Option<&str>,
+    limit: Option<usize>,
+    batch: &Batch,
+    configure: impl Fn(RecipeImporterBuilder) -> RecipeImporterBuilder,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let root = std::path::Path::new(root);
+    let pages = mirror::find_pages(root)
+        .map_err(|e| format!("Failed to read {}: {}", root.display(), e))?;
+    if pages.is_empty() {
+        return Err(format!("No saved pages (.html, .htm, .mht) in {}", root.display()).into());
+    }
+
+    let mut manifest = Manifest::load(&batch.manifest)?;
+    let review = batch.destination.review();
+    let mut converted = 0;
+    let mut failed = Vec::new();
+    let mut low_quality = Vec::new();
+    let mut not_recipes = 0;
+    let mut done = 0;
+    for relative in &pages {
+        let path = root.join(relative);
+        let source = path.display().to_string();
+        if batch.resume && manifest.is_imported(&source) {
+            done += 1;
+            continue;
+        }
+        if limit.is_some_and(|limit| converted + failed.len() + low_quality.len() >= limit) {
+            break;
+        }
+        let page = match SavedPage::read(&path) {
+            Ok(page) => page,
+            Err(e) => {
+                eprintln!("Skipped {}: {}", source, e);
+                failed.push((source.clone(), e.to_string()));
+                manifest.record(ManifestEntry::failed(&source, e));
+                manifest.save(&batch.manifest)?;
+                continue;
+            }
+        };
+        // Category, tag and about pages make up much of a site
+        if is_probably_recipe(&page.html) == RecipeLikelihood::Unlikely {
+            not_recipes += 1;
+            continue;
+        }
+
+        // The URL the page gives, else the one its place in the mirror gives
+        let url = page
+            .url
+            .clone()
+            .or_else(|| base_url.and_then(|base| mirror::page_url(base, relative)));
+        let mut builder = RecipeImporter::builder().html(page.html);
+        if let Some(url) = url {
+            builder = builder.source_url(url);
+        }
+        let destination = Destination {
+            dir: mirror::output_dir(&batch.destination.dir, relative),
+            index: batch.destination.index.clone(),
+        };
+        // Laid out like the mirror under review/ too
+        let review = Destination {
+            dir: mirror::output_dir(&review.dir, relative),
+            index: None,
+        };
+        let entry = import_one(
+            configure(builder),
+            &source,
+            batch.photos.as_ref(),
+            &destination,
+            batch.quality,
+            &review,
+        )
+        .await;
+        match entry.status {
+            ImportStatus::Imported => converted += 1,
+            ImportStatus::LowQuality => {
+                report_low_quality(&entry);
+                low_quality.push(entry.clone());
+            }
+            ImportStatus::Failed => {
+                let error = entry.error.clone().unwrap_or_default();
+                eprintln!("Skipped {}: {}", source, error);
+                failed.push((source.clone(), error));
+            }
+        }
+        manifest.record(entry);
+        manifest.save(&batch.manifest)?;
+    }
+
+    eprintln!();
+    eprintln!(
+        "Converted {} of {} recipe pages ({} failed, {} below the minimum quality)",
+        converted,
+        converted + failed.len() + low_quality.len(),
+        failed.len(),
+        low_quality.len()
+    );
+    if not_recipes > 0 {
+        eprintln!("Skipped {} pages without a recipe", not_recipes);
+    }
+    if done > 0 {
+        eprintln!("Skipped {} pages already converted", done);
+    }
+    if !failed.is_empty() {
+        eprintln!("Failed:");
+        for (source, error) in &failed {
+            eprintln!("  {}: {}", source, error);
+        }
+    }
+    if !low_quality.is_empty() {
+        eprintln!("Below the minimum quality:");
+        for entry in &low_quality {
+            eprintln!(
+                "  {} ({:.2}): {}",
+                entry.source,
+                entry.confidence.unwrap_or_default(),
+                entry.output.as_deref().unwrap_or("skipped")
+            );
+        }
+    }
+    eprintln!("Manifest: {}", batch.manifest);
+    Ok(())
+}
+
+/// `serve [--port PORT] [--host HOST]`: run the HTTP import API
+#[cfg(feature = "server")]
+async fn serve(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let option = |flag: &str| {
+        args.iter()
+            .position(|arg| arg == flag)
+            .map(|idx| args.get(idx + 1))
+    };
+    let port: u16 = match option("--port") {
+        Some(value) => value.ok_or("--port requires a number")?.parse()?,
+        None => 8080,
+    };
+    let host = match option("--host") {
+        Some(value) => value.ok_or("--host requires an address")?.as_str(),
+        None => "127.0.0.1",
+    };
+    let addr: std::net::SocketAddr = format!("{}:{}", host, port).parse()?;
+    eprintln!("Listening on http://{} (POST /import)", addr);
+    cooklang_import::server::serve(addr).await?;
+    Ok(())
+}
+
+#[cfg(not(feature = "server"))]
+async fn serve(_args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    Err("serve needs the server feature: cargo install cooklang-import --features server".into())
+}
+
+/// `test-site PAGE.html --adapter SITE.toml [--url URL]`: run a site adapter
+/// on a saved page
+fn test_site(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let page = args
+        .get(2)
+        .filter(|arg| !arg.starts_with("--"))
+        .ok_or("test-site requires a saved HTML page")?;
+    let adapter_path = args
+        .iter()
+        .position(|arg| arg == "--adapter")
+        .and_then(|idx| args.get(idx + 1))
+        .ok_or("test-site requires --adapter SITE.toml")?;
+
+    let adapter = SiteAdapter::from_file(adapter_path)?;
+    let html =
+        std::fs::read_to_string(page).map_err(|e| format!("Failed to read {}: {}", page, e))?;
+    let url = match args.iter().position(|arg| arg == "--url") {
+        Some(idx) => args.get(idx + 1).ok_or("--url requires a URL")?.clone(),
+        None => format!(
+            "https://{}/",
+            adapter
+                .domains
+                .first()
+                .map_or("example.com", String::as_str)
+        ),
+    };
+
+    println!(
+        "Adapter:    {} ({})",
+        adapter.name,
+        adapter.domains.join(", ")
+    );
+    let unmatched = adapter.unmatched(&html);
+    if unmatched.is_empty() {
+        println!("Unmatched:  none");
+    } else {
+        let fields: Vec<String> = unmatched
+            .iter()
+            .map(|(field, selector)| format!("{} ({})", field, selector))
+            .collect();
+        println!("Unmatched:  {}", fields.join(", "));
+    }
+    println!();
+
+    let components = adapter.extract(&html, &url)?;
+    println!("{}", stages::render(&components, &components.text));
+    Ok(())
+}
+
+/// `diff FIRST SECOND [--format json]`: compare two imports of a recipe
+fn compare(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let paths: Vec<&String> = args[2..]
+        .iter()
+        .take_while(|arg| !arg.starts_with("--"))
+        .collect();
+    let [first, second] = paths[..] else {
+        return Err("diff requires two recipe files".into());
+    };
+    let read = |path: &String| {
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))
+    };
+    let (first_text, second_text) = (read(first)?, read(second)?);
+    let is_cooklang = |path: &String| path.ends_with(".cook");
+    let report = if is_cooklang(first) && is_cooklang(second) {
+        converters::compare_recipes(&first_text, &second_text)
+    } else {
+        // Extracted recipes, as printed by --extract-only
+        let components = |text: &str| cooklang_import::RecipeComponents {
+            text: cooklang_import::split_frontmatter(text).1.to_string(),
+            ..Default::default()
+        };
+        converters::compare_components(&components(&first_text), &components(&second_text))
+    };
+
+    if report_as_json(args)? {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("--- {}\n+++ {}", first, second);
+        print!("{}", report);
+    }
+    Ok(())
+}
+
+/// `eval DIR [--providers P[:MODEL],...] [--format json]`: score providers
+/// on an eval set
+async fn benchmark(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = args
+        .get(2)
+        .filter(|arg| !arg.starts_with("--"))
+        .ok_or("eval requires a directory of NAME.txt and NAME.cook files")?;
+    let cases = eval::load_cases(std::path::Path::new(dir))
+        .map_err(|e| format!("Failed to read the eval set in {}: {}", dir, e))?;
+    if cases.is_empty() {
+        return Err(format!("No NAME.txt recipes in {}", dir).into());
+    }
+
+    let candidates = match args.iter().position(|arg| arg == "--providers") {
+        Some(idx) => args
+            .get(idx + 1)
+            .ok_or("--providers requires comma-separated provider names")?
+            .split(',')
+            .map(|spec| {
+                let (name, model) = match spec.trim().split_once(':') {
+                    Some((name, model)) => (name, Some(model.to_string())),
+                    None => (spec.trim(), None),
+                };
+                Ok(eval::Candidate {
+                    provider: parse_provider(name)?,
+                    model,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?,
+        None => {
+            let config = cooklang_import::config::load_config()?;
+            let mut names: Vec<&String> = config
+                .providers
+                .iter()
+                .filter(|(_, provider)| provider.enabled)
+                .map(|(name, _)| name)
+                .collect();
+            if names.is_empty() {
+                names.push(&config.default_provider);
+            }
+            names.sort();
+            names
+                .into_iter()
+                .map(|name| {
+                    Ok(eval::Candidate {
+                        provider: parse_provider(&name.replace("open_ai", "openai"))?,
+                        model: None,
+                    })
+                })
+                .collect::<Result<Vec<_>, String>>()?
+        }
+    };
+
+    let json = report_as_json(args)?;
+    let report = eval::run(&cases, &candidates).await;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{}", report);
+    }
+    Ok(())
+}
+
+/// Whether a subcommand's `--format` asks for JSON rather than text
+fn report_as_json(args: &[String]) -> Result<bool, Box<dyn std::error::Error>> {
+    match args.iter().position(|arg| arg == "--format") {
+        Some(idx) => match args.get(idx + 1).map(String::as_str) {
+            Some("json") => Ok(true),
+            Some("text") => Ok(false),
+            _ => Err("--format must be json or text".into()),
+        },
+        None => Ok(false),
+    }
+}
+
+/// `cooklang-import doctor`: print every check, failing if any found an error
+async fn doctor() -> Result<(), Box<dyn std::error::Error>> {
+    let checks = cooklang_import::doctor::run().await;
+    for check in &checks {
+        let mark = match check.status {
+            Status::Ok => "ok",
+            Status::Warning => "warn",
+            Status::Error => "FAIL",
+        };
+        println!("[{:>4}] {}: {}", mark, check.name, check.detail);
+        if let Some(hint) = &check.hint {
+            println!("       → {}", hint);
+        }
+    }
+    let errors = checks
+        .iter()
+        .filter(|check| check.status == Status::Error)
+        .count();
+    match errors {
+        0 => Ok(()),
+        1 => Err("doctor found 1 problem".into()),
+        n => Err(format!("doctor found {} problems", n).into()),
+    }
+}
+
+/// Print `question` and read a line from stdin; an empty answer is `default`
+fn ask(question: &str, default: &str) -> Result<String, Box<dyn std::error::Error>> {
+    if default.is_empty() {
+        eprint!("{}: ", question);
+    } else {
+        eprint!("{} [{}]: ", question, default);
+    }
+    let mut answer = String::new();
+    if std::io::stdin().read_line(&mut answer)? == 0 {
+        return Err("init needs answers on standard input".into());
+    }
+    Ok(match answer.trim() {
+        "" => default.to_string(),
+        answer => answer.to_string(),
+    })
+}
+
+/// Whether the answer to a yes/no `question` is yes
+fn confirm(question: &str, default: bool) -> Result<bool, Box<dyn std::error::Error>> {
+    eprint!("{} [{}] ", question, if default { "Y/n" } else { "y/N" });
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(match answer.trim().to_lowercase().as_str() {
+        "" => default,
+        answer => answer == "y" || answer == "yes",
+    })
+}
+
+/// `cooklang-import init`: ask for a provider and key, check them, write
+/// the config and convert a sample recipe
+async fn init() -> Result<(), Box<dyn std::error::Error>> {
+    let path = env::var_os(cooklang_import::config::CONFIG_PATH_VAR)
+        .map(std::path::PathBuf::from)
+        .or_else(cooklang_import::config::user_config_path)
+        .unwrap_or_else(|| "config.toml".into());
+    if path.exists() && !confirm(&format!("{} exists. Replace it?", path.display()), false)? {
+        return Ok(());
+    }
+
+    eprintln!("Which provider should convert recipes?");
+    for (i, provider) in setup::PROVIDERS.iter().enumerate() {
+        let local = if provider.env_var.is_none() {
+            " (local, no API key)"
+        } else {
+            ""
+        };
+        eprintln!("  {}. {}{}", i + 1, provider.label, local);
+    }
+    let provider = loop {
+        let choice = ask("Provider", "1")?;
+        match choice
+            .parse::<usize>()
+            .ok()
+            .and_then(|n| setup::PROVIDERS.get(n.wrapping_sub(1)))
+        {
+            Some(provider) => break provider,
+            None => eprintln!("Enter a number from 1 to {}", setup::PROVIDERS.len()),
+        }
+    };
+    let model = ask("Model", provider.model)?;
+    let base_url = match provider.env_var {
+        None => Some(ask("Ollama server", "http://localhost:11434")?),
+        Some(_) => None,
+    };
+    let api_key = match provider.env_var {
+        Some(env_var) if env::var_os(env_var).is_some() => {
+            if confirm(&format!("Use the key in {}?", env_var), true)? {
+                None
+            } else {
+                Some(ask("API key", "")?)
+            }
+        }
+        Some(env_var) => {
+            let key = ask(&format!("API key (empty to set {} later)", env_var), "")?;
+            (!key.is_empty()).then_some(key)
+        }
+        None => None,
+    };
+    let answers = setup::Setup {
+        provider,
+        model,
+        api_key,
+        base_url,
+    };
+
+    eprintln!("Checking {}...", provider.label);
+    let answered = match answers.ping().await {
+        Ok(()) => {
+            eprintln!("{} answered.", provider.label);
+            true
+        }
+        Err(e) => {
+            eprintln!("{} didn't answer: {}", provider.label, e);
+            if !confirm("Save the config anyway?", false)? {
+                return Ok(());
+            }
+            false
+        }
+    };
+
+    setup::write_config(&path, &answers.to_toml())
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    eprintln!("Wrote {}", path.display());
+    if !answered {
+        return Ok(());
+    }
+
+    // Read the new file even if an older ./config.toml would override it
+    env::set_var(cooklang_import::config::CONFIG_PATH_VAR, &path);
+    eprintln!("Converting a sample recipe...");
+    match RecipeImporter::builder()
+        .text(setup::SAMPLE_RECIPE)
+        .build()
+        .await
+    {
+        Ok(result) => {
+            if let ImportResult::Cooklang { content, .. } = result {
+                println!("{}", content);
+            }
+            eprintln!("All set: try cooklang-import https://example.com/recipe");
+        }
+        Err(e) => eprintln!("The sample conversion failed: {}", e),
+    }
+    Ok(())
+}
+
+/// Exit status for a failed run, by kind of failure (see EXIT CODES in the help)
+fn exit_code(kind: ErrorKind) -> u8 {
+    match kind {
+        ErrorKind::Other => 1,
+        ErrorKind::Input => 2,
+        ErrorKind::Fetch => 3,
+        ErrorKind::NoRecipe => 4,
+        ErrorKind::Llm => 5,
+        ErrorKind::Config => 6,
+        ErrorKind::Timeout => 7,
+        ErrorKind::LowQuality => 8,
+    }
+}
+
+/// `error` for `--error-format json`: its kind, exit code and message, plus
+/// the HTTP status, URL or provider when the error has them
+fn error_json(error: &(dyn std::error::Error + 'static)) -> serde_json::Value {
+    let import_error = error.downcast_ref::<ImportError>();
+    let kind = import_error.map_or(ErrorKind::Other, ImportError::kind);
+    let mut json = serde_json::json!({
+        "kind": kind,
+        "exit_code": exit_code(kind),
+        "message": error.to_string(),
+    });
+    match import_error {
+        Some(ImportError::FetchError(e)) => {
+            json["url"] = e.url().map(|url| url.as_str()).into();
+        }
+        Some(ImportError::HttpStatus { code, url }) => {
+            json["status"] = (*code).into();
+            json["url"] = url.as_str().into();
+        }
+        Some(ImportError::NoExtractorMatched(diagnostics)) => {
+            json["url"] = diagnostics.url.as_str().into();
+            json["details"] = diagnostics.to_string().into();
+        }
+        Some(ImportError::LlmApi {
+            provider, status, ..
+        }) => {
+            json["provider"] = provider.as_str().into();
+            json["status"] = (*status).into();
+        }
+        Some(ImportError::RateLimited {
+            provider,
+            retry_after,
+        }) => {
+            json["provider"] = provider.as_str().into();
+            json["retry_after_secs"] = retry_after.map(|wait| wait.as_secs()).into();
+        }
+        _ => {}
+    }
+    serde_json::json!({ "error": json })
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> ExitCode {
     // Initialize the logger
     env_logger::init();
 
     // Parse command line arguments
     let args: Vec<String> = env::args().collect();
 
+    let json_errors = match args.iter().position(|arg| arg == "--error-format") {
+        Some(idx) => match args.get(idx + 1).map(String::as_str) {
+            Some("json") => true,
+            Some("text") => false,
+            _ => {
+                eprintln!("Error: --error-format requires text or json");
+                return ExitCode::from(exit_code(ErrorKind::Input));
+            }
+        },
+        None => false,
+    };
+
+    let Err(error) = run(&args).await else {
+        return ExitCode::SUCCESS;
+    };
+    let import_error = error.downcast_ref::<ImportError>();
+    if json_errors {
+        eprintln!("{}", error_json(error.as_ref()));
+    } else {
+        eprintln!("Error: {}", error);
+        let verbose = args.iter().any(|arg| arg == "--verbose" || arg == "-v");
+        if matches!(import_error, Some(ImportError::NoExtractorMatched(_))) && !verbose {
+            eprintln!("Run with --verbose to see what each extractor found");
+        }
+    }
+    ExitCode::from(exit_code(
+        import_error.map_or(ErrorKind::Other, ImportError::kind),
+    ))
+}
+
+async fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
     // Check for help flag
     if args.len() == 1 || args.contains(&"--help".to_string()) || args.contains(&"-h".to_string()) {
         print_help();
         return Ok(());
     }
 
+    // Read before anything loads the config, for subcommands too
+    if let Some(idx) = args.iter().position(|arg| arg == "--config") {
+        let path = args.get(idx + 1).ok_or("--config requires a file path")?;
+        env::set_var(cooklang_import::config::CONFIG_PATH_VAR, path);
+    }
+
+    if args[1] == "test-site" {
+        return test_site(args);
+    }
+    if args[1] == "diff" {
+        return compare(args);
+    }
+    if args[1] == "eval" {
+        return benchmark(args).await;
+    }
+    if args[1] == "serve" {
+        return serve(args).await;
+    }
+    if args[1] == "doctor" {
+        return doctor().await;
+    }
+    if args[1] == "init" {
+        return init().await;
+    }
+    if args[1] == "daemon" {
+        cooklang_import::daemon::run_stdio().await?;
+        return Ok(());
+    }
+
     // Parse flags
     let extract_only = args.contains(&"--extract-only".to_string())
         || args.contains(&"--download-only".to_string());
-    let text_mode = args.contains(&"--text".to_string());
+    let text_mode = ["--text", "--text-file", "--stdin"]
+        .iter()
+        .any(|flag| args.iter().any(|arg| arg == flag));
     let image_mode = args.contains(&"--image".to_string());
+    let email_path = match args.iter().position(|arg| arg == "--email") {
+        Some(idx) => Some(args.get(idx + 1).ok_or("--email requires a file path")?),
+        None => None,
+    };
+    let markdown_path = match args.iter().position(|arg| arg == "--markdown") {
+        Some(idx) => Some(args.get(idx + 1).ok_or("--markdown requires a file path")?),
+        None => None,
+    };
+    let html_path = match args.iter().position(|arg| arg == "--html-file") {
+        Some(idx) => Some(
+            args.get(idx + 1)
+                .ok_or("--html-file requires a file path")?,
+        ),
+        None => None,
+    };
+    let source_url = match args.iter().position(|arg| arg == "--source-url") {
+        Some(idx) => Some(args.get(idx + 1).ok_or("--source-url requires a URL")?),
+        None => None,
+    };
+    let mirror_dir = match args.iter().position(|arg| arg == "--mirror") {
+        Some(idx) => Some(args.get(idx + 1).ok_or("--mirror requires a directory")?),
+        None => None,
+    };
+    if source_url.is_some() && html_path.is_none() && mirror_dir.is_none() {
+        return Err("--source-url only works with --html-file or --mirror".into());
+    }
+    let audio_path = match args.iter().position(|arg| arg == "--audio") {
+        Some(idx) => Some(args.get(idx + 1).ok_or("--audio requires a file path")?),
+        None => None,
+    };
+    let structured = args.contains(&"--structured".to_string());
+    let check_ingredients = args.contains(&"--check-ingredients".to_string());
+    let crawl_url = match args.iter().position(|arg| arg == "--crawl") {
+        Some(idx) => Some(
+            args.get(idx + 1)
+                .ok_or("--crawl requires an index page URL")?,
+        ),
+        None => None,
+    };
+    let crawl_limit = match args.iter().position(|arg| arg == "--limit") {
+        Some(idx) => {
+            let limit = args.get(idx + 1).ok_or("--limit requires a number")?;
+            limit
+                .parse()
+                .map_err(|_| format!("Invalid limit value: {}", limit))?
+        }
+        None => 20,
+    };
+    let retry = args.iter().any(|arg| arg == "--retry-failed");
+    let update_path = match args.iter().position(|arg| arg == "--update") {
+        Some(idx) => Some(args.get(idx + 1).ok_or("--update requires a .cook file")?),
+        None => None,
+    };
+    let vision = args.contains(&"--vision".to_string());
 
     // Parse provider option
     let provider = if let Some(idx) = args.iter().position(|arg| arg == "--provider") {
         let provider_name = args
             .get(idx + 1)
             .ok_or("--provider requires a provider name")?;
-        Some(match provider_name.as_str() {
-            "openai" => LlmProvider::OpenAI,
-            "anthropic" => LlmProvider::Anthropic,
-            "google" => LlmProvider::Google,
-            "azure_openai" => LlmProvider::AzureOpenAI,
-            "ollama" => LlmProvider::Ollama,
-            _ => {
-                return Err(format!(
-                "Unknown provider: {}. Available: openai, anthropic, google, azure_openai, ollama",
-                provider_name
-            )
-                .into())
-            }
-        })
+        Some(parse_provider(provider_name)?)
+    } else {
+        None
+    };
+
+    // Parse fallback chain option (comma-separated providers)
+    let providers = if let Some(idx) = args.iter().position(|arg| arg == "--providers") {
+        let value = args
+            .get(idx + 1)
+            .ok_or("--providers requires comma-separated provider names")?;
+        Some(
+            value
+                .split(',')
+                .map(|name| parse_provider(name.trim()))
+                .collect::<Result<Vec<_>, _>>()?,
+        )
+    } else {
+        None
+    };
+
+    // Parse race option (two comma-separated providers)
+    let race = if let Some(idx) = args.iter().position(|arg| arg == "--race") {
+        let value = args
+            .get(idx + 1)
+            .ok_or("--race requires two comma-separated provider names")?;
+        match value
+            .split(',')
+            .map(str::trim)
+            .collect::<Vec<_>>()
+            .as_slice()
+        {
+            [first, second] => Some((parse_provider(first)?, parse_provider(second)?)),
+            _ => return Err("--race requires exactly two providers, e.g. openai,anthropic".into()),
+        }
     } else {
         None
     };
@@ -122,54 +1601,475 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         None
     };
 
-    // Build and execute based on use case
-    let result = if image_mode {
-        // Use Case 5: Image → Cooklang (OCR then convert)
-        let image_path = if let Some(idx) = args.iter().position(|arg| arg == "--image") {
+    let no_cache = args.iter().any(|arg| arg == "--no-cache");
+    let offline = args.iter().any(|arg| arg == "--offline")
+        || cooklang_import::config::load_config().is_ok_and(|config| config.offline);
+    let dry_run = args.iter().any(|arg| arg == "--dry-run");
+
+    let config = cooklang_import::config::load_config().ok();
+    let min_quality = match args.iter().position(|arg| arg == "--min-quality") {
+        Some(idx) => {
+            let score = args
+                .get(idx + 1)
+                .ok_or("--min-quality requires a score from 0 to 1")?;
+            let min: f32 = score
+                .parse()
+                .map_err(|_| format!("Invalid minimum quality: {}", score))?;
+            Some(min)
+        }
+        None => config.as_ref().and_then(|c| c.min_quality),
+    };
+    if min_quality.is_some_and(|min| !(0.0..=1.0).contains(&min)) {
+        return Err("--min-quality (min_quality) must be between 0 and 1".into());
+    }
+    let below_quality = match args.iter().position(|arg| arg == "--below-quality") {
+        Some(idx) => match args.get(idx + 1).map(String::as_str) {
+            Some("review") => BelowQuality::Review,
+            Some("skip") => BelowQuality::Skip,
+            _ => return Err("--below-quality requires review or skip".into()),
+        },
+        None => config.map(|c| c.below_quality).unwrap_or_default(),
+    };
+    let quality = min_quality.map(|min| QualityGate {
+        min,
+        action: below_quality,
+    });
+
+    let all_recipes = args.iter().any(|arg| arg == "--all-recipes");
+    let sub_recipes = args.iter().any(|arg| arg == "--sub-recipes");
+    let verbose = args.iter().any(|arg| arg == "--verbose" || arg == "-v");
+    let keep_unknown_fields = args.iter().any(|arg| arg == "--keep-unknown-fields");
+    let provenance = args.iter().any(|arg| arg == "--provenance");
+    let both_temperatures = args.iter().any(|arg| arg == "--both-temperatures");
+    let aliases = match args.iter().position(|arg| arg == "--aliases") {
+        Some(idx) => Some(args.get(idx + 1).ok_or("--aliases requires a file path")?),
+        None => None,
+    };
+    let pantry = match args.iter().position(|arg| arg == "--pantry") {
+        Some(idx) => {
+            let path = args.get(idx + 1).ok_or("--pantry requires a file path")?;
+            let text = std::fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+            Some(cooklang_import::converters::parse_pantry(&text))
+        }
+        None => None,
+    };
+
+    let proxy = if let Some(idx) = args.iter().position(|arg| arg == "--proxy") {
+        Some(args.get(idx + 1).ok_or("--proxy requires a URL")?.clone())
+    } else {
+        None
+    };
+
+    // Same as `[fetch] user_agent`, through the config's environment layer
+    if let Some(idx) = args.iter().position(|arg| arg == "--user-agent") {
+        let user_agent = args.get(idx + 1).ok_or("--user-agent requires a value")?;
+        env::set_var("COOKLANG__FETCH__USER_AGENT", user_agent);
+    }
+    if args.iter().any(|arg| arg == "--wayback") {
+        env::set_var("COOKLANG__FETCH__WAYBACK", "true");
+    }
+
+    // Saving into a CookCLI collection: "<Title>.cook" in DIR with its photo,
+    // the collection's aisle.conf kept up to date and an optional index
+    let import_into = match args.iter().position(|arg| arg == "--import-into") {
+        Some(idx) => Some(PathBuf::from(
             args.get(idx + 1)
-                .ok_or("--image requires a file path")?
-                .clone()
-        } else {
-            return Err("--image mode requires a file path".into());
-        };
+                .ok_or("--import-into requires a directory")?,
+        )),
+        None => None,
+    };
+    let index = match args.iter().position(|arg| arg == "--index") {
+        Some(idx) => Some(args.get(idx + 1).ok_or("--index requires a file path")?),
+        None => None,
+    };
+    let destination = match import_into {
+        Some(dir) => Destination {
+            // Relative to the top of the collection, next to config/
+            index: index.map(|index| collection::collection_root(&dir).join(index)),
+            dir,
+        },
+        None if index.is_some() => return Err("--index requires --import-into".into()),
+        None => Destination::default(),
+    };
+    let importing_into = !destination.dir.as_os_str().is_empty();
 
-        info!(
-            "Converting image to Cooklang (image: {}, provider: {:?})",
-            image_path, provider
+    let aisle_conf = if let Some(idx) = args.iter().position(|arg| arg == "--aisle-conf") {
+        Some(
+            args.get(idx + 1)
+                .ok_or("--aisle-conf requires a file path")?
+                .clone(),
+        )
+    } else if importing_into {
+        collection::aisle_conf(&destination.dir).map(|path| path.display().to_string())
+    } else {
+        None
+    };
+
+    let dedupe_against = if let Some(idx) = args.iter().position(|arg| arg == "--dedupe-against") {
+        Some(
+            args.get(idx + 1)
+                .ok_or("--dedupe-against requires a directory")?
+                .clone(),
+        )
+    } else if importing_into && destination.dir.is_dir() {
+        let root = collection::collection_root(&destination.dir);
+        Some(root.display().to_string())
+    } else {
+        None
+    };
+    let skip_duplicates = args.iter().any(|arg| arg == "--skip-duplicates");
+
+    let json_output = match args.iter().position(|arg| arg == "--format") {
+        Some(idx) => match args.get(idx + 1).map(String::as_str) {
+            Some("json") => true,
+            Some("cooklang") => false,
+            Some(other) => {
+                return Err(format!("Unknown format: {} (expected cooklang or json)", other).into())
+            }
+            None => return Err("--format requires cooklang or json".into()),
+        },
+        None => false,
+    };
+
+    let title_style = if let Some(idx) = args.iter().position(|arg| arg == "--title-style") {
+        args.get(idx + 1)
+            .ok_or("--title-style requires frontmatter, filename or heading")?
+            .parse::<TitleStyle>()?
+    } else {
+        TitleStyle::Frontmatter
+    };
+    let title_style = if importing_into {
+        if args.iter().any(|arg| arg == "--title-style") && title_style != TitleStyle::Filename {
+            return Err(
+                "--import-into saves \"<Title>.cook\" files and needs --title-style filename"
+                    .into(),
+            );
+        }
+        TitleStyle::Filename
+    } else {
+        title_style
+    };
+    if json_output && importing_into {
+        return Err(
+            "--format json prints to stdout and cannot be combined with --import-into".into(),
+        );
+    }
+    if json_output && title_style == TitleStyle::Filename {
+        return Err(
+            "--format json prints to stdout and cannot be combined with --title-style filename"
+                .into(),
         );
+    }
+
+    let scale = if let Some(idx) = args.iter().position(|arg| arg == "--scale") {
+        let factor_str = args.get(idx + 1).ok_or("--scale requires a number")?;
+        let factor: f64 = factor_str
+            .parse()
+            .map_err(|_| format!("Invalid scale value: {}", factor_str))?;
+        Some(factor)
+    } else {
+        None
+    };
+
+    let units = if let Some(idx) = args.iter().position(|arg| arg == "--units") {
+        let system_str = args
+            .get(idx + 1)
+            .ok_or("--units requires metric or imperial")?;
+        Some(system_str.parse::<UnitSystem>()?)
+    } else {
+        None
+    };
+
+    let target_language = if let Some(idx) = args.iter().position(|arg| arg == "--target-language")
+    {
+        Some(
+            args.get(idx + 1)
+                .ok_or("--target-language requires a language")?
+                .clone(),
+        )
+    } else {
+        None
+    };
 
-        let mut builder = RecipeImporter::builder().image_path(&image_path);
+    let llm_timeout = if let Some(idx) = args.iter().position(|arg| arg == "--llm-timeout") {
+        let timeout_str = args.get(idx + 1).ok_or("--llm-timeout requires a number")?;
+        let seconds: u64 = timeout_str
+            .parse()
+            .map_err(|_| format!("Invalid LLM timeout value: {}", timeout_str))?;
+        Some(Duration::from_secs(seconds))
+    } else {
+        None
+    };
 
+    // Options shared by every source (and every page of a crawl)
+    let configure = |mut builder: RecipeImporterBuilder| {
         if extract_only {
             builder = builder.extract_only();
         }
 
-        if let Some(p) = provider {
-            builder = builder.provider(p);
+        if let Some(p) = &provider {
+            builder = builder.provider(p.clone());
         }
 
-        builder.build().await?
-    } else if text_mode {
-        // Use Case 4: Text → Cooklang
-        let text = if let Some(idx) = args.iter().position(|arg| arg == "--text") {
-            args.get(idx + 1).ok_or("--text requires a value")?.clone()
+        if let Some(chain) = &providers {
+            builder = builder.providers(chain);
+        }
+
+        if let Some((first, second)) = &race {
+            builder = builder.race(first.clone(), second.clone());
+        }
+
+        if structured {
+            builder = builder.structured_output();
+        }
+
+        if check_ingredients {
+            builder = builder.check_ingredients();
+        }
+
+        if let Some(t) = timeout {
+            builder = builder.timeout(t);
+        }
+
+        if let Some(t) = llm_timeout {
+            builder = builder.llm_timeout(t);
+        }
+
+        if let Some(url) = &proxy {
+            builder = builder.proxy(url);
+        }
+
+        if no_cache {
+            builder = builder.no_cache();
+        }
+
+        let builder = match scale {
+            Some(factor) => builder.scale(factor),
+            None => builder,
+        };
+        let builder = match units {
+            Some(system) => builder.units(system),
+            None => builder,
+        };
+        let builder = if both_temperatures {
+            builder.both_temperatures()
+        } else {
+            builder
+        };
+        let builder = match aliases {
+            Some(path) => builder.ingredient_aliases(path),
+            None => builder,
+        };
+        let builder = match &pantry {
+            Some(items) => builder.pantry(items),
+            None => builder,
+        };
+        let builder = match &target_language {
+            Some(language) => builder.target_language(language),
+            None => builder,
+        };
+        let builder = if keep_unknown_fields {
+            builder.keep_unknown_fields()
+        } else {
+            builder
+        };
+        let builder = if provenance {
+            builder.provenance()
+        } else {
+            builder
+        };
+        let builder = builder.title_style(title_style);
+        if offline {
+            builder.offline()
         } else {
-            return Err("--text mode requires a text value".into());
+            builder
+        }
+    };
+
+    if all_recipes && (text_mode || image_mode || email_path.is_some() || markdown_path.is_some()) {
+        return Err("--all-recipes only works when importing a URL or a saved page".into());
+    }
+    if (all_recipes || sub_recipes) && (json_output || dry_run) {
+        return Err(
+            "--all-recipes and --sub-recipes save each recipe to a file and cannot be combined with --format json or --dry-run"
+                .into(),
+        );
+    }
+    // A collection keeps each recipe's photo next to it when the binary can
+    let photos = if args.iter().any(|arg| arg == "--save-image")
+        || (importing_into && cfg!(feature = "image") && !offline)
+    {
+        if !cfg!(feature = "image") {
+            return Err(NO_IMAGE_FEATURE.into());
+        }
+        if offline {
+            return Err(ImportError::OfflineError("saving the recipe photo".to_string()).into());
+        }
+        Some(Photos {
+            timeout: timeout.unwrap_or_else(|| Timeouts::from_config().fetch),
+            proxy: proxy.clone(),
+        })
+    } else {
+        None
+    };
+
+    let batch = Batch {
+        limit: crawl_limit,
+        assume_yes: args.iter().any(|arg| arg == "--yes" || arg == "-y"),
+        manifest: match args.iter().position(|arg| arg == "--manifest") {
+            Some(idx) => args
+                .get(idx + 1)
+                .ok_or("--manifest requires a file path")?
+                .clone(),
+            None => "cooklang-import-manifest.json".to_string(),
+        },
+        resume: args.iter().any(|arg| arg == "--resume"),
+        photos: photos.clone(),
+        destination: destination.clone(),
+        quality,
+    };
+    if (crawl_url.is_some() || retry || mirror_dir.is_some()) && json_output {
+        return Err(
+            "--crawl, --retry-failed and --mirror save each recipe to a file and cannot be combined with --format json"
+                .into(),
+        );
+    }
+    if let Some(index) = crawl_url {
+        if offline {
+            return Err(ImportError::OfflineError("crawling an index page".to_string()).into());
+        }
+        let mut timeouts = Timeouts::from_config();
+        if let Some(t) = timeout {
+            timeouts.fetch = t;
+        }
+        return crawl(index, &batch, &timeouts, proxy.as_deref(), configure).await;
+    }
+    if retry {
+        return retry_failed(&batch, configure).await;
+    }
+    if let Some(dir) = mirror_dir {
+        // A whole site by default
+        let limit = args
+            .iter()
+            .any(|arg| arg == "--limit")
+            .then_some(batch.limit);
+        return convert_mirror(
+            dir,
+            source_url.map(String::as_str),
+            limit,
+            &batch,
+            configure,
+        )
+        .await;
+    }
+    if let Some(path) = update_path {
+        if offline {
+            return Err(ImportError::OfflineError("re-importing a recipe".to_string()).into());
+        }
+        let base = match args.iter().position(|arg| arg == "--base") {
+            Some(idx) => Some(args.get(idx + 1).ok_or("--base requires a file path")?),
+            None => None,
         };
+        let reuse_provider = !args
+            .iter()
+            .any(|arg| matches!(arg.as_str(), "--provider" | "--providers"));
+        return update(path, base.map(String::as_str), reuse_provider, configure).await;
+    }
 
-        info!("Converting text to Cooklang (provider: {:?})", provider);
+    // Configure the importer based on use case
+    let builder = if image_mode {
+        // Use Case 5: Image → Cooklang (OCR then convert)
+        // Each --image adds a page of the same recipe
+        let mut image_paths = Vec::new();
+        for (idx, _) in args.iter().enumerate().filter(|(_, arg)| *arg == "--image") {
+            let path = args
+                .get(idx + 1)
+                .filter(|path| !path.starts_with("--"))
+                .ok_or("--image requires a file path")?;
+            image_paths.push(path.clone());
+        }
+        let image_order = match args.iter().position(|arg| arg == "--image-order") {
+            Some(idx) => args
+                .get(idx + 1)
+                .ok_or("--image-order requires given or filename")?
+                .parse::<ImageOrder>()?,
+            None => ImageOrder::Given,
+        };
 
-        let mut builder = RecipeImporter::builder().text(&text);
+        info!(
+            "Converting image to Cooklang (images: {}, provider: {:?})",
+            image_paths.join(", "),
+            provider
+        );
 
-        if extract_only {
-            builder = builder.extract_only();
+        let mut builder = RecipeImporter::builder()
+            .images(image_paths.into_iter().map(ImageSource::Path).collect())
+            .image_order(image_order);
+
+        if vision {
+            builder = builder.vision_extraction();
         }
 
-        if let Some(p) = provider {
-            builder = builder.provider(p);
+        builder
+    } else if let Some(path) = audio_path {
+        // Dictated recipe → transcript → Cooklang
+        info!(
+            "Converting audio {} to Cooklang (provider: {:?})",
+            path, provider
+        );
+        RecipeImporter::builder().audio_path(path)
+    } else if let Some(path) = email_path {
+        // Email (.eml) → Cooklang
+        info!(
+            "Converting email {} to Cooklang (provider: {:?})",
+            path, provider
+        );
+        let eml = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        RecipeImporter::builder().email(eml)
+    } else if let Some(path) = html_path {
+        // Saved page (.html, .mht) → Cooklang
+        info!(
+            "Converting saved page {} to Cooklang (provider: {:?})",
+            path, provider
+        );
+        let page = SavedPage::read(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        let builder = RecipeImporter::builder().html(page.html);
+        match source_url.or(page.url.as_ref()) {
+            Some(url) => builder.source_url(url),
+            None => builder,
+        }
+    } else if let Some(path) = markdown_path {
+        // Markdown → Cooklang
+        info!(
+            "Converting Markdown {} to Cooklang (provider: {:?})",
+            path, provider
+        );
+        let markdown =
+            std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        RecipeImporter::builder().markdown(markdown)
+    } else if text_mode {
+        // Use Case 4: Text → Cooklang
+        let text = if let Some(idx) = args.iter().position(|arg| arg == "--text") {
+            args.get(idx + 1).ok_or("--text requires a value")?.clone()
+        } else if let Some(idx) = args.iter().position(|arg| arg == "--text-file") {
+            let path = args
+                .get(idx + 1)
+                .ok_or("--text-file requires a file path")?;
+            std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?
+        } else {
+            let mut text = String::new();
+            std::io::Read::read_to_string(&mut std::io::stdin(), &mut text)
+                .map_err(|e| format!("Failed to read stdin: {}", e))?;
+            text
+        };
+        if text.trim().is_empty() {
+            return Err("The recipe text is empty".into());
         }
 
-        builder.build().await?
+        info!("Converting text to Cooklang (provider: {:?})", provider);
+
+        RecipeImporter::builder().text(&text)
     } else {
         // Use Case 1 or 2: URL-based
         let url = args
@@ -182,32 +2082,128 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             url, extract_only, provider, timeout
         );
 
-        let mut builder = RecipeImporter::builder().url(url);
+        RecipeImporter::builder().url(url)
+    };
 
-        if extract_only {
-            builder = builder.extract_only();
-        }
+    let builder = configure(builder);
 
-        if let Some(p) = provider {
-            builder = builder.provider(p);
-        }
+    if dry_run {
+        print_report(&builder.dry_run().await?);
+        return Ok(());
+    }
 
-        if let Some(t) = timeout {
-            builder = builder.timeout(t);
+    let results = if all_recipes || sub_recipes {
+        let mut builder = builder.title_style(TitleStyle::Filename);
+        if all_recipes {
+            builder = builder.multi();
+        }
+        if sub_recipes {
+            builder = builder.sub_recipes();
+        }
+        builder.build_all().await
+    } else {
+        builder.build().await.map(|result| vec![result])
+    };
+    let mut results = match results {
+        Ok(results) => results,
+        Err(ImportError::NoExtractorMatched(diagnostics)) => {
+            if verbose {
+                eprintln!("{}", diagnostics);
+            }
+            return Err(ImportError::NoExtractorMatched(diagnostics).into());
         }
+        Err(e) => return Err(e.into()),
+    };
+    if all_recipes || sub_recipes {
+        let mut saved = 0;
+        let mut low_quality = None;
+        for result in &results {
+            let below = quality.and_then(|gate| gate.check(result));
+            let destination = match &below {
+                Some((e, BelowQuality::Skip)) => {
+                    eprintln!("Skipped: {}", e);
+                    low_quality = low_quality.or(below);
+                    continue;
+                }
+                Some(_) => destination.review(),
+                None => destination.clone(),
+            };
+            let path = save_result(result, &destination)?;
+            if let Some(photos) = &photos {
+                save_photo(result, &path, photos).await;
+            }
+            saved += 1;
+            low_quality = low_quality.or(below);
+        }
+        eprintln!("Saved {} recipes", saved);
+        return match low_quality {
+            Some((e, _)) => Err(e.into()),
+            None => Ok(()),
+        };
+    }
+    let result = results.remove(0);
+
+    // Below the minimum quality the recipe is still output (to review/ when
+    // saved), unless it's to be skipped, and the exit code says so
+    let low_quality = match quality.and_then(|gate| gate.check(&result)) {
+        Some((e, BelowQuality::Skip)) => return Err(e.into()),
+        below => below.map(|(e, _)| e),
+    };
+    let destination = match low_quality {
+        Some(_) => destination.review(),
+        None => destination,
+    };
 
-        builder.build().await?
+    let json = if json_output {
+        Some(serde_json::to_string_pretty(&result)?)
+    } else {
+        None
     };
+    // Saved after the recipe, which may turn out to be a duplicate
+    let photo = photos.as_ref().map(|photos| (photos, result.clone()));
 
     // Format and print output
     match result {
         ImportResult::Cooklang {
             content,
+            title,
             conversion_metadata,
         } => {
-            println!("{}", content);
-            // Log conversion metadata if available
-            if let Some(meta) = conversion_metadata {
+            if let Some(dir) = &dedupe_against {
+                if is_duplicate(&content, dir, skip_duplicates)? {
+                    return Ok(());
+                }
+            }
+            match &json {
+                Some(json) => println!("{}", json),
+                None => emit(&content, &title, title_style, &destination)?,
+            }
+            if let Some(path) = aisle_conf.as_ref().filter(|_| low_quality.is_none()) {
+                let existing = match std::fs::read_to_string(path) {
+                    Ok(existing) => existing,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+                    Err(e) => return Err(format!("Failed to read {}: {}", path, e).into()),
+                };
+                std::fs::write(
+                    path,
+                    cooklang_import::converters::update_aisle_conf(&existing, &content),
+                )
+                .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+            }
+            let confidence = conversion_metadata
+                .as_ref()
+                .and_then(|meta| meta.confidence);
+            if let Some(confidence) =
+                confidence.filter(|c| *c < cooklang_import::converters::LOW_CONFIDENCE)
+            {
+                eprintln!(
+                    "Warning: low conversion confidence ({:.2}); ingredients may be missing \
+                     or left unmarked, check the recipe against the source",
+                    confidence
+                );
+            }
+            // Log conversion metadata if available (the JSON output has it)
+            if let Some(meta) = conversion_metadata.filter(|_| json.is_none()) {
                 eprintln!("\n--- Conversion Metadata ---");
                 if let Some(model) = &meta.model_version {
                     eprintln!("Model: {}", model);
@@ -219,33 +2215,54 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     eprintln!("Output tokens: {}", output);
                 }
                 eprintln!("Latency: {}ms", meta.latency_ms);
-            }
-        }
-        ImportResult::Components(components) => {
-            // Build the output with frontmatter
-            let mut output = String::new();
-
-            // Add frontmatter if we have name or metadata
-            if !components.name.is_empty() || !components.metadata.is_empty() {
-                output.push_str("---\n");
-                if !components.name.is_empty() {
-                    output.push_str(&format!("title: {}\n", components.name));
+                if let Some(confidence) = meta.confidence {
+                    eprintln!("Confidence: {:.2}", confidence);
                 }
-                if !components.metadata.is_empty() {
-                    output.push_str(&components.metadata);
-                    if !components.metadata.ends_with('\n') {
-                        output.push('\n');
+                if let Some(check) = &meta.ingredient_check {
+                    eprintln!("\n--- Ingredient Check ---");
+                    if check.is_ok() {
+                        eprintln!("All extracted ingredients are in the recipe");
+                    }
+                    for line in &check.missing {
+                        eprintln!("Missing: {}", line);
+                    }
+                    for name in &check.extra {
+                        eprintln!("Not in the source: {}", name);
                     }
                 }
-                output.push_str("---\n\n");
             }
+        }
+        ImportResult::Components(components) => {
+            let output = stages::render_titled(&components, &components.text, title_style);
 
-            // Add recipe text
-            output.push_str(&components.text);
+            if let Some(dir) = &dedupe_against {
+                if is_duplicate(&output, dir, skip_duplicates)? {
+                    return Ok(());
+                }
+            }
+            match &json {
+                Some(json) => println!("{}", json),
+                None => emit(&output, &components.name, title_style, &destination)?,
+            }
+        }
+    }
 
-            println!("{}", output);
+    if let Some((photos, result)) = photo {
+        let title = match &result {
+            ImportResult::Cooklang { title, .. } => title,
+            ImportResult::Components(components) => &components.name,
+        };
+        match stages::file_name(title) {
+            Some(name) => {
+                let path = destination.dir.join(name);
+                save_photo(&result, &path.display().to_string(), photos).await
+            }
+            None => eprintln!("Couldn't save the photo: the recipe has no title to name it after"),
         }
     }
 
-    Ok(())
+    match low_quality {
+        Some(e) => Err(e.into()),
+        None => Ok(()),
+    }
 }
@@ -0,0 +1,262 @@
+//! Batch import manifest
+//!
+//! Batch tools (such as `--crawl`) record each source in a JSON manifest:
+//! where the recipe was saved, which extractor found it, the tokens spent,
+//! the conversion's confidence, and whether it worked or was set aside
+//! below `--min-quality`. A later run reads the manifest to skip what's
+//! already imported (`--resume`) or to retry only the failures
+//! (`--retry-failed`).
+
+use crate::{ImportError, ImportResult};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Outcome of importing one source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportStatus {
+    /// Imported and saved
+    Imported,
+    /// The import or the save failed; see [`ManifestEntry::error`]
+    Failed,
+    /// Imported, but its confidence is below the minimum quality: saved for
+    /// review, or skipped when [`ManifestEntry::output`] is `None`
+    LowQuality,
+}
+
+/// One source in a [`Manifest`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// URL (or path) the recipe was imported from
+    pub source: String,
+    pub status: ImportStatus,
+    /// File the recipe was saved to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// How the recipe was found, as from
+    /// [`build_with_extractor`](crate::RecipeImporterBuilder::build_with_extractor)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extractor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_tokens: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_tokens: Option<u32>,
+    /// The conversion's confidence score
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+    /// Why the import failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ManifestEntry {
+    /// A successful import of `source`, saved to `output`
+    pub fn imported(
+        source: impl Into<String>,
+        output: Option<String>,
+        result: &ImportResult,
+        extractor: Option<String>,
+    ) -> Self {
+        let tokens = match result {
+            ImportResult::Cooklang {
+                conversion_metadata: Some(metadata),
+                ..
+            } => Some(&metadata.tokens_used),
+            _ => None,
+        };
+        ManifestEntry {
+            source: source.into(),
+            status: ImportStatus::Imported,
+            output,
+            extractor,
+            input_tokens: tokens.and_then(|tokens| tokens.input_tokens),
+            output_tokens: tokens.and_then(|tokens| tokens.output_tokens),
+            confidence: result.confidence(),
+            error: None,
+        }
+    }
+
+    /// An import of `source` below the minimum quality, saved for review to
+    /// `output` or skipped
+    pub fn low_quality(
+        source: impl Into<String>,
+        output: Option<String>,
+        result: &ImportResult,
+        extractor: Option<String>,
+    ) -> Self {
+        ManifestEntry {
+            status: ImportStatus::LowQuality,
+            ..ManifestEntry::imported(source, output, result, extractor)
+        }
+    }
+
+    /// A failed import of `source`
+    pub fn failed(source: impl Into<String>, error: impl ToString) -> Self {
+        ManifestEntry {
+            source: source.into(),
+            status: ImportStatus::Failed,
+            output: None,
+            extractor: None,
+            input_tokens: None,
+            output_tokens: None,
+            confidence: None,
+            error: Some(error.to_string()),
+        }
+    }
+}
+
+/// Every source a batch has tried, one entry each, in the order first tried
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub entries: Vec<ManifestEntry>,
+}
+
+impl Manifest {
+    /// Read a manifest; a missing file is an empty manifest
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ImportError> {
+        let path = path.as_ref();
+        match std::fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| {
+                ImportError::ParseError(format!("Invalid manifest {}: {}", path.display(), e))
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Manifest::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Write the manifest, replacing the file only once the new one is
+    /// complete so an interrupted batch never leaves it half-written
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ImportError> {
+        let path = path.as_ref();
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| ImportError::ParseError(e.to_string()))?;
+        let partial = path.with_extension("json.partial");
+        std::fs::write(&partial, json)?;
+        std::fs::rename(&partial, path)?;
+        Ok(())
+    }
+
+    /// The entry for `source`, if it was tried
+    pub fn get(&self, source: &str) -> Option<&ManifestEntry> {
+        self.entries.iter().find(|entry| entry.source == source)
+    }
+
+    /// Whether `source` was already imported, including below the minimum
+    /// quality: importing it again would score the same
+    pub fn is_imported(&self, source: &str) -> bool {
+        self.get(source).is_some_and(|entry| {
+            matches!(
+                entry.status,
+                ImportStatus::Imported | ImportStatus::LowQuality
+            )
+        })
+    }
+
+    /// Entries set aside below the minimum quality
+    pub fn low_quality(&self) -> impl Iterator<Item = &ManifestEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.status == ImportStatus::LowQuality)
+    }
+
+    /// Sources whose last attempt failed
+    pub fn failed(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.status == ImportStatus::Failed)
+            .map(|entry| entry.source.clone())
+            .collect()
+    }
+
+    /// Add an entry, replacing an earlier one for the same source
+    pub fn record(&mut self, entry: ManifestEntry) {
+        match self
+            .entries
+            .iter_mut()
+            .find(|existing| existing.source == entry.source)
+        {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RecipeComponents;
+
+    #[test]
+    fn test_record_replaces_earlier_attempt() {
+        let mut manifest = Manifest::default();
+        manifest.record(ManifestEntry::failed("https://example.com/soup", "timeout"));
+        manifest.record(ManifestEntry::failed(
+            "https://example.com/stew",
+            "HTTP 404",
+        ));
+        assert_eq!(
+            manifest.failed(),
+            vec!["https://example.com/soup", "https://example.com/stew"]
+        );
+
+        let result = ImportResult::Components(RecipeComponents::default());
+        manifest.record(ManifestEntry::imported(
+            "https://example.com/soup",
+            Some("Soup.cook".to_string()),
+            &result,
+            Some("json-ld".to_string()),
+        ));
+
+        assert_eq!(manifest.entries.len(), 2);
+        assert!(manifest.is_imported("https://example.com/soup"));
+        assert!(!manifest.is_imported("https://example.com/stew"));
+        assert_eq!(manifest.failed(), vec!["https://example.com/stew"]);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir().join(format!(
+            "cooklang-import-manifest-test-{}.json",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(Manifest::load(&path).unwrap(), Manifest::default());
+
+        let mut manifest = Manifest::default();
+        manifest.record(ManifestEntry::failed("https://example.com/soup", "timeout"));
+        manifest.save(&path).unwrap();
+
+        assert_eq!(Manifest::load(&path).unwrap(), manifest);
+        let json = std::fs::read_to_string(&path).unwrap();
+        assert!(json.contains(r#""status": "failed""#));
+        assert!(!json.contains("output"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_low_quality_counts_as_imported() {
+        let result = ImportResult::Cooklang {
+            content: ">> title: Soup\n\nStir.".to_string(),
+            title: "Soup".to_string(),
+            conversion_metadata: Some(crate::converters::ConversionMetadata {
+                confidence: Some(0.4),
+                ..Default::default()
+            }),
+        };
+        let mut manifest = Manifest::default();
+        manifest.record(ManifestEntry::low_quality(
+            "https://example.com/soup",
+            Some("review/Soup.cook".to_string()),
+            &result,
+            None,
+        ));
+
+        assert!(manifest.is_imported("https://example.com/soup"));
+        assert!(manifest.failed().is_empty());
+        let entry = manifest.low_quality().next().unwrap();
+        assert_eq!(entry.confidence, Some(0.4));
+        assert!(serde_json::to_string(entry)
+            .unwrap()
+            .contains(r#""status":"low_quality""#));
+    }
+}
@@ -0,0 +1,159 @@
+//! Converting a mirrored website (`--mirror DIR`)
+//!
+//! wget (`--mirror`) and HTTrack save a site as a directory tree of HTML
+//! files, which is often all that's left of an old recipe site.
+//! [`find_pages`] lists the saved pages, [`page_url`] gives a page's
+//! original URL from the URL of the mirror's top directory, and
+//! [`output_dir`] the directory its recipe goes to in a tree laid out like
+//! the mirror.
+
+use crate::pipelines::html::PAGE_EXTENSIONS;
+use reqwest::Url;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// Directories mirroring tools keep their own state in
+const TOOL_DIRS: &[&str] = &["hts-cache"];
+
+/// The saved pages (`.html`, `.htm`, `.mht`, ...) under `root`, relative to
+/// it and sorted
+///
+/// Hidden files and directories, HTTrack's `hts-cache` and symbolic links
+/// are skipped.
+pub fn find_pages(root: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut pages = Vec::new();
+    let mut dirs = vec![PathBuf::new()];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(root.join(&dir))? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name.starts_with('.') {
+                continue;
+            }
+            let kind = entry.file_type()?;
+            if kind.is_dir() && !TOOL_DIRS.contains(&name.as_ref()) {
+                dirs.push(dir.join(entry.file_name()));
+            } else if kind.is_file() && is_page(Path::new(name.as_ref())) {
+                pages.push(dir.join(entry.file_name()));
+            }
+        }
+    }
+    pages.sort();
+    Ok(pages)
+}
+
+fn is_page(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| PAGE_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
+
+/// The URL a page at `relative` in the mirror was saved from, when the
+/// mirror's top directory was saved from `base`
+///
+/// `index.html` stands for its directory, as web servers serve it.
+pub fn page_url(base: &str, relative: &Path) -> Option<String> {
+    let mut base = Url::parse(base).ok()?;
+    // Join below the base, not next to it
+    if !base.path().ends_with('/') {
+        let path = format!("{}/", base.path());
+        base.set_path(&path);
+    }
+    let mut segments = relative
+        .components()
+        .map(|component| match component {
+            Component::Normal(segment) => Some(segment.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    if segments
+        .last()
+        .is_some_and(|name| matches!(name.to_lowercase().as_str(), "index.html" | "index.htm"))
+    {
+        segments.pop();
+        if !segments.is_empty() {
+            segments.push("".into());
+        }
+    }
+    base.join(&segments.join("/")).ok().map(String::from)
+}
+
+/// The directory under `output` for the recipe of the page at `relative` in
+/// the mirror: the page's own directory, so the recipes keep the site's
+/// layout
+pub fn output_dir(output: &Path, relative: &Path) -> PathBuf {
+    match relative.parent() {
+        Some(parent) => output.join(parent),
+        None => output.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_pages() {
+        let root = std::env::temp_dir().join(format!("cooklang-mirror-{}", std::process::id()));
+        for dir in ["example.com/recipes/soup", "hts-cache", ".git"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        for file in [
+            "index.html",
+            "hts-log.txt",
+            "hts-cache/doit.log.html",
+            ".git/page.html",
+            "example.com/recipes/pancakes.HTM",
+            "example.com/recipes/soup/index.html",
+            "example.com/style.css",
+        ] {
+            std::fs::write(root.join(file), "").unwrap();
+        }
+
+        assert_eq!(
+            find_pages(&root).unwrap(),
+            [
+                Path::new("example.com/recipes/pancakes.HTM"),
+                Path::new("example.com/recipes/soup/index.html"),
+                Path::new("index.html"),
+            ]
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_page_url() {
+        let base = "https://example.com/blog";
+        assert_eq!(
+            page_url(base, Path::new("recipes/pancakes.html")).as_deref(),
+            Some("https://example.com/blog/recipes/pancakes.html")
+        );
+        assert_eq!(
+            page_url(base, Path::new("recipes/soup/index.html")).as_deref(),
+            Some("https://example.com/blog/recipes/soup/")
+        );
+        assert_eq!(
+            page_url("https://example.com/", Path::new("index.html")).as_deref(),
+            Some("https://example.com/")
+        );
+        assert_eq!(
+            page_url(base, Path::new("my recipes/stew.html")).as_deref(),
+            Some("https://example.com/blog/my%20recipes/stew.html")
+        );
+        assert_eq!(page_url(base, Path::new("../stew.html")), None);
+        assert_eq!(page_url("not a url", Path::new("stew.html")), None);
+    }
+
+    #[test]
+    fn test_output_dir() {
+        assert_eq!(
+            output_dir(Path::new("recipes"), Path::new("blog/2009/stew.html")),
+            Path::new("recipes/blog/2009")
+        );
+        assert_eq!(
+            output_dir(Path::new("recipes"), Path::new("stew.html")),
+            Path::new("recipes")
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use crate::pipelines::metadata_to_yaml;
+use crate::url_to_text::html::diagnostics::DroppedField;
 use serde::Serialize;
 use std::collections::HashMap;
 
@@ -9,7 +10,12 @@ pub struct Recipe {
     pub image: Vec<String>,
     pub ingredients: Vec<String>,
     pub instructions: String,
+    /// Cookware and tools the page lists (schema.org `tool`, WPRM
+    /// equipment), which the converter tags as `#cookware{}`
+    pub equipment: Vec<String>,
     pub metadata: HashMap<String, String>,
+    /// Fields the extractor saw but couldn't map
+    pub dropped_fields: Vec<DroppedField>,
 }
 
 impl Recipe {
@@ -55,30 +61,220 @@ impl Recipe {
         // Instructions
         output.push_str(&self.instructions);
 
+        // Equipment list
+        if !self.equipment.is_empty() {
+            output.push_str("\n\n");
+            output.push_str(&crate::pipelines::equipment::block(&self.equipment));
+        }
+
         output
     }
 
     /// Extract frontmatter and body from text format
     pub fn parse_text_format(text: &str) -> (HashMap<String, String>, String) {
-        let mut metadata = HashMap::new();
-        let body;
-
-        if let Some(stripped) = text.strip_prefix("---\n") {
-            if let Some(end) = stripped.find("\n---\n") {
-                let frontmatter = &stripped[..end];
-                for line in frontmatter.lines() {
-                    if let Some((key, value)) = line.split_once(": ") {
-                        metadata.insert(key.to_string(), value.to_string());
-                    }
-                }
-                body = stripped[end + 5..].to_string();
-            } else {
-                body = text.to_string();
-            }
-        } else {
-            body = text.to_string();
+        let (frontmatter, body) = split_frontmatter(text);
+        let metadata = frontmatter
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| line.split_once(": "))
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        (metadata, body.to_string())
+    }
+}
+
+/// Split a recipe or Markdown document into its leading YAML frontmatter
+/// (between `---` lines) and the rest.
+///
+/// Blank lines before the opening `---`, CRLF line endings and spaces after
+/// either fence are accepted. Without a closing `---` line there is no
+/// frontmatter, and the whole text is the body.
+pub fn split_frontmatter(text: &str) -> (Option<&str>, &str) {
+    let start = text.trim_start();
+    let Some(rest) = start
+        .split_once('\n')
+        .filter(|(fence, _)| fence.trim_end() == "---")
+        .map(|(_, rest)| rest)
+    else {
+        return (None, text);
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    (None, text)
+}
+
+/// The lines of a recipe with their line endings, each with whether it's
+/// part of the frontmatter [`split_frontmatter`] finds (fences and any
+/// blank lines before them included)
+pub(crate) fn recipe_lines(text: &str) -> impl Iterator<Item = (bool, &str)> {
+    let frontmatter_len = text.len() - split_frontmatter(text).1.len();
+    text.split_inclusive('\n').scan(0, move |offset, line| {
+        let start = *offset;
+        *offset += line.len();
+        Some((start < frontmatter_len, line))
+    })
+}
+
+/// Metadata keys shared by every extractor, spelled the way Cooklang
+/// frontmatter conventionally does (`prep time`, `time required`, `source`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MetadataKey {
+    Source,
+    Author,
+    Description,
+    Image,
+    Servings,
+    ServingSize,
+    Course,
+    Cuisine,
+    Diet,
+    Tags,
+    PrepTime,
+    CookTime,
+    TotalTime,
+    Notes,
+    Nutrition,
+    Rating,
+    Video,
+}
+
+impl MetadataKey {
+    /// Every key, in frontmatter order
+    pub const ALL: [MetadataKey; 17] = [
+        MetadataKey::Source,
+        MetadataKey::Author,
+        MetadataKey::Description,
+        MetadataKey::Image,
+        MetadataKey::Servings,
+        MetadataKey::ServingSize,
+        MetadataKey::Course,
+        MetadataKey::Cuisine,
+        MetadataKey::Diet,
+        MetadataKey::Tags,
+        MetadataKey::PrepTime,
+        MetadataKey::CookTime,
+        MetadataKey::TotalTime,
+        MetadataKey::Notes,
+        MetadataKey::Nutrition,
+        MetadataKey::Rating,
+        MetadataKey::Video,
+    ];
+
+    /// The frontmatter spelling
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MetadataKey::Source => "source",
+            MetadataKey::Author => "author",
+            MetadataKey::Description => "description",
+            MetadataKey::Image => "image",
+            MetadataKey::Servings => "servings",
+            MetadataKey::ServingSize => "serving size",
+            MetadataKey::Course => "course",
+            MetadataKey::Cuisine => "cuisine",
+            MetadataKey::Diet => "diet",
+            MetadataKey::Tags => "tags",
+            MetadataKey::PrepTime => "prep time",
+            MetadataKey::CookTime => "cook time",
+            MetadataKey::TotalTime => "time required",
+            MetadataKey::Notes => "notes",
+            MetadataKey::Nutrition => "nutrition",
+            MetadataKey::Rating => "rating",
+            MetadataKey::Video => "video",
         }
+    }
+
+    /// The key for any common spelling: the frontmatter one, `snake_case`
+    /// ("prep_time"), and aliases such as "source_url", "total time",
+    /// "yield" or "keywords". Case-insensitive.
+    pub fn parse(key: &str) -> Option<MetadataKey> {
+        let key = key.trim().to_lowercase().replace(['_', '-'], " ");
+        let alias = match key.as_str() {
+            "source url" | "url" => MetadataKey::Source,
+            "yield" => MetadataKey::Servings,
+            "category" => MetadataKey::Course,
+            "keywords" => MetadataKey::Tags,
+            "total time" => MetadataKey::TotalTime,
+            _ => return MetadataKey::ALL.into_iter().find(|k| k.as_str() == key),
+        };
+        Some(alias)
+    }
+
+    /// `key` in its frontmatter spelling, or unchanged if it isn't a known key
+    pub fn canonical(key: &str) -> String {
+        MetadataKey::parse(key).map_or_else(|| key.to_string(), |k| k.as_str().to_string())
+    }
+}
+
+impl std::fmt::Display for MetadataKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_key_spellings() {
+        assert_eq!(MetadataKey::parse("prep_time"), Some(MetadataKey::PrepTime));
+        assert_eq!(MetadataKey::parse("Prep Time"), Some(MetadataKey::PrepTime));
+        assert_eq!(
+            MetadataKey::parse("total_time"),
+            Some(MetadataKey::TotalTime)
+        );
+        assert_eq!(MetadataKey::parse("source_url"), Some(MetadataKey::Source));
+        assert_eq!(MetadataKey::parse("calories"), None);
+
+        assert_eq!(MetadataKey::canonical("serving_size"), "serving size");
+        assert_eq!(MetadataKey::canonical("difficulty"), "difficulty");
+        for key in MetadataKey::ALL {
+            assert_eq!(MetadataKey::parse(key.as_str()), Some(key));
+        }
+    }
+
+    #[test]
+    fn test_split_frontmatter() {
+        assert_eq!(
+            split_frontmatter("---\ntitle: Soup\n---\n\nSimmer."),
+            (Some("title: Soup\n"), "\nSimmer.")
+        );
+        assert_eq!(
+            split_frontmatter("\r\n---\r\ntitle: Soup\r\n--- \r\nSimmer."),
+            (Some("title: Soup\r\n"), "Simmer.")
+        );
+        assert_eq!(
+            split_frontmatter("---\ntitle: Soup\n---"),
+            (Some("title: Soup\n"), "")
+        );
+        // No closing fence, or a `---` that isn't the first line
+        let unclosed = "---\ntitle: Soup\n\nSimmer.";
+        assert_eq!(split_frontmatter(unclosed), (None, unclosed));
+        let ruled = "Simmer.\n---\nServe.";
+        assert_eq!(split_frontmatter(ruled), (None, ruled));
+        let dashes = "----\nSimmer.\n---\n";
+        assert_eq!(split_frontmatter(dashes), (None, dashes));
+    }
 
-        (metadata, body)
+    #[test]
+    fn test_recipe_lines() {
+        let lines: Vec<_> = recipe_lines("\r\n---\r\ntitle: Soup\r\n---\r\nSimmer.\r\n").collect();
+        assert_eq!(
+            lines,
+            vec![
+                (true, "\r\n"),
+                (true, "---\r\n"),
+                (true, "title: Soup\r\n"),
+                (true, "---\r\n"),
+                (false, "Simmer.\r\n"),
+            ]
+        );
+        // An unclosed fence is body like everything after it
+        assert!(recipe_lines("---\ntitle: Soup\nSimmer.").all(|(frontmatter, _)| !frontmatter));
     }
 }
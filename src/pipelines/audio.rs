@@ -0,0 +1,38 @@
+use super::{Llm, RecipeComponents, Timeouts};
+use crate::audio_to_text;
+use crate::url_to_text::text::TextExtractor;
+use std::error::Error;
+
+pub async fn process(
+    path: &str,
+    timeouts: &Timeouts,
+    proxy: Option<&str>,
+) -> Result<RecipeComponents, Box<dyn Error + Send + Sync>> {
+    let extractor = TextExtractor::from_config(timeouts.extraction, proxy)
+        .await
+        .map_err(|e| e.to_string());
+    process_with(path, timeouts, proxy, Llm::from_result(&extractor)).await
+}
+
+/// Transcribe the recording, then run LLM text extraction on the transcript
+/// if `llm` has a provider; otherwise the transcript is the recipe text.
+/// Dictated recipes run ingredients and steps together, so extraction is
+/// what turns them into a recipe.
+pub(crate) async fn process_with(
+    path: &str,
+    timeouts: &Timeouts,
+    proxy: Option<&str>,
+    llm: Llm<'_>,
+) -> Result<RecipeComponents, Box<dyn Error + Send + Sync>> {
+    let transcript = audio_to_text::transcribe(path, timeouts.transcription, proxy).await?;
+
+    if let Llm::Extract(extractor) = llm {
+        extractor.extract(&transcript, path).await
+    } else {
+        Ok(RecipeComponents {
+            text: transcript,
+            metadata: format!("source: {}", path),
+            name: String::new(),
+        })
+    }
+}
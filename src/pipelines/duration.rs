@@ -0,0 +1,215 @@
+use std::time::Duration;
+
+/// Metadata keys (lowercase, `_` as space) for each time, most specific first
+const PREP_KEYS: &[&str] = &["prep time", "prep"];
+const COOK_KEYS: &[&str] = &["cook time", "cook"];
+const TOTAL_KEYS: &[&str] = &["total time", "time required", "time", "duration"];
+
+/// Prep, cook and total times parsed from recipe metadata
+///
+/// Each time is `None` when the metadata doesn't have it or the value
+/// couldn't be read as a duration.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RecipeTimes {
+    /// Preparation time
+    pub prep: Option<Duration>,
+    /// Cooking time
+    pub cook: Option<Duration>,
+    /// Total time, as stated by the source (not prep + cook)
+    pub total: Option<Duration>,
+}
+
+impl RecipeTimes {
+    /// Times from a YAML metadata string as found in `RecipeComponents`
+    pub(crate) fn from_yaml(metadata: &str) -> Self {
+        let mapping: serde_yaml::Mapping = serde_yaml::from_str(metadata).unwrap_or_default();
+        let entries: Vec<(String, String)> = mapping
+            .into_iter()
+            .filter_map(|(key, value)| {
+                let key = key.as_str()?.to_lowercase().replace('_', " ");
+                let value = match value {
+                    serde_yaml::Value::String(value) => value,
+                    _ => return None,
+                };
+                Some((key, value))
+            })
+            .collect();
+
+        let find = |keys: &[&str]| {
+            keys.iter().find_map(|key| {
+                entries
+                    .iter()
+                    .find(|(name, _)| name == key)
+                    .and_then(|(_, value)| parse_duration(value))
+            })
+        };
+        RecipeTimes {
+            prep: find(PREP_KEYS),
+            cook: find(COOK_KEYS),
+            total: find(TOTAL_KEYS),
+        }
+    }
+}
+
+/// Parse a recipe time such as "1 hour 30 minutes", "1h30m", "90 mins" or
+/// an ISO 8601 duration ("PT1H30M").
+///
+/// Ranges ("15-20 minutes", "15 to 20 minutes") resolve to their upper bound.
+/// Returns `None` if no number with a time unit is found.
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    let seconds = match text.strip_prefix(['P', 'p']) {
+        Some(iso)
+            if iso
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || ".,-".contains(c)) =>
+        {
+            parse_iso(iso)?
+        }
+        _ => parse_words(text)?,
+    };
+    Some(Duration::from_secs(seconds.round() as u64))
+}
+
+/// Seconds in an ISO 8601 duration, without the leading `P`
+fn parse_iso(iso: &str) -> Option<f64> {
+    let mut total = 0.0;
+    let mut found = false;
+    let mut in_time = false;
+    let mut number = String::new();
+    for c in iso.chars() {
+        let unit = match c.to_ascii_uppercase() {
+            'T' => {
+                in_time = true;
+                continue;
+            }
+            'W' => 604_800.0,
+            'D' => 86_400.0,
+            'H' if in_time => 3_600.0,
+            'M' if in_time => 60.0,
+            'S' if in_time => 1.0,
+            // Years and months have no fixed length
+            c if c.is_ascii_alphabetic() => return None,
+            _ => {
+                number.push(c);
+                continue;
+            }
+        };
+        total += parse_number(&number)? * unit;
+        found = true;
+        number.clear();
+    }
+    found.then_some(total)
+}
+
+/// Seconds in a "1 hour 30 minutes" style duration
+fn parse_words(text: &str) -> Option<f64> {
+    let text = text.to_lowercase();
+    let mut total = 0.0;
+    let mut found = false;
+    // Numbers seen since the last unit; "1 1/2 hours" adds up to 1.5
+    let mut pending: Option<f64> = None;
+
+    for token in tokens(&text) {
+        if token.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+            pending = Some(pending.unwrap_or(0.0) + parse_number(token)?);
+            continue;
+        }
+        let unit = match token {
+            "d" | "day" | "days" => 86_400.0,
+            "h" | "hr" | "hrs" | "hour" | "hours" => 3_600.0,
+            "m" | "min" | "mins" | "minute" | "minutes" => 60.0,
+            "s" | "sec" | "secs" | "second" | "seconds" => 1.0,
+            // "1 hour and 30 minutes"
+            "and" => continue,
+            // Anything else (e.g. "to" in a range) drops the pending number
+            _ => {
+                pending = None;
+                continue;
+            }
+        };
+        if let Some(amount) = pending.take() {
+            total += amount * unit;
+            found = true;
+        }
+    }
+    found.then_some(total)
+}
+
+/// Runs of number characters and runs of letters; everything else separates
+fn tokens(text: &str) -> impl Iterator<Item = &str> {
+    let is_number = |c: char| c.is_ascii_digit() || "./,-".contains(c);
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        rest = rest.trim_start_matches(|c: char| !is_number(c) && !c.is_alphabetic());
+        let first = rest.chars().next()?;
+        let end = if is_number(first) {
+            rest.find(|c: char| !is_number(c))
+        } else {
+            rest.find(|c: char| !c.is_alphabetic())
+        }
+        .unwrap_or(rest.len());
+        let (token, tail) = rest.split_at(end);
+        rest = tail;
+        Some(token)
+    })
+}
+
+/// A decimal ("1.5", "1,5"), fraction ("1/2") or range ("15-20", upper bound)
+fn parse_number(number: &str) -> Option<f64> {
+    let number = number.rsplit('-').next()?.replace(',', ".");
+    match number.split_once('/') {
+        Some((numerator, denominator)) => {
+            let denominator: f64 = denominator.parse().ok()?;
+            (denominator != 0.0).then_some(numerator.parse::<f64>().ok()? / denominator)
+        }
+        None => number.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minutes(n: u64) -> Option<Duration> {
+        Some(Duration::from_secs(n * 60))
+    }
+
+    #[test]
+    fn test_parse_iso_durations() {
+        assert_eq!(parse_duration("PT30M"), minutes(30));
+        assert_eq!(parse_duration("PT1H30M"), minutes(90));
+        assert_eq!(parse_duration("PT5400.0S"), minutes(90));
+        assert_eq!(parse_duration("P0DT2H"), minutes(120));
+        assert_eq!(parse_duration("PT15-20M"), minutes(20));
+        // A month has no fixed length
+        assert_eq!(parse_duration("P1M"), None);
+    }
+
+    #[test]
+    fn test_parse_human_durations() {
+        assert_eq!(parse_duration("1 hour 30 minutes"), minutes(90));
+        assert_eq!(parse_duration("1 hour and 30 minutes"), minutes(90));
+        assert_eq!(parse_duration("1h30m"), minutes(90));
+        assert_eq!(parse_duration("90 mins"), minutes(90));
+        assert_eq!(parse_duration("1 1/2 hours"), minutes(90));
+        assert_eq!(parse_duration("1,5 h"), minutes(90));
+        assert_eq!(parse_duration("15-20 minutes"), minutes(20));
+        assert_eq!(parse_duration("15 to 20 minutes"), minutes(20));
+        assert_eq!(parse_duration("2 days"), minutes(2 * 24 * 60));
+        assert_eq!(parse_duration("45"), None);
+        assert_eq!(parse_duration("overnight"), None);
+    }
+
+    #[test]
+    fn test_times_from_metadata() {
+        let times = RecipeTimes::from_yaml(
+            "prep time: 15 minutes\ncook_time: 1 hour\ntime required: 1 hour 15 minutes\nservings: 4",
+        );
+        assert_eq!(times.prep, minutes(15));
+        assert_eq!(times.cook, minutes(60));
+        assert_eq!(times.total, minutes(75));
+
+        assert_eq!(RecipeTimes::from_yaml(""), RecipeTimes::default());
+    }
+}
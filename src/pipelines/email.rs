@@ -0,0 +1,49 @@
+use super::url::{extract_from_html, Extraction};
+use super::{Llm, RecipeComponents};
+use crate::email::Email;
+use std::error::Error;
+
+/// Extract a recipe from a raw `.eml` message
+///
+/// The HTML body goes through the same extractors as a web page (structured
+/// data, then LLM text extraction); a text-only email goes straight to text
+/// extraction. The sender becomes the `source`, and the subject (without
+/// `Fwd:`) the title when nothing better was found. With [`Llm::Off`] the
+/// body's plain text is returned when no structured extractor matches.
+pub(crate) async fn extract(
+    eml: &[u8],
+    llm: Llm<'_>,
+) -> Result<Extraction, Box<dyn Error + Send + Sync>> {
+    let email = Email::parse(eml)?;
+    let source = email.from.clone().unwrap_or_else(|| "email".to_string());
+
+    let mut extraction = match (&email.html, &email.text) {
+        (Some(html), _) => extract_from_html(html, &source, llm).await?,
+        (None, Some(text)) => {
+            let components = match llm {
+                Llm::Extract(extractor) => extractor.extract(text, &source).await?,
+                Llm::Unavailable(reason) => {
+                    return Err(format!("LLM text extraction is unavailable: {}", reason).into())
+                }
+                Llm::Off => RecipeComponents {
+                    text: text.clone(),
+                    ..RecipeComponents::default()
+                },
+            };
+            Extraction {
+                components,
+                extractor: None,
+                dropped_fields: Vec::new(),
+                url: None,
+            }
+        }
+        (None, None) => unreachable!("Email::parse requires a body"),
+    };
+
+    if extraction.components.name.is_empty() {
+        if let Some(title) = email.title() {
+            extraction.components.name = super::sanitize_name(&title);
+        }
+    }
+    Ok(extraction)
+}
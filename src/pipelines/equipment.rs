@@ -0,0 +1,162 @@
+//! Equipment a recipe lists (schema.org `tool`, WPRM equipment), kept as
+//! Cooklang cookware
+//!
+//! Extractors put the list at the end of the recipe text under an
+//! `Equipment:` heading. The converter is asked to tag each item with `#`
+//! where it is first used; [`ensure_cookware`] then tags the first mention
+//! of any item the model left as plain text.
+
+/// Heading of the equipment list in recipe text
+pub(crate) const HEADING: &str = "Equipment:";
+
+/// The equipment list as it ends recipe text, or "" when there is none
+pub(crate) fn block(equipment: &[String]) -> String {
+    if equipment.is_empty() {
+        return String::new();
+    }
+    let items: Vec<String> = equipment.iter().map(|item| format!("- {}", item)).collect();
+    format!("{}\n{}", HEADING, items.join("\n"))
+}
+
+/// Names in page order, trimmed, without blanks or repeats (ignoring case)
+pub(crate) fn dedup(names: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut equipment: Vec<String> = Vec::new();
+    for name in names {
+        let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !name.is_empty()
+            && !equipment
+                .iter()
+                .any(|item| item.eq_ignore_ascii_case(&name))
+        {
+            equipment.push(name);
+        }
+    }
+    equipment
+}
+
+/// The items of the equipment list in recipe text: the lines of a block
+/// headed `Equipment:` (or `## Equipment`), without their bullets
+pub(crate) fn listed(text: &str) -> Vec<String> {
+    let mut lines = text.lines().map(str::trim);
+    let is_heading = |line: &str| {
+        let name = line.trim_start_matches('#').trim().trim_end_matches(':');
+        name.eq_ignore_ascii_case("equipment")
+    };
+    if !lines.any(is_heading) {
+        return Vec::new();
+    }
+    dedup(
+        lines
+            .take_while(|line| !line.is_empty())
+            .map(|line| line.trim_start_matches(['-', '*', '•', ' ']).to_string()),
+    )
+}
+
+/// Tag the first plain mention of each `equipment` item the model didn't
+/// already mark as cookware, e.g. "Heat the skillet" → "Heat the
+/// #skillet{}". Items never mentioned in a step are left out.
+pub(crate) fn ensure_cookware(cooklang: &str, equipment: &[String]) -> String {
+    let mut cooklang = cooklang.to_string();
+    for item in equipment {
+        let item = item.to_ascii_lowercase();
+        let tagged = cookware_names(&cooklang)
+            .iter()
+            .any(|name| name.contains(&item) || item.contains(name.as_str()));
+        if tagged {
+            continue;
+        }
+        match plain_mention(&cooklang, &item) {
+            Some(start) => {
+                let end = start + item.len();
+                cooklang = format!(
+                    "{}#{}{{}}{}",
+                    &cooklang[..start],
+                    &cooklang[start..end],
+                    &cooklang[end..]
+                );
+            }
+            None => tracing::debug!("Equipment {:?} isn't mentioned in any step", item),
+        }
+    }
+    cooklang
+}
+
+/// Names of the `#cookware{}` tags in `cooklang`, lowercased
+fn cookware_names(cooklang: &str) -> Vec<String> {
+    cooklang
+        .lines()
+        .flat_map(|line| line.split('#').skip(1))
+        .filter_map(|tag| {
+            let (name, _) = tag.split_once('{')?;
+            (!name.is_empty() && !name.contains(['@', '~', '}']))
+                .then(|| name.trim().to_ascii_lowercase())
+        })
+        .collect()
+}
+
+/// Byte offset of the first whole-word mention of `item` (lowercase) in a
+/// step, outside other tags, amounts and preparations
+fn plain_mention(cooklang: &str, item: &str) -> Option<usize> {
+    let lower = cooklang.to_ascii_lowercase();
+    let mut line_start = 0;
+    for line in lower.split_inclusive('\n') {
+        let is_step =
+            !line.trim().is_empty() && !line.trim_start().starts_with(['>', '-', '=', '[']);
+        let mut from = 0;
+        while let Some(found) = line[from..].find(item).filter(|_| is_step) {
+            let start = from + found;
+            let end = start + item.len();
+            from = end;
+
+            let before = &line[..start];
+            let word_start = !before.ends_with(|c: char| c.is_alphanumeric());
+            let word_end = !line[end..].starts_with(|c: char| c.is_alphanumeric());
+            let nested = |open, close| before.matches(open).count() > before.matches(close).count();
+            let in_tag = before
+                .rfind(['@', '#', '~'])
+                .is_some_and(|marker| !before[marker..].contains('{'));
+            if word_start && word_end && !nested('{', '}') && !nested('(', ')') && !in_tag {
+                return Some(line_start + start);
+            }
+        }
+        line_start += line.len();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    #[test]
+    fn test_block_round_trips() {
+        let equipment = dedup(names(&["Whisk", " 9x13-inch  baking dish", "whisk", ""]));
+        assert_eq!(equipment, names(&["Whisk", "9x13-inch baking dish"]));
+        let text = format!("2 eggs\n\nWhisk the eggs.\n\n{}", block(&equipment));
+        assert_eq!(listed(&text), equipment);
+        assert!(listed("2 eggs\n\nWhisk the eggs.").is_empty());
+        assert_eq!(block(&[]), "");
+    }
+
+    #[test]
+    fn test_ensure_cookware_tags_first_plain_mention() {
+        let cooklang = "Crack @eggs{2} into a bowl and beat with a whisk.\n\nHeat the Skillet and cook ~{3%minutes}.";
+        assert_eq!(
+            ensure_cookware(cooklang, &names(&["whisk", "skillet"])),
+            "Crack @eggs{2} into a bowl and beat with a #whisk{}.\n\nHeat the #Skillet{} and cook ~{3%minutes}."
+        );
+    }
+
+    #[test]
+    fn test_ensure_cookware_keeps_tagged_and_skips_markup() {
+        let cooklang = "Heat the #large skillet{}.\n\n> A pan works too.\n\nAdd @pancetta{100%g}(cut for the pan) and fry.";
+        assert_eq!(
+            ensure_cookware(cooklang, &names(&["skillet", "pan", "oven"])),
+            cooklang
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! Web pages saved from a browser: HTML files ("Save Page As") and MHTML
+//! archives (`.mht`, `.mhtml`, "Save as single file").
+//!
+//! The page goes through the same extractors as a fetched one, but nothing
+//! is fetched, so it works offline. The URL the page came from is recorded
+//! as the `source` when it's given, or when the page says so: an MHTML
+//! archive's snapshot location, the `saved from url` comment browsers
+//! write, or an absolute canonical link.
+
+use super::url::{extract_all_from_html, extract_from_html, Extraction};
+use super::Llm;
+use crate::email::Email;
+use crate::error::ImportError;
+use crate::url_to_text::html::prescan;
+use crate::url_to_text::normalize;
+use scraper::Html;
+use std::error::Error;
+use std::path::Path;
+
+/// File extensions of saved pages: HTML, then MHTML archives
+pub const PAGE_EXTENSIONS: &[&str] = &["html", "htm", "xhtml", "mht", "mhtml"];
+const MHTML_EXTENSIONS: &[&str] = &["mht", "mhtml"];
+
+/// A saved page's HTML and the URL it was saved from, when known
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedPage {
+    pub html: String,
+    pub url: Option<String>,
+}
+
+impl SavedPage {
+    /// A page saved as HTML
+    pub fn from_html(html: impl Into<String>) -> Self {
+        let html = html.into();
+        let url = saved_from(&html).or_else(|| canonical_link(&html));
+        SavedPage { html, url }
+    }
+
+    /// Read a saved page from a file: an MHTML archive when the extension
+    /// says so (`.mht`, `.mhtml`), else HTML in the charset its
+    /// `<meta charset>` names (sniffed when it names none)
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, ImportError> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)?;
+        let archive = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| MHTML_EXTENSIONS.contains(&extension.to_lowercase().as_str()));
+        if archive {
+            SavedPage::from_mhtml(&bytes)
+        } else {
+            Ok(SavedPage::from_html(crate::charset::decode_page(
+                &bytes, None,
+            )))
+        }
+    }
+
+    /// A page saved as an MHTML archive: the first HTML part, with the
+    /// archive's snapshot location as the URL
+    pub fn from_mhtml(raw: &[u8]) -> Result<Self, ImportError> {
+        let archive = Email::parse(raw)
+            .map_err(|e| ImportError::ParseError(format!("Not an MHTML archive: {}", e)))?;
+        let html = archive.html.ok_or_else(|| {
+            ImportError::ParseError("The MHTML archive has no HTML page".to_string())
+        })?;
+        // Parts can also be `cid:` or `file:` locations
+        let location = archive
+            .location
+            .filter(|location| normalize::validate_url(location).is_ok());
+        let page = SavedPage::from_html(html);
+        Ok(SavedPage {
+            url: location.or(page.url),
+            ..page
+        })
+    }
+}
+
+/// Extract the recipes on a saved page: every recipe of a multi-recipe page
+/// with `multi`, else one
+///
+/// `url` (the page's original URL) takes precedence over the one the page
+/// records. With [`Llm::Off`] the page's plain text is returned when no
+/// structured extractor matches.
+pub(crate) async fn extract(
+    page: &SavedPage,
+    url: Option<&str>,
+    multi: bool,
+    llm: Llm<'_>,
+) -> Result<Vec<Extraction>, Box<dyn Error + Send + Sync>> {
+    let url = url.or(page.url.as_deref());
+    // Extractors record the page URL as the source; an unknown one is left
+    // out afterwards
+    let page_url = url.unwrap_or_default();
+    let mut extractions = if multi {
+        extract_all_from_html(&page.html, page_url, llm).await?
+    } else {
+        vec![extract_from_html(&page.html, page_url, llm).await?]
+    };
+    for extraction in &mut extractions {
+        extraction.url = url.map(str::to_string);
+        if url.is_none() {
+            extraction.components.metadata = without_empty_values(&extraction.components.metadata);
+        }
+    }
+    Ok(extractions)
+}
+
+/// The URL in the `<!-- saved from url=(0042)https://... -->` comment
+/// browsers put at the top of saved pages
+fn saved_from(html: &str) -> Option<String> {
+    let head = prescan::head(html);
+    let start = head.find("<!-- saved from url=(")?;
+    let rest = &head[start..];
+    let url = rest[rest.find(')')? + 1..]
+        .split_whitespace()
+        .next()?
+        .trim_end_matches("-->");
+    normalize::validate_url(url).ok()?;
+    Some(url.to_string())
+}
+
+/// The page's canonical link, when it is absolute: there's no page URL to
+/// resolve a relative one against
+fn canonical_link(html: &str) -> Option<String> {
+    let document = Html::parse_document(prescan::head(html));
+    // Joining an absolute URL ignores the base
+    normalize::canonical_url(&document, "about:blank")
+}
+
+/// Metadata without the keys whose value is empty
+fn without_empty_values(metadata: &str) -> String {
+    let Ok(mut mapping) = serde_yaml::from_str::<serde_yaml::Mapping>(metadata) else {
+        return metadata.to_string();
+    };
+    mapping.retain(|_, value| value.as_str() != Some(""));
+    if mapping.is_empty() {
+        return String::new();
+    }
+    serde_yaml::to_string(&mapping).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = r#"<!DOCTYPE html>
+<!-- saved from url=(0036)https://example.com/recipes/pancakes -->
+<html><head><title>Pancakes</title>
+<script type="application/ld+json">
+{"@context": "https://schema.org", "@type": "Recipe", "name": "Pancakes",
+ "recipeIngredient": ["2 eggs", "200 g flour"],
+ "recipeInstructions": [{"@type": "HowToStep", "text": "Whisk and fry."}]}
+</script>
+</head><body><h1>Pancakes</h1></body></html>"#;
+
+    #[test]
+    fn test_saved_page_url() {
+        assert_eq!(
+            SavedPage::from_html(PAGE).url.as_deref(),
+            Some("https://example.com/recipes/pancakes")
+        );
+
+        let canonical =
+            r#"<html><head><link rel="canonical" href="https://example.com/soup"></head></html>"#;
+        assert_eq!(
+            SavedPage::from_html(canonical).url.as_deref(),
+            Some("https://example.com/soup")
+        );
+
+        let relative = r#"<html><head><link rel="canonical" href="/soup"></head></html>"#;
+        assert_eq!(SavedPage::from_html(relative).url, None);
+    }
+
+    #[test]
+    fn test_mhtml_page() {
+        let mhtml = "From: <Saved by Blink>\nSnapshot-Content-Location: https://example.com/stew\nSubject: Stew\nContent-Type: multipart/related; type=\"text/html\"; boundary=\"b\"\n\n--b\nContent-Type: text/html\nContent-Location: https://example.com/stew\n\n<h1>Stew</h1>\n--b--\n";
+        let page = SavedPage::from_mhtml(mhtml.as_bytes()).unwrap();
+        assert_eq!(page.html, "<h1>Stew</h1>");
+        assert_eq!(page.url.as_deref(), Some("https://example.com/stew"));
+
+        assert!(SavedPage::from_mhtml(PAGE.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_read_saved_page() {
+        let dir = std::env::temp_dir().join(format!("cooklang-saved-page-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // An old page in Windows-1252
+        let html = dir.join("Creme.HTM");
+        std::fs::write(
+            &html,
+            b"<html><head><meta charset=\"windows-1252\"></head><body>Cr\xe8me</body></html>",
+        )
+        .unwrap();
+        assert!(SavedPage::read(&html).unwrap().html.contains("Crème"));
+
+        let mhtml = dir.join("Creme.mhtml");
+        std::fs::write(
+            &mhtml,
+            "Content-Type: text/html\nContent-Transfer-Encoding: quoted-printable\n\n<h1>Cr=C3=A8me</h1>",
+        )
+        .unwrap();
+        assert_eq!(SavedPage::read(&mhtml).unwrap().html, "<h1>Crème</h1>");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_extract_saved_page() {
+        let page = SavedPage::from_html(PAGE);
+        let extraction = extract(&page, None, false, Llm::Off)
+            .await
+            .unwrap()
+            .remove(0);
+        assert_eq!(extraction.extractor, Some("json-ld"));
+        assert_eq!(extraction.components.name, "Pancakes");
+        assert!(extraction
+            .components
+            .metadata
+            .contains("source: https://example.com/recipes/pancakes"));
+
+        // The given URL wins; without any, no empty source is recorded
+        let bare = SavedPage {
+            url: None,
+            ..page.clone()
+        };
+        let extraction = extract(&bare, Some("https://example.org/p"), false, Llm::Off)
+            .await
+            .unwrap()
+            .remove(0);
+        assert!(extraction
+            .components
+            .metadata
+            .contains("source: https://example.org/p"));
+        let extraction = extract(&bare, None, false, Llm::Off)
+            .await
+            .unwrap()
+            .remove(0);
+        assert!(!extraction.components.metadata.contains("source"));
+    }
+}
@@ -1,30 +1,52 @@
-use super::RecipeComponents;
+use super::{Llm, RecipeComponents, Timeouts};
 use crate::images_to_text::{self, ImageSource};
 use crate::url_to_text::text::TextExtractor;
+use crate::ImportError;
 use std::error::Error;
 
+/// Comma-separated description of the images, used as the recipe source
+pub(crate) fn source_label(images: &[ImageSource]) -> String {
+    images
+        .iter()
+        .map(|image| match image {
+            ImageSource::Path(p) => p.clone(),
+            ImageSource::Base64(_) => "base64-image".to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 pub async fn process(
     images: &[ImageSource],
+    timeouts: &Timeouts,
+    proxy: Option<&str>,
 ) -> Result<RecipeComponents, Box<dyn Error + Send + Sync>> {
-    let mut all_text = Vec::new();
-    let mut sources = Vec::new();
-
-    for image in images {
-        let text = images_to_text::extract(image).await?;
-        all_text.push(text);
+    let extractor = TextExtractor::from_config(timeouts.extraction, proxy)
+        .await
+        .map_err(|e| e.to_string());
+    process_with(images, timeouts, proxy, Llm::from_result(&extractor)).await
+}
 
-        match image {
-            ImageSource::Path(p) => sources.push(p.clone()),
-            ImageSource::Base64(_) => sources.push("base64-image".to_string()),
-        }
+/// OCR the images, then run LLM text extraction on the text if `llm` has a
+/// provider; otherwise the raw OCR text is the recipe text. The images are
+/// read in the order given, as pages of one recipe (see
+/// [`images_to_text::join_pages`]); pages without text are skipped.
+pub(crate) async fn process_with(
+    images: &[ImageSource],
+    timeouts: &Timeouts,
+    proxy: Option<&str>,
+    llm: Llm<'_>,
+) -> Result<RecipeComponents, Box<dyn Error + Send + Sync>> {
+    let pages = images_to_text::extract_all(images, timeouts.ocr, proxy).await?;
+    if pages.iter().all(|page| page.trim().is_empty()) {
+        return Err(ImportError::OcrError("No text detected in images".to_string()).into());
     }
+    let combined = images_to_text::join_pages(&pages);
+    let source = source_label(images);
 
-    let combined = all_text.join("\n\n");
-    let source = sources.join(", ");
-
-    // Try structured extraction if API key available
-    if TextExtractor::is_available() {
-        TextExtractor::extract(&combined, &source).await
+    // Try structured extraction if a provider is available
+    if let Llm::Extract(extractor) = llm {
+        extractor.extract(&combined, &source).await
     } else {
         // Fallback: return raw OCR text
         Ok(RecipeComponents {
@@ -0,0 +1,942 @@
+//! Recipes written in Markdown: a `# Title`, an "Ingredients" heading over a
+//! list, and an "Instructions" (or "Method", "Directions", ...) heading over
+//! numbered steps or paragraphs.
+//!
+//! Parsing is deterministic. [`process`] returns the recipe as components
+//! for the usual conversion; [`to_cooklang`] renders it to Cooklang without
+//! an LLM, marking each ingredient at its first mention in the steps.
+//! [`text_to_cooklang`] does the same for recipe text in the components
+//! layout, for offline imports without a local model.
+
+use super::{normalize_quantities, RecipeComponents};
+use crate::converters::{self, StructuredIngredient, StructuredRecipe, StructuredSection};
+use crate::error::ImportError;
+use crate::model::{split_frontmatter, MetadataKey, Recipe};
+use std::collections::HashSet;
+
+/// Headings (lowercase, without a trailing colon) that start each part
+const INGREDIENT_HEADINGS: &[&str] = &[
+    "ingredients",
+    "ingredient list",
+    "you will need",
+    "you'll need",
+    "what you'll need",
+    "zutaten",
+    "ingrédients",
+    "ingredientes",
+    "ingredienti",
+];
+const INSTRUCTION_HEADINGS: &[&str] = &[
+    "instructions",
+    "method",
+    "directions",
+    "steps",
+    "preparation",
+    "procedure",
+    "how to make it",
+    "zubereitung",
+    "préparation",
+    "instrucciones",
+    "preparación",
+    "preparazione",
+];
+const NOTE_HEADINGS: &[&str] = &[
+    "notes",
+    "note",
+    "tips",
+    "tips and tricks",
+    "tips & tricks",
+    "cook's notes",
+];
+
+/// Units that aren't in the conversion table, since they never convert
+const COUNT_UNITS: &[&str] = &[
+    "tsp",
+    "tsps",
+    "teaspoon",
+    "teaspoons",
+    "tbsp",
+    "tbsps",
+    "tbs",
+    "tablespoon",
+    "tablespoons",
+    "clove",
+    "cloves",
+    "pinch",
+    "pinches",
+    "dash",
+    "dashes",
+    "can",
+    "cans",
+    "tin",
+    "tins",
+    "jar",
+    "jars",
+    "slice",
+    "slices",
+    "bunch",
+    "bunches",
+    "handful",
+    "handfuls",
+    "sprig",
+    "sprigs",
+    "stalk",
+    "stalks",
+    "stick",
+    "sticks",
+    "piece",
+    "pieces",
+    "package",
+    "packages",
+    "packet",
+    "packets",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Part {
+    /// Before the first part heading: title, description and metadata
+    Preamble,
+    Ingredients,
+    Instructions,
+    Notes,
+    /// Under any other heading
+    Other,
+}
+
+/// Ingredients or steps under one (sub)heading; `name` is `None` for the
+/// recipe's main list
+#[derive(Debug, Default)]
+struct Block {
+    name: Option<String>,
+    lines: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+struct MarkdownRecipe {
+    /// Name, description, image and metadata
+    recipe: Recipe,
+    ingredients: Vec<Block>,
+    steps: Vec<Block>,
+}
+
+enum Line {
+    Blank,
+    Heading(usize, String),
+    Item(String),
+    Text { text: String, indented: bool },
+}
+
+/// Parse a Markdown recipe into components for conversion.
+///
+/// Fails with [`ImportError::InvalidMarkdown`] when there is no ingredient
+/// list or no steps under the expected headings.
+pub fn process(markdown: &str) -> Result<RecipeComponents, ImportError> {
+    parse(markdown).map(|recipe| recipe.components())
+}
+
+/// Convert a Markdown recipe to Cooklang (without frontmatter) without an
+/// LLM.
+///
+/// Ingredient lines are split into quantity, unit, name and preparation
+/// ("200 g flour, sifted"), and each ingredient is marked up where its name
+/// first appears in the steps. Ingredients the steps never name are listed
+/// before the first step.
+pub fn to_cooklang(markdown: &str) -> Result<String, ImportError> {
+    parse(markdown).map(|recipe| recipe.to_cooklang())
+}
+
+/// Convert recipe text laid out as [`RecipeComponents::text`] (ingredient
+/// lines, a blank line, then the steps) to Cooklang without an LLM, the way
+/// [`to_cooklang`] converts Markdown.
+///
+/// Each paragraph is a step, as is each numbered or bulleted line. A short
+/// line ending in a colon ("Sauce:") starts a group of ingredients or a
+/// section of steps.
+pub fn text_to_cooklang(text: &str) -> String {
+    let text = text.replace("\r\n", "\n");
+    let mut paragraphs = text
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|paragraph| !paragraph.is_empty());
+
+    let mut recipe = MarkdownRecipe::default();
+    for line in paragraphs.next().unwrap_or_default().lines() {
+        let line = line.trim();
+        let line = list_item(line).unwrap_or(line);
+        match group_label(line) {
+            Some(name) => recipe.ingredients.push(Block {
+                name: Some(name),
+                lines: Vec::new(),
+            }),
+            None if !line.is_empty() => push_line(&mut recipe.ingredients, line.to_string()),
+            None => {}
+        }
+    }
+    for paragraph in paragraphs {
+        if let Some(name) = group_label(paragraph).filter(|_| !paragraph.contains('\n')) {
+            recipe.steps.push(Block {
+                name: Some(name),
+                lines: Vec::new(),
+            });
+            continue;
+        }
+        let mut continues = false;
+        for line in paragraph.lines().map(str::trim) {
+            match list_item(line) {
+                Some(item) => push_line(&mut recipe.steps, item.to_string()),
+                None if continues => append(&mut recipe.steps, line),
+                None => push_line(&mut recipe.steps, line.to_string()),
+            }
+            continues = true;
+        }
+    }
+    recipe.to_cooklang()
+}
+
+fn parse(markdown: &str) -> Result<MarkdownRecipe, ImportError> {
+    let markdown = markdown.replace("\r\n", "\n");
+    let (frontmatter, body) = split_frontmatter(&markdown);
+    let mut parsed = MarkdownRecipe::default();
+    if let Some(yaml) = frontmatter {
+        apply_frontmatter(&mut parsed.recipe, yaml)?;
+    }
+
+    let mut part = Part::Preamble;
+    let mut part_level = 0;
+    let mut heading_level = 0;
+    // Set by a heading such as "## Sauce" that has its own ingredients and
+    // steps underneath
+    let mut component: Option<String> = None;
+    let mut description: Vec<String> = Vec::new();
+    let mut notes: Vec<String> = Vec::new();
+    // Whether the previous line belongs to the paragraph or item being read
+    let mut continues = false;
+
+    for raw in body.lines() {
+        if parsed.recipe.image.is_empty() {
+            parsed.recipe.image.extend(image_url(raw));
+        }
+        match classify(raw, heading_level) {
+            Line::Blank => {
+                continues = false;
+                continue;
+            }
+            Line::Heading(level, title) => {
+                if raw.trim_start().starts_with('#') {
+                    heading_level = level;
+                }
+                match part_for(&title) {
+                    Some(kind) => {
+                        part = kind;
+                        part_level = level;
+                        let block = Block {
+                            name: component.clone(),
+                            lines: Vec::new(),
+                        };
+                        match kind {
+                            Part::Ingredients => parsed.ingredients.push(block),
+                            Part::Instructions => parsed.steps.push(block),
+                            _ => {}
+                        }
+                    }
+                    None if part == Part::Preamble && parsed.recipe.name.is_empty() => {
+                        parsed.recipe.name = title;
+                    }
+                    // The title again, after one from the frontmatter
+                    None if part == Part::Preamble && level == 1 => {}
+                    None if part == Part::Ingredients && level > part_level => {
+                        parsed.ingredients.push(Block {
+                            name: Some(title),
+                            lines: Vec::new(),
+                        });
+                    }
+                    None if part == Part::Instructions && level > part_level => {
+                        parsed.steps.push(Block {
+                            name: Some(title),
+                            lines: Vec::new(),
+                        });
+                    }
+                    None => {
+                        component = Some(title);
+                        part = Part::Other;
+                    }
+                }
+                continues = false;
+                continue;
+            }
+            Line::Item(text) => match part {
+                Part::Ingredients => push_line(&mut parsed.ingredients, text),
+                Part::Instructions => push_line(&mut parsed.steps, text),
+                Part::Notes => notes.push(text),
+                Part::Preamble | Part::Other => match metadata_line(&text) {
+                    Some((key, value)) => {
+                        parsed.recipe.metadata.insert(key, value);
+                    }
+                    None if part == Part::Preamble => description.push(text),
+                    None => notes.push(text),
+                },
+            },
+            Line::Text { text, indented } => match part {
+                Part::Ingredients => match group_label(&text) {
+                    Some(name) => parsed.ingredients.push(Block {
+                        name: Some(name),
+                        lines: Vec::new(),
+                    }),
+                    None if continues && indented => append(&mut parsed.ingredients, &text),
+                    None => push_line(&mut parsed.ingredients, text),
+                },
+                Part::Instructions => match group_label(&text) {
+                    Some(name) => parsed.steps.push(Block {
+                        name: Some(name),
+                        lines: Vec::new(),
+                    }),
+                    None if continues => append(&mut parsed.steps, &text),
+                    None => push_line(&mut parsed.steps, text),
+                },
+                Part::Notes => continue_paragraph(&mut notes, text, continues),
+                Part::Preamble | Part::Other => match metadata_line(&text) {
+                    Some((key, value)) => {
+                        parsed.recipe.metadata.insert(key, value);
+                    }
+                    None if part == Part::Preamble => {
+                        continue_paragraph(&mut description, text, continues)
+                    }
+                    None => continue_paragraph(&mut notes, text, continues),
+                },
+            },
+        }
+        continues = true;
+    }
+
+    if !description.is_empty() && parsed.recipe.description.is_none() {
+        parsed.recipe.description = Some(description.join("\n\n"));
+    }
+    if !notes.is_empty() {
+        parsed
+            .recipe
+            .metadata
+            .entry(MetadataKey::Notes.to_string())
+            .or_insert_with(|| notes.join("\n"));
+    }
+    parsed.ingredients.retain(|block| !block.lines.is_empty());
+    parsed.steps.retain(|block| !block.lines.is_empty());
+
+    if parsed.ingredients.is_empty() {
+        return Err(ImportError::InvalidMarkdown(
+            "no ingredient list found under an Ingredients heading".to_string(),
+        ));
+    }
+    if parsed.steps.is_empty() {
+        return Err(ImportError::InvalidMarkdown(
+            "no steps found under an Instructions, Method or Directions heading".to_string(),
+        ));
+    }
+    Ok(parsed)
+}
+
+impl MarkdownRecipe {
+    fn components(&self) -> RecipeComponents {
+        let mut recipe = self.recipe.clone();
+        recipe.ingredients = labelled_lines(&self.ingredients);
+        recipe.instructions = labelled_lines(&self.steps).join("\n\n");
+        super::url::recipe_to_components(&recipe)
+    }
+
+    fn to_cooklang(&self) -> String {
+        let mut recipe = StructuredRecipe::default();
+        for line in self.ingredients.iter().flat_map(|block| &block.lines) {
+            let mut ingredient = parse_ingredient(line);
+            ingredient.id = recipe.ingredients.len().to_string();
+            recipe.ingredients.push(ingredient);
+        }
+
+        let mut linked = HashSet::new();
+        for block in &self.steps {
+            let steps = block
+                .lines
+                .iter()
+                .map(|step| link_ingredients(step, &recipe.ingredients, &mut linked))
+                .collect();
+            recipe.sections.push(StructuredSection {
+                name: block.name.clone(),
+                steps,
+            });
+        }
+        converters::render_cooklang(&recipe)
+    }
+}
+
+/// Lines of every block, each named block led by a `Name:` line
+fn labelled_lines(blocks: &[Block]) -> Vec<String> {
+    blocks
+        .iter()
+        .flat_map(|block| {
+            block
+                .name
+                .iter()
+                .map(|name| format!("{}:", name))
+                .chain(block.lines.iter().cloned())
+        })
+        .collect()
+}
+
+fn apply_frontmatter(recipe: &mut Recipe, yaml: &str) -> Result<(), ImportError> {
+    if yaml.trim().is_empty() {
+        return Ok(());
+    }
+    let mapping: serde_yaml::Mapping = serde_yaml::from_str(yaml).map_err(|e| {
+        ImportError::InvalidMarkdown(format!("frontmatter is not valid YAML: {}", e))
+    })?;
+    for (key, value) in &mapping {
+        let (Some(key), Some(value)) = (key.as_str(), yaml_text(value)) else {
+            continue;
+        };
+        match key.to_lowercase().as_str() {
+            "title" | "name" => recipe.name = value,
+            "description" => recipe.description = Some(value),
+            "image" => recipe.image = vec![value],
+            _ => {
+                recipe.metadata.insert(MetadataKey::canonical(key), value);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A frontmatter value as text; lists (tags) are joined with commas
+fn yaml_text(value: &serde_yaml::Value) -> Option<String> {
+    use serde_yaml::Value;
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Sequence(items) => Some(
+            items
+                .iter()
+                .filter_map(yaml_text)
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+        _ => None,
+    }
+}
+
+/// What a line is. A line that is only a part name ("**Method:**") counts
+/// as a heading one level below the last real one.
+fn classify(raw: &str, heading_level: usize) -> Line {
+    let trimmed = raw.trim().trim_start_matches('>').trim();
+    if trimmed.is_empty() || is_rule(trimmed) {
+        return Line::Blank;
+    }
+
+    let hashes = trimmed.chars().take_while(|&c| c == '#').count();
+    if (1..=6).contains(&hashes) {
+        let title = &trimmed[hashes..];
+        if title.starts_with(char::is_whitespace) {
+            let title = strip_inline(title.trim().trim_end_matches('#'));
+            return Line::Heading(hashes, title.trim_end_matches(':').trim().to_string());
+        }
+    }
+
+    if let Some(item) = list_item(trimmed) {
+        let item = strip_inline(item);
+        return if item.is_empty() {
+            Line::Blank
+        } else {
+            Line::Item(item)
+        };
+    }
+
+    let text = strip_inline(trimmed);
+    if text.is_empty() {
+        // An image on its own
+        return Line::Blank;
+    }
+    if part_for(&text).is_some() {
+        let title = text.trim_end_matches(':').trim().to_string();
+        return Line::Heading(heading_level + 1, title);
+    }
+    Line::Text {
+        text,
+        indented: raw.starts_with([' ', '\t']),
+    }
+}
+
+/// The part a heading starts, if it is one of the part names
+fn part_for(title: &str) -> Option<Part> {
+    let title = title.trim().trim_end_matches(':').trim().to_lowercase();
+    if INGREDIENT_HEADINGS.contains(&title.as_str()) {
+        Some(Part::Ingredients)
+    } else if INSTRUCTION_HEADINGS.contains(&title.as_str()) {
+        Some(Part::Instructions)
+    } else if NOTE_HEADINGS.contains(&title.as_str()) {
+        Some(Part::Notes)
+    } else {
+        None
+    }
+}
+
+/// `---`, `***` or `___`
+fn is_rule(line: &str) -> bool {
+    let chars: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+    chars.len() >= 3
+        && ['-', '*', '_']
+            .iter()
+            .any(|&m| chars.iter().all(|&c| c == m))
+}
+
+/// The text of a bullet (`-`, `*`, `+`) or numbered (`1.`, `1)`) list item,
+/// without a task checkbox
+fn list_item(line: &str) -> Option<&str> {
+    let rest = ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|marker| line.strip_prefix(marker))
+        .or_else(|| {
+            let digits = line.chars().take_while(char::is_ascii_digit).count();
+            if digits == 0 || digits > 3 {
+                return None;
+            }
+            line[digits..]
+                .strip_prefix(". ")
+                .or_else(|| line[digits..].strip_prefix(") "))
+        })?;
+    let rest = rest.trim_start();
+    Some(
+        ["[ ] ", "[x] ", "[X] "]
+            .iter()
+            .find_map(|checkbox| rest.strip_prefix(checkbox))
+            .unwrap_or(rest)
+            .trim(),
+    )
+}
+
+/// A short line ending in a colon ("For the sauce:") that names the
+/// ingredients or steps below it
+fn group_label(text: &str) -> Option<String> {
+    let label = text.strip_suffix(':')?.trim();
+    (!label.is_empty() && label.split_whitespace().count() <= 6).then(|| label.to_string())
+}
+
+/// A `Key: value` line for a known metadata key ("Servings: 4",
+/// "**Prep time:** 10 minutes")
+fn metadata_line(text: &str) -> Option<(String, String)> {
+    let (key, value) = text.split_once(':')?;
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    let key = match key.trim().to_lowercase().as_str() {
+        "serves" | "makes" => MetadataKey::Servings,
+        "prep" => MetadataKey::PrepTime,
+        "cook" => MetadataKey::CookTime,
+        "total" => MetadataKey::TotalTime,
+        key => MetadataKey::parse(key)?,
+    };
+    Some((key.to_string(), value.to_string()))
+}
+
+fn push_line(blocks: &mut Vec<Block>, line: String) {
+    if blocks.is_empty() {
+        blocks.push(Block::default());
+    }
+    if let Some(block) = blocks.last_mut() {
+        block.lines.push(line);
+    }
+}
+
+/// Add a wrapped line to the last ingredient or step
+fn append(blocks: &mut Vec<Block>, text: &str) {
+    match blocks.last_mut().and_then(|block| block.lines.last_mut()) {
+        Some(line) => {
+            line.push(' ');
+            line.push_str(text);
+        }
+        None => push_line(blocks, text.to_string()),
+    }
+}
+
+fn continue_paragraph(paragraphs: &mut Vec<String>, text: String, continues: bool) {
+    match paragraphs.last_mut() {
+        Some(last) if continues => {
+            last.push(' ');
+            last.push_str(&text);
+        }
+        _ => paragraphs.push(text),
+    }
+}
+
+/// The target of the first image (`![alt](url)`) on a line
+fn image_url(line: &str) -> Option<String> {
+    let start = line.find("![")?;
+    let (_, target, _) = link(&line[start + 1..])?;
+    (!target.is_empty()).then(|| target.to_string())
+}
+
+/// A `[label](target "title")` at the start of `text`: its label, target
+/// and the text after it
+fn link(text: &str) -> Option<(&str, &str, &str)> {
+    let (label, after) = text.strip_prefix('[')?.split_once("](")?;
+    if label.contains(']') {
+        return None;
+    }
+    let (target, rest) = after.split_once(')')?;
+    Some((label, target.split_whitespace().next().unwrap_or(""), rest))
+}
+
+/// Plain text of a line: links become their label, images are dropped, and
+/// emphasis, code marks and backslash escapes are removed
+fn strip_inline(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let after = &rest[c.len_utf8()..];
+        match c {
+            '\\' if !after.is_empty() => {
+                let escaped = after.chars().next().unwrap_or_default();
+                out.push(escaped);
+                rest = &after[escaped.len_utf8()..];
+                continue;
+            }
+            '!' if after.starts_with('[') => {
+                if let Some((_, _, tail)) = link(after) {
+                    rest = tail;
+                    continue;
+                }
+            }
+            '[' => {
+                if let Some((label, _, tail)) = link(rest) {
+                    out.push_str(&strip_inline(label));
+                    rest = tail;
+                    continue;
+                }
+            }
+            '*' | '`' => {
+                rest = after;
+                continue;
+            }
+            // Underscores inside words (snake_case) are kept
+            '_' => {
+                let inside_word = out.chars().last().is_some_and(char::is_alphanumeric)
+                    && after.chars().next().is_some_and(char::is_alphanumeric);
+                if !inside_word {
+                    rest = after;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        out.push(c);
+        rest = after;
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Split an ingredient line into quantity, unit, name and preparation:
+/// "1 1/2 cups flour, sifted", "200g butter (softened)", "Salt (optional)"
+fn parse_ingredient(line: &str) -> StructuredIngredient {
+    let mut text = normalize_quantities(line).trim().to_string();
+    let mut optional = false;
+    for marker in ["(optional)", ", optional"] {
+        if let Some(at) = text.to_ascii_lowercase().find(marker) {
+            optional = true;
+            text.replace_range(at..at + marker.len(), "");
+        }
+    }
+
+    let (main, preparation) = match text.split_once(',') {
+        Some((main, preparation)) => (main.trim(), Some(preparation.trim())),
+        None => match text
+            .trim_end()
+            .strip_suffix(')')
+            .and_then(|t| t.rsplit_once('('))
+        {
+            Some((main, preparation)) => (main.trim(), Some(preparation.trim())),
+            None => (text.trim(), None),
+        },
+    };
+
+    let words: Vec<&str> = main.split_whitespace().collect();
+    let mut quantity: Vec<&str> = words
+        .iter()
+        .take_while(|word| is_number(word))
+        .copied()
+        .collect();
+    let mut rest = &words[quantity.len()..];
+    let mut unit = None;
+    if quantity.is_empty() {
+        // "200g"
+        if let Some(word) = rest.first() {
+            let at = word
+                .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '/'))
+                .unwrap_or(0);
+            if at > 0 && is_unit_word(&word[at..]) {
+                quantity.push(&word[..at]);
+                unit = Some(word[at..].trim_end_matches('.'));
+                rest = &rest[1..];
+            }
+        }
+    } else if let Some(word) = rest.first().filter(|word| is_unit_word(word)) {
+        unit = Some(word.trim_end_matches('.'));
+        rest = &rest[1..];
+    }
+    if unit.is_some() && rest.first() == Some(&"of") {
+        rest = &rest[1..];
+    }
+
+    let name = rest.join(" ");
+    if name.is_empty() {
+        return StructuredIngredient {
+            name: main.to_string(),
+            preparation: preparation.map(str::to_string),
+            optional,
+            ..StructuredIngredient::default()
+        };
+    }
+    StructuredIngredient {
+        id: String::new(),
+        name,
+        quantity: (!quantity.is_empty()).then(|| cooklang_quantity(&quantity)),
+        unit: unit.map(str::to_string),
+        preparation: preparation.filter(|p| !p.is_empty()).map(str::to_string),
+        optional,
+    }
+}
+
+/// "2", "1/2", "1.5" or a range such as "2-3"
+fn is_number(word: &str) -> bool {
+    word.chars().any(|c| c.is_ascii_digit())
+        && word
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '.' | '/' | '-'))
+}
+
+fn is_unit_word(word: &str) -> bool {
+    let word = word.trim_end_matches('.').to_lowercase();
+    converters::is_unit(&word) || COUNT_UNITS.contains(&word.as_str())
+}
+
+/// A quantity Cooklang can read: mixed numbers ("1 1/2") become decimals
+fn cooklang_quantity(words: &[&str]) -> String {
+    if let [whole, fraction] = words {
+        let value = whole.parse::<f64>().ok().zip(
+            fraction
+                .split_once('/')
+                .and_then(|(n, d)| Some((n.parse::<f64>().ok()?, d.parse::<f64>().ok()?)))
+                .filter(|&(_, d)| d != 0.0),
+        );
+        if let Some((whole, (numerator, denominator))) = value {
+            let value = whole + numerator / denominator;
+            return format!("{}", (value * 1000.0).round() / 1000.0);
+        }
+    }
+    words.join(" ")
+}
+
+/// Replace the first mention of each ingredient that no earlier step named
+/// with a placeholder for [`converters::render_cooklang`]. Longer names go
+/// first, so "brown sugar" wins over "sugar".
+fn link_ingredients(
+    step: &str,
+    ingredients: &[StructuredIngredient],
+    linked: &mut HashSet<String>,
+) -> String {
+    let mut candidates: Vec<&StructuredIngredient> = ingredients
+        .iter()
+        .filter(|ingredient| !linked.contains(&ingredient.id))
+        .collect();
+    candidates.sort_by_key(|ingredient| std::cmp::Reverse(ingredient.name.len()));
+
+    let mut step = step.to_string();
+    for ingredient in candidates {
+        if let Some(at) = find_word(&step, &ingredient.name) {
+            step.replace_range(
+                at..at + ingredient.name.len(),
+                &format!("{{{{ingredient:{}}}}}", ingredient.id),
+            );
+            linked.insert(ingredient.id.clone());
+        }
+    }
+    step
+}
+
+/// Byte offset of `word` as a whole word in `text`, ignoring ASCII case and
+/// anything inside `{{...}}` placeholders
+fn find_word(text: &str, word: &str) -> Option<usize> {
+    if word.is_empty() {
+        return None;
+    }
+    let mut placeholders = Vec::new();
+    let mut from = 0;
+    while let Some(start) = text[from..].find("{{") {
+        let start = from + start;
+        let end = text[start..]
+            .find("}}")
+            .map_or(text.len(), |e| start + e + 2);
+        placeholders.push(start..end);
+        from = end;
+    }
+
+    text.char_indices().map(|(i, _)| i).find(|&i| {
+        let Some(candidate) = text.get(i..i + word.len()) else {
+            return false;
+        };
+        candidate.eq_ignore_ascii_case(word)
+            && !placeholders.iter().any(|range| range.contains(&i))
+            && !text[..i]
+                .chars()
+                .next_back()
+                .is_some_and(char::is_alphanumeric)
+            && !text[i + word.len()..]
+                .chars()
+                .next()
+                .is_some_and(char::is_alphanumeric)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LASAGNE: &str = r#"---
+title: Weeknight Lasagne
+tags: [pasta, baked]
+---
+
+# Lasagne
+
+A **quick** lasagne for busy evenings,
+from [Grandma's notebook](https://example.com/notebook).
+
+![Lasagne](https://example.com/lasagne.jpg)
+
+- **Serves:** 4
+- Prep time: 20 minutes
+
+## Ingredients
+
+### Sauce
+- 500g beef mince
+- 1 can tomatoes
+- 2 cloves garlic, crushed
+
+### Assembly
+* 9 lasagne sheets
+* 1 1/2 cups mozzarella (grated)
+* Basil (optional)
+
+## Method
+
+1. Brown the beef mince, then add the garlic
+   and the tomatoes.
+2. Layer the sauce with lasagne sheets and mozzarella.
+
+Bake for 40 minutes.
+
+## Notes
+
+Keeps for three days.
+"#;
+
+    #[test]
+    fn test_process_markdown() {
+        let components = process(LASAGNE).unwrap();
+        assert_eq!(components.name, "Weeknight Lasagne");
+        assert_eq!(
+            components.text,
+            "Sauce:\n500g beef mince\n1 can tomatoes\n2 cloves garlic, crushed\nAssembly:\n9 lasagne sheets\n1 1/2 cups mozzarella (grated)\nBasil (optional)\n\n\
+             Brown the beef mince, then add the garlic and the tomatoes.\n\n\
+             Layer the sauce with lasagne sheets and mozzarella.\n\n\
+             Bake for 40 minutes."
+        );
+        let metadata: serde_yaml::Mapping = serde_yaml::from_str(&components.metadata).unwrap();
+        let field = |key: &str| metadata[key].as_str().unwrap().to_string();
+        assert_eq!(
+            field("description"),
+            "A quick lasagne for busy evenings, from Grandma's notebook."
+        );
+        assert_eq!(field("image"), "https://example.com/lasagne.jpg");
+        assert_eq!(field("servings"), "4");
+        assert_eq!(field("prep time"), "20 minutes");
+        assert_eq!(field("tags"), "pasta, baked");
+        assert_eq!(field("notes"), "Keeps for three days.");
+    }
+
+    #[test]
+    fn test_to_cooklang() {
+        let cooklang = to_cooklang(LASAGNE).unwrap();
+        assert_eq!(
+            cooklang,
+            "@?Basil{}\n\n\
+             Brown the @beef mince{500%g}, then add the @garlic{2%cloves}(crushed) and the @tomatoes{1%can}.\n\n\
+             Layer the sauce with @lasagne sheets{9} and @mozzarella{1.5%cups}(grated).\n\n\
+             Bake for 40 minutes."
+        );
+        assert!(converters::check_cooklang(&cooklang).is_ok());
+    }
+
+    #[test]
+    fn test_text_to_cooklang() {
+        let text = "Dough:\n- 200 g flour\n- 2 eggs\nSauce:\n1 can tomatoes\n\n\
+                    Dough:\n\n\
+                    1. Knead the flour and eggs.\n2. Rest for 30 minutes.\n\n\
+                    Sauce:\n\n\
+                    Simmer the tomatoes\nuntil thick.";
+        assert_eq!(
+            text_to_cooklang(text),
+            "== Dough ==\n\nKnead the @flour{200%g} and @eggs{2}.\n\nRest for 30 minutes.\n\n\
+             == Sauce ==\n\nSimmer the @tomatoes{1%can} until thick."
+        );
+    }
+
+    #[test]
+    fn test_component_headings_and_bold_labels() {
+        let markdown = "# Pancakes\n\n## Batter\n**Ingredients:**\n- 200 g flour\n- 2 eggs\n\n**Method:**\nWhisk the flour and eggs.\n\n## Topping\n### Ingredients\n- Maple syrup\n### Method\n1. Pour the maple syrup over.\n";
+        let recipe = parse(markdown).unwrap();
+        assert_eq!(recipe.recipe.name, "Pancakes");
+        assert_eq!(
+            recipe
+                .ingredients
+                .iter()
+                .map(|b| b.name.as_deref())
+                .collect::<Vec<_>>(),
+            vec![Some("Batter"), Some("Topping")]
+        );
+        assert_eq!(recipe.steps[0].lines, vec!["Whisk the flour and eggs."]);
+        assert_eq!(recipe.steps[1].name.as_deref(), Some("Topping"));
+        assert_eq!(
+            recipe.to_cooklang(),
+            "== Batter ==\n\nWhisk the @flour{200%g} and @eggs{2}.\n\n== Topping ==\n\nPour the @Maple syrup{} over."
+        );
+    }
+
+    #[test]
+    fn test_parse_ingredient() {
+        let ingredient = parse_ingredient("1 ½ cups of all-purpose flour, sifted");
+        assert_eq!(ingredient.quantity.as_deref(), Some("1.5"));
+        assert_eq!(ingredient.unit.as_deref(), Some("cups"));
+        assert_eq!(ingredient.name, "all-purpose flour");
+        assert_eq!(ingredient.preparation.as_deref(), Some("sifted"));
+
+        let ingredient = parse_ingredient("2-3 large eggs");
+        assert_eq!(ingredient.quantity.as_deref(), Some("2-3"));
+        assert_eq!(ingredient.unit, None);
+        assert_eq!(ingredient.name, "large eggs");
+
+        let ingredient = parse_ingredient("Salt and pepper, to taste");
+        assert_eq!(ingredient.quantity, None);
+        assert_eq!(ingredient.name, "Salt and pepper");
+        assert_eq!(ingredient.preparation.as_deref(), Some("to taste"));
+    }
+
+    #[test]
+    fn test_missing_parts_are_invalid() {
+        assert!(matches!(
+            process("# Toast\n\nToast the bread and butter it."),
+            Err(ImportError::InvalidMarkdown(_))
+        ));
+        assert!(matches!(
+            process("# Toast\n\n## Ingredients\n- 2 slices bread\n"),
+            Err(ImportError::InvalidMarkdown(_))
+        ));
+    }
+}
@@ -1,10 +1,130 @@
+pub mod audio;
+pub mod duration;
+pub mod email;
+pub(crate) mod equipment;
+pub mod html;
 pub mod image;
+pub mod markdown;
+mod quantities;
+pub mod sub_recipes;
 pub mod text;
 pub mod url;
 
+use crate::config::{load_config, AiConfig, FrontmatterConfig, MetadataConfig};
+use crate::url_to_text::text::TextExtractor;
+pub use duration::{parse_duration, RecipeTimes};
+pub(crate) use quantities::normalize_quantities;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Whether a pipeline runs LLM text extraction when it finds no structured
+/// recipe
+#[derive(Clone, Copy)]
+pub(crate) enum Llm<'a> {
+    /// Extract with this provider
+    Extract(&'a TextExtractor),
+    /// Extraction is wanted but no provider could be set up, for this reason
+    Unavailable(&'a str),
+    /// Never call the LLM; the plain text is returned as the recipe text
+    /// (dry runs, offline imports)
+    Off,
+}
+
+impl<'a> Llm<'a> {
+    pub(crate) fn from_result(extractor: &'a Result<TextExtractor, String>) -> Self {
+        match extractor {
+            Ok(extractor) => Llm::Extract(extractor),
+            Err(reason) => Llm::Unavailable(reason),
+        }
+    }
+}
+
+/// Per-stage request timeouts used while importing a recipe
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeouts {
+    /// Fetching web pages
+    pub fetch: Duration,
+    /// Rendering pages through the page scriber service
+    pub render: Duration,
+    /// Text recognition on images
+    pub ocr: Duration,
+    /// Transcribing audio
+    pub transcription: Duration,
+    /// LLM extraction of recipe fields from unstructured text
+    pub extraction: Duration,
+    /// LLM conversion to Cooklang
+    pub conversion: Duration,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Timeouts {
+            fetch: Duration::from_secs(30),
+            render: Duration::from_secs(60),
+            ocr: crate::converters::DEFAULT_LLM_TIMEOUT,
+            transcription: crate::converters::DEFAULT_LLM_TIMEOUT,
+            extraction: crate::converters::DEFAULT_LLM_TIMEOUT,
+            conversion: crate::converters::DEFAULT_LLM_TIMEOUT,
+        }
+    }
+}
+
+impl Timeouts {
+    /// Timeouts from the `[timeouts]` config table and the `timeout` and
+    /// `llm_timeout` settings
+    pub fn from_config() -> Self {
+        Self::resolve(crate::config::load_config().ok().as_ref(), None)
+    }
+
+    /// Resolve per-stage timeouts.
+    ///
+    /// A stage set in `[timeouts]` wins; otherwise fetching falls back to
+    /// `global` or the `timeout` setting, and LLM/OCR stages to `llm_timeout`.
+    /// `global` (the builder timeout) is also a ceiling for every stage.
+    pub(crate) fn resolve(config: Option<&AiConfig>, global: Option<Duration>) -> Self {
+        let defaults = Timeouts::default();
+        let stages = config.map(|c| c.timeouts.clone()).unwrap_or_default();
+        let fetch = global
+            .or_else(|| config.map(|c| Duration::from_secs(c.timeout)))
+            .unwrap_or(defaults.fetch);
+        let llm = config
+            .and_then(|c| c.llm_timeout)
+            .map(Duration::from_secs)
+            .unwrap_or(defaults.conversion);
+
+        let secs = |stage: Option<u64>, fallback: Duration| {
+            stage.map(Duration::from_secs).unwrap_or(fallback)
+        };
+        let timeouts = Timeouts {
+            fetch: secs(stages.fetch, fetch),
+            render: secs(stages.render, defaults.render),
+            ocr: secs(stages.ocr, llm),
+            transcription: secs(stages.transcription, llm),
+            extraction: secs(stages.extraction, llm),
+            conversion: secs(stages.conversion, llm),
+        };
+        match global {
+            Some(ceiling) => timeouts.capped(ceiling),
+            None => timeouts,
+        }
+    }
+
+    /// Limit every stage to at most `ceiling`
+    pub fn capped(self, ceiling: Duration) -> Self {
+        Timeouts {
+            fetch: self.fetch.min(ceiling),
+            render: self.render.min(ceiling),
+            ocr: self.ocr.min(ceiling),
+            transcription: self.transcription.min(ceiling),
+            extraction: self.extraction.min(ceiling),
+            conversion: self.conversion.min(ceiling),
+        }
+    }
+}
+
 /// Components extracted from a recipe source.
 /// All fields can be empty strings if the data is not available.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct RecipeComponents {
     /// Recipe text containing ingredients and instructions
     pub text: String,
@@ -14,6 +134,17 @@ pub struct RecipeComponents {
     pub name: String,
 }
 
+impl RecipeComponents {
+    /// Prep, cook and total times from the metadata, as durations
+    ///
+    /// Reads `prep time`, `cook time` and `time required` (or `total time`),
+    /// as well as their `snake_case` spellings, so callers don't have to
+    /// re-parse strings like "1 hour 30 minutes".
+    pub fn times(&self) -> RecipeTimes {
+        RecipeTimes::from_yaml(&self.metadata)
+    }
+}
+
 /// Collapse any whitespace (newlines, tabs, multiple spaces) into a single space.
 pub fn sanitize_name(name: &str) -> String {
     name.split_whitespace().collect::<Vec<_>>().join(" ")
@@ -21,12 +152,36 @@ pub fn sanitize_name(name: &str) -> String {
 
 /// Build a YAML metadata string from a Recipe's fields.
 /// Handles nested values (e.g. nutrition) by parsing pre-formatted YAML blocks.
+///
+/// Keys are renamed by the `[metadata.map]` table, and values are
+/// shortened to the `[frontmatter]` length caps when `truncate` is enabled
+/// in the config. Every extractor's metadata goes through here, so both
+/// apply to all of them.
 pub fn metadata_to_yaml(entries: &[(String, String)]) -> String {
+    let config = load_config().ok();
+    let caps = config
+        .as_ref()
+        .map(|c| c.frontmatter.clone())
+        .unwrap_or_default();
+    let keys = config.map(|c| c.metadata).unwrap_or_default();
+    metadata_to_yaml_with(entries, &caps, &keys)
+}
+
+fn metadata_to_yaml_with(
+    entries: &[(String, String)],
+    caps: &FrontmatterConfig,
+    keys: &MetadataConfig,
+) -> String {
     use serde_yaml::Value;
 
     let mut mapping = serde_yaml::Mapping::new();
 
     for (key, value) in entries {
+        let key = match keys.map.get(key) {
+            Some(renamed) if renamed.is_empty() => continue,
+            Some(renamed) => renamed,
+            None => key,
+        };
         if value.starts_with('\n') {
             // Pre-formatted nested YAML (e.g. nutrition) — parse as nested mapping
             let yaml_str = format!("{}:{}", key, value);
@@ -37,7 +192,10 @@ pub fn metadata_to_yaml(entries: &[(String, String)]) -> String {
                 continue;
             }
         }
-        mapping.insert(Value::String(key.clone()), Value::String(value.clone()));
+        mapping.insert(
+            Value::String(key.clone()),
+            Value::String(truncate_value(key, value, caps)),
+        );
     }
 
     if mapping.is_empty() {
@@ -47,6 +205,40 @@ pub fn metadata_to_yaml(entries: &[(String, String)]) -> String {
     }
 }
 
+/// Shorten `value` to the cap for `key`, cutting at a word boundary and
+/// ending with "…". URLs are never cut unless their field has its own cap,
+/// since a shortened link is useless.
+fn truncate_value(key: &str, value: &str, caps: &FrontmatterConfig) -> String {
+    if !caps.truncate {
+        return value.to_string();
+    }
+    let max = match caps.fields.get(key) {
+        Some(&max) => max,
+        None if value.starts_with("http://") || value.starts_with("https://") => {
+            return value.to_string()
+        }
+        None => caps.max_length,
+    };
+    if value.chars().count() <= max {
+        return value.to_string();
+    }
+
+    let cut = value
+        .char_indices()
+        .nth(max.saturating_sub(1))
+        .map_or(value.len(), |(i, _)| i);
+    let kept = &value[..cut];
+    // Back up to the last space, unless that throws away most of the text
+    let kept = match kept.rfind(char::is_whitespace) {
+        Some(space) if space >= cut / 2 => &kept[..space],
+        _ => kept,
+    };
+    format!(
+        "{}…",
+        kept.trim_end_matches(|c: char| c.is_whitespace() || ",;:.".contains(c))
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -83,8 +275,108 @@ mod tests {
         assert!(!yaml.contains("\""));
     }
 
+    #[test]
+    fn test_metadata_to_yaml_truncates_long_values() {
+        let caps = FrontmatterConfig {
+            truncate: true,
+            max_length: 20,
+            fields: [("notes".to_string(), 12)].into_iter().collect(),
+            ..FrontmatterConfig::default()
+        };
+        let entries = vec![
+            (
+                "description".to_string(),
+                "A rich, slow-cooked chilli with beans".to_string(),
+            ),
+            ("notes".to_string(), "Keeps for three days".to_string()),
+            ("servings".to_string(), "4".to_string()),
+            (
+                "source".to_string(),
+                "https://example.com/recipes/slow-cooker-chilli".to_string(),
+            ),
+        ];
+        let yaml = metadata_to_yaml_with(&entries, &caps, &MetadataConfig::default());
+        assert!(yaml.contains("description: A rich, slow-cooked…\n"));
+        assert!(yaml.contains("notes: Keeps for…\n"));
+        assert!(yaml.contains("servings: '4'"));
+        assert!(yaml.contains("source: https://example.com/recipes/slow-cooker-chilli"));
+
+        // Off unless switched on
+        let yaml = metadata_to_yaml_with(
+            &entries,
+            &FrontmatterConfig::default(),
+            &MetadataConfig::default(),
+        );
+        assert!(yaml.contains("with beans"));
+    }
+
+    #[test]
+    fn test_metadata_to_yaml_renames_keys() {
+        let keys = MetadataConfig {
+            map: [
+                ("servings".to_string(), "yield".to_string()),
+                ("source_url".to_string(), "source".to_string()),
+                ("image".to_string(), String::new()),
+            ]
+            .into_iter()
+            .collect(),
+        };
+        let entries = vec![
+            ("servings".to_string(), "4".to_string()),
+            ("source_url".to_string(), "https://example.com".to_string()),
+            (
+                "image".to_string(),
+                "https://example.com/chilli.jpg".to_string(),
+            ),
+            ("nutrition".to_string(), "\n  calories: 330".to_string()),
+        ];
+        let yaml = metadata_to_yaml_with(&entries, &FrontmatterConfig::default(), &keys);
+        assert!(yaml.contains("yield: '4'"));
+        assert!(yaml.contains("source: https://example.com\n"));
+        assert!(!yaml.contains("image"));
+        assert!(!yaml.contains("servings"));
+        assert!(yaml.contains("nutrition:"));
+    }
+
     #[test]
     fn test_sanitize_name() {
         assert_eq!(sanitize_name("hello  world\n test"), "hello world test");
     }
+
+    fn config_from_toml(toml: &str) -> AiConfig {
+        use config::{Config, File, FileFormat};
+        Config::builder()
+            .add_source(File::from_str(toml, FileFormat::Toml))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_timeouts_from_stage_table() {
+        let config = config_from_toml(
+            "timeout = 20\nllm_timeout = 90\n\n[timeouts]\nrender = 45\nconversion = 200\n",
+        );
+        let timeouts = Timeouts::resolve(Some(&config), None);
+        assert_eq!(timeouts.fetch, Duration::from_secs(20));
+        assert_eq!(timeouts.render, Duration::from_secs(45));
+        assert_eq!(timeouts.ocr, Duration::from_secs(90));
+        assert_eq!(timeouts.extraction, Duration::from_secs(90));
+        assert_eq!(timeouts.conversion, Duration::from_secs(200));
+    }
+
+    #[test]
+    fn test_global_timeout_is_a_ceiling() {
+        let config = config_from_toml("[timeouts]\nfetch = 10\nconversion = 200\n");
+        let timeouts = Timeouts::resolve(Some(&config), Some(Duration::from_secs(60)));
+        assert_eq!(timeouts.fetch, Duration::from_secs(10));
+        assert_eq!(timeouts.render, Duration::from_secs(60));
+        assert_eq!(timeouts.conversion, Duration::from_secs(60));
+
+        // Without a stage table the global timeout is the fetch timeout
+        let timeouts = Timeouts::resolve(None, Some(Duration::from_secs(90)));
+        assert_eq!(timeouts.fetch, Duration::from_secs(90));
+        assert_eq!(timeouts.extraction, Duration::from_secs(90));
+    }
 }
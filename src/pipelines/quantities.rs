@@ -0,0 +1,143 @@
+/// Unicode vulgar fractions and their ASCII spelling
+const FRACTIONS: &[(char, &str)] = &[
+    ('½', "1/2"),
+    ('⅓', "1/3"),
+    ('⅔', "2/3"),
+    ('¼', "1/4"),
+    ('¾', "3/4"),
+    ('⅕', "1/5"),
+    ('⅖', "2/5"),
+    ('⅗', "3/5"),
+    ('⅘', "4/5"),
+    ('⅙', "1/6"),
+    ('⅚', "5/6"),
+    ('⅐', "1/7"),
+    ('⅛', "1/8"),
+    ('⅜', "3/8"),
+    ('⅝', "5/8"),
+    ('⅞', "7/8"),
+    ('⅑', "1/9"),
+    ('⅒', "1/10"),
+];
+
+/// Dashes used between the ends of a range ("2–3", "2 - 3")
+const DASHES: &[char] = &['-', '‐', '‑', '‒', '–', '—', '−'];
+
+/// Rewrite numbers in recipe text into one canonical form before the LLM
+/// sees them, so locale spellings aren't misread as other quantities:
+///
+/// - unicode fractions become ASCII: "1½ cups" → "1 1/2 cups", "⅔" → "2/3"
+/// - decimal commas become points: "1,5 kg" → "1.5 kg" (but not "1,000 g")
+/// - ranges use a plain hyphen: "2–3 EL", "2 - 3 EL" → "2-3 EL"
+///
+/// Running it again changes nothing.
+pub(crate) fn normalize_quantities(text: &str) -> String {
+    let text = ascii_fractions(text);
+    let text = decimal_points(&text);
+    tight_ranges(&text)
+}
+
+fn ascii_fractions(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c == '⁄' {
+            // U+2044 FRACTION SLASH, as in "1⁄2"
+            output.push('/');
+        } else if let Some((_, ascii)) = FRACTIONS.iter().find(|(fraction, _)| *fraction == c) {
+            if output.ends_with(|c: char| c.is_ascii_digit()) {
+                output.push(' ');
+            }
+            output.push_str(ascii);
+        } else {
+            output.push(c);
+        }
+    }
+    output
+}
+
+fn decimal_points(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let digit = |i: usize| chars.get(i).is_some_and(|c| c.is_ascii_digit());
+
+    let mut output = String::with_capacity(text.len());
+    for (i, &c) in chars.iter().enumerate() {
+        if c == ',' && i > 0 && digit(i - 1) && digit(i + 1) {
+            let decimals = (i + 1..).take_while(|&j| digit(j)).count();
+            let integer_start = (0..i).rev().take_while(|&j| digit(j)).last().unwrap_or(i);
+            // "1,2,3" is a list and "1.000,5" mixes separators; three
+            // decimals ("1,000") are a thousands separator
+            let in_list = integer_start > 0
+                && matches!(chars[integer_start - 1], ',' | '.')
+                && digit(integer_start.saturating_sub(2))
+                || chars.get(i + 1 + decimals) == Some(&',') && digit(i + 2 + decimals);
+            if decimals <= 2 && !in_list {
+                output.push('.');
+                continue;
+            }
+        }
+        output.push(c);
+    }
+    output
+}
+
+fn tight_ranges(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut output = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        output.push(chars[i]);
+        if chars[i].is_ascii_digit() {
+            let dash = (i + 1..chars.len()).find(|&j| chars[j] != ' ');
+            if let Some(dash) = dash.filter(|&j| DASHES.contains(&chars[j])) {
+                let next = (dash + 1..chars.len()).find(|&j| chars[j] != ' ');
+                // A plain "2-3" is already canonical; spaces or other dashes aren't
+                let canonical = chars[dash] == '-' && dash == i + 1 && next == Some(dash + 1);
+                if let Some(next) = next.filter(|&j| chars[j].is_ascii_digit() && !canonical) {
+                    output.push('-');
+                    i = next;
+                    continue;
+                }
+            }
+        }
+        i += 1;
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unicode_fractions() {
+        assert_eq!(normalize_quantities("1½ cups milk"), "1 1/2 cups milk");
+        assert_eq!(normalize_quantities("⅔ Tasse Zucker"), "2/3 Tasse Zucker");
+        assert_eq!(normalize_quantities("1 ¼ tsp"), "1 1/4 tsp");
+        assert_eq!(normalize_quantities("1⁄2 lemon"), "1/2 lemon");
+    }
+
+    #[test]
+    fn test_decimal_commas() {
+        assert_eq!(normalize_quantities("1,5 kg Mehl"), "1.5 kg Mehl");
+        assert_eq!(normalize_quantities("0,25 l lait"), "0.25 l lait");
+        assert_eq!(normalize_quantities("1,000 g flour"), "1,000 g flour");
+        assert_eq!(normalize_quantities("steps 1,2,3"), "steps 1,2,3");
+        assert_eq!(normalize_quantities("salt, 2 eggs"), "salt, 2 eggs");
+    }
+
+    #[test]
+    fn test_ranges() {
+        assert_eq!(normalize_quantities("2–3 EL Öl"), "2-3 EL Öl");
+        assert_eq!(normalize_quantities("2 - 3 cloves"), "2-3 cloves");
+        assert_eq!(normalize_quantities("1,5 — 2 kg"), "1.5-2 kg");
+        assert_eq!(normalize_quantities("2-3 eggs"), "2-3 eggs");
+        assert_eq!(normalize_quantities("a 20-cm tin"), "a 20-cm tin");
+    }
+
+    #[test]
+    fn test_normalize_is_idempotent() {
+        let text = "1½ cups milk\n1,5 kg Mehl\n2–3 EL Öl";
+        let once = normalize_quantities(text);
+        assert_eq!(normalize_quantities(&once), once);
+    }
+}
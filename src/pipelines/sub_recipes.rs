@@ -0,0 +1,345 @@
+//! Split component recipes (dough, filling, glaze) out of a recipe
+//!
+//! Extractors mark ingredient groups and instruction sections with
+//! `## Name` lines (Markdown recipes with `Name:` lines). A group and a
+//! section with the same name ("For the dough" / "Dough") form a component
+//! recipe of its own, which the parent then refers to with Cooklang's
+//! recipe-reference syntax, `@./Name{}`.
+
+use super::RecipeComponents;
+use crate::model::MetadataKey;
+
+/// Prefixes dropped from group and section names before comparing them
+const NAME_PREFIXES: [&str; 5] = ["for the ", "for ", "to make the ", "make the ", "the "];
+
+/// A recipe with its component recipes split out
+#[derive(Debug, Clone)]
+pub struct SplitRecipe {
+    /// The recipe, whose first step references each part
+    pub parent: RecipeComponents,
+    /// The component recipes, in the order the page lists them; empty when
+    /// there was nothing to split
+    pub parts: Vec<RecipeComponents>,
+}
+
+/// A run of lines under an optional heading
+struct Block {
+    /// The heading line as written, e.g. "## For the dough"
+    heading: Option<String>,
+    /// Ingredient lines, or step paragraphs
+    lines: Vec<String>,
+}
+
+impl Block {
+    fn name(&self) -> Option<&str> {
+        self.heading.as_deref().and_then(heading_name)
+    }
+}
+
+/// Split the component recipes out of `components`.
+///
+/// A part needs both an ingredient group and an instruction section with the
+/// same name, and the parent must keep at least one step of its own.
+/// Parts are named after the parent ("Cinnamon Rolls Glaze") so their files
+/// don't collide with other recipes' parts.
+pub fn split(components: &RecipeComponents) -> SplitRecipe {
+    let unchanged = || SplitRecipe {
+        parent: components.clone(),
+        parts: Vec::new(),
+    };
+    let Some((ingredients, steps)) = components.text.split_once("\n\n") else {
+        return unchanged();
+    };
+    let groups = ingredient_groups(ingredients);
+    let sections = step_sections(steps);
+
+    let mut parts = Vec::new();
+    let mut kept_groups = Vec::new();
+    let mut split_sections = vec![false; sections.len()];
+    for group in groups {
+        let matched = group.name().and_then(|name| {
+            sections
+                .iter()
+                .position(|section| section.name().is_some_and(|s| key(s) == key(name)))
+        });
+        match matched {
+            Some(index) if !split_sections[index] => {
+                split_sections[index] = true;
+                parts.push((group, index));
+            }
+            _ => kept_groups.push(group),
+        }
+    }
+    let kept_sections: Vec<&Block> = sections
+        .iter()
+        .zip(&split_sections)
+        .filter(|(_, split)| !**split)
+        .map(|(section, _)| section)
+        .collect();
+    if parts.is_empty() || kept_sections.iter().all(|section| section.lines.is_empty()) {
+        return unchanged();
+    }
+
+    let source = source_metadata(&components.metadata);
+    let parts: Vec<RecipeComponents> = parts
+        .into_iter()
+        .map(|(group, index)| {
+            let title = part_title(group.name().unwrap_or_default());
+            RecipeComponents {
+                text: format!(
+                    "{}\n\n{}",
+                    group.lines.join("\n"),
+                    sections[index].lines.join("\n\n")
+                ),
+                metadata: source.clone(),
+                name: part_name(&components.name, &title),
+            }
+        })
+        .collect();
+
+    let names: Vec<&str> = parts.iter().map(|part| part.name.as_str()).collect();
+    let mut steps = vec![references_step(&names)];
+    for section in kept_sections {
+        steps.extend(section.heading.clone());
+        steps.extend(section.lines.iter().cloned());
+    }
+    let ingredients: Vec<String> = kept_groups
+        .into_iter()
+        .flat_map(|group| group.heading.into_iter().chain(group.lines))
+        .collect();
+    let text = if ingredients.is_empty() {
+        steps.join("\n\n")
+    } else {
+        format!("{}\n\n{}", ingredients.join("\n"), steps.join("\n\n"))
+    };
+
+    SplitRecipe {
+        parent: RecipeComponents {
+            text,
+            ..components.clone()
+        },
+        parts,
+    }
+}
+
+/// The Cooklang reference to the recipe in "<name>.cook" next to this one
+pub fn reference(name: &str) -> String {
+    format!("@./{}{{}}", name)
+}
+
+/// A step preparing every part, e.g. "Make the @./Pie Crust{} and @./Pie
+/// Filling{}."
+pub(crate) fn references_step(names: &[&str]) -> String {
+    let references: Vec<String> = names.iter().map(|name| reference(name)).collect();
+    let listed = match references.as_slice() {
+        [] => String::new(),
+        [only] => only.clone(),
+        [rest @ .., last] => format!("{} and {}", rest.join(", "), last),
+    };
+    format!("Make the {}.", listed)
+}
+
+/// Add the [`references_step`] before a converted parent's steps unless the
+/// model kept every reference already
+pub(crate) fn ensure_references(cooklang: &str, names: &[&str]) -> String {
+    if names.iter().all(|name| cooklang.contains(&reference(name))) {
+        return cooklang.to_string();
+    }
+    format!("{}\n\n{}", references_step(names), cooklang)
+}
+
+/// Ingredient lines grouped under their headings
+fn ingredient_groups(ingredients: &str) -> Vec<Block> {
+    let mut groups = vec![Block {
+        heading: None,
+        lines: Vec::new(),
+    }];
+    for line in ingredients.lines() {
+        if heading_name(line).is_some() {
+            groups.push(Block {
+                heading: Some(line.to_string()),
+                lines: Vec::new(),
+            });
+        } else if let Some(group) = groups.last_mut() {
+            group.lines.push(line.to_string());
+        }
+    }
+    groups.retain(|group| group.heading.is_some() || !group.lines.is_empty());
+    groups
+}
+
+/// Step paragraphs grouped under their section headings
+fn step_sections(steps: &str) -> Vec<Block> {
+    let mut sections = vec![Block {
+        heading: None,
+        lines: Vec::new(),
+    }];
+    let mut paragraph: Vec<&str> = Vec::new();
+    let flush = |paragraph: &mut Vec<&str>, sections: &mut Vec<Block>| {
+        if !paragraph.is_empty() {
+            if let Some(section) = sections.last_mut() {
+                section.lines.push(paragraph.join("\n"));
+            }
+            paragraph.clear();
+        }
+    };
+    for line in steps.lines() {
+        if line.trim().is_empty() {
+            flush(&mut paragraph, &mut sections);
+        } else if heading_name(line).is_some() {
+            flush(&mut paragraph, &mut sections);
+            sections.push(Block {
+                heading: Some(line.to_string()),
+                lines: Vec::new(),
+            });
+        } else {
+            paragraph.push(line);
+        }
+    }
+    flush(&mut paragraph, &mut sections);
+    sections
+}
+
+/// The name in a `## Name` heading or a short `Name:` label line
+fn heading_name(line: &str) -> Option<&str> {
+    let line = line.trim();
+    if let Some(name) = line.strip_prefix("## ") {
+        return Some(name.trim().trim_end_matches(':'));
+    }
+    let name = line.strip_suffix(':')?;
+    let is_label = !name.is_empty()
+        && name.chars().count() <= 40
+        && !name.chars().any(|c| c.is_ascii_digit() || c == '.');
+    is_label.then_some(name)
+}
+
+/// A group or section name without "For the" and the like, lowercased
+fn key(name: &str) -> String {
+    let name = name.trim().to_lowercase();
+    let stripped = NAME_PREFIXES
+        .iter()
+        .find_map(|prefix| name.strip_prefix(prefix))
+        .unwrap_or(&name);
+    stripped.trim().to_string()
+}
+
+/// A group name as a title: "For the glaze" becomes "Glaze"
+fn part_title(name: &str) -> String {
+    let name = name.trim().trim_end_matches(':');
+    let lower = name.to_lowercase();
+    let stripped = NAME_PREFIXES
+        .iter()
+        .find(|prefix| lower.starts_with(*prefix))
+        .map_or(name, |prefix| &name[prefix.len()..])
+        .trim();
+    let mut chars = stripped.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// A part's recipe name: the parent's name and the part's, unless the part
+/// already names the parent ("Pizza" and "Pizza sauce")
+fn part_name(parent: &str, title: &str) -> String {
+    if parent.is_empty() || title.to_lowercase().contains(&parent.to_lowercase()) {
+        title.to_string()
+    } else {
+        format!("{} {}", parent, title)
+    }
+}
+
+/// The parent's `source` entry, for its parts' metadata
+fn source_metadata(metadata: &str) -> String {
+    let key = MetadataKey::Source.to_string();
+    serde_yaml::from_str::<serde_yaml::Mapping>(metadata)
+        .ok()
+        .and_then(|mapping| {
+            mapping
+                .get(key.as_str())
+                .and_then(|value| value.as_str())
+                .map(str::to_string)
+        })
+        .map(|source| super::metadata_to_yaml(&[(key, source)]))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn components(text: &str) -> RecipeComponents {
+        RecipeComponents {
+            text: text.to_string(),
+            metadata: "source: https://example.com/rolls\nservings: '12'\n".to_string(),
+            name: "Cinnamon Rolls".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_split_sub_recipes() {
+        let split = split(&components(
+            "## For the dough\n500 g flour\n7 g yeast\n## For the glaze\n100 g icing sugar\n\
+             \n## Dough\n\nKnead the flour and yeast.\n\nLeave to rise.\n\n\
+             ## Assemble\n\nRoll up and bake.\n\n## Glaze\n\nWhisk the sugar with water.",
+        ));
+
+        let names: Vec<&str> = split.parts.iter().map(|part| part.name.as_str()).collect();
+        assert_eq!(names, ["Cinnamon Rolls Dough", "Cinnamon Rolls Glaze"]);
+        assert_eq!(
+            split.parts[0].text,
+            "500 g flour\n7 g yeast\n\nKnead the flour and yeast.\n\nLeave to rise."
+        );
+        assert_eq!(
+            split.parts[0].metadata,
+            "source: https://example.com/rolls\n"
+        );
+        assert_eq!(
+            split.parent.text,
+            "Make the @./Cinnamon Rolls Dough{} and @./Cinnamon Rolls Glaze{}.\n\n\
+             ## Assemble\n\nRoll up and bake."
+        );
+        assert!(split.parent.metadata.contains("servings"));
+    }
+
+    #[test]
+    fn test_split_keeps_unmatched_groups() {
+        // Markdown labels, and a group with no section of its own
+        let split = split(&components(
+            "Sauce:\n400 g tomatoes\nTo serve:\nbasil\n\n\
+             Sauce:\n\nSimmer the tomatoes.\n\nMethod:\n\nServe with basil.",
+        ));
+        assert_eq!(split.parts.len(), 1);
+        assert_eq!(split.parts[0].name, "Cinnamon Rolls Sauce");
+        assert_eq!(
+            split.parent.text,
+            "To serve:\nbasil\n\nMake the @./Cinnamon Rolls Sauce{}.\n\nMethod:\n\nServe with basil."
+        );
+    }
+
+    #[test]
+    fn test_split_nothing_to_split() {
+        // No sections, and a recipe that would be left without steps
+        for text in [
+            "2 eggs\n\nFry the eggs.",
+            "## Filling\n2 apples\n\n## Filling\n\nSlice the apples.",
+        ] {
+            let split = split(&components(text));
+            assert!(split.parts.is_empty());
+            assert_eq!(split.parent.text, text);
+        }
+    }
+
+    #[test]
+    fn test_ensure_references() {
+        let names = ["Pie Crust", "Pie Filling"];
+        let kept = "Fill the @./Pie Crust{} with the @./Pie Filling{}.";
+        assert_eq!(ensure_references(kept, &names), kept);
+        assert_eq!(
+            ensure_references("Bake for 40 minutes.", &names),
+            "Make the @./Pie Crust{} and @./Pie Filling{}.\n\nBake for 40 minutes."
+        );
+        assert_eq!(part_title("For the glaze:"), "Glaze");
+        assert_eq!(part_name("Pizza", "Pizza sauce"), "Pizza sauce");
+    }
+}
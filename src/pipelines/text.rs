@@ -1,20 +1,31 @@
-use super::RecipeComponents;
+use super::{RecipeComponents, Timeouts};
 use crate::url_to_text::text::TextExtractor;
 use std::error::Error;
 
 pub async fn process(
     text: &str,
     extract: bool,
+    timeouts: &Timeouts,
+    proxy: Option<&str>,
 ) -> Result<RecipeComponents, Box<dyn Error + Send + Sync>> {
     if extract {
         // Run through LLM extractor - returns RecipeComponents directly
-        TextExtractor::extract(text, "direct-input").await
+        let extractor = TextExtractor::from_config(timeouts.extraction, proxy).await?;
+        self::extract(text, &extractor).await
     } else {
         // Assume already formatted, parse it into components
         Ok(parse_text_to_components(text))
     }
 }
 
+/// LLM extraction of direct text input with `extractor`
+pub(crate) async fn extract(
+    text: &str,
+    extractor: &TextExtractor,
+) -> Result<RecipeComponents, Box<dyn Error + Send + Sync>> {
+    extractor.extract(text, "direct-input").await
+}
+
 /// Parse text format (with optional frontmatter) into RecipeComponents
 fn parse_text_to_components(text: &str) -> RecipeComponents {
     let (metadata_map, body) = crate::model::Recipe::parse_text_format(text);
@@ -1,107 +1,623 @@
-use super::RecipeComponents;
+use super::{Llm, RecipeComponents, Timeouts};
+use crate::cache::Cache;
 use crate::config::load_config;
-use crate::url_to_text::fetchers::{PageScriberFetcher, RequestFetcher};
+use crate::model::{MetadataKey, Recipe};
+use crate::url_to_text::fetchers::{FetchedPage, PageScriberFetcher, RequestFetcher};
+use crate::url_to_text::html::diagnostics::{DroppedField, ExtractionDiagnostics};
 use crate::url_to_text::html::extractors::{
-    Extractor, HtmlClassExtractor, JsonLdExtractor, MicroDataExtractor, ParsingContext,
+    registered_extractors, Extractor, HtmlClassExtractor, JsonLdExtractor, MicroDataExtractor,
+    OpenGraphExtractor, ParsingContext, SharedContext, SiteAdapter, SiteExtractor,
 };
+use crate::url_to_text::html::prescan;
+use crate::url_to_text::normalize;
+use crate::url_to_text::social::{self, Platform};
 use crate::url_to_text::text::TextExtractor;
+use crate::url_to_text::wayback;
 use scraper::Html;
+use std::collections::HashMap;
 use std::error::Error;
-use std::time::Duration;
+use std::sync::Arc;
+use tokio::task::JoinSet;
+use tracing::{debug, debug_span, warn, Instrument};
+
+/// What the URL pipeline found, and how
+pub(crate) struct Extraction {
+    pub components: RecipeComponents,
+    /// Structured extractor that matched ("site-adapter", a built-in site
+    /// such as "allrecipes", "json-ld", "microdata", "html-class" or a
+    /// registered extractor's name); `None`
+    /// when the LLM text extractor was used
+    pub extractor: Option<&'static str>,
+    /// Fields the structured extractor saw but didn't use
+    pub dropped_fields: Vec<DroppedField>,
+    /// Page the recipe was extracted from, after redirects and AMP
+    /// resolution; `None` when no page was fetched
+    pub url: Option<String>,
+}
 
 /// Process a URL to extract recipe content
 ///
 /// Pipeline:
+/// 0. Use the cached page if one is fresh enough
 /// 1. Check if domain is in page_scriber.domains → use PageScriberFetcher
 /// 2. Otherwise, use RequestFetcher
-/// 3. Try structured extractors (JSON-LD → MicroData → HtmlClass)
+/// 3. Try structured extractors (registered → site adapters → built-in
+///    site extractor → JSON-LD → MicroData → HtmlClass)
 /// 4. If RequestFetcher failed (402/blocked), auto-fallback to PageScriberFetcher
-/// 5. Final fallback: TextExtractor (LLM) on extracted text
-pub async fn process(url: &str) -> Result<RecipeComponents, Box<dyn Error + Send + Sync>> {
+/// 5. Final fallback: TextExtractor (LLM) on extracted text, with title,
+///    image and author filled in from Open Graph tags
+///
+/// Redirects are followed, and AMP pages are swapped for their canonical
+/// page before extraction. With `[fetch] wayback`, a page that is gone is
+/// replaced by its latest Wayback Machine snapshot.
+pub async fn process(
+    url: &str,
+    timeouts: &Timeouts,
+    proxy: Option<&str>,
+    cache: Option<&Cache>,
+) -> Result<RecipeComponents, Box<dyn Error + Send + Sync>> {
+    let extractor = TextExtractor::from_config(timeouts.extraction, proxy)
+        .await
+        .map_err(|e| e.to_string());
+    extract(url, timeouts, proxy, cache, Llm::from_result(&extractor))
+        .await
+        .map(|extraction| extraction.components)
+}
+
+/// Run the URL pipeline, reporting which extractor matched.
+///
+/// With [`Llm::Off`] the LLM text extractor is never called; if no
+/// structured extractor matches, the page's plain text is returned as the
+/// recipe text instead (used for dry runs).
+///
+/// Instagram and TikTok posts skip the page: their caption is read and goes
+/// through text extraction.
+///
+/// AMP cache links are replaced with the publisher's URL before fetching,
+/// and an AMP page is replaced with its canonical page (if that can be
+/// fetched), which has the full recipe markup.
+pub(crate) async fn extract(
+    url: &str,
+    timeouts: &Timeouts,
+    proxy: Option<&str>,
+    cache: Option<&Cache>,
+    llm: Llm<'_>,
+) -> Result<Extraction, Box<dyn Error + Send + Sync>> {
+    if let Some(platform) = Platform::detect(url) {
+        return social_extraction(url, platform, timeouts, proxy, llm).await;
+    }
+    let page = fetch_recipe_page(url, timeouts, proxy, cache).await?;
+    let mut extraction = extract_from_html(&page.html, &page.url, llm).await?;
+    extraction.url = Some(page.url);
+    Ok(extraction)
+}
+
+/// Run the URL pipeline for every recipe on the page.
+///
+/// A page whose JSON-LD describes several recipes (a roundup such as "10
+/// best curry recipes") gives one extraction per recipe, in page order.
+/// Any other page goes through [`extract`] and gives one.
+pub(crate) async fn extract_all(
+    url: &str,
+    timeouts: &Timeouts,
+    proxy: Option<&str>,
+    cache: Option<&Cache>,
+    llm: Llm<'_>,
+) -> Result<Vec<Extraction>, Box<dyn Error + Send + Sync>> {
+    if let Some(platform) = Platform::detect(url) {
+        return Ok(vec![
+            social_extraction(url, platform, timeouts, proxy, llm).await?,
+        ]);
+    }
+    let page = fetch_recipe_page(url, timeouts, proxy, cache).await?;
+    let mut extractions = extract_all_from_html(&page.html, &page.url, llm).await?;
+    for extraction in &mut extractions {
+        extraction.url = Some(page.url.clone());
+    }
+    Ok(extractions)
+}
+
+/// [`extract_all`] for a page that was already fetched (or saved)
+pub(crate) async fn extract_all_from_html(
+    html: &str,
+    url: &str,
+    llm: Llm<'_>,
+) -> Result<Vec<Extraction>, Box<dyn Error + Send + Sync>> {
+    let context = ParsingContext {
+        url: url.to_string(),
+        document: Html::parse_document(html),
+        texts: None,
+    };
+    let recipes = JsonLdExtractor.parse_all(&context);
+    if recipes.len() < 2 {
+        return Ok(vec![extract_from_html(html, url, llm).await?]);
+    }
+
+    debug!("{} has {} recipes", url, recipes.len());
+    let source = page_source(html, url);
+    Ok(recipes
+        .into_iter()
+        .map(|recipe| {
+            let mut components = recipe_to_components(&recipe);
+            if source != url {
+                components = with_source(components, url, &source);
+            }
+            Extraction {
+                components,
+                extractor: Some(JsonLdExtractor.name()),
+                dropped_fields: recipe.dropped_fields,
+                url: None,
+            }
+        })
+        .collect())
+}
+
+/// Fetch the page for `url`: the publisher's page for an AMP cache link, and
+/// the canonical page (if it can be fetched) for an AMP page
+async fn fetch_recipe_page(
+    url: &str,
+    timeouts: &Timeouts,
+    proxy: Option<&str>,
+    cache: Option<&Cache>,
+) -> Result<FetchedPage, Box<dyn Error + Send + Sync>> {
+    let origin = normalize::amp_cache_origin(url);
+    if let Some(origin) = &origin {
+        debug!("{} is an AMP cache link; fetching {}", url, origin);
+    }
+    let target = origin.as_deref().unwrap_or(url);
+    let mut page = match fetch_page(target, timeouts, proxy, cache).await {
+        Ok(page) => page,
+        Err(e) => return archived_page(target, e, timeouts, proxy, cache).await,
+    };
+    if let Some(canonical) = normalize::non_amp_url(&page.html, &page.url) {
+        match fetch_page(&canonical, timeouts, proxy, cache).await {
+            Ok(canonical_page) => {
+                debug!("{} is an AMP page; using {}", page.url, canonical_page.url);
+                page = canonical_page;
+            }
+            Err(e) => warn!(
+                "Could not fetch {}, the canonical page of AMP page {}: {}",
+                canonical, page.url, e
+            ),
+        }
+    }
+    Ok(page)
+}
+
+/// The Wayback Machine's latest snapshot of `url`, reported as served from
+/// the snapshot, when `[fetch] wayback` is on and `error` says the page is
+/// gone; `error` otherwise
+async fn archived_page(
+    url: &str,
+    error: Box<dyn Error + Send + Sync>,
+    timeouts: &Timeouts,
+    proxy: Option<&str>,
+    cache: Option<&Cache>,
+) -> Result<FetchedPage, Box<dyn Error + Send + Sync>> {
+    let enabled = load_config().ok().is_some_and(|c| c.fetch.wayback);
+    if !enabled || !wayback::is_dead_link(error.as_ref()) {
+        return Err(error);
+    }
+    let snapshot =
+        match wayback::latest_snapshot(wayback::AVAILABILITY_API, url, timeouts.fetch, proxy).await
+        {
+            Ok(Some(snapshot)) => snapshot,
+            Ok(None) => return Err(error),
+            Err(e) => {
+                warn!("Could not look up {} in the Wayback Machine: {}", url, e);
+                return Err(error);
+            }
+        };
+    warn!(
+        "{} is gone ({}); importing the Wayback Machine's snapshot {}",
+        url, error, snapshot
+    );
+    let page = fetch_page(
+        &wayback::raw_snapshot_url(&snapshot),
+        timeouts,
+        proxy,
+        cache,
+    )
+    .await?;
+    Ok(FetchedPage {
+        url: snapshot,
+        html: page.html,
+    })
+}
+
+/// Fetch a recipe page
+///
+/// 0. Use the cached page if one is fresh enough
+/// 1. Check if domain is in page_scriber.domains → use PageScriberFetcher
+/// 2. Otherwise, use RequestFetcher
+/// 3. If RequestFetcher failed (402/blocked), auto-fallback to PageScriberFetcher
+pub(crate) async fn fetch(
+    url: &str,
+    timeouts: &Timeouts,
+    proxy: Option<&str>,
+    cache: Option<&Cache>,
+) -> Result<String, Box<dyn Error + Send + Sync>> {
+    fetch_page(url, timeouts, proxy, cache)
+        .await
+        .map(|page| page.html)
+}
+
+/// [`fetch`], also reporting the URL the page came from after redirects.
+/// Cached and page scriber pages report `url` itself.
+async fn fetch_page(
+    url: &str,
+    timeouts: &Timeouts,
+    proxy: Option<&str>,
+    cache: Option<&Cache>,
+) -> Result<FetchedPage, Box<dyn Error + Send + Sync>> {
+    let page = |html: String| FetchedPage {
+        url: url.to_string(),
+        html,
+    };
+    if let Some(html) = cache.and_then(|c| c.get_page(url)) {
+        return Ok(page(html));
+    }
+
     let page_scriber_config = load_config()
         .ok()
         .map(|c| c.page_scriber)
         .unwrap_or_default();
+    let page_scriber = || {
+        PageScriberFetcher::new(
+            page_scriber_config.url.clone(),
+            Some(timeouts.render),
+            proxy,
+        )
+    };
 
     let use_page_scriber_first = domain_in_list(url, &page_scriber_config.domains);
+    let fetched = match page_scriber().filter(|_| use_page_scriber_first) {
+        Some(fetcher) => page(fetcher.fetch(url).await?),
+        // Page scriber not configured or domain not listed — use reqwest
+        None => match RequestFetcher::new(Some(timeouts.fetch), proxy)
+            .fetch_page(url)
+            .await
+        {
+            Ok(fetched) => fetched,
+            Err(e) => match page_scriber() {
+                Some(fetcher) => page(fetcher.fetch(url).await.map_err(|_| e)?),
+                None => return Err(e),
+            },
+        },
+    };
+
+    if let Some(cache) = cache {
+        cache.put_page(url, &fetched.html);
+    }
+    Ok(fetched)
+}
+
+/// Try the structured extractors (registered → site adapters → built-in
+/// site extractor → JSON-LD → MicroData → HtmlClass), then
+/// TextExtractor (LLM) on the page's text, topped up from Open Graph tags.
+/// The `source` is the page's canonical URL without tracking parameters.
+pub(crate) async fn extract_from_html(
+    html: &str,
+    url: &str,
+    llm: Llm<'_>,
+) -> Result<Extraction, Box<dyn Error + Send + Sync>> {
+    let mut extraction = extract_page(html, url, llm).await?;
+    let source = page_source(html, url);
+    if source != url {
+        extraction.components = with_source(extraction.components, url, &source);
+    }
+    Ok(extraction)
+}
 
-    // Step 1: Fetch HTML — either via page scriber (for listed domains) or reqwest
-    let (html_result, used_page_scriber) = if use_page_scriber_first {
-        match PageScriberFetcher::new(page_scriber_config.url.clone()) {
-            Some(fetcher) => (fetcher.fetch(url).await, true),
-            None => {
-                // Page scriber not configured despite domain being listed — fall back to reqwest
-                let fetcher = RequestFetcher::new(Some(Duration::from_secs(30)));
-                (fetcher.fetch(url).await, false)
+async fn extract_page(
+    html: &str,
+    url: &str,
+    llm: Llm<'_>,
+) -> Result<Extraction, Box<dyn Error + Send + Sync>> {
+    match try_structured_extractors(html, url).await {
+        Ok(extraction) => Ok(extraction),
+        Err(diagnostics) => {
+            // Only worth walking the whole page when something reads it
+            let plain_text = match llm {
+                Llm::Unavailable(_) => String::new(),
+                _ => extract_text_from_html(html),
+            };
+            let page = OpenGraphExtractor
+                .parse_page(&ParsingContext {
+                    url: url.to_string(),
+                    document: Html::parse_document(html),
+                    texts: None,
+                })
+                .ok();
+            let mut extraction = text_extraction(&plain_text, url, llm, diagnostics).await?;
+            if let Some(page) = page {
+                extraction.components = with_page_metadata(extraction.components, &page);
             }
+            Ok(extraction)
         }
-    } else {
-        let fetcher = RequestFetcher::new(Some(Duration::from_secs(30)));
-        (fetcher.fetch(url).await, false)
-    };
+    }
+}
 
-    // Step 2: If we got HTML, try structured extractors
-    if let Ok(html_content) = &html_result {
-        if let Some(components) = try_structured_extractors(html_content, url) {
-            return Ok(components);
+/// The `source:` of a page: its canonical URL without tracking parameters,
+/// or the snapshot itself for a Wayback Machine copy (whose canonical link
+/// is the dead page)
+fn page_source(html: &str, url: &str) -> String {
+    if wayback::is_snapshot(url) {
+        return url.to_string();
+    }
+    normalize::source_url(html, url)
+}
+
+/// Replace the fetched URL wherever an extractor stored it as the source
+/// (under any `[metadata.map]` name) with `source`
+fn with_source(mut components: RecipeComponents, fetched: &str, source: &str) -> RecipeComponents {
+    let mut metadata: serde_yaml::Mapping =
+        serde_yaml::from_str(&components.metadata).unwrap_or_default();
+    let mut replaced = false;
+    for (_, value) in metadata.iter_mut() {
+        if value.as_str() == Some(fetched) {
+            *value = serde_yaml::Value::String(source.to_string());
+            replaced = true;
         }
     }
+    if replaced {
+        components.metadata = serde_yaml::to_string(&metadata).unwrap_or_default();
+    }
+    components
+}
 
-    // Step 3: If reqwest failed, auto-fallback to page scriber
-    if !used_page_scriber && html_result.is_err() {
-        if let Some(fetcher) = PageScriberFetcher::new(page_scriber_config.url.clone()) {
-            if let Ok(html_content) = fetcher.fetch(url).await {
-                if let Some(components) = try_structured_extractors(&html_content, url) {
-                    return Ok(components);
-                }
-                // Structured extractors failed on page scriber HTML — try LLM
-                if TextExtractor::is_available() {
-                    let plain_text = extract_text_from_html(&html_content);
-                    return TextExtractor::extract(&plain_text, url).await;
-                }
-            }
+/// Fill in the name and any metadata keys text extraction left out from the
+/// page's Open Graph tags (or a social post). What the text extractor found
+/// is kept.
+fn with_page_metadata(
+    mut components: RecipeComponents,
+    page: &crate::model::Recipe,
+) -> RecipeComponents {
+    let page = recipe_to_components(page);
+    if components.name.is_empty() {
+        components.name = page.name;
+    }
+
+    let mut metadata: serde_yaml::Mapping =
+        serde_yaml::from_str(&components.metadata).unwrap_or_default();
+    let page_metadata: serde_yaml::Mapping =
+        serde_yaml::from_str(&page.metadata).unwrap_or_default();
+    for (key, value) in page_metadata {
+        if !metadata.contains_key(&key) {
+            metadata.insert(key, value);
         }
     }
+    if !metadata.is_empty() {
+        components.metadata = serde_yaml::to_string(&metadata).unwrap_or_default();
+    }
+    components
+}
 
-    // Step 4: Final fallback — LLM text extraction from whatever HTML we have
-    let html_content = html_result?;
+/// A social media post's caption through LLM text extraction (or as is when
+/// the LLM is off), with the author and thumbnail as metadata
+async fn social_extraction(
+    url: &str,
+    platform: Platform,
+    timeouts: &Timeouts,
+    proxy: Option<&str>,
+    llm: Llm<'_>,
+) -> Result<Extraction, Box<dyn Error + Send + Sync>> {
+    let post = social::fetch_post(url, platform, timeouts.fetch, proxy).await?;
+    let components = match llm {
+        Llm::Extract(extractor) => extractor.extract(&post.caption, url).await?,
+        Llm::Unavailable(reason) => {
+            return Err(format!(
+                "{:?} captions need LLM text extraction, which is unavailable: {}",
+                platform, reason
+            )
+            .into());
+        }
+        Llm::Off => RecipeComponents {
+            text: post.caption.clone(),
+            ..RecipeComponents::default()
+        },
+    };
 
-    if !TextExtractor::is_available() {
-        return Err("No recipe found on page. Structured data extractors failed and LLM extraction is not configured.".into());
+    let mut metadata = HashMap::new();
+    metadata.insert(
+        MetadataKey::Source.to_string(),
+        normalize::strip_tracking_params(url),
+    );
+    if let Some(author) = post.author {
+        metadata.insert(MetadataKey::Author.to_string(), author);
     }
+    let page = crate::model::Recipe {
+        image: post.image.into_iter().collect(),
+        metadata,
+        ..Default::default()
+    };
+    Ok(Extraction {
+        components: with_page_metadata(components, &page),
+        extractor: None,
+        dropped_fields: Vec::new(),
+        url: None,
+    })
+}
 
-    let plain_text = extract_text_from_html(&html_content);
-    TextExtractor::extract(&plain_text, url).await
+/// LLM text extraction, or just the plain text when the LLM is off.
+/// If the LLM finds no recipe either, the diagnostics are returned.
+async fn text_extraction(
+    plain_text: &str,
+    url: &str,
+    llm: Llm<'_>,
+    mut diagnostics: Box<ExtractionDiagnostics>,
+) -> Result<Extraction, Box<dyn Error + Send + Sync>> {
+    let components = match llm {
+        Llm::Extract(extractor) => match extractor.extract(plain_text, url).await {
+            Ok(components) => components,
+            Err(e) => {
+                diagnostics.llm_error = Some(e.to_string());
+                return Err(diagnostics);
+            }
+        },
+        Llm::Unavailable(reason) => {
+            diagnostics.llm_error = Some(format!("not configured ({})", reason));
+            return Err(diagnostics);
+        }
+        Llm::Off => RecipeComponents {
+            text: plain_text.to_string(),
+            ..RecipeComponents::default()
+        },
+    };
+    Ok(Extraction {
+        components,
+        extractor: None,
+        dropped_fields: Vec::new(),
+        url: None,
+    })
 }
 
-/// Try all structured extractors on HTML content.
-/// Returns Some(RecipeComponents) if any extractor succeeds, None otherwise.
-fn try_structured_extractors(html_content: &str, url: &str) -> Option<RecipeComponents> {
-    let document = Html::parse_document(html_content);
+/// Try all structured extractors on HTML content, starting with the ones
+/// added by `register_extractor`, the configured site adapters and the
+/// built-in extractor for the URL's domain.
+///
+/// The extractors run concurrently, each on its own task sharing the one
+/// parsed page, but the earliest one in that order that finds a recipe
+/// wins: a result is returned as soon as every extractor ahead of it has
+/// failed, and the rest are cancelled. Reading the page takes turns, but a
+/// registered extractor waiting on an API doesn't hold up the others, so it
+/// costs the time of the slowest extractor that has to run, not the sum of
+/// all of them.
+///
+/// When JSON-LD is the first extractor to try, it first reads just the
+/// page's JSON-LD blocks, and the whole page is only parsed if they hold no
+/// recipe.
+/// Returns the first extractor's result, or diagnostics if all of them fail.
+pub(crate) async fn try_structured_extractors(
+    html_content: &str,
+    url: &str,
+) -> Result<Extraction, Box<ExtractionDiagnostics>> {
+    let mut extractors: Vec<Arc<dyn Extractor>> = registered_extractors()
+        .into_iter()
+        .map(|extractor| extractor as Arc<dyn Extractor>)
+        .collect();
+    for adapter in site_adapters(url) {
+        extractors.push(Arc::new(adapter));
+    }
+    if let Some(site) = SiteExtractor::for_url(url) {
+        extractors.push(Arc::new(site));
+    }
 
-    let context = ParsingContext {
+    if extractors.is_empty() {
+        if let Some(extraction) = prescanned_json_ld(html_content, url) {
+            return Ok(extraction);
+        }
+    }
+
+    let context = SharedContext::new(ParsingContext {
         url: url.to_string(),
-        document,
+        document: Html::parse_document(html_content),
         texts: None,
-    };
+    });
+
+    extractors.push(Arc::new(JsonLdExtractor));
+    extractors.push(Arc::new(MicroDataExtractor));
+    extractors.push(Arc::new(HtmlClassExtractor));
+
+    let mut tasks = JoinSet::new();
+    let mut ids = HashMap::new();
+    for (i, extractor) in extractors.iter().enumerate() {
+        let extractor = Arc::clone(extractor);
+        let context = context.clone();
+        let span = debug_span!("extractor", name = extractor.name());
+        let task = tasks.spawn(
+            async move {
+                let result = extractor.parse(&context).await;
+                match &result {
+                    Ok(_) => debug!("Recipe found"),
+                    Err(e) => debug!("No recipe: {}", e),
+                }
+                (i, result.map_err(|e| e.to_string()))
+            }
+            .instrument(span),
+        );
+        ids.insert(task.id(), i);
+    }
+
+    let mut results: Vec<Option<Result<Recipe, String>>> = vec![None; extractors.len()];
+    while let Some(joined) = tasks.join_next().await {
+        let (i, result) = match joined {
+            Ok(done) => done,
+            // A panicking extractor counts as a failed one
+            Err(e) => (ids[&e.id()], Err(format!("extractor panicked: {}", e))),
+        };
+        results[i] = Some(result);
 
-    let extractors: Vec<Box<dyn Extractor>> = vec![
-        Box::new(JsonLdExtractor),
-        Box::new(MicroDataExtractor),
-        Box::new(HtmlClassExtractor),
-    ];
+        // The first extractor in order that is still running or succeeded
+        // decides; once it succeeded nothing behind it matters
+        if let Some(Some(Ok(_))) = results
+            .iter()
+            .find(|result| !matches!(result, Some(Err(_))))
+        {
+            break;
+        }
+    }
+    tasks.abort_all();
 
-    for extractor in extractors {
-        if let Ok(recipe) = extractor.parse(&context) {
-            return Some(recipe_to_components(&recipe));
+    let mut errors = Vec::new();
+    for (extractor, result) in extractors.iter().zip(results) {
+        match result {
+            Some(Ok(recipe)) => {
+                return Ok(Extraction {
+                    components: recipe_to_components(&recipe),
+                    extractor: Some(extractor.name()),
+                    dropped_fields: recipe.dropped_fields,
+                    url: None,
+                });
+            }
+            Some(Err(e)) => errors.push((extractor.name().to_string(), e)),
+            None => {}
         }
     }
 
-    None
+    Err(Box::new(context.read(|context| {
+        ExtractionDiagnostics::new(&context.document, url, errors)
+    })))
+}
+
+/// The recipe in the page's JSON-LD, read without parsing the rest of the page
+fn prescanned_json_ld(html: &str, url: &str) -> Option<Extraction> {
+    let context = ParsingContext {
+        url: url.to_string(),
+        document: prescan::json_ld_document(html)?,
+        texts: None,
+    };
+    let recipe = JsonLdExtractor.parse_page(&context).ok()?;
+    debug!("Recipe found in pre-scanned JSON-LD");
+    Some(Extraction {
+        components: recipe_to_components(&recipe),
+        extractor: Some(JsonLdExtractor.name()),
+        dropped_fields: recipe.dropped_fields,
+        url: None,
+    })
+}
+
+/// Site adapters from `[extractors] adapters` that are for `url`. Files that
+/// can't be read are skipped with a warning.
+fn site_adapters(url: &str) -> Vec<SiteAdapter> {
+    let paths = load_config()
+        .map(|c| c.extractors.adapters)
+        .unwrap_or_default();
+    paths
+        .iter()
+        .filter_map(|path| match SiteAdapter::from_file(path) {
+            Ok(adapter) => Some(adapter),
+            Err(e) => {
+                warn!("Skipping site adapter {}: {}", path, e);
+                None
+            }
+        })
+        .filter(|adapter| adapter.matches(url))
+        .collect()
 }
 
 /// Convert a Recipe to RecipeComponents
-fn recipe_to_components(recipe: &crate::model::Recipe) -> RecipeComponents {
+pub(crate) fn recipe_to_components(recipe: &crate::model::Recipe) -> RecipeComponents {
     // Build text from ingredients and instructions
     let mut text = String::new();
     for ingredient in &recipe.ingredients {
@@ -113,18 +629,24 @@ fn recipe_to_components(recipe: &crate::model::Recipe) -> RecipeComponents {
         text.push('\n');
     }
     text.push_str(recipe.instructions.trim_start());
+    // Equipment goes last, out of the way of the ingredient list
+    if !recipe.equipment.is_empty() {
+        text.push_str("\n\n");
+        text.push_str(&super::equipment::block(&recipe.equipment));
+    }
 
     // Build metadata YAML (without --- delimiters)
     let mut entries = Vec::new();
     if let Some(desc) = &recipe.description {
-        entries.push(("description".to_string(), desc.clone()));
+        entries.push((MetadataKey::Description.to_string(), desc.clone()));
     }
     // Only use the first image if multiple are available
     if let Some(first_image) = recipe.image.first() {
-        entries.push(("image".to_string(), first_image.clone()));
+        entries.push((MetadataKey::Image.to_string(), first_image.clone()));
     }
+    // One spelling per key, whichever extractor ran
     for (key, value) in &recipe.metadata {
-        entries.push((key.clone(), value.clone()));
+        entries.push((MetadataKey::canonical(key), value.clone()));
     }
 
     RecipeComponents {
@@ -136,16 +658,24 @@ fn recipe_to_components(recipe: &crate::model::Recipe) -> RecipeComponents {
 
 /// Simple text extraction from HTML
 ///
-/// Extracts all text content from the <body> element.
+/// Extracts all text content from the <body> element, leaving out scripts,
+/// styles and templates, which on large pages can be most of it.
 /// This is a basic fallback when structured extractors fail.
-fn extract_text_from_html(html: &str) -> String {
+pub(crate) fn extract_text_from_html(html: &str) -> String {
     let document = Html::parse_document(html);
     let selector = scraper::Selector::parse("body").unwrap();
-    document
-        .select(&selector)
-        .next()
-        .map(|el| el.text().collect::<Vec<_>>().join(" "))
-        .unwrap_or_default()
+    let Some(body) = document.select(&selector).next() else {
+        return String::new();
+    };
+    body.descendants()
+        .filter_map(|node| {
+            let text = node.value().as_text()?;
+            let parent = node.parent()?.value().as_element()?;
+            (!matches!(parent.name(), "script" | "style" | "noscript" | "template"))
+                .then_some(&**text)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 /// Check if a URL's domain matches any domain in the list (suffix-matched).
@@ -184,6 +714,81 @@ mod tests {
         assert!(text.contains("Some instructions"));
     }
 
+    #[test]
+    fn test_extract_text_skips_scripts() {
+        let html = r#"<html><body>
+            <p>Mix the flour</p>
+            <script>window.__STATE__ = {"huge": true};</script>
+            <style>p { color: red; }</style>
+            <noscript>Enable JavaScript</noscript>
+        </body></html>"#;
+
+        let text = extract_text_from_html(html);
+        assert!(text.contains("Mix the flour"));
+        assert!(!text.contains("__STATE__"));
+        assert!(!text.contains("color"));
+        assert!(!text.contains("Enable JavaScript"));
+    }
+
+    #[tokio::test]
+    async fn test_json_ld_without_full_parse() {
+        // Found from the JSON-LD blocks alone, whatever follows them
+        let html = r#"<html><head><script type="application/ld+json">
+            {"@type": "Recipe", "name": "Soup", "recipeIngredient": ["1 onion"],
+             "recipeInstructions": "Simmer."}
+            </script></head><body><div><table>"#;
+        let extraction = try_structured_extractors(html, "https://example.com/soup")
+            .await
+            .unwrap();
+        assert_eq!(extraction.extractor, Some("json-ld"));
+        assert_eq!(extraction.components.name, "Soup");
+        assert!(extraction.components.text.contains("1 onion"));
+    }
+
+    #[test]
+    fn test_with_page_metadata_fills_gaps() {
+        let page = crate::model::Recipe {
+            name: "Tomato Soup".to_string(),
+            image: vec!["https://example.com/soup.jpg".to_string()],
+            metadata: [
+                ("source".to_string(), "https://example.com/soup".to_string()),
+                ("author".to_string(), "Jane Doe".to_string()),
+            ]
+            .into(),
+            ..Default::default()
+        };
+        let components = RecipeComponents {
+            text: "2 tomatoes\n\nSimmer.".to_string(),
+            metadata: "source: https://example.com/soup?ref=feed\nservings: '4'\n".to_string(),
+            name: String::new(),
+        };
+
+        let components = with_page_metadata(components, &page);
+        assert_eq!(components.name, "Tomato Soup");
+        assert_eq!(components.text, "2 tomatoes\n\nSimmer.");
+        assert!(components
+            .metadata
+            .contains("source: https://example.com/soup?ref=feed"));
+        assert!(components.metadata.contains("servings: '4'"));
+        assert!(components.metadata.contains("author: Jane Doe"));
+        assert!(components
+            .metadata
+            .contains("image: https://example.com/soup.jpg"));
+    }
+
+    #[test]
+    fn test_page_source_keeps_wayback_snapshot() {
+        let html = r#"<html><head>
+            <link rel="canonical" href="https://example.com/soup?utm_source=feed">
+        </head></html>"#;
+        assert_eq!(
+            page_source(html, "https://example.com/soup-amp"),
+            "https://example.com/soup"
+        );
+        let snapshot = "https://web.archive.org/web/20190101120000/https://example.com/soup";
+        assert_eq!(page_source(html, snapshot), snapshot);
+    }
+
     #[test]
     fn test_domain_matches_exact() {
         let domains = vec!["seriouseats.com".to_string()];
@@ -0,0 +1,181 @@
+//! Where an imported recipe came from, recorded in its frontmatter
+//!
+//! With provenance on, every recipe gets an `import:` block saying what was
+//! imported, when, how the recipe was found and which provider, model and
+//! prompt converted it, so an import can be audited or repeated later:
+//!
+//! ```yaml
+//! import:
+//!   source: https://example.com/pancakes
+//!   date: 2026-10-16T09:30:00Z
+//!   extractor: json-ld
+//!   provider: anthropic
+//!   model: claude-sonnet-4-5
+//!   prompt: 4f1c2a9e0b7d3c58
+//!   version: 0.9.9
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Key of the provenance block in the frontmatter
+pub const KEY: &str = "import";
+
+/// The provenance of one imported recipe; fields that don't apply (no
+/// extractor for OCR, no provider for `extract_only`) are left out
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Provenance {
+    /// URL or file the recipe was imported from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// When it was imported, in UTC (`2026-10-16T09:30:00Z`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    /// How the recipe was found, as from
+    /// [`build_with_extractor`](crate::RecipeImporterBuilder::build_with_extractor)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extractor: Option<String>,
+    /// Provider that converted the recipe
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// Model that converted the recipe
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Hash of the conversion prompt, which changes with the template,
+    /// style notes, examples and target language
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    /// cooklang-import version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+impl Provenance {
+    /// Provenance for a recipe imported now by this version
+    pub fn now() -> Self {
+        Provenance {
+            date: Some(utc_timestamp(SystemTime::now())),
+            version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            ..Provenance::default()
+        }
+    }
+
+    /// The `import:` block, as YAML lines for the frontmatter
+    pub fn to_yaml(&self) -> String {
+        let mut block = serde_yaml::Mapping::new();
+        block.insert(
+            KEY.into(),
+            serde_yaml::to_value(self).unwrap_or(serde_yaml::Value::Null),
+        );
+        serde_yaml::to_string(&block).unwrap_or_default()
+    }
+
+    /// The `import:` block of a recipe file's frontmatter, if it has one
+    pub fn from_cooklang(cooklang: &str) -> Option<Self> {
+        serde_yaml::from_value(frontmatter(cooklang)?.get(KEY)?.clone()).ok()
+    }
+}
+
+/// Where a recipe file was imported from: `source` in its `import:` block,
+/// or else the page's own `source:` field
+pub fn recipe_source(cooklang: &str) -> Option<String> {
+    Provenance::from_cooklang(cooklang)
+        .and_then(|provenance| provenance.source)
+        .or_else(|| {
+            frontmatter(cooklang)?
+                .get("source")?
+                .as_str()
+                .map(str::to_string)
+        })
+        .filter(|source| !source.trim().is_empty())
+}
+
+/// A recipe file's YAML frontmatter
+fn frontmatter(cooklang: &str) -> Option<serde_yaml::Value> {
+    serde_yaml::from_str(crate::model::split_frontmatter(cooklang).0?).ok()
+}
+
+/// `time` as an ISO 8601 UTC timestamp, to the second
+pub fn utc_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (days, secs) = (secs / 86_400, secs % 86_400);
+    let (year, month, day) = civil_date(days);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs / 3_600,
+        secs % 3_600 / 60,
+        secs % 60
+    )
+}
+
+/// Days since 1970-01-01 to a civil date (proleptic Gregorian calendar):
+/// year, month (1-12) and day of the month (1-31)
+pub(crate) fn civil_date(days: u64) -> (i64, u64, u64) {
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month as u64, day as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_utc_timestamp() {
+        assert_eq!(utc_timestamp(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        let time = UNIX_EPOCH + Duration::from_secs(1_709_208_000 + 3_723);
+        assert_eq!(utc_timestamp(time), "2024-02-29T13:02:03Z");
+        let time = UNIX_EPOCH + Duration::from_secs(1_798_761_599);
+        assert_eq!(utc_timestamp(time), "2026-12-31T23:59:59Z");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let provenance = Provenance {
+            source: Some("https://example.com/pancakes".to_string()),
+            date: Some("2026-10-16T09:30:00Z".to_string()),
+            extractor: Some("json-ld".to_string()),
+            provider: Some("anthropic".to_string()),
+            ..Provenance::default()
+        };
+        let yaml = provenance.to_yaml();
+        assert_eq!(
+            yaml,
+            "import:\n  source: https://example.com/pancakes\n  date: 2026-10-16T09:30:00Z\n  \
+             extractor: json-ld\n  provider: anthropic\n"
+        );
+        let recipe = format!("---\ntitle: Pancakes\n{}---\n\nMix @flour{{}}.", yaml);
+        assert_eq!(Provenance::from_cooklang(&recipe), Some(provenance));
+        assert_eq!(Provenance::from_cooklang("Mix @flour{}."), None);
+        assert_eq!(
+            recipe_source(&recipe).as_deref(),
+            Some("https://example.com/pancakes")
+        );
+    }
+
+    #[test]
+    fn test_recipe_source_from_frontmatter() {
+        let recipe = "---\ntitle: Soup\nsource: https://example.com/soup\n---\n\nSimmer.";
+        assert_eq!(
+            recipe_source(recipe).as_deref(),
+            Some("https://example.com/soup")
+        );
+        assert_eq!(recipe_source("---\ntitle: Soup\n---\n\nSimmer."), None);
+    }
+}
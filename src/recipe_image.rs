@@ -0,0 +1,200 @@
+//! Saving recipe photos next to their recipes (`image` feature)
+//!
+//! Photos are decoded, turned upright, scaled down to
+//! `[images] max_dimension` and encoded again as JPEG or WebP. Nothing from
+//! the original file but the pixels is written, so EXIF data such as GPS
+//! coordinates and camera serial numbers never reaches the collection.
+
+use crate::config::{ImageFormat, ImagesConfig};
+use crate::ImportError;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageDecoder, ImageReader};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Photos larger than this are refused rather than downloaded
+const MAX_DOWNLOAD_BYTES: usize = 20 * 1024 * 1024;
+
+/// Download the photo at `url`, process it and save it next to the recipe at
+/// `cook_path` with the same name ("Pancakes.cook" → "Pancakes.jpg"), the
+/// way Cooklang apps look for recipe photos. Returns the photo's path.
+pub async fn save(
+    url: &str,
+    cook_path: &Path,
+    config: &ImagesConfig,
+    timeout: Duration,
+    proxy: Option<&str>,
+) -> Result<PathBuf, ImportError> {
+    let bytes = download(url, timeout, proxy).await?;
+    let processed = process(&bytes, config)?;
+    let path = cook_path.with_extension(config.format.extension());
+    tokio::fs::write(&path, processed).await?;
+    Ok(path)
+}
+
+/// Fetch a photo's bytes
+pub async fn download(
+    url: &str,
+    timeout: Duration,
+    proxy: Option<&str>,
+) -> Result<Vec<u8>, ImportError> {
+    let response = crate::http::client(url, timeout, proxy)?
+        .get(url)
+        .headers(crate::http::browser_headers())
+        .send()
+        .await?
+        .error_for_status()?;
+    let too_large = || {
+        ImportError::ImageError(format!(
+            "{} is larger than {} MB",
+            url,
+            MAX_DOWNLOAD_BYTES / 1024 / 1024
+        ))
+    };
+    if response
+        .content_length()
+        .is_some_and(|length| length as usize > MAX_DOWNLOAD_BYTES)
+    {
+        return Err(too_large());
+    }
+    let bytes = response.bytes().await?;
+    if bytes.len() > MAX_DOWNLOAD_BYTES {
+        return Err(too_large());
+    }
+    Ok(bytes.to_vec())
+}
+
+/// Re-encode a photo (JPEG, PNG, WebP or GIF) as `config` says: upright,
+/// at most `max_dimension` pixels on its longest side and without metadata
+pub fn process(bytes: &[u8], config: &ImagesConfig) -> Result<Vec<u8>, ImportError> {
+    let image_error = |e: image::ImageError| ImportError::ImageError(e.to_string());
+    let mut decoder = ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()?
+        .into_decoder()
+        .map_err(image_error)?;
+    // Phones store portrait photos sideways with an EXIF rotation; apply it
+    // before the EXIF data is dropped
+    let orientation = decoder.orientation().map_err(image_error)?;
+    let mut image = DynamicImage::from_decoder(decoder).map_err(image_error)?;
+    image.apply_orientation(orientation);
+
+    let max = config.max_dimension.max(1);
+    if image.width() > max || image.height() > max {
+        image = image.resize(max, max, FilterType::Lanczos3);
+    }
+
+    let mut output = Vec::new();
+    match config.format {
+        ImageFormat::Jpeg => {
+            // JPEG has no alpha channel
+            let encoder = JpegEncoder::new_with_quality(&mut output, config.quality.clamp(1, 100));
+            DynamicImage::ImageRgb8(image.to_rgb8())
+                .write_with_encoder(encoder)
+                .map_err(image_error)?;
+        }
+        ImageFormat::Webp => {
+            let encoder = WebPEncoder::new_lossless(&mut output);
+            DynamicImage::ImageRgba8(image.to_rgba8())
+                .write_with_encoder(encoder)
+                .map_err(image_error)?;
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::RgbImage;
+
+    fn jpeg(width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        DynamicImage::ImageRgb8(RgbImage::new(width, height))
+            .write_with_encoder(JpegEncoder::new(&mut bytes))
+            .unwrap();
+        bytes
+    }
+
+    /// `jpeg` with an EXIF segment holding a GPS-like marker
+    fn jpeg_with_exif(width: u32, height: u32) -> Vec<u8> {
+        let bytes = jpeg(width, height);
+        let mut payload = b"Exif\0\0MM\0*\0\0\0\x08\0\0\0\0\0\0".to_vec();
+        payload.extend_from_slice(b"GPS 51.5N 0.1W");
+        let mut exif = vec![0xFF, 0xE1];
+        exif.extend_from_slice(&((payload.len() + 2) as u16).to_be_bytes());
+        exif.extend(payload);
+        [&bytes[..2], &exif, &bytes[2..]].concat()
+    }
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack
+            .windows(needle.len())
+            .any(|window| window == needle)
+    }
+
+    #[test]
+    fn test_process_strips_exif_and_resizes() {
+        let original = jpeg_with_exif(400, 200);
+        assert!(contains(&original, b"GPS"));
+        let config = ImagesConfig {
+            max_dimension: 100,
+            ..ImagesConfig::default()
+        };
+
+        let processed = process(&original, &config).unwrap();
+        assert!(!contains(&processed, b"Exif"));
+        assert!(!contains(&processed, b"GPS"));
+        let image = image::load_from_memory(&processed).unwrap();
+        assert_eq!((image.width(), image.height()), (100, 50));
+    }
+
+    #[test]
+    fn test_process_webp_keeps_small_images() {
+        let config = ImagesConfig {
+            format: ImageFormat::Webp,
+            ..ImagesConfig::default()
+        };
+        let processed = process(&jpeg(40, 30), &config).unwrap();
+        assert_eq!(
+            image::guess_format(&processed).unwrap(),
+            image::ImageFormat::WebP
+        );
+        let image = image::load_from_memory(&processed).unwrap();
+        assert_eq!((image.width(), image.height()), (40, 30));
+
+        assert!(matches!(
+            process(b"not an image", &config),
+            Err(ImportError::ImageError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_save_next_to_recipe() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("GET", "/pancakes.jpg")
+            .with_status(200)
+            .with_header("content-type", "image/jpeg")
+            .with_body(jpeg_with_exif(64, 48))
+            .create_async()
+            .await;
+        let dir = std::env::temp_dir().join(format!("cooklang-image-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let path = save(
+            &format!("{}/pancakes.jpg", server.url()),
+            &dir.join("Pancakes.cook"),
+            &ImagesConfig::default(),
+            Duration::from_secs(5),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(path, dir.join("Pancakes.jpg"));
+        assert!(!contains(&std::fs::read(&path).unwrap(), b"Exif"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
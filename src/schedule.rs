@@ -0,0 +1,794 @@
+//! Scheduled batch imports for the daemon
+//!
+//! With a `[schedule]` section in config.toml, `cooklang-import daemon` also
+//! imports on a timer: the recipes linked from `feed_urls` when the `feeds`
+//! cron expression fires, and the files in `folder_paths` when `folders`
+//! does. Recipes are saved as "<Title>.cook" in `output_dir`, with a
+//! [`Manifest`] there so each source is imported once. A job that is still
+//! running when it's due again is skipped rather than started twice, and
+//! each run's [`Summary`] is POSTed as JSON to `webhook`.
+//!
+//! ```toml
+//! [schedule]
+//! feeds = "0 6 * * *"
+//! feed_urls = ["https://example.com/recipes/new"]
+//! output_dir = "/home/me/recipes"
+//! webhook = "https://hooks.example.com/recipes"
+//! ```
+
+use serde::Serialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::{Mutex, OwnedMutexGuard};
+use tracing::{info, warn};
+
+use crate::config::{load_config, BelowQuality, ScheduleConfig};
+use crate::manifest::{ImportStatus, Manifest, ManifestEntry};
+use crate::pipelines::html::{SavedPage, PAGE_EXTENSIONS};
+use crate::pipelines::Timeouts;
+use crate::provenance::{civil_date, utc_timestamp};
+use crate::{
+    discover_recipe_links, stages, ImportError, ImportResult, RecipeImporter,
+    RecipeImporterBuilder, TitleStyle,
+};
+
+/// Manifest file in `output_dir`, named as the CLI's batch imports name it
+pub const MANIFEST: &str = "cooklang-import-manifest.json";
+
+/// How far ahead to look for the next time a schedule fires: long enough
+/// for "29 February on a Monday"
+const MAX_DAYS: u64 = 366 * 28;
+
+const MINUTES_PER_DAY: u64 = 24 * 60;
+
+/// A batch import that can be scheduled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Job {
+    /// The recipes linked from `feed_urls`
+    Feeds,
+    /// The files in `folder_paths`
+    Folders,
+}
+
+impl fmt::Display for Job {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Job::Feeds => write!(f, "feeds"),
+            Job::Folders => write!(f, "folders"),
+        }
+    }
+}
+
+/// When a job runs: a 5-field cron expression (minute hour day-of-month
+/// month day-of-week, Sunday being 0 or 7) in UTC
+///
+/// Each field is `*`, a number, a range `a-b`, any of those with a step
+/// (`*/15`, `0-30/10`), or a comma-separated list of them. As in cron, when
+/// both the day of the month and the day of the week are restricted, a day
+/// matching either one fires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self, ImportError> {
+        let invalid = |why: String| {
+            ImportError::ConfigError(config::ConfigError::Message(format!(
+                "Invalid schedule \"{}\": {}",
+                expression, why
+            )))
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            return Err(invalid(format!(
+                "expected 5 fields (minute hour day month weekday), found {}",
+                fields.len()
+            )));
+        };
+        let mut weekdays = field(weekdays, 0, 7).map_err(invalid)?;
+        // 7 is Sunday too
+        if weekdays & 1 << 7 != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(CronSchedule {
+            minutes: field(minutes, 0, 59).map_err(invalid)?,
+            hours: field(hours, 0, 23).map_err(invalid)?,
+            days: field(days, 1, 31).map_err(invalid)?,
+            months: field(months, 1, 12).map_err(invalid)?,
+            weekdays,
+            any_day: days == "*",
+            any_weekday: fields[4] == "*",
+        })
+    }
+
+    /// The first minute after `time` this schedule fires at, or `None` if it
+    /// never does (such as on 31 February)
+    pub fn next_after(&self, time: SystemTime) -> Option<SystemTime> {
+        let start = time.duration_since(UNIX_EPOCH).ok()?.as_secs() / 60 + 1;
+        let first_day = start / MINUTES_PER_DAY;
+        (first_day..first_day + MAX_DAYS)
+            .filter(|&day| self.fires_on(day))
+            .find_map(|day| {
+                let from = if day == first_day {
+                    start % MINUTES_PER_DAY
+                } else {
+                    0
+                };
+                (from..MINUTES_PER_DAY)
+                    .find(|minute| {
+                        self.hours & 1 << (minute / 60) != 0
+                            && self.minutes & 1 << (minute % 60) != 0
+                    })
+                    .map(|minute| {
+                        UNIX_EPOCH + Duration::from_secs((day * MINUTES_PER_DAY + minute) * 60)
+                    })
+            })
+    }
+
+    /// Whether the schedule fires on `day`, counted from 1970-01-01
+    fn fires_on(&self, day: u64) -> bool {
+        let (_, month, day_of_month) = civil_date(day);
+        // 1970-01-01 was a Thursday
+        let weekday = (day + 4) % 7;
+        let by_date = self.days & 1 << day_of_month != 0;
+        let by_weekday = self.weekdays & 1 << weekday != 0;
+        let by_day = match (self.any_day, self.any_weekday) {
+            (false, false) => by_date || by_weekday,
+            _ => by_date && by_weekday,
+        };
+        self.months & 1 << month != 0 && by_day
+    }
+}
+
+/// The values a cron field allows, as bits
+fn field(text: &str, min: u64, max: u64) -> Result<u64, String> {
+    let number = |value: &str| -> Result<u64, String> {
+        match value.parse::<u64>() {
+            Ok(n) if (min..=max).contains(&n) => Ok(n),
+            _ => Err(format!(
+                "\"{}\" isn't a number from {} to {}",
+                value, min, max
+            )),
+        }
+    };
+    let mut bits = 0;
+    for item in text.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => match step.parse::<u64>() {
+                Ok(step) if step > 0 => (range, step),
+                _ => return Err(format!("\"{}\" isn't a valid step", step)),
+            },
+            None => (item, 1),
+        };
+        let (first, last) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((first, last)) => (number(first)?, number(last)?),
+            // "5/15" means from 5 to the end
+            None if step > 1 => (number(range)?, max),
+            None => (number(range)?, number(range)?),
+        };
+        if first > last {
+            return Err(format!("\"{}\" is an empty range", range));
+        }
+        for value in (first..=last).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+/// What one run of a job did, as POSTed to the webhook
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Summary {
+    pub job: Job,
+    /// ISO 8601 UTC timestamp
+    pub started_at: String,
+    pub duration_ms: u64,
+    /// Recipes saved, including those saved for review below `min_quality`
+    pub imported: usize,
+    /// Sources imported by an earlier run, and those below `min_quality`
+    /// with `below_quality = "skip"`
+    pub skipped: usize,
+    pub failed: usize,
+    pub failures: Vec<Failure>,
+}
+
+/// A source a run couldn't import
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Failure {
+    /// URL or path of the recipe, feed or folder
+    pub source: String,
+    pub error: String,
+}
+
+impl Summary {
+    fn new(job: Job) -> Self {
+        Summary {
+            job,
+            started_at: utc_timestamp(SystemTime::now()),
+            duration_ms: 0,
+            imported: 0,
+            skipped: 0,
+            failed: 0,
+            failures: Vec::new(),
+        }
+    }
+
+    fn fail(&mut self, source: impl Into<String>, error: impl ToString) {
+        self.failed += 1;
+        self.failures.push(Failure {
+            source: source.into(),
+            error: error.to_string(),
+        });
+    }
+}
+
+/// The scheduled jobs, started by the daemon
+pub struct Scheduler {
+    config: ScheduleConfig,
+    jobs: Vec<(Job, CronSchedule, Arc<Mutex<()>>)>,
+}
+
+impl Scheduler {
+    /// The jobs `config` schedules, or `None` when it schedules none. An
+    /// invalid expression or a job without `output_dir` is an error.
+    pub fn new(config: ScheduleConfig) -> Result<Option<Self>, ImportError> {
+        let mut jobs = Vec::new();
+        for (job, expression) in [(Job::Feeds, &config.feeds), (Job::Folders, &config.folders)] {
+            if let Some(expression) = expression {
+                jobs.push((job, CronSchedule::parse(expression)?, Arc::default()));
+            }
+        }
+        if jobs.is_empty() {
+            return Ok(None);
+        }
+        if config.output_dir.is_none() {
+            return Err(ImportError::ConfigError(config::ConfigError::Message(
+                "[schedule] needs an output_dir to save recipes in".to_string(),
+            )));
+        }
+        Ok(Some(Scheduler { config, jobs }))
+    }
+
+    /// The scheduler for config.toml's `[schedule]`, if it has one
+    pub fn from_config() -> Result<Option<Self>, ImportError> {
+        Scheduler::new(load_config()?.schedule)
+    }
+
+    /// Start each job when it's due, forever
+    pub async fn run(self) {
+        let mut last = SystemTime::now();
+        loop {
+            let Some(next) = self
+                .jobs
+                .iter()
+                .filter_map(|(_, schedule, _)| schedule.next_after(last))
+                .min()
+            else {
+                return;
+            };
+            let wait = next.duration_since(SystemTime::now()).unwrap_or_default();
+            tokio::time::sleep(wait).await;
+            for (job, schedule, _) in &self.jobs {
+                if schedule.next_after(last) == Some(next) {
+                    self.start(*job);
+                }
+            }
+            last = next;
+        }
+    }
+
+    /// Run `job` in the background, unless its last run is still going
+    fn start(&self, job: Job) {
+        let Some(guard) = self.try_lock(job) else {
+            warn!(
+                "Skipping the scheduled {} import: the last one is still running",
+                job
+            );
+            return;
+        };
+        let config = self.config.clone();
+        tokio::spawn(async move {
+            let summary = run_job(job, &config, |builder| builder).await;
+            drop(guard);
+            info!(
+                "Scheduled {} import: {} imported, {} skipped, {} failed",
+                job, summary.imported, summary.skipped, summary.failed
+            );
+            if let Some(webhook) = &config.webhook {
+                if let Err(e) = post_summary(webhook, &summary).await {
+                    warn!("Couldn't post the {} summary to {}: {}", job, webhook, e);
+                }
+            }
+        });
+    }
+
+    /// Hold `job` for one run; `None` while another run holds it
+    fn try_lock(&self, job: Job) -> Option<OwnedMutexGuard<()>> {
+        let (_, _, running) = self
+            .jobs
+            .iter()
+            .find(|(scheduled, _, _)| *scheduled == job)?;
+        running.clone().try_lock_owned().ok()
+    }
+}
+
+/// Run `job` once: import each source the manifest in `output_dir` doesn't
+/// list as imported yet, saving the recipes there. `configure` adjusts each
+/// import's builder.
+pub async fn run_job(
+    job: Job,
+    config: &ScheduleConfig,
+    configure: impl Fn(RecipeImporterBuilder) -> RecipeImporterBuilder,
+) -> Summary {
+    let started = Instant::now();
+    let mut summary = Summary::new(job);
+    let output = PathBuf::from(config.output_dir.as_deref().unwrap_or_default());
+    let manifest_path = output.join(MANIFEST);
+    match Manifest::load(&manifest_path) {
+        Ok(mut manifest) => {
+            let sources = match job {
+                Job::Feeds => feed_sources(&config.feed_urls, &mut summary).await,
+                Job::Folders => folder_sources(&config.folder_paths, &mut summary),
+            };
+            let quality = load_config()
+                .ok()
+                .and_then(|c| c.min_quality.map(|min| (min, c.below_quality)));
+            for source in sources {
+                let name = source.name();
+                if manifest.is_imported(&name) {
+                    summary.skipped += 1;
+                    continue;
+                }
+                let entry = import(&source, &output, quality, &configure).await;
+                match (entry.status, &entry.output) {
+                    (ImportStatus::Failed, _) => {
+                        summary.fail(&name, entry.error.as_deref().unwrap_or_default())
+                    }
+                    (ImportStatus::LowQuality, None) => summary.skipped += 1,
+                    _ => summary.imported += 1,
+                }
+                manifest.record(entry);
+                if let Err(e) = manifest.save(&manifest_path) {
+                    summary.fail(manifest_path.display().to_string(), e);
+                    break;
+                }
+            }
+        }
+        Err(e) => summary.fail(manifest_path.display().to_string(), e),
+    }
+    summary.duration_ms = started.elapsed().as_millis() as u64;
+    summary
+}
+
+/// Something a job imports
+enum Source {
+    Url(String),
+    File(PathBuf),
+}
+
+impl Source {
+    /// How the manifest lists it
+    fn name(&self) -> String {
+        match self {
+            Source::Url(url) => url.clone(),
+            Source::File(path) => path.display().to_string(),
+        }
+    }
+
+    fn builder(&self) -> Result<RecipeImporterBuilder, ImportError> {
+        let path = match self {
+            Source::Url(url) => return Ok(RecipeImporter::builder().url(url)),
+            Source::File(path) => path,
+        };
+        let builder = RecipeImporter::builder();
+        Ok(match extension(path).as_str() {
+            "md" | "markdown" => builder.markdown(std::fs::read_to_string(path)?),
+            "txt" => builder.text(std::fs::read_to_string(path)?),
+            "eml" => builder.email(std::fs::read(path)?),
+            _ => {
+                let page = SavedPage::read(path)?;
+                match page.url {
+                    Some(url) => builder.html(page.html).source_url(url),
+                    None => builder.html(page.html),
+                }
+            }
+        })
+    }
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+/// The recipe pages the feeds link to, in order; a feed that can't be
+/// fetched or links to no recipes is a failure
+async fn feed_sources(feeds: &[String], summary: &mut Summary) -> Vec<Source> {
+    let timeouts = Timeouts::from_config();
+    let mut sources = Vec::new();
+    for feed in feeds {
+        match stages::fetch(feed, &timeouts, None, None).await {
+            Ok(html) => {
+                let links = discover_recipe_links(&html, feed);
+                if links.is_empty() {
+                    summary.fail(feed, "No recipe links found");
+                }
+                sources.extend(links.into_iter().map(Source::Url));
+            }
+            Err(e) => summary.fail(feed, e),
+        }
+    }
+    sources
+}
+
+/// The importable files in the folders (not their subfolders), sorted;
+/// hidden files are left out and a folder that can't be read is a failure
+fn folder_sources(folders: &[String], summary: &mut Summary) -> Vec<Source> {
+    let mut sources = Vec::new();
+    for folder in folders {
+        let entries = match std::fs::read_dir(folder) {
+            Ok(entries) => entries,
+            Err(e) => {
+                summary.fail(folder, e);
+                continue;
+            }
+        };
+        let mut files: Vec<PathBuf> = entries
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_file()))
+            .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+            .map(|entry| entry.path())
+            .filter(|path| {
+                let extension = extension(path);
+                PAGE_EXTENSIONS.contains(&extension.as_str())
+                    || matches!(extension.as_str(), "md" | "markdown" | "txt" | "eml")
+            })
+            .collect();
+        files.sort();
+        sources.extend(files.into_iter().map(Source::File));
+    }
+    sources
+}
+
+/// Import one source as "<Title>.cook" in `output`, or below the minimum
+/// `quality` in `output/review` or nowhere, and return its manifest entry
+async fn import(
+    source: &Source,
+    output: &Path,
+    quality: Option<(f32, BelowQuality)>,
+    configure: impl Fn(RecipeImporterBuilder) -> RecipeImporterBuilder,
+) -> ManifestEntry {
+    let name = source.name();
+    let builder = match source.builder() {
+        Ok(builder) => builder,
+        Err(e) => return ManifestEntry::failed(name, e),
+    };
+    let (result, extractor) = match configure(builder)
+        .title_style(TitleStyle::Filename)
+        .build_with_extractor()
+        .await
+    {
+        Ok(imported) => imported,
+        Err(e) => return ManifestEntry::failed(name, e),
+    };
+    let below = quality.and_then(|(min, action)| result.check_quality(min).err().map(|_| action));
+    let dir = match below {
+        Some(BelowQuality::Skip) => {
+            return ManifestEntry::low_quality(name, None, &result, extractor)
+        }
+        Some(BelowQuality::Review) => output.join("review"),
+        None => output.to_path_buf(),
+    };
+    match save(&result, &dir) {
+        Ok(path) if below.is_some() => {
+            ManifestEntry::low_quality(name, Some(path), &result, extractor)
+        }
+        Ok(path) => ManifestEntry::imported(name, Some(path), &result, extractor),
+        Err(e) => ManifestEntry::failed(name, e),
+    }
+}
+
+/// Save a recipe as "<Title>.cook" in `dir`, never overwriting one that's
+/// already there, and return its path
+fn save(result: &ImportResult, dir: &Path) -> Result<String, ImportError> {
+    let (recipe, title) = match result {
+        ImportResult::Cooklang { content, title, .. } => (content.clone(), title),
+        ImportResult::Components(components) => (
+            stages::render_titled(components, &components.text, TitleStyle::Filename),
+            &components.name,
+        ),
+    };
+    let name = stages::file_name(title).ok_or_else(|| {
+        ImportError::ParseError("The recipe has no title to name its file".into())
+    })?;
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(name);
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)?;
+    std::io::Write::write_all(&mut file, recipe.as_bytes())?;
+    Ok(path.display().to_string())
+}
+
+/// POST a run's summary to the webhook
+async fn post_summary(webhook: &str, summary: &Summary) -> Result<(), ImportError> {
+    let timeouts = Timeouts::from_config();
+    let client = crate::http::client(webhook, timeouts.fetch, None)?;
+    client
+        .post(webhook)
+        .json(summary)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2026-10-16T05:59:30Z, a Friday
+    fn friday_morning() -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(1_792_130_370)
+    }
+
+    fn at(timestamp: &str, time: Option<SystemTime>) {
+        assert_eq!(time.map(utc_timestamp).as_deref(), Some(timestamp));
+    }
+
+    #[test]
+    fn test_next_after() {
+        let now = friday_morning();
+        at("2026-10-16T05:59:30Z", Some(now));
+
+        let daily = CronSchedule::parse("0 6 * * *").unwrap();
+        at("2026-10-16T06:00:00Z", daily.next_after(now));
+        let after = daily.next_after(now).unwrap();
+        at("2026-10-17T06:00:00Z", daily.next_after(after));
+
+        let half_hourly = CronSchedule::parse("*/30 * * * *").unwrap();
+        at("2026-10-16T06:00:00Z", half_hourly.next_after(now));
+        at("2026-10-16T06:30:00Z", half_hourly.next_after(after));
+
+        // Weekdays only, and 7 is Sunday
+        let weekdays = CronSchedule::parse("15 8 * * 1-5").unwrap();
+        at("2026-10-16T08:15:00Z", weekdays.next_after(now));
+        let sunday = CronSchedule::parse("0 0 * * 7").unwrap();
+        at("2026-10-18T00:00:00Z", sunday.next_after(now));
+
+        let new_year = CronSchedule::parse("0 0 1 1 *").unwrap();
+        at("2027-01-01T00:00:00Z", new_year.next_after(now));
+        let leap_day = CronSchedule::parse("0 12 29 2 *").unwrap();
+        at("2028-02-29T12:00:00Z", leap_day.next_after(now));
+    }
+
+    #[test]
+    fn test_day_of_month_or_weekday() {
+        // The 20th, or any Saturday
+        let schedule = CronSchedule::parse("0 6 20 * 6").unwrap();
+        at(
+            "2026-10-17T06:00:00Z",
+            schedule.next_after(friday_morning()),
+        );
+        let saturday = schedule.next_after(friday_morning()).unwrap();
+        at("2026-10-20T06:00:00Z", schedule.next_after(saturday));
+    }
+
+    #[test]
+    fn test_lists_and_steps() {
+        let schedule = CronSchedule::parse("5,50 7-9/2 * * *").unwrap();
+        let mut time = friday_morning();
+        let mut fired = Vec::new();
+        for _ in 0..4 {
+            time = schedule.next_after(time).unwrap();
+            fired.push(utc_timestamp(time));
+        }
+        assert_eq!(
+            fired,
+            vec![
+                "2026-10-16T07:05:00Z",
+                "2026-10-16T07:50:00Z",
+                "2026-10-16T09:05:00Z",
+                "2026-10-16T09:50:00Z",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_never_fires() {
+        let schedule = CronSchedule::parse("0 0 31 2 *").unwrap();
+        assert_eq!(schedule.next_after(friday_morning()), None);
+    }
+
+    #[test]
+    fn test_invalid_expressions() {
+        for expression in [
+            "0 6 * *",
+            "60 * * * *",
+            "* 6-2 * * *",
+            "*/0 * * * *",
+            "@daily",
+        ] {
+            let error = CronSchedule::parse(expression).unwrap_err();
+            assert!(
+                matches!(error, ImportError::ConfigError(_)),
+                "{}",
+                expression
+            );
+            assert!(error.to_string().contains(expression), "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_scheduler_config() {
+        assert!(Scheduler::new(ScheduleConfig::default()).unwrap().is_none());
+
+        let config = ScheduleConfig {
+            feeds: Some("0 6 * * *".to_string()),
+            ..Default::default()
+        };
+        assert!(Scheduler::new(config.clone()).is_err());
+
+        let scheduler = Scheduler::new(ScheduleConfig {
+            output_dir: Some("recipes".to_string()),
+            ..config
+        })
+        .unwrap()
+        .unwrap();
+        assert_eq!(scheduler.jobs.len(), 1);
+    }
+
+    #[test]
+    fn test_overlapping_run_is_skipped() {
+        let scheduler = Scheduler::new(ScheduleConfig {
+            feeds: Some("0 6 * * *".to_string()),
+            folders: Some("*/30 * * * *".to_string()),
+            output_dir: Some("recipes".to_string()),
+            ..Default::default()
+        })
+        .unwrap()
+        .unwrap();
+
+        let running = scheduler.try_lock(Job::Feeds).unwrap();
+        assert!(scheduler.try_lock(Job::Feeds).is_none());
+        // Other jobs still run
+        assert!(scheduler.try_lock(Job::Folders).is_some());
+
+        drop(running);
+        assert!(scheduler.try_lock(Job::Feeds).is_some());
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "cooklang-import-schedule-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    const RECIPE_PAGE: &str = r#"<html><head><script type="application/ld+json">
+        {"@type": "Recipe", "name": "Pancakes",
+         "recipeIngredient": ["200 g flour", "2 eggs"],
+         "recipeInstructions": "Whisk and fry."}
+        </script></head><body></body></html>"#;
+
+    #[tokio::test]
+    async fn test_folder_job_imports_each_file_once() {
+        let inbox = temp_dir("inbox");
+        let output = temp_dir("output");
+        std::fs::write(inbox.join("pancakes.html"), RECIPE_PAGE).unwrap();
+        std::fs::write(inbox.join("notes.pdf"), "not imported").unwrap();
+        std::fs::write(inbox.join(".hidden.html"), RECIPE_PAGE).unwrap();
+        let config = ScheduleConfig {
+            folders: Some("*/30 * * * *".to_string()),
+            folder_paths: vec![
+                inbox.display().to_string(),
+                inbox.join("missing").display().to_string(),
+            ],
+            output_dir: Some(output.display().to_string()),
+            ..Default::default()
+        };
+
+        let summary = run_job(Job::Folders, &config, |builder| builder.extract_only()).await;
+        assert_eq!(summary.job, Job::Folders);
+        assert_eq!(
+            (summary.imported, summary.skipped, summary.failed),
+            (1, 0, 1)
+        );
+        assert!(summary.failures[0].source.ends_with("missing"));
+        let saved = std::fs::read_to_string(output.join("Pancakes.cook")).unwrap();
+        assert!(saved.contains("200 g flour"));
+
+        // The manifest keeps the next run from importing it again
+        let summary = run_job(Job::Folders, &config, |builder| builder.extract_only()).await;
+        assert_eq!(
+            (summary.imported, summary.skipped, summary.failed),
+            (0, 1, 1)
+        );
+        let manifest = Manifest::load(output.join(MANIFEST)).unwrap();
+        assert!(manifest.is_imported(&inbox.join("pancakes.html").display().to_string()));
+
+        std::fs::remove_dir_all(&inbox).unwrap();
+        std::fs::remove_dir_all(&output).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_feed_job_imports_linked_recipes() {
+        let mut server = mockito::Server::new_async().await;
+        let sitemap = format!(
+            "<urlset><url><loc>{}/recipes/pancakes</loc></url></urlset>",
+            server.url()
+        );
+        let index = server
+            .mock("GET", "/sitemap.xml")
+            .with_body(sitemap)
+            .create_async()
+            .await;
+        let page = server
+            .mock("GET", "/recipes/pancakes")
+            .with_body(RECIPE_PAGE)
+            .create_async()
+            .await;
+        let output = temp_dir("feed");
+        let config = ScheduleConfig {
+            feeds: Some("0 6 * * *".to_string()),
+            feed_urls: vec![format!("{}/sitemap.xml", server.url())],
+            output_dir: Some(output.display().to_string()),
+            ..Default::default()
+        };
+
+        let summary = run_job(Job::Feeds, &config, |builder| builder.extract_only()).await;
+        assert_eq!(
+            (summary.imported, summary.skipped, summary.failed),
+            (1, 0, 0),
+            "{:?}",
+            summary.failures
+        );
+        index.assert_async().await;
+        page.assert_async().await;
+        assert!(output.join("Pancakes.cook").exists());
+
+        std::fs::remove_dir_all(&output).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_summary_posted_to_webhook() {
+        let mut server = mockito::Server::new_async().await;
+        let webhook = server
+            .mock("POST", "/hook")
+            .match_header("content-type", "application/json")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "job": "feeds",
+                "imported": 2,
+                "failed": 1,
+                "failures": [{"source": "https://example.com/soup", "error": "HTTP 404"}],
+            })))
+            .create_async()
+            .await;
+
+        let mut summary = Summary::new(Job::Feeds);
+        summary.imported = 2;
+        summary.fail("https://example.com/soup", "HTTP 404");
+        post_summary(&format!("{}/hook", server.url()), &summary)
+            .await
+            .unwrap();
+        webhook.assert_async().await;
+    }
+}
@@ -0,0 +1,140 @@
+//! API keys kept out of config.toml: a command that prints the key
+//! (`api_key_cmd`) or the OS secret store (`api_key_secret`)
+//!
+//! Keys are looked up when a converter is created, so a locked keychain or a
+//! password manager prompt only matters for the provider actually used.
+
+use crate::config::{ProviderConfig, SecretStore};
+use std::process::Command;
+use tracing::debug;
+
+/// Service name API keys are stored under in the keychain or Secret Service
+pub const SERVICE: &str = "cooklang-import";
+
+/// `config` with its API key filled in from `api_key_cmd` or
+/// `api_key_secret` when `api_key` isn't set. `provider` is the account the
+/// secret store keeps the key under, e.g. "anthropic".
+pub(crate) fn with_api_key(
+    provider: &str,
+    config: &ProviderConfig,
+) -> Result<ProviderConfig, String> {
+    let mut config = config.clone();
+    if config.api_key.is_some() {
+        return Ok(config);
+    }
+    if let Some(cmd) = &config.api_key_cmd {
+        debug!("Reading the {} API key from api_key_cmd", provider);
+        config.api_key =
+            Some(run(shell(cmd)).map_err(|e| format!("api_key_cmd for {}: {}", provider, e))?);
+    } else if let Some(store) = config.api_key_secret {
+        debug!("Reading the {} API key from {:?}", provider, store);
+        // Stored under the table name, [providers.openai]
+        let account = if provider == "open_ai" {
+            "openai"
+        } else {
+            provider
+        };
+        let lookup = match store {
+            SecretStore::Keychain => {
+                let mut command = Command::new("security");
+                command.args(["find-generic-password", "-s", SERVICE, "-a", account, "-w"]);
+                command
+            }
+            SecretStore::Libsecret => {
+                let mut command = Command::new("secret-tool");
+                command.args(["lookup", "service", SERVICE, "account", account]);
+                command
+            }
+        };
+        config.api_key =
+            Some(run(lookup).map_err(|e| format!("{:?} key for {}: {}", store, provider, e))?);
+    }
+    Ok(config)
+}
+
+/// `cmd` run by the platform shell
+fn shell(cmd: &str) -> Command {
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    let mut command = Command::new(shell);
+    command.args([flag, cmd]);
+    command
+}
+
+/// The first line `command` prints, which must not be empty
+fn run(mut command: Command) -> Result<String, String> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let output = command
+        .output()
+        .map_err(|e| format!("failed to run {}: {}", program, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} failed ({}): {}",
+            program,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    match stdout.lines().next().map(str::trim) {
+        Some(key) if !key.is_empty() => Ok(key.to_string()),
+        _ => Err(format!("{} printed no key", program)),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn provider_config(api_key: Option<&str>, api_key_cmd: Option<&str>) -> ProviderConfig {
+        ProviderConfig {
+            enabled: true,
+            model: "gpt-4.1-mini".to_string(),
+            temperature: 0.7,
+            max_tokens: 2000,
+            api_key: api_key.map(str::to_string),
+            api_key_cmd: api_key_cmd.map(str::to_string),
+            api_key_secret: None,
+            base_url: None,
+            endpoint: None,
+            deployment_name: None,
+            api_version: None,
+            project_id: None,
+            auto_pull: false,
+            timeout: None,
+            proxy: None,
+            requests_per_minute: None,
+            tokens_per_minute: None,
+            target_language: None,
+            units: None,
+            prompt: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_api_key_cmd() {
+        let config = provider_config(None, Some("printf 'sk-from-pass\\nlogin: me\\n'"));
+        let config = with_api_key("openai", &config).unwrap();
+        assert_eq!(config.api_key.as_deref(), Some("sk-from-pass"));
+
+        // A key in config.toml wins, and the command isn't run
+        let config = provider_config(Some("sk-config"), Some("exit 1"));
+        let config = with_api_key("openai", &config).unwrap();
+        assert_eq!(config.api_key.as_deref(), Some("sk-config"));
+    }
+
+    #[test]
+    fn test_api_key_cmd_failures() {
+        let err = with_api_key(
+            "openai",
+            &provider_config(None, Some("echo locked >&2; exit 1")),
+        )
+        .unwrap_err();
+        assert!(err.contains("api_key_cmd for openai"));
+        assert!(err.contains("locked"));
+        assert!(with_api_key("openai", &provider_config(None, Some("true"))).is_err());
+    }
+}
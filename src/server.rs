@@ -0,0 +1,136 @@
+//! HTTP import API (`server` feature)
+//!
+//! A small axum service so self-hosted apps can call the importer over the
+//! network. `POST /import` takes one of `url`, `text` or `image` (base64)
+//! (see [`ImportRequest`]) and answers with the same JSON as `--format json`:
+//!
+//! ```text
+//! POST /import
+//! {"url": "https://example.com/recipe", "provider": "anthropic"}
+//! ```
+//!
+//! Errors come back as `{"error": "..."}` with a 4xx/5xx status.
+
+use std::net::SocketAddr;
+
+use axum::{
+    extract::rejection::JsonRejection,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use tracing::info;
+
+use crate::{ImportError, ImportRequest};
+
+/// Error response: `{"error": "..."}` with a matching status code
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+impl From<ImportError> for ApiError {
+    fn from(err: ImportError) -> Self {
+        let status = match err {
+            ImportError::BuilderError(_) | ImportError::InvalidMarkdown(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            ImportError::NoExtractorMatched(_)
+            | ImportError::ParseError(_)
+            | ImportError::ExtractionError(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ImportError::FetchError(_)
+            | ImportError::HttpStatus { .. }
+            | ImportError::LlmApi { .. }
+            | ImportError::OcrError(_) => StatusCode::BAD_GATEWAY,
+            ImportError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+            ImportError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        ApiError(status, err.to_string())
+    }
+}
+
+async fn import(payload: Result<Json<ImportRequest>, JsonRejection>) -> Result<Response, ApiError> {
+    let Json(request) =
+        payload.map_err(|rejection| ApiError(rejection.status(), rejection.body_text()))?;
+    let result = request.into_builder()?.build().await?;
+    Ok(Json(result).into_response())
+}
+
+/// Routes of the import API: `POST /import` and `GET /health`
+pub fn router() -> Router {
+    Router::new()
+        .route("/import", post(import))
+        .route("/health", get(|| async { "ok" }))
+}
+
+/// Serve the import API on `addr` until the process is stopped
+pub async fn serve(addr: SocketAddr) -> std::io::Result<()> {
+    crate::daemon::keep_warm();
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!("Import API listening on http://{}", listener.local_addr()?);
+    axum::serve(listener, router()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn spawn() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router()).await });
+        format!("http://{}", addr)
+    }
+
+    async fn post_import(base: &str, body: &str) -> (u16, serde_json::Value) {
+        let response = reqwest::Client::new()
+            .post(format!("{}/import", base))
+            .header("content-type", "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .unwrap();
+        let status = response.status().as_u16();
+        (status, response.json().await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_import_text_extract_only() {
+        let base = spawn().await;
+        let (status, json) = post_import(
+            &base,
+            r#"{"text": "2 eggs\n\nFry the eggs.", "extract_only": true}"#,
+        )
+        .await;
+
+        assert_eq!(status, 200);
+        assert!(json["text"].as_str().unwrap().contains("Fry the eggs."));
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_two_sources() {
+        let base = spawn().await;
+        let (status, json) =
+            post_import(&base, r#"{"url": "https://example.com", "text": "2 eggs"}"#).await;
+
+        assert_eq!(status, 400);
+        assert_eq!(
+            json["error"],
+            "Builder error: Set exactly one of url, text or image"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_import_unknown_provider() {
+        let base = spawn().await;
+        let (status, json) = post_import(&base, r#"{"text": "2 eggs", "provider": "nope"}"#).await;
+
+        assert_eq!(status, 400);
+        assert_eq!(json["error"], "Builder error: Unknown provider: nope");
+    }
+}
@@ -0,0 +1,236 @@
+//! First-run configuration for `cooklang-import init`
+//!
+//! The CLI asks the questions; this module knows the providers, renders the
+//! answers as config.toml, checks the key with a one-line prompt and writes
+//! the file.
+
+use crate::config::AiConfig;
+use crate::converters;
+use config::{Config, File, FileFormat};
+use std::path::Path;
+use std::time::Duration;
+
+/// A provider `init` can set up
+#[derive(Debug)]
+pub struct ProviderChoice {
+    /// Name in `[providers.<name>]` and `default_provider`
+    pub name: &'static str,
+    /// Name shown to the user
+    pub label: &'static str,
+    /// Environment variable the provider reads its key from, if it needs one
+    pub env_var: Option<&'static str>,
+    /// Suggested model
+    pub model: &'static str,
+}
+
+/// Providers offered by `init`. Azure OpenAI needs an endpoint and
+/// deployment, so it is configured by editing config.toml.
+pub const PROVIDERS: &[ProviderChoice] = &[
+    ProviderChoice {
+        name: "open_ai",
+        label: "OpenAI",
+        env_var: Some("OPENAI_API_KEY"),
+        model: "gpt-4.1-mini",
+    },
+    ProviderChoice {
+        name: "anthropic",
+        label: "Anthropic Claude",
+        env_var: Some("ANTHROPIC_API_KEY"),
+        model: "claude-sonnet-4.5",
+    },
+    ProviderChoice {
+        name: "google",
+        label: "Google Gemini",
+        env_var: Some("GOOGLE_API_KEY"),
+        model: "gemini-2.5-flash",
+    },
+    ProviderChoice {
+        name: "mistral",
+        label: "Mistral",
+        env_var: Some("MISTRAL_API_KEY"),
+        model: "mistral-small-latest",
+    },
+    ProviderChoice {
+        name: "groq",
+        label: "Groq",
+        env_var: Some("GROQ_API_KEY"),
+        model: "llama-3.3-70b-versatile",
+    },
+    ProviderChoice {
+        name: "ollama",
+        label: "Ollama",
+        env_var: None,
+        model: "llama3",
+    },
+];
+
+/// A short recipe converted at the end of `init` to show the setup works
+pub const SAMPLE_RECIPE: &str = "2 eggs\n1 tbsp butter\n1 pinch salt\n\n\
+Whisk the eggs with the salt.\n\
+Melt the butter in a pan over low heat, add the eggs and stir for 3 minutes until just set.";
+
+/// The answers to `init`'s questions
+#[derive(Debug)]
+pub struct Setup {
+    /// Provider to use by default
+    pub provider: &'static ProviderChoice,
+    /// Model, the provider's suggestion unless another was entered
+    pub model: String,
+    /// Written to config.toml; `None` leaves the key in the environment
+    pub api_key: Option<String>,
+    /// Server address, for Ollama
+    pub base_url: Option<String>,
+}
+
+impl Setup {
+    /// The config.toml for these answers
+    pub fn to_toml(&self) -> String {
+        let mut toml = format!(
+            "# Written by `cooklang-import init`; see config.toml.example for all options\n\
+             default_provider = {}\n\n\
+             [providers.{}]\n\
+             enabled = true\n\
+             model = {}\n",
+            quote(self.provider.name),
+            self.provider.name,
+            quote(&self.model),
+        );
+        if let Some(api_key) = &self.api_key {
+            toml.push_str(&format!("api_key = {}\n", quote(api_key)));
+        } else if let Some(env_var) = self.provider.env_var {
+            toml.push_str(&format!(
+                "# API key from the {} environment variable\n",
+                env_var
+            ));
+        }
+        if let Some(base_url) = &self.base_url {
+            toml.push_str(&format!("base_url = {}\n", quote(base_url)));
+        }
+        toml
+    }
+
+    /// Send a one-line prompt with these settings, to check the key, model
+    /// and (for Ollama) that the server is up
+    pub async fn ping(&self) -> Result<(), String> {
+        let config: AiConfig = Config::builder()
+            .add_source(File::from_str(&self.to_toml(), FileFormat::Toml))
+            .build()
+            .and_then(Config::try_deserialize)
+            .map_err(|e| e.to_string())?;
+        let provider_config = config
+            .providers
+            .get(self.provider.name)
+            .ok_or("provider missing from the generated config")?;
+        let converter = converters::create_converter(self.provider.name, provider_config)
+            .ok_or_else(|| match self.provider.env_var {
+                Some(env_var) => format!("no API key: enter one or set {}", env_var),
+                None => format!("could not create the {} provider", self.provider.label),
+            })?;
+        let reply = tokio::time::timeout(
+            Duration::from_secs(60),
+            converter.complete("Reply with the single word OK."),
+        )
+        .await
+        .map_err(|_| "no reply within 60 seconds".to_string())?
+        .map_err(|e| e.to_string())?;
+        tracing::debug!("Ping reply: {}", reply.content.trim());
+        Ok(())
+    }
+}
+
+/// Write `contents` to `path`, creating its directory. On Unix the file is
+/// only readable by the user, since it may hold an API key.
+pub fn write_config(path: &Path, contents: &str) -> std::io::Result<()> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, contents)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(())
+}
+
+/// A TOML basic string
+fn quote(value: &str) -> String {
+    format!("{:?}", value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider(name: &str) -> &'static ProviderChoice {
+        PROVIDERS.iter().find(|p| p.name == name).unwrap()
+    }
+
+    fn parse(toml: &str) -> AiConfig {
+        Config::builder()
+            .add_source(File::from_str(toml, FileFormat::Toml))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_config_with_api_key() {
+        let setup = Setup {
+            provider: provider("anthropic"),
+            model: "claude-sonnet-4.5".to_string(),
+            api_key: Some("sk-ant-\"quoted\"".to_string()),
+            base_url: None,
+        };
+        let config = parse(&setup.to_toml());
+        assert_eq!(config.default_provider, "anthropic");
+        let anthropic = &config.providers["anthropic"];
+        assert!(anthropic.enabled);
+        assert_eq!(anthropic.model, "claude-sonnet-4.5");
+        assert_eq!(anthropic.api_key.as_deref(), Some("sk-ant-\"quoted\""));
+    }
+
+    #[test]
+    fn test_config_from_environment_and_ollama() {
+        let setup = Setup {
+            provider: provider("open_ai"),
+            model: "gpt-4.1-mini".to_string(),
+            api_key: None,
+            base_url: None,
+        };
+        let toml = setup.to_toml();
+        assert!(toml.contains("OPENAI_API_KEY"));
+        assert!(parse(&toml).providers["open_ai"].api_key.is_none());
+
+        let setup = Setup {
+            provider: provider("ollama"),
+            model: "llama3".to_string(),
+            api_key: None,
+            base_url: Some("http://localhost:11434".to_string()),
+        };
+        let config = parse(&setup.to_toml());
+        assert_eq!(
+            config.providers["ollama"].base_url.as_deref(),
+            Some("http://localhost:11434")
+        );
+    }
+
+    #[test]
+    fn test_write_config() {
+        let dir = std::env::temp_dir().join(format!("cooklang-init-{}", std::process::id()));
+        let path = dir.join("cooklang-import").join("config.toml");
+        write_config(&path, "default_provider = \"ollama\"\n").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "default_provider = \"ollama\"\n"
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
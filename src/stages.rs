@@ -0,0 +1,318 @@
+//! Individual import stages, for composing custom pipelines
+//!
+//! [`RecipeImporter`](crate::RecipeImporter) runs fetch → extract →
+//! normalize → convert → write in one call. These functions run one stage
+//! each, so integrators can split the work, e.g. extract on a phone and
+//! convert on a server. Stages pass plain HTML strings and
+//! [`RecipeComponents`] between each other, so any stage's output can be
+//! stored or sent elsewhere before the next one runs.
+//!
+//! # Example
+//! ```no_run
+//! use cooklang_import::{pipelines::Timeouts, stages, RecipeImporter};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let url = "https://example.com/recipe";
+//! let html = stages::fetch(url, &Timeouts::from_config(), None, None).await?;
+//! let components = stages::normalize(stages::extract(&html, url).await?);
+//!
+//! let conversion = stages::convert(&components, &RecipeImporter::builder()).await?;
+//! stages::write("recipe.cook", &components, &conversion.content).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::builder::RecipeImporterBuilder;
+use crate::cache::Cache;
+use crate::converters::ConversionResult;
+use crate::pipelines::{self, RecipeComponents, Timeouts};
+use crate::ImportError;
+use std::path::Path;
+
+/// Fetch a recipe page's HTML.
+///
+/// Uses the cache when given one, the page scriber for domains listed in
+/// `[page_scriber]`, and falls back to the page scriber when a plain request
+/// is blocked.
+pub async fn fetch(
+    url: &str,
+    timeouts: &Timeouts,
+    proxy: Option<&str>,
+    cache: Option<&Cache>,
+) -> Result<String, ImportError> {
+    pipelines::url::fetch(url, timeouts, proxy, cache)
+        .await
+        .map_err(|e| ImportError::from_pipeline(e, ImportError::ExtractionError))
+}
+
+/// Extract a recipe from a page's structured data (JSON-LD, microdata or
+/// recipe-card classes).
+///
+/// No LLM is called. When nothing matches, the error is
+/// [`ImportError::NoExtractorMatched`]; use [`html_to_text`] and
+/// [`extract_text`] to fall back to LLM extraction.
+pub async fn extract(html: &str, url: &str) -> Result<RecipeComponents, ImportError> {
+    pipelines::url::try_structured_extractors(html, url)
+        .await
+        .map(|extraction| extraction.components)
+        .map_err(ImportError::NoExtractorMatched)
+}
+
+/// The visible text of a page's `<body>`, for [`extract_text`]
+pub fn html_to_text(html: &str) -> String {
+    pipelines::url::extract_text_from_html(html)
+}
+
+/// Extract a recipe from unstructured text (page text, OCR output) with
+/// an LLM.
+///
+/// Uses the provider configured in config.toml (`default_provider`, or the
+/// `[fallback]`/`[race]` providers), else OpenAI with `OPENAI_API_KEY`.
+/// `source` is recorded as the recipe's source in the metadata.
+pub async fn extract_text(
+    text: &str,
+    source: &str,
+    timeouts: &Timeouts,
+    proxy: Option<&str>,
+) -> Result<RecipeComponents, ImportError> {
+    let extractor =
+        crate::url_to_text::text::TextExtractor::from_config(timeouts.extraction, proxy).await?;
+    extractor
+        .extract(text, source)
+        .await
+        .map_err(|e| ImportError::from_pipeline(e, ImportError::ExtractionError))
+}
+
+/// Tidy extracted components before conversion.
+///
+/// Collapses whitespace in the name, strips trailing whitespace from every
+/// line and squeezes runs of blank lines in the text and metadata. Numbers
+/// in the text get one spelling, so the LLM doesn't misread them: unicode
+/// fractions become "1/2", decimal commas ("1,5 kg") become points and
+/// ranges ("2–3", "2 - 3") become "2-3".
+/// Normalizing twice gives the same result.
+pub fn normalize(components: RecipeComponents) -> RecipeComponents {
+    RecipeComponents {
+        text: tidy_lines(&pipelines::normalize_quantities(&components.text)),
+        metadata: tidy_lines(&components.metadata),
+        name: pipelines::sanitize_name(&components.name),
+    }
+}
+
+/// Split component recipes (dough, filling, glaze) out of a recipe.
+///
+/// An ingredient group and an instruction section with the same name
+/// become a recipe of their own, and the parent's first step references
+/// each one as `@./<Name>{}`. Write the parts with [`file_name`] next to
+/// the parent so the references resolve. Nothing is split when the recipe
+/// has no matching groups and sections.
+pub fn split_sub_recipes(components: &RecipeComponents) -> pipelines::sub_recipes::SplitRecipe {
+    pipelines::sub_recipes::split(components)
+}
+
+/// Convert components to Cooklang with an LLM.
+///
+/// `importer` supplies the provider, model, timeouts and post-processing
+/// (`merge_duplicate_ingredients`, `scale`); its input source is ignored.
+/// The returned content has no frontmatter; [`render`] or [`write`] add it.
+pub async fn convert(
+    components: &RecipeComponents,
+    importer: &RecipeImporterBuilder,
+) -> Result<ConversionResult, ImportError> {
+    importer.convert(components).await
+}
+
+/// Where [`render_titled`] puts the recipe title
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TitleStyle {
+    /// `title:` in the YAML frontmatter
+    #[default]
+    Frontmatter,
+    /// Left out of the file, which is named after it instead (see [`file_name`])
+    Filename,
+    /// A leading `# Title` line after the frontmatter
+    Heading,
+}
+
+impl std::str::FromStr for TitleStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "frontmatter" => Ok(TitleStyle::Frontmatter),
+            "filename" => Ok(TitleStyle::Filename),
+            "heading" => Ok(TitleStyle::Heading),
+            _ => Err(format!(
+                "Unknown title style: {} (expected frontmatter, filename or heading)",
+                s
+            )),
+        }
+    }
+}
+
+/// The final recipe file: YAML frontmatter (title and metadata) followed by
+/// the Cooklang
+pub fn render(components: &RecipeComponents, cooklang: &str) -> String {
+    render_titled(components, cooklang, TitleStyle::Frontmatter)
+}
+
+/// Like [`render`], with the title written the way `style` says
+pub fn render_titled(components: &RecipeComponents, cooklang: &str, style: TitleStyle) -> String {
+    let mut output = String::new();
+    let has_name = !components.name.is_empty();
+    let has_metadata = !components.metadata.is_empty();
+    let title_in_frontmatter = has_name && style == TitleStyle::Frontmatter;
+
+    if title_in_frontmatter || has_metadata {
+        output.push_str("---\n");
+        if title_in_frontmatter {
+            let title_yaml =
+                pipelines::metadata_to_yaml(&[("title".to_string(), components.name.clone())]);
+            output.push_str(&title_yaml);
+        }
+        if has_metadata {
+            output.push_str(&components.metadata);
+            if !components.metadata.ends_with('\n') {
+                output.push('\n');
+            }
+        }
+        output.push_str("---\n\n");
+    }
+    if has_name && style == TitleStyle::Heading {
+        output.push_str(&format!("# {}\n\n", components.name));
+    }
+    output.push_str(cooklang);
+    output
+}
+
+/// A `.cook` file name for a recipe title, e.g. "Slow Cooker Chilli.cook".
+///
+/// Slashes become dashes and other characters that aren't allowed in file
+/// names on common systems are dropped. Returns `None` when nothing usable is left.
+pub fn file_name(title: &str) -> Option<String> {
+    let name: String = title
+        .chars()
+        .map(|c| if matches!(c, '/' | '\\') { '-' } else { c })
+        .filter(|c| !matches!(c, ':' | '*' | '?' | '"' | '<' | '>' | '|'))
+        .filter(|c| !c.is_control())
+        .collect();
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    let name = name.trim_matches('.');
+    if name.is_empty() {
+        None
+    } else {
+        Some(format!("{}.cook", name))
+    }
+}
+
+/// Write the [`render`]ed recipe to `path`, replacing any existing file
+pub async fn write(
+    path: impl AsRef<Path>,
+    components: &RecipeComponents,
+    cooklang: &str,
+) -> Result<(), ImportError> {
+    tokio::fs::write(path, render(components, cooklang)).await?;
+    Ok(())
+}
+
+/// Trim trailing whitespace from each line and keep at most one blank line
+/// in a row
+fn tidy_lines(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut blank = true;
+    for line in text.lines().map(str::trim_end) {
+        if line.is_empty() {
+            if !blank {
+                output.push('\n');
+            }
+            blank = true;
+        } else {
+            output.push_str(line);
+            output.push('\n');
+            blank = false;
+        }
+    }
+    output.truncate(output.trim_end().len());
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        let components = RecipeComponents {
+            text: "\n2 eggs  \n\n\n\nFry the eggs.\t\n\n".to_string(),
+            metadata: "servings: 2  \n".to_string(),
+            name: " Fried\n Eggs ".to_string(),
+        };
+        let normalized = normalize(components);
+        assert_eq!(normalized.text, "2 eggs\n\nFry the eggs.");
+        assert_eq!(normalized.metadata, "servings: 2");
+        assert_eq!(normalized.name, "Fried Eggs");
+
+        let again = normalize(normalized.clone());
+        assert_eq!(again.text, normalized.text);
+    }
+
+    #[tokio::test]
+    async fn test_extract_reports_diagnostics() {
+        let html = r#"<script type="application/ld+json">
+            {"@type": "Recipe", "name": "Toast",
+             "recipeIngredient": ["1 slice bread"],
+             "recipeInstructions": "Toast the bread."}
+            </script>"#;
+        let components = extract(html, "https://example.com/toast").await.unwrap();
+        assert_eq!(components.name, "Toast");
+        assert!(components.text.contains("1 slice bread"));
+
+        let result = extract("<p>Hello</p>", "https://example.com/hello").await;
+        assert!(matches!(result, Err(ImportError::NoExtractorMatched(_))));
+    }
+
+    #[test]
+    fn test_render() {
+        let components = RecipeComponents {
+            text: String::new(),
+            metadata: "servings: 2".to_string(),
+            name: "Toast".to_string(),
+        };
+        assert_eq!(
+            render(&components, "Toast @bread{1%slice}."),
+            "---\ntitle: Toast\nservings: 2\n---\n\nToast @bread{1%slice}."
+        );
+        assert_eq!(
+            render(&RecipeComponents::default(), "Toast @bread{1%slice}."),
+            "Toast @bread{1%slice}."
+        );
+    }
+
+    #[test]
+    fn test_render_title_styles() {
+        let components = RecipeComponents {
+            text: String::new(),
+            metadata: "servings: 2".to_string(),
+            name: "Toast".to_string(),
+        };
+        assert_eq!(
+            render_titled(&components, "Toast @bread{1%slice}.", TitleStyle::Heading),
+            "---\nservings: 2\n---\n\n# Toast\n\nToast @bread{1%slice}."
+        );
+        let untitled = RecipeComponents {
+            metadata: String::new(),
+            ..components
+        };
+        assert_eq!(
+            render_titled(&untitled, "Toast @bread{1%slice}.", TitleStyle::Filename),
+            "Toast @bread{1%slice}."
+        );
+
+        assert_eq!(
+            file_name("Mac & Cheese: 1/2 batch?").as_deref(),
+            Some("Mac & Cheese 1-2 batch.cook")
+        );
+        assert_eq!(file_name(" ... "), None);
+    }
+}
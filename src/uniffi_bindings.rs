@@ -53,6 +53,8 @@ pub enum FfiLlmProvider {
     Google,
     AzureOpenAI,
     Ollama,
+    Mistral,
+    Groq,
 }
 
 impl From<FfiLlmProvider> for crate::LlmProvider {
@@ -63,6 +65,8 @@ impl From<FfiLlmProvider> for crate::LlmProvider {
             FfiLlmProvider::Google => crate::LlmProvider::Google,
             FfiLlmProvider::AzureOpenAI => crate::LlmProvider::AzureOpenAI,
             FfiLlmProvider::Ollama => crate::LlmProvider::Ollama,
+            FfiLlmProvider::Mistral => crate::LlmProvider::Mistral,
+            FfiLlmProvider::Groq => crate::LlmProvider::Groq,
         }
     }
 }
@@ -97,6 +101,26 @@ pub enum FfiImportError {
     ConfigError { reason: String },
     /// Runtime error (tokio)
     RuntimeError { reason: String },
+    /// The recipe page answered with an HTTP error status
+    HttpStatus { code: u16, url: String },
+    /// The LLM provider's API returned an error
+    LlmApi {
+        provider: String,
+        status: Option<u16>,
+        message: String,
+    },
+    /// Text recognition failed
+    OcrError { reason: String },
+    /// A request ran past its timeout
+    Timeout { reason: String },
+    /// The LLM provider's rate limit was exceeded; retry after
+    /// `retry_after_secs` when given
+    RateLimited {
+        provider: String,
+        retry_after_secs: Option<u64>,
+    },
+    /// The conversion's confidence score is below the requested minimum
+    LowQuality { confidence: f32, min: f32 },
 }
 
 impl fmt::Display for FfiImportError {
@@ -114,6 +138,29 @@ impl fmt::Display for FfiImportError {
             FfiImportError::BuilderError { reason } => write!(f, "Builder error: {}", reason),
             FfiImportError::ConfigError { reason } => write!(f, "Config error: {}", reason),
             FfiImportError::RuntimeError { reason } => write!(f, "Runtime error: {}", reason),
+            FfiImportError::HttpStatus { code, url } => write!(f, "HTTP {} from {}", code, url),
+            FfiImportError::LlmApi {
+                provider,
+                status: Some(status),
+                message,
+            } => write!(f, "{} API error (HTTP {}): {}", provider, status, message),
+            FfiImportError::LlmApi {
+                provider, message, ..
+            } => write!(f, "{} API error: {}", provider, message),
+            FfiImportError::OcrError { reason } => write!(f, "OCR error: {}", reason),
+            FfiImportError::Timeout { reason } => write!(f, "Timeout: {}", reason),
+            FfiImportError::RateLimited {
+                provider,
+                retry_after_secs,
+            } => match retry_after_secs {
+                Some(secs) => write!(f, "{} rate limit exceeded, retry after {}s", provider, secs),
+                None => write!(f, "{} rate limit exceeded", provider),
+            },
+            FfiImportError::LowQuality { confidence, min } => write!(
+                f,
+                "Conversion confidence {:.2} is below the minimum quality {:.2}",
+                confidence, min
+            ),
         }
     }
 }
@@ -127,8 +174,8 @@ impl From<ImportError> for FfiImportError {
                 reason: e.to_string(),
             },
             ImportError::ParseError(msg) => FfiImportError::ParseError { reason: msg },
-            ImportError::NoExtractorMatched => FfiImportError::NoExtractorMatched {
-                reason: "No extractor could parse the recipe from this webpage".to_string(),
+            ImportError::NoExtractorMatched(diagnostics) => FfiImportError::NoExtractorMatched {
+                reason: diagnostics.to_string(),
             },
             ImportError::ConversionError(msg) => FfiImportError::ConversionError { reason: msg },
             ImportError::InvalidMarkdown(msg) => FfiImportError::InvalidInput { reason: msg },
@@ -143,6 +190,37 @@ impl From<ImportError> for FfiImportError {
             ImportError::ConfigError(e) => FfiImportError::ConfigError {
                 reason: e.to_string(),
             },
+            ImportError::IoError(e) => FfiImportError::RuntimeError {
+                reason: e.to_string(),
+            },
+            ImportError::ImageError(msg) => FfiImportError::RuntimeError { reason: msg },
+            ImportError::OfflineError(what) => FfiImportError::InvalidInput {
+                reason: format!("Offline mode: {} needs the network", what),
+            },
+            ImportError::HttpStatus { code, url } => FfiImportError::HttpStatus { code, url },
+            ImportError::LlmApi {
+                provider,
+                status,
+                message,
+            } => FfiImportError::LlmApi {
+                provider,
+                status,
+                message,
+            },
+            ImportError::OcrError(msg) => FfiImportError::OcrError { reason: msg },
+            ImportError::Timeout(e) => FfiImportError::Timeout {
+                reason: e.to_string(),
+            },
+            ImportError::RateLimited {
+                provider,
+                retry_after,
+            } => FfiImportError::RateLimited {
+                provider,
+                retry_after_secs: retry_after.map(|wait| wait.as_secs()),
+            },
+            ImportError::LowQuality { confidence, min } => {
+                FfiImportError::LowQuality { confidence, min }
+            }
         }
     }
 }
@@ -319,6 +397,41 @@ async fn convert_image_async(
     }
 }
 
+/// Read the text of an image on the device, without sending it anywhere
+///
+/// Runs the ONNX text detection and recognition models in `model_dir`
+/// (`det.onnx`, `rec.onnx` and `charset.txt`). Needs the library built with
+/// the `ocr-onnx` feature. Pass the text to [`convert_text_to_cooklang`] to
+/// convert it.
+///
+/// # Arguments
+/// * `image_path` - Path to the image file
+/// * `model_dir` - Directory holding the models
+/// * `timeout_seconds` - Optional timeout in seconds (default: 120)
+///
+/// # Returns
+/// The text of the image, one line per line
+#[cfg(feature = "ocr-onnx")]
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn extract_text_on_device(
+    image_path: String,
+    model_dir: String,
+    timeout_seconds: Option<u64>,
+) -> Result<String, FfiImportError> {
+    let rt = create_runtime()?;
+    let timeout = timeout_seconds
+        .map(Duration::from_secs)
+        .unwrap_or(crate::converters::DEFAULT_LLM_TIMEOUT);
+    rt.block_on(crate::images_to_text::extract_on_device(
+        &crate::ImageSource::Path(image_path),
+        model_dir,
+        timeout,
+    ))
+    .map_err(|e| FfiImportError::OcrError {
+        reason: e.to_string(),
+    })
+}
+
 /// Extract recipe components from a URL without converting to Cooklang format
 ///
 /// # Arguments
@@ -390,6 +503,8 @@ pub fn is_provider_available(provider: FfiLlmProvider) -> bool {
             // Ollama doesn't require API key, check if base URL is set or use default
             true
         }
+        FfiLlmProvider::Mistral => std::env::var("MISTRAL_API_KEY").is_ok(),
+        FfiLlmProvider::Groq => std::env::var("GROQ_API_KEY").is_ok(),
     }
 }
 
@@ -416,6 +531,33 @@ mod tests {
         assert_eq!(back.text, components.text);
     }
 
+    #[test]
+    fn test_ffi_error_keeps_fields() {
+        let err: FfiImportError = ImportError::RateLimited {
+            provider: "OpenAI".to_string(),
+            retry_after: Some(std::time::Duration::from_secs(20)),
+        }
+        .into();
+        assert!(matches!(
+            &err,
+            FfiImportError::RateLimited {
+                retry_after_secs: Some(20),
+                ..
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "OpenAI rate limit exceeded, retry after 20s"
+        );
+
+        let err: FfiImportError = ImportError::HttpStatus {
+            code: 404,
+            url: "https://example.com/soup".to_string(),
+        }
+        .into();
+        assert!(matches!(err, FfiImportError::HttpStatus { code: 404, .. }));
+    }
+
     #[test]
     fn test_get_version() {
         let version = get_version();
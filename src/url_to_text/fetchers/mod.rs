@@ -2,4 +2,4 @@ mod page_scriber;
 mod request;
 
 pub use page_scriber::PageScriberFetcher;
-pub use request::RequestFetcher;
+pub use request::{FetchedPage, RequestFetcher};
@@ -1,6 +1,6 @@
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::error::Error;
+use std::time::Duration;
 
 #[derive(Serialize)]
 struct SourceRequest {
@@ -14,15 +14,24 @@ struct SourceResponse {
 
 pub struct PageScriberFetcher {
     endpoint: String,
-    client: Client,
+    timeout: Duration,
+    proxy: Option<String>,
 }
 
 impl PageScriberFetcher {
-    pub fn new(page_scriber_url: Option<String>) -> Option<Self> {
+    pub fn new(
+        page_scriber_url: Option<String>,
+        timeout: Option<Duration>,
+        proxy: Option<&str>,
+    ) -> Option<Self> {
         let base_url = page_scriber_url?;
         let endpoint = format!("{}/api/fetch-source", base_url);
-        let client = Client::new();
-        Some(Self { endpoint, client })
+        let timeout = timeout.unwrap_or(Duration::from_secs(60));
+        Some(Self {
+            endpoint,
+            timeout,
+            proxy: proxy.map(str::to_string),
+        })
     }
 
     pub fn is_available(page_scriber_url: Option<&String>) -> bool {
@@ -31,9 +40,9 @@ impl PageScriberFetcher {
 
     /// Fetch HTML source from a URL via the page scriber service.
     /// Returns raw HTML that can be parsed by structured extractors.
+    #[tracing::instrument(name = "fetch", skip_all, fields(url = %url, fetcher = "page-scriber"))]
     pub async fn fetch(&self, url: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
-        let response = self
-            .client
+        let response = crate::http::client(&self.endpoint, self.timeout, self.proxy.as_deref())?
             .post(&self.endpoint)
             .json(&SourceRequest {
                 url: url.to_string(),
@@ -60,13 +69,14 @@ mod tests {
 
     #[test]
     fn test_new_returns_none_without_url() {
-        let fetcher = PageScriberFetcher::new(None);
+        let fetcher = PageScriberFetcher::new(None, None, None);
         assert!(fetcher.is_none());
     }
 
     #[test]
     fn test_new_returns_some_with_url() {
-        let fetcher = PageScriberFetcher::new(Some("http://localhost:4000".to_string()));
+        let fetcher =
+            PageScriberFetcher::new(Some("http://localhost:4000".to_string()), None, None);
         assert!(fetcher.is_some());
     }
 
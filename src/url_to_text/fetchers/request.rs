@@ -1,35 +1,136 @@
-use reqwest::Client;
 use std::error::Error;
 use std::time::Duration;
 
+/// A fetched page and the URL it was served from after redirects
+#[derive(Debug, Clone)]
+pub struct FetchedPage {
+    pub url: String,
+    pub html: String,
+}
+
 pub struct RequestFetcher {
-    client: Client,
+    timeout: Duration,
+    proxy: Option<String>,
 }
 
 impl RequestFetcher {
-    pub fn new(timeout: Option<Duration>) -> Self {
-        let timeout = timeout.unwrap_or(Duration::from_secs(30));
-        let client = Client::builder()
-            .timeout(timeout)
-            .user_agent("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
-            .build()
-            .expect("Failed to create HTTP client");
-
-        Self { client }
+    pub fn new(timeout: Option<Duration>, proxy: Option<&str>) -> Self {
+        Self {
+            timeout: timeout.unwrap_or(Duration::from_secs(30)),
+            proxy: proxy.map(str::to_string),
+        }
     }
 
     pub async fn fetch(&self, url: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
-        let response = self.client.get(url).send().await?;
+        self.fetch_page(url).await.map(|page| page.html)
+    }
+
+    /// Fetch a page, following redirects (shortened links, `http` to
+    /// `https`, moved recipes), and report where it ended up
+    #[tracing::instrument(name = "fetch", skip_all, fields(url = %url, fetcher = "request"))]
+    pub async fn fetch_page(&self, url: &str) -> Result<FetchedPage, Box<dyn Error + Send + Sync>> {
+        // Per host, since TLS settings can depend on the host
+        let proxy = self.proxy.as_deref();
+        let client = crate::http::shared_client("page", url, self.timeout, proxy, || {
+            crate::http::client_builder(url, proxy)
+                .timeout(self.timeout)
+                .build()
+        })?;
+        let response = client
+            .get(url)
+            .headers(crate::http::browser_headers())
+            .send()
+            .await?;
         let status = response.status();
         if !status.is_success() {
-            return Err(format!(
-                "Failed to fetch page: HTTP {} ({})",
-                status.as_u16(),
-                status.canonical_reason().unwrap_or("Unknown")
-            )
-            .into());
+            return Err(Box::new(crate::ImportError::HttpStatus {
+                code: status.as_u16(),
+                url: url.to_string(),
+            }));
+        }
+        let final_url = response.url().to_string();
+        if final_url != url {
+            tracing::debug!("Redirected to {}", final_url);
+        }
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let bytes = read_body(response, crate::http::max_page_size()).await?;
+        let html = crate::charset::decode_page(&bytes, content_type.as_deref());
+        Ok(FetchedPage {
+            url: final_url,
+            html,
+        })
+    }
+}
+
+/// Read a response body up to `limit` bytes, dropping the rest so a huge
+/// page can't exhaust memory
+async fn read_body(
+    mut response: reqwest::Response,
+    limit: u64,
+) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        let room = limit as usize - body.len();
+        if chunk.len() >= room {
+            body.extend_from_slice(&chunk[..room]);
+            if chunk.len() > room || response.chunk().await?.is_some() {
+                tracing::warn!(
+                    "Page is larger than {} bytes, reading only the start ([http] max_page_size)",
+                    limit
+                );
+            }
+            break;
         }
-        let html = response.text().await?;
-        Ok(html)
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+
+    #[tokio::test]
+    async fn test_read_body_stops_at_limit() {
+        let mut server = Server::new_async().await;
+        let page = server
+            .mock("GET", "/huge")
+            .with_body("x".repeat(100_000))
+            .expect(2)
+            .create();
+
+        let response = reqwest::get(format!("{}/huge", server.url()))
+            .await
+            .unwrap();
+        assert_eq!(read_body(response, 1000).await.unwrap().len(), 1000);
+        let response = reqwest::get(format!("{}/huge", server.url()))
+            .await
+            .unwrap();
+        assert_eq!(read_body(response, 1 << 20).await.unwrap().len(), 100_000);
+        page.assert();
+    }
+
+    #[tokio::test]
+    async fn test_fetch_sends_browser_headers() {
+        let mut server = Server::new_async().await;
+        let page = server
+            .mock("GET", "/soup")
+            .match_header("user-agent", crate::http::DEFAULT_USER_AGENT)
+            .match_header("accept", mockito::Matcher::Regex("^text/html".to_string()))
+            .match_header("accept-language", mockito::Matcher::Any)
+            .with_body("<html></html>")
+            .create();
+
+        let fetcher = RequestFetcher::new(None, None);
+        assert!(fetcher
+            .fetch(&format!("{}/soup", server.url()))
+            .await
+            .is_ok());
+        page.assert();
     }
 }
@@ -0,0 +1,174 @@
+use super::extractors::{has_json_ld_recipe, recipe_class_counts, MicroDataExtractor};
+use scraper::{Html, Selector};
+
+/// Words that name the ingredient list in a heading
+const INGREDIENT_HEADINGS: &[&str] = &[
+    "ingredients",
+    "zutaten",
+    "ingredientes",
+    "ingrédients",
+    "ingredienti",
+];
+
+/// Words that name the method in a heading
+const STEP_HEADINGS: &[&str] = &[
+    "instructions",
+    "directions",
+    "method",
+    "preparation",
+    "steps",
+    "zubereitung",
+    "preparación",
+    "préparation",
+    "procedimento",
+];
+
+/// Words recipe sites put in their URL paths
+const URL_KEYWORDS: &[&str] = &[
+    "recipe", "rezept", "receta", "recette", "ricetta", "recept", "przepis",
+];
+
+/// How likely a page is to contain a recipe, from least to most
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RecipeLikelihood {
+    /// No recipe markup or recipe-like structure
+    Unlikely,
+    /// Recipe-like headings, a partial recipe card or a recipe-like URL
+    Possible,
+    /// Structured recipe data (JSON-LD, microdata) or a full recipe card
+    Likely,
+}
+
+/// Cheaply guess whether a URL or an HTML page holds a recipe.
+///
+/// HTML is checked for JSON-LD and microdata recipes, the recipe-card classes
+/// the HTML class extractor knows, and ingredient/method headings. A bare URL
+/// is only checked for recipe words in its path; nothing is fetched and no
+/// LLM is called, so this is suitable for filtering crawl or bookmark
+/// candidates before importing them.
+///
+/// # Example
+/// ```
+/// use cooklang_import::{is_probably_recipe, RecipeLikelihood};
+///
+/// let html = r#"<script type="application/ld+json">{"@type": "Recipe"}</script>"#;
+/// assert_eq!(is_probably_recipe(html), RecipeLikelihood::Likely);
+/// assert_eq!(
+///     is_probably_recipe("https://example.com/about-us"),
+///     RecipeLikelihood::Unlikely
+/// );
+/// ```
+pub fn is_probably_recipe(url_or_html: &str) -> RecipeLikelihood {
+    // '<' is never valid unescaped in a URL
+    if url_or_html.contains('<') {
+        html_likelihood(&Html::parse_document(url_or_html))
+    } else {
+        url_likelihood(url_or_html)
+    }
+}
+
+fn html_likelihood(document: &Html) -> RecipeLikelihood {
+    if has_json_ld_recipe(document) || MicroDataExtractor.find_recipe_container(document).is_some()
+    {
+        return RecipeLikelihood::Likely;
+    }
+
+    match recipe_class_counts(document) {
+        (0, 0) if has_recipe_headings(document) => RecipeLikelihood::Possible,
+        (0, 0) => RecipeLikelihood::Unlikely,
+        (0, _) | (_, 0) => RecipeLikelihood::Possible,
+        _ => RecipeLikelihood::Likely,
+    }
+}
+
+/// Both an ingredients heading and a method heading
+fn has_recipe_headings(document: &Html) -> bool {
+    let selector = Selector::parse("h1, h2, h3, h4, h5, h6").unwrap();
+    let headings: Vec<String> = document
+        .select(&selector)
+        .map(|heading| heading.text().collect::<String>().to_lowercase())
+        .collect();
+    let mentions = |words: &[&str]| {
+        headings
+            .iter()
+            .any(|heading| words.iter().any(|word| heading.contains(word)))
+    };
+    mentions(INGREDIENT_HEADINGS) && mentions(STEP_HEADINGS)
+}
+
+pub(crate) fn url_likelihood(url: &str) -> RecipeLikelihood {
+    let path = url
+        .split("://")
+        .nth(1)
+        .unwrap_or(url)
+        .split_once('/')
+        .map_or("", |(_, path)| path)
+        .to_lowercase();
+    if URL_KEYWORDS.iter().any(|word| path.contains(word)) {
+        RecipeLikelihood::Possible
+    } else {
+        RecipeLikelihood::Unlikely
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_structured_data_is_likely() {
+        let json_ld = r#"<html><head><script type="application/ld+json">
+            {"@context": "https://schema.org", "@graph": [
+                {"@type": "WebPage"},
+                {"@type": "Recipe", "name": "Pancakes"}
+            ]}
+            </script></head><body></body></html>"#;
+        assert_eq!(is_probably_recipe(json_ld), RecipeLikelihood::Likely);
+
+        let microdata = r#"<div itemscope itemtype="https://schema.org/Recipe">
+            <h1 itemprop="name">Pancakes</h1></div>"#;
+        assert_eq!(is_probably_recipe(microdata), RecipeLikelihood::Likely);
+    }
+
+    #[test]
+    fn test_recipe_card_classes() {
+        let full = r#"<div class="wprm-recipe-ingredients-container"></div>
+            <div class="wprm-recipe-instructions-container"></div>"#;
+        assert_eq!(is_probably_recipe(full), RecipeLikelihood::Likely);
+
+        let partial = r#"<ul class="recipe-ingredients"><li>2 eggs</li></ul>"#;
+        assert_eq!(is_probably_recipe(partial), RecipeLikelihood::Possible);
+    }
+
+    #[test]
+    fn test_headings() {
+        let html = "<h2>Zutaten</h2><ul><li>2 Eier</li></ul><h2>Zubereitung</h2><p>Braten.</p>";
+        assert_eq!(is_probably_recipe(html), RecipeLikelihood::Possible);
+
+        let article = "<h1>Our favourite ingredients this spring</h1><p>Asparagus.</p>";
+        assert_eq!(is_probably_recipe(article), RecipeLikelihood::Unlikely);
+    }
+
+    #[test]
+    fn test_urls() {
+        assert_eq!(
+            is_probably_recipe("https://example.com/recipes/pancakes"),
+            RecipeLikelihood::Possible
+        );
+        assert_eq!(
+            is_probably_recipe("https://www.chefkoch.de/rezepte/123/kuchen.html"),
+            RecipeLikelihood::Possible
+        );
+        // The host alone doesn't count
+        assert_eq!(
+            is_probably_recipe("https://recipes.example.com/about"),
+            RecipeLikelihood::Unlikely
+        );
+    }
+
+    #[test]
+    fn test_likelihood_ordering() {
+        assert!(RecipeLikelihood::Likely > RecipeLikelihood::Possible);
+        assert!(RecipeLikelihood::Possible > RecipeLikelihood::Unlikely);
+    }
+}
@@ -0,0 +1,161 @@
+use super::extractors::{has_json_ld_recipe, recipe_class_counts, MicroDataExtractor};
+use scraper::{Html, Selector};
+use std::fmt;
+
+/// Why no recipe could be extracted from a page
+///
+/// Returned in [`ImportError::NoExtractorMatched`](crate::ImportError::NoExtractorMatched)
+/// when every structured extractor failed and LLM text extraction was not
+/// available or failed too.
+#[derive(Debug, Clone, Default)]
+pub struct ExtractionDiagnostics {
+    /// The page that was parsed
+    pub url: String,
+    /// Contents of the page's `<title>`
+    pub page_title: Option<String>,
+    /// Number of `<script type="application/ld+json">` blocks
+    pub json_ld_scripts: usize,
+    /// Whether any JSON-LD block has a `Recipe` type
+    pub json_ld_recipe: bool,
+    /// Whether the page has a microdata `Recipe` item
+    pub microdata_recipe: bool,
+    /// Elements with a known recipe-card ingredient class
+    pub ingredient_class_count: usize,
+    /// Elements with a known recipe-card instruction class
+    pub instruction_class_count: usize,
+    /// Each structured extractor's error, in the order they were tried
+    pub extractor_errors: Vec<(String, String)>,
+    /// Why LLM text extraction didn't produce a recipe, if it was attempted
+    /// or unavailable
+    pub llm_error: Option<String>,
+}
+
+impl ExtractionDiagnostics {
+    /// Describe a page's recipe markup, given the extractor errors
+    pub(crate) fn new(document: &Html, url: &str, extractor_errors: Vec<(String, String)>) -> Self {
+        let title = Selector::parse("title").unwrap();
+        let scripts = Selector::parse("script[type='application/ld+json']").unwrap();
+        let (ingredient_class_count, instruction_class_count) = recipe_class_counts(document);
+
+        ExtractionDiagnostics {
+            url: url.to_string(),
+            page_title: document
+                .select(&title)
+                .next()
+                .map(|el| el.text().collect::<String>().trim().to_string())
+                .filter(|title| !title.is_empty()),
+            json_ld_scripts: document.select(&scripts).count(),
+            json_ld_recipe: has_json_ld_recipe(document),
+            microdata_recipe: MicroDataExtractor.find_recipe_container(document).is_some(),
+            ingredient_class_count,
+            instruction_class_count,
+            extractor_errors,
+            llm_error: None,
+        }
+    }
+}
+
+impl fmt::Display for ExtractionDiagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "No recipe found at {}", self.url)?;
+        if let Some(title) = &self.page_title {
+            writeln!(f, "  Page title: {}", title)?;
+        }
+        writeln!(
+            f,
+            "  JSON-LD scripts: {}{}",
+            self.json_ld_scripts,
+            if self.json_ld_recipe {
+                " (with a Recipe)"
+            } else {
+                " (no Recipe)"
+            }
+        )?;
+        writeln!(
+            f,
+            "  Microdata Recipe: {}",
+            if self.microdata_recipe { "yes" } else { "no" }
+        )?;
+        writeln!(
+            f,
+            "  Recipe card classes: {} ingredient, {} instruction elements",
+            self.ingredient_class_count, self.instruction_class_count
+        )?;
+        for (extractor, error) in &self.extractor_errors {
+            writeln!(f, "  {}: {}", extractor, error)?;
+        }
+        match &self.llm_error {
+            Some(error) => write!(f, "  LLM text extraction: {}", error),
+            None => write!(f, "  LLM text extraction: not attempted"),
+        }
+    }
+}
+
+impl std::error::Error for ExtractionDiagnostics {}
+
+/// A field a structured extractor found on the page but didn't put in the
+/// recipe
+///
+/// Reported in [`ImportReport::dropped_fields`](crate::ImportReport::dropped_fields)
+/// so lost metadata can be noticed, and written to the frontmatter with an
+/// `x-` prefix when unknown fields are kept.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct DroppedField {
+    /// Key as written in the source (`"aggregateRating"`, `"author"`)
+    pub key: String,
+    /// Value as found, flattened to text (JSON for nested objects)
+    pub value: String,
+    /// Why it was dropped
+    pub reason: DropReason,
+}
+
+/// Why a [`DroppedField`] was not used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum DropReason {
+    /// The extractor doesn't map this key to any recipe field
+    Unknown,
+    /// The value was empty once HTML and whitespace were removed
+    Empty,
+}
+
+impl fmt::Display for DropReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DropReason::Unknown => write!(f, "unknown field"),
+            DropReason::Empty => write!(f, "empty after cleaning"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnostics_describe_page() {
+        let document = Html::parse_document(
+            r#"<html><head><title> Weeknight Dinners </title>
+            <script type="application/ld+json">{"@type": "WebPage"}</script></head>
+            <body><ul class="recipe-ingredients"><li>2 eggs</li></ul></body></html>"#,
+        );
+        let mut diagnostics = ExtractionDiagnostics::new(
+            &document,
+            "https://example.com/dinners",
+            vec![("json-ld".to_string(), "No valid recipe".to_string())],
+        );
+        diagnostics.llm_error = Some("not configured".to_string());
+
+        assert_eq!(diagnostics.page_title.as_deref(), Some("Weeknight Dinners"));
+        assert_eq!(diagnostics.json_ld_scripts, 1);
+        assert!(!diagnostics.json_ld_recipe);
+        assert!(!diagnostics.microdata_recipe);
+        assert_eq!(diagnostics.ingredient_class_count, 1);
+        assert_eq!(diagnostics.instruction_class_count, 0);
+
+        let report = diagnostics.to_string();
+        assert!(report.contains("No recipe found at https://example.com/dinners"));
+        assert!(report.contains("JSON-LD scripts: 1 (no Recipe)"));
+        assert!(report.contains("json-ld: No valid recipe"));
+        assert!(report.contains("LLM text extraction: not configured"));
+    }
+}
@@ -0,0 +1,198 @@
+use super::detect::{url_likelihood, RecipeLikelihood};
+use super::extractors::item_list_urls;
+use reqwest::Url;
+use scraper::{Html, Selector};
+
+/// Path segments of listing pages rather than recipes
+const LISTING_SEGMENTS: &[&str] = &[
+    "category",
+    "categories",
+    "tag",
+    "tags",
+    "page",
+    "search",
+    "collection",
+    "collections",
+    "author",
+];
+
+/// Find the recipe pages a category/index page links to.
+///
+/// Uses, in order, the first source that finds anything: a JSON-LD
+/// `ItemList`, the `<loc>` entries of a sitemap (when `html` is one), then
+/// links on the same site whose path has a recipe word (like
+/// [`is_probably_recipe`](crate::is_probably_recipe) on a URL) and ends in a
+/// slug or ID rather than a listing. URLs are made absolute, stripped of
+/// fragments and deduplicated, in page order. Nothing is fetched.
+///
+/// # Example
+/// ```
+/// use cooklang_import::discover_recipe_links;
+///
+/// let html = r#"<a href="/recipes/fluffy-pancakes">Pancakes</a>
+///     <a href="/recipes/category/breakfast">Breakfast</a>
+///     <a href="/about">About</a>"#;
+/// assert_eq!(
+///     discover_recipe_links(html, "https://example.com/recipes/"),
+///     vec!["https://example.com/recipes/fluffy-pancakes"]
+/// );
+/// ```
+pub fn discover_recipe_links(html: &str, page_url: &str) -> Vec<String> {
+    let Ok(base) = Url::parse(page_url) else {
+        return Vec::new();
+    };
+
+    if html.contains("<urlset") {
+        return dedupe(&base, sitemap_locations(html), |_| true);
+    }
+
+    let document = Html::parse_document(html);
+    let listed = dedupe(&base, item_list_urls(&document), |_| true);
+    if !listed.is_empty() {
+        return listed;
+    }
+
+    let selector = Selector::parse("a[href]").unwrap();
+    let links = document
+        .select(&selector)
+        .filter_map(|link| link.value().attr("href"))
+        .map(str::to_string)
+        .collect();
+    dedupe(&base, links, |url| {
+        same_site(url, &base) && looks_like_recipe(url)
+    })
+}
+
+/// `<loc>` values of a sitemap, skipping nested sitemaps
+fn sitemap_locations(xml: &str) -> Vec<String> {
+    xml.split("<loc>")
+        .skip(1)
+        .filter_map(|rest| rest.split_once("</loc>"))
+        .map(|(loc, _)| {
+            loc.trim()
+                .trim_start_matches("<![CDATA[")
+                .trim_end_matches("]]>")
+                .replace("&amp;", "&")
+        })
+        .filter(|loc| !loc.ends_with(".xml"))
+        .collect()
+}
+
+/// Resolve `links` against `base`, keep those passing `keep`, drop
+/// fragments, duplicates and the page itself
+fn dedupe(base: &Url, links: Vec<String>, keep: impl Fn(&Url) -> bool) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    for link in links {
+        let Ok(mut url) = base.join(link.trim()) else {
+            continue;
+        };
+        if !matches!(url.scheme(), "http" | "https") || !keep(&url) {
+            continue;
+        }
+        url.set_fragment(None);
+        let url = url.to_string();
+        if url != base.as_str() && !urls.contains(&url) {
+            urls.push(url);
+        }
+    }
+    urls
+}
+
+/// Same host, ignoring a leading `www.`
+fn same_site(url: &Url, base: &Url) -> bool {
+    let host = |url: &Url| {
+        url.host_str()
+            .map(|host| host.trim_start_matches("www.").to_lowercase())
+    };
+    host(url) == host(base)
+}
+
+/// A recipe word in the path, no listing segment, and a last segment that
+/// reads like a slug or ID ("fluffy-pancakes", "12345") rather than a
+/// section name ("breakfast")
+fn looks_like_recipe(url: &Url) -> bool {
+    if url_likelihood(url.as_str()) == RecipeLikelihood::Unlikely {
+        return false;
+    }
+    let segments: Vec<String> = url
+        .path_segments()
+        .map(|segments| {
+            segments
+                .filter(|segment| !segment.is_empty())
+                .map(str::to_lowercase)
+                .collect()
+        })
+        .unwrap_or_default();
+    if segments
+        .iter()
+        .any(|segment| LISTING_SEGMENTS.contains(&segment.as_str()))
+    {
+        return false;
+    }
+    segments
+        .last()
+        .is_some_and(|last| last.contains(['-', '_']) || last.chars().any(|c| c.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_item_list() {
+        let html = r#"<html><head><script type="application/ld+json">
+            {"@context": "https://schema.org", "@type": "ItemList", "itemListElement": [
+                {"@type": "ListItem", "position": 1, "url": "https://example.com/soup"},
+                {"@type": "ListItem", "position": 2, "item": {"@id": "/stew"}},
+                {"@type": "ListItem", "position": 3, "item": "https://example.com/soup#top"}
+            ]}
+            </script></head>
+            <body><a href="/recipes/other-recipe">Other</a></body></html>"#;
+
+        assert_eq!(
+            discover_recipe_links(html, "https://example.com/collections/winter"),
+            vec!["https://example.com/soup", "https://example.com/stew"]
+        );
+    }
+
+    #[test]
+    fn test_sitemap() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+              <url><loc>https://example.com/recipes/pancakes</loc></url>
+              <url><loc> https://example.com/recipes/waffles?a=1&amp;b=2 </loc></url>
+            </urlset>"#;
+
+        assert_eq!(
+            discover_recipe_links(xml, "https://example.com/sitemap.xml"),
+            vec![
+                "https://example.com/recipes/pancakes",
+                "https://example.com/recipes/waffles?a=1&b=2"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_link_heuristics() {
+        let html = r#"<html><body>
+            <a href="/recipes/">All recipes</a>
+            <a href="/recipes/breakfast">Breakfast</a>
+            <a href="/recipes/tag/quick-meals">Quick</a>
+            <a href="/recipes/page/2">Next</a>
+            <a href="/recipes/fluffy-pancakes">Pancakes</a>
+            <a href="/recipes/fluffy-pancakes#comments">Comments</a>
+            <a href="https://www.example.com/recipe/12345">Waffles</a>
+            <a href="https://other.com/recipes/crepes-suzette">Crepes</a>
+            <a href="/blog/my-holiday">Holiday</a>
+            <a href="mailto:hi@example.com">Mail</a>
+        </body></html>"#;
+
+        assert_eq!(
+            discover_recipe_links(html, "https://example.com/recipes/"),
+            vec![
+                "https://example.com/recipes/fluffy-pancakes",
+                "https://www.example.com/recipe/12345"
+            ]
+        );
+    }
+}
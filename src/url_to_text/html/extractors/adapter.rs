@@ -0,0 +1,337 @@
+use super::{Extractor, ParsingContext, SharedContext};
+use crate::error::ImportError;
+use crate::model::{MetadataKey, Recipe};
+use crate::pipelines::RecipeComponents;
+use async_trait::async_trait;
+use scraper::{ElementRef, Html, Selector};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::Path;
+
+/// CSS selectors for one site's recipe markup, written as a TOML file so
+/// sites can be supported without writing Rust:
+///
+/// ```toml
+/// name = "mysite"
+/// domains = ["mysite.com"]
+/// title = "h1.recipe-title"
+/// ingredients = ".ingredients li"
+/// instructions = ".method ol > li"
+///
+/// [metadata]
+/// servings = ".recipe-yield"
+/// "prep time" = ".prep-time"
+/// ```
+///
+/// `ingredients` and `instructions` select one element per ingredient or
+/// step; every other selector uses its first match. Images are read from
+/// `src` or `content`, everything else from the element's text. A `<br>`
+/// inside an instructions element starts a new step. Metadata keys are
+/// written the way [`MetadataKey`] spells them.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SiteAdapter {
+    /// Short name, shown in reports
+    pub name: String,
+    /// Hosts the adapter is for; subdomains match too
+    #[serde(default)]
+    pub domains: Vec<String>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image: Option<String>,
+    pub ingredients: String,
+    pub instructions: String,
+    /// Frontmatter key → selector
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl SiteAdapter {
+    /// Read an adapter from a TOML file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ImportError> {
+        let path = path.as_ref();
+        let toml = std::fs::read_to_string(path)?;
+        Self::from_toml(&toml)
+            .map_err(|e| ImportError::ParseError(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Parse an adapter and check that all of its selectors are valid CSS
+    pub fn from_toml(toml: &str) -> Result<Self, ImportError> {
+        let adapter: SiteAdapter = config::Config::builder()
+            .add_source(config::File::from_str(toml, config::FileFormat::Toml))
+            .build()
+            .and_then(|settings| settings.try_deserialize())?;
+        for (field, selector) in adapter.selectors() {
+            parse_selector(field, selector)?;
+        }
+        Ok(adapter)
+    }
+
+    /// Whether the adapter is for the host of `url`
+    pub fn matches(&self, url: &str) -> bool {
+        let host = url
+            .split("://")
+            .nth(1)
+            .unwrap_or(url)
+            .split(['/', ':', '?', '#'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        self.domains.iter().any(|domain| {
+            let domain = domain.to_lowercase();
+            host == domain || host.ends_with(&format!(".{}", domain))
+        })
+    }
+
+    /// Run the adapter on a saved page, as the URL pipeline would for `url`
+    pub fn extract(&self, html: &str, url: &str) -> Result<RecipeComponents, ImportError> {
+        let context = ParsingContext {
+            url: url.to_string(),
+            document: Html::parse_document(html),
+            texts: None,
+        };
+        let recipe = self
+            .parse_page(&context)
+            .map_err(|e| ImportError::ParseError(e.to_string()))?;
+        Ok(crate::stages::normalize(
+            crate::pipelines::url::recipe_to_components(&recipe),
+        ))
+    }
+
+    /// Fields whose selector matches nothing in `html`, with the selector,
+    /// so adapter authors can see what to fix
+    pub fn unmatched(&self, html: &str) -> Vec<(String, String)> {
+        let document = Html::parse_document(html);
+        self.selectors()
+            .into_iter()
+            .filter(|(field, selector)| match parse_selector(field, selector) {
+                Ok(selector) => document.select(&selector).next().is_none(),
+                Err(_) => true,
+            })
+            .map(|(field, selector)| (field.to_string(), selector.to_string()))
+            .collect()
+    }
+
+    /// Every (field, selector) pair: the fixed fields, then metadata by key
+    fn selectors(&self) -> Vec<(&str, &str)> {
+        let mut selectors = Vec::new();
+        for (field, selector) in [
+            ("title", &self.title),
+            ("description", &self.description),
+            ("image", &self.image),
+        ] {
+            if let Some(selector) = selector {
+                selectors.push((field, selector.as_str()));
+            }
+        }
+        selectors.push(("ingredients", self.ingredients.as_str()));
+        selectors.push(("instructions", self.instructions.as_str()));
+        for (key, selector) in &self.metadata {
+            selectors.push((key.as_str(), selector.as_str()));
+        }
+        selectors
+    }
+}
+
+impl SiteAdapter {
+    /// The recipe picked out of the page by the adapter's selectors
+    pub fn parse_page(
+        &self,
+        context: &ParsingContext,
+    ) -> Result<Recipe, Box<dyn std::error::Error>> {
+        let document = &context.document;
+        let all = |field: &str, selector: &str| -> Result<Vec<String>, ImportError> {
+            let selector = parse_selector(field, selector)?;
+            Ok(document
+                .select(&selector)
+                .map(element_text)
+                .filter(|text| !text.is_empty())
+                .collect())
+        };
+        let first = |field: &str, selector: Option<&str>| -> Result<Option<String>, ImportError> {
+            match selector {
+                Some(selector) => Ok(all(field, selector)?.into_iter().next()),
+                None => Ok(None),
+            }
+        };
+
+        let ingredients = all("ingredients", &self.ingredients)?;
+        if ingredients.is_empty() {
+            return Err(format!(
+                "{}: no ingredients matched `{}`",
+                self.name, self.ingredients
+            )
+            .into());
+        }
+
+        let image = match &self.image {
+            Some(selector) => {
+                let selector = parse_selector("image", selector)?;
+                document
+                    .select(&selector)
+                    .find_map(|element| {
+                        let value = element.value();
+                        value.attr("src").or_else(|| value.attr("content"))
+                    })
+                    .map(str::to_string)
+                    .into_iter()
+                    .collect()
+            }
+            None => Vec::new(),
+        };
+
+        let mut metadata = std::collections::HashMap::new();
+        metadata.insert(MetadataKey::Source.to_string(), context.url.clone());
+        for (key, selector) in &self.metadata {
+            if let Some(value) = first(key, Some(selector))? {
+                metadata.insert(MetadataKey::canonical(key), value);
+            }
+        }
+
+        Ok(Recipe {
+            name: first("title", self.title.as_deref())?.unwrap_or_default(),
+            description: first("description", self.description.as_deref())?,
+            image,
+            ingredients,
+            instructions: {
+                let selector = parse_selector("instructions", &self.instructions)?;
+                document
+                    .select(&selector)
+                    .flat_map(element_lines)
+                    .collect::<Vec<_>>()
+                    .join("\n\n")
+            },
+            equipment: Vec::new(),
+            metadata,
+            dropped_fields: Vec::new(),
+        })
+    }
+}
+
+#[async_trait]
+impl Extractor for SiteAdapter {
+    fn name(&self) -> &'static str {
+        "site-adapter"
+    }
+
+    async fn parse(&self, context: &SharedContext) -> Result<Recipe, Box<dyn Error + Send + Sync>> {
+        context
+            .read(|context| self.parse_page(context))
+            .map_err(|e| e.to_string().into())
+    }
+}
+
+fn parse_selector(field: &str, selector: &str) -> Result<Selector, ImportError> {
+    Selector::parse(selector).map_err(|e| {
+        ImportError::ParseError(format!("invalid {} selector `{}`: {}", field, selector, e))
+    })
+}
+
+/// An element's text with whitespace collapsed. Text from separate child
+/// elements is kept apart, so `<span>1</span><span>cup</span>` reads
+/// "1 cup".
+fn element_text(element: ElementRef) -> String {
+    tidy(&element.text().collect::<Vec<_>>().join(" "))
+}
+
+/// An element's text split at `<br>`, one tidied line per non-empty part
+fn element_lines(element: ElementRef) -> Vec<String> {
+    let mut text = String::new();
+    for node in element.descendants() {
+        match node.value() {
+            scraper::Node::Text(t) => {
+                text.push_str(t);
+                text.push(' ');
+            }
+            scraper::Node::Element(e) if e.name() == "br" => text.push('\n'),
+            _ => {}
+        }
+    }
+    text.lines()
+        .map(tidy)
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+/// Collapse whitespace, without leaving a space before punctuation that
+/// ends a word or inside brackets ("flour , sifted" → "flour, sifted", but
+/// ".5 cup" stays)
+fn tidy(text: &str) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let mut tidied = String::new();
+    for (i, word) in words.iter().enumerate() {
+        let closes = word.len() == 1 && ",.;:!?)".contains(*word) || word.starts_with(')');
+        let after_open = i > 0 && words[i - 1].ends_with('(');
+        if i > 0 && !closes && !after_open {
+            tidied.push(' ');
+        }
+        tidied.push_str(word);
+    }
+    tidied
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADAPTER: &str = r#"
+        name = "mysite"
+        domains = ["mysite.com"]
+        title = "h1"
+        image = ".hero img"
+        ingredients = ".ingredients li"
+        instructions = ".method li"
+
+        [metadata]
+        prep_time = ".prep"
+        difficulty = ".difficulty"
+    "#;
+
+    const PAGE: &str = r#"<html><body>
+        <h1>Fried  Eggs</h1>
+        <div class="hero"><img src="https://mysite.com/eggs.jpg"></div>
+        <ul class="ingredients"><li>2 eggs</li><li>1 tbsp <b>butter</b></li></ul>
+        <ol class="method"><li>Melt the <a href="/butter">butter</a>.</li><li>Fry the eggs.<br>Serve.</li></ol>
+        <span class="prep">5 minutes</span>
+    </body></html>"#;
+
+    fn context(html: &str) -> ParsingContext {
+        ParsingContext {
+            url: "https://www.mysite.com/eggs".to_string(),
+            document: Html::parse_document(html),
+            texts: None,
+        }
+    }
+
+    #[test]
+    fn test_adapter_extracts_recipe() {
+        let adapter = SiteAdapter::from_toml(ADAPTER).unwrap();
+        assert!(adapter.matches("https://www.mysite.com/eggs"));
+        assert!(!adapter.matches("https://othersite.com/eggs"));
+
+        let recipe = adapter.parse_page(&context(PAGE)).unwrap();
+        assert_eq!(recipe.name, "Fried Eggs");
+        assert_eq!(recipe.image, vec!["https://mysite.com/eggs.jpg"]);
+        assert_eq!(recipe.ingredients, vec!["2 eggs", "1 tbsp butter"]);
+        assert_eq!(
+            recipe.instructions,
+            "Melt the butter.\n\nFry the eggs.\n\nServe."
+        );
+        assert_eq!(recipe.metadata.get("prep time").unwrap(), "5 minutes");
+
+        assert_eq!(
+            adapter.unmatched(PAGE),
+            vec![("difficulty".to_string(), ".difficulty".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_adapter_errors() {
+        let invalid = ADAPTER.replace(".method li", ".method >>> li");
+        assert!(SiteAdapter::from_toml(&invalid).is_err());
+        assert!(SiteAdapter::from_toml("name = \"incomplete\"").is_err());
+
+        let adapter = SiteAdapter::from_toml(ADAPTER).unwrap();
+        assert!(adapter.parse_page(&context("<h1>Eggs</h1>")).is_err());
+    }
+}
@@ -0,0 +1,132 @@
+/// Turn an ISO 8601 duration into words: "PT30M" → "30 minutes",
+/// "PT1H30M" → "1 hour 30 minutes". Handles ranges ("PT15-20M") and
+/// seconds ("PT5400.0S"); anything else is returned unchanged.
+pub(crate) fn convert_duration(duration: &str) -> String {
+    if let Some(duration) = duration.strip_prefix("PT") {
+        let mut result = String::new();
+
+        // Handle hours
+        if let Some(h_pos) = duration.find('H') {
+            let hours: u32 = duration[..h_pos].parse().unwrap_or(0);
+            result.push_str(&format!(
+                "{} hour{}",
+                hours,
+                if hours == 1 { "" } else { "s" }
+            ));
+        }
+
+        // Handle minutes (including ranges)
+        if let Some(m_pos) = duration.find('M') {
+            let start = duration.find('H').map(|p| p + 1).unwrap_or(0);
+            let minutes_str = &duration[start..m_pos];
+
+            // Check if it's a range (e.g., "15-20")
+            if minutes_str.contains('-') {
+                // For ranges, just use the full range string
+                if !result.is_empty() {
+                    result.push(' ');
+                }
+                result.push_str(&format!("{minutes_str} minutes"));
+            } else if let Ok(minutes) = minutes_str.parse::<u32>() {
+                // Convert minutes > 60 to hours and minutes
+                if minutes >= 60 {
+                    let hours = minutes / 60;
+                    let remaining_minutes = minutes % 60;
+
+                    if !result.is_empty() {
+                        result.push(' ');
+                    }
+                    result.push_str(&format!(
+                        "{} hour{}",
+                        hours,
+                        if hours == 1 { "" } else { "s" }
+                    ));
+
+                    if remaining_minutes > 0 {
+                        result.push_str(&format!(
+                            " {} minute{}",
+                            remaining_minutes,
+                            if remaining_minutes == 1 { "" } else { "s" }
+                        ));
+                    }
+                } else {
+                    if !result.is_empty() {
+                        result.push(' ');
+                    }
+                    result.push_str(&format!(
+                        "{} minute{}",
+                        minutes,
+                        if minutes == 1 { "" } else { "s" }
+                    ));
+                }
+            }
+        }
+
+        // Handle seconds (including decimal values like 5400.0S)
+        if let Some(s_pos) = duration.find('S') {
+            let start = duration.rfind(['H', 'M']).map(|p| p + 1).unwrap_or(0);
+            let seconds_str = &duration[start..s_pos];
+
+            if let Ok(seconds) = seconds_str.parse::<f64>() {
+                let total_minutes = (seconds / 60.0).round() as u32;
+                let hours = total_minutes / 60;
+                let minutes = total_minutes % 60;
+
+                result.clear(); // Clear any existing result
+
+                if hours > 0 {
+                    result.push_str(&format!(
+                        "{} hour{}",
+                        hours,
+                        if hours == 1 { "" } else { "s" }
+                    ));
+                }
+
+                if minutes > 0 {
+                    if !result.is_empty() {
+                        result.push(' ');
+                    }
+                    result.push_str(&format!(
+                        "{} minute{}",
+                        minutes,
+                        if minutes == 1 { "" } else { "s" }
+                    ));
+                }
+            }
+        }
+
+        if result.is_empty() {
+            duration.to_string()
+        } else {
+            result
+        }
+    } else {
+        duration.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duration_conversion() {
+        assert_eq!(convert_duration("PT30M"), "30 minutes");
+        assert_eq!(convert_duration("PT1H"), "1 hour");
+        assert_eq!(convert_duration("PT1H30M"), "1 hour 30 minutes");
+        assert_eq!(convert_duration("PT90M"), "1 hour 30 minutes");
+        assert_eq!(convert_duration("PT2H15M"), "2 hours 15 minutes");
+        assert_eq!(convert_duration("invalid"), "invalid");
+        // Test ranges
+        assert_eq!(convert_duration("PT15-20M"), "15-20 minutes");
+        assert_eq!(convert_duration("PT25-30M"), "25-30 minutes");
+        // Test seconds
+        assert_eq!(convert_duration("PT5400S"), "1 hour 30 minutes");
+        assert_eq!(convert_duration("PT5400.0S"), "1 hour 30 minutes");
+        assert_eq!(convert_duration("PT300S"), "5 minutes");
+        // Test large minute values
+        assert_eq!(convert_duration("PT150M"), "2 hours 30 minutes");
+        assert_eq!(convert_duration("PT180M"), "3 hours");
+        assert_eq!(convert_duration("PT65M"), "1 hour 5 minutes");
+    }
+}
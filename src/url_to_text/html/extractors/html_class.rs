@@ -1,8 +1,11 @@
-use super::{Extractor, ParsingContext};
-use crate::model::Recipe;
-use log::debug;
-use scraper::{Html, Selector};
+use super::{split_serving_size, Extractor, ParsingContext, SharedContext};
+use crate::model::{MetadataKey, Recipe};
+use crate::pipelines::equipment;
+use async_trait::async_trait;
+use scraper::{ElementRef, Html, Selector};
 use std::collections::HashMap;
+use std::error::Error;
+use tracing::debug;
 
 pub struct HtmlClassExtractor;
 
@@ -215,6 +218,17 @@ impl ClassMatchers {
         ClassMatchers { exact, fuzzy }
     }
 
+    /// Number of elements carrying one of the exact classes for `field`
+    fn count_exact_classes(&self, document: &Html, field: &str) -> usize {
+        self.exact
+            .get(field)
+            .into_iter()
+            .flatten()
+            .filter_map(|class_name| Selector::parse(&format!(".{class_name}")).ok())
+            .map(|selector| document.select(&selector).count())
+            .sum()
+    }
+
     fn find_by_class(&self, document: &Html, field: &str) -> Option<String> {
         // Try exact matches first
         if let Some(classes) = self.exact.get(field) {
@@ -322,6 +336,29 @@ impl ClassMatchers {
         items
     }
 
+    /// Extract ingredients with group headers preserved
+    ///
+    /// WPRM splits each ingredient into amount, unit, name and notes spans
+    /// separated only by markup whitespace, so those are joined explicitly
+    /// instead of taking the element's raw text. Group names become
+    /// `## Group` markers, like instruction sections.
+    fn extract_ingredients_with_sections(&self, document: &Html) -> Vec<String> {
+        let items = wprm_ingredients(document);
+        if !items.is_empty() {
+            debug!("Found {} ingredient lines using WPRM spans", items.len());
+            return items;
+        }
+
+        let items = tasty_items(document, "ingredients");
+        if !items.is_empty() {
+            debug!("Found {} ingredient lines using Tasty Recipes", items.len());
+            return items;
+        }
+
+        // Fall back to regular extraction without sections
+        self.extract_list_items(document, "ingredients")
+    }
+
     /// Extract instructions with section headers preserved
     fn extract_instructions_with_sections(&self, document: &Html) -> Vec<String> {
         let mut items = Vec::new();
@@ -423,13 +460,136 @@ impl ClassMatchers {
             }
         }
 
+        // Tasty Recipes marks groups with plain headings
+        let items = tasty_items(document, "instructions");
+        if !items.is_empty() {
+            debug!("Found {} instructions using Tasty Recipes", items.len());
+            return items;
+        }
+
         // Fall back to regular extraction without sections
         self.extract_list_items(document, "instructions")
     }
 }
 
-impl Extractor for HtmlClassExtractor {
-    fn parse(&self, context: &ParsingContext) -> Result<Recipe, Box<dyn std::error::Error>> {
+/// Element text with markup whitespace collapsed to single spaces
+fn element_text(element: ElementRef) -> String {
+    element
+        .text()
+        .flat_map(str::split_whitespace)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A group name as a `## Group` marker, skipping the card's own heading for
+/// the section (e.g. "Ingredients")
+fn group_marker(element: ElementRef, section: &str) -> Option<String> {
+    let name = element_text(element);
+    let name = name.trim_end_matches(':').trim();
+    if name.is_empty() || name.len() >= 100 || name.eq_ignore_ascii_case(section) {
+        return None;
+    }
+    Some(format!("## {}", name))
+}
+
+/// WPRM ingredient lines and group markers, in document order
+fn wprm_ingredients(document: &Html) -> Vec<String> {
+    let containers = Selector::parse(".wprm-recipe-ingredients-container").unwrap();
+    let entries = Selector::parse(
+        ".wprm-recipe-ingredient-group-name, .wprm-recipe-group-name, li.wprm-recipe-ingredient",
+    )
+    .unwrap();
+    let parts: Vec<Selector> = ["amount", "unit", "name", "notes"]
+        .iter()
+        .map(|part| Selector::parse(&format!(".wprm-recipe-ingredient-{}", part)).unwrap())
+        .collect();
+
+    let mut items = Vec::new();
+    for container in document.select(&containers) {
+        for entry in container.select(&entries) {
+            if entry.value().name() != "li" {
+                items.extend(group_marker(entry, "ingredients"));
+                continue;
+            }
+            let spans: Vec<String> = parts
+                .iter()
+                .filter_map(|part| entry.select(part).next())
+                .map(element_text)
+                .filter(|text| !text.is_empty())
+                .collect();
+            let line = if spans.is_empty() {
+                element_text(entry)
+            } else {
+                spans.join(" ")
+            };
+            if !line.is_empty() {
+                items.push(line);
+            }
+        }
+    }
+
+    // Group markers alone aren't an ingredient list
+    if items.iter().all(|item| item.starts_with("## ")) {
+        items.clear();
+    }
+    items
+}
+
+/// Names in WPRM's equipment block, e.g. `<div
+/// class="wprm-recipe-equipment-name">Skillet</div>`
+fn wprm_equipment(document: &Html) -> Vec<String> {
+    let names = Selector::parse(".wprm-recipe-equipment-name").unwrap();
+    equipment::dedup(document.select(&names).map(element_text))
+}
+
+/// Tasty Recipes list lines for `section` ("ingredients" or "instructions"),
+/// with `h3`/`h4` group headings as markers
+fn tasty_items(document: &Html, section: &str) -> Vec<String> {
+    let body = Selector::parse(&format!(".tasty-recipes-{}-body", section)).unwrap();
+    let container = Selector::parse(&format!(".tasty-recipes-{}", section)).unwrap();
+    let entries = Selector::parse("h3, h4, li").unwrap();
+
+    let Some(root) = document
+        .select(&body)
+        .next()
+        .or_else(|| document.select(&container).next())
+    else {
+        return Vec::new();
+    };
+
+    let mut items = Vec::new();
+    for entry in root.select(&entries) {
+        if entry.value().name() == "li" {
+            let line = element_text(entry);
+            if !line.is_empty() {
+                items.push(line);
+            }
+        } else {
+            items.extend(group_marker(entry, section));
+        }
+    }
+
+    if items.iter().all(|item| item.starts_with("## ")) {
+        items.clear();
+    }
+    items
+}
+
+/// Number of elements with known recipe-card ingredient and instruction classes
+pub(crate) fn recipe_class_counts(document: &Html) -> (usize, usize) {
+    let matchers = ClassMatchers::new();
+    (
+        matchers.count_exact_classes(document, "ingredients"),
+        matchers.count_exact_classes(document, "instructions"),
+    )
+}
+
+impl HtmlClassExtractor {
+    /// The recipe found through recipe-card class names
+    pub fn parse_page(
+        &self,
+        context: &ParsingContext,
+    ) -> Result<Recipe, Box<dyn std::error::Error>> {
         debug!("Attempting to extract recipe using HTML class matchers");
 
         let matchers = ClassMatchers::new();
@@ -459,35 +619,39 @@ impl Extractor for HtmlClassExtractor {
             description = Some(desc);
         }
 
-        // Extract ingredients
-        let ingredients = matchers.extract_list_items(&context.document, "ingredients");
+        // Extract ingredients (with group headers preserved)
+        let ingredients = matchers.extract_ingredients_with_sections(&context.document);
 
         // Extract instructions (with section headers preserved)
         let instructions_list = matchers.extract_instructions_with_sections(&context.document);
 
         // Extract metadata
         if let Some(prep_time) = matchers.find_by_class(&context.document, "prep_time") {
-            metadata.insert("prep_time".to_string(), prep_time);
+            metadata.insert(MetadataKey::PrepTime.to_string(), prep_time);
         }
 
         if let Some(cook_time) = matchers.find_by_class(&context.document, "cook_time") {
-            metadata.insert("cook_time".to_string(), cook_time);
+            metadata.insert(MetadataKey::CookTime.to_string(), cook_time);
         }
 
         if let Some(total_time) = matchers.find_by_class(&context.document, "total_time") {
-            metadata.insert("total_time".to_string(), total_time);
+            metadata.insert(MetadataKey::TotalTime.to_string(), total_time);
         }
 
         if let Some(servings) = matchers.find_by_class(&context.document, "servings") {
-            metadata.insert("servings".to_string(), servings);
+            let (servings, serving_size) = split_serving_size(&servings);
+            metadata.insert(MetadataKey::Servings.to_string(), servings);
+            if let Some(serving_size) = serving_size {
+                metadata.insert(MetadataKey::ServingSize.to_string(), serving_size);
+            }
         }
 
         if let Some(notes) = matchers.find_by_class(&context.document, "notes") {
-            metadata.insert("notes".to_string(), notes);
+            metadata.insert(MetadataKey::Notes.to_string(), notes);
         }
 
         // Add source URL to metadata
-        metadata.insert("source_url".to_string(), context.url.clone());
+        metadata.insert(MetadataKey::Source.to_string(), context.url.clone());
 
         // Validation
         if name.is_empty() {
@@ -516,7 +680,22 @@ impl Extractor for HtmlClassExtractor {
             image: Vec::new(),
             ingredients,
             instructions,
+            equipment: wprm_equipment(&context.document),
             metadata,
+            dropped_fields: Vec::new(),
         })
     }
 }
+
+#[async_trait]
+impl Extractor for HtmlClassExtractor {
+    fn name(&self) -> &'static str {
+        "html-class"
+    }
+
+    async fn parse(&self, context: &SharedContext) -> Result<Recipe, Box<dyn Error + Send + Sync>> {
+        context
+            .read(|context| self.parse_page(context))
+            .map_err(|e| e.to_string().into())
+    }
+}
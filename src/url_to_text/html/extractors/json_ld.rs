@@ -1,12 +1,18 @@
-use super::{Extractor, ParsingContext};
-use crate::model::Recipe;
+use super::{
+    convert_duration, format_rating, split_serving_size, Extractor, ParsingContext, SharedContext,
+};
+use crate::model::{MetadataKey, Recipe};
+use crate::pipelines::equipment;
+use crate::url_to_text::html::diagnostics::{DropReason, DroppedField};
+use async_trait::async_trait;
 use html_escape::decode_html_entities;
-use log::debug;
 use scraper::Selector;
 use serde::Deserialize;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::convert::TryFrom;
+use std::error::Error;
+use tracing::debug;
 
 pub struct JsonLdExtractor;
 
@@ -14,9 +20,10 @@ impl JsonLdExtractor {
     fn convert_to_recipe(&self, json_ld_recipe: JsonLdRecipe, url: &str) -> Recipe {
         let recipe_name = json_ld_recipe.recipe_name();
         let mut metadata = HashMap::new();
+        let mut dropped_fields = unknown_fields(&json_ld_recipe.extra);
 
         // Add source URL (primary key: source)
-        metadata.insert("source".to_string(), url.to_string());
+        metadata.insert(MetadataKey::Source.to_string(), url.to_string());
 
         // Map author
         if let Some(author) = json_ld_recipe.author {
@@ -32,10 +39,11 @@ impl JsonLdExtractor {
                     }
                 }
             };
-            if let Some(name) = author_name {
-                if !name.is_empty() {
-                    metadata.insert("author".to_string(), name);
+            match author_name {
+                Some(name) if !name.trim().is_empty() => {
+                    metadata.insert(MetadataKey::Author.to_string(), name);
                 }
+                name => dropped_fields.push(empty_field("author", name.unwrap_or_default())),
             }
         }
 
@@ -53,8 +61,14 @@ impl JsonLdExtractor {
                         .unwrap_or_default()
                 }
             };
-            if !yield_str.is_empty() {
-                metadata.insert("servings".to_string(), yield_str);
+            let (servings, serving_size) = split_serving_size(&yield_str);
+            if !servings.is_empty() {
+                metadata.insert(MetadataKey::Servings.to_string(), servings);
+            } else if serving_size.is_none() {
+                dropped_fields.push(empty_field("recipeYield", yield_str));
+            }
+            if let Some(serving_size) = serving_size {
+                metadata.insert(MetadataKey::ServingSize.to_string(), serving_size);
             }
         }
 
@@ -65,26 +79,37 @@ impl JsonLdExtractor {
                 RecipeCategory::Multiple(v) => v.join(", "),
             };
             if !category_str.is_empty() {
-                metadata.insert("course".to_string(), category_str);
+                metadata.insert(MetadataKey::Course.to_string(), category_str);
+            } else {
+                dropped_fields.push(empty_field("recipeCategory", category_str));
             }
         }
 
         // Map time fields (use specific keys, not duplicates)
         if let Some(total_time) = json_ld_recipe.total_time {
             if !total_time.is_empty() {
-                metadata.insert("time required".to_string(), convert_duration(&total_time));
+                metadata.insert(
+                    MetadataKey::TotalTime.to_string(),
+                    convert_duration(&total_time),
+                );
             }
         }
 
         if let Some(prep_time) = json_ld_recipe.prep_time {
             if !prep_time.is_empty() {
-                metadata.insert("prep time".to_string(), convert_duration(&prep_time));
+                metadata.insert(
+                    MetadataKey::PrepTime.to_string(),
+                    convert_duration(&prep_time),
+                );
             }
         }
 
         if let Some(cook_time) = json_ld_recipe.cook_time {
             if !cook_time.is_empty() {
-                metadata.insert("cook time".to_string(), convert_duration(&cook_time));
+                metadata.insert(
+                    MetadataKey::CookTime.to_string(),
+                    convert_duration(&cook_time),
+                );
             }
         }
 
@@ -95,7 +120,9 @@ impl JsonLdExtractor {
                 RecipeCuisine::Multiple(v) => v.join(", "),
             };
             if !cuisine_str.is_empty() {
-                metadata.insert("cuisine".to_string(), cuisine_str);
+                metadata.insert(MetadataKey::Cuisine.to_string(), cuisine_str);
+            } else {
+                dropped_fields.push(empty_field("recipeCuisine", cuisine_str));
             }
         }
 
@@ -109,7 +136,11 @@ impl JsonLdExtractor {
                     .collect::<Vec<String>>()
                     .join(", "),
             };
-            metadata.insert("diet".to_string(), diet_str);
+            if !diet_str.is_empty() {
+                metadata.insert(MetadataKey::Diet.to_string(), diet_str);
+            } else {
+                dropped_fields.push(empty_field("suitableForDiet", diet_str));
+            }
         }
 
         // Map keywords as tags
@@ -119,7 +150,9 @@ impl JsonLdExtractor {
                 Keywords::Multiple(v) => v.join(", "),
             };
             if !tags.is_empty() {
-                metadata.insert("tags".to_string(), tags);
+                metadata.insert(MetadataKey::Tags.to_string(), tags);
+            } else {
+                dropped_fields.push(empty_field("keywords", tags));
             }
         }
 
@@ -169,16 +202,73 @@ impl JsonLdExtractor {
             if let Some(serving_size) = &nutrition.serving_size {
                 if !serving_size.is_empty() {
                     nutrition_lines.push(format!("  serving size: {}", serving_size));
+                    // Nutrition facts are the most precise source for serving size
+                    metadata.insert(MetadataKey::ServingSize.to_string(), serving_size.clone());
                 }
             }
             if !nutrition_lines.is_empty() {
                 metadata.insert(
-                    "nutrition".to_string(),
+                    MetadataKey::Nutrition.to_string(),
                     format!("\n{}", nutrition_lines.join("\n")),
                 );
             }
         }
 
+        // Map aggregateRating as "4.7 (1234 reviews)"
+        if let Some(aggregate_rating) = &json_ld_recipe.aggregate_rating {
+            // Some sites wrap it in an array
+            let aggregate_rating = match aggregate_rating {
+                Value::Array(ratings) => ratings.first().unwrap_or(&Value::Null),
+                rating => rating,
+            };
+            let field = |key: &str| {
+                aggregate_rating
+                    .get(key)
+                    .map(value_text)
+                    .filter(|text| !text.trim().is_empty())
+            };
+            let rating = field("ratingValue").and_then(|value| {
+                format_rating(
+                    &value,
+                    field("ratingCount")
+                        .or_else(|| field("reviewCount"))
+                        .as_deref(),
+                    field("bestRating").as_deref(),
+                )
+            });
+            match rating {
+                Some(rating) => {
+                    metadata.insert(MetadataKey::Rating.to_string(), rating);
+                }
+                None => dropped_fields
+                    .push(empty_field("aggregateRating", value_text(aggregate_rating))),
+            }
+        }
+
+        // Map the video's URL: a VideoObject's file, else its player
+        if let Some(video) = &json_ld_recipe.video {
+            match video_url(video) {
+                Some(url) => {
+                    metadata.insert(MetadataKey::Video.to_string(), url);
+                }
+                None => dropped_fields.push(empty_field("video", value_text(video))),
+            }
+        }
+
+        // Equipment from the recipe's and its steps' tools
+        let mut tools = json_ld_recipe
+            .tool
+            .as_ref()
+            .map(tool_names)
+            .unwrap_or_default();
+        tools.extend(json_ld_recipe.step_tools);
+        let equipment = equipment::dedup(tools);
+        if equipment.is_empty() {
+            if let Some(tool) = &json_ld_recipe.tool {
+                dropped_fields.push(empty_field("tool", value_text(tool)));
+            }
+        }
+
         // Extract ingredients as Vec<String>
         let ingredients = match json_ld_recipe.recipe_ingredient {
             Some(RecipeIngredients::Strings(ingredients)) => ingredients
@@ -301,26 +391,23 @@ impl JsonLdExtractor {
             None => String::new(),
         };
 
+        let description = json_ld_recipe.description.and_then(|desc| {
+            let raw = match desc {
+                DescriptionType::String(d) => d,
+                DescriptionType::Object(d) => d.text,
+            };
+            let decoded = decode_html_symbols(&raw);
+            if decoded.trim().is_empty() {
+                dropped_fields.push(empty_field("description", raw));
+                None
+            } else {
+                Some(decoded)
+            }
+        });
+
         Recipe {
             name: decode_html_symbols(&recipe_name),
-            description: json_ld_recipe.description.and_then(|desc| match desc {
-                DescriptionType::String(d) => {
-                    let decoded = decode_html_symbols(&d);
-                    if decoded.is_empty() {
-                        None
-                    } else {
-                        Some(decoded)
-                    }
-                }
-                DescriptionType::Object(d) => {
-                    let decoded = decode_html_symbols(&d.text);
-                    if decoded.is_empty() {
-                        None
-                    } else {
-                        Some(decoded)
-                    }
-                }
-            }),
+            description,
             image: json_ld_recipe.image.map_or(vec![], |img| match img {
                 ImageType::String(i) => vec![decode_html_symbols(&i)],
                 ImageType::MultipleStrings(imgs) => {
@@ -332,8 +419,97 @@ impl JsonLdExtractor {
             }),
             ingredients,
             instructions,
+            equipment,
             metadata,
+            dropped_fields,
+        }
+    }
+}
+
+/// Schema keys the extractor doesn't map, in key order. JSON-LD keywords
+/// (`@context`, `@type`, `@id`) are structure, not recipe data.
+fn unknown_fields(extra: &HashMap<String, Value>) -> Vec<DroppedField> {
+    let mut keys: Vec<&String> = extra.keys().filter(|key| !key.starts_with('@')).collect();
+    keys.sort();
+    keys.into_iter()
+        .map(|key| DroppedField {
+            key: key.clone(),
+            value: value_text(&extra[key]),
+            reason: DropReason::Unknown,
+        })
+        .collect()
+}
+
+/// Names of `HowToTool`s: plain strings, objects with a `name` (or
+/// `text`), or lists of either
+fn tool_names(tool: &Value) -> Vec<String> {
+    match tool {
+        Value::String(name) => vec![decode_html_symbols(name)],
+        Value::Array(tools) => tools.iter().flat_map(tool_names).collect(),
+        Value::Object(_) => tool
+            .get("name")
+            .or_else(|| tool.get("text"))
+            .and_then(Value::as_str)
+            .map(|name| vec![decode_html_symbols(name)])
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+/// Tool names from the `tool` of every step, in sections or not
+fn collect_step_tools(instructions: &Value, tools: &mut Vec<String>) {
+    match instructions {
+        Value::Array(steps) => {
+            for step in steps {
+                collect_step_tools(step, tools);
+            }
+        }
+        Value::Object(step) => {
+            if let Some(tool) = step.get("tool") {
+                tools.extend(tool_names(tool));
+            }
+            if let Some(steps) = step.get("itemListElement") {
+                collect_step_tools(steps, tools);
+            }
         }
+        _ => {}
+    }
+}
+
+/// URL of the first video: `contentUrl`, then `embedUrl`, then `url` of a
+/// VideoObject, or a plain URL string
+fn video_url(video: &Value) -> Option<String> {
+    let url = match video {
+        Value::String(url) => Some(url.as_str()),
+        Value::Array(videos) => return videos.iter().find_map(video_url),
+        Value::Object(_) => ["contentUrl", "embedUrl", "url"]
+            .iter()
+            .find_map(|key| video.get(key).and_then(Value::as_str))
+            .filter(|url| !url.trim().is_empty()),
+        _ => None,
+    }?;
+    let url = url.trim();
+    url.starts_with("http").then(|| url.to_string())
+}
+
+fn empty_field(key: &str, value: String) -> DroppedField {
+    DroppedField {
+        key: key.to_string(),
+        value,
+        reason: DropReason::Empty,
+    }
+}
+
+/// Strings and numbers as they are, lists of them comma-separated, anything
+/// else as compact JSON
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        Value::Array(items) if items.iter().all(|item| !item.is_object()) => {
+            items.iter().map(value_text).collect::<Vec<_>>().join(", ")
+        }
+        Value::Bool(_) | Value::Number(_) | Value::Array(_) | Value::Object(_) => value.to_string(),
     }
 }
 
@@ -380,6 +556,20 @@ struct JsonLdRecipe {
     keywords: Option<Keywords>,
     author: Option<Author>,
     nutrition: Option<NutritionInformation>,
+    /// `AggregateRating`, whose values may be numbers or strings
+    #[serde(rename = "aggregateRating")]
+    aggregate_rating: Option<Value>,
+    /// `VideoObject`, a list of them, or a URL
+    video: Option<Value>,
+    /// `HowToTool`s (or plain names) needed for the whole recipe
+    tool: Option<Value>,
+    /// Tool names listed on individual steps, read from the raw JSON since
+    /// steps come in several shapes
+    #[serde(skip)]
+    step_tools: Vec<String>,
+    /// Keys not mapped above, reported as dropped fields
+    #[serde(flatten)]
+    extra: HashMap<String, Value>,
 }
 
 impl JsonLdRecipe {
@@ -572,7 +762,9 @@ impl TryFrom<&Value> for JsonLdRecipe {
     type Error = serde_json::Error;
 
     fn try_from(value: &Value) -> Result<Self, Self::Error> {
-        serde_json::from_value(value.clone())
+        let mut recipe: JsonLdRecipe = serde_json::from_value(value.clone())?;
+        collect_step_tools(&value["recipeInstructions"], &mut recipe.step_tools);
+        Ok(recipe)
     }
 }
 
@@ -675,200 +867,369 @@ fn clean_diet_value(diet: &str) -> String {
         .to_string()
 }
 
-fn convert_duration(duration: &str) -> String {
-    // Convert ISO 8601 duration to human-readable format
-    // e.g., PT30M -> 30 minutes, PT1H30M -> 1 hour 30 minutes
-    // Also handle ranges like PT15-20M and seconds like PT5400.0S
-    if let Some(duration) = duration.strip_prefix("PT") {
-        let mut result = String::new();
-
-        // Handle hours
-        if let Some(h_pos) = duration.find('H') {
-            let hours: u32 = duration[..h_pos].parse().unwrap_or(0);
-            result.push_str(&format!(
-                "{} hour{}",
-                hours,
-                if hours == 1 { "" } else { "s" }
-            ));
-        }
+/// Deepest nesting of `@graph`s, arrays and `mainEntity`s searched for a
+/// recipe
+const MAX_DEPTH: usize = 8;
+
+/// Most references followed one inside another when resolving `@id`s
+const MAX_REFERENCES: usize = 3;
+
+/// Recipe fields whose `{"@id": ...}` references are resolved: the parts of
+/// a node the extractor reads. Other references (`isPartOf`,
+/// `mainEntityOfPage`) point back at the page and are kept as they are.
+const RESOLVED_FIELDS: &[&str] = &[
+    "recipeInstructions",
+    "author",
+    "image",
+    "video",
+    "nutrition",
+    "tool",
+    "aggregateRating",
+];
+
+/// Whether `value`'s `@type` is `name` or lists it (`["Recipe",
+/// "NewsArticle"]`), with or without a schema.org prefix (`schema:Recipe`,
+/// `https://schema.org/Recipe`)
+fn has_type(value: &Value, name: &str) -> bool {
+    let is_name = |type_value: &Value| {
+        type_value.as_str().is_some_and(|type_str| {
+            let type_str = type_str
+                .trim_start_matches("http://schema.org/")
+                .trim_start_matches("https://schema.org/")
+                .trim_start_matches("schema:");
+            type_str.eq_ignore_ascii_case(name)
+        })
+    };
+    match value.get("@type") {
+        Some(Value::Array(types)) => types.iter().any(is_name),
+        Some(type_value) => is_name(type_value),
+        None => false,
+    }
+}
 
-        // Handle minutes (including ranges)
-        if let Some(m_pos) = duration.find('M') {
-            let start = duration.find('H').map(|p| p + 1).unwrap_or(0);
-            let minutes_str = &duration[start..m_pos];
+fn is_recipe_type(value: &Value) -> bool {
+    has_type(value, "recipe")
+}
 
-            // Check if it's a range (e.g., "15-20")
-            if minutes_str.contains('-') {
-                // For ranges, just use the full range string
-                if !result.is_empty() {
-                    result.push(' ');
+/// Collect recipe objects and standalone step lists from one JSON-LD block
+///
+/// Recipes are found at the top, in arrays, in `@graph`s and as the
+/// `mainEntity` of a page or article. References to other nodes of the
+/// block (`{"@id": "#step-1"}`) in the fields the extractor reads are
+/// replaced by those nodes.
+fn collect_candidates(json_ld: &Value, recipes: &mut Vec<Value>, step_lists: &mut Vec<Value>) {
+    let mut nodes = HashMap::new();
+    index_nodes(json_ld, &mut nodes, 0);
+    collect_nodes(json_ld, &nodes, recipes, step_lists, 0);
+    if recipes.is_empty() && step_lists.is_empty() {
+        debug!("JsonLdExtractor: No recipe found in this JSON-LD");
+    }
+}
+
+fn collect_nodes(
+    value: &Value,
+    nodes: &HashMap<&str, &Value>,
+    recipes: &mut Vec<Value>,
+    step_lists: &mut Vec<Value>,
+    depth: usize,
+) {
+    if depth > MAX_DEPTH {
+        return;
+    }
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                collect_nodes(item, nodes, recipes, step_lists, depth + 1);
+            }
+        }
+        Value::Object(object) => {
+            let has_instructions = object.contains_key("recipeInstructions");
+            let is_recipe = is_recipe_type(value);
+            if has_instructions || is_recipe {
+                debug!(
+                    "JsonLdExtractor: Found recipe - has_instructions: {}, is_recipe: {}",
+                    has_instructions, is_recipe
+                );
+                recipes.push(resolve_fields(value, nodes));
+                return;
+            }
+            if has_type(value, "itemlist") || has_type(value, "howtosection") {
+                if let Some(steps) = step_list(&resolve(value, nodes, &mut Vec::new())) {
+                    debug!("JsonLdExtractor: Found standalone step list");
+                    step_lists.push(steps);
+                    return;
                 }
-                result.push_str(&format!("{minutes_str} minutes"));
-            } else if let Ok(minutes) = minutes_str.parse::<u32>() {
-                // Convert minutes > 60 to hours and minutes
-                if minutes >= 60 {
-                    let hours = minutes / 60;
-                    let remaining_minutes = minutes % 60;
-
-                    if !result.is_empty() {
-                        result.push(' ');
-                    }
-                    result.push_str(&format!(
-                        "{} hour{}",
-                        hours,
-                        if hours == 1 { "" } else { "s" }
-                    ));
-
-                    if remaining_minutes > 0 {
-                        result.push_str(&format!(
-                            " {} minute{}",
-                            remaining_minutes,
-                            if remaining_minutes == 1 { "" } else { "s" }
-                        ));
-                    }
-                } else {
-                    if !result.is_empty() {
-                        result.push(' ');
-                    }
-                    result.push_str(&format!(
-                        "{} minute{}",
-                        minutes,
-                        if minutes == 1 { "" } else { "s" }
-                    ));
+            }
+            for key in ["@graph", "mainEntity"] {
+                if let Some(inner) = object.get(key) {
+                    debug!("JsonLdExtractor: Looking in {}", key);
+                    collect_nodes(inner, nodes, recipes, step_lists, depth + 1);
                 }
             }
         }
+        _ => {}
+    }
+}
 
-        // Handle seconds (including decimal values like 5400.0S)
-        if let Some(s_pos) = duration.find('S') {
-            let start = duration.rfind(['H', 'M']).map(|p| p + 1).unwrap_or(0);
-            let seconds_str = &duration[start..s_pos];
-
-            if let Ok(seconds) = seconds_str.parse::<f64>() {
-                let total_minutes = (seconds / 60.0).round() as u32;
-                let hours = total_minutes / 60;
-                let minutes = total_minutes % 60;
-
-                result.clear(); // Clear any existing result
-
-                if hours > 0 {
-                    result.push_str(&format!(
-                        "{} hour{}",
-                        hours,
-                        if hours == 1 { "" } else { "s" }
-                    ));
-                }
-
-                if minutes > 0 {
-                    if !result.is_empty() {
-                        result.push(' ');
-                    }
-                    result.push_str(&format!(
-                        "{} minute{}",
-                        minutes,
-                        if minutes == 1 { "" } else { "s" }
-                    ));
+/// Every node of the block with an `@id` and data of its own, by `@id`
+fn index_nodes<'a>(value: &'a Value, nodes: &mut HashMap<&'a str, &'a Value>, depth: usize) {
+    if depth > MAX_DEPTH {
+        return;
+    }
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                index_nodes(item, nodes, depth + 1);
+            }
+        }
+        Value::Object(object) => {
+            if let Some(id) = object.get("@id").and_then(Value::as_str) {
+                if reference(value).is_none() {
+                    nodes.entry(id).or_insert(value);
                 }
             }
+            for inner in object.values() {
+                index_nodes(inner, nodes, depth + 1);
+            }
         }
+        _ => {}
+    }
+}
 
-        if result.is_empty() {
-            duration.to_string()
-        } else {
-            result
+/// The `@id` of a bare reference to another node, `{"@id": "#step-1"}`
+fn reference(value: &Value) -> Option<&str> {
+    let object = value.as_object()?;
+    let id = object.get("@id")?.as_str()?;
+    // Some generators repeat the type next to the reference
+    object
+        .keys()
+        .all(|key| key == "@id" || key == "@type")
+        .then_some(id)
+}
+
+/// A recipe with the references in its [`RESOLVED_FIELDS`] resolved
+fn resolve_fields(recipe: &Value, nodes: &HashMap<&str, &Value>) -> Value {
+    let mut recipe = recipe.clone();
+    // A reference back to the recipe itself is left alone
+    let mut seen: Vec<String> = recipe
+        .get("@id")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .into_iter()
+        .collect();
+    if let Some(object) = recipe.as_object_mut() {
+        for field in RESOLVED_FIELDS {
+            if let Some(value) = object.get_mut(*field) {
+                *value = resolve(value, nodes, &mut seen);
+            }
         }
-    } else {
-        duration.to_string()
     }
+    recipe
 }
 
-fn is_recipe_type(value: &Value) -> bool {
-    if let Some(type_value) = value.get("@type") {
-        // Handle @type as a string: "@type": "Recipe"
-        if let Some(type_str) = type_value.as_str() {
-            return type_str.eq_ignore_ascii_case("recipe");
-        }
-        // Handle @type as an array: "@type": ["Recipe"]
-        if let Some(type_arr) = type_value.as_array() {
-            return type_arr
+/// `value` with each reference to a node in `nodes` replaced by the node,
+/// following references inside those up to [`MAX_REFERENCES`] deep and never
+/// back to a node in `seen`. References left in a list carry no data and
+/// are dropped, so one missing step doesn't spoil the rest.
+fn resolve(value: &Value, nodes: &HashMap<&str, &Value>, seen: &mut Vec<String>) -> Value {
+    match value {
+        Value::Array(items) => Value::Array(
+            items
                 .iter()
-                .any(|t| t.as_str().is_some_and(|s| s.eq_ignore_ascii_case("recipe")));
+                .map(|item| resolve(item, nodes, seen))
+                .filter(|item| reference(item).is_none())
+                .collect(),
+        ),
+        Value::Object(object) => {
+            if let Some(id) = reference(value) {
+                return match nodes.get(id) {
+                    Some(node) if seen.len() < MAX_REFERENCES && !seen.iter().any(|s| s == id) => {
+                        seen.push(id.to_string());
+                        let resolved = resolve(node, nodes, seen);
+                        seen.pop();
+                        resolved
+                    }
+                    _ => value.clone(),
+                };
+            }
+            Value::Object(
+                object
+                    .iter()
+                    .map(|(key, inner)| (key.clone(), resolve(inner, nodes, seen)))
+                    .collect(),
+            )
         }
+        _ => value.clone(),
     }
-    false
 }
 
-impl Extractor for JsonLdExtractor {
-    fn parse(&self, context: &ParsingContext) -> Result<Recipe, Box<dyn std::error::Error>> {
-        debug!("JsonLdExtractor: Starting parse for URL: {}", context.url);
-        let selector = Selector::parse("script[type='application/ld+json']").unwrap();
-        let document = &context.document;
+/// The steps of an `ItemList`/`HowToSection` block published apart from its recipe
+fn step_list(value: &Value) -> Option<Value> {
+    if !has_type(value, "itemlist") && !has_type(value, "howtosection") {
+        return None;
+    }
+    let steps: Vec<Value> = value
+        .get("itemListElement")?
+        .as_array()?
+        .iter()
+        .filter(|item| has_type(item, "howtostep") || has_type(item, "howtosection"))
+        .cloned()
+        .collect();
+    (!steps.is_empty()).then_some(Value::Array(steps))
+}
 
-        let scripts: Vec<_> = document.select(&selector).collect();
-        debug!(
-            "JsonLdExtractor: Found {} JSON-LD script tags",
-            scripts.len()
-        );
+/// Whether a field is absent or carries no data
+fn is_blank(value: Option<&Value>) -> bool {
+    match value {
+        None | Some(Value::Null) => true,
+        Some(Value::String(s)) => s.trim().is_empty(),
+        Some(Value::Array(a)) => a.is_empty(),
+        Some(Value::Object(o)) => o.is_empty(),
+        _ => false,
+    }
+}
 
-        // Try each script element until we find a valid recipe
-        for (index, script) in scripts.iter().enumerate() {
-            let raw_json = script.inner_html();
-            debug!(
-                "JsonLdExtractor: Script {} raw content: {}",
-                index, raw_json
-            );
+/// Whether two recipe blocks describe the same recipe (same name, or one unnamed)
+fn same_recipe(a: &Value, b: &Value) -> bool {
+    let name = |v: &Value| {
+        v.get("name")
+            .or_else(|| v.get("headline"))
+            .and_then(Value::as_str)
+            .map(|n| n.trim().to_lowercase())
+            .filter(|n| !n.is_empty())
+    };
+    match (name(a), name(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => true,
+    }
+}
+
+/// The distinct recipes among the blocks, in page order. Blocks for the
+/// same recipe are merged: fields a recipe lacks are filled from later
+/// blocks for it, and missing instructions of the first recipe from a
+/// standalone step list.
+fn distinct_recipes(recipes: &[Value], step_lists: &[Value]) -> Vec<Value> {
+    let mut distinct: Vec<Value> = Vec::new();
+    for block in recipes {
+        let Some(merged) = distinct
+            .iter_mut()
+            .find(|merged| same_recipe(merged, block))
+        else {
+            distinct.push(block.clone());
+            continue;
+        };
+        if let (Some(target), Some(source)) = (merged.as_object_mut(), block.as_object()) {
+            for (key, value) in source {
+                if is_blank(target.get(key)) && !is_blank(Some(value)) {
+                    debug!("JsonLdExtractor: Filling '{}' from another block", key);
+                    target.insert(key.clone(), value.clone());
+                }
+            }
+        }
+    }
+
+    if let Some(first) = distinct.first_mut() {
+        if is_blank(first.get("recipeInstructions")) {
+            if let (Some(target), Some(steps)) = (first.as_object_mut(), step_lists.first()) {
+                debug!("JsonLdExtractor: Using standalone step list as instructions");
+                target.insert("recipeInstructions".to_string(), steps.clone());
+            }
+        }
+    }
+    distinct
+}
+
+/// Whether any JSON-LD script on the page describes a recipe
+pub(crate) fn has_recipe(document: &scraper::Html) -> bool {
+    let selector = Selector::parse("script[type='application/ld+json']").unwrap();
+    document.select(&selector).any(|script| {
+        let mut recipes = Vec::new();
+        if let Ok(json_ld) = serde_json::from_str::<Value>(&sanitize_json(&script.inner_html())) {
+            collect_candidates(&json_ld, &mut recipes, &mut Vec::new());
+        }
+        !recipes.is_empty()
+    })
+}
 
-            let cleaned_json = sanitize_json(&raw_json);
-            match serde_json::from_str::<Value>(&cleaned_json) {
-                Ok(json_ld) => {
-                    debug!(
-                        "JsonLdExtractor: Successfully parsed JSON-LD {}: {:#?}",
-                        index, json_ld
-                    );
-
-                    let recipe_json = if json_ld.is_array() {
-                        debug!("JsonLdExtractor: JSON-LD is an array");
-                        json_ld.as_array().and_then(|arr| {
-                            arr.iter()
-                                .find(|item| {
-                                    let has_instructions = item.get("recipeInstructions").is_some();
-                                    let is_recipe = is_recipe_type(item);
-                                    debug!("JsonLdExtractor: Array item - has_instructions: {}, is_recipe: {}", has_instructions, is_recipe);
-                                    has_instructions || is_recipe
-                                })
-                        })
-                    } else if is_recipe_type(&json_ld) {
-                        debug!("JsonLdExtractor: Found Recipe type in root");
-                        Some(&json_ld)
-                    } else if let Some(graph) = json_ld.get("@graph") {
-                        debug!("JsonLdExtractor: Found @graph");
-                        graph.as_array().and_then(|arr| {
-                            arr.iter().find(|item| {
-                                let is_recipe = is_recipe_type(item);
-                                debug!("JsonLdExtractor: @graph item - is_recipe: {}", is_recipe);
-                                is_recipe
+/// URLs listed in JSON-LD `ItemList`s (category and collection pages), as
+/// written on the page
+pub(crate) fn item_list_urls(document: &scraper::Html) -> Vec<String> {
+    fn collect(value: &Value, urls: &mut Vec<String>) {
+        match value {
+            Value::Array(values) => values.iter().for_each(|value| collect(value, urls)),
+            Value::Object(object) => {
+                if let Some(graph) = object.get("@graph") {
+                    collect(graph, urls);
+                }
+                let is_item_list = match object.get("@type") {
+                    Some(Value::String(kind)) => kind == "ItemList",
+                    Some(Value::Array(kinds)) => kinds.iter().any(|kind| kind == "ItemList"),
+                    _ => false,
+                };
+                if !is_item_list {
+                    return;
+                }
+                let elements = match object.get("itemListElement") {
+                    Some(Value::Array(elements)) => elements.as_slice(),
+                    Some(element) => std::slice::from_ref(element),
+                    None => &[],
+                };
+                // An element is a URL, a ListItem with `url`, or a ListItem
+                // whose `item` is a URL or a thing with `url`/`@id`
+                for element in elements {
+                    let url = match element {
+                        Value::String(url) => Some(url.as_str()),
+                        Value::Object(item) => {
+                            item.get("url").and_then(Value::as_str).or_else(|| {
+                                match item.get("item") {
+                                    Some(Value::String(url)) => Some(url.as_str()),
+                                    Some(Value::Object(thing)) => thing
+                                        .get("url")
+                                        .or_else(|| thing.get("@id"))
+                                        .and_then(Value::as_str),
+                                    _ => None,
+                                }
                             })
-                        })
-                    } else {
-                        debug!("JsonLdExtractor: No recipe found in this JSON-LD");
-                        None
+                        }
+                        _ => None,
                     };
+                    urls.extend(url.map(str::to_string));
+                }
+            }
+            _ => {}
+        }
+    }
 
-                    if let Some(recipe) = recipe_json {
-                        debug!("JsonLdExtractor: Found recipe JSON: {:#?}", recipe);
-                        match JsonLdRecipe::try_from(recipe) {
-                            Ok(recipe) => {
-                                debug!("JsonLdExtractor: Successfully converted to JsonLdRecipe");
-                                return Ok(self.convert_to_recipe(recipe, &context.url));
-                            }
-                            Err(e) => {
-                                debug!("JsonLdExtractor: Failed to convert to JsonLdRecipe: {}", e);
-                            }
-                        }
-                    }
+    let selector = Selector::parse("script[type='application/ld+json']").unwrap();
+    let mut urls = Vec::new();
+    for script in document.select(&selector) {
+        if let Ok(json_ld) = serde_json::from_str::<Value>(&sanitize_json(&script.inner_html())) {
+            collect(&json_ld, &mut urls);
+        }
+    }
+    urls
+}
+
+impl JsonLdExtractor {
+    /// The recipe in the page's JSON-LD `<script>` blocks
+    pub fn parse_page(
+        &self,
+        context: &ParsingContext,
+    ) -> Result<Recipe, Box<dyn std::error::Error>> {
+        debug!("JsonLdExtractor: Starting parse for URL: {}", context.url);
+        let (recipes, step_lists) = candidates(&context.document);
+
+        // Prefer the merged recipe, then each block on its own
+        let merged = distinct_recipes(&recipes, &step_lists).into_iter().next();
+        for recipe in merged.iter().chain(recipes.iter()) {
+            debug!("JsonLdExtractor: Found recipe JSON: {:#?}", recipe);
+            match JsonLdRecipe::try_from(recipe) {
+                Ok(recipe) => {
+                    debug!("JsonLdExtractor: Successfully converted to JsonLdRecipe");
+                    return Ok(self.convert_to_recipe(recipe, &context.url));
                 }
                 Err(e) => {
-                    debug!("JsonLdExtractor: Failed to parse JSON-LD {}: {}", index, e);
+                    debug!("JsonLdExtractor: Failed to convert to JsonLdRecipe: {}", e);
                 }
             }
         }
@@ -877,6 +1238,75 @@ impl Extractor for JsonLdExtractor {
         debug!("JsonLdExtractor: {}", error_msg);
         Err(error_msg.into())
     }
+
+    /// Every recipe in the page's JSON-LD, in page order, for roundups such
+    /// as "10 best curry recipes". Blocks describing the same recipe are
+    /// merged as in [`parse_page`](Self::parse_page); recipes that can't be
+    /// read are skipped.
+    pub fn parse_all(&self, context: &ParsingContext) -> Vec<Recipe> {
+        let (recipes, step_lists) = candidates(&context.document);
+        distinct_recipes(&recipes, &step_lists)
+            .iter()
+            .filter_map(|recipe| match JsonLdRecipe::try_from(recipe) {
+                Ok(recipe) => Some(self.convert_to_recipe(recipe, &context.url)),
+                Err(e) => {
+                    debug!("JsonLdExtractor: Skipping unreadable recipe: {}", e);
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+/// Recipe objects and standalone step lists from every JSON-LD script.
+///
+/// Some CMSes split a recipe across scripts (metadata in one, steps in
+/// another), so candidates are gathered from all of them before deciding.
+fn candidates(document: &scraper::Html) -> (Vec<Value>, Vec<Value>) {
+    let selector = Selector::parse("script[type='application/ld+json']").unwrap();
+    let scripts: Vec<_> = document.select(&selector).collect();
+    debug!(
+        "JsonLdExtractor: Found {} JSON-LD script tags",
+        scripts.len()
+    );
+
+    let mut recipes = Vec::new();
+    let mut step_lists = Vec::new();
+    for (index, script) in scripts.iter().enumerate() {
+        let raw_json = script.inner_html();
+        debug!(
+            "JsonLdExtractor: Script {} raw content: {}",
+            index, raw_json
+        );
+
+        let cleaned_json = sanitize_json(&raw_json);
+        match serde_json::from_str::<Value>(&cleaned_json) {
+            Ok(json_ld) => {
+                debug!(
+                    "JsonLdExtractor: Successfully parsed JSON-LD {}: {:#?}",
+                    index, json_ld
+                );
+                collect_candidates(&json_ld, &mut recipes, &mut step_lists);
+            }
+            Err(e) => {
+                debug!("JsonLdExtractor: Failed to parse JSON-LD {}: {}", index, e);
+            }
+        }
+    }
+    (recipes, step_lists)
+}
+
+#[async_trait]
+impl Extractor for JsonLdExtractor {
+    fn name(&self) -> &'static str {
+        "json-ld"
+    }
+
+    async fn parse(&self, context: &SharedContext) -> Result<Recipe, Box<dyn Error + Send + Sync>> {
+        context
+            .read(|context| self.parse_page(context))
+            .map_err(|e| e.to_string().into())
+    }
 }
 
 fn sanitize_json(json_str: &str) -> String {
@@ -965,6 +1395,7 @@ fn sanitize_json(json_str: &str) -> String {
 mod tests {
     use super::*;
     use scraper::Html;
+    use serde_json::json;
 
     fn create_html_document(json_ld: &str) -> String {
         format!(
@@ -993,7 +1424,7 @@ mod tests {
         };
         let extractor = JsonLdExtractor;
         // Just verify that parse returns an error for invalid input
-        assert!(extractor.parse(&context).is_err());
+        assert!(extractor.parse_page(&context).is_err());
     }
 
     #[test]
@@ -1026,7 +1457,7 @@ mod tests {
             texts: None,
         };
 
-        let result = extractor.parse(&context).unwrap();
+        let result = extractor.parse_page(&context).unwrap();
 
         assert_eq!(result.name, "Chocolate Chip Cookies");
         assert_eq!(
@@ -1058,27 +1489,6 @@ mod tests {
         );
     }
 
-    #[test]
-    fn test_duration_conversion() {
-        assert_eq!(convert_duration("PT30M"), "30 minutes");
-        assert_eq!(convert_duration("PT1H"), "1 hour");
-        assert_eq!(convert_duration("PT1H30M"), "1 hour 30 minutes");
-        assert_eq!(convert_duration("PT90M"), "1 hour 30 minutes");
-        assert_eq!(convert_duration("PT2H15M"), "2 hours 15 minutes");
-        assert_eq!(convert_duration("invalid"), "invalid");
-        // Test ranges
-        assert_eq!(convert_duration("PT15-20M"), "15-20 minutes");
-        assert_eq!(convert_duration("PT25-30M"), "25-30 minutes");
-        // Test seconds
-        assert_eq!(convert_duration("PT5400S"), "1 hour 30 minutes");
-        assert_eq!(convert_duration("PT5400.0S"), "1 hour 30 minutes");
-        assert_eq!(convert_duration("PT300S"), "5 minutes");
-        // Test large minute values
-        assert_eq!(convert_duration("PT150M"), "2 hours 30 minutes");
-        assert_eq!(convert_duration("PT180M"), "3 hours");
-        assert_eq!(convert_duration("PT65M"), "1 hour 5 minutes");
-    }
-
     #[test]
     fn test_metadata_with_source_url() {
         let extractor = JsonLdExtractor;
@@ -1103,7 +1513,7 @@ mod tests {
             texts: None,
         };
 
-        let result = extractor.parse(&context).unwrap();
+        let result = extractor.parse_page(&context).unwrap();
 
         assert_eq!(result.metadata.get("diet").unwrap(), "GlutenFree");
         assert_eq!(result.metadata.get("tags").unwrap(), "healthy, quick, easy");
@@ -1149,7 +1559,7 @@ mod tests {
             texts: None,
         };
 
-        let result = extractor.parse(&context).unwrap();
+        let result = extractor.parse_page(&context).unwrap();
 
         assert_eq!(result.name, "Pasta Carbonara");
         assert_eq!(
@@ -1232,4 +1642,200 @@ mod tests {
             result
         );
     }
+
+    fn parse_scripts(scripts: &[&str]) -> Result<Recipe, Box<dyn std::error::Error>> {
+        let body: String = scripts
+            .iter()
+            .map(|json| format!(r#"<script type="application/ld+json">{json}</script>"#))
+            .collect();
+        let context = ParsingContext {
+            url: "http://example.com".to_string(),
+            document: Html::parse_document(&format!("<html><head>{body}</head></html>")),
+            texts: None,
+        };
+        JsonLdExtractor.parse_page(&context)
+    }
+
+    #[test]
+    fn test_merges_metadata_and_step_list_blocks() {
+        let metadata = r#"{
+            "@context": "https://schema.org",
+            "@type": "Recipe",
+            "name": "Lentil Soup",
+            "recipeYield": "4",
+            "recipeIngredient": ["200g red lentils", "1 onion"]
+        }"#;
+        let steps = r#"{
+            "@context": "https://schema.org",
+            "@type": "ItemList",
+            "itemListElement": [
+                {"@type": "HowToStep", "text": "Chop the onion."},
+                {"@type": "HowToStep", "text": "Simmer with the lentils."}
+            ]
+        }"#;
+
+        let recipe = parse_scripts(&[metadata, steps]).unwrap();
+        assert_eq!(recipe.name, "Lentil Soup");
+        assert_eq!(recipe.ingredients, vec!["200g red lentils", "1 onion"]);
+        assert!(recipe.instructions.contains("Chop the onion."));
+        assert!(recipe.instructions.contains("Simmer with the lentils."));
+        assert_eq!(recipe.metadata.get("servings").unwrap(), "4");
+    }
+
+    #[test]
+    fn test_fills_missing_fields_from_same_recipe_only() {
+        let partial =
+            r#"{"@type": "Recipe", "name": "Flatbread", "recipeIngredient": ["flour", "water"]}"#;
+        let other = r#"{"@type": "Recipe", "name": "Hummus", "recipeInstructions": "Blend."}"#;
+        let details = r#"{"@type": "Recipe", "name": "Flatbread", "recipeInstructions": "Knead and bake.", "prepTime": "PT10M"}"#;
+
+        let recipe = parse_scripts(&[partial, other, details]).unwrap();
+        assert_eq!(recipe.name, "Flatbread");
+        assert_eq!(recipe.instructions, "Knead and bake.");
+        assert_eq!(recipe.metadata.get("prep time").unwrap(), "10 minutes");
+    }
+
+    #[test]
+    fn test_parse_all_returns_each_recipe() {
+        let roundup = r#"{"@graph": [
+            {"@type": "WebPage", "name": "10 best curries"},
+            {"@type": "Recipe", "name": "Dal", "recipeIngredient": ["lentils"]},
+            {"@type": "Recipe", "name": "Korma", "recipeIngredient": ["cashews"],
+             "recipeInstructions": "Simmer."}
+        ]}"#;
+        let dal_steps = r#"{"@type": "Recipe", "name": "Dal", "recipeInstructions": "Boil."}"#;
+        let html = format!(
+            r#"<script type="application/ld+json">{}</script><script type="application/ld+json">{}</script>"#,
+            roundup, dal_steps
+        );
+        let context = ParsingContext {
+            url: "http://example.com/curries".to_string(),
+            document: Html::parse_document(&html),
+            texts: None,
+        };
+
+        let recipes = JsonLdExtractor.parse_all(&context);
+        let names: Vec<_> = recipes.iter().map(|recipe| recipe.name.as_str()).collect();
+        assert_eq!(names, ["Dal", "Korma"]);
+        assert_eq!(recipes[0].instructions, "Boil.");
+        assert_eq!(recipes[1].ingredients, ["cashews"]);
+        assert_eq!(JsonLdExtractor.parse_page(&context).unwrap().name, "Dal");
+    }
+
+    #[test]
+    fn test_reports_dropped_fields() {
+        let recipe = parse_scripts(&[r#"{
+            "@context": "https://schema.org",
+            "@type": "Recipe",
+            "name": "Pancakes",
+            "description": " \n ",
+            "recipeCuisine": [],
+            "recipeIngredient": ["2 eggs"],
+            "aggregateRating": {"@type": "AggregateRating", "ratingValue": 4.8},
+            "datePublished": "2024-01-05",
+            "tool": ["pan", "whisk"]
+        }"#])
+        .unwrap();
+
+        let dropped: Vec<(&str, &str, DropReason)> = recipe
+            .dropped_fields
+            .iter()
+            .map(|field| (field.key.as_str(), field.value.as_str(), field.reason))
+            .collect();
+        assert_eq!(
+            dropped,
+            vec![
+                ("datePublished", "2024-01-05", DropReason::Unknown),
+                ("recipeCuisine", "", DropReason::Empty),
+                ("description", " \n ", DropReason::Empty),
+            ]
+        );
+        assert!(!recipe.metadata.contains_key("cuisine"));
+        assert_eq!(recipe.metadata.get("rating").unwrap(), "4.8");
+        assert_eq!(recipe.equipment, vec!["pan", "whisk"]);
+    }
+
+    #[test]
+    fn test_video_url() {
+        let video: Value = serde_json::from_str(
+            r#"{"@type": "VideoObject", "name": "How to make pancakes",
+                "contentUrl": "https://cdn.example.com/pancakes.mp4",
+                "embedUrl": "https://www.youtube.com/embed/abc123"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            video_url(&video).as_deref(),
+            Some("https://cdn.example.com/pancakes.mp4")
+        );
+        let videos: Value = serde_json::from_str(
+            r#"[{"@type": "VideoObject", "name": "No link"},
+                {"@type": "VideoObject", "embedUrl": "https://www.youtube.com/embed/abc123"}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            video_url(&videos).as_deref(),
+            Some("https://www.youtube.com/embed/abc123")
+        );
+        assert_eq!(video_url(&Value::String("soon".to_string())), None);
+    }
+
+    #[test]
+    fn test_recipe_and_step_tools_become_equipment() {
+        let recipe = parse_scripts(&[r#"{
+            "@context": "https://schema.org",
+            "@type": "Recipe",
+            "name": "Crêpes",
+            "recipeIngredient": ["2 eggs", "250 ml milk"],
+            "tool": {"@type": "HowToTool", "name": "Crêpe pan"},
+            "recipeInstructions": [
+                {"@type": "HowToSection", "name": "Batter", "itemListElement": [
+                    {"@type": "HowToStep", "text": "Whisk the eggs and milk.",
+                     "tool": [{"@type": "HowToTool", "name": "whisk"}, "Mixing bowl"]}
+                ]},
+                {"@type": "HowToStep", "text": "Cook in the pan.", "tool": "crêpe pan"}
+            ]
+        }"#])
+        .unwrap();
+
+        assert_eq!(recipe.equipment, vec!["Crêpe pan", "whisk", "Mixing bowl"]);
+        assert!(recipe
+            .dropped_fields
+            .iter()
+            .all(|field| field.key != "tool"));
+    }
+
+    #[test]
+    fn test_recipe_types() {
+        for recipe_type in [
+            json!("Recipe"),
+            json!("schema:Recipe"),
+            json!("https://schema.org/Recipe"),
+            json!(["NewsArticle", "Recipe"]),
+        ] {
+            assert!(is_recipe_type(&json!({ "@type": recipe_type })));
+        }
+        assert!(!is_recipe_type(&json!({"@type": ["NewsArticle"]})));
+        assert!(!is_recipe_type(&json!({"@type": "RecipeCollection"})));
+    }
+
+    #[test]
+    fn test_resolves_references_without_cycles() {
+        // The recipe points at its page, which names the recipe as its main
+        // entity; the steps and the author are listed on their own
+        let recipe = parse_scripts(&[r##"{"@graph": [
+            {"@type": "WebPage", "@id": "#page", "mainEntity": {"@id": "#recipe"}},
+            {"@type": "Recipe", "@id": "#recipe", "name": "Toast",
+             "mainEntityOfPage": {"@id": "#page"},
+             "author": {"@id": "#me"},
+             "recipeIngredient": ["1 slice bread"],
+             "recipeInstructions": [{"@id": "#step"}, {"@id": "#missing"}]},
+            {"@type": "HowToStep", "@id": "#step", "text": "Toast the bread.",
+             "isPartOf": {"@id": "#recipe"}},
+            {"@type": "Person", "@id": "#me", "name": "Sam", "knows": {"@id": "#me"}}
+        ]}"##])
+        .unwrap();
+        assert_eq!(recipe.name, "Toast");
+        assert_eq!(recipe.instructions, "Toast the bread.");
+        assert_eq!(recipe.metadata.get("author").unwrap(), "Sam");
+    }
 }
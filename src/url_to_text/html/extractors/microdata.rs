@@ -1,13 +1,22 @@
-use super::{Extractor, ParsingContext};
-use crate::model::Recipe;
-use log::debug;
+use super::{
+    convert_duration, format_rating, split_serving_size, Extractor, ParsingContext, SharedContext,
+};
+use crate::model::{MetadataKey, Recipe};
+use crate::pipelines::equipment;
+use crate::url_to_text::html::diagnostics::{DropReason, DroppedField};
+use async_trait::async_trait;
 use scraper::{ElementRef, Selector};
 use std::collections::HashMap;
+use std::error::Error;
+use tracing::debug;
 
 pub struct MicroDataExtractor;
 
 impl MicroDataExtractor {
-    fn find_recipe_container<'a>(&self, document: &'a scraper::Html) -> Option<ElementRef<'a>> {
+    pub(crate) fn find_recipe_container<'a>(
+        &self,
+        document: &'a scraper::Html,
+    ) -> Option<ElementRef<'a>> {
         // Look for elements with itemscope and itemtype containing "Recipe"
         let selector = Selector::parse("[itemscope]").unwrap();
         for element in document.select(&selector) {
@@ -29,6 +38,78 @@ impl MicroDataExtractor {
             .map(|el| el.text().collect::<Vec<_>>().join(" ").trim().to_string())
     }
 
+    /// A time property in words. Read from the `content` attribute
+    /// (`<meta itemprop="prepTime" content="PT15M">`), else the element's
+    /// text, else its `datetime`; ISO 8601 durations become "15 minutes".
+    fn get_duration_itemprop(&self, root: ElementRef, prop: &str) -> Option<String> {
+        let selector = Selector::parse(&format!("[itemprop='{}']", prop)).unwrap();
+        let el = root.select(&selector).next()?;
+        let text = el.text().collect::<Vec<_>>().join(" ").trim().to_string();
+        let value = el
+            .value()
+            .attr("content")
+            .map(|content| content.trim().to_string())
+            .filter(|content| !content.is_empty())
+            .or_else(|| Some(text).filter(|text| !text.is_empty()))
+            .or_else(|| el.value().attr("datetime").map(|d| d.trim().to_string()))?;
+        Some(convert_duration(&value))
+    }
+
+    /// The recipe's `aggregateRating` item as "4.7 (1234 reviews)". Values
+    /// are read from `content` (`<meta itemprop="ratingValue" content="4.7">`)
+    /// or the element's text.
+    fn get_rating(&self, root: ElementRef) -> Option<String> {
+        let rating = own_props(root, "aggregateRating").into_iter().next()?;
+        let field = |prop: &str| {
+            let el = own_props(rating, prop).into_iter().next()?;
+            let value = el
+                .value()
+                .attr("content")
+                .map(str::to_string)
+                .unwrap_or_else(|| el.text().collect::<Vec<_>>().join(" "));
+            Some(value.trim().to_string())
+        };
+        format_rating(
+            &field("ratingValue")?,
+            field("ratingCount")
+                .or_else(|| field("reviewCount"))
+                .as_deref(),
+            field("bestRating").as_deref(),
+        )
+    }
+
+    /// URL of the recipe's `video` item: its `contentUrl`, else its
+    /// `embedUrl`, read from `content`, `href` or `src`
+    fn get_video(&self, root: ElementRef) -> Option<String> {
+        let video = own_props(root, "video").into_iter().next()?;
+        ["contentUrl", "embedUrl"].iter().find_map(|prop| {
+            let el = own_props(video, prop).into_iter().next()?;
+            let url = ["content", "href", "src"]
+                .iter()
+                .find_map(|attr| el.value().attr(attr))?
+                .trim();
+            url.starts_with("http").then(|| url.to_string())
+        })
+    }
+
+    /// Names of the `tool` items of the recipe and its steps
+    fn get_tools(&self, root: ElementRef) -> Vec<String> {
+        let selector = Selector::parse("[itemprop~='tool']").unwrap();
+        let names = root.select(&selector).map(|el| {
+            let target = if el.value().attr("itemscope").is_some() {
+                own_props(el, "name").into_iter().next().unwrap_or(el)
+            } else {
+                el
+            };
+            target
+                .value()
+                .attr("content")
+                .map(str::to_string)
+                .unwrap_or_else(|| target.text().collect::<Vec<_>>().join(" "))
+        });
+        equipment::dedup(names)
+    }
+
     fn get_itemprop_list(&self, root: ElementRef, prop: &str) -> Vec<String> {
         let mut items = Vec::new();
         let selector = Selector::parse(&format!("[itemprop='{}']", prop)).unwrap();
@@ -40,10 +121,130 @@ impl MicroDataExtractor {
         }
         items
     }
+
+    /// Instruction lines, with `HowToSection` names as `## Section` markers
+    fn get_instructions(&self, root: ElementRef, prop: &str) -> Vec<String> {
+        let mut items = Vec::new();
+        let selector = Selector::parse(&format!("[itemprop='{}']", prop)).unwrap();
+        for el in root.select(&selector) {
+            let itemtype = el.value().attr("itemtype").unwrap_or_default();
+            if itemtype.contains("HowToSection") {
+                let name = own_props(el, "name")
+                    .into_iter()
+                    .next()
+                    .map(|name| name.text().collect::<Vec<_>>().join(" ").trim().to_string())
+                    .unwrap_or_default();
+                if !name.is_empty() {
+                    items.push(format!("## {}", name.trim_end_matches(':')));
+                }
+                items.extend(
+                    own_props(el, "itemListElement")
+                        .into_iter()
+                        .filter_map(step_text),
+                );
+            } else {
+                items.extend(step_text(el));
+            }
+        }
+        items
+    }
 }
 
-impl Extractor for MicroDataExtractor {
-    fn parse(&self, context: &ParsingContext) -> Result<Recipe, Box<dyn std::error::Error>> {
+/// Text of a step, preferring a `HowToStep`'s `text` property over its name
+fn step_text(step: ElementRef) -> Option<String> {
+    let target = own_props(step, "text").into_iter().next().unwrap_or(step);
+    let text = target
+        .text()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim()
+        .to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// Properties of the recipe item the extractor reads
+const KNOWN_PROPS: &[&str] = &[
+    "name",
+    "description",
+    "image",
+    "author",
+    "prepTime",
+    "cookTime",
+    "totalTime",
+    "recipeYield",
+    "nutrition",
+    "recipeCategory",
+    "recipeCuisine",
+    "suitableForDiet",
+    "keywords",
+    "aggregateRating",
+    "video",
+    "tool",
+    "ingredients",
+    "recipeIngredient",
+    "recipeInstructions",
+    "instructions",
+];
+
+/// Properties of the recipe item no recipe field is read from, first
+/// occurrence of each, in page order
+fn unknown_props(recipe: ElementRef) -> Vec<DroppedField> {
+    let selector = Selector::parse("[itemprop]").unwrap();
+    let mut dropped: Vec<DroppedField> = Vec::new();
+    for el in recipe.select(&selector) {
+        let owner = el
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .find(|ancestor| ancestor.value().attr("itemscope").is_some());
+        if !owner.is_some_and(|owner| owner.id() == recipe.id()) {
+            continue;
+        }
+        let value = el
+            .value()
+            .attr("content")
+            .or_else(|| el.value().attr("datetime"))
+            .map(str::to_string)
+            .unwrap_or_else(|| el.text().collect::<Vec<_>>().join(" ").trim().to_string());
+        for key in el
+            .value()
+            .attr("itemprop")
+            .unwrap_or_default()
+            .split_whitespace()
+        {
+            if !KNOWN_PROPS.contains(&key) && !dropped.iter().any(|field| field.key == key) {
+                dropped.push(DroppedField {
+                    key: key.to_string(),
+                    value: value.clone(),
+                    reason: DropReason::Unknown,
+                });
+            }
+        }
+    }
+    dropped
+}
+
+/// Elements with `itemprop` = `prop` belonging to the `scope` item itself,
+/// not to an item nested inside it
+fn own_props<'a>(scope: ElementRef<'a>, prop: &str) -> Vec<ElementRef<'a>> {
+    let selector = Selector::parse(&format!("[itemprop='{}']", prop)).unwrap();
+    scope
+        .select(&selector)
+        .filter(|el| {
+            el.ancestors()
+                .filter_map(ElementRef::wrap)
+                .find(|ancestor| ancestor.value().attr("itemscope").is_some())
+                .is_some_and(|item| item.id() == scope.id())
+        })
+        .collect()
+}
+
+impl MicroDataExtractor {
+    /// The recipe in the page's schema.org microdata (`itemprop`s inside an
+    /// `itemtype` Recipe)
+    pub fn parse_page(
+        &self,
+        context: &ParsingContext,
+    ) -> Result<Recipe, Box<dyn std::error::Error>> {
         debug!("Attempting to extract recipe using MicroData extractor");
 
         let container = self.find_recipe_container(&context.document);
@@ -78,7 +279,7 @@ impl Extractor for MicroDataExtractor {
         let image_selector = Selector::parse("[itemprop='image']").unwrap();
         if let Some(img_el) = container.select(&image_selector).next() {
             if let Some(src) = img_el.value().attr("src") {
-                metadata.insert("image".to_string(), src.to_string());
+                metadata.insert(MetadataKey::Image.to_string(), src.to_string());
             } else {
                 let text = img_el
                     .text()
@@ -87,7 +288,7 @@ impl Extractor for MicroDataExtractor {
                     .trim()
                     .to_string();
                 if !text.is_empty() {
-                    metadata.insert("image".to_string(), text);
+                    metadata.insert(MetadataKey::Image.to_string(), text);
                 }
             }
         }
@@ -108,44 +309,62 @@ impl Extractor for MicroDataExtractor {
                 .to_string();
 
             if !text.is_empty() {
-                metadata.insert("author".to_string(), text);
+                metadata.insert(MetadataKey::Author.to_string(), text);
             }
         }
 
         // Times
-        if let Some(prep) = self.get_itemprop(container, "prepTime") {
-            metadata.insert("prep_time".to_string(), prep);
+        if let Some(prep) = self.get_duration_itemprop(container, "prepTime") {
+            metadata.insert(MetadataKey::PrepTime.to_string(), prep);
         }
-        if let Some(cook) = self.get_itemprop(container, "cookTime") {
-            metadata.insert("cook_time".to_string(), cook);
+        if let Some(cook) = self.get_duration_itemprop(container, "cookTime") {
+            metadata.insert(MetadataKey::CookTime.to_string(), cook);
         }
-        if let Some(total) = self.get_itemprop(container, "totalTime") {
-            metadata.insert("total_time".to_string(), total);
+        if let Some(total) = self.get_duration_itemprop(container, "totalTime") {
+            metadata.insert(MetadataKey::TotalTime.to_string(), total);
         }
 
         // Yield/Servings
         if let Some(yield_val) = self.get_itemprop(container, "recipeYield") {
-            metadata.insert("servings".to_string(), yield_val);
+            let (servings, serving_size) = split_serving_size(&yield_val);
+            metadata.insert(MetadataKey::Servings.to_string(), servings);
+            if let Some(serving_size) = serving_size {
+                metadata.insert(MetadataKey::ServingSize.to_string(), serving_size);
+            }
+        }
+        // Nutrition facts are the most precise source for serving size
+        if let Some(serving_size) = self.get_itemprop(container, "servingSize") {
+            metadata.insert(MetadataKey::ServingSize.to_string(), serving_size);
         }
 
         // Course / Category
         if let Some(category) = self.get_itemprop(container, "recipeCategory") {
-            metadata.insert("course".to_string(), category);
+            metadata.insert(MetadataKey::Course.to_string(), category);
         }
 
         // Cuisine
         if let Some(cuisine) = self.get_itemprop(container, "recipeCuisine") {
-            metadata.insert("cuisine".to_string(), cuisine);
+            metadata.insert(MetadataKey::Cuisine.to_string(), cuisine);
         }
 
         // Diet
         if let Some(diet) = self.get_itemprop(container, "suitableForDiet") {
-            metadata.insert("diet".to_string(), diet);
+            metadata.insert(MetadataKey::Diet.to_string(), diet);
         }
 
         // Keywords / Tags
         if let Some(keywords) = self.get_itemprop(container, "keywords") {
-            metadata.insert("tags".to_string(), keywords);
+            metadata.insert(MetadataKey::Tags.to_string(), keywords);
+        }
+
+        // Rating
+        if let Some(rating) = self.get_rating(container) {
+            metadata.insert(MetadataKey::Rating.to_string(), rating);
+        }
+
+        // Video
+        if let Some(video) = self.get_video(container) {
+            metadata.insert(MetadataKey::Video.to_string(), video);
         }
 
         // Ingredients
@@ -156,10 +375,10 @@ impl Extractor for MicroDataExtractor {
         }
 
         // Instructions
-        // Try 'recipeInstructions' and 'instructions'
-        let mut instructions_list = self.get_itemprop_list(container, "recipeInstructions");
+        // Try 'recipeInstructions' and 'instructions', keeping HowToSection names
+        let mut instructions_list = self.get_instructions(container, "recipeInstructions");
         if instructions_list.is_empty() {
-            instructions_list = self.get_itemprop_list(container, "instructions");
+            instructions_list = self.get_instructions(container, "instructions");
         }
 
         // Validation
@@ -171,7 +390,7 @@ impl Extractor for MicroDataExtractor {
         let instructions = instructions_list.join("\n\n");
 
         // Add source URL
-        metadata.insert("source_url".to_string(), context.url.clone());
+        metadata.insert(MetadataKey::Source.to_string(), context.url.clone());
 
         Ok(Recipe {
             name,
@@ -179,7 +398,22 @@ impl Extractor for MicroDataExtractor {
             image: Vec::new(),
             ingredients,
             instructions,
+            equipment: self.get_tools(container),
             metadata,
+            dropped_fields: unknown_props(container),
         })
     }
 }
+
+#[async_trait]
+impl Extractor for MicroDataExtractor {
+    fn name(&self) -> &'static str {
+        "microdata"
+    }
+
+    async fn parse(&self, context: &SharedContext) -> Result<Recipe, Box<dyn Error + Send + Sync>> {
+        context
+            .read(|context| self.parse_page(context))
+            .map_err(|e| e.to_string().into())
+    }
+}
@@ -1,20 +1,83 @@
 use crate::model::Recipe;
+use async_trait::async_trait;
 use scraper::Html;
+use std::error::Error;
+use std::sync::{Arc, Mutex, PoisonError};
 
+mod adapter;
+mod duration;
 mod html_class;
 mod json_ld;
 mod microdata;
+mod opengraph;
+mod rating;
+mod registry;
+mod serving;
+mod sites;
 
+pub use adapter::SiteAdapter;
+pub(crate) use duration::convert_duration;
+pub(crate) use html_class::recipe_class_counts;
 pub use html_class::HtmlClassExtractor;
+pub(crate) use json_ld::has_recipe as has_json_ld_recipe;
+pub(crate) use json_ld::item_list_urls;
 pub use json_ld::JsonLdExtractor;
 pub use microdata::MicroDataExtractor;
+pub use opengraph::OpenGraphExtractor;
+pub(crate) use rating::format_rating;
+pub use registry::register_extractor;
+pub(crate) use registry::registered_extractors;
+pub(crate) use serving::split_serving_size;
+pub use sites::SiteExtractor;
 
+#[derive(Clone)]
 pub struct ParsingContext {
     pub url: String,
     pub document: Html,
     pub texts: Option<String>,
 }
 
-pub trait Extractor {
-    fn parse(&self, context: &ParsingContext) -> Result<Recipe, Box<dyn std::error::Error>>;
+/// A [`ParsingContext`] shared by the extractors running on a page at the
+/// same time, so the page is parsed and held once. Cloning it is cheap.
+///
+/// `scraper::Html` can be sent between threads but not shared, so the
+/// context sits behind a lock and is read through [`read`](Self::read),
+/// whose closure can't hold it across an `.await`.
+#[derive(Clone)]
+pub struct SharedContext(Arc<Mutex<ParsingContext>>);
+
+impl SharedContext {
+    pub fn new(context: ParsingContext) -> Self {
+        Self(Arc::new(Mutex::new(context)))
+    }
+
+    /// Run `read` on the page; other extractors wait until it returns
+    pub fn read<T>(&self, read: impl FnOnce(&ParsingContext) -> T) -> T {
+        // An extractor that panicked while reading left the page as it was
+        let context = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        read(&context)
+    }
+}
+
+impl From<ParsingContext> for SharedContext {
+    fn from(context: ParsingContext) -> Self {
+        Self::new(context)
+    }
+}
+
+/// Reads a recipe from a parsed page.
+///
+/// `parse` is async so an extractor can call out to an API (an app's own
+/// backend for a paywalled site, say) without blocking the runtime. All
+/// extractors on a page share one [`SharedContext`]: read what you need
+/// from it with [`SharedContext::read`], then await anything else. The
+/// built-in extractors do no I/O and also have a synchronous `parse_page`.
+#[async_trait]
+pub trait Extractor: Send + Sync {
+    async fn parse(&self, context: &SharedContext) -> Result<Recipe, Box<dyn Error + Send + Sync>>;
+
+    /// Short name reported as the extractor that matched, and in diagnostics
+    fn name(&self) -> &'static str {
+        "custom"
+    }
 }
@@ -0,0 +1,143 @@
+use super::{Extractor, ParsingContext, SharedContext};
+use crate::model::{MetadataKey, Recipe};
+use async_trait::async_trait;
+use scraper::{Html, Selector};
+use std::collections::HashMap;
+use std::error::Error;
+
+/// Page metadata from Open Graph and plain `<meta>` tags: title, image,
+/// description and author.
+///
+/// This never finds ingredients or steps, so it isn't one of the structured
+/// extractors. When they all fail, the URL pipeline takes the recipe body
+/// from LLM text extraction and fills in whatever it left out from here, so
+/// pages without structured data still get a name, image and source.
+pub struct OpenGraphExtractor;
+
+impl OpenGraphExtractor {
+    /// The page's title, image, description and author, as a recipe
+    /// without ingredients or steps
+    pub fn parse_page(
+        &self,
+        context: &ParsingContext,
+    ) -> Result<Recipe, Box<dyn std::error::Error>> {
+        let document = &context.document;
+
+        let name = meta(document, &["og:title", "twitter:title"]).or_else(|| {
+            let selector = Selector::parse("head title").unwrap();
+            document
+                .select(&selector)
+                .next()
+                .map(|title| title.text().collect::<String>().trim().to_string())
+                .filter(|title| !title.is_empty())
+        });
+        let description = meta(
+            document,
+            &["og:description", "twitter:description", "description"],
+        );
+        let image = meta(document, &["og:image", "og:image:url", "twitter:image"]);
+        let author = meta(document, &["article:author", "author"]);
+
+        if name.is_none() && image.is_none() && description.is_none() {
+            return Err("No Open Graph or meta tags found".into());
+        }
+
+        let mut metadata = HashMap::new();
+        let source = meta(document, &["og:url"]).unwrap_or_else(|| context.url.clone());
+        metadata.insert(MetadataKey::Source.to_string(), source);
+        if let Some(author) = author {
+            metadata.insert(MetadataKey::Author.to_string(), author);
+        }
+
+        Ok(Recipe {
+            name: name.unwrap_or_default(),
+            description,
+            image: image.into_iter().collect(),
+            ingredients: Vec::new(),
+            instructions: String::new(),
+            equipment: Vec::new(),
+            metadata,
+            dropped_fields: Vec::new(),
+        })
+    }
+}
+
+#[async_trait]
+impl Extractor for OpenGraphExtractor {
+    fn name(&self) -> &'static str {
+        "open-graph"
+    }
+
+    async fn parse(&self, context: &SharedContext) -> Result<Recipe, Box<dyn Error + Send + Sync>> {
+        context
+            .read(|context| self.parse_page(context))
+            .map_err(|e| e.to_string().into())
+    }
+}
+
+/// `content` of the first non-empty `<meta>` tag among `names`, in order.
+/// Open Graph uses `property=`, everything else `name=`; both are checked.
+fn meta(document: &Html, names: &[&str]) -> Option<String> {
+    names.iter().find_map(|name| {
+        let selector =
+            Selector::parse(&format!("meta[property='{0}'], meta[name='{0}']", name)).unwrap();
+        document
+            .select(&selector)
+            .filter_map(|element| element.value().attr("content"))
+            .map(str::trim)
+            .find(|content| !content.is_empty())
+            .map(str::to_string)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(html: &str) -> ParsingContext {
+        ParsingContext {
+            url: "https://example.com/soup?utm_source=feed".to_string(),
+            document: Html::parse_document(html),
+            texts: None,
+        }
+    }
+
+    #[test]
+    fn test_open_graph_tags() {
+        let html = r#"<html><head>
+            <title>Tomato Soup | Example Kitchen</title>
+            <meta property="og:title" content="Tomato Soup">
+            <meta property="og:image" content="https://example.com/soup.jpg">
+            <meta name="description" content="A quick weeknight soup.">
+            <meta property="article:author" content="Jane Doe">
+            <meta property="og:url" content="https://example.com/soup">
+        </head><body></body></html>"#;
+
+        let recipe = OpenGraphExtractor.parse_page(&context(html)).unwrap();
+        assert_eq!(recipe.name, "Tomato Soup");
+        assert_eq!(recipe.image, vec!["https://example.com/soup.jpg"]);
+        assert_eq!(
+            recipe.description.as_deref(),
+            Some("A quick weeknight soup.")
+        );
+        assert_eq!(recipe.metadata["author"], "Jane Doe");
+        assert_eq!(recipe.metadata["source"], "https://example.com/soup");
+        assert!(recipe.ingredients.is_empty());
+    }
+
+    #[test]
+    fn test_falls_back_to_title_and_errors_without_tags() {
+        let recipe = OpenGraphExtractor
+            .parse_page(&context("<html><head><title> Soup </title></head></html>"))
+            .unwrap();
+        assert_eq!(recipe.name, "Soup");
+        assert_eq!(
+            recipe.metadata["source"],
+            "https://example.com/soup?utm_source=feed"
+        );
+
+        assert!(OpenGraphExtractor
+            .parse_page(&context("<html><body><p>Soup</p></body></html>"))
+            .is_err());
+    }
+}
@@ -0,0 +1,79 @@
+/// Format a schema.org `AggregateRating` as "4.7 (1234 reviews)".
+///
+/// `value` is the `ratingValue`, rounded to one decimal; `count` the
+/// `ratingCount` (or `reviewCount`), read for its digits so "1,234" works.
+/// A `best` rating other than 5 is kept as "9/10". Returns `None` when the
+/// value isn't a number or nobody has rated the recipe.
+pub(crate) fn format_rating(
+    value: &str,
+    count: Option<&str>,
+    best: Option<&str>,
+) -> Option<String> {
+    let value: f64 = value.trim().parse().ok()?;
+    let count = count.and_then(|count| {
+        let whole = count.trim().split('.').next().unwrap_or_default();
+        whole
+            .chars()
+            .filter(char::is_ascii_digit)
+            .collect::<String>()
+            .parse::<u64>()
+            .ok()
+    });
+    if value <= 0.0 || count == Some(0) {
+        return None;
+    }
+
+    let mut rating = number(value);
+    if let Some(best) = best.and_then(|best| best.trim().parse::<f64>().ok()) {
+        if best > 0.0 && best != 5.0 {
+            rating = format!("{}/{}", rating, number(best));
+        }
+    }
+    match count {
+        Some(1) => Some(format!("{} (1 review)", rating)),
+        Some(count) => Some(format!("{} ({} reviews)", rating, count)),
+        None => Some(rating),
+    }
+}
+
+/// One decimal at most: 4.666 → "4.7", 5.0 → "5"
+fn number(value: f64) -> String {
+    let rounded = format!("{:.1}", value);
+    rounded
+        .strip_suffix(".0")
+        .map(str::to_string)
+        .unwrap_or(rounded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_rating() {
+        assert_eq!(
+            format_rating("4.7", Some("1234"), None).as_deref(),
+            Some("4.7 (1234 reviews)")
+        );
+        assert_eq!(
+            format_rating("4.6666", Some("1,234"), Some("5")).as_deref(),
+            Some("4.7 (1234 reviews)")
+        );
+        assert_eq!(
+            format_rating("5", Some("1"), None).as_deref(),
+            Some("5 (1 review)")
+        );
+        assert_eq!(
+            format_rating("9", None, Some("10")).as_deref(),
+            Some("9/10")
+        );
+    }
+
+    #[test]
+    fn test_unrated_recipe_has_no_rating() {
+        assert_eq!(format_rating("0", Some("0"), None), None);
+        assert_eq!(format_rating("4.5", Some("0"), None), None);
+        assert_eq!(format_rating("", None, None), None);
+        assert_eq!(format_rating("great", Some("3"), None), None);
+    }
+}
@@ -0,0 +1,28 @@
+use super::Extractor;
+use std::sync::{Arc, PoisonError, RwLock};
+
+type Registered = Arc<dyn Extractor + Send + Sync>;
+
+static REGISTRY: RwLock<Vec<Registered>> = RwLock::new(Vec::new());
+
+/// Add an extractor for the URL pipeline to try before the built-in ones,
+/// e.g. for a paywalled site an app has access to.
+///
+/// Registered extractors run in registration order on every page, ahead of
+/// site adapters and JSON-LD, so one should return an error for pages it
+/// isn't meant for (check `context.url`). The first one that returns a
+/// recipe wins and is reported under its [`Extractor::name`].
+pub fn register_extractor(extractor: Box<dyn Extractor + Send + Sync>) {
+    REGISTRY
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push(extractor.into());
+}
+
+/// Everything added with [`register_extractor`], in order
+pub(crate) fn registered_extractors() -> Vec<Registered> {
+    REGISTRY
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
@@ -0,0 +1,94 @@
+/// Phrases that mark the per-serving amount in yield text
+const PER_SERVING_MARKERS: &[&str] = &[
+    "per serving",
+    "per portion",
+    "per person",
+    "a serving",
+    "/serving",
+    "each",
+];
+
+/// Split yield text into servings and an optional serving size.
+///
+/// Sites often put both in one field: "4 servings (250 g each)" or
+/// "6 portions, about 300g per portion". The serving size is the
+/// parenthesised or comma-separated part that mentions a per-serving amount
+/// and contains a number. Anything else is returned unchanged as servings.
+pub(crate) fn split_serving_size(yield_text: &str) -> (String, Option<String>) {
+    let text = yield_text.trim();
+
+    // "4 servings (250 g each)"
+    if let (Some(open), Some(close)) = (text.find('('), text.rfind(')')) {
+        if open < close {
+            if let Some(size) = serving_size_from(&text[open + 1..close]) {
+                let servings = format!("{} {}", text[..open].trim(), text[close + 1..].trim());
+                return (servings.trim().to_string(), Some(size));
+            }
+        }
+    }
+
+    // "6 portions, about 300g per portion" / "Serves 4; 2 pieces per person"
+    if let Some(split) = text.find([',', ';']) {
+        if let Some(size) = serving_size_from(&text[split + 1..]) {
+            return (text[..split].trim().to_string(), Some(size));
+        }
+    }
+
+    (text.to_string(), None)
+}
+
+/// Strip the per-serving marker from a clause, if it has one and a number
+fn serving_size_from(clause: &str) -> Option<String> {
+    // ASCII lowercasing keeps byte offsets aligned with the original
+    let lower = clause.to_ascii_lowercase();
+    let marker = PER_SERVING_MARKERS.iter().find(|m| lower.contains(*m))?;
+    if !clause.contains(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let start = lower.find(marker)?;
+    let size = format!(
+        "{} {}",
+        clause[..start].trim(),
+        clause[start + marker.len()..].trim()
+    );
+    let size = size.trim().trim_end_matches(['.', ':']).trim();
+    (!size.is_empty()).then(|| size.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parenthesised_serving_size() {
+        assert_eq!(
+            split_serving_size("4 servings (250 g each)"),
+            ("4 servings".to_string(), Some("250 g".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_trailing_clause_serving_size() {
+        assert_eq!(
+            split_serving_size("6 portions, about 300g per portion"),
+            ("6 portions".to_string(), Some("about 300g".to_string()))
+        );
+        assert_eq!(
+            split_serving_size("Serves 4; 2 pieces per person"),
+            ("Serves 4".to_string(), Some("2 pieces".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_plain_yield_is_unchanged() {
+        assert_eq!(
+            split_serving_size("12 cookies"),
+            ("12 cookies".to_string(), None)
+        );
+        assert_eq!(
+            split_serving_size("1 loaf (about 12 slices)"),
+            ("1 loaf (about 12 slices)".to_string(), None)
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Built-in extractors for popular recipe sites whose markup the generic
+//! extractors handle poorly. Each site is a [`SiteAdapter`] shipped with the
+//! crate; JSON-LD on the page fills in what the markup doesn't carry.
+
+use super::{Extractor, JsonLdExtractor, ParsingContext, SharedContext, SiteAdapter};
+use crate::model::Recipe;
+use async_trait::async_trait;
+use std::error::Error;
+
+/// (reported name, adapter TOML)
+const SITES: &[(&str, &str)] = &[
+    ("nyt-cooking", include_str!("nyt_cooking.toml")),
+    ("allrecipes", include_str!("allrecipes.toml")),
+    ("serious-eats", include_str!("serious_eats.toml")),
+    ("marmiton", include_str!("marmiton.toml")),
+    ("chefkoch", include_str!("chefkoch.toml")),
+];
+
+/// A built-in site extractor
+pub struct SiteExtractor {
+    name: &'static str,
+    adapter: SiteAdapter,
+}
+
+impl SiteExtractor {
+    /// The built-in extractor for the host of `url`, if there is one
+    pub fn for_url(url: &str) -> Option<Self> {
+        SITES.iter().find_map(|&(name, toml)| {
+            let adapter = SiteAdapter::from_toml(toml).expect("built-in site adapters are valid");
+            adapter
+                .matches(url)
+                .then_some(SiteExtractor { name, adapter })
+        })
+    }
+}
+
+impl SiteExtractor {
+    /// Ingredients, steps and title from the site's markup, then
+    /// description, image and metadata from JSON-LD where the markup had
+    /// none
+    pub fn parse_page(
+        &self,
+        context: &ParsingContext,
+    ) -> Result<Recipe, Box<dyn std::error::Error>> {
+        let mut recipe = self.adapter.parse_page(context)?;
+        let Ok(json_ld) = JsonLdExtractor.parse_page(context) else {
+            return Ok(recipe);
+        };
+
+        if recipe.name.is_empty() {
+            recipe.name = json_ld.name;
+        }
+        if recipe.description.is_none() {
+            recipe.description = json_ld.description;
+        }
+        if recipe.image.is_empty() {
+            recipe.image = json_ld.image;
+        }
+        for (key, value) in json_ld.metadata {
+            recipe.metadata.entry(key).or_insert(value);
+        }
+        Ok(recipe)
+    }
+}
+
+#[async_trait]
+impl Extractor for SiteExtractor {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    async fn parse(&self, context: &SharedContext) -> Result<Recipe, Box<dyn Error + Send + Sync>> {
+        context
+            .read(|context| self.parse_page(context))
+            .map_err(|e| e.to_string().into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scraper::Html;
+
+    fn extract(url: &str, html: &str) -> Recipe {
+        let extractor = SiteExtractor::for_url(url).expect("a built-in site");
+        extractor
+            .parse_page(&ParsingContext {
+                url: url.to_string(),
+                document: Html::parse_document(html),
+                texts: None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_dispatch_by_hostname() {
+        for (url, name) in [
+            ("https://cooking.nytimes.com/recipes/1015819", "nyt-cooking"),
+            ("https://www.allrecipes.com/recipe/21014/", "allrecipes"),
+            ("https://www.seriouseats.com/pizza", "serious-eats"),
+            ("https://www.marmiton.org/recettes/crepes.aspx", "marmiton"),
+            (
+                "https://www.chefkoch.de/rezepte/123/Gulasch.html",
+                "chefkoch",
+            ),
+        ] {
+            assert_eq!(SiteExtractor::for_url(url).unwrap().name(), name);
+        }
+        assert!(SiteExtractor::for_url("https://www.nytimes.com/").is_none());
+        assert!(SiteExtractor::for_url("https://example.com/").is_none());
+    }
+
+    #[test]
+    fn test_nyt_cooking_joins_quantity_spans() {
+        let recipe = extract(
+            "https://cooking.nytimes.com/recipes/1015819",
+            r#"<h1>Cacio e Pepe</h1>
+            <ul>
+                <li class="ingredient_ingredient__rfjvs"><span class="ingredient_quantity__Z_Mvw">6</span><span>ounces pasta</span></li>
+                <li class="ingredient_ingredient__rfjvs"><span class="ingredient_quantity__Z_Mvw">1</span><span>cup pecorino, finely grated</span></li>
+            </ul>
+            <ol>
+                <li class="preparation_step__nzZHP"><p class="pantry--body-long">Boil the pasta.</p></li>
+                <li class="preparation_step__nzZHP"><p class="pantry--body-long">Toss with cheese and pepper.</p></li>
+            </ol>"#,
+        );
+        assert_eq!(recipe.name, "Cacio e Pepe");
+        assert_eq!(
+            recipe.ingredients,
+            vec!["6 ounces pasta", "1 cup pecorino, finely grated"]
+        );
+        assert_eq!(
+            recipe.instructions,
+            "Boil the pasta.\n\nToss with cheese and pepper."
+        );
+    }
+
+    #[test]
+    fn test_allrecipes_fills_metadata_from_json_ld() {
+        let recipe = extract(
+            "https://www.allrecipes.com/recipe/21014/good-old-fashioned-pancakes/",
+            r#"<html><head><script type="application/ld+json">
+                {"@type": "Recipe", "name": "Pancakes", "prepTime": "PT5M",
+                 "recipeYield": "8", "recipeIngredient": ["1 1/2 cups flour"],
+                 "recipeInstructions": "Mix."}
+            </script></head><body>
+            <h1 class="article-heading">Good Old-Fashioned Pancakes</h1>
+            <ul class="mm-recipes-structured-ingredients__list">
+                <li class="mm-recipes-structured-ingredients__list-item"><p><span data-ingredient-quantity="true">1 ½</span><span data-ingredient-unit="true">cups</span><span data-ingredient-name="true">all-purpose flour</span></p></li>
+            </ul>
+            <div id="mm-recipes-steps__content_1-0"><ol>
+                <li><p>Sift the flour.</p><figure><figcaption>Dotdash Meredith Food Studios</figcaption></figure></li>
+                <li><p>Make a well in the center.</p></li>
+            </ol></div>
+            </body></html>"#,
+        );
+        assert_eq!(recipe.name, "Good Old-Fashioned Pancakes");
+        assert_eq!(recipe.ingredients, vec!["1 ½ cups all-purpose flour"]);
+        assert_eq!(
+            recipe.instructions,
+            "Sift the flour.\n\nMake a well in the center."
+        );
+        assert_eq!(recipe.metadata["prep time"], "5 minutes");
+        assert_eq!(recipe.metadata["servings"], "8");
+    }
+
+    #[test]
+    fn test_serious_eats_steps_with_several_paragraphs() {
+        let recipe = extract(
+            "https://www.seriouseats.com/the-best-chili",
+            r#"<h1 class="heading__title">The Best Chili</h1>
+            <ul><li class="structured-ingredients__list-item"><p><span data-ingredient-quantity="true">2</span> <span data-ingredient-unit="true">pounds</span> <span data-ingredient-name="true">beef chuck</span></p></li></ul>
+            <section id="structured-project__steps_1-0"><ol>
+                <li><p>Brown the beef.</p><p>Work in batches.</p></li>
+            </ol></section>"#,
+        );
+        assert_eq!(recipe.ingredients, vec!["2 pounds beef chuck"]);
+        assert_eq!(recipe.instructions, "Brown the beef.\n\nWork in batches.");
+    }
+
+    #[test]
+    fn test_marmiton_ingredient_cards() {
+        let recipe = extract(
+            "https://www.marmiton.org/recettes/recette_pate-a-crepes_12372.aspx",
+            r#"<h1>Pâte à crêpes</h1>
+            <div class="card-ingredient"><img src="farine.jpg"><div class="card-ingredient-content"><span class="card-ingredient-quantity"><span class="count">300</span><span class="unit">g</span></span><span class="ingredient-name">farine</span><span class="ingredient-complement">(tamisée)</span></div></div>
+            <div class="recipe-step-list"><div class="recipe-step-list__container"><h3>Étape 1</h3><p>Mettre la farine dans un saladier.</p></div></div>"#,
+        );
+        assert_eq!(recipe.ingredients, vec!["300 g farine (tamisée)"]);
+        assert_eq!(recipe.instructions, "Mettre la farine dans un saladier.");
+    }
+
+    #[test]
+    fn test_chefkoch_table_and_br_steps() {
+        let recipe = extract(
+            "https://www.chefkoch.de/rezepte/123/Gulasch.html",
+            r#"<h1>Gulasch</h1>
+            <table class="ingredients"><tbody>
+                <tr><td class="td-left"><span>500&nbsp;g</span></td><td class="td-right"><span><a href="/rezepte/rind">Rindfleisch</a>, gewürfelt</span></td></tr>
+                <tr><td class="td-left"></td><td class="td-right"><span>Salz</span></td></tr>
+            </tbody></table>
+            <article class="recipe-instructions"><h2>Zubereitung</h2>
+                <div class="ds-box">Das Fleisch anbraten.<br>Mit Wasser ablöschen.<br><br>2 Stunden schmoren.</div>
+            </article>"#,
+        );
+        assert_eq!(
+            recipe.ingredients,
+            vec!["500 g Rindfleisch, gewürfelt", "Salz"]
+        );
+        assert_eq!(
+            recipe.instructions,
+            "Das Fleisch anbraten.\n\nMit Wasser ablöschen.\n\n2 Stunden schmoren."
+        );
+    }
+}
@@ -1 +1,5 @@
+pub mod detect;
+pub mod diagnostics;
+pub mod discover;
 pub mod extractors;
+pub mod prescan;
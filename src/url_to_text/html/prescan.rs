@@ -0,0 +1,78 @@
+//! Reading the few parts of a page the extractors need first straight from
+//! the raw HTML, so a multi-megabyte page isn't parsed into a full DOM when
+//! its JSON-LD or `<head>` is enough.
+
+use scraper::Html;
+
+/// The page's JSON-LD `<script>` blocks as a small document of their own;
+/// `None` when the raw HTML has none
+pub fn json_ld_document(html: &str) -> Option<Html> {
+    // ASCII lowercasing keeps byte offsets, so they index `html` as well
+    let lower = html.to_ascii_lowercase();
+    let mut scripts = String::new();
+    let mut from = 0;
+    while let Some(start) = lower[from..].find("<script").map(|i| from + i) {
+        let Some(content) = lower[start..].find('>').map(|i| start + i + 1) else {
+            break;
+        };
+        let Some(end) = lower[content..].find("</script").map(|i| content + i) else {
+            break;
+        };
+        if lower[start..content].contains("application/ld+json") {
+            scripts.push_str(&html[start..end]);
+            scripts.push_str("</script>");
+        }
+        from = end;
+    }
+    (!scripts.is_empty())
+        .then(|| Html::parse_document(&format!("<html><head>{}</head></html>", scripts)))
+}
+
+/// The page up to the end of its `<head>`, or all of it when it has no
+/// closing `</head>`
+pub fn head(html: &str) -> &str {
+    match html.to_ascii_lowercase().find("</head") {
+        Some(end) => &html[..end],
+        None => html,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scraper::Selector;
+
+    #[test]
+    fn test_json_ld_document() {
+        let html = r#"<html><head>
+            <script src="app.js"></script>
+            <SCRIPT type="application/ld+json">{"@type": "Recipe", "name": "Soup"}</SCRIPT>
+            <script>var recipe = "<div>";</script>
+            </head><body><div>huge page</div>
+            <script type='application/ld+json'>{"@type": "BreadcrumbList"}</script>
+            </body></html>"#;
+        let document = json_ld_document(html).unwrap();
+        let selector = Selector::parse("script[type='application/ld+json']").unwrap();
+        let scripts: Vec<String> = document
+            .select(&selector)
+            .map(|script| script.inner_html())
+            .collect();
+        assert_eq!(
+            scripts,
+            [
+                r#"{"@type": "Recipe", "name": "Soup"}"#,
+                r#"{"@type": "BreadcrumbList"}"#
+            ]
+        );
+        assert!(!document.html().contains("huge page"));
+
+        assert!(json_ld_document("<html><body><script>1</script></body></html>").is_none());
+    }
+
+    #[test]
+    fn test_head() {
+        let html = "<html><head><title>Soup</title></HEAD><body>text</body></html>";
+        assert_eq!(head(html), "<html><head><title>Soup</title>");
+        assert_eq!(head("<p>no head</p>"), "<p>no head</p>");
+    }
+}
@@ -1,3 +1,6 @@
 pub mod fetchers;
 pub mod html;
+pub mod normalize;
+pub mod social;
 pub mod text;
+pub mod wayback;
@@ -0,0 +1,262 @@
+//! Recipe URL checks and clean-up: which URLs are worth fetching, and the
+//! URL to store as `source`
+
+use reqwest::Url;
+use scraper::{Html, Selector};
+
+/// Query parameters added by newsletters, ads and share buttons
+const TRACKING_PARAMS: &[&str] = &[
+    "fbclid", "gclid", "dclid", "gbraid", "wbraid", "msclkid", "yclid", "twclid", "igshid",
+    "mc_cid", "mc_eid", "_hsenc", "_hsmi", "mkt_tok",
+];
+
+/// File extensions of images, which go through OCR instead of a fetch
+const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "gif", "webp", "heic", "heif"];
+
+/// File extensions of downloads that never hold an HTML recipe
+const BINARY_EXTENSIONS: &[&str] = &[
+    "pdf", "zip", "gz", "tar", "rar", "7z", "mp3", "mp4", "mov", "avi", "webm", "exe", "dmg",
+    "apk", "doc", "docx", "xls", "xlsx",
+];
+
+/// Check that `url` is an http(s) link to something other than an image or
+/// a download, returning the reason when it isn't
+pub fn validate_url(url: &str) -> Result<(), String> {
+    let parsed = Url::parse(url.trim()).map_err(|e| format!("Invalid URL '{}': {}", url, e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!(
+            "Unsupported URL scheme '{}': only http and https pages can be imported",
+            parsed.scheme()
+        ));
+    }
+
+    let extension = parsed
+        .path()
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_lowercase())
+        .unwrap_or_default();
+    if IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+        return Err(format!(
+            "{} is an image, not a recipe page; download it and import it as an image",
+            url
+        ));
+    }
+    if BINARY_EXTENSIONS.contains(&extension.as_str()) {
+        return Err(format!(
+            "{} is a .{} file, not a recipe page",
+            url, extension
+        ));
+    }
+    Ok(())
+}
+
+/// `url` without tracking parameters (`utm_*`, `fbclid`, ...) and fragment.
+/// Anything that doesn't parse is returned as is.
+pub fn strip_tracking_params(url: &str) -> String {
+    let Ok(mut parsed) = Url::parse(url) else {
+        return url.to_string();
+    };
+    let is_tracking = |key: &str| key.starts_with("utm_") || TRACKING_PARAMS.contains(&key);
+    let tracked = parsed.query_pairs().any(|(key, _)| is_tracking(&key));
+    if !tracked && parsed.fragment().is_none() {
+        // Leave the URL exactly as written
+        return url.to_string();
+    }
+
+    parsed.set_fragment(None);
+    if tracked {
+        let kept: Vec<(String, String)> = parsed
+            .query_pairs()
+            .filter(|(key, _)| !is_tracking(key))
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect();
+        if kept.is_empty() {
+            parsed.set_query(None);
+        } else {
+            parsed.query_pairs_mut().clear().extend_pairs(kept);
+        }
+    }
+    parsed.to_string()
+}
+
+/// The page's `<link rel="canonical">`, made absolute, if it is an http(s)
+/// URL
+pub fn canonical_url(document: &Html, page_url: &str) -> Option<String> {
+    let selector = Selector::parse("link[rel~='canonical'][href]").unwrap();
+    let href = document.select(&selector).next()?.value().attr("href")?;
+    let canonical = Url::parse(page_url).ok()?.join(href.trim()).ok()?;
+    matches!(canonical.scheme(), "http" | "https").then(|| canonical.to_string())
+}
+
+/// The publisher's URL for a page on an AMP cache
+/// (`https://www-example-com.cdn.ampproject.org/c/s/www.example.com/soup`,
+/// `https://www.google.com/amp/s/www.example.com/soup`), which only serves a
+/// copy of the AMP page
+pub fn amp_cache_origin(url: &str) -> Option<String> {
+    let parsed = Url::parse(url).ok()?;
+    let host = parsed.host_str()?;
+    let path = parsed.path();
+    let rest = if host.ends_with(".cdn.ampproject.org") {
+        // /c/ for documents, /v/ for the viewer
+        path.strip_prefix("/c/")
+            .or_else(|| path.strip_prefix("/v/"))?
+    } else if host.trim_start_matches("www.").starts_with("google.") {
+        path.strip_prefix("/amp/")?
+    } else {
+        return None;
+    };
+    let origin = match rest.strip_prefix("s/") {
+        Some(rest) => format!("https://{}", rest),
+        None => format!("http://{}", rest),
+    };
+    let mut origin = Url::parse(&origin).ok()?;
+    origin.set_query(parsed.query());
+    Some(origin.to_string())
+}
+
+/// Whether a page is the AMP version of an article: its `<html>` tag has the
+/// `amp` (or `⚡`) attribute, or the URL has an `amp` path segment or an
+/// `amp`/`outputType=amp` query
+pub fn is_amp(document: &Html, page_url: &str) -> bool {
+    let root = document.root_element().value();
+    if root.attr("amp").is_some() || root.attr("⚡").is_some() {
+        return true;
+    }
+    let Ok(url) = Url::parse(page_url) else {
+        return false;
+    };
+    let amp_segment = url
+        .path_segments()
+        .is_some_and(|mut segments| segments.any(|segment| segment == "amp"));
+    amp_segment
+        || url.query_pairs().any(|(key, value)| {
+            key == "amp" || (key.eq_ignore_ascii_case("outputType") && value == "amp")
+        })
+}
+
+/// The non-AMP page to extract from instead of `page_url`, when that is an
+/// AMP page with a canonical URL elsewhere. Only the page's `<head>` is
+/// parsed, which holds both the `<html amp>` tag and the canonical link.
+pub fn non_amp_url(html: &str, page_url: &str) -> Option<String> {
+    let document = Html::parse_document(crate::url_to_text::html::prescan::head(html));
+    if !is_amp(&document, page_url) {
+        return None;
+    }
+    canonical_url(&document, page_url).filter(|canonical| canonical != page_url)
+}
+
+/// The URL to store as `source` for a page fetched from `page_url`: its
+/// canonical URL when it has one, without tracking parameters. Only the
+/// page's `<head>` is parsed, where the canonical link belongs.
+pub fn source_url(html: &str, page_url: &str) -> String {
+    let head = crate::url_to_text::html::prescan::head(html);
+    let canonical = canonical_url(&Html::parse_document(head), page_url);
+    strip_tracking_params(canonical.as_deref().unwrap_or(page_url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_url() {
+        assert!(validate_url("https://example.com/recipes/pancakes").is_ok());
+        assert!(validate_url("http://example.com/recipe.php?id=3").is_ok());
+
+        assert!(validate_url("ftp://example.com/recipe")
+            .unwrap_err()
+            .contains("Unsupported URL scheme 'ftp'"));
+        assert!(validate_url("file:///home/me/recipe.html").is_err());
+        assert!(validate_url("example.com/recipe")
+            .unwrap_err()
+            .starts_with("Invalid URL"));
+        assert!(validate_url("https://example.com/photos/Cake.JPG")
+            .unwrap_err()
+            .contains("is an image"));
+        assert!(validate_url("https://example.com/cookbook.pdf")
+            .unwrap_err()
+            .contains("is a .pdf file"));
+    }
+
+    #[test]
+    fn test_strip_tracking_params() {
+        assert_eq!(
+            strip_tracking_params(
+                "https://example.com/soup?utm_source=newsletter&utm_medium=email&id=3&fbclid=abc#comments"
+            ),
+            "https://example.com/soup?id=3"
+        );
+        assert_eq!(
+            strip_tracking_params("https://example.com/soup?utm_campaign=x"),
+            "https://example.com/soup"
+        );
+        assert_eq!(
+            strip_tracking_params("https://example.com?id=a+b"),
+            "https://example.com?id=a+b"
+        );
+    }
+
+    #[test]
+    fn test_amp_cache_origin() {
+        assert_eq!(
+            amp_cache_origin(
+                "https://www-example-com.cdn.ampproject.org/c/s/www.example.com/soup/amp?id=3"
+            )
+            .as_deref(),
+            Some("https://www.example.com/soup/amp?id=3")
+        );
+        assert_eq!(
+            amp_cache_origin("https://www.google.com/amp/s/www.example.com/soup").as_deref(),
+            Some("https://www.example.com/soup")
+        );
+        assert_eq!(
+            amp_cache_origin("https://www.google.com/search?q=soup"),
+            None
+        );
+        assert_eq!(amp_cache_origin("https://example.com/amp/soup"), None);
+    }
+
+    #[test]
+    fn test_non_amp_url() {
+        let amp = r#"<html amp><head><link rel="canonical" href="https://example.com/soup"></head></html>"#;
+        assert_eq!(
+            non_amp_url(amp, "https://example.com/soup?amp=1").as_deref(),
+            Some("https://example.com/soup")
+        );
+        assert_eq!(
+            non_amp_url(amp, "https://example.com/soup"),
+            None,
+            "already on the canonical page"
+        );
+
+        let by_url = r#"<html><head><link rel="canonical" href="/soup"></head></html>"#;
+        assert_eq!(
+            non_amp_url(by_url, "https://example.com/soup/amp/").as_deref(),
+            Some("https://example.com/soup")
+        );
+        assert_eq!(non_amp_url(by_url, "https://example.com/soup-2"), None);
+
+        let lightning = r#"<html ⚡ lang="en"><head><link rel="canonical" href="/soup"></head><body><p>Soup</p></body></html>"#;
+        assert_eq!(
+            non_amp_url(lightning, "https://example.com/soup.amp.html").as_deref(),
+            Some("https://example.com/soup")
+        );
+    }
+
+    #[test]
+    fn test_source_url() {
+        let html = r#"<html><head><link rel="canonical" href="/recipes/soup"></head></html>"#;
+        assert_eq!(
+            source_url(html, "https://example.com/soup-123?utm_source=pinterest"),
+            "https://example.com/recipes/soup"
+        );
+
+        assert_eq!(
+            source_url(
+                "<html></html>",
+                "https://example.com/soup?utm_source=pinterest"
+            ),
+            "https://example.com/soup"
+        );
+    }
+}
@@ -0,0 +1,276 @@
+//! Recipes posted as short videos on Instagram and TikTok, where the
+//! ingredients and steps live in the post's caption.
+
+use crate::url_to_text::fetchers::RequestFetcher;
+use crate::url_to_text::html::extractors::{OpenGraphExtractor, ParsingContext};
+use scraper::Html;
+use std::error::Error;
+use std::time::Duration;
+
+const TIKTOK_OEMBED: &str = "https://www.tiktok.com/oembed";
+
+/// A social network whose posts can carry a recipe caption
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    Instagram,
+    TikTok,
+}
+
+impl Platform {
+    /// The platform `url` is a post on, if it is one
+    pub fn detect(url: &str) -> Option<Self> {
+        let rest = url.split("://").nth(1).unwrap_or(url);
+        let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+        let host = host.to_lowercase();
+        let host = host.strip_prefix("www.").unwrap_or(&host);
+        match host {
+            "instagram.com"
+                if ["p/", "reel/", "reels/", "tv/"]
+                    .iter()
+                    .any(|prefix| path.starts_with(prefix)) =>
+            {
+                Some(Platform::Instagram)
+            }
+            "tiktok.com" if path.contains("/video/") => Some(Platform::TikTok),
+            // Share links redirect to the video
+            "vm.tiktok.com" | "vt.tiktok.com" if !path.is_empty() => Some(Platform::TikTok),
+            _ => None,
+        }
+    }
+}
+
+/// A post's caption and what else is known about it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Post {
+    /// Caption with hashtags and emoji cleaned up (see [`clean_caption`])
+    pub caption: String,
+    pub author: Option<String>,
+    /// Video thumbnail
+    pub image: Option<String>,
+}
+
+/// Fetch a post's caption: TikTok through its public oEmbed endpoint,
+/// Instagram from the post page's meta tags
+pub async fn fetch_post(
+    url: &str,
+    platform: Platform,
+    timeout: Duration,
+    proxy: Option<&str>,
+) -> Result<Post, Box<dyn Error + Send + Sync>> {
+    let post = match platform {
+        Platform::TikTok => {
+            let json: serde_json::Value = crate::http::client(TIKTOK_OEMBED, timeout, proxy)?
+                .get(TIKTOK_OEMBED)
+                .query(&[("url", url)])
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            parse_oembed(&json)
+        }
+        Platform::Instagram => {
+            let html = RequestFetcher::new(Some(timeout), proxy).fetch(url).await?;
+            parse_instagram_page(&html, url)
+        }
+    };
+    match post {
+        Some(post) if !post.caption.is_empty() => Ok(post),
+        _ => Err(format!("No caption found for {}", url).into()),
+    }
+}
+
+/// A post from an oEmbed response; TikTok puts the caption in `title`
+fn parse_oembed(json: &serde_json::Value) -> Option<Post> {
+    let field = |name: &str| {
+        json[name]
+            .as_str()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    Some(Post {
+        caption: clean_caption(&field("title")?),
+        author: field("author_name"),
+        image: field("thumbnail_url"),
+    })
+}
+
+/// A post from an Instagram page. Its description reads
+/// `12 likes, 3 comments - chef on May 1, 2024: "caption".`
+fn parse_instagram_page(html: &str, url: &str) -> Option<Post> {
+    let page = OpenGraphExtractor
+        .parse_page(&ParsingContext {
+            url: url.to_string(),
+            document: Html::parse_document(html),
+            texts: None,
+        })
+        .ok()?;
+    let description = page.description?;
+    let caption = match description.split_once(": \"") {
+        Some((_, quoted)) => quoted
+            .trim_end()
+            .trim_end_matches('.')
+            .trim_end_matches('"'),
+        None => description.as_str(),
+    };
+    // The title reads `chef on Instagram: "caption"`
+    let author = page
+        .name
+        .split_once(" on Instagram")
+        .map(|(author, _)| author.trim().to_string())
+        .filter(|author| !author.is_empty());
+    Some(Post {
+        caption: clean_caption(caption),
+        author,
+        image: page.image.into_iter().next(),
+    })
+}
+
+/// Tidy a caption for text extraction.
+///
+/// Hashtags and @mentions at the end of a line are dropped, and ones inside
+/// a sentence lose their `#`. Emoji used as bullets become `- `, keycap
+/// digits (1️⃣) become `1.`, and other emoji are removed.
+pub fn clean_caption(caption: &str) -> String {
+    let mut lines = Vec::new();
+    for line in caption.lines() {
+        let line = normalize_emoji(line);
+        let mut words: Vec<&str> = line.split_whitespace().collect();
+        while words.last().is_some_and(|word| is_tag(word)) {
+            words.pop();
+        }
+        let line = words
+            .iter()
+            .map(|word| match word.strip_prefix('#') {
+                Some(tag) if is_tag(word) => tag,
+                _ => word,
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !line.is_empty() || lines.last().is_some_and(|last: &String| !last.is_empty()) {
+            lines.push(line);
+        }
+    }
+    lines.join("\n").trim().to_string()
+}
+
+fn is_tag(word: &str) -> bool {
+    let Some(tag) = word.strip_prefix('#').or_else(|| word.strip_prefix('@')) else {
+        return false;
+    };
+    tag.chars().next().is_some_and(char::is_alphabetic)
+}
+
+fn normalize_emoji(line: &str) -> String {
+    let mut out = String::new();
+    let mut chars = line.trim().chars().peekable();
+    let mut at_start = true;
+    while let Some(c) = chars.next() {
+        // Keycap: digit, optional variation selector, U+20E3
+        if c.is_ascii_digit() {
+            let mut ahead = chars.clone();
+            if ahead.peek() == Some(&'\u{FE0F}') {
+                ahead.next();
+            }
+            if ahead.next() == Some('\u{20E3}') {
+                chars = ahead;
+                out.push(c);
+                out.push_str(". ");
+                at_start = false;
+                continue;
+            }
+        }
+        if is_emoji(c) {
+            if at_start {
+                out.push_str("- ");
+                at_start = false;
+            } else {
+                out.push(' ');
+            }
+            // Skip the rest of the emoji sequence (modifiers, joiners)
+            while chars.peek().is_some_and(|&next| is_emoji(next)) {
+                chars.next();
+            }
+            continue;
+        }
+        if !c.is_whitespace() {
+            at_start = false;
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32,
+        0x1F000..=0x1FAFF // pictographs, emoticons, food, skin tones
+        | 0x2600..=0x27BF // symbols and dingbats (✅ ✔ ➡)
+        | 0x2B00..=0x2BFF // arrows, stars
+        | 0x2022 | 0x25AA..=0x25FE // bullets and squares
+        | 0xFE0F | 0x200D | 0x20E3) // variation selector, joiner, keycap
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_platform() {
+        assert_eq!(
+            Platform::detect("https://www.instagram.com/reel/C1a2b3c4/"),
+            Some(Platform::Instagram)
+        );
+        assert_eq!(
+            Platform::detect("https://www.tiktok.com/@chef/video/7301234567890"),
+            Some(Platform::TikTok)
+        );
+        assert_eq!(
+            Platform::detect("https://vm.tiktok.com/ZMabc123/"),
+            Some(Platform::TikTok)
+        );
+        assert_eq!(Platform::detect("https://www.instagram.com/chef/"), None);
+        assert_eq!(Platform::detect("https://example.com/p/pasta"), None);
+    }
+
+    #[test]
+    fn test_clean_caption() {
+        let caption = "Garlic butter pasta 🍝🔥 #dinner\n\n🧄 4 cloves #garlic, minced\n✅ 200g spaghetti\n1️⃣ Boil the pasta\n2️⃣ Toss with butter 😍\n\n#pasta #easyrecipes @chef";
+        assert_eq!(
+            clean_caption(caption),
+            "Garlic butter pasta\n\n- 4 cloves garlic, minced\n- 200g spaghetti\n1. Boil the pasta\n2. Toss with butter"
+        );
+    }
+
+    #[test]
+    fn test_parse_posts() {
+        let tiktok = serde_json::json!({
+            "title": "Crispy potatoes 🥔 #fyp",
+            "author_name": "Chef",
+            "thumbnail_url": "https://p16.tiktokcdn.com/thumb.jpg"
+        });
+        assert_eq!(
+            parse_oembed(&tiktok),
+            Some(Post {
+                caption: "Crispy potatoes".to_string(),
+                author: Some("Chef".to_string()),
+                image: Some("https://p16.tiktokcdn.com/thumb.jpg".to_string()),
+            })
+        );
+
+        let instagram = r#"<html><head>
+            <meta property="og:title" content="chef on Instagram: &quot;Lemon pasta&quot;">
+            <meta property="og:image" content="https://scontent.cdninstagram.com/pasta.jpg">
+            <meta property="og:description" content="1,024 likes, 12 comments - chef on May 1, 2024: &quot;Lemon pasta&#x1f34b;
+2 lemons
+#pasta&quot;.">
+        </head></html>"#;
+        let post = parse_instagram_page(instagram, "https://www.instagram.com/p/abc/").unwrap();
+        assert_eq!(post.caption, "Lemon pasta\n2 lemons");
+        assert_eq!(post.author.as_deref(), Some("chef"));
+        assert_eq!(
+            post.image.as_deref(),
+            Some("https://scontent.cdninstagram.com/pasta.jpg")
+        );
+    }
+}
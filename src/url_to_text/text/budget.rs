@@ -0,0 +1,281 @@
+//! Fitting long page text into the text extractor's token budget
+//!
+//! Recipe blogs can bury a short recipe in tens of thousands of words of
+//! stories, comments and navigation. Instead of cutting the text off at the
+//! budget (and losing a recipe at the bottom of the page), it is split into
+//! blocks, and the blocks that read like a recipe — quantities with units,
+//! cooking verbs, "Ingredients"/"Instructions" headings — are kept.
+
+use crate::config::load_config;
+use crate::converters::estimate_tokens;
+use tracing::debug;
+
+/// Token budget for text sent to the text extractor when
+/// `[extractors] max_input_tokens` isn't set
+pub const DEFAULT_MAX_INPUT_TOKENS: u64 = 16_000;
+
+/// Longest block; longer lines are split at sentence ends or spaces
+const MAX_BLOCK_CHARS: usize = 600;
+
+/// Marks where blocks were left out
+const GAP: &str = "[…]";
+
+const UNITS: &[&str] = &[
+    "g",
+    "gram",
+    "grams",
+    "kg",
+    "mg",
+    "ml",
+    "l",
+    "litre",
+    "liter",
+    "litres",
+    "liters",
+    "dl",
+    "cl",
+    "oz",
+    "ounce",
+    "ounces",
+    "lb",
+    "lbs",
+    "pound",
+    "pounds",
+    "cup",
+    "cups",
+    "tbsp",
+    "tbs",
+    "tsp",
+    "tablespoon",
+    "tablespoons",
+    "teaspoon",
+    "teaspoons",
+    "pinch",
+    "clove",
+    "cloves",
+    "can",
+    "cans",
+    "slice",
+    "slices",
+    "stick",
+    "sticks",
+    "quart",
+    "quarts",
+    "pint",
+    "pints",
+    "el",
+    "tl",
+    "bunch",
+    "handful",
+];
+
+const VERBS: &[&str] = &[
+    "add", "bake", "beat", "blend", "boil", "bring", "chop", "combine", "cook", "cool", "cut",
+    "dice", "drain", "fold", "fry", "grate", "grill", "heat", "knead", "melt", "mix", "peel",
+    "pour", "preheat", "reduce", "rest", "roast", "saute", "sauté", "season", "serve", "simmer",
+    "slice", "sprinkle", "stir", "toss", "whisk",
+];
+
+const HEADINGS: &[&str] = &[
+    "ingredients",
+    "instructions",
+    "directions",
+    "method",
+    "preparation",
+    "steps",
+    "zutaten",
+    "zubereitung",
+    "ingrédients",
+    "préparation",
+    "ingredientes",
+    "ingredienti",
+];
+
+/// `[extractors] max_input_tokens`, or [`DEFAULT_MAX_INPUT_TOKENS`]
+pub(crate) fn max_input_tokens() -> u64 {
+    load_config()
+        .ok()
+        .and_then(|config| config.extractors.max_input_tokens)
+        .unwrap_or(DEFAULT_MAX_INPUT_TOKENS)
+}
+
+/// `text` cut down to about `max_tokens` (as counted by
+/// [`estimate_tokens`](crate::converters::estimate_tokens)), keeping the
+/// parts most likely to be the recipe.
+///
+/// Text within the budget is returned unchanged. Otherwise the text is split
+/// into lines (long lines into sentences) and each block is scored by its
+/// quantities with units, cooking verbs and recipe headings, plus half the
+/// score of its neighbours, so an unscored "Salt" inside an ingredient list
+/// stays. The densest blocks that fit are kept in page order, with "[…]"
+/// where blocks were left out. If nothing looks like a recipe, the start of
+/// the text is kept.
+///
+/// # Example
+/// ```
+/// use cooklang_import::url_to_text::text::select_recipe_text;
+///
+/// let page = format!(
+///     "{}\nIngredients\n200 g flour\n2 eggs\nWhisk the eggs into the flour.",
+///     "A long story about my grandmother. ".repeat(200)
+/// );
+/// let selected = select_recipe_text(&page, 50);
+/// assert!(selected.contains("200 g flour\n2 eggs\nWhisk the eggs"));
+/// assert!(selected.len() < page.len());
+/// ```
+pub fn select_recipe_text(text: &str, max_tokens: u64) -> String {
+    let total = estimate_tokens(text);
+    if total <= max_tokens {
+        return text.to_string();
+    }
+
+    let blocks = blocks(text);
+    let scores: Vec<u32> = blocks.iter().map(|block| score(block)).collect();
+    let smoothed: Vec<u32> = (0..blocks.len())
+        .map(|i| {
+            let neighbour = |j: Option<usize>| j.and_then(|j| scores.get(j)).copied().unwrap_or(0);
+            scores[i] * 2 + neighbour(i.checked_sub(1)) + neighbour(Some(i + 1))
+        })
+        .collect();
+
+    let cost = |i: usize| estimate_tokens(&blocks[i]) + 1;
+    let mut order: Vec<usize> = (0..blocks.len()).filter(|&i| smoothed[i] > 0).collect();
+    // Densest first: score per token, compared without division
+    order.sort_by(|&a, &b| {
+        (smoothed[b] as u64 * cost(a))
+            .cmp(&(smoothed[a] as u64 * cost(b)))
+            .then(a.cmp(&b))
+    });
+    if order.is_empty() {
+        order = (0..blocks.len()).collect();
+    }
+
+    let mut keep = vec![false; blocks.len()];
+    let mut used = 0;
+    for i in order {
+        if used + cost(i) <= max_tokens {
+            used += cost(i);
+            keep[i] = true;
+        }
+    }
+    debug!(
+        total_tokens = total,
+        max_tokens,
+        kept_blocks = keep.iter().filter(|&&kept| kept).count(),
+        blocks = blocks.len(),
+        "Text over the extraction budget; keeping the recipe-like blocks"
+    );
+
+    let mut lines = Vec::new();
+    for (i, block) in blocks.iter().enumerate() {
+        if keep[i] {
+            lines.push(block.as_str());
+        } else if lines.last() != Some(&GAP) {
+            lines.push(GAP);
+        }
+    }
+    lines.join("\n")
+}
+
+/// Non-empty lines with whitespace collapsed; long lines are split at a
+/// sentence end past half of [`MAX_BLOCK_CHARS`], or at a space past it
+fn blocks(text: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    for line in text.lines() {
+        let mut block = String::new();
+        for word in line.split_whitespace() {
+            if !block.is_empty() {
+                block.push(' ');
+            }
+            block.push_str(word);
+            let sentence_end = word.ends_with(['.', '!', '?']);
+            if block.len() >= MAX_BLOCK_CHARS
+                || (sentence_end && block.len() >= MAX_BLOCK_CHARS / 2)
+            {
+                blocks.push(std::mem::take(&mut block));
+            }
+        }
+        if !block.is_empty() {
+            blocks.push(block);
+        }
+    }
+    blocks
+}
+
+/// 3 per quantity with a unit ("200 g", "200g", "1/2 cup"), 1 per other
+/// number, 2 per cooking verb, 5 per recipe heading
+fn score(block: &str) -> u32 {
+    let words: Vec<String> = block
+        .split(|c: char| c.is_whitespace() || matches!(c, ',' | ';' | ':' | '(' | ')'))
+        .filter(|word| !word.is_empty())
+        .map(|word| word.trim_matches(['.', '!', '?']).to_lowercase())
+        .collect();
+
+    let mut score = 0;
+    for (i, word) in words.iter().enumerate() {
+        let is_quantity = word
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_digit() || "½⅓⅔¼¾⅛".contains(c));
+        if is_quantity {
+            let suffix = word.trim_start_matches(|c: char| !c.is_alphabetic());
+            let next = words.get(i + 1).map(String::as_str).unwrap_or("");
+            score += if UNITS.contains(&suffix) || UNITS.contains(&next) {
+                3
+            } else {
+                1
+            };
+        } else if VERBS.contains(&word.as_str()) {
+            score += 2;
+        } else if HEADINGS.contains(&word.as_str()) {
+            score += 5;
+        }
+    }
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_text_is_unchanged() {
+        let text = "2 eggs\n\n  Whisk.  ";
+        assert_eq!(select_recipe_text(text, 100), text);
+    }
+
+    #[test]
+    fn test_keeps_recipe_blocks_in_order() {
+        let story = "We spent the summer at the lake and the kids loved it. ".repeat(40);
+        let comments = "Great post, thanks for sharing! Love your blog. ".repeat(40);
+        let page = format!(
+            "Home About Contact\n{story}\nIngredients\n500 g potatoes\nSalt\n2 tbsp butter\n\
+             Instructions\nBoil the potatoes for 20 minutes. Drain and mash with the butter.\n{comments}"
+        );
+
+        let selected = select_recipe_text(&page, 60);
+        assert!(estimate_tokens(&selected) <= 60 + 10);
+        assert_eq!(
+            selected,
+            "[…]\nIngredients\n500 g potatoes\nSalt\n2 tbsp butter\nInstructions\n\
+             Boil the potatoes for 20 minutes. Drain and mash with the butter.\n[…]"
+        );
+    }
+
+    #[test]
+    fn test_without_recipe_keeps_the_start() {
+        let page = "Once upon a time.\n".repeat(100);
+        let selected = select_recipe_text(&page, 20);
+        assert!(selected.starts_with("Once upon a time.\nOnce upon a time."));
+        assert!(selected.ends_with("[…]"));
+    }
+
+    #[test]
+    fn test_score() {
+        assert_eq!(score("200g flour"), 3);
+        assert_eq!(score("1/2 cup milk, 2 eggs"), 4);
+        assert_eq!(score("Whisk, then bake."), 4);
+        assert_eq!(score("Ingredients:"), 5);
+        assert_eq!(score("Subscribe to my newsletter"), 0);
+    }
+}
@@ -1,8 +1,11 @@
+use crate::converters::Converter;
+use crate::model::MetadataKey;
 use crate::pipelines::RecipeComponents;
-use reqwest::Client;
+use crate::{ImportError, RecipeImporter};
 use serde_json::Value;
 use std::env;
 use std::error::Error;
+use std::time::Duration;
 
 const PROMPT: &str = r#"
 You're an expert in extracting recipe information from messy texts (often OCR'd from images).
@@ -15,6 +18,7 @@ Given the text, output only this JSON without any other characters:
 {
   "title": "<RECIPE TITLE OR null IF NOT EXPLICITLY STATED>",
   "servings": "<SERVINGS OR null IF NOT EXPLICITLY STATED>",
+  "serving_size": "<AMOUNT PER SERVING, E.G. 250 g OR 2 pieces, OR null IF NOT EXPLICITLY STATED>",
   "prep_time": "<PREP TIME OR null IF NOT EXPLICITLY STATED>",
   "cook_time": "<COOK TIME OR null IF NOT EXPLICITLY STATED>",
   "total_time": "<TOTAL TIME OR null IF NOT EXPLICITLY STATED>",
@@ -24,21 +28,48 @@ Given the text, output only this JSON without any other characters:
 }
 "#;
 
-const MODEL: &str = "gpt-4o-mini";
-
-pub struct TextExtractor;
+/// LLM extraction of a recipe from unstructured text (page text, OCR
+/// output, captions), with any configured provider
+pub struct TextExtractor {
+    converter: Box<dyn Converter>,
+}
 
 impl TextExtractor {
-    /// Check if the TextExtractor is available (has required API key configured)
-    pub fn is_available() -> bool {
-        env::var("OPENAI_API_KEY").is_ok()
+    /// Extract with `converter`'s provider (or fallback chain or race)
+    pub fn new(converter: Box<dyn Converter>) -> Self {
+        TextExtractor { converter }
+    }
+
+    /// Extract with the provider a default [`RecipeImporter`] converts with:
+    /// `default_provider` (or the `[fallback]`/`[race]` providers) from
+    /// config.toml with their models and API keys, else OpenAI.
+    ///
+    /// Fails when the provider can't be created, e.g. without its API key.
+    pub async fn from_config(timeout: Duration, proxy: Option<&str>) -> Result<Self, ImportError> {
+        let mut builder = RecipeImporter::builder().llm_timeout(timeout);
+        if let Some(proxy) = proxy {
+            builder = builder.proxy(proxy);
+        }
+        builder.text_extractor().await
+    }
+
+    /// Name of the provider used ("open_ai", "ollama", "fallback", ...)
+    pub fn provider(&self) -> &str {
+        self.converter.name()
     }
 
+    /// Extract a recipe from `plain_text`, recording `source` as its source.
+    ///
+    /// Text over the token budget is cut down first (see
+    /// [`select_recipe_text`](super::select_recipe_text)). Fails if the text
+    /// isn't a recipe.
     pub async fn extract(
+        &self,
         plain_text: &str,
         source: &str,
     ) -> Result<RecipeComponents, Box<dyn Error + Send + Sync>> {
-        let json = fetch_json(plain_text.to_string()).await?;
+        let text = super::select_recipe_text(plain_text, super::max_input_tokens());
+        let json = self.fetch_json(&text).await?;
 
         // Check for error (not a recipe)
         if let Some(error) = json["error"].as_str() {
@@ -51,11 +82,17 @@ impl TextExtractor {
         let name = json["title"].as_str().unwrap_or("").to_string();
 
         // Build metadata YAML from available fields
-        let mut entries = vec![("source".to_string(), source.to_string())];
-        for field in ["servings", "prep_time", "cook_time", "total_time"] {
+        let mut entries = vec![(MetadataKey::Source.to_string(), source.to_string())];
+        for field in [
+            "servings",
+            "serving_size",
+            "prep_time",
+            "cook_time",
+            "total_time",
+        ] {
             if let Some(val) = json[field].as_str() {
                 if !val.is_empty() {
-                    entries.push((field.to_string(), val.to_string()));
+                    entries.push((MetadataKey::canonical(field), val.to_string()));
                 }
             }
         }
@@ -88,56 +125,65 @@ impl TextExtractor {
             name,
         })
     }
-}
 
-async fn fetch_json(texts: String) -> Result<Value, Box<dyn Error + Send + Sync>> {
-    let api_key = env::var("OPENAI_API_KEY")?;
-
-    // For testing environment, return mock data
-    if api_key == "test_key" {
-        return Ok(serde_json::json!({
-            "title": "Test Recipe",
-            "servings": "4",
-            "prep_time": "10 min",
-            "cook_time": "20 min",
-            "total_time": "30 min",
-            "ingredients": ["pasta", "sauce"],
-            "instructions": ["Cook pasta with sauce"],
-            "error": null
-        }));
+    async fn fetch_json(&self, text: &str) -> Result<Value, Box<dyn Error + Send + Sync>> {
+        // For testing environment, return mock data
+        if self.converter.name() == "open_ai"
+            && env::var("OPENAI_API_KEY").is_ok_and(|key| key == "test_key")
+        {
+            return Ok(serde_json::json!({
+                "title": "Test Recipe",
+                "servings": "4",
+                "prep_time": "10 min",
+                "cook_time": "20 min",
+                "total_time": "30 min",
+                "ingredients": ["pasta", "sauce"],
+                "instructions": ["Cook pasta with sauce"],
+                "error": null
+            }));
+        }
+
+        let prompt = format!("{}\n{}", PROMPT.trim(), text);
+        let response = self.converter.complete(&prompt).await?;
+        parse_json(&response.content)
     }
+}
 
-    let response = Client::new()
-        .post("https://api.openai.com/v1/chat/completions")
-        .header("Authorization", format!("Bearer {api_key}"))
-        .json(&serde_json::json!({
-            "model": MODEL,
-            "messages": [
-                { "role": "system", "content": PROMPT },
-                { "role": "user", "content": texts }
-            ]
-        }))
-        .send()
-        .await?
-        .json::<Value>()
-        .await?;
-
-    let content = response["choices"][0]["message"]["content"]
-        .as_str()
-        .ok_or("Failed to get response content")?;
-
-    serde_json::from_str(content).map_err(|e| e.into())
+/// The JSON object in a model's reply, which may be wrapped in a Markdown
+/// code fence or a sentence
+fn parse_json(reply: &str) -> Result<Value, Box<dyn Error + Send + Sync>> {
+    let start = reply.find('{');
+    let end = reply.rfind('}');
+    let json = match (start, end) {
+        (Some(start), Some(end)) if start < end => &reply[start..=end],
+        _ => reply,
+    };
+    serde_json::from_str(json).map_err(|e| {
+        format!(
+            "Text extraction reply is not JSON ({}): {}",
+            e,
+            reply.chars().take(200).collect::<String>()
+        )
+        .into()
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::converters::{OllamaConverter, OpenAiConverter};
+    use mockito::Server;
 
     #[tokio::test]
     async fn test_extract_returns_recipe_components() {
         std::env::set_var("OPENAI_API_KEY", "test_key");
+        let extractor = TextExtractor::new(Box::new(OpenAiConverter::with_base_url(
+            "test_key".to_string(),
+            "https://api.openai.com".to_string(),
+            "gpt-4o-mini".to_string(),
+        )));
 
-        let result = TextExtractor::extract("some recipe text", "test-source").await;
+        let result = extractor.extract("some recipe text", "test-source").await;
 
         assert!(result.is_ok());
         let components = result.unwrap();
@@ -145,23 +191,56 @@ mod tests {
         assert_eq!(components.name, "Test Recipe");
         assert!(components.metadata.contains("source: test-source"));
         assert!(components.metadata.contains("servings: '4'"));
-        assert!(components.metadata.contains("prep_time: 10 min"));
-        assert!(components.metadata.contains("cook_time: 20 min"));
-        assert!(components.metadata.contains("total_time: 30 min"));
+        assert!(components.metadata.contains("prep time: 10 min"));
+        assert!(components.metadata.contains("cook time: 20 min"));
+        assert!(components.metadata.contains("time required: 30 min"));
         assert!(components.text.contains("pasta"));
         assert!(components.text.contains("sauce"));
         assert!(components.text.contains("Cook pasta with sauce"));
     }
 
-    #[test]
-    fn test_is_available_without_key() {
-        std::env::remove_var("OPENAI_API_KEY");
-        assert!(!TextExtractor::is_available());
+    #[tokio::test]
+    async fn test_extract_with_ollama() {
+        let reply = "```json\n{\"title\": \"Pancakes\", \"servings\": \"2\", \
+                     \"ingredients\": [\"1 egg\"], \"instructions\": [\"Whisk.\"], \"error\": null}\n```";
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::Regex("Pancakes for two".to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                serde_json::json!({"choices": [{"message": {"content": reply}}]}).to_string(),
+            )
+            .create();
+
+        let extractor = TextExtractor::new(Box::new(OllamaConverter::with_base_url(
+            server.url(),
+            "llama3".to_string(),
+        )));
+        assert_eq!(extractor.provider(), "ollama");
+
+        let components = extractor
+            .extract("Pancakes for two: 1 egg, whisk.", "photo.jpg")
+            .await
+            .unwrap();
+        assert_eq!(components.name, "Pancakes");
+        assert!(components.metadata.contains("servings: '2'"));
+        assert_eq!(components.text, "1 egg\n\nWhisk.");
+        mock.assert();
     }
 
     #[test]
-    fn test_is_available_with_key() {
-        std::env::set_var("OPENAI_API_KEY", "test_key");
-        assert!(TextExtractor::is_available());
+    fn test_parse_json() {
+        assert_eq!(parse_json("{\"a\": 1}").unwrap()["a"], 1);
+        assert_eq!(
+            parse_json("Here you go:\n```json\n{\"a\": {\"b\": 2}}\n```").unwrap()["a"]["b"],
+            2
+        );
+        assert!(parse_json("Sorry, I can't help with that.").is_err());
+        // Cut after 200 characters, not inside the "é" at byte 200
+        let reply = format!("D{}", "é".repeat(300));
+        let error = parse_json(&reply).unwrap_err().to_string();
+        assert!(error.ends_with(&format!("D{}", "é".repeat(199))));
     }
 }
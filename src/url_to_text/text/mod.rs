@@ -1,3 +1,6 @@
+mod budget;
 mod extractor;
 
+pub(crate) use budget::max_input_tokens;
+pub use budget::{select_recipe_text, DEFAULT_MAX_INPUT_TOKENS};
 pub use extractor::TextExtractor;
@@ -0,0 +1,151 @@
+//! Archived copies of dead recipe links from the Internet Archive's Wayback
+//! Machine (`[fetch] wayback`)
+
+use crate::ImportError;
+use serde_json::Value;
+use std::error::Error;
+use std::time::Duration;
+use tracing::debug;
+
+/// The Wayback Machine's availability API
+pub const AVAILABILITY_API: &str = "https://archive.org/wayback/available";
+
+/// Whether a failed fetch means the page is gone: HTTP 404 or 410, or a
+/// host that can't be reached (usually a domain that no longer resolves)
+pub fn is_dead_link(error: &(dyn Error + Send + Sync + 'static)) -> bool {
+    if let Some(ImportError::HttpStatus { code, .. }) = error.downcast_ref::<ImportError>() {
+        return matches!(code, 404 | 410);
+    }
+    error
+        .downcast_ref::<reqwest::Error>()
+        .is_some_and(reqwest::Error::is_connect)
+}
+
+/// The latest snapshot of `url` at `api` (normally [`AVAILABILITY_API`]),
+/// or `None` when it was never archived
+pub async fn latest_snapshot(
+    api: &str,
+    url: &str,
+    timeout: Duration,
+    proxy: Option<&str>,
+) -> Result<Option<String>, Box<dyn Error + Send + Sync>> {
+    let response = crate::http::client(api, timeout, proxy)?
+        .get(api)
+        .query(&[("url", url)])
+        .send()
+        .await?
+        .error_for_status()?;
+    let json: Value = response.json().await?;
+    let closest = &json["archived_snapshots"]["closest"];
+    if closest["available"].as_bool() != Some(true) {
+        debug!("No Wayback Machine snapshot of {}", url);
+        return Ok(None);
+    }
+    Ok(closest["url"]
+        .as_str()
+        .map(|snapshot| snapshot.replacen("http://", "https://", 1)))
+}
+
+/// The snapshot's page as originally served, without the Wayback Machine's
+/// toolbar and rewritten links (the `id_` form of its timestamp)
+pub fn raw_snapshot_url(snapshot: &str) -> String {
+    let Some(start) = snapshot.find("/web/").map(|i| i + "/web/".len()) else {
+        return snapshot.to_string();
+    };
+    let timestamp_len = snapshot[start..]
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(snapshot.len() - start);
+    let end = start + timestamp_len;
+    if timestamp_len == 0 || !snapshot[end..].starts_with('/') {
+        return snapshot.to_string();
+    }
+    format!("{}id_{}", &snapshot[..end], &snapshot[end..])
+}
+
+/// Whether `url` is a Wayback Machine snapshot, whose source is the
+/// snapshot itself rather than the dead page's canonical URL
+pub fn is_snapshot(url: &str) -> bool {
+    url.split("//")
+        .nth(1)
+        .and_then(|rest| rest.split('/').next())
+        .is_some_and(|host| host == "web.archive.org")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server};
+
+    #[test]
+    fn test_raw_snapshot_url() {
+        assert_eq!(
+            raw_snapshot_url("https://web.archive.org/web/20190101120000/https://example.com/soup"),
+            "https://web.archive.org/web/20190101120000id_/https://example.com/soup"
+        );
+        assert_eq!(
+            raw_snapshot_url("https://web.archive.org/web/*/example.com"),
+            "https://web.archive.org/web/*/example.com"
+        );
+        assert!(is_snapshot(
+            "https://web.archive.org/web/20190101120000/https://example.com/soup"
+        ));
+        assert!(!is_snapshot("https://example.com/web.archive.org"));
+    }
+
+    #[test]
+    fn test_is_dead_link() {
+        let status = |code| -> Box<dyn Error + Send + Sync> {
+            Box::new(ImportError::HttpStatus {
+                code,
+                url: "https://example.com/soup".to_string(),
+            })
+        };
+        assert!(is_dead_link(status(404).as_ref()));
+        assert!(is_dead_link(status(410).as_ref()));
+        assert!(!is_dead_link(status(403).as_ref()));
+        assert!(!is_dead_link(status(500).as_ref()));
+    }
+
+    #[tokio::test]
+    async fn test_latest_snapshot() {
+        let mut server = Server::new_async().await;
+        let api = format!("{}/wayback/available", server.url());
+        let _archived = server
+            .mock("GET", "/wayback/available")
+            .match_query(Matcher::UrlEncoded(
+                "url".to_string(),
+                "https://example.com/soup".to_string(),
+            ))
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"url": "https://example.com/soup", "archived_snapshots": {"closest": {
+                    "status": "200", "available": true, "timestamp": "20190101120000",
+                    "url": "http://web.archive.org/web/20190101120000/https://example.com/soup"}}}"#,
+            )
+            .create();
+        let _missing = server
+            .mock("GET", "/wayback/available")
+            .match_query(Matcher::UrlEncoded(
+                "url".to_string(),
+                "https://example.com/new".to_string(),
+            ))
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"url": "https://example.com/new", "archived_snapshots": {}}"#)
+            .create();
+
+        let timeout = Duration::from_secs(5);
+        assert_eq!(
+            latest_snapshot(&api, "https://example.com/soup", timeout, None)
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("https://web.archive.org/web/20190101120000/https://example.com/soup")
+        );
+        assert_eq!(
+            latest_snapshot(&api, "https://example.com/new", timeout, None)
+                .await
+                .unwrap(),
+            None
+        );
+    }
+}
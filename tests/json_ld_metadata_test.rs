@@ -235,3 +235,102 @@ async fn test_nutrition_extraction() {
     assert!(result.metadata.contains("  sodium: 1.4 g"));
     assert!(result.metadata.contains("  serving size: 451"));
 }
+
+#[tokio::test]
+async fn test_serving_size_metadata() {
+    env::set_var("OPENAI_API_KEY", "test_key");
+
+    let mut server = mockito::Server::new_async().await;
+    let json_ld = r#"
+    {
+        "@context": "https://schema.org/",
+        "@type": "Recipe",
+        "name": "Lentil Soup",
+        "recipeYield": "4 servings (350 g each)",
+        "recipeIngredient": ["lentils", "water"],
+        "recipeInstructions": "Simmer the lentils in water"
+    }
+    "#;
+
+    let _m = server
+        .mock("GET", "/recipe")
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body(create_recipe_html_with_metadata(json_ld))
+        .create();
+
+    let url = format!("{}/recipe", server.url());
+    let result = url_to_recipe(&url).await.unwrap();
+
+    assert!(result.metadata.contains("servings: 4 servings"));
+    assert!(result.metadata.contains("serving size: 350 g"));
+}
+
+#[tokio::test]
+async fn test_rating_metadata() {
+    env::set_var("OPENAI_API_KEY", "test_key");
+
+    let mut server = mockito::Server::new_async().await;
+    let json_ld = r#"
+    {
+        "@context": "https://schema.org/",
+        "@type": "Recipe",
+        "name": "Lentil Soup",
+        "aggregateRating": {
+            "@type": "AggregateRating",
+            "ratingValue": "4.71",
+            "ratingCount": 1234,
+            "bestRating": "5"
+        },
+        "recipeIngredient": ["lentils", "water"],
+        "recipeInstructions": "Simmer the lentils in water"
+    }
+    "#;
+
+    let _m = server
+        .mock("GET", "/recipe")
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body(create_recipe_html_with_metadata(json_ld))
+        .create();
+
+    let url = format!("{}/recipe", server.url());
+    let result = url_to_recipe(&url).await.unwrap();
+
+    assert!(result.metadata.contains("rating: 4.7 (1234 reviews)"));
+}
+
+#[tokio::test]
+async fn test_video_metadata() {
+    env::set_var("OPENAI_API_KEY", "test_key");
+
+    let mut server = mockito::Server::new_async().await;
+    let json_ld = r#"
+    {
+        "@context": "https://schema.org/",
+        "@type": "Recipe",
+        "name": "Lentil Soup",
+        "video": {
+            "@type": "VideoObject",
+            "name": "Lentil Soup",
+            "embedUrl": "https://www.youtube.com/embed/abc123"
+        },
+        "recipeIngredient": ["lentils", "water"],
+        "recipeInstructions": "Simmer the lentils in water"
+    }
+    "#;
+
+    let _m = server
+        .mock("GET", "/recipe")
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body(create_recipe_html_with_metadata(json_ld))
+        .create();
+
+    let url = format!("{}/recipe", server.url());
+    let result = url_to_recipe(&url).await.unwrap();
+
+    assert!(result
+        .metadata
+        .contains("video: https://www.youtube.com/embed/abc123"));
+}
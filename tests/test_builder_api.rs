@@ -1,5 +1,6 @@
 use cooklang_import::{
-    text_to_cooklang, url_to_recipe, ImportResult, RecipeComponents, RecipeImporter,
+    provenance::Provenance, text_to_cooklang, url_to_recipe, ErrorKind, ImportError, ImportResult,
+    LlmProvider, RecipeComponents, RecipeImporter,
 };
 
 /// Test Use Case 1: URL → Cooklang with builder API
@@ -113,6 +114,39 @@ async fn test_builder_text_to_cooklang() {
     }
 }
 
+/// Text → Cooklang end to end, with the mock provider echoing the recipe
+#[cfg(feature = "mock")]
+#[tokio::test]
+async fn test_builder_text_to_cooklang_with_mock_provider() {
+    let recipe_text = "2 eggs\n1 cup flour\n\nMix and bake for 30 minutes.";
+
+    let result = RecipeImporter::builder()
+        .text(recipe_text)
+        .provider(LlmProvider::Mock)
+        .check_ingredients()
+        .no_cache()
+        .offline()
+        .build()
+        .await
+        .unwrap();
+
+    match result {
+        ImportResult::Cooklang {
+            content,
+            conversion_metadata,
+            ..
+        } => {
+            assert!(content.ends_with("Mix and bake for 30 minutes."));
+            let metadata = conversion_metadata.unwrap();
+            assert_eq!(metadata.model_version.as_deref(), Some("mock"));
+            // Nothing was marked up
+            assert_eq!(metadata.confidence, Some(0.0));
+            assert_eq!(metadata.ingredient_check.unwrap().missing.len(), 2);
+        }
+        ImportResult::Components(_) => panic!("Expected Cooklang result"),
+    }
+}
+
 /// Test convenience function: text_to_cooklang
 /// This test is ignored by default since it requires OpenAI API key
 #[tokio::test]
@@ -160,27 +194,211 @@ async fn test_builder_text_extract_only_error() {
 }
 
 /// Test builder validation: empty text
-/// NOTE: In the new architecture, empty text validation is not implemented yet
 #[tokio::test]
-#[ignore] // Validation not implemented in new architecture yet
 async fn test_builder_empty_text_error_duplicate() {
-    let result = RecipeImporter::builder().text("").build().await;
+    let result = RecipeImporter::builder().text("  \n").build().await;
 
-    // In new architecture, empty text currently succeeds (returns empty result)
-    // This may need validation added later
-    assert!(result.is_ok());
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("The recipe text is empty"));
 }
 
 /// Test builder validation: original empty text test
-/// NOTE: In the new architecture, empty text validation is not implemented yet
 #[tokio::test]
-#[ignore] // Validation not implemented in new architecture yet
 async fn test_builder_empty_text_error() {
     let result = RecipeImporter::builder().text("").build().await;
 
-    // In new architecture, empty text currently succeeds (returns empty result)
-    // This may need validation added later
-    assert!(result.is_ok());
+    assert!(matches!(result, Err(ImportError::BuilderError(_))));
+}
+
+/// Test builder validation: URLs that aren't http(s) pages are rejected
+/// before anything is fetched
+#[tokio::test]
+async fn test_builder_invalid_url_error() {
+    for url in [
+        "ftp://example.com/recipe",
+        "not a url",
+        "https://example.com/cake.jpg",
+        "https://example.com/cookbook.pdf",
+    ] {
+        let result = RecipeImporter::builder().url(url).dry_run().await;
+        assert!(
+            matches!(result, Err(ImportError::BuilderError(_))),
+            "{} was accepted",
+            url
+        );
+    }
+}
+
+/// The source is the page's canonical URL, without tracking parameters
+#[tokio::test]
+async fn test_builder_source_is_canonical() {
+    let mut server = mockito::Server::new_async().await;
+    let _mock = server
+        .mock("GET", "/print/soup")
+        .match_query(mockito::Matcher::Any)
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body(
+            r#"<html><head><link rel="canonical" href="/recipes/soup?utm_source=feed">
+            <script type="application/ld+json">
+            {"@type": "Recipe", "name": "Soup",
+             "recipeIngredient": ["1 l stock"],
+             "recipeInstructions": [{"@type": "HowToStep", "text": "Heat the stock."}]}
+            </script></head><body></body></html>"#,
+        )
+        .create_async()
+        .await;
+
+    let result = RecipeImporter::builder()
+        .url(format!("{}/print/soup?utm_source=pinterest", server.url()))
+        .extract_only()
+        .no_cache()
+        .build()
+        .await
+        .unwrap();
+
+    let json = serde_json::to_value(&result).unwrap();
+    assert_eq!(json["source"], format!("{}/recipes/soup", server.url()));
+}
+
+/// Redirects are followed and an AMP page is swapped for its canonical page
+#[tokio::test]
+async fn test_builder_follows_redirects_and_amp() {
+    let mut server = mockito::Server::new_async().await;
+    let _short = server
+        .mock("GET", "/s/abc")
+        .with_status(301)
+        .with_header("location", "/recipes/soup/amp")
+        .create_async()
+        .await;
+    let _amp = server
+        .mock("GET", "/recipes/soup/amp")
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body(
+            r#"<html amp><head><link rel="canonical" href="/recipes/soup"></head>
+            <body>Soup, abridged</body></html>"#,
+        )
+        .create_async()
+        .await;
+    let _canonical = server
+        .mock("GET", "/recipes/soup")
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body(
+            r#"<html><head><script type="application/ld+json">
+            {"@type": "Recipe", "name": "Soup",
+             "recipeIngredient": ["1 l stock"],
+             "recipeInstructions": [{"@type": "HowToStep", "text": "Heat the stock."}]}
+            </script></head><body></body></html>"#,
+        )
+        .create_async()
+        .await;
+
+    let report = RecipeImporter::builder()
+        .url(format!("{}/s/abc", server.url()))
+        .extract_only()
+        .no_cache()
+        .dry_run()
+        .await
+        .unwrap();
+
+    let canonical = format!("{}/recipes/soup", server.url());
+    assert_eq!(report.url.as_deref(), Some(canonical.as_str()));
+    assert_eq!(report.extractor.as_deref(), Some("json-ld"));
+    assert!(report
+        .components
+        .metadata
+        .contains(&format!("source: {}", canonical)));
+}
+
+/// Pages in a legacy charset are decoded before extraction
+#[tokio::test]
+async fn test_builder_decodes_latin1_page() {
+    let mut server = mockito::Server::new_async().await;
+    let page = r#"<html><head><script type="application/ld+json">
+        {"@type": "Recipe", "name": "Tarte",
+         "recipeIngredient": ["200 g crème fraîche"],
+         "recipeInstructions": [{"@type": "HowToStep", "text": "Mélanger."}]}
+        </script></head><body></body></html>"#;
+    let latin1: Vec<u8> = page.chars().map(|c| c as u8).collect();
+    let _mock = server
+        .mock("GET", "/tarte")
+        .with_status(200)
+        .with_header("content-type", "text/html; charset=ISO-8859-1")
+        .with_body(latin1)
+        .create_async()
+        .await;
+
+    let result = RecipeImporter::builder()
+        .url(format!("{}/tarte", server.url()))
+        .extract_only()
+        .no_cache()
+        .build()
+        .await
+        .unwrap();
+
+    let ImportResult::Components(components) = result else {
+        panic!("Expected components");
+    };
+    assert!(components.text.contains("200 g crème fraîche"));
+    assert!(components.text.contains("Mélanger."));
+}
+
+/// multi() returns every recipe on a page, each with the page as its source
+#[tokio::test]
+async fn test_builder_multi_recipe_page() {
+    let mut server = mockito::Server::new_async().await;
+    let _mock = server
+        .mock("GET", "/curry-night")
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body(
+            r#"<html><head><script type="application/ld+json">
+            [{"@type": "Recipe", "name": "Dal",
+              "recipeIngredient": ["200 g red lentils"],
+              "recipeInstructions": "Simmer the lentils."},
+             {"@type": "Recipe", "name": "Rice",
+              "recipeIngredient": ["300 g basmati"],
+              "recipeInstructions": "Steam the rice."}]
+            </script></head><body></body></html>"#,
+        )
+        .create_async()
+        .await;
+    let url = format!("{}/curry-night", server.url());
+
+    let results = RecipeImporter::builder()
+        .url(&url)
+        .extract_only()
+        .no_cache()
+        .multi()
+        .build_all()
+        .await
+        .unwrap();
+
+    let names: Vec<String> = results
+        .iter()
+        .map(|result| match result {
+            ImportResult::Components(components) => {
+                assert!(components.metadata.contains(&format!("source: {}", url)));
+                components.name.clone()
+            }
+            ImportResult::Cooklang { .. } => panic!("Expected components"),
+        })
+        .collect();
+    assert_eq!(names, ["Dal", "Rice"]);
+
+    // build() returns a single recipe, so it refuses multi()
+    let result = RecipeImporter::builder()
+        .url(&url)
+        .extract_only()
+        .no_cache()
+        .multi()
+        .build()
+        .await;
+    assert!(matches!(result, Err(ImportError::BuilderError(_))));
 }
 
 /// Test builder method chaining
@@ -194,7 +412,8 @@ async fn test_builder_method_chaining() {
     let builder = RecipeImporter::builder()
         .url("https://example.com/recipe")
         .provider(LlmProvider::OpenAI)
-        .timeout(Duration::from_secs(30));
+        .timeout(Duration::from_secs(30))
+        .llm_timeout(Duration::from_secs(90));
 
     // We can't actually execute this without network access,
     // but we can verify the builder is constructed correctly
@@ -261,3 +480,782 @@ async fn test_builder_with_timeout_extract_only() {
         ImportResult::Cooklang { .. } => panic!("Expected Components result"),
     }
 }
+
+/// Dry runs fetch and extract but never call the LLM (no provider mock exists)
+#[tokio::test]
+async fn test_builder_dry_run_reports_extractor() {
+    use cooklang_import::LlmProvider;
+
+    let mut server = mockito::Server::new_async().await;
+    let _mock = server
+        .mock("GET", "/recipe")
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body(
+            r#"<html><head><script type="application/ld+json">
+            {"@type": "Recipe", "name": "Fried Eggs", "recipeYield": "2",
+             "recipeIngredient": ["2 eggs", "1 tbsp butter"],
+             "recipeInstructions": [{"@type": "HowToStep", "text": "Fry the eggs in butter."}]}
+            </script></head><body></body></html>"#,
+        )
+        .create_async()
+        .await;
+
+    let report = RecipeImporter::builder()
+        .url(format!("{}/recipe", server.url()))
+        .provider(LlmProvider::Ollama)
+        .model("llama3")
+        .dry_run()
+        .await
+        .unwrap();
+
+    assert_eq!(report.extractor.as_deref(), Some("json-ld"));
+    assert_eq!(report.components.name, "Fried Eggs");
+    assert!(report.components.text.contains("2 eggs"));
+    assert_eq!(report.providers, vec!["ollama".to_string()]);
+    assert_eq!(report.model, "llama3");
+    assert!(report.estimated_input_tokens > 0);
+    assert_eq!(report.estimated_cost_usd, Some(0.0));
+}
+
+#[tokio::test]
+async fn test_builder_dry_run_without_structured_data() {
+    let mut server = mockito::Server::new_async().await;
+    let _mock = server
+        .mock("GET", "/post")
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body("<html><body><p>Whisk 2 eggs and fry them.</p></body></html>")
+        .create_async()
+        .await;
+
+    let report = RecipeImporter::builder()
+        .url(format!("{}/post", server.url()))
+        .extract_only()
+        .dry_run()
+        .await
+        .unwrap();
+
+    assert_eq!(report.extractor, None);
+    assert!(report.components.text.contains("Whisk 2 eggs"));
+    assert!(report.providers.is_empty());
+}
+
+#[tokio::test]
+async fn test_builder_dry_run_rejects_images() {
+    let result = RecipeImporter::builder()
+        .image_path("/path/to/recipe.jpg")
+        .dry_run()
+        .await;
+
+    assert!(matches!(result, Err(ImportError::BuilderError(_))));
+}
+
+#[tokio::test]
+async fn test_builder_http_error_status() {
+    let mut server = mockito::Server::new_async().await;
+    let _mock = server
+        .mock("GET", "/gone")
+        .with_status(404)
+        .create_async()
+        .await;
+
+    let url = format!("{}/gone", server.url());
+    let result = RecipeImporter::builder()
+        .url(&url)
+        .extract_only()
+        .no_cache()
+        .build()
+        .await;
+    assert!(
+        matches!(&result, Err(ImportError::HttpStatus { code: 404, url: failed }) if *failed == url),
+        "{:?}",
+        result
+    );
+    assert_eq!(result.unwrap_err().kind(), ErrorKind::Fetch);
+}
+
+#[tokio::test]
+async fn test_builder_error_kinds() {
+    // Nothing listens on the discard port
+    let result = RecipeImporter::builder()
+        .url("http://127.0.0.1:9/recipe")
+        .extract_only()
+        .no_cache()
+        .build()
+        .await;
+    assert!(
+        matches!(result, Err(ImportError::FetchError(_))),
+        "{:?}",
+        result
+    );
+
+    let result = RecipeImporter::builder()
+        .text("2 eggs\n\nFry the eggs.")
+        .scale(-1.0)
+        .build()
+        .await;
+    assert_eq!(result.unwrap_err().kind(), ErrorKind::Input);
+
+    let result = RecipeImporter::builder()
+        .text("2 eggs\n\nFry the eggs.")
+        // Azure needs an endpoint and deployment from config.toml
+        .provider(LlmProvider::AzureOpenAI)
+        .api_key("test-key")
+        .no_cache()
+        .build()
+        .await;
+    assert_eq!(result.unwrap_err().kind(), ErrorKind::Config);
+}
+
+#[tokio::test]
+async fn test_builder_no_recipe_returns_diagnostics() {
+    let mut server = mockito::Server::new_async().await;
+    let _mock = server
+        .mock("GET", "/about")
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body(
+            r#"<html><head><title>About us</title>
+            <script type="application/ld+json">{"@type": "Organization"}</script></head>
+            <body><p>We love food.</p></body></html>"#,
+        )
+        .create_async()
+        .await;
+
+    let result = RecipeImporter::builder()
+        .url(format!("{}/about", server.url()))
+        .extract_only()
+        .build()
+        .await;
+
+    let Err(ImportError::NoExtractorMatched(diagnostics)) = result else {
+        panic!("Expected NoExtractorMatched, got {:?}", result);
+    };
+    assert_eq!(ErrorKind::NoRecipe.as_str(), "no_recipe");
+    assert_eq!(diagnostics.page_title.as_deref(), Some("About us"));
+    assert_eq!(diagnostics.json_ld_scripts, 1);
+    assert!(!diagnostics.json_ld_recipe);
+    let extractors: Vec<&str> = diagnostics
+        .extractor_errors
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect();
+    assert_eq!(extractors, vec!["json-ld", "microdata", "html-class"]);
+    assert!(diagnostics.llm_error.is_some());
+}
+
+#[tokio::test]
+async fn test_builder_scale_validation() {
+    let result = RecipeImporter::builder()
+        .text("2 eggs\n\nFry the eggs.")
+        .scale(0.0)
+        .build()
+        .await;
+    assert!(matches!(result, Err(ImportError::BuilderError(_))));
+
+    let result = RecipeImporter::builder()
+        .text("2 eggs\n\nFry the eggs.")
+        .extract_only()
+        .scale(2.0)
+        .build()
+        .await;
+    assert!(matches!(result, Err(ImportError::BuilderError(_))));
+}
+
+#[tokio::test]
+async fn test_builder_target_language_validation() {
+    let result = RecipeImporter::builder()
+        .text("2 Eier\n\nDie Eier braten.")
+        .extract_only()
+        .target_language("en")
+        .build()
+        .await;
+    assert!(matches!(result, Err(ImportError::BuilderError(_))));
+
+    let result = RecipeImporter::builder()
+        .text("2 Eier\n\nDie Eier braten.")
+        .target_language(" ")
+        .build()
+        .await;
+    assert!(matches!(result, Err(ImportError::BuilderError(_))));
+}
+
+#[tokio::test]
+async fn test_builder_offline_refuses_network() {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server.mock("GET", "/recipe").expect(0).create_async().await;
+
+    let result = RecipeImporter::builder()
+        .url(format!("{}/recipe", server.url()))
+        .extract_only()
+        .offline()
+        .build()
+        .await;
+    assert!(matches!(result, Err(ImportError::OfflineError(_))));
+    mock.assert_async().await;
+
+    let result = RecipeImporter::builder()
+        .text("2 eggs\n\nFry the eggs.")
+        .provider(LlmProvider::OpenAI)
+        .no_cache()
+        .offline()
+        .build()
+        .await;
+    assert!(matches!(result, Err(ImportError::OfflineError(_))));
+
+    // Without a provider asked for by name, the recipe is converted by rules
+    let result = RecipeImporter::builder()
+        .text("2 eggs\n1 tbsp butter\n\nFry the eggs in the butter.")
+        .no_cache()
+        .offline()
+        .build()
+        .await;
+    match result {
+        Ok(ImportResult::Cooklang { content, .. }) => {
+            assert_eq!(content, "Fry the @eggs{2} in the @butter{1%tbsp}.")
+        }
+        other => panic!("expected Cooklang, got {:?}", other),
+    }
+
+    // Local input that needs no LLM is fine
+    let result = RecipeImporter::builder()
+        .text("2 eggs\n\nFry the eggs.")
+        .extract_only()
+        .offline()
+        .build()
+        .await;
+    assert!(matches!(result, Ok(ImportResult::Components(_))));
+
+    // Text extraction runs on the conversion provider: refused with a cloud
+    // provider, attempted with a local Ollama server
+    let result = RecipeImporter::builder()
+        .text_with_extraction("Fry two eggs in butter.")
+        .provider(LlmProvider::OpenAI)
+        .extract_only()
+        .offline()
+        .build()
+        .await;
+    assert!(matches!(result, Err(ImportError::OfflineError(_))));
+
+    let result = RecipeImporter::builder()
+        .text_with_extraction("Fry two eggs in butter.")
+        .provider(LlmProvider::Ollama)
+        .model("llama3")
+        .extract_only()
+        .offline()
+        .build()
+        .await;
+    assert!(!matches!(result, Err(ImportError::OfflineError(_))));
+}
+
+#[tokio::test]
+async fn test_builder_audio_source() {
+    let result = RecipeImporter::builder().audio_path(" ").build().await;
+    assert!(matches!(result, Err(ImportError::BuilderError(msg)) if msg.contains("audio")));
+
+    // The Whisper API needs the network
+    let result = RecipeImporter::builder()
+        .audio_path("memo.m4a")
+        .extract_only()
+        .offline()
+        .build()
+        .await;
+    assert!(matches!(result, Err(ImportError::OfflineError(msg)) if msg.contains("transcription")));
+
+    let result = RecipeImporter::builder()
+        .audio_path("memo.m4a")
+        .dry_run()
+        .await;
+    assert!(matches!(result, Err(ImportError::BuilderError(_))));
+}
+
+#[tokio::test]
+async fn test_builder_dropped_fields() {
+    let mut server = mockito::Server::new_async().await;
+    let _mock = server
+        .mock("GET", "/recipe")
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body(
+            r#"<html><head><script type="application/ld+json">
+            {"@type": "Recipe", "name": "Fried Eggs", "datePublished": "2024-01-05",
+             "recipeIngredient": ["2 eggs"],
+             "recipeInstructions": [{"@type": "HowToStep", "text": "Fry the eggs."}]}
+            </script></head><body></body></html>"#,
+        )
+        .create_async()
+        .await;
+    let url = format!("{}/recipe", server.url());
+
+    let report = RecipeImporter::builder()
+        .url(&url)
+        .extract_only()
+        .no_cache()
+        .dry_run()
+        .await
+        .unwrap();
+    assert_eq!(report.dropped_fields.len(), 1);
+    assert_eq!(report.dropped_fields[0].key, "datePublished");
+    assert!(!report.components.metadata.contains("x-datePublished"));
+
+    let result = RecipeImporter::builder()
+        .url(&url)
+        .extract_only()
+        .no_cache()
+        .keep_unknown_fields()
+        .build()
+        .await
+        .unwrap();
+    match result {
+        ImportResult::Components(components) => {
+            assert!(components.metadata.contains("x-datePublished: 2024-01-05"));
+        }
+        ImportResult::Cooklang { .. } => panic!("Expected Components result"),
+    }
+}
+
+#[tokio::test]
+async fn test_builder_email_newsletter() {
+    let html = r#"<html><head><script type="application/ld+json">
+        {"@type": "Recipe", "name": "Lemon Bars",
+         "recipeIngredient": ["1 cup flour", "2 lemons"],
+         "recipeInstructions": "Bake the crust, then the filling."}
+    </script></head><body><p>This week's recipe</p></body></html>"#;
+    let eml = format!(
+        "From: Sunday Baking <news@example.com>\r\nSubject: Fwd: This week: lemon bars\r\nContent-Type: multipart/alternative; boundary=\"sep\"\r\n\r\n--sep\r\nContent-Type: text/plain\r\n\r\nLemon bars!\r\n--sep\r\nContent-Type: text/html; charset=utf-8\r\n\r\n{}\r\n--sep--\r\n",
+        html
+    );
+
+    let result = RecipeImporter::builder()
+        .email(eml)
+        .extract_only()
+        .offline()
+        .build()
+        .await
+        .unwrap();
+    match result {
+        ImportResult::Components(components) => {
+            assert_eq!(components.name, "Lemon Bars");
+            assert_eq!(
+                components.text,
+                "1 cup flour\n2 lemons\n\nBake the crust, then the filling."
+            );
+            assert!(components
+                .metadata
+                .contains("source: Sunday Baking <news@example.com>"));
+        }
+        ImportResult::Cooklang { .. } => panic!("expected components"),
+    }
+
+    let result = RecipeImporter::builder()
+        .email("<html>not an email</html>")
+        .extract_only()
+        .build()
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_builder_saved_page() {
+    let html = r#"<!DOCTYPE html>
+<!-- saved from url=(0032)https://example.com/fried-eggs?a -->
+<html><head><script type="application/ld+json">
+    {"@type": "Recipe", "name": "Fried Eggs",
+     "recipeIngredient": ["2 eggs", "1 tbsp butter"],
+     "recipeInstructions": "Fry the eggs in the butter."}
+</script></head><body><p>Fried eggs</p></body></html>"#;
+
+    // Nothing is fetched, so offline is fine; with no model the recipe is
+    // converted by rules
+    let result = RecipeImporter::builder()
+        .html(html)
+        .no_cache()
+        .offline()
+        .build()
+        .await
+        .unwrap();
+    match result {
+        ImportResult::Cooklang { content, .. } => {
+            assert!(content.contains("source: https://example.com/fried-eggs?a"));
+            assert!(content.ends_with("Fry the @eggs{2} in the @butter{1%tbsp}."));
+        }
+        ImportResult::Components(_) => panic!("expected Cooklang"),
+    }
+
+    let mhtml = format!(
+        "From: <Saved by Blink>\r\nSnapshot-Content-Location: https://example.com/eggs\r\nSubject: Fried Eggs\r\nMIME-Version: 1.0\r\nContent-Type: multipart/related; type=\"text/html\"; boundary=\"----b\"\r\n\r\n------b\r\nContent-Type: text/html\r\nContent-Location: https://example.com/eggs\r\n\r\n{}\r\n------b--\r\n",
+        html
+    );
+    let result = RecipeImporter::builder()
+        .mhtml(mhtml.clone())
+        .extract_only()
+        .offline()
+        .build()
+        .await
+        .unwrap();
+    match result {
+        ImportResult::Components(components) => {
+            assert_eq!(components.name, "Fried Eggs");
+            assert!(components
+                .metadata
+                .contains("source: https://example.com/eggs"));
+        }
+        ImportResult::Cooklang { .. } => panic!("expected components"),
+    }
+
+    // The URL given wins over the one in the page
+    let result = RecipeImporter::builder()
+        .mhtml(mhtml)
+        .source_url("https://example.org/eggs")
+        .extract_only()
+        .offline()
+        .build()
+        .await
+        .unwrap();
+    match result {
+        ImportResult::Components(components) => assert!(components
+            .metadata
+            .contains("source: https://example.org/eggs")),
+        ImportResult::Cooklang { .. } => panic!("expected components"),
+    }
+
+    let result = RecipeImporter::builder()
+        .html(html)
+        .source_url("file:///tmp/eggs.html")
+        .extract_only()
+        .build()
+        .await;
+    assert!(matches!(result, Err(ImportError::BuilderError(_))));
+}
+
+#[tokio::test]
+async fn test_builder_markdown() {
+    let markdown = "# Fried Eggs\n\nServes: 2\n\n## Ingredients\n- 2 eggs\n- 1 tbsp butter\n\n## Method\n1. Melt the butter in a pan.\n2. Fry the eggs.\n";
+
+    let result = RecipeImporter::builder()
+        .markdown(markdown)
+        .extract_only()
+        .offline()
+        .build()
+        .await
+        .unwrap();
+    match result {
+        ImportResult::Components(components) => {
+            assert_eq!(components.name, "Fried Eggs");
+            assert_eq!(
+                components.text,
+                "2 eggs\n1 tbsp butter\n\nMelt the butter in a pan.\n\nFry the eggs."
+            );
+            assert!(components.metadata.contains("servings: '2'"));
+        }
+        ImportResult::Cooklang { .. } => panic!("expected components"),
+    }
+
+    // Offline, the Markdown is converted without an LLM
+    let result = RecipeImporter::builder()
+        .markdown(markdown)
+        .provider(LlmProvider::OpenAI)
+        .no_cache()
+        .offline()
+        .build()
+        .await
+        .unwrap();
+    match result {
+        ImportResult::Cooklang { content, title, .. } => {
+            assert_eq!(title, "Fried Eggs");
+            assert!(
+                content.ends_with("Melt the @butter{1%tbsp} in a pan.\n\nFry the @eggs{2}."),
+                "{}",
+                content
+            );
+        }
+        ImportResult::Components(_) => panic!("expected Cooklang"),
+    }
+
+    // Without the headings it needs LLM text extraction
+    let result = RecipeImporter::builder()
+        .markdown("Fry two eggs in butter.")
+        .extract_only()
+        .offline()
+        .build()
+        .await;
+    assert!(matches!(result, Err(ImportError::OfflineError(_))));
+}
+
+#[tokio::test]
+async fn test_builder_ingredient_aliases() {
+    let path = std::env::temp_dir().join(format!("cooklang-aliases-{}.conf", std::process::id()));
+    std::fs::write(&path, "[produce]\ngreen onions|scallions|spring onions\n").unwrap();
+    let markdown = "# Noodles\n\n## Ingredients\n- 4 scallions\n- 200 g noodles\n\n\
+        ## Method\n1. Boil the noodles.\n2. Top with the scallions.\n";
+
+    let result = RecipeImporter::builder()
+        .markdown(markdown)
+        .ingredient_aliases(path.to_str().unwrap())
+        .no_cache()
+        .offline()
+        .build()
+        .await
+        .unwrap();
+    let ImportResult::Cooklang { content, .. } = result else {
+        panic!("expected Cooklang");
+    };
+    assert!(
+        content.ends_with("Boil the @noodles{200%g}.\n\nTop with the @green onions|scallions{4}."),
+        "{}",
+        content
+    );
+    std::fs::remove_file(&path).unwrap();
+
+    let result = RecipeImporter::builder()
+        .markdown(markdown)
+        .ingredient_aliases(path.to_str().unwrap())
+        .offline()
+        .build()
+        .await;
+    assert_eq!(result.unwrap_err().kind(), ErrorKind::Config);
+}
+
+#[tokio::test]
+async fn test_builder_pantry() {
+    let markdown =
+        "# Pasta\n\n## Ingredients\n- 200 g pasta\n- 1 tbsp olive oil\n- 1 bell pepper\n\n\
+        ## Method\n1. Boil the pasta.\n2. Fry the bell pepper in the olive oil.\n";
+
+    let result = RecipeImporter::builder()
+        .markdown(markdown)
+        .pantry(["olive oil", "pepper"])
+        .no_cache()
+        .offline()
+        .build()
+        .await
+        .unwrap();
+    let ImportResult::Cooklang { content, .. } = result else {
+        panic!("expected Cooklang");
+    };
+    assert!(
+        content.contains("@bell pepper{1} in the @olive oil{1%tbsp}(pantry)."),
+        "{}",
+        content
+    );
+}
+
+/// sub_recipes() splits a component recipe out and references it
+#[tokio::test]
+async fn test_builder_sub_recipes() {
+    let markdown = "# Pizza\n\n## Ingredients\n- 1 ball mozzarella\n### Dough\n- 500 g flour\n\n\
+                    ## Method\n### Dough\n1. Knead the flour with water.\n### Bake\n1. Top with mozzarella and bake.\n";
+
+    let results = RecipeImporter::builder()
+        .markdown(markdown)
+        .extract_only()
+        .offline()
+        .sub_recipes()
+        .build_all()
+        .await
+        .unwrap();
+    let components: Vec<RecipeComponents> = results
+        .into_iter()
+        .map(|result| match result {
+            ImportResult::Components(components) => components,
+            ImportResult::Cooklang { .. } => panic!("expected components"),
+        })
+        .collect();
+    assert_eq!(components.len(), 2);
+    assert_eq!(components[0].name, "Pizza");
+    assert_eq!(
+        components[0].text,
+        "1 ball mozzarella\n\nMake the @./Pizza Dough{}.\n\nBake:\n\nTop with mozzarella and bake."
+    );
+    assert_eq!(components[1].name, "Pizza Dough");
+    assert_eq!(
+        components[1].text,
+        "500 g flour\n\nKnead the flour with water."
+    );
+}
+
+#[tokio::test]
+async fn test_import_result_json() {
+    let markdown = "---\nsource: https://example.com/eggs\nimage: https://example.com/eggs.jpg\n---\n\n# Fried Eggs\n\nServes: 2\n\n## Ingredients\n- 2 eggs\n\n## Method\n1. Fry the eggs.\n";
+
+    let result = RecipeImporter::builder()
+        .markdown(markdown)
+        .offline()
+        .build()
+        .await
+        .unwrap();
+    let json = serde_json::to_value(&result).unwrap();
+    assert_eq!(json["name"], "Fried Eggs");
+    assert!(json["cooklang"]
+        .as_str()
+        .unwrap()
+        .ends_with("Fry the @eggs{2}."));
+    assert_eq!(json["text"], serde_json::Value::Null);
+    assert_eq!(json["metadata"]["servings"], "2");
+    assert_eq!(json["metadata"]["title"], serde_json::Value::Null);
+    assert_eq!(json["source"], "https://example.com/eggs");
+    assert_eq!(
+        json["images"],
+        serde_json::json!(["https://example.com/eggs.jpg"])
+    );
+    // No LLM was used
+    assert_eq!(json["conversion"], serde_json::Value::Null);
+
+    let result = RecipeImporter::builder()
+        .markdown(markdown)
+        .extract_only()
+        .offline()
+        .build()
+        .await
+        .unwrap();
+    let json = serde_json::to_value(&result).unwrap();
+    assert_eq!(json["cooklang"], serde_json::Value::Null);
+    assert_eq!(json["text"], "2 eggs\n\nFry the eggs.");
+    assert_eq!(json["conversion"], serde_json::Value::Null);
+    assert_eq!(
+        result.image_url().as_deref(),
+        Some("https://example.com/eggs.jpg")
+    );
+}
+
+#[tokio::test]
+async fn test_build_with_extractor() {
+    let mut server = mockito::Server::new_async().await;
+    let _mock = server
+        .mock("GET", "/recipe")
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body(
+            r#"<html><head><script type="application/ld+json">
+            {"@type": "Recipe", "name": "Fried Eggs",
+             "recipeIngredient": ["2 eggs"],
+             "recipeInstructions": [{"@type": "HowToStep", "text": "Fry the eggs."}]}
+            </script></head><body></body></html>"#,
+        )
+        .create_async()
+        .await;
+
+    let (result, extractor) = RecipeImporter::builder()
+        .url(format!("{}/recipe", server.url()))
+        .extract_only()
+        .no_cache()
+        .build_with_extractor()
+        .await
+        .unwrap();
+    assert_eq!(extractor.as_deref(), Some("json-ld"));
+    assert!(matches!(result, ImportResult::Components(c) if c.name == "Fried Eggs"));
+
+    let (_, extractor) = RecipeImporter::builder()
+        .text("2 eggs\n\nFry the eggs.")
+        .extract_only()
+        .build_with_extractor()
+        .await
+        .unwrap();
+    assert_eq!(extractor.as_deref(), Some("text"));
+}
+
+#[tokio::test]
+async fn test_builder_provenance() {
+    let mut server = mockito::Server::new_async().await;
+    let _mock = server
+        .mock("GET", "/recipe")
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body(
+            r#"<html><head><script type="application/ld+json">
+            {"@type": "Recipe", "name": "Fried Eggs",
+             "recipeIngredient": ["2 eggs"],
+             "recipeInstructions": [{"@type": "HowToStep", "text": "Fry the eggs."}]}
+            </script></head><body></body></html>"#,
+        )
+        .create_async()
+        .await;
+    let url = format!("{}/recipe", server.url());
+
+    let result = RecipeImporter::builder()
+        .url(&url)
+        .extract_only()
+        .provenance()
+        .no_cache()
+        .build()
+        .await
+        .unwrap();
+    let ImportResult::Components(components) = result else {
+        panic!("expected components");
+    };
+    let yaml = format!("---\n{}---\n", components.metadata);
+    let provenance = Provenance::from_cooklang(&yaml).unwrap();
+    assert_eq!(provenance.source.as_deref(), Some(url.as_str()));
+    assert_eq!(provenance.extractor.as_deref(), Some("json-ld"));
+    assert_eq!(provenance.provider, None);
+    assert!(provenance.date.is_some_and(|date| date.ends_with('Z')));
+
+    // Without provenance() the block is left out
+    let result = RecipeImporter::builder()
+        .url(&url)
+        .extract_only()
+        .no_cache()
+        .build()
+        .await
+        .unwrap();
+    let ImportResult::Components(components) = result else {
+        panic!("expected components");
+    };
+    assert!(!components.metadata.contains("import:"));
+}
+
+#[cfg(feature = "mock")]
+#[tokio::test]
+async fn test_builder_provenance_records_conversion() {
+    let result = RecipeImporter::builder()
+        .text("2 eggs\n\nFry the eggs.")
+        .provider(LlmProvider::Mock)
+        .provenance()
+        .no_cache()
+        .offline()
+        .build()
+        .await
+        .unwrap();
+    let ImportResult::Cooklang { content, .. } = result else {
+        panic!("expected Cooklang");
+    };
+    let provenance = Provenance::from_cooklang(&content).unwrap();
+    assert_eq!(provenance.extractor.as_deref(), Some("text"));
+    assert_eq!(provenance.provider.as_deref(), Some("mock"));
+    assert_eq!(provenance.model.as_deref(), Some("mock"));
+    assert_eq!(provenance.prompt.map(|hash| hash.len()), Some(16));
+}
+
+#[cfg(feature = "mock")]
+#[tokio::test]
+async fn test_eval_with_mock_provider() {
+    use cooklang_import::eval;
+
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/eval");
+    let mut cases = eval::load_cases(&dir).unwrap();
+    assert_eq!(cases.len(), 3);
+    // The mock provider echoes the recipe, so a case whose text is already
+    // Cooklang scores full marks
+    cases.push(eval::EvalCase {
+        name: "eggs".to_string(),
+        source: "Fry @eggs{2} in @butter{1%tbsp}.".to_string(),
+        reference: "Fry @eggs{2} in @butter{1%tbsp}.".to_string(),
+    });
+    let candidates = [eval::Candidate {
+        provider: LlmProvider::Mock,
+        model: None,
+    }];
+
+    let report = eval::run(&cases, &candidates).await;
+    let result = &report.candidates[0];
+    assert_eq!(result.candidate, "mock");
+    assert_eq!(result.converted(), 4);
+    let recall: Vec<f32> = result
+        .cases
+        .iter()
+        .map(|case| case.scores.unwrap().ingredient_recall)
+        .collect();
+    assert_eq!(recall, vec![0.0, 0.0, 0.0, 1.0]);
+    assert!(report.to_string().starts_with("Provider  Cases"));
+}
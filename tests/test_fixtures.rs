@@ -0,0 +1,116 @@
+//! Golden tests: every saved page in tests/fixtures/ is run through the
+//! extractor chain offline and compared with its recorded snapshot.
+//!
+//! Record a new fixture with `cargo run --example record_fixture -- <url> <name>`.
+//! After an intended extraction change, rewrite the snapshots with
+//! `UPDATE_FIXTURES=1 cargo test --test test_fixtures` and review the diff.
+
+use cooklang_import::stages;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+}
+
+#[tokio::test]
+async fn test_fixtures_match_snapshots() {
+    let update = std::env::var_os("UPDATE_FIXTURES").is_some();
+    let mut pages: Vec<PathBuf> = std::fs::read_dir(fixtures_dir())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "html"))
+        .collect();
+    pages.sort();
+    assert!(!pages.is_empty(), "No fixtures in {:?}", fixtures_dir());
+
+    let mut failures = Vec::new();
+    for page in &pages {
+        let name = page.file_stem().unwrap().to_string_lossy();
+        let snapshot_path = page.with_extension("json");
+        let snapshot: Value = match std::fs::read_to_string(&snapshot_path) {
+            Ok(json) => serde_json::from_str(&json).unwrap(),
+            Err(e) => {
+                failures.push(format!("{}: no snapshot ({})", name, e));
+                continue;
+            }
+        };
+        let url = snapshot["url"].as_str().unwrap();
+        let html = std::fs::read_to_string(page).unwrap();
+
+        let actual = match stages::extract(&html, url).await {
+            Ok(components) => json!({
+                "url": url,
+                "name": components.name,
+                "metadata": metadata(&components.metadata),
+                "text": components.text,
+            }),
+            Err(e) => json!({"url": url, "error": e.to_string()}),
+        };
+
+        if update {
+            let json = serde_json::to_string_pretty(&actual).unwrap();
+            std::fs::write(&snapshot_path, json + "\n").unwrap();
+        } else if actual != snapshot {
+            failures.push(diff(&name, &snapshot, &actual));
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} of {} fixtures changed (UPDATE_FIXTURES=1 rewrites the snapshots):\n\n{}",
+        failures.len(),
+        pages.len(),
+        failures.join("\n\n")
+    );
+}
+
+/// The YAML metadata as an object; extractors don't keep the keys in order
+fn metadata(yaml: &str) -> Value {
+    serde_yaml::from_str::<Value>(yaml).unwrap_or(Value::Null)
+}
+
+/// The fields that differ, with the first differing line of each
+fn diff(name: &str, expected: &Value, actual: &Value) -> String {
+    let mut report = format!("{}:", name);
+    let empty = serde_json::Map::new();
+    let expected_metadata = expected["metadata"].as_object().unwrap_or(&empty);
+    let actual_metadata = actual["metadata"].as_object().unwrap_or(&empty);
+    let mut keys: Vec<&String> = expected_metadata
+        .keys()
+        .chain(actual_metadata.keys())
+        .collect();
+    keys.sort();
+    keys.dedup();
+    for key in keys {
+        let (expected, actual) = (expected_metadata.get(key), actual_metadata.get(key));
+        if expected != actual {
+            report.push_str(&format!(
+                "\n  metadata {:?}: expected {}, actual {}",
+                key,
+                expected.unwrap_or(&Value::Null),
+                actual.unwrap_or(&Value::Null)
+            ));
+        }
+    }
+    for field in ["name", "text", "error"] {
+        let expected = expected[field].as_str().unwrap_or_default();
+        let actual = actual[field].as_str().unwrap_or_default();
+        if expected == actual {
+            continue;
+        }
+        let line = expected
+            .lines()
+            .zip(actual.lines())
+            .position(|(a, b)| a != b)
+            .unwrap_or_else(|| expected.lines().count().min(actual.lines().count()));
+        report.push_str(&format!(
+            "\n  {} differs at line {}:\n    expected: {:?}\n    actual:   {:?}",
+            field,
+            line + 1,
+            expected.lines().nth(line).unwrap_or_default(),
+            actual.lines().nth(line).unwrap_or_default()
+        ));
+    }
+    report
+}
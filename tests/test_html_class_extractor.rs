@@ -5,8 +5,8 @@ mod tests {
     };
     use scraper::Html;
 
-    #[test]
-    fn test_wprm_recipe_extraction() {
+    #[tokio::test]
+    async fn test_wprm_recipe_extraction() {
         // Sample HTML with WordPress Recipe Maker (WPRM) classes
         let html = r#"
         <html>
@@ -50,7 +50,7 @@ mod tests {
         };
 
         let extractor = HtmlClassExtractor;
-        let result = extractor.parse(&context);
+        let result = extractor.parse(&context.into()).await;
 
         assert!(result.is_ok());
         let recipe = result.unwrap();
@@ -71,11 +71,11 @@ mod tests {
         assert!(recipe.instructions.contains("Preheat oven to 350°F"));
         assert!(recipe.instructions.contains("Bake for 10-12 minutes"));
         assert_eq!(
-            recipe.metadata.get("prep_time"),
+            recipe.metadata.get("prep time"),
             Some(&"15 minutes".to_string())
         );
         assert_eq!(
-            recipe.metadata.get("cook_time"),
+            recipe.metadata.get("cook time"),
             Some(&"12 minutes".to_string())
         );
         assert_eq!(
@@ -84,8 +84,8 @@ mod tests {
         );
     }
 
-    #[test]
-    fn test_tasty_recipes_extraction() {
+    #[tokio::test]
+    async fn test_tasty_recipes_extraction() {
         // Sample HTML with Tasty Recipes classes
         let html = r#"
         <html>
@@ -121,7 +121,7 @@ mod tests {
         };
 
         let extractor = HtmlClassExtractor;
-        let result = extractor.parse(&context);
+        let result = extractor.parse(&context.into()).await;
 
         assert!(result.is_ok());
         let recipe = result.unwrap();
@@ -139,8 +139,8 @@ mod tests {
         assert_eq!(recipe.metadata.get("servings"), Some(&"1 loaf".to_string()));
     }
 
-    #[test]
-    fn test_generic_recipe_classes() {
+    #[tokio::test]
+    async fn test_generic_recipe_classes() {
         // Sample HTML with generic recipe classes
         let html = r#"
         <html>
@@ -177,7 +177,7 @@ mod tests {
         };
 
         let extractor = HtmlClassExtractor;
-        let result = extractor.parse(&context);
+        let result = extractor.parse(&context.into()).await;
 
         assert!(result.is_ok());
         let recipe = result.unwrap();
@@ -196,8 +196,8 @@ mod tests {
             .contains("Cook pasta according to package"));
     }
 
-    #[test]
-    fn test_fallback_to_fuzzy_matching() {
+    #[tokio::test]
+    async fn test_fallback_to_fuzzy_matching() {
         // HTML with partial class name matches
         let html = r#"
         <html>
@@ -225,7 +225,7 @@ mod tests {
         };
 
         let extractor = HtmlClassExtractor;
-        let result = extractor.parse(&context);
+        let result = extractor.parse(&context.into()).await;
 
         match result {
             Ok(recipe) => {
@@ -242,8 +242,8 @@ mod tests {
         }
     }
 
-    #[test]
-    fn test_extraction_failure_no_content() {
+    #[tokio::test]
+    async fn test_extraction_failure_no_content() {
         // HTML without recipe content
         let html = r#"
         <html>
@@ -261,8 +261,225 @@ mod tests {
         };
 
         let extractor = HtmlClassExtractor;
-        let result = extractor.parse(&context);
+        let result = extractor.parse(&context.into()).await;
 
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_wprm_ingredient_spans_and_groups() {
+        // WPRM puts each part of an ingredient in its own span, split across lines
+        let html = r#"
+        <html>
+            <body>
+                <h2 class="wprm-recipe-name">Chickpea Salad</h2>
+                <div class="wprm-recipe-ingredients-container">
+                    <h3 class="wprm-recipe-header">Ingredients</h3>
+                    <div class="wprm-recipe-ingredient-group">
+                        <h4 class="wprm-recipe-group-name wprm-recipe-ingredient-group-name">For the Salad:</h4>
+                        <ul class="wprm-recipe-ingredients">
+                            <li class="wprm-recipe-ingredient">
+                                <span class="wprm-checkbox-container"><input type="checkbox"></span>
+                                <span class="wprm-recipe-ingredient-amount">2</span>
+                                <span class="wprm-recipe-ingredient-unit">15 oz
+                                    cans</span>
+                                <span class="wprm-recipe-ingredient-name">chickpeas</span>
+                                <span class="wprm-recipe-ingredient-notes">drained</span>
+                            </li>
+                            <li class="wprm-recipe-ingredient">
+                                <span class="wprm-recipe-ingredient-amount">1</span>
+                                <span class="wprm-recipe-ingredient-name"><a href="/cucumber">cucumber</a></span>
+                            </li>
+                        </ul>
+                    </div>
+                    <div class="wprm-recipe-ingredient-group">
+                        <h4 class="wprm-recipe-group-name wprm-recipe-ingredient-group-name">For the Dressing</h4>
+                        <ul class="wprm-recipe-ingredients">
+                            <li class="wprm-recipe-ingredient">
+                                <span class="wprm-recipe-ingredient-amount">1/4</span>
+                                <span class="wprm-recipe-ingredient-unit">cup</span>
+                                <span class="wprm-recipe-ingredient-name">olive oil</span>
+                            </li>
+                        </ul>
+                    </div>
+                </div>
+                <div class="wprm-recipe-instructions-container">
+                    <ul><li>Toss everything together.</li></ul>
+                </div>
+            </body>
+        </html>
+        "#;
+
+        let context = ParsingContext {
+            url: "https://example.com/chickpea-salad".to_string(),
+            document: Html::parse_document(html),
+            texts: None,
+        };
+
+        let recipe = HtmlClassExtractor.parse(&context.into()).await.unwrap();
+
+        assert_eq!(
+            recipe.ingredients,
+            vec![
+                "## For the Salad",
+                "2 15 oz cans chickpeas drained",
+                "1 cucumber",
+                "## For the Dressing",
+                "1/4 cup olive oil",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tasty_ingredient_groups() {
+        let html = r#"
+        <html>
+            <body>
+                <h2 class="tasty-recipes-title">Apple Pie</h2>
+                <div class="tasty-recipes-ingredients">
+                    <div class="tasty-recipes-ingredients-header"><h3>Ingredients</h3></div>
+                    <div class="tasty-recipes-ingredients-body">
+                        <h4>Crust</h4>
+                        <ul>
+                            <li><span data-amount="2" data-unit="cup">2 cups</span>
+                                flour</li>
+                            <li><span data-amount="1" data-unit="cup">1 cup</span> butter</li>
+                        </ul>
+                        <h4>Filling</h4>
+                        <ul>
+                            <li><span data-amount="6">6</span> apples</li>
+                        </ul>
+                    </div>
+                </div>
+                <div class="tasty-recipes-instructions">
+                    <li>Make the crust and bake the pie.</li>
+                </div>
+            </body>
+        </html>
+        "#;
+
+        let context = ParsingContext {
+            url: "https://example.com/apple-pie".to_string(),
+            document: Html::parse_document(html),
+            texts: None,
+        };
+
+        let recipe = HtmlClassExtractor.parse(&context.into()).await.unwrap();
+
+        assert_eq!(
+            recipe.ingredients,
+            vec![
+                "## Crust",
+                "2 cups flour",
+                "1 cup butter",
+                "## Filling",
+                "6 apples"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_instruction_group_headers() {
+        let html = r#"
+        <html>
+            <body>
+                <h2 class="wprm-recipe-name">Layer Cake</h2>
+                <div class="wprm-recipe-ingredients-container">
+                    <ul><li class="wprm-recipe-ingredient">3 eggs</li></ul>
+                </div>
+                <div class="wprm-recipe-instructions-container">
+                    <div class="wprm-recipe-instruction-group">
+                        <h4 class="wprm-recipe-group-name wprm-recipe-instruction-group-name">Cake:</h4>
+                        <ul><li class="wprm-recipe-instruction">Bake the layers.</li></ul>
+                    </div>
+                    <div class="wprm-recipe-instruction-group">
+                        <h4 class="wprm-recipe-group-name wprm-recipe-instruction-group-name">Frosting</h4>
+                        <ul><li class="wprm-recipe-instruction">Whip the cream.</li></ul>
+                    </div>
+                </div>
+            </body>
+        </html>
+        "#;
+
+        let context = ParsingContext {
+            url: "https://example.com/layer-cake".to_string(),
+            document: Html::parse_document(html),
+            texts: None,
+        };
+        let recipe = HtmlClassExtractor.parse(&context.into()).await.unwrap();
+        assert_eq!(
+            recipe.instructions,
+            "## Cake\n\nBake the layers.\n\n## Frosting\n\nWhip the cream."
+        );
+
+        // Tasty Recipes uses plain headings inside the instructions body
+        let html = r#"
+        <html>
+            <body>
+                <h2 class="tasty-recipes-title">Layer Cake</h2>
+                <div class="tasty-recipes-ingredients"><ul><li>3 eggs</li></ul></div>
+                <div class="tasty-recipes-instructions">
+                    <h3>Instructions</h3>
+                    <div class="tasty-recipes-instructions-body">
+                        <h4>Cake</h4>
+                        <ol><li>Bake the layers.</li></ol>
+                        <h4>Frosting</h4>
+                        <ol><li>Whip the cream.</li></ol>
+                    </div>
+                </div>
+            </body>
+        </html>
+        "#;
+
+        let context = ParsingContext {
+            url: "https://example.com/layer-cake".to_string(),
+            document: Html::parse_document(html),
+            texts: None,
+        };
+        let recipe = HtmlClassExtractor.parse(&context.into()).await.unwrap();
+        assert_eq!(
+            recipe.instructions,
+            "## Cake\n\nBake the layers.\n\n## Frosting\n\nWhip the cream."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wprm_equipment() {
+        let html = r#"
+        <html><body>
+            <h2 class="wprm-recipe-name">Skillet Cornbread</h2>
+            <div class="wprm-recipe-equipment-container">
+                <ul class="wprm-recipe-equipment">
+                    <li class="wprm-recipe-equipment-item">
+                        <div class="wprm-recipe-equipment-name">
+                            <a href="https://example.com/skillet">Cast iron   skillet</a>
+                        </div>
+                    </li>
+                    <li class="wprm-recipe-equipment-item">
+                        <div class="wprm-recipe-equipment-name">Whisk</div>
+                    </li>
+                </ul>
+            </div>
+            <div class="wprm-recipe-ingredients-container">
+                <ul><li class="wprm-recipe-ingredient">1 cup cornmeal</li></ul>
+            </div>
+            <div class="wprm-recipe-instructions-container">
+                <ul><li>Bake in the skillet.</li></ul>
+            </div>
+        </body></html>
+        "#;
+
+        let context = ParsingContext {
+            url: "https://example.com/cornbread".to_string(),
+            document: Html::parse_document(html),
+            texts: None,
+        };
+
+        let recipe = HtmlClassExtractor.parse(&context.into()).await.unwrap();
+
+        assert_eq!(recipe.equipment, vec!["Cast iron skillet", "Whisk"]);
+        assert!(recipe
+            .to_text_with_metadata()
+            .ends_with("Equipment:\n- Cast iron skillet\n- Whisk"));
+    }
 }
@@ -4,8 +4,8 @@ mod tests {
     use cooklang_import::url_to_text::html::extractors::{Extractor, ParsingContext};
     use scraper::Html;
 
-    #[test]
-    fn test_microdata_extraction() {
+    #[tokio::test]
+    async fn test_microdata_extraction() {
         let html = r#"
         <html>
         <body>
@@ -74,7 +74,7 @@ mod tests {
         };
 
         let extractor = MicroDataExtractor;
-        let result = extractor.parse(&context);
+        let result = extractor.parse(&context.into()).await;
 
         assert!(result.is_ok(), "Failed to extract recipe");
         let recipe = result.unwrap();
@@ -98,15 +98,15 @@ mod tests {
         assert!(recipe.instructions.contains("Preheat oven to 350 degrees"));
 
         assert_eq!(
-            recipe.metadata.get("prep_time"),
+            recipe.metadata.get("prep time"),
             Some(&"10 mins".to_string())
         );
         assert_eq!(
-            recipe.metadata.get("cook_time"),
+            recipe.metadata.get("cook time"),
             Some(&"1 hour".to_string())
         );
         assert_eq!(
-            recipe.metadata.get("total_time"),
+            recipe.metadata.get("time required"),
             Some(&"1 hour 10 mins".to_string())
         );
         assert_eq!(
@@ -137,4 +137,175 @@ mod tests {
             Some(&"banana, bread, sweet".to_string())
         );
     }
+
+    #[tokio::test]
+    async fn test_microdata_iso_durations() {
+        let html = r#"
+        <div itemscope itemtype="https://schema.org/Recipe">
+            <h1 itemprop="name">Pancakes</h1>
+            <meta itemprop="prepTime" content="PT15M">
+            <span itemprop="cookTime" content="PT1H30M">90 min</span>
+            <time itemprop="totalTime" datetime="PT1H45M"></time>
+            <ul><li itemprop="recipeIngredient">2 eggs</li></ul>
+            <p itemprop="recipeInstructions">Mix and fry.</p>
+        </div>
+        "#;
+
+        let context = ParsingContext {
+            url: "https://example.com/pancakes".to_string(),
+            document: Html::parse_document(html),
+            texts: None,
+        };
+
+        let recipe = MicroDataExtractor.parse(&context.into()).await.unwrap();
+
+        assert_eq!(
+            recipe.metadata.get("prep time"),
+            Some(&"15 minutes".to_string())
+        );
+        assert_eq!(
+            recipe.metadata.get("cook time"),
+            Some(&"1 hour 30 minutes".to_string())
+        );
+        assert_eq!(
+            recipe.metadata.get("time required"),
+            Some(&"1 hour 45 minutes".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_microdata_instruction_sections() {
+        let html = r#"
+        <div itemscope itemtype="https://schema.org/Recipe">
+            <h1 itemprop="name">Pizza</h1>
+            <ul><li itemprop="recipeIngredient">500 g flour</li></ul>
+            <div itemprop="recipeInstructions" itemscope itemtype="https://schema.org/HowToSection">
+                <h3 itemprop="name">Dough:</h3>
+                <ol>
+                    <li itemprop="itemListElement" itemscope itemtype="https://schema.org/HowToStep">
+                        <span itemprop="name">Knead</span>
+                        <p itemprop="text">Knead the dough for 10 minutes.</p>
+                    </li>
+                    <li itemprop="itemListElement" itemscope itemtype="https://schema.org/HowToStep">
+                        <p itemprop="text">Let it rise for an hour.</p>
+                    </li>
+                </ol>
+            </div>
+            <div itemprop="recipeInstructions" itemscope itemtype="https://schema.org/HowToSection">
+                <h3 itemprop="name">Topping</h3>
+                <ol>
+                    <li itemprop="itemListElement" itemscope itemtype="https://schema.org/HowToStep">
+                        <p itemprop="text">Spread the sauce.</p>
+                    </li>
+                </ol>
+            </div>
+        </div>
+        "#;
+
+        let context = ParsingContext {
+            url: "https://example.com/pizza".to_string(),
+            document: Html::parse_document(html),
+            texts: None,
+        };
+
+        let recipe = MicroDataExtractor.parse(&context.into()).await.unwrap();
+
+        assert_eq!(recipe.name, "Pizza");
+        assert_eq!(
+            recipe.instructions,
+            "## Dough\n\nKnead the dough for 10 minutes.\n\nLet it rise for an hour.\n\n## Topping\n\nSpread the sauce."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_microdata_rating() {
+        let html = r#"
+        <div itemscope itemtype="https://schema.org/Recipe">
+            <h1 itemprop="name">Pancakes</h1>
+            <div itemprop="aggregateRating" itemscope itemtype="https://schema.org/AggregateRating">
+                <meta itemprop="ratingValue" content="4.5">
+                <span itemprop="reviewCount">87</span> reviews
+            </div>
+            <ul><li itemprop="recipeIngredient">2 eggs</li></ul>
+            <p itemprop="recipeInstructions">Mix and fry.</p>
+        </div>
+        "#;
+
+        let context = ParsingContext {
+            url: "https://example.com/pancakes".to_string(),
+            document: Html::parse_document(html),
+            texts: None,
+        };
+
+        let recipe = MicroDataExtractor.parse(&context.into()).await.unwrap();
+
+        assert_eq!(
+            recipe.metadata.get("rating"),
+            Some(&"4.5 (87 reviews)".to_string())
+        );
+        assert!(recipe
+            .dropped_fields
+            .iter()
+            .all(|field| field.key != "aggregateRating"));
+    }
+
+    #[tokio::test]
+    async fn test_microdata_video() {
+        let html = r#"
+        <div itemscope itemtype="https://schema.org/Recipe">
+            <h1 itemprop="name">Pancakes</h1>
+            <div itemprop="video" itemscope itemtype="https://schema.org/VideoObject">
+                <meta itemprop="name" content="Making pancakes">
+                <link itemprop="embedUrl" href="https://www.youtube.com/embed/abc123">
+            </div>
+            <ul><li itemprop="recipeIngredient">2 eggs</li></ul>
+            <p itemprop="recipeInstructions">Mix and fry.</p>
+        </div>
+        "#;
+
+        let context = ParsingContext {
+            url: "https://example.com/pancakes".to_string(),
+            document: Html::parse_document(html),
+            texts: None,
+        };
+
+        let recipe = MicroDataExtractor.parse(&context.into()).await.unwrap();
+
+        assert_eq!(
+            recipe.metadata.get("video"),
+            Some(&"https://www.youtube.com/embed/abc123".to_string())
+        );
+        assert_eq!(recipe.name, "Pancakes");
+    }
+
+    #[tokio::test]
+    async fn test_microdata_tools() {
+        let html = r#"
+        <div itemscope itemtype="https://schema.org/Recipe">
+            <h1 itemprop="name">Pancakes</h1>
+            <meta itemprop="tool" content="Griddle">
+            <ul><li itemprop="recipeIngredient">2 eggs</li></ul>
+            <div itemprop="recipeInstructions" itemscope itemtype="https://schema.org/HowToStep">
+                <p itemprop="text">Whisk and fry.</p>
+                <span itemprop="tool" itemscope itemtype="https://schema.org/HowToTool">
+                    <span itemprop="name">Whisk</span>
+                </span>
+            </div>
+        </div>
+        "#;
+
+        let context = ParsingContext {
+            url: "https://example.com/pancakes".to_string(),
+            document: Html::parse_document(html),
+            texts: None,
+        };
+
+        let recipe = MicroDataExtractor.parse(&context.into()).await.unwrap();
+
+        assert_eq!(recipe.equipment, vec!["Griddle", "Whisk"]);
+        assert!(recipe
+            .dropped_fields
+            .iter()
+            .all(|field| field.key != "tool"));
+    }
 }
@@ -5,8 +5,8 @@ mod tests {
     };
     use scraper::Html;
 
-    #[test]
-    fn test_natashaskitchen_wprm_extraction() {
+    #[tokio::test]
+    async fn test_natashaskitchen_wprm_extraction() {
         // Simplified HTML structure from Natasha's Kitchen using WPRM plugin
         let html = r#"
         <html>
@@ -132,7 +132,7 @@ mod tests {
         };
 
         let extractor = HtmlClassExtractor;
-        let result = extractor.parse(&context);
+        let result = extractor.parse(&context.into()).await;
 
         assert!(result.is_ok(), "Failed to extract recipe");
         let recipe = result.unwrap();
@@ -169,11 +169,11 @@ mod tests {
 
         // Verify metadata extraction
         assert_eq!(
-            recipe.metadata.get("prep_time"),
+            recipe.metadata.get("prep time"),
             Some(&"15 mins".to_string())
         );
         assert_eq!(
-            recipe.metadata.get("total_time"),
+            recipe.metadata.get("time required"),
             Some(&"15 mins".to_string())
         );
         assert_eq!(
@@ -195,8 +195,8 @@ mod tests {
         );
     }
 
-    #[test]
-    fn test_wprm_with_ingredient_groups() {
+    #[tokio::test]
+    async fn test_wprm_with_ingredient_groups() {
         // Test that we properly handle recipes with ingredient groups (like "For the Salad:" and "For the Dressing:")
         let html = r#"
         <div class="wprm-recipe-container">
@@ -225,7 +225,7 @@ mod tests {
         };
 
         let extractor = HtmlClassExtractor;
-        let result = extractor.parse(&context);
+        let result = extractor.parse(&context.into()).await;
 
         assert!(result.is_ok());
         let recipe = result.unwrap();
@@ -0,0 +1,127 @@
+use async_trait::async_trait;
+use cooklang_import::url_to_text::html::extractors::{Extractor, SharedContext};
+use cooklang_import::{register_extractor, stages, Recipe};
+use std::error::Error;
+
+/// Reads the members-only recipe markup of a made-up paywalled site
+struct MembersOnlyExtractor;
+
+#[async_trait]
+impl Extractor for MembersOnlyExtractor {
+    async fn parse(&self, context: &SharedContext) -> Result<Recipe, Box<dyn Error + Send + Sync>> {
+        // The page is read inside `read`, before the first await
+        let recipe = context.read(|context| {
+            if !context.url.starts_with("https://members.example.com/") {
+                return Err("not a members.example.com page");
+            }
+            let select = |css: &str| {
+                let selector = scraper::Selector::parse(css).unwrap();
+                context
+                    .document
+                    .select(&selector)
+                    .map(|element| element.text().collect::<String>())
+                    .collect::<Vec<_>>()
+            };
+            Ok(Recipe {
+                name: select("h2.dish").join(" "),
+                ingredients: select(".needs span"),
+                instructions: select(".then p").join("\n\n"),
+                ..Recipe::default()
+            })
+        })?;
+        // Stands in for a call to the site's API
+        tokio::task::yield_now().await;
+        Ok(recipe)
+    }
+
+    fn name(&self) -> &'static str {
+        "members-only"
+    }
+}
+
+const PAGE: &str = r#"<html><body>
+    <h2 class="dish">Secret Stew</h2>
+    <div class="needs"><span>2 carrots</span><span>1 onion</span></div>
+    <div class="then"><p>Chop everything.</p><p>Simmer for an hour.</p></div>
+</body></html>"#;
+
+#[tokio::test]
+async fn test_registered_extractor_runs_first() {
+    register_extractor(Box::new(MembersOnlyExtractor));
+
+    let components = stages::extract(PAGE, "https://members.example.com/stew")
+        .await
+        .unwrap();
+    assert_eq!(components.name, "Secret Stew");
+    assert_eq!(
+        components.text,
+        "2 carrots\n1 onion\n\nChop everything.\n\nSimmer for an hour."
+    );
+
+    // Other sites still go through the built-in extractors, and the
+    // registered extractor's error shows up in the diagnostics
+    match stages::extract(PAGE, "https://example.com/stew").await {
+        Err(cooklang_import::ImportError::NoExtractorMatched(diagnostics)) => {
+            assert!(diagnostics.to_string().contains("members-only"));
+        }
+        other => panic!(
+            "expected NoExtractorMatched, got {:?}",
+            other.map(|c| c.name)
+        ),
+    }
+}
+
+/// Answers for slow.example.com pages after a delay, with a recipe or an
+/// error
+struct SlowExtractor {
+    name: &'static str,
+    finds_recipe: bool,
+}
+
+#[async_trait]
+impl Extractor for SlowExtractor {
+    async fn parse(&self, context: &SharedContext) -> Result<Recipe, Box<dyn Error + Send + Sync>> {
+        if !context.read(|context| context.url.starts_with("https://slow.example.com/")) {
+            return Err("not a slow.example.com page".into());
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        if !self.finds_recipe {
+            return Err("no recipe".into());
+        }
+        Ok(Recipe {
+            name: "Slow Stew".to_string(),
+            ingredients: vec!["1 turnip".to_string()],
+            instructions: "Stew it.".to_string(),
+            ..Recipe::default()
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+#[tokio::test]
+async fn test_extractors_run_concurrently_in_order() {
+    register_extractor(Box::new(SlowExtractor {
+        name: "slow-miss",
+        finds_recipe: false,
+    }));
+    register_extractor(Box::new(SlowExtractor {
+        name: "slow-hit",
+        finds_recipe: true,
+    }));
+    let page = r#"<script type="application/ld+json">
+        {"@type": "Recipe", "name": "Quick Stew",
+         "recipeIngredient": ["1 leek"], "recipeInstructions": "Stew it."}
+    </script>"#;
+
+    let start = std::time::Instant::now();
+    let components = stages::extract(page, "https://slow.example.com/stew")
+        .await
+        .unwrap();
+    // JSON-LD finishes first, but the registered extractor ranks ahead of it
+    assert_eq!(components.name, "Slow Stew");
+    // Both slow extractors waited at the same time
+    assert!(start.elapsed() < std::time::Duration::from_millis(550));
+}